@@ -0,0 +1,20 @@
+//! Local automation API
+//!
+//! When `OPEN_SCREENSTUDIO_AUTOMATION` is set, the app accepts commands on a
+//! local socket (a named pipe on Windows) so recordings and exports can be
+//! driven from scripts and test harnesses. The socket address and a per-launch
+//! token are written to `automation.json` in the app data directory, which is
+//! removed again when the app exits; requests without the token are rejected.
+
+pub mod protocol;
+mod server;
+
+pub use server::{connection_file_path, start, stop};
+
+/// Environment variable that enables the automation server
+pub const AUTOMATION_ENV: &str = "OPEN_SCREENSTUDIO_AUTOMATION";
+
+/// Whether the automation server should be started
+pub fn is_enabled() -> bool {
+    std::env::var(AUTOMATION_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
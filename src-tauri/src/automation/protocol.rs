@@ -0,0 +1,130 @@
+//! Automation wire protocol
+//!
+//! Clients send one JSON request per line and get one JSON response per
+//! line back. Every request carries the token from the connection file:
+//!
+//! ```json
+//! {"token": "…", "command": "start", "config": { …RecordingConfig… }}
+//! {"ok": true, "result": null}
+//! ```
+
+use crate::export::{ExportOptions, TrackEdits};
+use crate::recorder::state::RecordingConfig;
+use crate::utils::error::{AppError, AppResult, ErrorResponse};
+use serde::{Deserialize, Serialize};
+
+/// What a script can ask for
+#[derive(Debug, Deserialize)]
+#[serde(
+    tag = "command",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum AutomationCommand {
+    /// Recording state, duration and whether an export is running
+    Status,
+    Start {
+        config: RecordingConfig,
+    },
+    Stop,
+    Pause,
+    Resume,
+    /// Start exporting a project; poll `status` for completion
+    Export {
+        project_dir: String,
+        options: ExportOptions,
+        #[serde(default)]
+        edits: Option<TrackEdits>,
+    },
+    CancelExport,
+}
+
+#[derive(Debug, Deserialize)]
+struct AutomationRequest {
+    token: String,
+    #[serde(flatten)]
+    command: AutomationCommand,
+}
+
+/// Reply to one request
+#[derive(Debug, Serialize)]
+pub struct AutomationResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+impl From<AppResult<serde_json::Value>> for AutomationResponse {
+    fn from(result: AppResult<serde_json::Value>) -> Self {
+        match result {
+            Ok(value) => Self {
+                ok: true,
+                result: Some(value),
+                error: None,
+            },
+            Err(e) => Self {
+                ok: false,
+                result: None,
+                error: Some(e.into()),
+            },
+        }
+    }
+}
+
+/// Parse a request line, rejecting it unless it carries `token`
+pub fn parse_request(line: &str, token: &str) -> AppResult<AutomationCommand> {
+    let request: AutomationRequest = serde_json::from_str(line)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid automation request: {}", e)))?;
+    if !tokens_match(&request.token, token) {
+        return Err(AppError::PermissionDenied(
+            "Invalid automation token".to_string(),
+        ));
+    }
+    Ok(request.command)
+}
+
+/// Compare tokens without returning early on the first difference
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_need_the_token() {
+        let command = parse_request(r#"{"token": "secret", "command": "stop"}"#, "secret").unwrap();
+        assert!(matches!(command, AutomationCommand::Stop));
+
+        let command = parse_request(
+            r#"{"token": "secret", "command": "export", "projectDir": "/tmp/p.osp", "options": {
+                "format": "mp4", "quality": "high", "width": null, "height": null, "fps": null,
+                "outputPath": "/tmp/out.mp4", "includeCursor": true, "includeWebcam": false,
+                "includeMicAudio": true, "includeSystemAudio": true,
+                "screenEdits": null, "cameraEdits": null}}"#,
+            "secret",
+        )
+        .unwrap();
+        assert!(
+            matches!(command, AutomationCommand::Export { ref project_dir, edits: None, .. } if project_dir == "/tmp/p.osp")
+        );
+
+        let denied = parse_request(r#"{"token": "guess!", "command": "stop"}"#, "secret");
+        assert!(matches!(denied, Err(AppError::PermissionDenied(_))));
+        let invalid = parse_request(r#"{"token": "secret", "command": "reboot"}"#, "secret");
+        assert!(matches!(invalid, Err(AppError::InvalidInput(_))));
+
+        let response = AutomationResponse::from(invalid.map(|_| serde_json::Value::Null));
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["ok"], false);
+        assert_eq!(json["error"]["code"], "INVALID_INPUT");
+    }
+}
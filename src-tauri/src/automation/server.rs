@@ -0,0 +1,274 @@
+//! Automation server
+//!
+//! Listens on a Unix socket (macOS, Linux) or a named pipe (Windows) and runs
+//! requests through the same command handlers the GUI uses, so a scripted
+//! recording behaves exactly like one started from the toolbar.
+
+use super::protocol::{parse_request, AutomationCommand, AutomationResponse};
+use crate::commands::export::{self as export_commands, ExportState};
+use crate::commands::recording::{self as recording_commands, RecorderState};
+use crate::project::encryption::create_private_dir;
+use crate::utils::error::{AppError, AppResult};
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Longest request line accepted, in bytes. A client sending a longer one
+/// is answered with an error and disconnected.
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// Where scripts find the server, written to `connection_file_path()`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionInfo<'a> {
+    /// Socket path, or pipe name on Windows
+    address: &'a str,
+    token: &'a str,
+    pid: u32,
+}
+
+/// File the socket address and token are written to, readable only by the
+/// current user
+pub fn connection_file_path() -> AppResult<PathBuf> {
+    Ok(app_data_dir()?.join("automation.json"))
+}
+
+fn app_data_dir() -> AppResult<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| AppError::Platform("Could not determine data directory".to_string()))?;
+    Ok(data_dir.join("Open ScreenStudio"))
+}
+
+/// Remove the connection file and socket, so scripts don't find a server
+/// that's gone
+pub fn stop() {
+    let files = connection_file_path().into_iter().chain(socket_path());
+    for path in files {
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Start listening in the background. A new token is made for every launch.
+pub fn start(app: AppHandle) -> AppResult<()> {
+    let token = Arc::new(format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    ));
+    std::fs::create_dir_all(app_data_dir()?)?;
+
+    let address = listen(app, token.clone())?;
+    let info = ConnectionInfo {
+        address: &address,
+        token: &token,
+        pid: std::process::id(),
+    };
+    write_private(&connection_file_path()?, &serde_json::to_vec_pretty(&info)?)?;
+    tracing::info!("Automation server listening on {}", address);
+    Ok(())
+}
+
+/// The socket, inside a directory only the current user can enter, so
+/// it's never reachable by others between being bound and chmod-ed
+#[cfg(unix)]
+fn socket_path() -> AppResult<PathBuf> {
+    Ok(app_data_dir()?.join("automation").join("automation.sock"))
+}
+
+#[cfg(windows)]
+fn socket_path() -> AppResult<PathBuf> {
+    Err(AppError::Platform("Automation uses a named pipe on Windows".to_string()))
+}
+
+#[cfg(unix)]
+fn listen(app: AppHandle, token: Arc<String>) -> AppResult<String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = socket_path()?;
+    let dir = path.parent().unwrap_or(&path);
+    create_private_dir(dir)?;
+    // An existing directory keeps its old mode
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    // Left behind if the app didn't exit cleanly
+    let _ = std::fs::remove_file(&path);
+    let listener = {
+        // Binding needs a runtime context
+        let _guard = tauri::async_runtime::handle().inner().enter();
+        tokio::net::UnixListener::bind(&path)?
+    };
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(serve(app.clone(), token.clone(), stream));
+                }
+                Err(e) => tracing::warn!("Automation socket accept failed: {}", e),
+            }
+        }
+    });
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(windows)]
+fn listen(app: AppHandle, token: Arc<String>) -> AppResult<String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    const PIPE_NAME: &str = r"\\.\pipe\open-screenstudio-automation";
+    let mut server = {
+        let _guard = tauri::async_runtime::handle().inner().enter();
+        ServerOptions::new()
+            .first_pipe_instance(true)
+            .reject_remote_clients(true)
+            .create(PIPE_NAME)?
+    };
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                tracing::warn!("Automation pipe connect failed: {}", e);
+                continue;
+            }
+            // Keep an instance listening while this client is served
+            let next = match ServerOptions::new()
+                .reject_remote_clients(true)
+                .create(PIPE_NAME)
+            {
+                Ok(next) => next,
+                Err(e) => {
+                    tracing::error!("Failed to create automation pipe: {}", e);
+                    return;
+                }
+            };
+            let client = std::mem::replace(&mut server, next);
+            tauri::async_runtime::spawn(serve(app.clone(), token.clone(), client));
+        }
+    });
+    Ok(PIPE_NAME.to_string())
+}
+
+/// Write `data` to `path` so only the current user can read it
+fn write_private(path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // An existing file keeps its old mode
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    std::io::Write::write_all(&mut options.open(path)?, data)
+}
+
+/// Answer requests on one connection until the client hangs up
+async fn serve<S>(app: AppHandle, token: Arc<String>, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let limit = MAX_REQUEST_BYTES as u64 + 1;
+        match (&mut reader).take(limit).read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let too_long = line.len() > MAX_REQUEST_BYTES && !line.ends_with(b"\n");
+        let result = if too_long {
+            Err(AppError::InvalidInput(format!(
+                "Request is longer than {} bytes",
+                MAX_REQUEST_BYTES
+            )))
+        } else {
+            let request = String::from_utf8_lossy(&line);
+            if request.trim().is_empty() {
+                continue;
+            }
+            match parse_request(&request, &token) {
+                Ok(command) => run(&app, command).await,
+                Err(e) => Err(e),
+            }
+        };
+        let mut response = serde_json::to_vec(&AutomationResponse::from(result))
+            .unwrap_or_else(|_| br#"{"ok":false}"#.to_vec());
+        response.push(b'\n');
+        if writer.write_all(&response).await.is_err() || too_long {
+            break;
+        }
+    }
+}
+
+async fn run(app: &AppHandle, command: AutomationCommand) -> AppResult<serde_json::Value> {
+    tracing::info!("Automation request: {:?}", command);
+    let recorder = app.state::<RecorderState>();
+    match command {
+        AutomationCommand::Status => {
+            let (state, duration_ms) = {
+                let coordinator = recorder.coordinator.lock().await;
+                (coordinator.state(), coordinator.duration_ms())
+            };
+            Ok(json!({
+                "state": state,
+                "durationMs": duration_ms,
+                "exporting": export_commands::is_exporting(app.state::<ExportState>()),
+            }))
+        }
+        AutomationCommand::Start { config } => {
+            let permissions =
+                recording_commands::start_recording(app.clone(), recorder, config).await?;
+            Ok(serde_json::to_value(permissions)?)
+        }
+        AutomationCommand::Stop => {
+            let result = recording_commands::stop_recording(app.clone(), recorder).await?;
+            Ok(serde_json::to_value(result)?)
+        }
+        AutomationCommand::Pause => {
+            recording_commands::pause_recording(recorder).await?;
+            Ok(serde_json::Value::Null)
+        }
+        AutomationCommand::Resume => {
+            recording_commands::resume_recording(recorder).await?;
+            Ok(serde_json::Value::Null)
+        }
+        AutomationCommand::Export {
+            project_dir,
+            options,
+            edits,
+        } => {
+            let state = app.state::<ExportState>();
+            match edits {
+                Some(edits) => {
+                    export_commands::start_export_with_edits(
+                        app.clone(),
+                        state,
+                        project_dir,
+                        options,
+                        edits,
+                    )
+                    .await?
+                }
+                None => {
+                    export_commands::start_export(app.clone(), state, project_dir, options).await?
+                }
+            }
+            Ok(serde_json::Value::Null)
+        }
+        AutomationCommand::CancelExport => {
+            export_commands::cancel_export(app.state::<ExportState>())?;
+            Ok(serde_json::Value::Null)
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! Application activity tracking
+//!
+//! Samples the frontmost application about once a second while recording
+//! and writes the result to `recording-{n}-app-activity.json` as spans of
+//! time each application was in front. The same samples are written to
+//! `recording-{n}-windows.json` per window, with the window's number and
+//! bounds, so the editor can add chapters at app switches or blur a window
+//! wherever it moves. Window titles are only kept when
+//! `RecordingConfig::record_window_titles` is on, and samples taken while a
+//! password field has focus keep the application but drop the title.
+
+use crate::capture::input::privacy::is_secure_input_active;
+use crate::capture::traits::{frontmost_window, FrontmostWindow, WindowBounds};
+use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::FileKind;
+use async_trait::async_trait;
+use parking_lot::Mutex as ParkingMutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Time between samples of the frontmost window
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time one application (and window, if titles are recorded) was frontmost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppActivitySpan {
+    pub app: String,
+    pub window_title: Option<String>,
+    /// Session time the application was first seen in front
+    pub start_ms: f64,
+    /// Session time of the next change, or the end of the session
+    pub end_ms: f64,
+    pub unix_time_ms: u64,
+}
+
+/// Time one window was frontmost without moving or resizing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowSpan {
+    pub app: String,
+    pub window_title: Option<String>,
+    /// Window number (CGWindowID on macOS, HWND on Windows)
+    pub window_id: Option<u64>,
+    /// Global screen coordinates (points on macOS, pixels on Windows)
+    pub bounds: Option<WindowBounds>,
+    /// Session time the window was first seen in front at these bounds
+    pub start_ms: f64,
+    /// Session time of the next change, or the end of the session
+    pub end_ms: f64,
+    pub unix_time_ms: u64,
+}
+
+/// Builds spans from successive samples of the frontmost window
+#[derive(Debug, Default)]
+pub struct ActivityTimeline {
+    spans: Vec<AppActivitySpan>,
+    windows: Vec<WindowSpan>,
+    /// Whether the last sample found no window, closing the previous span
+    idle: bool,
+}
+
+impl ActivityTimeline {
+    /// Record what was in front at `time_ms`; None if no window was found
+    pub fn sample(&mut self, window: Option<FrontmostWindow>, time_ms: f64, unix_time_ms: u64) {
+        let Some(window) = window else {
+            self.close(time_ms);
+            self.idle = true;
+            return;
+        };
+
+        self.close(time_ms);
+        let open = !self.idle;
+        self.idle = false;
+
+        let same_window = self.windows.last().filter(|_| open).is_some_and(|last| {
+            last.app == window.app
+                && last.window_title == window.title
+                && last.window_id == window.id
+                && last.bounds == window.bounds
+        });
+        if !same_window {
+            self.windows.push(WindowSpan {
+                app: window.app.clone(),
+                window_title: window.title.clone(),
+                window_id: window.id,
+                bounds: window.bounds,
+                start_ms: time_ms,
+                end_ms: time_ms,
+                unix_time_ms,
+            });
+        }
+
+        let same_app = self.spans.last().filter(|_| open).is_some_and(|last| {
+            last.app == window.app && last.window_title == window.title
+        });
+        if !same_app {
+            self.spans.push(AppActivitySpan {
+                app: window.app,
+                window_title: window.title,
+                start_ms: time_ms,
+                end_ms: time_ms,
+                unix_time_ms,
+            });
+        }
+    }
+
+    /// End the open spans at `time_ms`
+    pub fn close(&mut self, time_ms: f64) {
+        if self.idle {
+            return;
+        }
+        if let Some(last) = self.spans.last_mut() {
+            last.end_ms = time_ms;
+        }
+        if let Some(last) = self.windows.last_mut() {
+            last.end_ms = time_ms;
+        }
+    }
+
+    /// Application spans and window spans, in that order
+    pub fn into_spans(self) -> (Vec<AppActivitySpan>, Vec<WindowSpan>) {
+        (self.spans, self.windows)
+    }
+}
+
+pub struct AppActivityChannel {
+    id: String,
+    record_window_titles: bool,
+    is_recording: Arc<AtomicBool>,
+    output_dir: Option<PathBuf>,
+    session_index: usize,
+    output_files: Arc<ParkingMutex<Vec<String>>>,
+    thread_handle: Option<std::thread::JoinHandle<(Vec<AppActivitySpan>, Vec<WindowSpan>)>>,
+}
+
+impl AppActivityChannel {
+    pub fn new(record_window_titles: bool) -> Self {
+        Self {
+            id: "app-activity".to_string(),
+            record_window_titles,
+            is_recording: Arc::new(AtomicBool::new(false)),
+            output_dir: None,
+            session_index: 0,
+            output_files: Arc::new(ParkingMutex::new(Vec::new())),
+            thread_handle: None,
+        }
+    }
+
+    fn flush_to_disk(
+        &mut self,
+        spans: &[AppActivitySpan],
+        windows: &[WindowSpan],
+    ) -> RecordingResult<()> {
+        let output_dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+
+        std::fs::create_dir_all(&output_dir)?;
+
+        let files = [
+            (FileKind::AppActivity, serde_json::to_vec_pretty(spans)),
+            (FileKind::Windows, serde_json::to_vec_pretty(windows)),
+        ];
+        for (kind, data) in files {
+            let path = output_dir.join(kind.conventional_name(self.session_index));
+            let data = data.map_err(|e| RecordingError::IoError(std::io::Error::other(e)))?;
+            std::fs::write(&path, data)?;
+
+            self.output_files
+                .lock()
+                .push(path.to_string_lossy().to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Sample the frontmost window until `is_recording` is cleared
+fn sample_activity(
+    is_recording: Arc<AtomicBool>,
+    record_window_titles: bool,
+) -> (Vec<AppActivitySpan>, Vec<WindowSpan>) {
+    let start = Instant::now();
+    let mut timeline = ActivityTimeline::default();
+    while is_recording.load(Ordering::SeqCst) {
+        let window = frontmost_window().map(|mut window| {
+            if !record_window_titles || is_secure_input_active() {
+                window.title = None;
+            }
+            window
+        });
+        let unix_time_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        timeline.sample(window, start.elapsed().as_secs_f64() * 1000.0, unix_time_ms);
+
+        // Sleep in short steps so stopping isn't held up by a full interval
+        let next = Instant::now() + SAMPLE_INTERVAL;
+        while is_recording.load(Ordering::SeqCst) && Instant::now() < next {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+    timeline.close(start.elapsed().as_secs_f64() * 1000.0);
+    timeline.into_spans()
+}
+
+#[async_trait]
+impl RecordingChannel for AppActivityChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::AppActivity
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+        tracing::info!(
+            "App activity channel initialized (titles={}, session={})",
+            self.record_window_titles,
+            session_index
+        );
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if self.is_recording.load(Ordering::SeqCst) {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        if self.session_index == 0 {
+            self.output_files.lock().clear();
+        }
+
+        self.is_recording.store(true, Ordering::SeqCst);
+        let is_recording = self.is_recording.clone();
+        let record_window_titles = self.record_window_titles;
+        let handle = std::thread::Builder::new()
+            .name("app-activity".to_string())
+            .spawn(move || sample_activity(is_recording, record_window_titles))
+            .map_err(RecordingError::IoError)?;
+        self.thread_handle = Some(handle);
+
+        tracing::info!("App activity tracking started");
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> RecordingResult<()> {
+        if !self.is_recording.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.is_recording.store(false, Ordering::SeqCst);
+
+        let (spans, windows) = match self.thread_handle.take().map(|handle| handle.join()) {
+            Some(Ok(spans)) => spans,
+            Some(Err(_)) => {
+                return Err(RecordingError::CaptureError(
+                    "App activity thread panicked".to_string(),
+                ))
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        self.flush_to_disk(&spans, &windows)?;
+
+        tracing::info!(
+            "App activity tracking stopped ({} spans, {} window spans)",
+            spans.len(),
+            windows.len()
+        );
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> RecordingResult<()> {
+        self.stop().await
+    }
+
+    async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+        self.session_index = session_index;
+        self.start().await
+    }
+
+    fn is_recording(&self) -> bool {
+        self.is_recording.load(Ordering::SeqCst)
+    }
+
+    fn output_files(&self) -> Vec<String> {
+        self.output_files.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(app: &str, title: Option<&str>) -> Option<FrontmostWindow> {
+        Some(FrontmostWindow {
+            app: app.to_string(),
+            title: title.map(String::from),
+            id: None,
+            bounds: None,
+        })
+    }
+
+    #[test]
+    fn test_samples_merge_into_spans() {
+        let mut timeline = ActivityTimeline::default();
+        timeline.sample(window("Safari", Some("Docs")), 0.0, 1000);
+        timeline.sample(window("Safari", Some("Docs")), 1000.0, 2000);
+        timeline.sample(window("Safari", Some("Mail")), 2000.0, 3000);
+        timeline.sample(window("Terminal", None), 3000.0, 4000);
+        // Nothing in front (e.g. locked screen) leaves a gap
+        timeline.sample(None, 5000.0, 6000);
+        timeline.sample(None, 6000.0, 7000);
+        timeline.sample(window("Terminal", None), 7000.0, 8000);
+        timeline.close(8500.0);
+
+        let spans: Vec<_> = timeline
+            .into_spans()
+            .0
+            .into_iter()
+            .map(|s| (s.app, s.window_title, s.start_ms, s.end_ms))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("Safari".to_string(), Some("Docs".to_string()), 0.0, 2000.0),
+                (
+                    "Safari".to_string(),
+                    Some("Mail".to_string()),
+                    2000.0,
+                    3000.0
+                ),
+                ("Terminal".to_string(), None, 3000.0, 5000.0),
+                ("Terminal".to_string(), None, 7000.0, 8500.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_window_moves_split_window_spans() {
+        let at = |x: i32| {
+            Some(FrontmostWindow {
+                app: "Safari".to_string(),
+                title: None,
+                id: Some(42),
+                bounds: Some(WindowBounds {
+                    x,
+                    y: 0,
+                    width: 800,
+                    height: 600,
+                }),
+            })
+        };
+        let mut timeline = ActivityTimeline::default();
+        timeline.sample(at(0), 0.0, 1000);
+        timeline.sample(at(0), 1000.0, 2000);
+        timeline.sample(at(100), 2000.0, 3000);
+        timeline.close(2500.0);
+
+        let (spans, windows) = timeline.into_spans();
+        assert_eq!(spans.len(), 1);
+        assert_eq!((spans[0].start_ms, spans[0].end_ms), (0.0, 2500.0));
+        let windows: Vec<_> = windows
+            .into_iter()
+            .map(|w| (w.bounds.unwrap().x, w.start_ms, w.end_ms))
+            .collect();
+        assert_eq!(windows, vec![(0, 0.0, 2000.0), (100, 2000.0, 2500.0)]);
+    }
+}
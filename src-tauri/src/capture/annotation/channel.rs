@@ -0,0 +1,167 @@
+use crate::capture::annotation::types::{Annotation, AnnotationInput};
+use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::FileKind;
+use async_trait::async_trait;
+use parking_lot::Mutex as ParkingMutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Shared entry point for annotations coming from the drawing overlay.
+///
+/// The sink is cloned into both the recorder state (so commands can push into it)
+/// and the `AnnotationChannel` (which owns the session timing and persistence).
+#[derive(Clone, Default)]
+pub struct AnnotationSink {
+    inner: Arc<ParkingMutex<SinkState>>,
+}
+
+#[derive(Default)]
+struct SinkState {
+    /// Start of the current session; None while not recording
+    session_start: Option<Instant>,
+    annotations: Vec<Annotation>,
+}
+
+impl AnnotationSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Timestamp an annotation against the current session and store it
+    pub fn push(&self, input: AnnotationInput) -> RecordingResult<Annotation> {
+        let mut state = self.inner.lock();
+        let session_start = state.session_start.ok_or(RecordingError::NotRecording)?;
+
+        let annotation = Annotation {
+            id: uuid::Uuid::new_v4().to_string(),
+            shape: input.shape,
+            color: input.color,
+            stroke_width: input.stroke_width,
+            duration_ms: input.duration_ms,
+            process_time_ms: session_start.elapsed().as_secs_f64() * 1000.0,
+            unix_time_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        state.annotations.push(annotation.clone());
+        Ok(annotation)
+    }
+
+    fn begin_session(&self) {
+        let mut state = self.inner.lock();
+        state.session_start = Some(Instant::now());
+        state.annotations.clear();
+    }
+
+    fn end_session(&self) -> Vec<Annotation> {
+        let mut state = self.inner.lock();
+        state.session_start = None;
+        std::mem::take(&mut state.annotations)
+    }
+}
+
+pub struct AnnotationChannel {
+    id: String,
+    sink: AnnotationSink,
+    is_recording: Arc<AtomicBool>,
+    output_dir: Option<PathBuf>,
+    session_index: usize,
+    output_files: Arc<ParkingMutex<Vec<String>>>,
+}
+
+impl AnnotationChannel {
+    pub fn new(sink: AnnotationSink) -> Self {
+        Self {
+            id: "annotation".to_string(),
+            sink,
+            is_recording: Arc::new(AtomicBool::new(false)),
+            output_dir: None,
+            session_index: 0,
+            output_files: Arc::new(ParkingMutex::new(Vec::new())),
+        }
+    }
+
+    fn flush_to_disk(&mut self, annotations: &[Annotation]) -> RecordingResult<()> {
+        let output_dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+
+        std::fs::create_dir_all(&output_dir)?;
+
+        let path = output_dir.join(FileKind::Annotations.conventional_name(self.session_index));
+        let data = serde_json::to_vec_pretty(annotations)
+            .map_err(|e| RecordingError::IoError(std::io::Error::other(e)))?;
+        std::fs::write(&path, data)?;
+
+        self.output_files.lock().push(path.to_string_lossy().to_string());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordingChannel for AnnotationChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Annotation
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+        tracing::info!("Annotation channel initialized (session={})", session_index);
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if self.is_recording.load(Ordering::SeqCst) {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        if self.session_index == 0 {
+            self.output_files.lock().clear();
+        }
+
+        self.sink.begin_session();
+        self.is_recording.store(true, Ordering::SeqCst);
+
+        tracing::info!("Annotation capture started");
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> RecordingResult<()> {
+        if !self.is_recording.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.is_recording.store(false, Ordering::SeqCst);
+
+        let annotations = self.sink.end_session();
+        self.flush_to_disk(&annotations)?;
+
+        tracing::info!("Annotation capture stopped ({} annotations)", annotations.len());
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> RecordingResult<()> {
+        self.stop().await
+    }
+
+    async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+        self.session_index = session_index;
+        self.start().await
+    }
+
+    fn is_recording(&self) -> bool {
+        self.is_recording.load(Ordering::SeqCst)
+    }
+
+    fn output_files(&self) -> Vec<String> {
+        self.output_files.lock().clone()
+    }
+}
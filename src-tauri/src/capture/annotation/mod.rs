@@ -0,0 +1,12 @@
+//! Live annotation capture
+//!
+//! Implements a `RecordingChannel` that collects annotations drawn on screen
+//! during a recording (pen strokes, arrows, highlights). Annotations are pushed
+//! from the drawing overlay through an `AnnotationSink`, timestamped against the
+//! current session, and rendered on top of the screen during export.
+
+pub mod channel;
+pub mod types;
+
+pub use channel::{AnnotationChannel, AnnotationSink};
+pub use types::{Annotation, AnnotationInput, AnnotationPoint, AnnotationShape};
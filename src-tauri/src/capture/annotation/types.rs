@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// Point in display pixel coordinates (top-left origin, same space as mouse moves)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AnnotationPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Geometry of an annotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum AnnotationShape {
+    /// Freehand pen stroke
+    Pen { points: Vec<AnnotationPoint> },
+    /// Straight arrow pointing at `to`
+    Arrow {
+        from: AnnotationPoint,
+        to: AnnotationPoint,
+    },
+    /// Translucent highlight rectangle
+    Highlight {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+}
+
+/// Annotation as sent by the drawing overlay (not yet timestamped)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationInput {
+    pub shape: AnnotationShape,
+    /// CSS hex color (#RRGGBB or #RRGGBBAA)
+    pub color: String,
+    /// Stroke width in pixels
+    pub stroke_width: f64,
+    /// How long the annotation stays visible (None = default duration)
+    pub duration_ms: Option<f64>,
+}
+
+/// Annotation recorded during a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub id: String,
+    pub shape: AnnotationShape,
+    pub color: String,
+    pub stroke_width: f64,
+    pub duration_ms: Option<f64>,
+    pub process_time_ms: f64,
+    pub unix_time_ms: u64,
+}
@@ -3,17 +3,35 @@
 //! This module provides microphone capture functionality using the cpal crate.
 //! System audio capture is handled separately by platform-specific modules.
 
+use crate::capture::level::{AudioLevel, LevelMeter, LEVEL_INTERVAL};
 use crate::capture::traits::AudioDeviceInfo;
 use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::coordinator::RecordingEvent;
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::segment::live_audio_movflags_args;
+use crate::recorder::state::{AudioEncodingConfig, SUPPORTED_AUDIO_SAMPLE_RATES};
+use crate::utils::process::{track, TrackedChild};
+use crate::waveform::live::{write_waveform, LivePeaks, LIVE_PEAKS_PER_SECOND};
 use async_trait::async_trait;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, StreamConfig};
+use cpal::{
+    Device, SampleFormat, SampleRate, StreamConfig, SupportedStreamConfig,
+    SupportedStreamConfigRange,
+};
 use parking_lot::Mutex as ParkingMutex;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
+use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// How often to retry opening the default input after the mic disappears
+const FALLBACK_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A stream that delivers no buffers for this long is treated as disconnected
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Get list of available audio input devices
 pub fn get_audio_input_devices() -> Vec<AudioDeviceInfo> {
@@ -34,6 +52,7 @@ pub fn get_audio_input_devices() -> Vec<AudioDeviceInfo> {
                     name: name.clone(),
                     is_input: true,
                     is_default,
+                    sample_rates: supported_sample_rates(&input_config_ranges(&device)),
                 });
             }
         }
@@ -42,6 +61,74 @@ pub fn get_audio_input_devices() -> Vec<AudioDeviceInfo> {
     devices
 }
 
+/// Every input configuration a device offers
+fn input_config_ranges(device: &Device) -> Vec<SupportedStreamConfigRange> {
+    device
+        .supported_input_configs()
+        .map(|configs| configs.collect())
+        .unwrap_or_default()
+}
+
+/// Sample formats the microphone stream can convert from
+fn is_readable(format: SampleFormat) -> bool {
+    matches!(format, SampleFormat::F32 | SampleFormat::I16 | SampleFormat::U16)
+}
+
+/// Supported recording sample rates some readable configuration covers
+fn supported_sample_rates(ranges: &[SupportedStreamConfigRange]) -> Vec<u32> {
+    SUPPORTED_AUDIO_SAMPLE_RATES
+        .into_iter()
+        .filter(|rate| ranges.iter().any(|range| covers(range, *rate)))
+        .collect()
+}
+
+fn covers(range: &SupportedStreamConfigRange, rate: u32) -> bool {
+    is_readable(range.sample_format())
+        && range.min_sample_rate().0 <= rate
+        && rate <= range.max_sample_rate().0
+}
+
+/// Configuration at `rate` out of `ranges`, keeping the default
+/// configuration's channel count and sample format where possible
+fn config_at_rate(
+    ranges: &[SupportedStreamConfigRange],
+    default: &SupportedStreamConfig,
+    rate: u32,
+) -> Option<SupportedStreamConfig> {
+    ranges
+        .iter()
+        .filter(|range| covers(range, rate))
+        .max_by_key(|range| {
+            (
+                range.channels() == default.channels(),
+                range.sample_format() == default.sample_format(),
+            )
+        })
+        .map(|range| range.clone().with_sample_rate(SampleRate(rate)))
+}
+
+/// Configuration to open a microphone with: its default one, at `rate` if
+/// given and the device supports it
+fn input_config(device: &Device, rate: Option<u32>) -> Result<SupportedStreamConfig, String> {
+    let default = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to get audio config: {}", e))?;
+    let Some(rate) = rate.filter(|rate| *rate != default.sample_rate().0) else {
+        return Ok(default);
+    };
+    match config_at_rate(&input_config_ranges(device), &default, rate) {
+        Some(config) => Ok(config),
+        None => {
+            tracing::warn!(
+                "Microphone doesn't support {}Hz, recording at {}Hz",
+                rate,
+                default.sample_rate().0
+            );
+            Ok(default)
+        }
+    }
+}
+
 /// Get the default audio input device
 pub fn get_default_input_device() -> Option<Device> {
     let host = cpal::default_host();
@@ -65,23 +152,28 @@ pub fn get_input_device_by_name(name: &str) -> Option<Device> {
 
 /// FFmpeg encoder for audio
 pub struct AudioEncoder {
-    process: ParkingMutex<Option<Child>>,
+    process: ParkingMutex<Option<TrackedChild>>,
     sample_count: AtomicU64,
     running: AtomicBool,
     output_path: PathBuf,
+    /// Waveform peaks computed from the samples as they are written
+    peaks: ParkingMutex<LivePeaks>,
+    /// Level of the samples written since it was last taken
+    meter: ParkingMutex<LevelMeter>,
 }
 
 impl AudioEncoder {
     pub fn new(
         sample_rate: u32,
         channels: u16,
+        bitrate_kbps: u32,
         output_dir: &Path,
         session_index: usize,
-        suffix: &str,
+        kind: FileKind,
     ) -> Result<Self, std::io::Error> {
         std::fs::create_dir_all(output_dir)?;
 
-        let output_path = output_dir.join(format!("recording-{}-{}.m4a", session_index, suffix));
+        let output_path = output_dir.join(kind.conventional_name(session_index));
 
         // Start FFmpeg process for audio encoding
         // Input: 32-bit float PCM from cpal
@@ -94,27 +186,30 @@ impl AudioEncoder {
                 "-ac", &channels.to_string(),   // Channel count
                 "-i", "-",                       // Read from stdin
                 "-c:a", "aac",                   // AAC codec
-                "-b:a", "192k",                  // 192kbps bitrate
-                "-movflags", "+faststart",       // For streaming
-                output_path.to_str().unwrap(),
+                "-b:a", &format!("{}k", bitrate_kbps), // Bitrate
             ])
+            .args(live_audio_movflags_args())    // Recoverable after a crash
+            .arg(&output_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
             .spawn()?;
 
         tracing::info!(
-            "Started audio encoder: {}Hz {}ch, output: {:?}",
+            "Started audio encoder: {}Hz {}ch {}kbps, output: {:?}",
             sample_rate,
             channels,
+            bitrate_kbps,
             output_path
         );
 
         Ok(Self {
-            process: ParkingMutex::new(Some(process)),
+            process: ParkingMutex::new(Some(track(process, "audio encoder"))),
             sample_count: AtomicU64::new(0),
             running: AtomicBool::new(true),
             output_path,
+            peaks: ParkingMutex::new(LivePeaks::new(sample_rate, channels, LIVE_PEAKS_PER_SECOND)),
+            meter: ParkingMutex::new(LevelMeter::new()),
         })
     }
 
@@ -128,6 +223,12 @@ impl AudioEncoder {
             if let Some(ref mut stdin) = process.stdin {
                 if stdin.write_all(data).is_ok() {
                     self.sample_count.fetch_add((data.len() / 4) as u64, Ordering::Relaxed);
+                    let samples: Vec<f32> = data
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect();
+                    self.peaks.lock().push(&samples);
+                    self.meter.lock().push(&samples);
                     return true;
                 }
             }
@@ -139,6 +240,16 @@ impl AudioEncoder {
         self.sample_count.load(Ordering::Relaxed)
     }
 
+    /// Latest peak level (0.0-1.0) of the written audio
+    pub fn level(&self) -> f32 {
+        self.peaks.lock().level()
+    }
+
+    /// Level of the audio written since the last call, `elapsed` ago
+    pub fn take_level(&self, elapsed: Duration) -> AudioLevel {
+        self.meter.lock().take(elapsed)
+    }
+
     pub fn finish(&self) -> Result<Option<String>, std::io::Error> {
         self.running.store(false, Ordering::Relaxed);
         let mut guard = self.process.lock();
@@ -152,6 +263,9 @@ impl AudioEncoder {
         }
 
         if self.output_path.exists() && self.sample_count() > 0 {
+            if let Err(e) = write_waveform(&self.output_path, &self.peaks.lock().to_waveform()) {
+                tracing::warn!("Failed to write waveform for {:?}: {}", self.output_path, e);
+            }
             tracing::info!(
                 "Audio encoding finished: {} samples, output: {:?}",
                 self.sample_count(),
@@ -164,6 +278,270 @@ impl AudioEncoder {
     }
 }
 
+/// Sample rate and channel layout of an interleaved f32 audio stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl AudioFormat {
+    /// Format to encode at: the configured settings, or this (native) format
+    pub(crate) fn with_encoding(self, encoding: &AudioEncodingConfig) -> Self {
+        Self {
+            sample_rate: encoding.sample_rate.unwrap_or(self.sample_rate),
+            channels: encoding
+                .channel_layout
+                .map(|layout| layout.channel_count())
+                .unwrap_or(self.channels),
+        }
+    }
+}
+
+/// Convert interleaved f32 samples between channel layouts and sample rates.
+///
+/// Used when a device doesn't match the format the encoder was opened with
+/// (a fallback device, or a configured encoding that differs from the native
+/// format). Resampling is linear and per-buffer, which is good enough for speech.
+pub(crate) fn convert_samples(data: &[f32], from: AudioFormat, to: AudioFormat) -> Vec<f32> {
+    if from == to {
+        return data.to_vec();
+    }
+
+    let in_ch = from.channels.max(1) as usize;
+    let out_ch = to.channels.max(1) as usize;
+    let in_frames = data.len() / in_ch;
+    if in_frames == 0 {
+        return Vec::new();
+    }
+
+    // Remap channels: duplicate mono, average down to mono, otherwise wrap
+    let mut remapped = Vec::with_capacity(in_frames * out_ch);
+    for frame in data.chunks_exact(in_ch) {
+        for c in 0..out_ch {
+            let sample = if in_ch == 1 {
+                frame[0]
+            } else if out_ch == 1 {
+                frame.iter().sum::<f32>() / in_ch as f32
+            } else {
+                frame[c % in_ch]
+            };
+            remapped.push(sample);
+        }
+    }
+
+    if from.sample_rate == to.sample_rate {
+        return remapped;
+    }
+
+    let out_frames = (in_frames as u64 * to.sample_rate as u64 / from.sample_rate as u64) as usize;
+    let ratio = from.sample_rate as f64 / to.sample_rate as f64;
+    let mut out = Vec::with_capacity(out_frames * out_ch);
+    for i in 0..out_frames {
+        let pos = i as f64 * ratio;
+        let i0 = (pos.floor() as usize).min(in_frames - 1);
+        let i1 = (i0 + 1).min(in_frames - 1);
+        let t = (pos - i0 as f64) as f32;
+        for c in 0..out_ch {
+            let a = remapped[i0 * out_ch + c];
+            let b = remapped[i1 * out_ch + c];
+            out.push(a + (b - a) * t);
+        }
+    }
+    out
+}
+
+/// Number of interleaved samples covering `duration` in the given format
+fn sample_count_for(duration: Duration, format: AudioFormat) -> usize {
+    let frames = (duration.as_secs_f64() * format.sample_rate as f64).round() as usize;
+    frames * format.channels as usize
+}
+
+/// Write silence so the encoder has `gap` worth of audio since the device was lost.
+/// Returns the number of samples written on top of `already_written`.
+fn write_silence(encoder: &AudioEncoder, gap: Duration, format: AudioFormat, already_written: usize) -> usize {
+    let due = sample_count_for(gap, format).saturating_sub(already_written);
+    if due == 0 {
+        return 0;
+    }
+    let silence = vec![0u8; due * 4];
+    if encoder.write_samples(&silence) {
+        due
+    } else {
+        0
+    }
+}
+
+/// Tracks microphone stream liveness so device loss is detected even when the
+/// backend stops delivering buffers without reporting an error, and measures
+/// the gaps between buffers so they can be filled with silence.
+#[derive(Clone)]
+struct StreamMonitor {
+    epoch: Instant,
+    /// When the last written audio ended, in µs since `epoch` (0 = none yet)
+    last_written_us: Arc<AtomicU64>,
+    device_lost: Arc<AtomicBool>,
+}
+
+impl StreamMonitor {
+    fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            last_written_us: Arc::new(AtomicU64::new(0)),
+            device_lost: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn elapsed_us(&self) -> u64 {
+        (self.epoch.elapsed().as_micros() as u64).max(1)
+    }
+
+    /// Record a buffer `buffer` long arriving now. Returns the audio missing
+    /// before it: the time since the last written audio beyond the buffer's
+    /// own length, when that's more than a buffer period.
+    fn mark_buffer(&self, buffer: Duration) -> Option<Duration> {
+        self.mark_buffer_at(self.elapsed_us(), buffer)
+    }
+
+    fn mark_buffer_at(&self, now_us: u64, buffer: Duration) -> Option<Duration> {
+        let last = self.last_written_us.swap(now_us, Ordering::Relaxed);
+        if last == 0 {
+            return None;
+        }
+        let missing = Duration::from_micros(now_us.saturating_sub(last)).saturating_sub(buffer);
+        (missing > buffer).then_some(missing)
+    }
+
+    /// When the last written audio ended, if any was written
+    fn last_written(&self) -> Option<Instant> {
+        let last = self.last_written_us.load(Ordering::Relaxed);
+        (last > 0).then(|| self.epoch + Duration::from_micros(last))
+    }
+
+    fn mark_lost(&self) {
+        self.device_lost.store(true, Ordering::SeqCst);
+    }
+
+    fn is_lost(&self) -> bool {
+        if self.device_lost.load(Ordering::SeqCst) {
+            return true;
+        }
+        let last = self.last_written_us.load(Ordering::Relaxed);
+        last > 0 && self.elapsed_us().saturating_sub(last) > STALL_TIMEOUT.as_micros() as u64
+    }
+
+    /// Start watching a new stream, whose audio carries on from now
+    fn reset(&self) {
+        self.device_lost.store(false, Ordering::SeqCst);
+        self.last_written_us.store(self.elapsed_us(), Ordering::Relaxed);
+    }
+}
+
+/// Open and start an input stream on `device` that feeds `encoder` in the `target` format
+fn open_mic_stream(
+    device: &Device,
+    encoder: Arc<AudioEncoder>,
+    target: AudioFormat,
+    input_rate: Option<u32>,
+    is_recording: Arc<AtomicBool>,
+    monitor: StreamMonitor,
+) -> Result<cpal::Stream, String> {
+    let config = input_config(device, input_rate)?;
+
+    let sample_format = config.sample_format();
+    let stream_config: StreamConfig = config.into();
+    let source = AudioFormat {
+        sample_rate: stream_config.sample_rate.0,
+        channels: stream_config.channels,
+    };
+
+    // Log the actual stream configuration for debugging
+    tracing::info!(
+        "Microphone stream config: format={:?}, sample_rate={}, channels={}",
+        sample_format,
+        source.sample_rate,
+        source.channels
+    );
+
+    let stream = match sample_format {
+        SampleFormat::F32 => build_mic_input_stream::<f32>(
+            device, &stream_config, source, target, encoder, is_recording, monitor,
+            |sample| sample,
+        ),
+        SampleFormat::I16 => build_mic_input_stream::<i16>(
+            device, &stream_config, source, target, encoder, is_recording, monitor,
+            |sample| sample as f32 / i16::MAX as f32,
+        ),
+        SampleFormat::U16 => build_mic_input_stream::<u16>(
+            device, &stream_config, source, target, encoder, is_recording, monitor,
+            |sample| (sample as f32 / u16::MAX as f32) * 2.0 - 1.0,
+        ),
+        _ => {
+            return Err(format!("Unsupported microphone sample format: {:?}", sample_format));
+        }
+    }
+    .map_err(|e| format!("Failed to build audio stream: {}", e))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start microphone stream: {}", e))?;
+
+    Ok(stream)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_mic_input_stream<T: cpal::SizedSample>(
+    device: &Device,
+    config: &StreamConfig,
+    source: AudioFormat,
+    target: AudioFormat,
+    encoder: Arc<AudioEncoder>,
+    is_recording: Arc<AtomicBool>,
+    monitor: StreamMonitor,
+    to_f32: fn(T) -> f32,
+) -> Result<cpal::Stream, cpal::BuildStreamError> {
+    let error_monitor = monitor.clone();
+    let mut callback_count = 0u64;
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let frames = data.len() / source.channels.max(1) as usize;
+            let buffer = Duration::from_secs_f64(frames as f64 / source.sample_rate as f64);
+            let missing = monitor.mark_buffer(buffer);
+
+            // Log first callback and then every 500th to confirm mic is working
+            if callback_count == 0 {
+                tracing::info!("Microphone: first callback received - capture working!");
+            } else if callback_count % 500 == 0 {
+                tracing::debug!("Microphone: {} callbacks, {} samples this batch", callback_count, data.len());
+            }
+            callback_count += 1;
+
+            if is_recording.load(Ordering::Relaxed) {
+                // The device skipped buffers: silence keeps the track in step
+                if let Some(missing) = missing {
+                    write_silence(&encoder, missing, target, 0);
+                }
+                let samples: Vec<f32> = data.iter().map(|&sample| to_f32(sample)).collect();
+                let bytes: Vec<u8> = convert_samples(&samples, source, target)
+                    .iter()
+                    .flat_map(|sample| sample.to_le_bytes())
+                    .collect();
+                encoder.write_samples(&bytes);
+            }
+        },
+        move |err| match err {
+            cpal::StreamError::DeviceNotAvailable => {
+                tracing::warn!("Microphone stream error: device no longer available");
+                error_monitor.mark_lost();
+            }
+            err => tracing::error!("Microphone stream error: {}", err),
+        },
+        None,
+    )
+}
+
 /// Microphone capture channel
 /// 
 /// Uses a background thread for the audio stream since cpal::Stream is not Send.
@@ -178,12 +556,18 @@ pub struct MicrophoneCaptureChannel {
     stream_handle: Arc<ParkingMutex<Option<std::thread::JoinHandle<()>>>>,
     sample_rate: u32,
     channels: u16,
+    /// Rate the device is opened at, found at initialization
+    input_sample_rate: Option<u32>,
+    encoding: AudioEncodingConfig,
+    event_tx: Option<broadcast::Sender<RecordingEvent>>,
+    /// Name of the device resolved at initialization
+    device_name: Option<String>,
 }
 
 impl MicrophoneCaptureChannel {
     /// Create a new microphone capture channel
     /// If device_id is None, uses the default input device
-    pub fn new(device_id: Option<String>) -> Self {
+    pub fn new(device_id: Option<String>, encoding: AudioEncodingConfig) -> Self {
         Self {
             id: "microphone".to_string(),
             device_id,
@@ -195,6 +579,10 @@ impl MicrophoneCaptureChannel {
             stream_handle: Arc::new(ParkingMutex::new(None)),
             sample_rate: 48000,
             channels: 2,
+            input_sample_rate: None,
+            encoding,
+            event_tx: None,
+            device_name: None,
         }
     }
 
@@ -223,7 +611,7 @@ impl RecordingChannel for MicrophoneCaptureChannel {
     async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
         // Check if FFmpeg is available
         if Command::new("ffmpeg").arg("-version").output().is_err() {
-            return Err(RecordingError::ConfigurationError(
+            return Err(RecordingError::FfmpegMissing(
                 "FFmpeg not found. Please install FFmpeg.".to_string(),
             ));
         }
@@ -232,21 +620,31 @@ impl RecordingChannel for MicrophoneCaptureChannel {
         let device = self.get_device()?;
         let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
 
-        // Get supported config
-        let config = device.default_input_config().map_err(|e| {
-            RecordingError::ConfigurationError(format!("Failed to get audio config: {}", e))
-        })?;
-
-        self.sample_rate = config.sample_rate().0;
-        self.channels = config.channels();
+        // Get supported config, at the chosen input rate if there is one
+        let config = input_config(&device, self.encoding.input_sample_rate)
+            .map_err(RecordingError::ConfigurationError)?;
+
+        // Encode at the configured format; the stream callback converts to it
+        let native = AudioFormat {
+            sample_rate: config.sample_rate().0,
+            channels: config.channels(),
+        };
+        let target = native.with_encoding(&self.encoding);
+        self.sample_rate = target.sample_rate;
+        self.channels = target.channels;
+        self.input_sample_rate = Some(native.sample_rate);
         self.output_dir = Some(output_dir.to_path_buf());
         self.session_index = session_index;
+        self.device_name = Some(device_name.clone());
 
         tracing::info!(
-            "Microphone channel initialized: {} ({}Hz, {}ch)",
+            "Microphone channel initialized: {} ({}Hz {}ch native, encoding {}Hz {}ch {}kbps)",
             device_name,
+            native.sample_rate,
+            native.channels,
             self.sample_rate,
-            self.channels
+            self.channels,
+            self.encoding.bitrate_kbps()
         );
         Ok(())
     }
@@ -265,9 +663,10 @@ impl RecordingChannel for MicrophoneCaptureChannel {
             AudioEncoder::new(
                 self.sample_rate,
                 self.channels,
+                self.encoding.bitrate_kbps(),
                 &output_dir,
                 self.session_index,
-                "mic",
+                FileKind::MicAudio,
             )
             .map_err(|e| RecordingError::CaptureError(format!("Failed to start audio encoder: {}", e)))?,
         );
@@ -278,6 +677,13 @@ impl RecordingChannel for MicrophoneCaptureChannel {
         // Clone values for the thread
         let device_id = self.device_id.clone();
         let is_recording = self.is_recording.clone();
+        let event_tx = self.event_tx.clone();
+        let channel_id = self.id.clone();
+        let input_rate = self.encoding.input_sample_rate;
+        let target = AudioFormat {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        };
 
         // Spawn a thread to handle the audio stream (cpal::Stream is not Send)
         let handle = std::thread::spawn(move || {
@@ -293,138 +699,124 @@ impl RecordingChannel for MicrophoneCaptureChannel {
                     return;
                 }
             };
+            let original_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            let mut device_name = original_name.clone();
 
-            let config = match device.default_input_config() {
-                Ok(c) => c,
+            let monitor = StreamMonitor::new();
+            let mut stream = match open_mic_stream(&device, encoder.clone(), target, input_rate, is_recording.clone(), monitor.clone()) {
+                Ok(s) => Some(s),
                 Err(e) => {
-                    tracing::error!("Failed to get audio config: {}", e);
+                    tracing::error!("{}", e);
                     return;
                 }
             };
 
-            let sample_format = config.sample_format();
-            let stream_config: StreamConfig = config.into();
+            tracing::info!("Microphone audio stream started successfully");
 
-            // Log the actual stream configuration for debugging
-            tracing::info!(
-                "Microphone stream config: format={:?}, sample_rate={}, channels={}",
-                sample_format,
-                stream_config.sample_rate.0,
-                stream_config.channels
-            );
+            // Device loss handling: while the device is gone we keep the track
+            // in sync by feeding silence, and periodically try the original
+            // device again, or else the default input.
+            let mut lost_at: Option<Instant> = None;
+            let mut silence_written = 0usize;
+            let mut last_attempt = Instant::now();
+            let mut metered_at = Instant::now();
 
-            // Callback counter for diagnostic logging
-            let callback_count = Arc::new(AtomicU64::new(0));
-
-            let stream = match sample_format {
-                SampleFormat::F32 => {
-                    let encoder_clone = encoder.clone();
-                    let is_rec = is_recording.clone();
-                    let cc = callback_count.clone();
-                    device.build_input_stream(
-                        &stream_config,
-                        move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                            let count = cc.fetch_add(1, Ordering::Relaxed);
-                            // Log first callback and then every 500th to confirm mic is working
-                            if count == 0 {
-                                tracing::info!("Microphone: first callback received - capture working!");
-                            } else if count % 500 == 0 {
-                                tracing::debug!("Microphone: {} callbacks, {} samples this batch", count, data.len());
-                            }
-                            
-                            if is_rec.load(Ordering::Relaxed) {
-                                let bytes: Vec<u8> = data
-                                    .iter()
-                                    .flat_map(|&sample| sample.to_le_bytes())
-                                    .collect();
-                                encoder_clone.write_samples(&bytes);
-                            }
-                        },
-                        |err| tracing::error!("Microphone stream error: {}", err),
-                        None,
-                    )
+            // Keep thread alive while recording
+            while is_recording.load(Ordering::SeqCst) {
+                std::thread::sleep(LEVEL_INTERVAL);
+
+                // Report the level for the toolbar's meter; silence written
+                // while the device is gone counts as silent
+                let level = encoder.take_level(metered_at.elapsed());
+                metered_at = Instant::now();
+                if let Some(ref tx) = event_tx {
+                    let _ = tx.send(RecordingEvent::MicLevel(level));
                 }
-                SampleFormat::I16 => {
-                    let encoder_clone = encoder.clone();
-                    let is_rec = is_recording.clone();
-                    let cc = callback_count.clone();
-                    device.build_input_stream(
-                        &stream_config,
-                        move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                            let count = cc.fetch_add(1, Ordering::Relaxed);
-                            if count == 0 {
-                                tracing::info!("Microphone: first callback received - capture working!");
-                            } else if count % 500 == 0 {
-                                tracing::debug!("Microphone: {} callbacks, {} samples this batch", count, data.len());
-                            }
-                            
-                            if is_rec.load(Ordering::Relaxed) {
-                                let bytes: Vec<u8> = data
-                                    .iter()
-                                    .map(|&sample| sample as f32 / i16::MAX as f32)
-                                    .flat_map(|sample| sample.to_le_bytes())
-                                    .collect();
-                                encoder_clone.write_samples(&bytes);
-                            }
-                        },
-                        |err| tracing::error!("Microphone stream error: {}", err),
-                        None,
-                    )
+
+                if lost_at.is_none() && monitor.is_lost() {
+                    tracing::warn!("Microphone '{}' was disconnected", device_name);
+                    stream = None;
+                    // The gap runs from the last audio written, not from
+                    // when the loss was noticed
+                    lost_at = Some(monitor.last_written().unwrap_or_else(Instant::now));
+                    silence_written = 0;
+                    if let Some(ref tx) = event_tx {
+                        let _ = tx.send(RecordingEvent::DeviceLost {
+                            channel: channel_id.clone(),
+                            device: device_name.clone(),
+                        });
+                    }
                 }
-                SampleFormat::U16 => {
-                    let encoder_clone = encoder.clone();
-                    let is_rec = is_recording.clone();
-                    let cc = callback_count.clone();
-                    device.build_input_stream(
-                        &stream_config,
-                        move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                            let count = cc.fetch_add(1, Ordering::Relaxed);
-                            if count == 0 {
-                                tracing::info!("Microphone: first callback received - capture working!");
-                            } else if count % 500 == 0 {
-                                tracing::debug!("Microphone: {} callbacks, {} samples this batch", count, data.len());
+
+                let Some(lost) = lost_at else {
+                    continue;
+                };
+
+                silence_written += write_silence(&encoder, lost.elapsed(), target, silence_written);
+
+                if last_attempt.elapsed() < FALLBACK_RETRY_INTERVAL {
+                    continue;
+                }
+                last_attempt = Instant::now();
+
+                let Some(fallback) =
+                    get_input_device_by_name(&original_name).or_else(get_default_input_device)
+                else {
+                    continue;
+                };
+                let fallback_name = fallback.name().unwrap_or_else(|_| "Unknown".to_string());
+
+                // The silence written reaches up to now, where the new
+                // stream's audio carries on
+                monitor.reset();
+                match open_mic_stream(&fallback, encoder.clone(), target, input_rate, is_recording.clone(), monitor.clone()) {
+                    Ok(s) => {
+                        let gap_ms = lost.elapsed().as_secs_f64() * 1000.0;
+                        let event = if fallback_name == original_name {
+                            tracing::info!(
+                                "Microphone '{}' reconnected ({:.0}ms gap filled with silence)",
+                                fallback_name,
+                                gap_ms
+                            );
+                            RecordingEvent::DeviceRecovered {
+                                channel: channel_id.clone(),
+                                device: fallback_name.clone(),
+                                gap_ms,
                             }
-                            
-                            if is_rec.load(Ordering::Relaxed) {
-                                let bytes: Vec<u8> = data
-                                    .iter()
-                                    .map(|&sample| (sample as f32 / u16::MAX as f32) * 2.0 - 1.0)
-                                    .flat_map(|sample| sample.to_le_bytes())
-                                    .collect();
-                                encoder_clone.write_samples(&bytes);
+                        } else {
+                            tracing::warn!(
+                                "Microphone fell back from '{}' to '{}' ({:.0}ms gap filled with silence)",
+                                device_name,
+                                fallback_name,
+                                gap_ms
+                            );
+                            RecordingEvent::DeviceFallback {
+                                channel: channel_id.clone(),
+                                from: device_name.clone(),
+                                to: fallback_name.clone(),
+                                gap_ms,
                             }
-                        },
-                        |err| tracing::error!("Microphone stream error: {}", err),
-                        None,
-                    )
-                }
-                _ => {
-                    tracing::error!("Unsupported microphone sample format: {:?}", sample_format);
-                    return;
+                        };
+                        if let Some(ref tx) = event_tx {
+                            let _ = tx.send(event);
+                        }
+                        stream = Some(s);
+                        device_name = fallback_name;
+                        lost_at = None;
+                    }
+                    Err(e) => {
+                        tracing::debug!("Microphone fallback to '{}' failed: {}", fallback_name, e);
+                    }
                 }
-            };
-
-            let stream = match stream {
-                Ok(s) => s,
-                Err(e) => {
-                    tracing::error!("Failed to build audio stream: {}", e);
-                    return;
-                }
-            };
-
-            if let Err(e) = stream.play() {
-                tracing::error!("Failed to start microphone stream: {}", e);
-                return;
             }
 
-            tracing::info!("Microphone audio stream started successfully");
-
-            // Keep thread alive while recording
-            while is_recording.load(Ordering::SeqCst) {
-                std::thread::sleep(std::time::Duration::from_millis(100));
+            // Pad the track up to the stop time if the device never came back
+            if let Some(lost) = lost_at {
+                write_silence(&encoder, lost.elapsed(), target, silence_written);
             }
 
             // Stream is dropped here, stopping capture
+            drop(stream);
             tracing::info!("Microphone audio stream stopped");
         });
 
@@ -474,4 +866,152 @@ impl RecordingChannel for MicrophoneCaptureChannel {
     fn output_files(&self) -> Vec<String> {
         self.output_files.lock().clone()
     }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<RecordingEvent>) {
+        self.event_tx = Some(event_tx);
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: self.device_name.clone(),
+            sample_rate: Some(self.sample_rate),
+            input_sample_rate: self.input_sample_rate.filter(|rate| *rate != self.sample_rate),
+            channels: Some(self.channels),
+            bitrate_kbps: Some(self.encoding.bitrate_kbps()),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::state::AudioChannelLayout;
+
+    const STEREO_48K: AudioFormat = AudioFormat {
+        sample_rate: 48000,
+        channels: 2,
+    };
+
+    #[test]
+    fn test_convert_samples_mono_to_stereo() {
+        let mono_48k = AudioFormat {
+            sample_rate: 48000,
+            channels: 1,
+        };
+        let out = convert_samples(&[0.1, 0.2], mono_48k, STEREO_48K);
+        assert_eq!(out, vec![0.1, 0.1, 0.2, 0.2]);
+    }
+
+    #[test]
+    fn test_convert_samples_resamples_to_target_rate() {
+        let stereo_24k = AudioFormat {
+            sample_rate: 24000,
+            channels: 2,
+        };
+        let input = vec![0.0; 240 * 2];
+        let out = convert_samples(&input, stereo_24k, STEREO_48K);
+        assert_eq!(out.len(), 480 * 2);
+    }
+
+    #[test]
+    fn test_encoding_overrides_native_format() {
+        let native = AudioFormat {
+            sample_rate: 44100,
+            channels: 1,
+        };
+        assert_eq!(native.with_encoding(&AudioEncodingConfig::default()), native);
+
+        let encoding = AudioEncodingConfig {
+            input_sample_rate: None,
+            sample_rate: Some(48000),
+            channel_layout: Some(AudioChannelLayout::Stereo),
+            bitrate_kbps: Some(320),
+        };
+        assert_eq!(native.with_encoding(&encoding), STEREO_48K);
+        assert!(encoding.validate().is_ok());
+
+        let bad_rate = AudioEncodingConfig {
+            sample_rate: Some(12345),
+            ..Default::default()
+        };
+        assert!(bad_rate.validate().is_err());
+    }
+
+    #[test]
+    fn test_input_config_at_rate() {
+        use cpal::SupportedBufferSize;
+        let range = |channels, min, max, format| {
+            SupportedStreamConfigRange::new(
+                channels,
+                SampleRate(min),
+                SampleRate(max),
+                SupportedBufferSize::Unknown,
+                format,
+            )
+        };
+        let ranges = vec![
+            range(1, 8000, 48000, SampleFormat::I16),
+            range(2, 44100, 96000, SampleFormat::F32),
+            range(2, 8000, 192000, SampleFormat::I32),
+        ];
+        let default = SupportedStreamConfig::new(
+            2,
+            SampleRate(48000),
+            SupportedBufferSize::Unknown,
+            SampleFormat::F32,
+        );
+
+        let config = config_at_rate(&ranges, &default, 96000).unwrap();
+        assert_eq!(config.sample_rate(), SampleRate(96000));
+        assert_eq!((config.channels(), config.sample_format()), (2, SampleFormat::F32));
+        // Only the mono range goes that low
+        let config = config_at_rate(&ranges, &default, 16000).unwrap();
+        assert_eq!((config.channels(), config.sample_format()), (1, SampleFormat::I16));
+        // I32 isn't read, whatever its rates
+        assert!(config_at_rate(&ranges, &default, 192000).is_none());
+
+        assert_eq!(
+            supported_sample_rates(&ranges),
+            vec![8000, 16000, 22050, 24000, 32000, 44100, 48000, 96000]
+        );
+    }
+
+    #[test]
+    fn test_sample_count_for_gap() {
+        let count = sample_count_for(Duration::from_millis(500), STEREO_48K);
+        assert_eq!(count, 24000 * 2);
+    }
+
+    #[test]
+    fn test_stream_monitor_detects_loss() {
+        let monitor = StreamMonitor::new();
+        assert!(!monitor.is_lost());
+
+        monitor.mark_lost();
+        assert!(monitor.is_lost());
+
+        monitor.reset();
+        assert!(!monitor.is_lost());
+    }
+
+    #[test]
+    fn test_stream_monitor_measures_gaps_from_last_buffer() {
+        let monitor = StreamMonitor::new();
+        let buffer = Duration::from_millis(10);
+        // The first buffer has nothing to follow
+        assert_eq!(monitor.mark_buffer_at(1_000_000, buffer), None);
+        // On time, or within a buffer period of jitter
+        assert_eq!(monitor.mark_buffer_at(1_010_000, buffer), None);
+        assert_eq!(monitor.mark_buffer_at(1_028_000, buffer), None);
+        // 300ms late: everything but this buffer is missing
+        assert_eq!(
+            monitor.mark_buffer_at(1_328_000, buffer),
+            Some(Duration::from_millis(290))
+        );
+        assert_eq!(
+            monitor.last_written(),
+            Some(monitor.epoch + Duration::from_micros(1_328_000))
+        );
+    }
 }
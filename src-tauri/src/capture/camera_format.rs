@@ -0,0 +1,177 @@
+//! Camera capture formats
+//!
+//! Cameras offer a list of formats (resolution, frame rate and pixel
+//! format). `get_cameras` reports them so users can pick e.g. 1080p at 60fps
+//! over 720p at 30fps, and the webcam channel and the camera preview ask for
+//! the chosen format, or for the camera's highest resolution if none is
+//! chosen.
+
+use crate::capture::traits::{CameraFormat, Resolution};
+use nokhwa::pixel_format::RgbFormat;
+use nokhwa::utils::{
+    CameraFormat as NokhwaFormat, CameraIndex, FrameFormat, RequestedFormat, RequestedFormatType,
+    Resolution as NokhwaResolution,
+};
+use nokhwa::{Camera, NokhwaError};
+
+/// Pixel format asked for when a chosen format doesn't name one; nearly
+/// every camera offers its full frame rates in MJPEG
+const DEFAULT_PIXEL_FORMAT: FrameFormat = FrameFormat::MJPEG;
+
+/// Pixel formats FFmpeg can read the camera's frames in as they are
+const KNOWN_PIXEL_FORMATS: [FrameFormat; 5] = [
+    FrameFormat::MJPEG,
+    FrameFormat::YUYV,
+    FrameFormat::NV12,
+    FrameFormat::RAWRGB,
+    FrameFormat::GRAY,
+];
+
+/// FFmpeg name of a camera pixel format, if it's one FFmpeg can read as is
+pub fn pixel_format_name(format: FrameFormat) -> Option<&'static str> {
+    match format {
+        FrameFormat::MJPEG => Some("mjpeg"),
+        FrameFormat::YUYV => Some("yuyv422"),
+        FrameFormat::NV12 => Some("nv12"),
+        FrameFormat::RAWRGB => Some("rgb24"),
+        FrameFormat::GRAY => Some("gray"),
+        _ => None,
+    }
+}
+
+fn frame_format(name: &str) -> Option<FrameFormat> {
+    KNOWN_PIXEL_FORMATS
+        .into_iter()
+        .find(|format| pixel_format_name(*format) == Some(name))
+}
+
+/// What to ask the camera for: the format closest to the chosen one, or its
+/// highest resolution
+pub fn requested_format(format: Option<&CameraFormat>) -> RequestedFormatType {
+    match format {
+        Some(format) => {
+            let pixel_format = format
+                .pixel_format
+                .as_deref()
+                .and_then(frame_format)
+                .unwrap_or(DEFAULT_PIXEL_FORMAT);
+            RequestedFormatType::Closest(NokhwaFormat::new(
+                NokhwaResolution::new(format.width, format.height),
+                pixel_format,
+                format.fps,
+            ))
+        }
+        None => RequestedFormatType::AbsoluteHighestResolution,
+    }
+}
+
+/// Formats a camera supports, largest and fastest first. The camera is
+/// opened without starting its stream, which fails while it's in use.
+pub fn query_formats(index: &CameraIndex) -> Result<Vec<CameraFormat>, NokhwaError> {
+    let requested = RequestedFormat::new::<RgbFormat>(RequestedFormatType::None);
+    let mut camera = Camera::new(index.clone(), requested)?;
+    let formats = camera
+        .compatible_camera_formats()?
+        .into_iter()
+        .filter_map(|format| {
+            Some(CameraFormat {
+                width: format.resolution().width(),
+                height: format.resolution().height(),
+                fps: format.frame_rate(),
+                pixel_format: Some(pixel_format_name(format.format())?.to_string()),
+            })
+        })
+        .collect();
+    Ok(sorted_formats(formats))
+}
+
+/// Largest and fastest first, without duplicates
+fn sorted_formats(mut formats: Vec<CameraFormat>) -> Vec<CameraFormat> {
+    let key = |f: &CameraFormat| (f.width as u64 * f.height as u64, f.width, f.fps);
+    formats.sort_by(|a, b| {
+        key(b)
+            .cmp(&key(a))
+            .then_with(|| a.pixel_format.cmp(&b.pixel_format))
+    });
+    formats.dedup();
+    formats
+}
+
+/// Distinct resolutions of `formats`, in order
+pub fn resolutions(formats: &[CameraFormat]) -> Vec<Resolution> {
+    let mut sizes: Vec<(u32, u32)> = Vec::new();
+    for format in formats {
+        if !sizes.contains(&(format.width, format.height)) {
+            sizes.push((format.width, format.height));
+        }
+    }
+    sizes
+        .into_iter()
+        .map(|(width, height)| Resolution { width, height })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(width: u32, height: u32, fps: u32, pixel_format: &str) -> CameraFormat {
+        CameraFormat {
+            width,
+            height,
+            fps,
+            pixel_format: Some(pixel_format.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_formats_sorted_and_requested() {
+        let formats = sorted_formats(vec![
+            format(1280, 720, 30, "yuyv422"),
+            format(1920, 1080, 30, "mjpeg"),
+            format(1920, 1080, 60, "mjpeg"),
+            format(1280, 720, 30, "mjpeg"),
+            format(1920, 1080, 60, "mjpeg"),
+        ]);
+        assert_eq!(
+            formats,
+            vec![
+                format(1920, 1080, 60, "mjpeg"),
+                format(1920, 1080, 30, "mjpeg"),
+                format(1280, 720, 30, "mjpeg"),
+                format(1280, 720, 30, "yuyv422"),
+            ]
+        );
+        let sizes: Vec<(u32, u32)> = resolutions(&formats)
+            .iter()
+            .map(|r| (r.width, r.height))
+            .collect();
+        assert_eq!(sizes, vec![(1920, 1080), (1280, 720)]);
+
+        for pixel_format in KNOWN_PIXEL_FORMATS {
+            let name = pixel_format_name(pixel_format).unwrap();
+            assert_eq!(frame_format(name), Some(pixel_format));
+        }
+
+        assert!(matches!(
+            requested_format(None),
+            RequestedFormatType::AbsoluteHighestResolution
+        ));
+        let mut chosen = format(1280, 720, 30, "yuyv422");
+        match requested_format(Some(&chosen)) {
+            RequestedFormatType::Closest(requested) => {
+                assert_eq!(requested.width(), 1280);
+                assert_eq!(requested.frame_rate(), 30);
+                assert_eq!(requested.format(), FrameFormat::YUYV);
+            }
+            other => panic!("unexpected request {:?}", other),
+        }
+        chosen.pixel_format = None;
+        match requested_format(Some(&chosen)) {
+            RequestedFormatType::Closest(requested) => {
+                assert_eq!(requested.format(), DEFAULT_PIXEL_FORMAT)
+            }
+            other => panic!("unexpected request {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Camera preview
+//!
+//! Streams the selected camera to the recorder UI as small JPEG frames, so
+//! users can frame themselves before they start recording. The camera is
+//! opened the same way the webcam channel opens it, in the chosen format or
+//! at its highest resolution, so the preview shows the field of view that
+//! gets recorded.
+//! Only one preview runs at a time, and it's stopped before a recording
+//! opens the camera itself.
+
+use crate::capture::camera_format::requested_format;
+use crate::capture::traits::CameraFormat;
+use crate::recorder::channel::RecordingError;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::{self, FilterType};
+use image::{ExtendedColorType, RgbImage};
+use nokhwa::pixel_format::RgbFormat;
+use nokhwa::utils::{CameraIndex, RequestedFormat};
+use nokhwa::Camera;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Width preview frames are scaled down to (never up)
+pub const PREVIEW_MAX_WIDTH: u32 = 640;
+
+/// Frames sent per second; the camera's other frames are dropped
+const PREVIEW_FPS: u32 = 15;
+
+/// JPEG quality of preview frames (1-100)
+const PREVIEW_JPEG_QUALITY: u8 = 70;
+
+/// Pause after a failed frame read, so a camera that's gone isn't polled in
+/// a busy loop
+const FRAME_ERROR_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Failed frame reads in a row after which the preview gives up on the
+/// camera (about 3 seconds with the backoff)
+const MAX_FRAME_ERRORS: u32 = 60;
+
+/// Camera to open for a device ID from `get_cameras` (the first camera if
+/// none is given)
+pub fn camera_index(device_id: Option<&str>) -> CameraIndex {
+    match device_id {
+        Some(id) => match id.parse::<u32>() {
+            Ok(idx) => CameraIndex::Index(idx),
+            Err(_) => CameraIndex::String(id.to_string()),
+        },
+        None => CameraIndex::Index(0),
+    }
+}
+
+/// Size of a preview frame for a camera frame of `width` x `height`, keeping
+/// the aspect ratio. Both sides stay even for the JPEG's chroma subsampling.
+pub fn preview_size(width: u32, height: u32) -> (u32, u32) {
+    if width <= PREVIEW_MAX_WIDTH {
+        return (width, height);
+    }
+    let scaled = (height as f64 * PREVIEW_MAX_WIDTH as f64 / width as f64).round() as u32;
+    (PREVIEW_MAX_WIDTH, (scaled & !1).max(2))
+}
+
+/// Downscale an RGB frame and encode it as JPEG
+fn encode_frame(frame: &RgbImage) -> Result<Vec<u8>, image::ImageError> {
+    let (width, height) = preview_size(frame.width(), frame.height());
+    let scaled;
+    let frame = if (width, height) == frame.dimensions() {
+        frame
+    } else {
+        scaled = imageops::resize(frame, width, height, FilterType::Triangle);
+        &scaled
+    };
+
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, PREVIEW_JPEG_QUALITY).encode(
+        frame.as_raw(),
+        width,
+        height,
+        ExtendedColorType::Rgb8,
+    )?;
+    Ok(jpeg)
+}
+
+/// A running camera preview; dropping it closes the camera
+pub struct CameraPreview {
+    device_id: Option<String>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CameraPreview {
+    /// Open the camera in `format` (its highest resolution if None) and call
+    /// `on_frame` with each JPEG frame on a background thread, until stopped,
+    /// `on_frame` returns false or the camera keeps failing to deliver frames.
+    /// Returns once the camera's stream is open.
+    pub fn start<F>(
+        device_id: Option<String>,
+        format: Option<&CameraFormat>,
+        mut on_frame: F,
+    ) -> Result<Self, RecordingError>
+    where
+        F: FnMut(Vec<u8>) -> bool + Send + 'static,
+    {
+        let index = camera_index(device_id.as_deref());
+        let requested = requested_format(format);
+        let running = Arc::new(AtomicBool::new(true));
+        let (opened_tx, opened_rx) = mpsc::channel();
+
+        let thread_running = running.clone();
+        let thread = std::thread::spawn(move || {
+            let format = RequestedFormat::new::<RgbFormat>(requested);
+            let mut camera = match Camera::new(index.clone(), format)
+                .and_then(|mut camera| camera.open_stream().map(|_| camera))
+            {
+                Ok(camera) => camera,
+                Err(e) => {
+                    let _ = opened_tx.send(Err(RecordingError::DeviceNotFound(format!(
+                        "Failed to open camera {:?}: {}",
+                        index, e
+                    ))));
+                    return;
+                }
+            };
+            let _ = opened_tx.send(Ok(()));
+
+            let interval = Duration::from_secs(1) / PREVIEW_FPS;
+            let mut last_sent: Option<Instant> = None;
+            let mut frame_errors = 0;
+            while thread_running.load(Ordering::SeqCst) {
+                // Blocks until the camera delivers the next frame
+                let buffer = match camera.frame() {
+                    Ok(buffer) => buffer,
+                    Err(e) => {
+                        frame_errors += 1;
+                        if frame_errors >= MAX_FRAME_ERRORS {
+                            tracing::warn!("Camera stopped delivering preview frames: {:?}", e);
+                            break;
+                        }
+                        tracing::debug!("Failed to capture preview frame: {:?}", e);
+                        std::thread::sleep(FRAME_ERROR_BACKOFF);
+                        continue;
+                    }
+                };
+                frame_errors = 0;
+                if last_sent.is_some_and(|sent| sent.elapsed() < interval) {
+                    continue;
+                }
+                last_sent = Some(Instant::now());
+
+                let jpeg = buffer
+                    .decode_image::<RgbFormat>()
+                    .map_err(|e| e.to_string())
+                    .and_then(|decoded| {
+                        let (width, height) = (decoded.width(), decoded.height());
+                        RgbImage::from_raw(width, height, decoded.into_raw())
+                            .ok_or_else(|| "frame buffer too small".to_string())
+                    })
+                    .and_then(|frame| encode_frame(&frame).map_err(|e| e.to_string()));
+                match jpeg {
+                    Ok(jpeg) => {
+                        if !on_frame(jpeg) {
+                            break;
+                        }
+                    }
+                    Err(e) => tracing::debug!("Failed to encode preview frame: {}", e),
+                }
+            }
+
+            if let Err(e) = camera.stop_stream() {
+                tracing::warn!("Error stopping camera preview stream: {:?}", e);
+            }
+            tracing::info!("Camera preview stopped");
+        });
+
+        match opened_rx.recv() {
+            Ok(Ok(())) => {
+                tracing::info!("Camera preview started ({:?})", device_id);
+                Ok(Self {
+                    device_id,
+                    running,
+                    thread: Some(thread),
+                })
+            }
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
+            }
+            Err(_) => Err(RecordingError::DeviceNotFound(
+                "Camera preview thread exited".to_string(),
+            )),
+        }
+    }
+
+    /// Camera being previewed (`None` for the default camera)
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    /// Close the camera, waiting until it's released
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for CameraPreview {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_size_and_encoding() {
+        assert_eq!(preview_size(1920, 1080), (640, 360));
+        assert_eq!(preview_size(1280, 720), (640, 360));
+        // Odd heights are rounded down to even
+        assert_eq!(preview_size(1000, 567), (640, 362));
+        assert_eq!(preview_size(640, 480), (640, 480));
+        assert_eq!(preview_size(320, 240), (320, 240));
+
+        let jpeg = encode_frame(&RgbImage::new(1280, 720)).unwrap();
+        assert_eq!(&jpeg[..2], &[0xFF, 0xD8]);
+        let decoded = image::load_from_memory(&jpeg).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (640, 360));
+
+        assert!(matches!(camera_index(None), CameraIndex::Index(0)));
+        assert!(matches!(camera_index(Some("2")), CameraIndex::Index(2)));
+        assert!(matches!(
+            camera_index(Some("FaceTime")),
+            CameraIndex::String(_)
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+//! Recording channels available on this platform
+//!
+//! Registers a factory per capture source with the channel registry. Each
+//! factory decides from the recording configuration whether its channel is
+//! needed; camera-only and audio-only recordings get no display, input or
+//! annotation channels.
+
+use crate::capture::annotation::AnnotationChannel;
+use crate::recorder::channel::{ChannelType, RecordingChannel};
+use crate::recorder::registry::{ChannelContext, ChannelRegistry};
+
+/// Build the registry of channels this platform can record
+pub fn platform_registry() -> ChannelRegistry {
+    let mut registry = ChannelRegistry::new();
+
+    // Display capture, or a single window via ScreenCaptureKit
+    #[cfg(target_os = "macos")]
+    registry.register(ChannelType::Display, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        if !config.capture_screen {
+            return None;
+        }
+        let channel: Box<dyn RecordingChannel> = match config.window_id {
+            Some(window_id) => Box::new(
+                crate::capture::macos::window::WindowCaptureChannel::new(window_id)
+                    .display(config.display_id)
+                    .video_encoding(config.video_encoding)
+                    .quality(config.quality)
+                    .show_cursor(config.show_system_cursor),
+            ),
+            None => Box::new(
+                crate::capture::macos::screen::DisplayCaptureChannel::new(config.display_id)
+                    .follow_cursor_on_resume(config.follow_cursor_on_resume)
+                    .region(config.region)
+                    .video_encoding(config.video_encoding)
+                    .quality(config.quality)
+                    .max_fps(config.max_fps)
+                    .show_cursor(config.show_system_cursor)
+                    .exclude_app_windows(config.exclude_app_windows),
+            ),
+        };
+        Some(channel)
+    });
+
+    #[cfg(target_os = "windows")]
+    registry.register(ChannelType::Display, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        config.capture_screen.then(|| {
+            Box::new(
+                crate::capture::windows::screen::DisplayCaptureChannel::new(config.display_id)
+                    .follow_cursor_on_resume(config.follow_cursor_on_resume)
+                    .region(config.region)
+                    .video_encoding(config.video_encoding)
+                    .quality(config.quality)
+                    .max_fps(config.max_fps)
+                    .show_cursor(config.show_system_cursor),
+            ) as Box<dyn RecordingChannel>
+        })
+    });
+
+    #[cfg(target_os = "linux")]
+    registry.register(ChannelType::Display, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        config.capture_screen.then(|| {
+            Box::new(
+                crate::capture::linux::screen::DisplayCaptureChannel::new(config.display_id)
+                    .follow_cursor_on_resume(config.follow_cursor_on_resume)
+                    .region(config.region)
+                    .video_encoding(config.video_encoding)
+                    .quality(config.quality)
+                    .max_fps(config.max_fps)
+                    // Without an input track, export has no cursor to draw
+                    .show_cursor(true),
+            ) as Box<dyn RecordingChannel>
+        })
+    });
+
+    // Input tracking (needs Accessibility permission on macOS)
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    registry.register(ChannelType::Input, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        (config.capture_screen && config.track_input).then(|| {
+            Box::new(
+                crate::capture::InputTrackingChannel::new(config.display_id)
+                    .follow_cursor_on_resume(config.follow_cursor_on_resume),
+            ) as Box<dyn RecordingChannel>
+        })
+    });
+
+    // Key presses, for keystroke overlays (needs Accessibility permission on
+    // macOS)
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    registry.register(ChannelType::Keyboard, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        (config.capture_screen && config.track_keystrokes).then(|| {
+            Box::new(crate::capture::KeyboardTrackingChannel::new(
+                config.keystroke_mode,
+                config.mask_secure_input,
+            )) as Box<dyn RecordingChannel>
+        })
+    });
+
+    // Frontmost application over time
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    registry.register(ChannelType::AppActivity, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        (config.capture_screen && config.track_app_activity).then(|| {
+            Box::new(crate::capture::AppActivityChannel::new(
+                config.record_window_titles,
+            )) as Box<dyn RecordingChannel>
+        })
+    });
+
+    // Annotations (receives strokes from the drawing overlay)
+    registry.register(ChannelType::Annotation, |ctx: &ChannelContext| {
+        let annotations = ctx.annotations.filter(|_| ctx.config.capture_screen)?;
+        Some(Box::new(AnnotationChannel::new(annotations.clone())))
+    });
+
+    registry.register(ChannelType::Microphone, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        config.capture_microphone.then(|| {
+            Box::new(crate::capture::audio::MicrophoneCaptureChannel::new(
+                config.microphone_device_id.clone(),
+                config.microphone_encoding,
+            )) as Box<dyn RecordingChannel>
+        })
+    });
+
+    #[cfg(target_os = "macos")]
+    registry.register(ChannelType::SystemAudio, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        config.capture_system_audio.then(|| {
+            Box::new(
+                crate::capture::macos::system_audio::SystemAudioCaptureChannel::new(
+                    config.display_id,
+                    config.system_audio_encoding,
+                    config.audio_app_filter.clone(),
+                ),
+            ) as Box<dyn RecordingChannel>
+        })
+    });
+
+    #[cfg(target_os = "windows")]
+    registry.register(ChannelType::SystemAudio, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        config.capture_system_audio.then(|| {
+            Box::new(
+                crate::capture::windows::system_audio::SystemAudioCaptureChannel::new(
+                    config.system_audio_device_id.clone(),
+                    config.system_audio_encoding,
+                    config.audio_app_filter.clone(),
+                ),
+            ) as Box<dyn RecordingChannel>
+        })
+    });
+
+    #[cfg(target_os = "linux")]
+    registry.register(ChannelType::SystemAudio, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        if config.capture_system_audio && config.audio_app_filter.is_some() {
+            tracing::warn!("Per-app audio capture isn't supported on Linux, recording all system audio");
+        }
+        config.capture_system_audio.then(|| {
+            Box::new(
+                crate::capture::linux::system_audio::SystemAudioCaptureChannel::new(
+                    config.system_audio_encoding,
+                ),
+            ) as Box<dyn RecordingChannel>
+        })
+    });
+
+    // Default to 1280x720 @ 30fps for webcam, unless a format was chosen
+    #[cfg(target_os = "macos")]
+    registry.register(ChannelType::Webcam, |ctx: &ChannelContext| {
+        let config = ctx.config;
+        config.capture_webcam.then(|| {
+            Box::new(
+                crate::capture::macos::webcam::WebcamCaptureChannel::new(
+                    config.webcam_device_id.clone(),
+                    1280,
+                    720,
+                    30,
+                )
+                .format(config.webcam_format.clone())
+                .video_encoding(config.video_encoding)
+                .quality(config.quality),
+            ) as Box<dyn RecordingChannel>
+        })
+    });
+
+    // TODO: Implement Windows webcam capture
+    #[cfg(target_os = "windows")]
+    registry.register(ChannelType::Webcam, |ctx: &ChannelContext| {
+        if ctx.config.capture_webcam {
+            tracing::warn!("Webcam capture not yet implemented on Windows");
+        }
+        None
+    });
+
+    registry
+}
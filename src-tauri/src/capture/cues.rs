@@ -0,0 +1,116 @@
+//! Playback of recording cues
+//!
+//! Plays the synthesized cue tones on the default output device with cpal,
+//! and triggers trackpad haptics on macOS.
+
+use crate::recorder::cues::{synthesize, Cue, CueConfig, CuePlayer};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{SampleFormat, StreamConfig};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Extra time given to the device to drain its buffer after the last sample
+const PLAYBACK_TAIL: Duration = Duration::from_millis(60);
+
+/// Plays cues on the system's default output device
+#[derive(Debug, Default)]
+pub struct SystemCuePlayer;
+
+impl CuePlayer for SystemCuePlayer {
+    fn play(&self, cue: Cue, config: &CueConfig) {
+        if config.haptics {
+            perform_haptic();
+        }
+        if config.sounds {
+            if let Err(e) = play_sound(cue) {
+                tracing::warn!("Failed to play {:?} cue: {}", cue, e);
+            }
+        }
+    }
+}
+
+/// Play a cue and block until it has finished
+fn play_sound(cue: Cue) -> Result<(), String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| "No output device".to_string())?;
+    let supported = device.default_output_config().map_err(|e| e.to_string())?;
+    let sample_format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+
+    let samples = synthesize(cue, config.sample_rate.0, config.channels);
+    let duration = Duration::from_secs_f64(
+        samples.len() as f64 / config.channels.max(1) as f64 / config.sample_rate.0 as f64,
+    );
+    let (done_tx, done_rx) = mpsc::channel();
+
+    let stream = match sample_format {
+        SampleFormat::F32 => build_cue_stream(&device, &config, samples, done_tx, |s| s),
+        SampleFormat::I16 => build_cue_stream(&device, &config, samples, done_tx, |s| {
+            (s * i16::MAX as f32) as i16
+        }),
+        other => return Err(format!("Unsupported output sample format: {:?}", other)),
+    }
+    .map_err(|e| e.to_string())?;
+    stream.play().map_err(|e| e.to_string())?;
+
+    // Wait for the callback to run out of samples, with a timeout in case the
+    // device stops calling back
+    let _ = done_rx.recv_timeout(duration + Duration::from_secs(1));
+    std::thread::sleep(PLAYBACK_TAIL);
+    Ok(())
+}
+
+fn build_cue_stream<T: cpal::SizedSample + Default>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    samples: Vec<f32>,
+    done_tx: mpsc::Sender<()>,
+    from_f32: fn(f32) -> T,
+) -> Result<cpal::Stream, cpal::BuildStreamError> {
+    let mut position = 0;
+
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            for out in data.iter_mut() {
+                *out = samples
+                    .get(position)
+                    .map(|&s| from_f32(s))
+                    .unwrap_or_default();
+                position += 1;
+            }
+            if position >= samples.len() {
+                let _ = done_tx.send(());
+            }
+        },
+        |err| tracing::warn!("Cue playback error: {}", err),
+        None,
+    )
+}
+
+/// Trigger a generic trackpad haptic (Force Touch trackpads only)
+#[cfg(target_os = "macos")]
+fn perform_haptic() {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    // NSHapticFeedbackPatternGeneric, NSHapticFeedbackPerformanceTimeNow
+    const PATTERN_GENERIC: isize = 0;
+    const PERFORMANCE_TIME_NOW: usize = 1;
+
+    unsafe {
+        let performer: *mut AnyObject =
+            msg_send![class!(NSHapticFeedbackManager), defaultPerformer];
+        if !performer.is_null() {
+            let _: () = msg_send![
+                performer,
+                performFeedbackPattern: PATTERN_GENERIC,
+                performanceTime: PERFORMANCE_TIME_NOW
+            ];
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn perform_haptic() {}
@@ -0,0 +1,145 @@
+//! Display configuration watching
+//!
+//! A display can change resolution or be unplugged in the middle of a
+//! recording, and the capture APIs don't reliably say so: frames start
+//! coming back at the wrong size, scaled, or not at all. Display channels run
+//! a `DisplayWatcher` while recording, which polls the display's size and
+//! reports the first change as a `RecordingEvent`. The coordinator's
+//! `watch_display_changes` task reacts to it.
+
+use crate::recorder::coordinator::RecordingEvent;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// How often the display is checked
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the watcher checks whether it should exit
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A change to the recorded display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayChange {
+    Resized { width: u32, height: u32 },
+    Disconnected,
+}
+
+/// Compare the display's size when capture started with its current one
+/// (None once the display is gone)
+pub fn detect_change(initial: (u32, u32), current: Option<(u32, u32)>) -> Option<DisplayChange> {
+    match current {
+        None => Some(DisplayChange::Disconnected),
+        Some(size) if size == initial => None,
+        Some((width, height)) => Some(DisplayChange::Resized { width, height }),
+    }
+}
+
+/// Polls a display in the background while a channel records
+pub struct DisplayWatcher {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DisplayWatcher {
+    /// Watch `display_id` using `probe` (its pixel size, None once it's gone)
+    /// and report the first change for `channel` on `event_tx`. Returns None
+    /// if the display can't be probed to begin with.
+    pub fn start(
+        channel: &str,
+        display_id: u32,
+        probe: fn(u32) -> Option<(u32, u32)>,
+        event_tx: broadcast::Sender<RecordingEvent>,
+    ) -> Option<Self> {
+        let initial = probe(display_id)?;
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let channel = channel.to_string();
+
+        let handle = std::thread::spawn(move || {
+            let mut last_check = Instant::now();
+            while thread_running.load(Ordering::Relaxed) {
+                std::thread::sleep(STOP_POLL_INTERVAL);
+                if last_check.elapsed() < WATCH_INTERVAL {
+                    continue;
+                }
+                last_check = Instant::now();
+
+                let Some(change) = detect_change(initial, probe(display_id)) else {
+                    continue;
+                };
+                let display = display_id.to_string();
+                let event = match change {
+                    DisplayChange::Resized { width, height } => {
+                        tracing::warn!(
+                            "Display {} changed from {}x{} to {}x{}",
+                            display_id,
+                            initial.0,
+                            initial.1,
+                            width,
+                            height
+                        );
+                        RecordingEvent::DisplayChanged {
+                            channel,
+                            display,
+                            width,
+                            height,
+                        }
+                    }
+                    DisplayChange::Disconnected => {
+                        tracing::warn!("Display {} was disconnected", display_id);
+                        RecordingEvent::DisplayDisconnected { channel, display }
+                    }
+                };
+                let _ = event_tx.send(event);
+                break;
+            }
+        });
+
+        Some(Self {
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// Stop watching
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DisplayWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_change() {
+        assert_eq!(detect_change((2560, 1440), Some((2560, 1440))), None);
+        assert_eq!(
+            detect_change((2560, 1440), Some((1920, 1080))),
+            Some(DisplayChange::Resized {
+                width: 1920,
+                height: 1080
+            })
+        );
+        assert_eq!(
+            detect_change((2560, 1440), None),
+            Some(DisplayChange::Disconnected)
+        );
+    }
+}
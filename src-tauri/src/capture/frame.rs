@@ -0,0 +1,401 @@
+//! Frame geometry helpers
+//!
+//! Window capture produces frames whose size follows the window, while the
+//! encoder needs a fixed canvas. These helpers letterbox variable-size frames
+//! into a canvas and record where the content landed, so export can crop it
+//! back out and re-fit it dynamically. Region capture crops a fixed
+//! rectangle out of each display frame before it reaches the encoder.
+//! Rotated (portrait) displays are captured as shown on screen; `Rotation`
+//! describes how a display, or a video's frames, are turned.
+
+use serde::{Deserialize, Serialize};
+
+/// Region of a canvas covered by captured content, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Clockwise rotation in quarter turns, serialized as degrees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(into = "u32", from = "u32")]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+impl Rotation {
+    /// Nearest quarter turn to a clockwise angle in degrees (may be negative)
+    pub fn from_degrees(degrees: f64) -> Self {
+        match ((degrees / 90.0).round() as i64).rem_euclid(4) {
+            1 => Self::Cw90,
+            2 => Self::Cw180,
+            3 => Self::Cw270,
+            _ => Self::None,
+        }
+    }
+
+    pub fn degrees(self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Cw90 => 90,
+            Self::Cw180 => 180,
+            Self::Cw270 => 270,
+        }
+    }
+
+    /// Size of a `width`x`height` frame once rotated
+    pub fn rotated_size(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Self::Cw90 | Self::Cw270 => (height, width),
+            Self::None | Self::Cw180 => (width, height),
+        }
+    }
+
+    /// FFmpeg filter applying the rotation, if any
+    pub fn ffmpeg_filter(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Cw90 => Some("transpose=clock"),
+            Self::Cw180 => Some("hflip,vflip"),
+            Self::Cw270 => Some("transpose=cclock"),
+        }
+    }
+}
+
+impl From<u32> for Rotation {
+    fn from(degrees: u32) -> Self {
+        Self::from_degrees(degrees as f64)
+    }
+}
+
+impl From<Rotation> for u32 {
+    fn from(rotation: Rotation) -> Self {
+        rotation.degrees()
+    }
+}
+
+/// Emitted whenever the captured window changes size or moves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowResizeEvent {
+    /// Session time the new size took effect
+    pub process_time_ms: f64,
+    /// Window size in pixels
+    pub source_width: u32,
+    pub source_height: u32,
+    /// Where the window content sits in the recorded frame
+    pub content: ContentRect,
+    /// Top-left of the window on the display mouse positions were recorded
+    /// on, in display pixels. Absent in older recordings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_origin: Option<(f64, f64)>,
+}
+
+/// Largest centered rect with the source aspect ratio that fits the canvas
+pub fn letterbox_rect(src_width: u32, src_height: u32, canvas_width: u32, canvas_height: u32) -> ContentRect {
+    if src_width == 0 || src_height == 0 {
+        return ContentRect {
+            x: 0,
+            y: 0,
+            width: canvas_width,
+            height: canvas_height,
+        };
+    }
+
+    let scale = (canvas_width as f64 / src_width as f64).min(canvas_height as f64 / src_height as f64);
+    let width = ((src_width as f64 * scale).round() as u32).clamp(1, canvas_width);
+    let height = ((src_height as f64 * scale).round() as u32).clamp(1, canvas_height);
+
+    ContentRect {
+        x: (canvas_width - width) / 2,
+        y: (canvas_height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Scale a 4-byte-per-pixel frame into a letterboxed canvas (nearest neighbour).
+///
+/// `src_stride` is the source row length in bytes. Pixels outside the content
+/// rect are cleared to opaque black. Returns the content rect.
+pub fn letterbox_into(
+    src: &[u8],
+    src_width: u32,
+    src_height: u32,
+    src_stride: usize,
+    canvas: &mut [u8],
+    canvas_width: u32,
+    canvas_height: u32,
+) -> ContentRect {
+    let rect = letterbox_rect(src_width, src_height, canvas_width, canvas_height);
+
+    for px in canvas.chunks_exact_mut(4) {
+        px.copy_from_slice(&[0, 0, 0, 255]);
+    }
+    if src_width == 0 || src_height == 0 {
+        return rect;
+    }
+
+    for y in 0..rect.height {
+        let sy = (y as u64 * src_height as u64 / rect.height as u64) as usize;
+        let dst_row = ((rect.y + y) * canvas_width + rect.x) as usize * 4;
+        for x in 0..rect.width {
+            let sx = (x as u64 * src_width as u64 / rect.width as u64) as usize;
+            let s = sy * src_stride + sx * 4;
+            let d = dst_row + x as usize * 4;
+            if s + 4 <= src.len() && d + 4 <= canvas.len() {
+                canvas[d..d + 4].copy_from_slice(&src[s..s + 4]);
+            }
+        }
+    }
+
+    rect
+}
+
+/// Resize event in effect at a session time
+fn event_at(events: &[WindowResizeEvent], time_ms: f64) -> Option<&WindowResizeEvent> {
+    events
+        .iter()
+        .take_while(|e| e.process_time_ms <= time_ms)
+        .last()
+        .or(events.first())
+}
+
+/// Content rect in effect at a session time
+pub fn content_rect_at(events: &[WindowResizeEvent], time_ms: f64) -> Option<ContentRect> {
+    event_at(events, time_ms).map(|e| e.content)
+}
+
+/// Bring a position recorded in display pixels onto a window capture's
+/// frame: made relative to the window's origin at `time_ms`, then scaled
+/// into where the window content sits. Unchanged if the origin isn't known.
+pub fn window_point(events: &[WindowResizeEvent], time_ms: f64, x: f64, y: f64) -> (f64, f64) {
+    let Some(event) = event_at(events, time_ms) else {
+        return (x, y);
+    };
+    let Some((origin_x, origin_y)) = event.window_origin else {
+        return (x, y);
+    };
+    let scale = |content: u32, source: u32| {
+        if source == 0 {
+            1.0
+        } else {
+            content as f64 / source as f64
+        }
+    };
+    (
+        event.content.x as f64 + (x - origin_x) * scale(event.content.width, event.source_width),
+        event.content.y as f64 + (y - origin_y) * scale(event.content.height, event.source_height),
+    )
+}
+
+/// Crop `rect` out of a frame and re-fit it to fill as much of the frame as
+/// its aspect ratio allows. The frame is modified in place, and left alone
+/// if the content already fills it that way (window content letterboxed at
+/// its captured size).
+pub fn refit_content(frame: &mut [u8], width: u32, height: u32, rect: ContentRect) {
+    if rect.width == 0 || rect.height == 0 {
+        return;
+    }
+    if letterbox_rect(rect.width, rect.height, width, height) == rect {
+        return;
+    }
+
+    let stride = width as usize * 4;
+    let Some(cropped) = crop_frame(frame, stride, rect) else {
+        return;
+    };
+    letterbox_into(
+        &cropped,
+        rect.width,
+        rect.height,
+        rect.width as usize * 4,
+        frame,
+        width,
+        height,
+    );
+}
+
+/// Map a capture region given in display coordinates (as reported by
+/// `get_displays`) onto a captured frame, which may be at a higher pixel
+/// density. The result is clamped to the frame and has even dimensions, as
+/// the H.264 encoder requires. None if nothing of the region is on the frame.
+pub fn region_in_frame(
+    region: ContentRect,
+    display_width: u32,
+    display_height: u32,
+    frame_width: u32,
+    frame_height: u32,
+) -> Option<ContentRect> {
+    let scale = |frame: u32, display: u32| if display == 0 { 1.0 } else { frame as f64 / display as f64 };
+    let (sx, sy) = (scale(frame_width, display_width), scale(frame_height, display_height));
+
+    let x0 = ((region.x as f64 * sx).round() as u32).min(frame_width);
+    let y0 = ((region.y as f64 * sy).round() as u32).min(frame_height);
+    let x1 = ((region.x.saturating_add(region.width) as f64 * sx).round() as u32).min(frame_width);
+    let y1 = ((region.y.saturating_add(region.height) as f64 * sy).round() as u32).min(frame_height);
+
+    let width = x1.saturating_sub(x0) & !1;
+    let height = y1.saturating_sub(y0) & !1;
+    (width > 0 && height > 0).then_some(ContentRect { x: x0, y: y0, width, height })
+}
+
+/// Copy `rect` out of a 4-byte-per-pixel frame into a tightly packed buffer.
+/// `src_stride` is the source row length in bytes. None if the frame is too
+/// small to contain the rect.
+pub fn crop_frame(src: &[u8], src_stride: usize, rect: ContentRect) -> Option<Vec<u8>> {
+    if (rect.x + rect.width) as usize * 4 > src_stride {
+        return None;
+    }
+    let row_len = rect.width as usize * 4;
+    let mut cropped = Vec::with_capacity(row_len * rect.height as usize);
+    for y in rect.y..rect.y + rect.height {
+        let start = y as usize * src_stride + rect.x as usize * 4;
+        cropped.extend_from_slice(src.get(start..start + row_len)?);
+    }
+    Some(cropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letterbox_rect_pillarbox_and_letterbox() {
+        // Tall window in a wide canvas: bars on the sides
+        assert_eq!(
+            letterbox_rect(500, 1000, 1920, 1000),
+            ContentRect {
+                x: 710,
+                y: 0,
+                width: 500,
+                height: 1000
+            }
+        );
+        // Wide window in a tall canvas: bars top and bottom
+        assert_eq!(
+            letterbox_rect(200, 100, 100, 100),
+            ContentRect {
+                x: 0,
+                y: 25,
+                width: 100,
+                height: 50
+            }
+        );
+    }
+
+    #[test]
+    fn test_letterbox_into_scales_and_clears() {
+        // 1x1 white source into a 4x2 canvas: 2x2 content centered
+        let src = [255u8; 4];
+        let mut canvas = vec![7u8; 4 * 2 * 4];
+        let rect = letterbox_into(&src, 1, 1, 4, &mut canvas, 4, 2);
+
+        assert_eq!(rect, ContentRect { x: 1, y: 0, width: 2, height: 2 });
+        assert_eq!(&canvas[0..4], &[0, 0, 0, 255]);
+        assert_eq!(&canvas[4..8], &[255, 255, 255, 255]);
+        assert_eq!(&canvas[12..16], &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_rotation_from_degrees_and_size() {
+        assert_eq!(Rotation::from_degrees(-90.0), Rotation::Cw270);
+        assert_eq!(Rotation::from_degrees(450.0), Rotation::Cw90);
+        assert_eq!(Rotation::from_degrees(179.9), Rotation::Cw180);
+        assert_eq!(Rotation::Cw90.rotated_size(1920, 1080), (1080, 1920));
+        assert_eq!(Rotation::Cw180.rotated_size(1920, 1080), (1920, 1080));
+        assert_eq!(serde_json::to_value(Rotation::Cw270).unwrap(), serde_json::json!(270));
+        assert_eq!(serde_json::from_value::<Rotation>(serde_json::json!(90)).unwrap(), Rotation::Cw90);
+    }
+
+    #[test]
+    fn test_content_rect_at_picks_latest_event() {
+        let event = |t: f64, w: u32| WindowResizeEvent {
+            process_time_ms: t,
+            source_width: w,
+            source_height: 10,
+            content: ContentRect { x: 0, y: 0, width: w, height: 10 },
+            window_origin: None,
+        };
+        let events = vec![event(0.0, 10), event(500.0, 20)];
+
+        assert_eq!(content_rect_at(&events, 100.0).map(|r| r.width), Some(10));
+        assert_eq!(content_rect_at(&events, 600.0).map(|r| r.width), Some(20));
+        assert_eq!(content_rect_at(&[], 0.0), None);
+    }
+
+    #[test]
+    fn test_window_point_follows_window_moves_and_resizes() {
+        // An 800x600 window at (100, 50), filling its canvas, then moved to
+        // (300, 50) and resized to 1600x600, letterboxed into 800x300
+        let events = vec![
+            WindowResizeEvent {
+                process_time_ms: 0.0,
+                source_width: 800,
+                source_height: 600,
+                content: ContentRect { x: 0, y: 0, width: 800, height: 600 },
+                window_origin: Some((100.0, 50.0)),
+            },
+            WindowResizeEvent {
+                process_time_ms: 1000.0,
+                source_width: 1600,
+                source_height: 600,
+                content: ContentRect { x: 0, y: 150, width: 800, height: 300 },
+                window_origin: Some((300.0, 50.0)),
+            },
+        ];
+        assert_eq!(window_point(&events, 500.0, 500.0, 350.0), (400.0, 300.0));
+        assert_eq!(window_point(&events, 1500.0, 500.0, 350.0), (100.0, 300.0));
+
+        // Recordings without an origin keep display positions
+        let mut old = events[0].clone();
+        old.window_origin = None;
+        assert_eq!(window_point(&[old], 0.0, 500.0, 350.0), (500.0, 350.0));
+    }
+
+    #[test]
+    fn test_refit_content_fills_frame() {
+        // 4x2 frame with a 2x2 white square centered; refit keeps it 2x2 (aspect 1:1)
+        let mut frame = vec![0u8; 4 * 2 * 4];
+        for x in 1..3 {
+            for y in 0..2 {
+                let i = (y * 4 + x) * 4;
+                frame[i..i + 4].copy_from_slice(&[255; 4]);
+            }
+        }
+        refit_content(&mut frame, 4, 2, ContentRect { x: 1, y: 0, width: 2, height: 2 });
+        assert_eq!(&frame[4..8], &[255; 4]);
+
+        // Wide content strip gets stretched to full width
+        let mut frame = vec![0u8; 4 * 4 * 4];
+        frame[20..28].copy_from_slice(&[9; 8]);
+        refit_content(&mut frame, 4, 4, ContentRect { x: 1, y: 1, width: 2, height: 1 });
+        // Content now spans rows 1-2 at full width
+        assert_eq!(&frame[16..20], &[9; 4]);
+        assert_eq!(&frame[44..48], &[9; 4]);
+        assert_eq!(&frame[0..4], &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_region_scales_to_frame_and_crops() {
+        // 100x50pt display captured at 2x; region partly off the right edge
+        let region = ContentRect { x: 10, y: 5, width: 100, height: 21 };
+        let rect = region_in_frame(region, 100, 50, 200, 100).unwrap();
+        assert_eq!(rect, ContentRect { x: 20, y: 10, width: 180, height: 42 });
+        assert_eq!(region_in_frame(ContentRect { x: 120, ..region }, 100, 50, 200, 100), None);
+
+        // 3x2 frame whose pixels hold their own index; crop the right 2x1
+        let frame: Vec<u8> = (0..6u8).flat_map(|i| [i; 4]).collect();
+        let cropped = crop_frame(&frame, 12, ContentRect { x: 1, y: 1, width: 2, height: 1 }).unwrap();
+        assert_eq!(cropped, vec![4, 4, 4, 4, 5, 5, 5, 5]);
+        assert_eq!(crop_frame(&frame, 12, ContentRect { x: 0, y: 1, width: 3, height: 2 }), None);
+    }
+}
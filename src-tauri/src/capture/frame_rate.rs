@@ -0,0 +1,166 @@
+//! Screen capture frame rate
+//!
+//! The screen is captured at the display's refresh rate so motion on a
+//! 60Hz or 120Hz display isn't halved, capped by `RecordingConfig::max_fps`
+//! and, for the Performance preset, at 30fps. Displays that don't report a
+//! refresh rate use the preset's frame rate. Capture can fall behind the
+//! requested rate on a busy machine, so channels that feed the encoder
+//! themselves measure the rate they actually encoded at and record it in the
+//! manifest for export. Encoders are fed at a constant frame rate, so a
+//! channel that falls behind repeats its last frame over the gap rather
+//! than letting the video run short of the audio.
+
+use crate::recorder::state::CaptureQuality;
+use std::time::{Duration, Instant};
+
+/// Default `RecordingConfig::max_fps`
+pub const DEFAULT_MAX_FPS: u32 = 60;
+
+/// Highest frame rate the Performance preset captures at
+const PERFORMANCE_MAX_FPS: u32 = 30;
+
+/// Frame rate to capture a display at
+pub fn display_fps(quality: CaptureQuality, refresh_rate: Option<u32>, max_fps: u32) -> u32 {
+    let cap = match quality {
+        CaptureQuality::Performance => max_fps.min(PERFORMANCE_MAX_FPS),
+        CaptureQuality::Balanced | CaptureQuality::MaxQuality => max_fps,
+    };
+    refresh_rate
+        .filter(|&hz| hz > 0)
+        .unwrap_or_else(|| quality.fps())
+        .min(cap)
+        .max(1)
+}
+
+/// Measures the frame rate a channel encoded at across its sessions
+///
+/// Paused time isn't counted, so the rate covers only the recorded sessions.
+#[derive(Debug, Default)]
+pub struct FrameRateMeter {
+    frames: u64,
+    /// Recorded time in finished sessions
+    active: Duration,
+    /// Start of the current session
+    since: Option<Instant>,
+}
+
+impl FrameRateMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start timing a session
+    pub fn start(&mut self) {
+        self.start_at(Instant::now());
+    }
+
+    /// End the session, which encoded `frames` frames
+    pub fn stop(&mut self, frames: u64) {
+        self.stop_at(Instant::now(), frames);
+    }
+
+    /// Frames per second over the finished sessions, once there's enough
+    /// recorded to tell
+    pub fn achieved_fps(&self) -> Option<f64> {
+        let secs = self.active.as_secs_f64();
+        (secs >= 1.0 && self.frames > 0).then(|| self.frames as f64 / secs)
+    }
+
+    fn start_at(&mut self, now: Instant) {
+        self.since.get_or_insert(now);
+    }
+
+    fn stop_at(&mut self, now: Instant, frames: u64) {
+        if let Some(since) = self.since.take() {
+            self.active += now.saturating_duration_since(since);
+            self.frames += frames;
+        }
+    }
+}
+
+/// Wall clock a constant frame rate encoder has to keep up with
+#[derive(Debug, Clone, Copy)]
+pub struct FrameClock {
+    started: Instant,
+    fps: u32,
+}
+
+impl FrameClock {
+    /// Start the clock as the first frame is encoded
+    pub fn new(fps: u32) -> Self {
+        Self {
+            started: Instant::now(),
+            fps: fps.max(1),
+        }
+    }
+
+    /// Frames that should have been encoded by now
+    pub fn frames_due(&self) -> u64 {
+        self.frames_due_at(Instant::now())
+    }
+
+    /// Copies of the previous frame to encode before the next one, given
+    /// the frames encoded so far
+    pub fn repeats_before_next(&self, encoded: u64) -> u64 {
+        self.repeats_before_next_at(Instant::now(), encoded)
+    }
+
+    fn frames_due_at(&self, now: Instant) -> u64 {
+        let elapsed = now.saturating_duration_since(self.started);
+        (elapsed.as_secs_f64() * self.fps as f64) as u64
+    }
+
+    fn repeats_before_next_at(&self, now: Instant, encoded: u64) -> u64 {
+        self.frames_due_at(now).saturating_sub(1).saturating_sub(encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fps_follows_refresh_rate_up_to_cap() {
+        let balanced = CaptureQuality::Balanced;
+        assert_eq!(display_fps(balanced, Some(60), 60), 60);
+        assert_eq!(display_fps(balanced, Some(144), 60), 60);
+        assert_eq!(display_fps(balanced, Some(144), 120), 120);
+        assert_eq!(display_fps(balanced, Some(50), 60), 50);
+        // Unknown refresh rate falls back to the preset
+        assert_eq!(display_fps(balanced, None, 60), 30);
+        assert_eq!(display_fps(balanced, Some(0), 60), 30);
+        assert_eq!(display_fps(CaptureQuality::MaxQuality, None, 60), 60);
+        assert_eq!(display_fps(CaptureQuality::Performance, Some(120), 60), 30);
+
+        // Paused time doesn't count against the rate
+        let mut meter = FrameRateMeter::new();
+        let start = Instant::now();
+        meter.stop_at(start, 10);
+        assert_eq!(meter.achieved_fps(), None);
+        meter.start_at(start);
+        meter.stop_at(start + Duration::from_secs(1), 60);
+        assert_eq!(meter.achieved_fps(), Some(60.0));
+        meter.start_at(start + Duration::from_secs(5));
+        meter.stop_at(start + Duration::from_secs(7), 30);
+        assert_eq!(meter.achieved_fps(), Some(30.0));
+    }
+
+    #[test]
+    fn test_frame_clock_repeats_over_missed_frames() {
+        let clock = FrameClock::new(30);
+        let start = clock.started;
+        assert_eq!(clock.frames_due_at(start), 0);
+        assert_eq!(clock.repeats_before_next_at(start, 1), 0);
+        // On time: the next frame fills the next slot
+        let on_time = start + Duration::from_millis(40);
+        assert_eq!(clock.frames_due_at(on_time), 1);
+        assert_eq!(clock.repeats_before_next_at(on_time, 1), 0);
+        // Half a second late with two frames encoded: twelve repeats fill
+        // the slots before the frame that's about to be written
+        let late = start + Duration::from_millis(500);
+        assert_eq!(clock.frames_due_at(late), 15);
+        assert_eq!(clock.repeats_before_next_at(late, 2), 12);
+        // Ahead of the clock never repeats
+        assert_eq!(clock.repeats_before_next_at(late, 20), 0);
+    }
+}
@@ -1,5 +1,8 @@
 use crate::capture::input::types::{CursorInfo, MouseClick, MouseMove};
+use crate::capture::traits::{display_under_cursor, has_accessibility_permission};
+use crate::project::paths;
 use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::FileKind;
 use async_trait::async_trait;
 use parking_lot::Mutex as ParkingMutex;
 use std::collections::HashMap;
@@ -14,6 +17,29 @@ use crate::capture::macos::input as platform;
 #[cfg(target_os = "windows")]
 use crate::capture::windows::input as platform;
 
+/// No input tracking on other platforms yet; the channel isn't registered there
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::*;
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_input_tracking(
+        _is_recording: Arc<AtomicBool>,
+        _mouse_moves: Arc<ParkingMutex<Vec<MouseMove>>>,
+        _mouse_clicks: Arc<ParkingMutex<Vec<MouseClick>>>,
+        _cursors: Arc<ParkingMutex<HashMap<String, CursorInfo>>>,
+        _cursors_dir: PathBuf,
+        _start_time: Instant,
+        _poll_interval: Duration,
+        _unix_ms_fn: fn() -> u64,
+        _display_id: u32,
+    ) -> RecordingResult<std::thread::JoinHandle<()>> {
+        Err(RecordingError::PlatformError(
+            "Input tracking is not supported on this platform".to_string(),
+        ))
+    }
+}
+
 pub struct InputTrackingChannel {
     id: String,
     display_id: u32,
@@ -28,6 +54,9 @@ pub struct InputTrackingChannel {
 
     thread_handle: Arc<ParkingMutex<Option<std::thread::JoinHandle<()>>>>,
     start_time: Arc<ParkingMutex<Option<Instant>>>,
+
+    /// Re-pick the display under the cursor on resume (mirrors the display channel)
+    follow_cursor_on_resume: bool,
 }
 
 impl InputTrackingChannel {
@@ -44,9 +73,16 @@ impl InputTrackingChannel {
             cursors: Arc::new(ParkingMutex::new(HashMap::new())),
             thread_handle: Arc::new(ParkingMutex::new(None)),
             start_time: Arc::new(ParkingMutex::new(None)),
+            follow_cursor_on_resume: false,
         }
     }
 
+    /// Switch to the display under the cursor whenever recording resumes
+    pub fn follow_cursor_on_resume(mut self, follow: bool) -> Self {
+        self.follow_cursor_on_resume = follow;
+        self
+    }
+
     fn session_basename(&self) -> String {
         format!("recording-{}", self.session_index)
     }
@@ -74,9 +110,12 @@ impl InputTrackingChannel {
 
         let base = self.session_basename();
 
-        let mouse_moves_path = output_dir.join(format!("{}-mouse-moves.json", base));
-        let mouse_clicks_path = output_dir.join(format!("{}-mouse-clicks.json", base));
-        let cursors_json_path = output_dir.join(format!("{}-cursors.json", base));
+        let mouse_moves_path =
+            output_dir.join(FileKind::MouseMoves.conventional_name(self.session_index));
+        let mouse_clicks_path =
+            output_dir.join(FileKind::MouseClicks.conventional_name(self.session_index));
+        let cursors_json_path =
+            output_dir.join(FileKind::Cursors.conventional_name(self.session_index));
         let cursors_dir = output_dir.join(format!("{}-cursors", base));
 
         std::fs::create_dir_all(&cursors_dir)?;
@@ -84,7 +123,19 @@ impl InputTrackingChannel {
         // Write event JSON files
         Self::write_json(&mouse_moves_path, &*self.mouse_moves.lock())?;
         Self::write_json(&mouse_clicks_path, &*self.mouse_clicks.lock())?;
-        Self::write_json(&cursors_json_path, &*self.cursors.lock())?;
+        // Cursor images are captured with absolute paths; save them relative
+        // to the cursors directory so the bundle can be moved
+        let cursors: HashMap<String, CursorInfo> = self
+            .cursors
+            .lock()
+            .iter()
+            .map(|(id, info)| {
+                let mut info = info.clone();
+                info.image_path = paths::to_relative(&cursors_dir, Path::new(&info.image_path));
+                (id.clone(), info)
+            })
+            .collect();
+        Self::write_json(&cursors_json_path, &cursors)?;
 
         // Cursor PNGs are saved during capture (platform impl)
 
@@ -107,6 +158,16 @@ impl RecordingChannel for InputTrackingChannel {
     }
 
     async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        // Without it the event tap silently receives nothing
+        if !has_accessibility_permission() {
+            return Err(RecordingError::PermissionDenied(
+                "Accessibility permission is needed to track the mouse and keyboard. \
+                 Allow it in System Settings > Privacy & Security > Accessibility, \
+                 or turn off input tracking."
+                    .to_string(),
+            ));
+        }
+
         self.output_dir = Some(output_dir.to_path_buf());
         self.session_index = session_index;
 
@@ -127,11 +188,14 @@ impl RecordingChannel for InputTrackingChannel {
             RecordingError::ConfigurationError("Output directory not set".to_string())
         })?;
 
-        // Clear previous buffers
+        // Clear previous buffers. Files from earlier sessions of this
+        // recording are kept so every session ends up in the manifest.
         self.mouse_moves.lock().clear();
         self.mouse_clicks.lock().clear();
         self.cursors.lock().clear();
-        self.output_files.lock().clear();
+        if self.session_index == 0 {
+            self.output_files.lock().clear();
+        }
 
         let base = self.session_basename();
         let cursors_dir = output_dir.join(format!("{}-cursors", base));
@@ -193,6 +257,11 @@ impl RecordingChannel for InputTrackingChannel {
 
     async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
         self.session_index = session_index;
+        if self.follow_cursor_on_resume {
+            if let Some(display_id) = display_under_cursor() {
+                self.display_id = display_id;
+            }
+        }
         self.start().await
     }
 
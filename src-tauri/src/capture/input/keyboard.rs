@@ -0,0 +1,279 @@
+//! Keystroke tracking
+//!
+//! Records key presses while recording to `recording-{n}-keystrokes.json`,
+//! for keystroke overlays. Keys come from a listen-only CGEventTap on macOS
+//! and a low-level keyboard hook (`WH_KEYBOARD_LL`) on Windows, named the
+//! same way on both: modifiers as in `activeModifiers` ("shift", "control",
+//! "alt", "meta"), letters and digits as lowercase characters, and other keys
+//! by name ("return", "arrowLeft", "f5", ...).
+//!
+//! `KeystrokeMode::ShortcutsOnly` (the default) keeps only modifier keys and
+//! keys pressed with Cmd/Ctrl, so typed text never ends up in the bundle.
+//! Keys typed into password fields are masked in either mode when
+//! `RecordingConfig::mask_secure_input` is on.
+
+use crate::capture::input::privacy::{is_shortcut, KeystrokePrivacyFilter};
+use crate::capture::input::types::KeyPress;
+use crate::capture::traits::has_accessibility_permission;
+use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::FileKind;
+use crate::recorder::state::KeystrokeMode;
+use async_trait::async_trait;
+use parking_lot::Mutex as ParkingMutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(target_os = "macos")]
+use crate::capture::macos::input as platform;
+
+#[cfg(target_os = "windows")]
+use crate::capture::windows::input as platform;
+
+/// No keystroke tracking on other platforms yet; the channel isn't registered there
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::*;
+
+    pub fn start_keyboard_tracking(
+        _is_recording: Arc<AtomicBool>,
+        _keys: Arc<ParkingMutex<Vec<KeyPress>>>,
+        _recorder: KeystrokeRecorder,
+        _start_time: Instant,
+        _unix_ms_fn: fn() -> u64,
+    ) -> RecordingResult<std::thread::JoinHandle<()>> {
+        Err(RecordingError::PlatformError(
+            "Keystroke tracking is not supported on this platform".to_string(),
+        ))
+    }
+}
+
+/// Names of the modifier keys themselves
+const MODIFIER_KEYS: [&str; 6] = ["shift", "control", "alt", "meta", "capsLock", "fn"];
+
+/// Decides which key events are recorded, and how
+pub struct KeystrokeRecorder {
+    mode: KeystrokeMode,
+    privacy: KeystrokePrivacyFilter,
+}
+
+impl KeystrokeRecorder {
+    pub fn new(mode: KeystrokeMode, mask_secure_input: bool) -> Self {
+        Self {
+            mode,
+            privacy: KeystrokePrivacyFilter::new(mask_secure_input),
+        }
+    }
+
+    /// The event to store for `key`, if it's recorded at all
+    pub fn record(&self, key: KeyPress) -> Option<KeyPress> {
+        let keep = match self.mode {
+            KeystrokeMode::All => true,
+            KeystrokeMode::ShortcutsOnly => {
+                MODIFIER_KEYS.contains(&key.key.as_str()) || is_shortcut(&key)
+            }
+        };
+        keep.then(|| self.privacy.apply(key))
+    }
+}
+
+pub struct KeyboardTrackingChannel {
+    id: String,
+    mode: KeystrokeMode,
+    mask_secure_input: bool,
+    is_recording: Arc<AtomicBool>,
+    output_dir: Option<PathBuf>,
+    session_index: usize,
+    output_files: Arc<ParkingMutex<Vec<String>>>,
+    keys: Arc<ParkingMutex<Vec<KeyPress>>>,
+    thread_handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl KeyboardTrackingChannel {
+    pub fn new(mode: KeystrokeMode, mask_secure_input: bool) -> Self {
+        Self {
+            id: "keyboard".to_string(),
+            mode,
+            mask_secure_input,
+            is_recording: Arc::new(AtomicBool::new(false)),
+            output_dir: None,
+            session_index: 0,
+            output_files: Arc::new(ParkingMutex::new(Vec::new())),
+            keys: Arc::new(ParkingMutex::new(Vec::new())),
+            thread_handle: None,
+        }
+    }
+
+    fn now_unix_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+
+    fn flush_to_disk(&mut self) -> RecordingResult<()> {
+        let output_dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+
+        std::fs::create_dir_all(&output_dir)?;
+
+        let path = output_dir.join(FileKind::Keystrokes.conventional_name(self.session_index));
+        let data = serde_json::to_vec_pretty(&*self.keys.lock())
+            .map_err(|e| RecordingError::IoError(std::io::Error::other(e)))?;
+        std::fs::write(&path, data)?;
+
+        self.output_files
+            .lock()
+            .push(path.to_string_lossy().to_string());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordingChannel for KeyboardTrackingChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Keyboard
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        // Without it the event tap silently receives nothing
+        if !has_accessibility_permission() {
+            return Err(RecordingError::PermissionDenied(
+                "Accessibility permission is needed to record keystrokes. \
+                 Allow it in System Settings > Privacy & Security > Accessibility, \
+                 or turn off keystroke tracking."
+                    .to_string(),
+            ));
+        }
+
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+
+        tracing::info!(
+            "Keyboard tracking channel initialized (mode={:?}, session={})",
+            self.mode,
+            session_index
+        );
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if self.is_recording.load(Ordering::SeqCst) {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        // Files from earlier sessions of this recording are kept so every
+        // session ends up in the manifest
+        self.keys.lock().clear();
+        if self.session_index == 0 {
+            self.output_files.lock().clear();
+        }
+
+        self.is_recording.store(true, Ordering::SeqCst);
+        let handle = platform::start_keyboard_tracking(
+            self.is_recording.clone(),
+            self.keys.clone(),
+            KeystrokeRecorder::new(self.mode, self.mask_secure_input),
+            Instant::now(),
+            Self::now_unix_ms,
+        );
+        let handle = match handle {
+            Ok(handle) => handle,
+            Err(e) => {
+                self.is_recording.store(false, Ordering::SeqCst);
+                return Err(e);
+            }
+        };
+        self.thread_handle = Some(handle);
+
+        tracing::info!("Keyboard tracking started");
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> RecordingResult<()> {
+        if !self.is_recording.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.is_recording.store(false, Ordering::SeqCst);
+
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+
+        self.flush_to_disk()?;
+
+        tracing::info!(
+            "Keyboard tracking stopped ({} keys)",
+            self.keys.lock().len()
+        );
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> RecordingResult<()> {
+        self.stop().await
+    }
+
+    async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+        self.session_index = session_index;
+        self.start().await
+    }
+
+    fn is_recording(&self) -> bool {
+        self.is_recording.load(Ordering::SeqCst)
+    }
+
+    fn output_files(&self) -> Vec<String> {
+        self.output_files.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str, modifiers: &[&str]) -> KeyPress {
+        KeyPress {
+            key: key.to_string(),
+            event_type: "down".to_string(),
+            active_modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            masked: false,
+            process_time_ms: 0.0,
+            unix_time_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_shortcuts_only_drops_typed_text() {
+        let recorder = KeystrokeRecorder {
+            mode: KeystrokeMode::ShortcutsOnly,
+            privacy: KeystrokePrivacyFilter::new(false),
+        };
+        assert!(recorder.record(key("h", &[])).is_none());
+        assert!(recorder.record(key("h", &["shift"])).is_none());
+        assert_eq!(recorder.record(key("c", &["meta"])).unwrap().key, "c");
+        assert_eq!(
+            recorder
+                .record(key("s", &["control", "shift"]))
+                .unwrap()
+                .key,
+            "s"
+        );
+        assert_eq!(
+            recorder.record(key("shift", &["shift"])).unwrap().key,
+            "shift"
+        );
+
+        let everything = KeystrokeRecorder {
+            mode: KeystrokeMode::All,
+            privacy: KeystrokePrivacyFilter::new(false),
+        };
+        assert_eq!(everything.record(key("h", &[])).unwrap().key, "h");
+    }
+}
@@ -1,11 +1,15 @@
-//! Input tracking (mouse, cursor) capture
+//! Input tracking (mouse, cursor, keyboard) capture
 //!
 //! Implements a `RecordingChannel` that records high-frequency mouse movement,
 //! mouse clicks, and cursor metadata for later processing (cursor smoothing,
-//! auto-zoom, etc.).
+//! auto-zoom, etc.), and one that records key presses for keystroke overlays.
 
 pub mod channel;
+pub mod keyboard;
+pub mod privacy;
 pub mod types;
 
 pub use channel::InputTrackingChannel;
-pub use types::{CursorInfo, MouseClick, MouseMove};
+pub use keyboard::KeyboardTrackingChannel;
+pub use privacy::KeystrokePrivacyFilter;
+pub use types::{CursorInfo, KeyPress, MouseClick, MouseMove};
@@ -0,0 +1,118 @@
+//! Keystroke privacy filtering
+//!
+//! Keystrokes typed while the OS reports a secure input (password fields,
+//! secure text entry) are masked before they are stored, so passwords never
+//! end up in a recording bundle. Shortcuts (keys held with Cmd/Ctrl) are kept
+//! since they don't reveal typed text and are useful for keystroke overlays.
+
+use super::types::KeyPress;
+
+#[cfg(target_os = "macos")]
+use crate::capture::macos::input as platform;
+
+#[cfg(target_os = "windows")]
+use crate::capture::windows::input as platform;
+
+/// Placeholder stored instead of a masked key
+pub const MASKED_KEY: &str = "•";
+
+/// Whether the focused input is a password/secure text field
+pub fn is_secure_input_active() -> bool {
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    {
+        platform::is_secure_input_active()
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        false
+    }
+}
+
+/// Masks keystrokes typed into secure inputs
+pub struct KeystrokePrivacyFilter {
+    enabled: bool,
+    secure_input_active: fn() -> bool,
+}
+
+impl KeystrokePrivacyFilter {
+    /// Create a filter using the platform's secure-input detection
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            secure_input_active: is_secure_input_active,
+        }
+    }
+
+    /// Apply the filter to a key event as it is recorded
+    pub fn apply(&self, key: KeyPress) -> KeyPress {
+        if self.enabled && (self.secure_input_active)() {
+            mask_keystroke(key)
+        } else {
+            key
+        }
+    }
+}
+
+/// Whether a key event is a shortcut (pressed with Cmd/Ctrl held)
+pub fn is_shortcut(key: &KeyPress) -> bool {
+    key.active_modifiers
+        .iter()
+        .any(|m| m == "meta" || m == "control")
+}
+
+/// Replace the key of a non-shortcut key event with a placeholder
+pub fn mask_keystroke(mut key: KeyPress) -> KeyPress {
+    if !is_shortcut(&key) {
+        key.key = MASKED_KEY.to_string();
+        key.masked = true;
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str, modifiers: &[&str]) -> KeyPress {
+        KeyPress {
+            key: key.to_string(),
+            event_type: "down".to_string(),
+            active_modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            masked: false,
+            process_time_ms: 0.0,
+            unix_time_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_secure_input_masks_typed_keys_but_not_shortcuts() {
+        let filter = KeystrokePrivacyFilter {
+            enabled: true,
+            secure_input_active: || true,
+        };
+
+        let typed = filter.apply(key("p", &["shift"]));
+        assert_eq!(typed.key, MASKED_KEY);
+        assert!(typed.masked);
+
+        let shortcut = filter.apply(key("v", &["meta"]));
+        assert_eq!(shortcut.key, "v");
+        assert!(!shortcut.masked);
+    }
+
+    #[test]
+    fn test_filter_passes_keys_outside_secure_input() {
+        let filter = KeystrokePrivacyFilter {
+            enabled: true,
+            secure_input_active: || false,
+        };
+        assert_eq!(filter.apply(key("a", &[])).key, "a");
+
+        let disabled = KeystrokePrivacyFilter {
+            enabled: false,
+            secure_input_active: || true,
+        };
+        assert_eq!(disabled.apply(key("a", &[])).key, "a");
+    }
+}
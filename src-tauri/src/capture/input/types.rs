@@ -24,6 +24,19 @@ pub struct MouseClick {
     pub unix_time_ms: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPress {
+    pub key: String,
+    pub event_type: String,
+    pub active_modifiers: Vec<String>,
+    /// Key was replaced by the privacy filter (typed into a secure input)
+    #[serde(default)]
+    pub masked: bool,
+    pub process_time_ms: f64,
+    pub unix_time_ms: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CursorInfo {
@@ -33,4 +46,22 @@ pub struct CursorInfo {
     pub hotspot_y: f64,
     pub width: u32,
     pub height: u32,
+    /// Standard shape this cursor was recognized as, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<CursorKind>,
+}
+
+/// Standard cursor shapes, which cursor packs provide replacements for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CursorKind {
+    Arrow,
+    Pointer,
+    Text,
+    Crosshair,
+    OpenHand,
+    ClosedHand,
+    NotAllowed,
+    ResizeLeftRight,
+    ResizeUpDown,
 }
@@ -0,0 +1,106 @@
+//! Microphone level metering
+//!
+//! While recording, the microphone channel measures the level of what it
+//! captured every `LEVEL_INTERVAL` and sends it as a `MicLevel` event, which
+//! reaches the frontend as `mic-level` for the toolbar's meter. A mic that
+//! stays below the noise floor for `SILENT_AFTER` is reported as silent, so a
+//! muted or wrong input is noticed before a long recording rather than after.
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// How often the level is measured
+pub const LEVEL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Peak below which a microphone is treated as silent (-60 dBFS). Room noise
+/// through a working mic is louder than this.
+const SILENCE_THRESHOLD: f32 = 0.001;
+
+/// How long a microphone has to stay silent to be reported
+const SILENT_AFTER: Duration = Duration::from_secs(5);
+
+/// Level of the audio captured over one interval, linear (0.0-1.0)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioLevel {
+    pub rms: f32,
+    pub peak: f32,
+    /// Nothing above the noise floor for `SILENT_AFTER`
+    pub silent: bool,
+}
+
+/// Accumulates captured samples into `AudioLevel`s
+#[derive(Debug, Default)]
+pub struct LevelMeter {
+    sum_squares: f64,
+    peak: f32,
+    samples: u64,
+    /// How long the input has been silent
+    quiet: Duration,
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add captured samples, in any channel layout
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.sum_squares += f64::from(sample) * f64::from(sample);
+            self.peak = self.peak.max(sample.abs());
+        }
+        self.samples += samples.len() as u64;
+    }
+
+    /// Level of the samples pushed since the last call, `elapsed` ago
+    pub fn take(&mut self, elapsed: Duration) -> AudioLevel {
+        let rms = if self.samples > 0 {
+            (self.sum_squares / self.samples as f64).sqrt() as f32
+        } else {
+            0.0
+        };
+        let peak = self.peak.min(1.0);
+        if peak < SILENCE_THRESHOLD {
+            self.quiet += elapsed;
+        } else {
+            self.quiet = Duration::ZERO;
+        }
+
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        self.samples = 0;
+        AudioLevel {
+            rms: rms.min(1.0),
+            peak,
+            silent: self.quiet >= SILENT_AFTER,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_and_silence() {
+        let mut meter = LevelMeter::new();
+        meter.push(&[0.5, -0.5, 0.5, -0.5]);
+        meter.push(&[-1.5]);
+        let level = meter.take(LEVEL_INTERVAL);
+        assert_eq!(level.peak, 1.0);
+        assert!((level.rms - (3.25f32 / 5.0).sqrt()).abs() < 1e-6);
+        assert!(!level.silent);
+
+        // Quiet for five seconds, then reported until there's sound again
+        for _ in 0..49 {
+            meter.push(&[0.0005; 480]);
+            assert!(!meter.take(LEVEL_INTERVAL).silent);
+        }
+        let level = meter.take(LEVEL_INTERVAL);
+        assert_eq!(level.rms, 0.0);
+        assert!(level.silent);
+        meter.push(&[0.1]);
+        assert!(!meter.take(LEVEL_INTERVAL).silent);
+    }
+}
@@ -0,0 +1,23 @@
+//! Linux capture implementations
+//!
+//! Uses x11grab (XShm) on X11 and the ScreenCast portal with PipeWire on
+//! Wayland for screen capture, and the PulseAudio monitor source (also
+//! served by PipeWire) for system audio. The microphone goes through the
+//! cross-platform cpal channel.
+
+pub mod screen;
+pub mod system_audio;
+
+pub use screen::*;
+pub use system_audio::*;
+
+/// X11 needs no permission; on Wayland the portal asks when recording starts
+pub mod permissions {
+    pub fn has_screen_recording_permission() -> bool {
+        true
+    }
+
+    pub fn request_screen_recording_permission() -> bool {
+        true
+    }
+}
@@ -0,0 +1,956 @@
+//! Linux display capture
+//!
+//! On X11 the display is grabbed by FFmpeg's `x11grab` device, which reads
+//! the screen through the MIT-SHM (XShm) extension. Wayland compositors
+//! don't let clients read the screen; there the ScreenCast portal asks the
+//! user to share a monitor and hands back a PipeWire stream, which
+//! GStreamer's `pipewiresrc` converts to raw BGRA frames for the FFmpeg
+//! encoder. The portal remembers the choice while the app runs, so resuming
+//! a paused recording doesn't ask again.
+//!
+//! Displays are enumerated through RandR (served by XWayland on Wayland), so
+//! IDs are monitor indices as on Windows. On Wayland the display is picked
+//! in the portal dialog; `display_id` only places a capture region.
+
+use crate::capture::display_watch::DisplayWatcher;
+use crate::capture::frame::{region_in_frame, ContentRect, Rotation};
+use crate::capture::frame_rate::{display_fps, DEFAULT_MAX_FPS};
+use crate::capture::traits::DisplayInfo;
+use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::coordinator::RecordingEvent;
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::segment::live_movflags_args;
+use crate::recorder::state::{CaptureQuality, VideoEncodingConfig};
+use crate::utils::process::{track, TrackedChild};
+use ashpd::desktop::screencast::{CursorMode, Screencast, SourceType};
+use ashpd::desktop::{PersistMode, Session};
+use ashpd::WindowIdentifier;
+use async_trait::async_trait;
+use std::io::Write;
+use std::os::fd::OwnedFd;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tokio::sync::{broadcast, oneshot};
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::{ConnectionExt as _, ModeFlag};
+use x11rb::protocol::xproto::ConnectionExt as _;
+
+/// Kind of graphical session the app is running in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionType {
+    X11,
+    Wayland,
+}
+
+/// Kind of the current session, from `XDG_SESSION_TYPE` or `WAYLAND_DISPLAY`
+pub fn session_type() -> SessionType {
+    let wayland = std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t == "wayland")
+        || std::env::var_os("WAYLAND_DISPLAY").is_some();
+    if wayland {
+        SessionType::Wayland
+    } else {
+        SessionType::X11
+    }
+}
+
+/// A RandR monitor, in X screen coordinates
+#[derive(Debug, Clone)]
+struct Monitor {
+    name: String,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    primary: bool,
+    rotation: Rotation,
+    refresh_rate: Option<u32>,
+}
+
+/// Active monitors in RandR order, or none without an X server
+fn monitors() -> Vec<Monitor> {
+    let Ok((conn, screen_num)) = x11rb::connect(None) else {
+        return Vec::new();
+    };
+    let root = conn.setup().roots[screen_num].root;
+    let Some(reply) = conn
+        .randr_get_monitors(root, true)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+    else {
+        return Vec::new();
+    };
+    let modes = conn
+        .randr_get_screen_resources_current(root)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|resources| resources.modes)
+        .unwrap_or_default();
+
+    reply
+        .monitors
+        .iter()
+        .map(|m| {
+            let name = conn
+                .get_atom_name(m.name)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map(|reply| String::from_utf8_lossy(&reply.name).to_string())
+                .unwrap_or_else(|| "Display".to_string());
+            let crtc = m.outputs.first().and_then(|&output| {
+                let crtc = conn.randr_get_output_info(output, 0).ok()?.reply().ok()?.crtc;
+                conn.randr_get_crtc_info(crtc, 0).ok()?.reply().ok()
+            });
+            // Monitor geometry is already rotated; the CRTC says how
+            let rotation = crtc
+                .as_ref()
+                .map(|crtc| randr_rotation(crtc.rotation))
+                .unwrap_or_default();
+            let refresh_rate = crtc
+                .and_then(|crtc| modes.iter().find(|mode| mode.id == crtc.mode))
+                .and_then(|mode| {
+                    let flags = u32::from(mode.mode_flags);
+                    mode_refresh_rate(
+                        mode.dot_clock,
+                        mode.htotal,
+                        mode.vtotal,
+                        flags & u32::from(ModeFlag::INTERLACE) != 0,
+                        flags & u32::from(ModeFlag::DOUBLE_SCAN) != 0,
+                    )
+                });
+            Monitor {
+                name,
+                x: m.x as i32,
+                y: m.y as i32,
+                width: m.width as u32,
+                height: m.height as u32,
+                primary: m.primary,
+                rotation,
+                refresh_rate,
+            }
+        })
+        .collect()
+}
+
+/// RandR rotations turn counter-clockwise
+fn randr_rotation(rotation: x11rb::protocol::randr::Rotation) -> Rotation {
+    use x11rb::protocol::randr::Rotation as R;
+    let degrees = if rotation.contains(R::ROTATE90) {
+        90.0
+    } else if rotation.contains(R::ROTATE180) {
+        180.0
+    } else if rotation.contains(R::ROTATE270) {
+        270.0
+    } else {
+        0.0
+    };
+    Rotation::from_degrees(-degrees)
+}
+
+/// Refresh rate of a RandR mode in whole hertz, e.g. 60 for 59.95Hz
+fn mode_refresh_rate(
+    dot_clock: u32,
+    htotal: u16,
+    vtotal: u16,
+    interlaced: bool,
+    double_scan: bool,
+) -> Option<u32> {
+    let mut lines = htotal as f64 * vtotal as f64;
+    if interlaced {
+        lines /= 2.0;
+    }
+    if double_scan {
+        lines *= 2.0;
+    }
+    (dot_clock > 0 && lines > 0.0).then(|| (dot_clock as f64 / lines).round() as u32)
+}
+
+/// Get list of available displays on Linux
+pub fn get_displays() -> Vec<DisplayInfo> {
+    let mut displays: Vec<DisplayInfo> = monitors()
+        .into_iter()
+        .enumerate()
+        .map(|(index, m)| DisplayInfo {
+            id: index as u32,
+            name: if m.primary {
+                "Primary Display".to_string()
+            } else {
+                m.name
+            },
+            width: m.width,
+            height: m.height,
+            // X11 coordinates are device pixels
+            scale_factor: 1.0,
+            is_primary: m.primary,
+            refresh_rate: m.refresh_rate,
+            rotation: m.rotation,
+        })
+        .collect();
+
+    // Wayland without XWayland: the portal picks the screen when recording
+    if displays.is_empty() {
+        displays.push(DisplayInfo {
+            id: 0,
+            name: "Primary Display".to_string(),
+            width: 1920,
+            height: 1080,
+            scale_factor: 1.0,
+            is_primary: true,
+            refresh_rate: None,
+            rotation: Rotation::None,
+        });
+    }
+
+    displays
+}
+
+/// ID of the display the mouse cursor is currently on
+///
+/// IDs are RandR monitor indices, matching `get_displays`. Wayland only
+/// reports the pointer over XWayland windows, so this is usually None there.
+pub fn display_under_cursor() -> Option<u32> {
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots[screen_num].root;
+    let pointer = conn.query_pointer(root).ok()?.reply().ok()?;
+    let (x, y) = (pointer.root_x as i32, pointer.root_y as i32);
+
+    monitors()
+        .iter()
+        .position(|m| x >= m.x && x < m.x + m.width as i32 && y >= m.y && y < m.y + m.height as i32)
+        .map(|index| index as u32)
+}
+
+/// Size of a display, or None once it's no longer connected (or can't be
+/// seen without XWayland)
+pub(crate) fn display_size(display_id: u32) -> Option<(u32, u32)> {
+    monitors()
+        .into_iter()
+        .nth(display_id as usize)
+        .map(|m| (m.width, m.height))
+}
+
+/// X display FFmpeg should grab, e.g. ":0"
+fn x_display_name() -> String {
+    std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string())
+}
+
+/// Capture a single BGRA frame of an X11 display
+pub(crate) fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+    if session_type() == SessionType::Wayland {
+        return None;
+    }
+    let monitor = monitors().into_iter().nth(display_id as usize)?;
+    let output = Command::new("ffmpeg")
+        .args([
+            "-v",
+            "error",
+            "-f",
+            "x11grab",
+            "-draw_mouse",
+            "0",
+            "-video_size",
+            &format!("{}x{}", monitor.width, monitor.height),
+            "-i",
+            &format!("{}+{},{}", x_display_name(), monitor.x, monitor.y),
+            "-frames:v",
+            "1",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "bgra",
+            "-",
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+
+    let expected = (monitor.width * monitor.height * 4) as usize;
+    (output.status.success() && output.stdout.len() >= expected).then(|| {
+        let mut data = output.stdout;
+        data.truncate(expected);
+        (data, monitor.width, monitor.height)
+    })
+}
+
+/// Where the encoder's frames come from
+#[derive(Debug, Clone, PartialEq)]
+enum CaptureSource {
+    /// FFmpeg grabs `width`x`height` at `x`,`y` of an X display itself
+    X11 {
+        display: String,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        /// Draw the cursor into the frames
+        draw_mouse: bool,
+    },
+    /// Raw BGRA frames of this size arrive on stdin
+    Raw { width: u32, height: u32 },
+}
+
+/// FFmpeg arguments recording `source` to `output`, cropped to `crop` (in
+/// raw frame coordinates) and scaled down as `quality` asks
+fn encoder_args(
+    source: &CaptureSource,
+    fps: u32,
+    encoding: &VideoEncodingConfig,
+    quality: CaptureQuality,
+    crop: Option<ContentRect>,
+    output: &Path,
+) -> Vec<String> {
+    let mut args = vec!["-y".to_string()];
+    match source {
+        CaptureSource::X11 {
+            display,
+            x,
+            y,
+            width,
+            height,
+            draw_mouse,
+        } => {
+            args.extend(
+                [
+                    "-f",
+                    "x11grab",
+                    // See `DisplayCaptureChannel::show_cursor`
+                    "-draw_mouse",
+                    if *draw_mouse { "1" } else { "0" },
+                    "-framerate",
+                    &fps.to_string(),
+                    "-video_size",
+                    &format!("{}x{}", width, height),
+                    "-i",
+                    &format!("{}+{},{}", display, x, y),
+                ]
+                .map(String::from),
+            );
+        }
+        CaptureSource::Raw { width, height } => {
+            args.extend(
+                [
+                    "-f",
+                    "rawvideo",
+                    "-pixel_format",
+                    "bgra",
+                    "-video_size",
+                    &format!("{}x{}", width, height),
+                    "-framerate",
+                    &fps.to_string(),
+                    "-i",
+                    "-",
+                ]
+                .map(String::from),
+            );
+        }
+    }
+    let crop = crop.map(|r| format!("crop={}:{}:{}:{}", r.width, r.height, r.x, r.y));
+    args.extend(encoding.screen_filter_args(fps, quality, crop));
+    args.extend(
+        ["-c:v", "libx264", "-preset", "veryfast", "-pix_fmt", "yuv420p"].map(String::from),
+    );
+    args.extend(encoding.ffmpeg_args(fps, quality));
+    args.extend(live_movflags_args().map(String::from));
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+/// A monitor shared through the ScreenCast portal
+struct PortalStream {
+    node_id: u32,
+    /// PipeWire remote the stream is read from
+    fd: OwnedFd,
+    size: Option<(i32, i32)>,
+    restore_token: Option<String>,
+}
+
+/// Ask the portal for a monitor, reusing an earlier choice if `restore_token`
+/// is still valid
+async fn share_monitor(
+    proxy: &Screencast<'_>,
+    session: &Session<'_, Screencast<'_>>,
+    restore_token: Option<&str>,
+    show_cursor: bool,
+) -> ashpd::Result<PortalStream> {
+    let cursor_mode = if show_cursor {
+        CursorMode::Embedded
+    } else {
+        CursorMode::Hidden
+    };
+    proxy
+        .select_sources(
+            session,
+            cursor_mode,
+            SourceType::Monitor.into(),
+            false,
+            restore_token,
+            PersistMode::Application,
+        )
+        .await?;
+    let response = proxy
+        .start(session, &WindowIdentifier::default())
+        .await?
+        .response()?;
+    let stream = response.streams().first().ok_or(ashpd::Error::NoResponse)?;
+    let fd = proxy.open_pipe_wire_remote(session).await?;
+
+    Ok(PortalStream {
+        node_id: stream.pipe_wire_node_id(),
+        fd,
+        size: stream.size(),
+        restore_token: response.restore_token().map(str::to_string),
+    })
+}
+
+/// Run a portal session: report the shared stream on `ready`, then keep the
+/// session open until `stop` fires (or its sender is dropped)
+async fn run_portal(
+    restore_token: Option<String>,
+    show_cursor: bool,
+    ready: oneshot::Sender<Result<PortalStream, String>>,
+    stop: oneshot::Receiver<()>,
+) {
+    let proxy = match Screencast::new().await {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            let _ = ready.send(Err(e.to_string()));
+            return;
+        }
+    };
+    let session = match proxy.create_session().await {
+        Ok(session) => session,
+        Err(e) => {
+            let _ = ready.send(Err(e.to_string()));
+            return;
+        }
+    };
+
+    match share_monitor(&proxy, &session, restore_token.as_deref(), show_cursor).await {
+        Ok(stream) => {
+            if ready.send(Ok(stream)).is_ok() {
+                let _ = stop.await;
+            }
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e.to_string()));
+        }
+    }
+
+    if let Err(e) = session.close().await {
+        tracing::debug!("Failed to close ScreenCast session: {}", e);
+    }
+}
+
+/// Display capture channel for Linux
+pub struct DisplayCaptureChannel {
+    id: String,
+    display_id: u32,
+    is_recording: bool,
+    output_dir: Option<PathBuf>,
+    session_index: usize,
+    output_files: Vec<String>,
+    /// FFmpeg, encoding the session
+    encoder: Option<TrackedChild>,
+    /// GStreamer, feeding the encoder from PipeWire (Wayland)
+    source: Option<TrackedChild>,
+    /// Closes the portal session when sent (Wayland)
+    portal_stop: Option<oneshot::Sender<()>>,
+    restore_token: Option<String>,
+    width: u32,
+    height: u32,
+    fps: u32,
+    video_encoding: VideoEncodingConfig,
+    quality: CaptureQuality,
+    follow_cursor_on_resume: bool,
+    show_cursor: bool,
+    region: Option<ContentRect>,
+    max_fps: u32,
+    event_tx: Option<broadcast::Sender<RecordingEvent>>,
+    watcher: Option<DisplayWatcher>,
+}
+
+impl DisplayCaptureChannel {
+    pub fn new(display_id: u32) -> Self {
+        Self {
+            id: format!("display-{}", display_id),
+            display_id,
+            is_recording: false,
+            output_dir: None,
+            session_index: 0,
+            output_files: Vec::new(),
+            encoder: None,
+            source: None,
+            portal_stop: None,
+            restore_token: None,
+            width: 1920,
+            height: 1080,
+            fps: 30,
+            video_encoding: VideoEncodingConfig::default(),
+            quality: CaptureQuality::default(),
+            follow_cursor_on_resume: false,
+            show_cursor: false,
+            region: None,
+            max_fps: DEFAULT_MAX_FPS,
+            event_tx: None,
+            watcher: None,
+        }
+    }
+
+    /// Switch to the display under the cursor whenever recording resumes
+    /// (X11 only; on Wayland the portal's choice stands)
+    pub fn follow_cursor_on_resume(mut self, follow: bool) -> Self {
+        self.follow_cursor_on_resume = follow;
+        self
+    }
+
+    /// Record only this part of the display, in the coordinates reported by
+    /// `get_displays`
+    pub fn region(mut self, region: Option<ContentRect>) -> Self {
+        self.region = region;
+        self
+    }
+
+    /// Keyframe and B-frame settings for the encoder
+    pub fn video_encoding(mut self, encoding: VideoEncodingConfig) -> Self {
+        self.video_encoding = encoding;
+        self
+    }
+
+    /// Frame rate, scaling and encoder quality preset
+    pub fn quality(mut self, quality: CaptureQuality) -> Self {
+        self.fps = quality.fps();
+        self.quality = quality;
+        self
+    }
+
+    /// Highest frame rate to follow the display's refresh rate up to
+    pub fn max_fps(mut self, max_fps: u32) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
+
+    /// Record the system cursor into the frames. There's no input tracking
+    /// on Linux to draw it from at export, so the registry always asks for it.
+    pub fn show_cursor(mut self, show: bool) -> Self {
+        self.show_cursor = show;
+        self
+    }
+
+    fn output_path(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join(FileKind::ScreenVideo.conventional_name(self.session_index))
+    }
+
+    /// Capture region in frame coordinates for a `frame_w`x`frame_h` capture
+    fn crop_rect(&self, frame_w: u32, frame_h: u32) -> RecordingResult<Option<ContentRect>> {
+        let Some(region) = self.region else {
+            return Ok(None);
+        };
+        let (display_w, display_h) = get_displays()
+            .into_iter()
+            .nth(self.display_id as usize)
+            .map(|d| (d.width, d.height))
+            .unwrap_or((frame_w, frame_h));
+        region_in_frame(region, display_w, display_h, frame_w, frame_h)
+            .map(Some)
+            .ok_or_else(|| {
+                RecordingError::ConfigurationError(
+                    "Capture region is outside the display".to_string(),
+                )
+            })
+    }
+
+    /// Grab the display with x11grab
+    fn start_x11(&mut self, output_dir: &Path) -> RecordingResult<()> {
+        let monitor = monitors()
+            .into_iter()
+            .nth(self.display_id as usize)
+            .ok_or_else(|| {
+                RecordingError::DeviceNotFound(format!("Display {}", self.display_id))
+            })?;
+
+        // x11grab reads just the region, so no crop filter is needed
+        let source = match self.crop_rect(monitor.width, monitor.height)? {
+            Some(rect) => CaptureSource::X11 {
+                display: x_display_name(),
+                x: monitor.x + rect.x as i32,
+                y: monitor.y + rect.y as i32,
+                width: rect.width,
+                height: rect.height,
+                draw_mouse: self.show_cursor,
+            },
+            None => CaptureSource::X11 {
+                display: x_display_name(),
+                x: monitor.x,
+                y: monitor.y,
+                // yuv420p needs even dimensions
+                width: monitor.width & !1,
+                height: monitor.height & !1,
+                draw_mouse: self.show_cursor,
+            },
+        };
+        if let CaptureSource::X11 { width, height, .. } = source {
+            self.width = width;
+            self.height = height;
+        }
+
+        let args = encoder_args(
+            &source,
+            self.fps,
+            &self.video_encoding,
+            self.quality,
+            None,
+            &self.output_path(output_dir),
+        );
+        let encoder = Command::new("ffmpeg")
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| RecordingError::CaptureError(format!("Failed to start FFmpeg: {}", e)))?;
+        self.encoder = Some(track(encoder, "screen encoder"));
+        Ok(())
+    }
+
+    /// Share a monitor through the portal and pipe it from GStreamer to FFmpeg
+    async fn start_wayland(&mut self, output_dir: &Path) -> RecordingResult<()> {
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (stop_tx, stop_rx) = oneshot::channel();
+        tokio::spawn(run_portal(
+            self.restore_token.clone(),
+            self.show_cursor,
+            ready_tx,
+            stop_rx,
+        ));
+
+        let stream = ready_rx
+            .await
+            .map_err(|_| RecordingError::PlatformError("ScreenCast portal closed".to_string()))?
+            .map_err(|e| {
+                RecordingError::PermissionDenied(format!("Screen sharing was not allowed: {}", e))
+            })?;
+        self.portal_stop = Some(stop_tx);
+        if stream.restore_token.is_some() {
+            self.restore_token = stream.restore_token;
+        }
+
+        let (stream_w, stream_h) = stream
+            .size
+            .filter(|&(w, h)| w > 0 && h > 0)
+            .map(|(w, h)| (w.max(2) as u32 & !1, h.max(2) as u32 & !1))
+            .unwrap_or((self.width, self.height));
+        let crop = self.crop_rect(stream_w, stream_h)?;
+        self.width = crop.map_or(stream_w, |c| c.width);
+        self.height = crop.map_or(stream_h, |c| c.height);
+
+        // The PipeWire remote becomes GStreamer's stdin. videorate turns the
+        // damage-driven stream into a constant frame rate.
+        let caps = format!(
+            "video/x-raw,format=BGRA,width={},height={},framerate={}/1",
+            stream_w, stream_h, self.fps
+        );
+        let mut source = Command::new("gst-launch-1.0")
+            .args([
+                "-q",
+                "pipewiresrc",
+                "fd=0",
+                &format!("path={}", stream.node_id),
+                "do-timestamp=true",
+                "keepalive-time=1000",
+                "!",
+                "videorate",
+                "!",
+                "videoconvert",
+                "!",
+                "videoscale",
+                "!",
+                &caps,
+                "!",
+                "fdsink",
+                "fd=1",
+            ])
+            .stdin(Stdio::from(stream.fd))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                RecordingError::CaptureError(format!("Failed to start GStreamer: {}", e))
+            })?;
+        let frames = source
+            .stdout
+            .take()
+            .ok_or_else(|| RecordingError::CaptureError("GStreamer has no output".to_string()))?;
+
+        let args = encoder_args(
+            &CaptureSource::Raw {
+                width: stream_w,
+                height: stream_h,
+            },
+            self.fps,
+            &self.video_encoding,
+            self.quality,
+            crop,
+            &self.output_path(output_dir),
+        );
+        let encoder = Command::new("ffmpeg")
+            .args(&args)
+            .stdin(Stdio::from(frames))
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| RecordingError::CaptureError(format!("Failed to start FFmpeg: {}", e)))?;
+
+        self.source = Some(track(source, "pipewire capture"));
+        self.encoder = Some(track(encoder, "screen encoder"));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordingChannel for DisplayCaptureChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Display
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        if Command::new("ffmpeg").arg("-version").output().is_err() {
+            return Err(RecordingError::FfmpegMissing(
+                "FFmpeg not found. Please install FFmpeg and add it to PATH.".to_string(),
+            ));
+        }
+        if session_type() == SessionType::Wayland
+            && Command::new("gst-launch-1.0")
+                .arg("--version")
+                .output()
+                .is_err()
+        {
+            return Err(RecordingError::PlatformError(
+                "Screen capture on Wayland needs GStreamer with the PipeWire plugin (gst-launch-1.0 not found)"
+                    .to_string(),
+            ));
+        }
+
+        let display = get_displays().into_iter().nth(self.display_id as usize);
+        if let Some(display) = &display {
+            self.width = display.width;
+            self.height = display.height;
+        }
+        let refresh_rate = display.and_then(|d| d.refresh_rate);
+        self.fps = display_fps(self.quality, refresh_rate, self.max_fps);
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+
+        tracing::info!(
+            "Linux display capture initialized for display {} ({:?} session)",
+            self.display_id,
+            session_type()
+        );
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if self.is_recording {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        let output_dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+        std::fs::create_dir_all(&output_dir)?;
+
+        match session_type() {
+            SessionType::X11 => self.start_x11(&output_dir)?,
+            SessionType::Wayland => self.start_wayland(&output_dir).await?,
+        }
+        self.is_recording = true;
+        self.watcher = self
+            .event_tx
+            .clone()
+            .and_then(|tx| DisplayWatcher::start(&self.id, self.display_id, display_size, tx));
+
+        tracing::info!(
+            "Linux display capture started for display {} ({}x{} @ {}fps)",
+            self.display_id,
+            self.width,
+            self.height,
+            self.fps
+        );
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> RecordingResult<()> {
+        if !self.is_recording {
+            return Err(RecordingError::NotRecording);
+        }
+        self.is_recording = false;
+        if let Some(watcher) = self.watcher.take() {
+            watcher.stop();
+        }
+
+        match self.source.take() {
+            // The encoder finishes once GStreamer's output closes
+            Some(mut source) => {
+                let _ = source.kill();
+                let _ = source.wait_with_output();
+            }
+            // Ask x11grab to finish the file
+            None => {
+                if let Some(stdin) = self.encoder.as_mut().and_then(|e| e.stdin.as_mut()) {
+                    let _ = stdin.write_all(b"q");
+                }
+            }
+        }
+
+        if let Some(encoder) = self.encoder.take() {
+            let output = tokio::task::spawn_blocking(move || encoder.wait_with_output())
+                .await
+                .map_err(|e| {
+                    RecordingError::CaptureError(format!("Encoder task failed: {}", e))
+                })??;
+            if !output.status.success() {
+                tracing::warn!(
+                    "FFmpeg exited with status {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+        }
+
+        if let Some(stop) = self.portal_stop.take() {
+            let _ = stop.send(());
+        }
+
+        if let Some(output_dir) = &self.output_dir {
+            let output_file = self.output_path(output_dir);
+            if output_file.exists() {
+                self.output_files
+                    .push(output_file.to_string_lossy().to_string());
+            }
+        }
+
+        tracing::info!("Linux display capture stopped");
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> RecordingResult<()> {
+        self.stop().await
+    }
+
+    async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+        self.session_index = session_index;
+        if self.follow_cursor_on_resume && session_type() == SessionType::X11 {
+            if let Some(display_id) = display_under_cursor().filter(|&id| id != self.display_id) {
+                tracing::info!(
+                    "Cursor moved to display {}, switching capture from display {}",
+                    display_id,
+                    self.display_id
+                );
+                self.display_id = display_id;
+            }
+        }
+        self.start().await
+    }
+
+    fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    fn output_files(&self) -> Vec<String> {
+        self.output_files.clone()
+    }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<RecordingEvent>) {
+        self.event_tx = Some(event_tx);
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: Some(self.display_id.to_string()),
+            width: Some(self.width),
+            height: Some(self.height),
+            fps: Some(self.fps),
+            region: self.region,
+            downscale: Some(self.quality.downscale()).filter(|&n| n > 1),
+            shows_cursor: self.show_cursor,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoder_args_for_each_source() {
+        let x11 = CaptureSource::X11 {
+            display: ":0".to_string(),
+            x: 1920,
+            y: 0,
+            width: 800,
+            height: 600,
+            draw_mouse: false,
+        };
+        let default = VideoEncodingConfig::default();
+        let args = encoder_args(
+            &x11,
+            30,
+            &default,
+            CaptureQuality::Balanced,
+            None,
+            Path::new("/tmp/recording-0.mp4"),
+        );
+        let joined = args.join(" ");
+        assert!(joined.starts_with(
+            "-y -f x11grab -draw_mouse 0 -framerate 30 -video_size 800x600 -i :0+1920,0"
+        ));
+        // Unchanged frames are dropped, keeping the others' timestamps
+        assert!(joined.contains("-vf mpdecimate=hi=0:lo=0:frac=0:max=30 -fps_mode vfr -c:v"));
+        assert!(joined.contains("-crf 18 -g 60 -movflags +frag_keyframe+empty_moov"));
+        assert_eq!(args.last().unwrap(), "/tmp/recording-0.mp4");
+
+        let raw = CaptureSource::Raw {
+            width: 2560,
+            height: 1440,
+        };
+        let crop = ContentRect {
+            x: 100,
+            y: 50,
+            width: 640,
+            height: 480,
+        };
+        let encoding = VideoEncodingConfig {
+            keyframe_interval_secs: Some(0.5),
+            b_frames: Some(0),
+            ..Default::default()
+        };
+        let args = encoder_args(
+            &raw,
+            30,
+            &encoding,
+            CaptureQuality::Performance,
+            Some(crop),
+            Path::new("/tmp/recording-1.mp4"),
+        );
+        let joined = args.join(" ");
+        // A fixed GOP keeps every frame
+        assert!(joined.contains("-f rawvideo -pixel_format bgra -video_size 2560x1440"));
+        assert!(joined.contains(
+            "-i - -vf crop=640:480:100:50,scale=trunc(iw/4)*2:trunc(ih/4)*2 -c:v libx264"
+        ));
+        assert!(joined.contains("-crf 23 -g 15 -keyint_min 15 -sc_threshold 0 -bf 0 -movflags"));
+    }
+
+    #[test]
+    fn test_mode_refresh_rate() {
+        // 1920x1080 CVT reduced blanking, 59.95Hz
+        assert_eq!(mode_refresh_rate(138_500_000, 2080, 1111, false, false), Some(60));
+        // 2560x1440 at 143.97Hz
+        assert_eq!(mode_refresh_rate(586_590_000, 2720, 1498, false, false), Some(144));
+        assert_eq!(mode_refresh_rate(74_250_000, 2200, 1125, true, false), Some(60));
+        assert_eq!(mode_refresh_rate(0, 2200, 1125, false, false), None);
+    }
+}
@@ -0,0 +1,243 @@
+//! Linux system audio capture from the PulseAudio monitor source
+//!
+//! Every PulseAudio sink has a monitor source carrying what it plays, and
+//! PipeWire exposes the same through `pipewire-pulse`. `parec` records the
+//! default sink's monitor (`@DEFAULT_MONITOR@`), converting to the encoding
+//! format on the server side, and its output is fed to the audio encoder.
+
+use crate::capture::audio::{AudioEncoder, AudioFormat};
+use crate::capture::traits::SystemAudioSupport;
+use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::state::AudioEncodingConfig;
+use crate::utils::process::{track, TrackedChild};
+use async_trait::async_trait;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+/// Source PulseAudio resolves to the default output's monitor
+const DEFAULT_MONITOR: &str = "@DEFAULT_MONITOR@";
+
+/// Format of the monitor stream unless the encoding overrides it
+const DEFAULT_FORMAT: AudioFormat = AudioFormat {
+    sample_rate: 48000,
+    channels: 2,
+};
+
+/// Whether `parec` is available to record the monitor source
+pub fn is_system_audio_available() -> bool {
+    Command::new("parec")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Whether system audio can be recorded, and why not
+pub fn system_audio_support() -> SystemAudioSupport {
+    if is_system_audio_available() {
+        SystemAudioSupport::available()
+    } else {
+        SystemAudioSupport::unavailable(
+            "System audio capture needs PulseAudio or pipewire-pulse (parec wasn't found)",
+        )
+    }
+}
+
+/// System audio capture channel for Linux
+pub struct SystemAudioCaptureChannel {
+    id: String,
+    is_recording: bool,
+    output_dir: Option<PathBuf>,
+    session_index: usize,
+    output_files: Vec<String>,
+    encoder: Option<Arc<AudioEncoder>>,
+    recorder: Option<TrackedChild>,
+    reader: Option<std::thread::JoinHandle<()>>,
+    format: AudioFormat,
+    encoding: AudioEncodingConfig,
+    available: bool,
+}
+
+impl SystemAudioCaptureChannel {
+    /// Create a new system audio capture channel
+    pub fn new(encoding: AudioEncodingConfig) -> Self {
+        let available = is_system_audio_available();
+        if !available {
+            tracing::warn!(
+                "parec not found; system audio capture needs PulseAudio or pipewire-pulse"
+            );
+        }
+
+        Self {
+            id: "system-audio".to_string(),
+            is_recording: false,
+            output_dir: None,
+            session_index: 0,
+            output_files: Vec::new(),
+            encoder: None,
+            recorder: None,
+            reader: None,
+            format: DEFAULT_FORMAT.with_encoding(&encoding),
+            encoding,
+            available,
+        }
+    }
+}
+
+#[async_trait]
+impl RecordingChannel for SystemAudioCaptureChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::SystemAudio
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+
+        tracing::info!(
+            "System audio channel initialized: {} ({}Hz {}ch, {}kbps)",
+            DEFAULT_MONITOR,
+            self.format.sample_rate,
+            self.format.channels,
+            self.encoding.bitrate_kbps()
+        );
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if !self.available {
+            tracing::warn!("Skipping system audio capture - not available");
+            return Ok(());
+        }
+
+        if self.is_recording {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        let output_dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+
+        let encoder = Arc::new(
+            AudioEncoder::new(
+                self.format.sample_rate,
+                self.format.channels,
+                self.encoding.bitrate_kbps(),
+                &output_dir,
+                self.session_index,
+                FileKind::SystemAudio,
+            )
+            .map_err(|e| {
+                RecordingError::CaptureError(format!("Failed to start audio encoder: {}", e))
+            })?,
+        );
+
+        let mut recorder = Command::new("parec")
+            .args([
+                &format!("--device={}", DEFAULT_MONITOR),
+                "--format=float32le",
+                &format!("--rate={}", self.format.sample_rate),
+                &format!("--channels={}", self.format.channels),
+                "--latency-msec=20",
+                "--raw",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| RecordingError::CaptureError(format!("Failed to start parec: {}", e)))?;
+        let mut stdout = recorder
+            .stdout
+            .take()
+            .ok_or_else(|| RecordingError::CaptureError("parec has no output".to_string()))?;
+
+        // Forward whole frames only, so channels stay interleaved correctly
+        let frame_bytes = self.format.channels.max(1) as usize * 4;
+        let reader_encoder = encoder.clone();
+        let reader = std::thread::spawn(move || {
+            let mut buffer = vec![0u8; frame_bytes * 1024];
+            let mut filled = 0;
+            loop {
+                match stdout.read(&mut buffer[filled..]) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => filled += n,
+                }
+                let whole = filled - filled % frame_bytes;
+                if whole > 0 {
+                    reader_encoder.write_samples(&buffer[..whole]);
+                    buffer.copy_within(whole..filled, 0);
+                    filled -= whole;
+                }
+            }
+            tracing::info!("System audio stream stopped");
+        });
+
+        self.encoder = Some(encoder);
+        self.recorder = Some(track(recorder, "system audio recorder"));
+        self.reader = Some(reader);
+        self.is_recording = true;
+
+        tracing::info!("System audio capture started");
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> RecordingResult<()> {
+        if !self.is_recording {
+            return Ok(());
+        }
+        self.is_recording = false;
+
+        // The reader ends once parec's output closes
+        if let Some(mut recorder) = self.recorder.take() {
+            let _ = recorder.kill();
+            let _ = recorder.wait_with_output();
+        }
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+
+        if let Some(encoder) = self.encoder.take() {
+            if let Ok(Some(output_file)) = encoder.finish() {
+                self.output_files.push(output_file);
+            }
+        }
+
+        tracing::info!("System audio capture stopped");
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> RecordingResult<()> {
+        self.stop().await
+    }
+
+    async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+        self.session_index = session_index;
+        self.start().await
+    }
+
+    fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    fn output_files(&self) -> Vec<String> {
+        self.output_files.clone()
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: Some(DEFAULT_MONITOR.to_string()),
+            sample_rate: Some(self.format.sample_rate),
+            channels: Some(self.format.channels),
+            bitrate_kbps: Some(self.encoding.bitrate_kbps()),
+            ..Default::default()
+        }
+    }
+}
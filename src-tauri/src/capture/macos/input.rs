@@ -1,6 +1,13 @@
-use crate::capture::input::types::{CursorInfo, MouseClick, MouseMove};
-use crate::recorder::channel::RecordingResult;
+use crate::capture::input::keyboard::KeystrokeRecorder;
+use crate::capture::input::types::{CursorInfo, CursorKind, KeyPress, MouseClick, MouseMove};
+use crate::capture::screenshot::CursorSnapshot;
+use crate::recorder::channel::{RecordingError, RecordingResult};
+use core_foundation::runloop::{kCFRunLoopCommonModes, kCFRunLoopDefaultMode, CFRunLoop};
 use core_graphics::display::CGDisplay;
+use core_graphics::event::{
+    CGEvent, CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
+    CGEventType, EventField,
+};
 use objc2::rc::Retained;
 use objc2_app_kit::{NSBitmapImageFileType, NSBitmapImageRep, NSCursor, NSEvent, NSImage};
 use objc2_foundation::{NSDictionary, NSString};
@@ -60,6 +67,7 @@ pub fn start_input_tracking(
         let mut last_right_down = false;
         // Track which cursor hashes we've already saved to avoid duplicates
         let mut saved_cursor_hashes: HashSet<u64> = HashSet::new();
+        let standard_kinds = standard_cursor_kinds();
 
         while is_recording.load(Ordering::Relaxed) {
             let loop_start = Instant::now();
@@ -105,7 +113,8 @@ pub fn start_input_tracking(
             // Only save cursor if we haven't seen this exact image before
             if !saved_cursor_hashes.contains(&cursor_hash) && cursor_hash != 0 {
                 if let Some(ref cursor) = cursor_opt {
-                    if let Some(info) = capture_cursor_png(cursor, &cursor_id, &cursors_dir) {
+                    let kind = standard_kinds.get(&cursor_hash).copied();
+                    if let Some(info) = capture_cursor_png(cursor, &cursor_id, kind, &cursors_dir) {
                         cursors.lock().insert(cursor_id.clone(), info);
                         saved_cursor_hashes.insert(cursor_hash);
                     }
@@ -172,6 +181,223 @@ pub fn start_input_tracking(
     Ok(handle)
 }
 
+/// How long the keyboard thread's run loop runs before checking whether
+/// recording stopped
+const KEY_RUN_LOOP_SLICE: Duration = Duration::from_millis(100);
+
+/// Start keystroke tracking thread (macOS)
+///
+/// Key presses come from a listen-only CGEventTap on the thread's run loop.
+/// Modifier keys don't send key down/up events; they're recorded from the
+/// flags-changed events instead. Auto-repeated key downs are skipped.
+pub fn start_keyboard_tracking(
+    is_recording: Arc<AtomicBool>,
+    keys: Arc<ParkingMutex<Vec<KeyPress>>>,
+    recorder: KeystrokeRecorder,
+    start_time: Instant,
+    unix_ms_fn: fn() -> u64,
+) -> RecordingResult<std::thread::JoinHandle<()>> {
+    // The tap has to be created on the thread whose run loop it's added to;
+    // report back whether that worked
+    let (started_tx, started_rx) = std::sync::mpsc::channel();
+
+    let handle = std::thread::spawn(move || {
+        let tap = CGEventTap::new(
+            CGEventTapLocation::Session,
+            CGEventTapPlacement::HeadInsertEventTap,
+            CGEventTapOptions::ListenOnly,
+            vec![
+                CGEventType::KeyDown,
+                CGEventType::KeyUp,
+                CGEventType::FlagsChanged,
+            ],
+            |_proxy, event_type, event| {
+                if let Some(key) = key_press(event_type, event, start_time, unix_ms_fn) {
+                    if let Some(key) = recorder.record(key) {
+                        keys.lock().push(key);
+                    }
+                }
+                None
+            },
+        );
+        let tap = match tap {
+            Ok(tap) => tap,
+            Err(()) => {
+                let _ = started_tx.send(Err("Failed to create the keyboard event tap".to_string()));
+                return;
+            }
+        };
+        let source = match tap.mach_port.create_runloop_source(0) {
+            Ok(source) => source,
+            Err(()) => {
+                let _ = started_tx.send(Err("Failed to add the keyboard event tap".to_string()));
+                return;
+            }
+        };
+        unsafe {
+            CFRunLoop::get_current().add_source(&source, kCFRunLoopCommonModes);
+        }
+        tap.enable();
+        let _ = started_tx.send(Ok(()));
+
+        tracing::info!("macOS keyboard tracking started");
+        while is_recording.load(Ordering::Relaxed) {
+            let mode = unsafe { kCFRunLoopDefaultMode };
+            let _ = CFRunLoop::run_in_mode(mode, KEY_RUN_LOOP_SLICE, false);
+        }
+        tracing::info!("macOS keyboard tracking thread stopped");
+    });
+
+    match started_rx.recv() {
+        Ok(Ok(())) => Ok(handle),
+        Ok(Err(reason)) => Err(RecordingError::PlatformError(reason)),
+        Err(_) => Err(RecordingError::PlatformError(
+            "Keyboard tracking thread exited".to_string(),
+        )),
+    }
+}
+
+/// Key press for an event from the keyboard tap, if it's one to record
+fn key_press(
+    event_type: CGEventType,
+    event: &CGEvent,
+    start_time: Instant,
+    unix_ms_fn: fn() -> u64,
+) -> Option<KeyPress> {
+    let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) as u16;
+    let flags = event.get_flags();
+    let (key, down) = match event_type {
+        CGEventType::KeyDown => {
+            if event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0 {
+                return None;
+            }
+            (key_name(keycode)?, true)
+        }
+        CGEventType::KeyUp => (key_name(keycode)?, false),
+        CGEventType::FlagsChanged => {
+            let (key, flag) = modifier_key(keycode)?;
+            (key, flags.contains(flag))
+        }
+        _ => return None,
+    };
+
+    Some(KeyPress {
+        key: key.to_string(),
+        event_type: if down { "down" } else { "up" }.to_string(),
+        active_modifiers: modifiers_from_event_flags(flags),
+        masked: false,
+        process_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
+        unix_time_ms: unix_ms_fn(),
+    })
+}
+
+/// Modifier key of a flags-changed event and the flag it sets
+fn modifier_key(keycode: u16) -> Option<(&'static str, CGEventFlags)> {
+    match keycode {
+        54 | 55 => Some(("meta", CGEventFlags::CGEventFlagCommand)),
+        56 | 60 => Some(("shift", CGEventFlags::CGEventFlagShift)),
+        57 => Some(("capsLock", CGEventFlags::CGEventFlagAlphaShift)),
+        58 | 61 => Some(("alt", CGEventFlags::CGEventFlagAlternate)),
+        59 | 62 => Some(("control", CGEventFlags::CGEventFlagControl)),
+        63 => Some(("fn", CGEventFlags::CGEventFlagSecondaryFn)),
+        _ => None,
+    }
+}
+
+/// Name of a (non-modifier) key by its virtual keycode. Keycodes are
+/// positions on the keyboard, named after the US layout.
+fn key_name(keycode: u16) -> Option<&'static str> {
+    Some(match keycode {
+        0 => "a",
+        1 => "s",
+        2 => "d",
+        3 => "f",
+        4 => "h",
+        5 => "g",
+        6 => "z",
+        7 => "x",
+        8 => "c",
+        9 => "v",
+        11 => "b",
+        12 => "q",
+        13 => "w",
+        14 => "e",
+        15 => "r",
+        16 => "y",
+        17 => "t",
+        18 => "1",
+        19 => "2",
+        20 => "3",
+        21 => "4",
+        22 => "6",
+        23 => "5",
+        24 => "=",
+        25 => "9",
+        26 => "7",
+        27 => "-",
+        28 => "8",
+        29 => "0",
+        30 => "]",
+        31 => "o",
+        32 => "u",
+        33 => "[",
+        34 => "i",
+        35 => "p",
+        36 | 76 => "return",
+        37 => "l",
+        38 => "j",
+        39 => "'",
+        40 => "k",
+        41 => ";",
+        42 => "\\",
+        43 => ",",
+        44 => "/",
+        45 => "n",
+        46 => "m",
+        47 => ".",
+        48 => "tab",
+        49 => "space",
+        50 => "`",
+        51 => "backspace",
+        53 => "escape",
+        96 => "f5",
+        97 => "f6",
+        98 => "f7",
+        99 => "f3",
+        100 => "f8",
+        101 => "f9",
+        103 => "f11",
+        109 => "f10",
+        111 => "f12",
+        115 => "home",
+        116 => "pageUp",
+        117 => "delete",
+        118 => "f4",
+        119 => "end",
+        120 => "f2",
+        121 => "pageDown",
+        122 => "f1",
+        123 => "arrowLeft",
+        124 => "arrowRight",
+        125 => "arrowDown",
+        126 => "arrowUp",
+        _ => return None,
+    })
+}
+
+fn modifiers_from_event_flags(flags: CGEventFlags) -> Vec<String> {
+    [
+        (CGEventFlags::CGEventFlagShift, "shift"),
+        (CGEventFlags::CGEventFlagControl, "control"),
+        (CGEventFlags::CGEventFlagAlternate, "alt"),
+        (CGEventFlags::CGEventFlagCommand, "meta"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags.contains(*flag))
+    .map(|(_, name)| name.to_string())
+    .collect()
+}
+
 /// Generate a stable cursor ID and hash based on image content.
 /// Returns (cursor_id, image_hash) where the hash is used for deduplication.
 fn cursor_id_and_hash(cursor: &Retained<NSCursor>) -> (String, u64) {
@@ -207,16 +433,73 @@ fn cursor_id_and_hash(cursor: &Retained<NSCursor>) -> (String, u64) {
     }
 }
 
-fn capture_cursor_png(cursor: &Retained<NSCursor>, cursor_id: &str, cursors_dir: &PathBuf) -> Option<CursorInfo> {
+/// Image hashes of AppKit's standard cursors, to recognize which one is shown.
+/// Best effort: the system cursor only matches if it uses the same image.
+fn standard_cursor_kinds() -> HashMap<u64, CursorKind> {
+    let standard = unsafe {
+        [
+            (NSCursor::arrowCursor(), CursorKind::Arrow),
+            (NSCursor::pointingHandCursor(), CursorKind::Pointer),
+            (NSCursor::IBeamCursor(), CursorKind::Text),
+            (NSCursor::crosshairCursor(), CursorKind::Crosshair),
+            (NSCursor::openHandCursor(), CursorKind::OpenHand),
+            (NSCursor::closedHandCursor(), CursorKind::ClosedHand),
+            (NSCursor::operationNotAllowedCursor(), CursorKind::NotAllowed),
+            (NSCursor::resizeLeftRightCursor(), CursorKind::ResizeLeftRight),
+            (NSCursor::resizeUpDownCursor(), CursorKind::ResizeUpDown),
+        ]
+    };
+    standard
+        .iter()
+        .map(|(cursor, kind)| (cursor_id_and_hash(cursor).1, *kind))
+        .filter(|(hash, _)| *hash != 0)
+        .collect()
+}
+
+fn capture_cursor_png(
+    cursor: &Retained<NSCursor>,
+    cursor_id: &str,
+    kind: Option<CursorKind>,
+    cursors_dir: &PathBuf,
+) -> Option<CursorInfo> {
+    let png = encode_cursor_png(cursor)?;
+
+    let file_name = format!("{}.png", cursor_id);
+    let image_path = cursors_dir.join(&file_name);
+
+    if std::fs::write(&image_path, &png.data).is_err() {
+        return None;
+    }
+
+    // Store dimensions and hotspot in PIXEL coordinates (matching the PNG file)
+    // This ensures the playback side can use these values directly with the image
+    Some(CursorInfo {
+        id: cursor_id.to_string(),
+        image_path: image_path.to_string_lossy().to_string(),
+        hotspot_x: png.hotspot_x,
+        hotspot_y: png.hotspot_y,
+        width: png.width,
+        height: png.height,
+        kind,
+    })
+}
+
+/// PNG-encoded cursor image with its hotspot, in pixel coordinates
+struct CursorPng {
+    data: Vec<u8>,
+    hotspot_x: f64,
+    hotspot_y: f64,
+    width: u32,
+    height: u32,
+}
+
+fn encode_cursor_png(cursor: &Retained<NSCursor>) -> Option<CursorPng> {
     // All NSImage/NSBitmapImageRep calls require unsafe in objc2 v0.2
     unsafe {
         let hotspot = cursor.hotSpot();
         let image: Retained<NSImage> = cursor.image();
         let logical_size = image.size();  // Logical points (e.g., 32x32)
 
-        let file_name = format!("{}.png", cursor_id);
-        let image_path = cursors_dir.join(&file_name);
-
         // Convert NSImage -> TIFF NSData
         let tiff_data = image.TIFFRepresentation()?;
 
@@ -253,23 +536,52 @@ fn capture_cursor_png(cursor: &Retained<NSCursor>, cursor_id: &str, cursors_dir:
             len as _,
         );
 
-        if std::fs::write(&image_path, &buf).is_err() {
-            return None;
-        }
-
-        // Store dimensions and hotspot in PIXEL coordinates (matching the PNG file)
-        // This ensures the playback side can use these values directly with the image
-        Some(CursorInfo {
-            id: cursor_id.to_string(),
-            image_path: image_path.to_string_lossy().to_string(),
-            hotspot_x: hotspot.x * scale_x,  // Convert to pixel coordinates
-            hotspot_y: hotspot.y * scale_y,  // Convert to pixel coordinates
-            width: pixel_width,               // Actual PNG pixel width
-            height: pixel_height,             // Actual PNG pixel height
+        Some(CursorPng {
+            data: buf,
+            hotspot_x: hotspot.x * scale_x,
+            hotspot_y: hotspot.y * scale_y,
+            width: pixel_width,
+            height: pixel_height,
         })
     }
 }
 
+/// Snapshot the current system cursor and its position on the given display.
+///
+/// Uses the same coordinate transform as the input tracking loop, so the
+/// position is in display pixels with a top-left origin.
+pub(crate) fn current_cursor_snapshot(display_id: u32) -> Option<CursorSnapshot> {
+    let display = CGDisplay::new(display_id);
+    let bounds = display.bounds();
+    let scale_factor = display.pixels_high() as f64 / bounds.size.height;
+
+    let pos = unsafe { NSEvent::mouseLocation() };
+    let x = (pos.x - bounds.origin.x) * scale_factor;
+    let y = (bounds.size.height - pos.y) * scale_factor;
+
+    let cursor = unsafe { NSCursor::currentSystemCursor() }?;
+    let png = encode_cursor_png(&cursor)?;
+
+    Some(CursorSnapshot {
+        png_data: png.data,
+        x,
+        y,
+        hotspot_x: png.hotspot_x,
+        hotspot_y: png.hotspot_y,
+    })
+}
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn IsSecureEventInputEnabled() -> u8;
+}
+
+/// Whether any app has secure event input enabled (password fields, secure
+/// text entry in terminals, etc.)
+pub(crate) fn is_secure_input_active() -> bool {
+    unsafe { IsSecureEventInputEnabled() != 0 }
+}
+
 fn modifiers_from_flags(flags: objc2_app_kit::NSEventModifierFlags) -> Vec<String> {
     use objc2_app_kit::NSEventModifierFlags;
     let mut v = Vec::new();
@@ -7,9 +7,11 @@ pub mod screen;
 pub mod system_audio;
 pub mod input;
 pub mod webcam;
+pub mod window;
 
 pub use permissions::*;
 pub use screen::*;
 pub use system_audio::*;
 pub use input::*;
 pub use webcam::*;
+pub use window::*;
@@ -1,6 +1,13 @@
 //! macOS permission handling
 //!
-//! Handles screen recording and other permissions on macOS.
+//! Handles screen recording and other permissions on macOS. Camera and
+//! microphone access go through AVFoundation's authorization API.
+
+use crate::capture::traits::{MediaPermission, PermissionStatus};
+use block2::RcBlock;
+use objc2::runtime::Bool;
+use objc2::{class, msg_send};
+use objc2_foundation::NSString;
 
 /// Check if screen recording permission is granted
 pub fn has_screen_recording_permission() -> bool {
@@ -48,7 +55,29 @@ pub fn has_accessibility_permission() -> bool {
 }
 
 /// Request accessibility permission
-pub fn request_accessibility_permission() {
+///
+/// Shows the system prompt pointing the user to the Accessibility settings
+/// if the app isn't trusted yet. Returns whether it is trusted now; the
+/// user's choice only takes effect after they toggle the app in Settings.
+pub fn request_accessibility_permission() -> bool {
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::string::{CFString, CFStringRef};
+
+    extern "C" {
+        static kAXTrustedCheckOptionPrompt: CFStringRef;
+        fn AXIsProcessTrustedWithOptions(options: CFDictionaryRef) -> bool;
+    }
+    unsafe {
+        let prompt = CFString::wrap_under_get_rule(kAXTrustedCheckOptionPrompt);
+        let options = CFDictionary::from_CFType_pairs(&[(prompt, CFBoolean::true_value())]);
+        AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef())
+    }
+}
+
+/// Open System Preferences to the Accessibility pane
+pub fn open_accessibility_preferences() {
     let url = "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
     if let Ok(output) = std::process::Command::new("open").arg(url).output() {
         if !output.status.success() {
@@ -57,25 +86,71 @@ pub fn request_accessibility_permission() {
     }
 }
 
+/// AVFoundation's media type for a capture permission
+fn av_media_type(kind: MediaPermission) -> &'static NSString {
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeVideo: &'static NSString;
+        static AVMediaTypeAudio: &'static NSString;
+    }
+    unsafe {
+        match kind {
+            MediaPermission::Camera => AVMediaTypeVideo,
+            MediaPermission::Microphone => AVMediaTypeAudio,
+        }
+    }
+}
+
+/// Current camera or microphone permission, without prompting
+pub fn media_permission_status(kind: MediaPermission) -> PermissionStatus {
+    // AVAuthorizationStatus: NotDetermined, Restricted, Denied, Authorized
+    let status: isize = unsafe {
+        msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: av_media_type(kind)]
+    };
+    match status {
+        0 => PermissionStatus::Undetermined,
+        3 => PermissionStatus::Granted,
+        _ => PermissionStatus::Denied,
+    }
+}
+
+/// Ask for camera or microphone access
+///
+/// Shows the system prompt if the user hasn't decided yet and blocks until
+/// they answer; otherwise returns the earlier decision straight away.
+pub fn request_media_permission(kind: MediaPermission) -> PermissionStatus {
+    let status = media_permission_status(kind);
+    if status != PermissionStatus::Undetermined {
+        return status;
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handler = RcBlock::new(move |granted: Bool| {
+        let _ = tx.send(granted.as_bool());
+    });
+    unsafe {
+        let _: () = msg_send![
+            class!(AVCaptureDevice),
+            requestAccessForMediaType: av_media_type(kind),
+            completionHandler: &*handler
+        ];
+    }
+    match rx.recv() {
+        Ok(true) => PermissionStatus::Granted,
+        Ok(false) => PermissionStatus::Denied,
+        Err(_) => media_permission_status(kind),
+    }
+}
+
 /// Check if camera permission is granted
-/// 
-/// Uses a simple check via system_profiler to verify camera access.
-/// The actual permission check happens when nokhwa tries to access the camera.
 pub fn has_camera_permission() -> bool {
-    // On macOS, camera permission is typically requested when first accessing the camera.
-    // We return true here and let the camera library handle the permission request.
-    // This is because AVFoundation permission checking requires additional bindings.
-    true
+    media_permission_status(MediaPermission::Camera) == PermissionStatus::Granted
 }
 
-/// Request camera permission
-/// 
-/// Opens the Camera preferences pane if permission is needed.
-/// Returns true (actual permission is checked by the camera library).
+/// Request camera permission, prompting if the user hasn't decided yet.
+/// Returns whether access was granted.
 pub fn request_camera_permission() -> bool {
-    // The nokhwa library will trigger the system permission dialog when accessing the camera.
-    // We just return true here - if permission is denied, the camera open will fail.
-    true
+    request_media_permission(MediaPermission::Camera) == PermissionStatus::Granted
 }
 
 /// Open System Preferences to the Camera pane
@@ -1,18 +1,31 @@
-//! macOS screen capture using CGWindowListCreateImage
+//! macOS screen capture using ScreenCaptureKit
 //!
-//! This module provides screen capture functionality using Core Graphics.
-//! Frames are captured and encoded to H.264 segments using FFmpeg.
-
+//! Display frames are streamed from ScreenCaptureKit at the display's native
+//! resolution and encoded to H.264 with FFmpeg. When the stream can't be
+//! started, capture falls back to polling Core Graphics for screenshots.
+//! Rotated displays are captured as shown, at their rotated size.
+
+use crate::capture::display_watch::DisplayWatcher;
+use crate::capture::frame::{crop_frame, region_in_frame, ContentRect, Rotation};
+use crate::capture::frame_rate::{display_fps, FrameClock, FrameRateMeter, DEFAULT_MAX_FPS};
 use crate::capture::traits::DisplayInfo;
 use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::coordinator::RecordingEvent;
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::segment::live_movflags_args;
+use crate::recorder::state::{CaptureQuality, VideoEncodingConfig};
+use crate::utils::process::{track, TrackedChild};
 use async_trait::async_trait;
 use core_graphics::display::{kCGWindowListOptionOnScreenOnly, CGDisplay};
+use objc2_app_kit::NSEvent;
 use parking_lot::Mutex as ParkingMutex;
+use screencapturekit::prelude::*;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
+use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
 /// Get list of available displays
 pub fn get_displays() -> Vec<DisplayInfo> {
@@ -44,13 +57,34 @@ pub fn get_displays() -> Vec<DisplayInfo> {
                 scale_factor: display.pixels_high() as f64 / bounds.size.height,
                 is_primary: is_main,
                 refresh_rate,
+                rotation: display_rotation(id),
             }
         })
         .collect()
 }
 
+/// ID of the display the mouse cursor is currently on
+pub fn display_under_cursor() -> Option<u32> {
+    // NSEvent reports global points with a bottom-left origin on the main
+    // display; CGDisplay bounds use a top-left origin
+    let pos = unsafe { NSEvent::mouseLocation() };
+    let main_height = CGDisplay::main().bounds().size.height;
+    let (x, y) = (pos.x, main_height - pos.y);
+
+    CGDisplay::active_displays()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|&id| {
+            let b = CGDisplay::new(id).bounds();
+            x >= b.origin.x
+                && x < b.origin.x + b.size.width
+                && y >= b.origin.y
+                && y < b.origin.y + b.size.height
+        })
+}
+
 /// Capture a single frame from a display using CGDisplayCreateImage
-fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+pub(crate) fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
     let display = CGDisplay::new(display_id);
     let bounds = display.bounds();
 
@@ -89,8 +123,8 @@ fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
 }
 
 /// FFmpeg encoder for HLS segment output
-struct FFmpegSegmentEncoder {
-    process: ParkingMutex<Option<Child>>,
+pub(super) struct FFmpegSegmentEncoder {
+    process: ParkingMutex<Option<TrackedChild>>,
     frame_count: AtomicU64,
     running: AtomicBool,
     output_dir: PathBuf,
@@ -98,10 +132,12 @@ struct FFmpegSegmentEncoder {
 }
 
 impl FFmpegSegmentEncoder {
-    fn new(
+    pub(super) fn new(
         width: u32,
         height: u32,
         fps: u32,
+        encoding: &VideoEncodingConfig,
+        quality: CaptureQuality,
         output_dir: &Path,
         segment_index: usize,
     ) -> Result<Self, std::io::Error> {
@@ -109,7 +145,7 @@ impl FFmpegSegmentEncoder {
         std::fs::create_dir_all(output_dir)?;
 
         let output_file = output_dir
-            .join(format!("recording-{segment_index}.mp4"))
+            .join(FileKind::ScreenVideo.conventional_name(segment_index))
             .to_string_lossy()
             .to_string();
 
@@ -127,11 +163,11 @@ impl FFmpegSegmentEncoder {
                 "-c:v", "libx264",               // H.264 codec
                 "-preset", "veryfast",           // Good balance of speed and compression
                 "-pix_fmt", "yuv420p",           // Output pixel format (required for compatibility)
-                "-crf", "18",                    // High quality (lower = better, 18 is visually lossless)
-                "-g", &(fps * 2).to_string(),    // GOP size = 2 seconds
-                "-movflags", "+faststart",       // Move moov atom to start for streaming
-                &output_file,
             ])
+            .args(encoding.screen_filter_args(fps, quality, None)) // Scaling, duplicate frames
+            .args(encoding.ffmpeg_args(fps, quality)) // CRF, GOP size and B-frames
+            .args(live_movflags_args())          // Playable while recording
+            .arg(&output_file)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped()) // Capture stderr for debugging
@@ -146,7 +182,7 @@ impl FFmpegSegmentEncoder {
         );
 
         Ok(Self {
-            process: ParkingMutex::new(Some(process)),
+            process: ParkingMutex::new(Some(track(process, "screen encoder"))),
             frame_count: AtomicU64::new(0),
             running: AtomicBool::new(true),
             output_dir: output_dir.to_path_buf(),
@@ -154,7 +190,7 @@ impl FFmpegSegmentEncoder {
         })
     }
 
-    fn write_frame(&self, data: &[u8]) -> bool {
+    pub(super) fn write_frame(&self, data: &[u8]) -> bool {
         if !self.running.load(Ordering::Relaxed) {
             return false;
         }
@@ -171,11 +207,11 @@ impl FFmpegSegmentEncoder {
         false
     }
 
-    fn frame_count(&self) -> u64 {
+    pub(super) fn frame_count(&self) -> u64 {
         self.frame_count.load(Ordering::Relaxed)
     }
 
-    fn finish(&self) -> Result<Vec<String>, std::io::Error> {
+    pub(super) fn finish(&self) -> Result<Vec<String>, std::io::Error> {
         self.running.store(false, Ordering::Relaxed);
         let mut guard = self.process.lock();
         if let Some(mut process) = guard.take() {
@@ -191,7 +227,7 @@ impl FFmpegSegmentEncoder {
 
         // Find the output file
         let output_file = self.output_dir
-            .join(format!("recording-{}.mp4", self.segment_index))
+            .join(FileKind::ScreenVideo.conventional_name(self.segment_index))
             .to_string_lossy()
             .to_string();
         
@@ -210,10 +246,111 @@ impl FFmpegSegmentEncoder {
     }
 }
 
-/// Display capture channel using CGWindowListCreateImage
+/// Native pixel size of a display as shown on screen, with even dimensions
+/// for the encoder
+fn display_pixel_size(display_id: u32) -> (u32, u32) {
+    let display = CGDisplay::new(display_id);
+    // Display modes are sized in the panel's native orientation; asking
+    // ScreenCaptureKit for that size squashes a portrait display
+    let rotation = display_rotation(display_id);
+    let (width, height) = display
+        .display_mode()
+        .map(|mode| rotation.rotated_size(mode.pixel_width() as u32, mode.pixel_height() as u32))
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .unwrap_or((display.pixels_wide() as u32, display.pixels_high() as u32));
+    (width & !1, height & !1)
+}
+
+/// How a display is turned from its native orientation
+fn display_rotation(display_id: u32) -> Rotation {
+    Rotation::from_degrees(CGDisplay::new(display_id).rotation())
+}
+
+/// Pixel size of a display, or None once it's no longer connected
+pub(crate) fn display_size(display_id: u32) -> Option<(u32, u32)> {
+    CGDisplay::active_displays()
+        .ok()?
+        .contains(&display_id)
+        .then(|| display_pixel_size(display_id))
+}
+
+/// Keeps a constant frame rate for a stream that only delivers frames when
+/// the screen changes, by repeating the last frame over the gaps
+struct FramePacer {
+    clock: FrameClock,
+    last_frame: ParkingMutex<Vec<u8>>,
+}
+
+impl FramePacer {
+    fn new(fps: u32) -> Self {
+        Self {
+            clock: FrameClock::new(fps),
+            last_frame: ParkingMutex::new(Vec::new()),
+        }
+    }
+
+    /// Repeat the last frame until the encoder has `count` frames
+    fn repeat_last_until(&self, encoder: &FFmpegSegmentEncoder, last: &[u8], count: u64) {
+        while !last.is_empty() && encoder.frame_count() < count {
+            if !encoder.write_frame(last) {
+                break;
+            }
+        }
+    }
+
+    /// Encode `frame`, first filling any gap since the previous one
+    fn write(&self, encoder: &FFmpegSegmentEncoder, frame: Vec<u8>) -> bool {
+        let mut last = self.last_frame.lock();
+        self.repeat_last_until(encoder, &last, self.clock.frames_due().saturating_sub(1));
+        let written = encoder.write_frame(&frame);
+        *last = frame;
+        written
+    }
+
+    /// Cover the time between the last frame and the end of the recording
+    fn finish(&self, encoder: &FFmpegSegmentEncoder) {
+        let last = self.last_frame.lock();
+        self.repeat_last_until(encoder, &last, self.clock.frames_due());
+    }
+}
+
+/// Receives display frames from ScreenCaptureKit and feeds the encoder
+struct DisplayOutputHandler {
+    encoder: Arc<FFmpegSegmentEncoder>,
+    pacer: Arc<FramePacer>,
+    is_recording: Arc<AtomicBool>,
+    /// Part of each frame to encode: the region, or the whole frame
+    rect: ContentRect,
+    dropped_frames: Arc<AtomicU64>,
+}
+
+impl SCStreamOutputTrait for DisplayOutputHandler {
+    fn did_output_sample_buffer(&self, sample_buffer: CMSampleBuffer, of_type: SCStreamOutputType) {
+        if of_type != SCStreamOutputType::Screen || !self.is_recording.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // Idle/blank status frames carry no image
+        let Some(pixel_buffer) = sample_buffer.image_buffer() else {
+            return;
+        };
+        let Ok(guard) = pixel_buffer.lock_read_only() else {
+            self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+
+        let written = crop_frame(guard.as_slice(), pixel_buffer.bytes_per_row(), self.rect)
+            .is_some_and(|frame| self.pacer.write(&self.encoder, frame));
+        if !written {
+            self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Display capture channel using ScreenCaptureKit
 ///
-/// This implementation captures frames using Core Graphics and
-/// encodes them to H.264 segments using FFmpeg.
+/// Frames are streamed from ScreenCaptureKit, or polled from Core Graphics
+/// when the stream is unavailable, and encoded to H.264 using FFmpeg.
 pub struct DisplayCaptureChannel {
     /// Channel identifier
     id: String,
@@ -236,9 +373,15 @@ pub struct DisplayCaptureChannel {
     /// FFmpeg encoder
     encoder: Option<Arc<FFmpegSegmentEncoder>>,
 
-    /// Capture task handle
+    /// Capture task handle (polling fallback)
     capture_handle: Option<tokio::task::JoinHandle<()>>,
 
+    /// ScreenCaptureKit stream
+    stream: ParkingMutex<Option<SCStream>>,
+
+    /// Keeps streamed frames at a constant rate
+    pacer: Option<Arc<FramePacer>>,
+
     /// Capture width
     width: u32,
 
@@ -247,6 +390,42 @@ pub struct DisplayCaptureChannel {
 
     /// Capture FPS
     fps: u32,
+
+    /// Keyframe and B-frame settings for the encoder
+    video_encoding: VideoEncodingConfig,
+
+    /// Frame rate, scaling and encoder quality preset
+    quality: CaptureQuality,
+
+    /// Frames that failed to capture or were skipped because capture fell behind
+    dropped_frames: Arc<AtomicU64>,
+
+    /// Frame rate encoded at, over the sessions so far
+    frame_rate: FrameRateMeter,
+
+    /// Highest frame rate to follow the display's refresh rate up to
+    max_fps: u32,
+
+    /// Re-pick the display under the cursor on resume
+    follow_cursor_on_resume: bool,
+
+    /// Record the system cursor into the frames
+    show_cursor: bool,
+
+    /// Leave the app's own windows (toolbar, overlays) out of the frames
+    exclude_app_windows: bool,
+
+    /// Part of the display to record, in display points
+    region: Option<ContentRect>,
+
+    /// `region` on the captured frames, once capture started
+    crop: Option<ContentRect>,
+
+    /// Where resolution changes and disconnects are reported
+    event_tx: Option<broadcast::Sender<RecordingEvent>>,
+
+    /// Watches the display for configuration changes while recording
+    watcher: Option<DisplayWatcher>,
 }
 
 impl DisplayCaptureChannel {
@@ -261,78 +440,200 @@ impl DisplayCaptureChannel {
             output_files: Arc::new(ParkingMutex::new(Vec::new())),
             encoder: None,
             capture_handle: None,
+            stream: ParkingMutex::new(None),
+            pacer: None,
             width: 1920,
             height: 1080,
             fps: 30,
+            video_encoding: VideoEncodingConfig::default(),
+            quality: CaptureQuality::default(),
+            dropped_frames: Arc::new(AtomicU64::new(0)),
+            frame_rate: FrameRateMeter::new(),
+            max_fps: DEFAULT_MAX_FPS,
+            follow_cursor_on_resume: false,
+            show_cursor: false,
+            exclude_app_windows: false,
+            region: None,
+            crop: None,
+            event_tx: None,
+            watcher: None,
         }
     }
-}
 
-#[async_trait]
-impl RecordingChannel for DisplayCaptureChannel {
-    fn id(&self) -> &str {
-        &self.id
+    /// Switch to the display under the cursor whenever recording resumes
+    pub fn follow_cursor_on_resume(mut self, follow: bool) -> Self {
+        self.follow_cursor_on_resume = follow;
+        self
     }
 
-    fn channel_type(&self) -> ChannelType {
-        ChannelType::Display
+    /// Record only this part of the display, in the coordinates reported by
+    /// `get_displays`
+    pub fn region(mut self, region: Option<ContentRect>) -> Self {
+        self.region = region;
+        self
     }
 
-    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
-        // Check permission first
-        if !super::permissions::has_screen_recording_permission() {
-            super::permissions::request_screen_recording_permission();
-            return Err(RecordingError::PermissionDenied(
-                "Screen recording permission not granted. Please allow in System Preferences."
-                    .to_string(),
-            ));
-        }
+    /// Keyframe and B-frame settings for the encoder
+    pub fn video_encoding(mut self, encoding: VideoEncodingConfig) -> Self {
+        self.video_encoding = encoding;
+        self
+    }
 
-        // Check if FFmpeg is available
-        if Command::new("ffmpeg").arg("-version").output().is_err() {
-            return Err(RecordingError::ConfigurationError(
-                "FFmpeg not found. Please install FFmpeg: brew install ffmpeg".to_string(),
-            ));
-        }
+    /// Frame rate, scaling and encoder quality preset
+    pub fn quality(mut self, quality: CaptureQuality) -> Self {
+        self.fps = quality.fps();
+        self.quality = quality;
+        self
+    }
 
-        // Get display info for resolution - use native (pixel) resolution for Retina displays
-        let display = CGDisplay::new(self.display_id);
-        self.width = display.pixels_wide() as u32;
-        self.height = display.pixels_high() as u32;
+    /// Highest frame rate to follow the display's refresh rate up to
+    pub fn max_fps(mut self, max_fps: u32) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
 
-        self.output_dir = Some(output_dir.to_path_buf());
-        self.session_index = session_index;
+    /// Record the system cursor into the frames instead of leaving it to
+    /// be drawn at export time
+    pub fn show_cursor(mut self, show: bool) -> Self {
+        self.show_cursor = show;
+        self
+    }
 
-        tracing::info!(
-            "Display capture channel initialized for display {} ({}x{})",
-            self.display_id,
-            self.width,
-            self.height
-        );
-        Ok(())
+    /// Leave the app's own windows out of the frames
+    pub fn exclude_app_windows(mut self, exclude: bool) -> Self {
+        self.exclude_app_windows = exclude;
+        self
     }
 
-    async fn start(&mut self) -> RecordingResult<()> {
-        if self.is_recording.load(Ordering::SeqCst) {
-            return Err(RecordingError::AlreadyRecording);
-        }
+    /// Part of frames of the given size to encode, if a region is set
+    fn crop_rect(&self, frame_width: u32, frame_height: u32) -> RecordingResult<Option<ContentRect>> {
+        let Some(region) = self.region else {
+            return Ok(None);
+        };
+        // The region is in points; frames are in (Retina) pixels
+        let bounds = CGDisplay::new(self.display_id).bounds();
+        region_in_frame(
+            region,
+            bounds.size.width as u32,
+            bounds.size.height as u32,
+            frame_width,
+            frame_height,
+        )
+        .map(Some)
+        .ok_or_else(|| {
+            RecordingError::ConfigurationError("Capture region is outside the display".to_string())
+        })
+    }
 
-        let output_dir = self.output_dir.clone().ok_or_else(|| {
-            RecordingError::ConfigurationError("Output directory not set".to_string())
+    /// Stream frames from ScreenCaptureKit into a new encoder
+    fn start_stream(&mut self, output_dir: &Path) -> RecordingResult<()> {
+        let content = SCShareableContent::get().map_err(|e| {
+            RecordingError::CaptureError(format!("Failed to get shareable content: {:?}", e))
         })?;
+        let display = content
+            .displays()
+            .into_iter()
+            .find(|d| d.display_id() == self.display_id)
+            .ok_or_else(|| {
+                RecordingError::DeviceNotFound(format!("Display {} not found", self.display_id))
+            })?;
+
+        let (frame_width, frame_height) = display_pixel_size(self.display_id);
+        self.crop = self.crop_rect(frame_width, frame_height)?;
+        let rect = self.crop.unwrap_or(ContentRect {
+            x: 0,
+            y: 0,
+            width: frame_width,
+            height: frame_height,
+        });
 
+        let encoder = Arc::new(
+            FFmpegSegmentEncoder::new(
+                rect.width,
+                rect.height,
+                self.fps,
+                &self.video_encoding,
+                self.quality,
+                output_dir,
+                self.session_index,
+            )
+            .map_err(|e| RecordingError::CaptureError(format!("Failed to start FFmpeg: {}", e)))?,
+        );
+
+        // The app's own windows are also marked not to be shared, but macOS 15
+        // and later ignore that for ScreenCaptureKit. Leaving the whole
+        // application out covers windows it opens mid-recording as well.
+        let own_pid = std::process::id() as i32;
+        let applications = content.applications();
+        let own_app: Vec<&SCRunningApplication> = applications
+            .iter()
+            .filter(|app| self.exclude_app_windows && app.process_id() == own_pid)
+            .collect();
+        let filter = SCContentFilter::create()
+            .with_display(&display)
+            .with_excluding_applications(&own_app, &[])
+            .build();
+        let config = SCStreamConfiguration::new()
+            .with_width(frame_width)
+            .with_height(frame_height)
+            .with_pixel_format(PixelFormat::BGRA)
+            .with_minimum_frame_interval(&CMTime::new(1, self.fps as i32))
+            // Unless asked for, the cursor is composited at export time from
+            // input tracking instead
+            .with_shows_cursor(self.show_cursor);
+        let mut stream = SCStream::new(&filter, &config);
+
+        let pacer = Arc::new(FramePacer::new(self.fps));
+        stream.add_output_handler(
+            DisplayOutputHandler {
+                encoder: encoder.clone(),
+                pacer: pacer.clone(),
+                is_recording: self.is_recording.clone(),
+                rect,
+                dropped_frames: self.dropped_frames.clone(),
+            },
+            SCStreamOutputType::Screen,
+        );
+
+        self.is_recording.store(true, Ordering::SeqCst);
+        if let Err(e) = stream.start_capture() {
+            self.is_recording.store(false, Ordering::SeqCst);
+            // Nothing was encoded; the fallback writes the same file
+            let _ = encoder.finish();
+            return Err(RecordingError::CaptureError(format!(
+                "Failed to start display stream: {:?}",
+                e
+            )));
+        }
+
+        self.width = rect.width;
+        self.height = rect.height;
+        *self.stream.lock() = Some(stream);
+        self.pacer = Some(pacer);
+        self.encoder = Some(encoder);
+        Ok(())
+    }
+
+    /// Poll CGDisplay for frames on a timer. Fallback for when the
+    /// ScreenCaptureKit stream can't be started.
+    fn start_polling(&mut self, output_dir: &Path) -> RecordingResult<()> {
         // Capture first frame to determine actual dimensions
         let (first_frame, actual_width, actual_height) = capture_display_frame(self.display_id)
             .ok_or_else(|| RecordingError::CaptureError("Failed to capture initial frame".to_string()))?;
         
+        let crop = self.crop_rect(actual_width, actual_height)?;
+        self.crop = crop;
+
         // Update dimensions to match actual capture
-        self.width = actual_width;
-        self.height = actual_height;
+        self.width = crop.map_or(actual_width, |c| c.width);
+        self.height = crop.map_or(actual_height, |c| c.height);
         
         tracing::info!(
-            "Actual capture dimensions: {}x{} (from first frame)",
+            "Actual capture dimensions: {}x{} (from first frame), recording {}x{}",
             actual_width,
-            actual_height
+            actual_height,
+            self.width,
+            self.height
         );
 
         // Create FFmpeg encoder with actual dimensions
@@ -341,16 +642,27 @@ impl RecordingChannel for DisplayCaptureChannel {
                 self.width,
                 self.height,
                 self.fps,
-                &output_dir,
+                &self.video_encoding,
+                self.quality,
+                output_dir,
                 self.session_index,
             )
             .map_err(|e| RecordingError::CaptureError(format!("Failed to start FFmpeg: {}", e)))?,
         );
         
         // Write the first frame
-        let expected_size = (self.width * self.height * 4) as usize;
-        if first_frame.len() >= expected_size {
-            encoder.write_frame(&first_frame[..expected_size]);
+        match crop {
+            Some(rect) => {
+                if let Some(cropped) = crop_frame(&first_frame, actual_width as usize * 4, rect) {
+                    encoder.write_frame(&cropped);
+                }
+            }
+            None => {
+                let expected_size = (self.width * self.height * 4) as usize;
+                if first_frame.len() >= expected_size {
+                    encoder.write_frame(&first_frame[..expected_size]);
+                }
+            }
         }
         
         self.encoder = Some(encoder.clone());
@@ -362,19 +674,40 @@ impl RecordingChannel for DisplayCaptureChannel {
         let fps = self.fps;
         let width = self.width;
         let height = self.height;
+        let dropped_frames = self.dropped_frames.clone();
 
         let handle = tokio::spawn(async move {
-            let frame_interval = std::time::Duration::from_millis(1000 / fps as u64);
+            let frame_interval = std::time::Duration::from_secs_f64(1.0 / fps as f64);
             let expected_size = (width * height * 4) as usize; // BGRA = 4 bytes per pixel
+            let clock = FrameClock::new(fps);
+            let mut last = Vec::new();
 
             while is_recording.load(Ordering::SeqCst) {
                 let start = std::time::Instant::now();
 
                 // Capture frame
-                if let Some((data, _w, _h)) = capture_display_frame(display_id) {
-                    if data.len() >= expected_size {
-                        encoder.write_frame(&data[..expected_size]);
+                let frame = match (capture_display_frame(display_id), crop) {
+                    (Some((data, w, _h)), Some(rect)) => crop_frame(&data, w as usize * 4, rect),
+                    (Some((mut data, _w, _h)), None) if data.len() >= expected_size => {
+                        data.truncate(expected_size);
+                        Some(data)
+                    }
+                    _ => None,
+                };
+                let written = frame.is_some_and(|frame| {
+                    // Repeat the last frame over any slots capture fell behind
+                    // on, so the video keeps time with the audio
+                    for _ in 0..clock.repeats_before_next(encoder.frame_count()) {
+                        if last.is_empty() || !encoder.write_frame(&last) {
+                            break;
+                        }
                     }
+                    let written = encoder.write_frame(&frame);
+                    last = frame;
+                    written
+                });
+                if !written {
+                    dropped_frames.fetch_add(1, Ordering::Relaxed);
                 }
 
                 // Log progress periodically
@@ -393,11 +726,90 @@ impl RecordingChannel for DisplayCaptureChannel {
                 let elapsed = start.elapsed();
                 if elapsed < frame_interval {
                     tokio::time::sleep(frame_interval - elapsed).await;
+                } else {
+                    // Every full interval beyond the first is a frame we never captured
+                    let missed = (elapsed.as_nanos() / frame_interval.as_nanos()) as u64;
+                    dropped_frames.fetch_add(missed.saturating_sub(1), Ordering::Relaxed);
                 }
             }
         });
 
         self.capture_handle = Some(handle);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordingChannel for DisplayCaptureChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Display
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        // Check permission first
+        if !super::permissions::has_screen_recording_permission() {
+            super::permissions::request_screen_recording_permission();
+            return Err(RecordingError::PermissionDenied(
+                "Screen recording permission not granted. Please allow in System Preferences."
+                    .to_string(),
+            ));
+        }
+
+        // Check if FFmpeg is available
+        if Command::new("ffmpeg").arg("-version").output().is_err() {
+            return Err(RecordingError::FfmpegMissing(
+                "FFmpeg not found. Please install FFmpeg: brew install ffmpeg".to_string(),
+            ));
+        }
+
+        // Get display info for resolution - use native (pixel) resolution for Retina displays
+        let display = CGDisplay::new(self.display_id);
+        self.width = display.pixels_wide() as u32;
+        self.height = display.pixels_high() as u32;
+        // Some built-in panels report 0, leaving the preset's rate
+        let refresh_rate = display
+            .display_mode()
+            .map(|mode| mode.refresh_rate().round() as u32);
+        self.fps = display_fps(self.quality, refresh_rate, self.max_fps);
+
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+
+        tracing::info!(
+            "Display capture channel initialized for display {} ({}x{} @ {}fps)",
+            self.display_id,
+            self.width,
+            self.height,
+            self.fps
+        );
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if self.is_recording.load(Ordering::SeqCst) {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        let output_dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+
+        self.frame_rate.start();
+        if let Err(e) = self.start_stream(&output_dir) {
+            tracing::warn!(
+                "ScreenCaptureKit display capture unavailable, falling back to polling: {}",
+                e
+            );
+            self.start_polling(&output_dir)?;
+        }
+        self.watcher = self
+            .event_tx
+            .clone()
+            .and_then(|tx| DisplayWatcher::start(&self.id, self.display_id, display_size, tx));
 
         tracing::info!(
             "Display capture started for display {} ({}x{} @ {}fps)",
@@ -415,6 +827,15 @@ impl RecordingChannel for DisplayCaptureChannel {
         }
 
         self.is_recording.store(false, Ordering::SeqCst);
+        if let Some(watcher) = self.watcher.take() {
+            watcher.stop();
+        }
+
+        if let Some(stream) = self.stream.lock().take() {
+            if let Err(e) = stream.stop_capture() {
+                tracing::warn!("Error stopping display capture stream: {:?}", e);
+            }
+        }
 
         // Wait for capture task to finish
         if let Some(handle) = self.capture_handle.take() {
@@ -423,6 +844,10 @@ impl RecordingChannel for DisplayCaptureChannel {
 
         // Finish encoding and collect output files
         if let Some(ref encoder) = self.encoder {
+            if let Some(pacer) = self.pacer.take() {
+                pacer.finish(encoder);
+            }
+            self.frame_rate.stop(encoder.frame_count());
             let segments = encoder.finish().map_err(|e| {
                 RecordingError::CaptureError(format!("Failed to finish encoding: {}", e))
             })?;
@@ -442,6 +867,16 @@ impl RecordingChannel for DisplayCaptureChannel {
 
     async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
         self.session_index = session_index;
+        if self.follow_cursor_on_resume {
+            if let Some(display_id) = display_under_cursor().filter(|&id| id != self.display_id) {
+                tracing::info!(
+                    "Cursor moved to display {}, switching capture from display {}",
+                    display_id,
+                    self.display_id
+                );
+                self.display_id = display_id;
+            }
+        }
         self.start().await
     }
 
@@ -452,4 +887,27 @@ impl RecordingChannel for DisplayCaptureChannel {
     fn output_files(&self) -> Vec<String> {
         self.output_files.lock().clone()
     }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<RecordingEvent>) {
+        self.event_tx = Some(event_tx);
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: Some(self.display_id.to_string()),
+            width: Some(self.width),
+            height: Some(self.height),
+            fps: Some(self.fps),
+            region: self.region,
+            crop: self.crop,
+            downscale: Some(self.quality.downscale()).filter(|&n| n > 1),
+            achieved_fps: self.frame_rate.achieved_fps(),
+            shows_cursor: self.show_cursor,
+            ..Default::default()
+        }
+    }
+
+    fn dropped_frames(&self) -> Option<u64> {
+        Some(self.dropped_frames.load(Ordering::Relaxed))
+    }
 }
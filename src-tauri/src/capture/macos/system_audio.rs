@@ -1,7 +1,10 @@
 //! macOS System Audio Capture using ScreenCaptureKit
 //!
-//! Uses Apple's ScreenCaptureKit framework (macOS 12.3+) to capture system audio
-//! natively without requiring external virtual audio devices like BlackHole.
+//! Uses Apple's ScreenCaptureKit framework to capture system audio natively
+//! without requiring external virtual audio devices like BlackHole.
+//! ScreenCaptureKit only captures audio from macOS 13, so availability is
+//! checked against the running OS version; on older systems the channel
+//! records nothing and `system_audio_support` says why.
 //!
 //! ## Audio Format Handling
 //!
@@ -10,23 +13,85 @@
 //! - **Non-interleaved**: Separate buffers per channel (LLLL... and RRRR...)
 //!
 //! This module handles both formats and converts to interleaved stereo for FFmpeg.
+//! Sample rate and channel count are requested from ScreenCaptureKit directly,
+//! so a configured mono or 44.1kHz track needs no conversion here.
+//!
+//! ## App Filtering
+//!
+//! ScreenCaptureKit only delivers audio from the applications its content
+//! filter includes, so an `AudioAppFilter` is applied by building the filter
+//! from the display with those apps included or excluded.
 
-use crate::capture::audio::AudioEncoder;
+use crate::capture::audio::{AudioEncoder, AudioFormat};
+use crate::capture::traits::{AudioCaptureApp, SystemAudioSupport};
 use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::state::{AudioAppFilter, AudioAppFilterMode, AudioEncodingConfig};
 use async_trait::async_trait;
 use parking_lot::Mutex as ParkingMutex;
 use screencapturekit::cm::{AudioBuffer, AudioBufferList, CMFormatDescription};
 use screencapturekit::prelude::*;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+
+/// First macOS version whose ScreenCaptureKit delivers audio
+const MIN_AUDIO_MACOS_VERSION: (u32, u32) = (13, 0);
+
+/// Running macOS version as (major, minor), read once from `sw_vers`
+fn macos_version() -> Option<(u32, u32)> {
+    static VERSION: OnceLock<Option<(u32, u32)>> = OnceLock::new();
+    *VERSION.get_or_init(|| {
+        let output = Command::new("sw_vers").arg("-productVersion").output().ok()?;
+        let version = String::from_utf8_lossy(&output.stdout);
+        let mut parts = version.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+        Some((major, minor))
+    })
+}
+
+/// Whether system audio can be recorded, and why not
+///
+/// If the version can't be read, capture is attempted and any failure is
+/// reported when recording starts.
+pub fn system_audio_support() -> SystemAudioSupport {
+    match macos_version() {
+        Some(version) if version < MIN_AUDIO_MACOS_VERSION => SystemAudioSupport::unavailable(
+            format!(
+                "System audio capture needs macOS {}.{} or later (this Mac runs {}.{})",
+                MIN_AUDIO_MACOS_VERSION.0, MIN_AUDIO_MACOS_VERSION.1, version.0, version.1
+            ),
+        ),
+        _ => SystemAudioSupport::available(),
+    }
+}
 
 /// Check if system audio capture is available
-/// Returns true on macOS 12.3+ (ScreenCaptureKit is available)
 pub fn is_system_audio_available() -> bool {
-    // ScreenCaptureKit is available on macOS 12.3+
-    // The screencapturekit crate handles version checking internally
-    true
+    system_audio_support().available
+}
+
+/// Running applications, sorted by name, for picking an audio app filter
+pub fn get_audio_capture_apps() -> Vec<AudioCaptureApp> {
+    let Ok(content) = SCShareableContent::get() else {
+        return Vec::new();
+    };
+
+    let own_pid = std::process::id() as i32;
+    let mut apps: Vec<AudioCaptureApp> = content
+        .applications()
+        .iter()
+        .filter(|app| app.process_id() != own_pid && !app.bundle_identifier().is_empty())
+        .map(|app| AudioCaptureApp {
+            id: app.bundle_identifier(),
+            name: app.application_name(),
+            pid: app.process_id() as u32,
+        })
+        .collect();
+    apps.sort_by_key(|app| app.name.to_lowercase());
+    apps
 }
 
 /// Audio output handler that receives audio samples from ScreenCaptureKit
@@ -35,6 +100,8 @@ struct AudioOutputHandler {
     is_recording: Arc<AtomicBool>,
     sample_count: Arc<AtomicU64>,
     format_logged: AtomicBool,
+    /// Sample rate requested from ScreenCaptureKit
+    expected_sample_rate: u32,
 }
 
 impl AudioOutputHandler {
@@ -42,12 +109,14 @@ impl AudioOutputHandler {
         encoder: Arc<ParkingMutex<Option<Arc<AudioEncoder>>>>,
         is_recording: Arc<AtomicBool>,
         sample_count: Arc<AtomicU64>,
+        expected_sample_rate: u32,
     ) -> Self {
         Self {
             encoder,
             is_recording,
             sample_count,
             format_logged: AtomicBool::new(false),
+            expected_sample_rate,
         }
     }
 
@@ -98,14 +167,15 @@ impl AudioOutputHandler {
                 is_interleaved
             );
 
-            // Warn if format doesn't match expected 48kHz f32le
+            // Warn if format doesn't match the requested rate as f32le
             let sample_rate = fd.audio_sample_rate().unwrap_or(0.0) as u32;
             let is_float = fd.audio_is_float();
             let is_big_endian = fd.audio_is_big_endian();
 
-            if sample_rate != 48000 || !is_float || is_big_endian {
+            if sample_rate != self.expected_sample_rate || !is_float || is_big_endian {
                 tracing::warn!(
-                    "Audio format differs from expected 48kHz/f32le! Actual: {}Hz/{}/{}",
+                    "Audio format differs from expected {}Hz/f32le! Actual: {}Hz/{}/{}",
+                    self.expected_sample_rate,
                     sample_rate,
                     if is_float { "float" } else { "int" },
                     if is_big_endian { "big-endian" } else { "little-endian" }
@@ -232,13 +302,32 @@ pub struct SystemAudioCaptureChannel {
     session_index: usize,
     output_files: Arc<ParkingMutex<Vec<String>>>,
     encoder: Arc<ParkingMutex<Option<Arc<AudioEncoder>>>>,
+    /// Encoder slot of the running stream's output handler. Replaced on every
+    /// start and cleared on stop, so buffers a stopped stream still delivers
+    /// can't reach the next session's encoder.
+    stream_encoder: Arc<ParkingMutex<Option<Arc<AudioEncoder>>>>,
     stream: ParkingMutex<Option<SCStream>>,
     sample_count: Arc<AtomicU64>,
+    /// Format requested from ScreenCaptureKit and encoded
+    format: AudioFormat,
+    bitrate_kbps: u32,
+    /// Apps to record or leave out, instead of all system audio
+    app_filter: Option<AudioAppFilter>,
 }
 
+/// ScreenCaptureKit's native system audio format
+const NATIVE_FORMAT: AudioFormat = AudioFormat {
+    sample_rate: 48000,
+    channels: 2,
+};
+
 impl SystemAudioCaptureChannel {
     /// Create a new system audio capture channel
-    pub fn new(display_id: u32) -> Self {
+    pub fn new(
+        display_id: u32,
+        encoding: AudioEncodingConfig,
+        app_filter: Option<AudioAppFilter>,
+    ) -> Self {
         Self {
             id: "system-audio".to_string(),
             display_id,
@@ -247,8 +336,12 @@ impl SystemAudioCaptureChannel {
             session_index: 0,
             output_files: Arc::new(ParkingMutex::new(Vec::new())),
             encoder: Arc::new(ParkingMutex::new(None)),
+            stream_encoder: Arc::new(ParkingMutex::new(None)),
             stream: ParkingMutex::new(None),
             sample_count: Arc::new(AtomicU64::new(0)),
+            format: NATIVE_FORMAT.with_encoding(&encoding),
+            bitrate_kbps: encoding.bitrate_kbps(),
+            app_filter,
         }
     }
 
@@ -260,7 +353,7 @@ impl SystemAudioCaptureChannel {
 
 impl Default for SystemAudioCaptureChannel {
     fn default() -> Self {
-        Self::new(1) // Default to primary display
+        Self::new(1, AudioEncodingConfig::default(), None) // Default to primary display
     }
 }
 
@@ -294,6 +387,15 @@ impl RecordingChannel for SystemAudioCaptureChannel {
             RecordingError::ConfigurationError("Output directory not set".to_string())
         })?;
 
+        let support = system_audio_support();
+        if !support.available {
+            tracing::warn!(
+                "Skipping system audio capture: {}",
+                support.reason.unwrap_or_default()
+            );
+            return Ok(());
+        }
+
         // Warn about potential Bluetooth audio interference
         tracing::warn!(
             "Starting system audio capture via ScreenCaptureKit. \
@@ -325,11 +427,38 @@ impl RecordingChannel for SystemAudioCaptureChannel {
             target_display.display_id()
         );
 
-        // Create content filter for the display
-        let filter = SCContentFilter::create()
-            .with_display(target_display)
-            .with_excluding_windows(&[])
-            .build();
+        // Create content filter for the display, limited to the filtered apps
+        let applications = content.applications();
+        let filter = match &self.app_filter {
+            Some(app_filter) => {
+                let apps: Vec<&SCRunningApplication> = applications
+                    .iter()
+                    .filter(|app| app_filter.matches(&app.bundle_identifier()))
+                    .collect();
+                tracing::info!(
+                    "System audio {} {} of {} filtered apps",
+                    match app_filter.mode {
+                        AudioAppFilterMode::Include => "includes",
+                        AudioAppFilterMode::Exclude => "excludes",
+                    },
+                    apps.len(),
+                    app_filter.app_ids.len()
+                );
+                if apps.is_empty() && app_filter.mode == AudioAppFilterMode::Include {
+                    tracing::warn!("None of the apps to record audio from are running");
+                }
+                let builder = SCContentFilter::create().with_display(target_display);
+                match app_filter.mode {
+                    AudioAppFilterMode::Include => builder.with_including_applications(&apps, &[]),
+                    AudioAppFilterMode::Exclude => builder.with_excluding_applications(&apps, &[]),
+                }
+                .build()
+            }
+            None => SCContentFilter::create()
+                .with_display(target_display)
+                .with_excluding_windows(&[])
+                .build(),
+        };
 
         // Create stream configuration for audio capture
         // We use minimal video settings since we only want audio
@@ -338,29 +467,37 @@ impl RecordingChannel for SystemAudioCaptureChannel {
             .with_height(2) // Minimal height
             .with_minimum_frame_interval(&CMTime::new(1, 1)) // 1 fps - minimal video
             .with_captures_audio(true)
-            .with_sample_rate(48000)
-            .with_channel_count(2)
+            .with_sample_rate(self.format.sample_rate as i32)
+            .with_channel_count(self.format.channels as i32)
             .with_excludes_current_process_audio(true); // Don't capture our own audio
 
         // Create the stream
         let mut stream = SCStream::new(&filter, &config);
 
-        // Create encoder (48kHz stereo)
+        // Create encoder in the same format ScreenCaptureKit delivers
         let encoder = Arc::new(
-            AudioEncoder::new(48000, 2, &output_dir, self.session_index, "system").map_err(
-                |e| RecordingError::CaptureError(format!("Failed to start audio encoder: {}", e)),
-            )?,
+            AudioEncoder::new(
+                self.format.sample_rate,
+                self.format.channels,
+                self.bitrate_kbps,
+                &output_dir,
+                self.session_index,
+                FileKind::SystemAudio,
+            )
+            .map_err(|e| RecordingError::CaptureError(format!("Failed to start audio encoder: {}", e)))?,
         );
-        *self.encoder.lock() = Some(encoder);
+        *self.encoder.lock() = Some(encoder.clone());
+        self.stream_encoder = Arc::new(ParkingMutex::new(Some(encoder)));
 
         self.is_recording.store(true, Ordering::SeqCst);
         self.sample_count.store(0, Ordering::SeqCst);
 
         // Create output handler with proper interleaving support
         let output_handler = AudioOutputHandler::new(
-            self.encoder.clone(),
+            self.stream_encoder.clone(),
             self.is_recording.clone(),
             self.sample_count.clone(),
+            self.format.sample_rate,
         );
 
         // Add output handler for audio
@@ -393,6 +530,7 @@ impl RecordingChannel for SystemAudioCaptureChannel {
                 tracing::warn!("Error stopping ScreenCaptureKit stream: {:?}", e);
             }
         }
+        self.stream_encoder.lock().take();
 
         // Finish encoding
         if let Some(ref encoder) = *self.encoder.lock() {
@@ -428,4 +566,14 @@ impl RecordingChannel for SystemAudioCaptureChannel {
     fn output_files(&self) -> Vec<String> {
         self.output_files.lock().clone()
     }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: Some(format!("display-{}", self.display_id)),
+            sample_rate: Some(self.format.sample_rate),
+            channels: Some(self.format.channels),
+            bitrate_kbps: Some(self.bitrate_kbps),
+            ..Default::default()
+        }
+    }
 }
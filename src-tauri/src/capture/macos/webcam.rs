@@ -3,18 +3,55 @@
 //! This module provides webcam capture functionality using the nokhwa crate.
 //! Frames are captured and encoded to H.264 using FFmpeg.
 
-use crate::capture::traits::{CameraInfo, Resolution};
-use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::capture::camera_format::{self, pixel_format_name, requested_format, resolutions};
+use crate::capture::camera_preview::camera_index;
+use crate::capture::traits::{CameraFormat, CameraInfo, Resolution};
+use crate::recorder::channel::{
+    ChannelHealth, ChannelType, RecordingChannel, RecordingError, RecordingResult,
+};
+use crate::recorder::coordinator::RecordingEvent;
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::segment::live_movflags_args;
+use crate::recorder::state::{CaptureQuality, VideoEncodingConfig};
+use crate::utils::process::{track, TrackedChild};
 use async_trait::async_trait;
 use nokhwa::pixel_format::RgbAFormat;
-use nokhwa::utils::{ApiBackend, CameraIndex, FrameFormat, RequestedFormat, RequestedFormatType};
+use nokhwa::utils::{
+    ApiBackend, CameraFormat as NokhwaFormat, CameraIndex, FrameFormat, RequestedFormat,
+    RequestedFormatType,
+};
 use nokhwa::Camera;
 use parking_lot::Mutex as ParkingMutex;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
+use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// How long the camera can go without delivering a frame before it counts
+/// as disconnected
+const FRAME_STALL: Duration = Duration::from_secs(2);
+
+/// Like `FRAME_STALL`, for the first frame; some cameras take a few seconds
+/// to warm up
+const FIRST_FRAME_STALL: Duration = Duration::from_secs(5);
+
+/// How long the encoder thread waits for a frame before checking for a stall
+const FRAME_WAIT: Duration = Duration::from_millis(100);
+
+/// Pause after a failed frame read, so a camera that's gone isn't polled in
+/// a busy loop
+const FRAME_ERROR_BACKOFF: Duration = Duration::from_millis(10);
+
+/// How often to try opening a camera again after it disconnects
+const REOPEN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long an attempt to reopen a camera may take before trying again
+const REOPEN_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Get list of available cameras
 pub fn get_cameras() -> Vec<CameraInfo> {
@@ -29,8 +66,12 @@ pub fn get_cameras() -> Vec<CameraInfo> {
                 };
                 let name = info.human_name().to_string();
 
-                // Common resolutions
-                let resolutions = vec![
+                let formats = camera_format::query_formats(info.index()).unwrap_or_else(|e| {
+                    tracing::warn!("Failed to query formats of camera {}: {:?}", name, e);
+                    Vec::new()
+                });
+                // Common resolutions, if the camera's couldn't be queried
+                let common = vec![
                     Resolution {
                         width: 1920,
                         height: 1080,
@@ -48,7 +89,12 @@ pub fn get_cameras() -> Vec<CameraInfo> {
                 CameraInfo {
                     id,
                     name,
-                    supported_resolutions: resolutions,
+                    supported_resolutions: if formats.is_empty() {
+                        common
+                    } else {
+                        resolutions(&formats)
+                    },
+                    supported_formats: formats,
                 }
             })
             .collect(),
@@ -61,7 +107,7 @@ pub fn get_cameras() -> Vec<CameraInfo> {
 
 /// FFmpeg encoder for webcam video output
 struct FFmpegWebcamEncoder {
-    process: ParkingMutex<Option<Child>>,
+    process: ParkingMutex<Option<TrackedChild>>,
     frame_count: AtomicU64,
     running: AtomicBool,
     output_dir: PathBuf,
@@ -69,10 +115,13 @@ struct FFmpegWebcamEncoder {
 }
 
 impl FFmpegWebcamEncoder {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         width: u32,
         height: u32,
         fps: u32,
+        encoding: &VideoEncodingConfig,
+        quality: CaptureQuality,
         output_dir: &Path,
         session_index: usize,
         pixel_format: &str,
@@ -81,7 +130,7 @@ impl FFmpegWebcamEncoder {
         std::fs::create_dir_all(output_dir)?;
 
         let output_file = output_dir
-            .join(format!("recording-{session_index}-webcam.mp4"))
+            .join(FileKind::WebcamVideo.conventional_name(session_index))
             .to_string_lossy()
             .to_string();
 
@@ -108,14 +157,10 @@ impl FFmpegWebcamEncoder {
                 "veryfast",             // Good balance of speed and compression
                 "-pix_fmt",
                 "yuv420p",              // Output pixel format (required for compatibility)
-                "-crf",
-                "18",                   // High quality
-                "-g",
-                &(fps * 2).to_string(), // GOP size = 2 seconds
-                "-movflags",
-                "+faststart",           // Move moov atom to start for streaming
-                &output_file,
             ])
+            .args(encoding.ffmpeg_args(fps, quality)) // CRF, GOP size and B-frames
+            .args(live_movflags_args()) // Playable while recording
+            .arg(&output_file)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
@@ -131,7 +176,7 @@ impl FFmpegWebcamEncoder {
         );
 
         Ok(Self {
-            process: ParkingMutex::new(Some(process)),
+            process: ParkingMutex::new(Some(track(process, "webcam encoder"))),
             frame_count: AtomicU64::new(0),
             running: AtomicBool::new(true),
             output_dir: output_dir.to_path_buf(),
@@ -181,7 +226,7 @@ impl FFmpegWebcamEncoder {
         // Find the output file
         let output_file = self
             .output_dir
-            .join(format!("recording-{}-webcam.mp4", self.session_index))
+            .join(FileKind::WebcamVideo.conventional_name(self.session_index))
             .to_string_lossy()
             .to_string();
 
@@ -200,6 +245,163 @@ impl FFmpegWebcamEncoder {
     }
 }
 
+/// What a camera thread sends to the channel's encoder thread
+enum CameraMessage {
+    /// The camera opened, with its name and actual format
+    Opened { name: String, format: NokhwaFormat },
+    /// The camera couldn't be opened
+    Failed(String),
+    /// A raw frame in the camera's pixel format
+    Frame(Vec<u8>),
+}
+
+/// Camera capturing on a thread of its own. A disconnected camera can block
+/// `Camera::frame` indefinitely, so the encoder thread only waits on
+/// `frames`, with a timeout, and leaves a camera that went away behind.
+struct CameraThread {
+    frames: mpsc::Receiver<CameraMessage>,
+    active: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl CameraThread {
+    /// Open the camera at `index` on a new thread, asking for `format` or
+    /// its highest resolution
+    fn spawn(index: CameraIndex, format: Option<CameraFormat>) -> Self {
+        // Small, so a slow encoder holds the camera back rather than
+        // buffering frames
+        let (tx, frames) = mpsc::sync_channel(2);
+        let active = Arc::new(AtomicBool::new(true));
+        let running = active.clone();
+        let handle = std::thread::spawn(move || run_camera(index, format, &running, &tx));
+        Self {
+            frames,
+            active,
+            handle,
+        }
+    }
+
+    /// Stop the camera and wait for its thread to close it
+    fn stop(self) {
+        self.active.store(false, Ordering::SeqCst);
+        drop(self.frames);
+        let _ = self.handle.join();
+    }
+
+    /// Stop the camera without waiting for its thread, which may be stuck
+    /// in the camera; it closes the camera if it ever returns
+    fn abandon(self) {
+        self.active.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Open and start the camera at `index`, falling back to its highest
+/// resolution if it has nothing close to `format`
+fn open_camera(index: &CameraIndex, format: Option<&CameraFormat>) -> Result<Camera, String> {
+    let requested = RequestedFormat::new::<RgbAFormat>(requested_format(format));
+    let mut camera = Camera::new(index.clone(), requested)
+        .or_else(|e| {
+            if format.is_none() {
+                return Err(e);
+            }
+            tracing::warn!("Webcam format unavailable ({}), using highest resolution", e);
+            let highest = RequestedFormatType::AbsoluteHighestResolution;
+            Camera::new(index.clone(), RequestedFormat::new::<RgbAFormat>(highest))
+        })
+        .map_err(|e| format!("Failed to open camera {:?}: {}", index, e))?;
+    camera
+        .open_stream()
+        .map_err(|e| format!("Failed to open camera stream: {}", e))?;
+    Ok(camera)
+}
+
+/// Body of a camera thread: open the camera and send its frames until
+/// `active` is cleared or the encoder thread stops listening
+fn run_camera(
+    index: CameraIndex,
+    format: Option<CameraFormat>,
+    active: &AtomicBool,
+    tx: &mpsc::SyncSender<CameraMessage>,
+) {
+    let mut camera = match open_camera(&index, format.as_ref()) {
+        Ok(camera) => camera,
+        Err(reason) => {
+            let _ = tx.send(CameraMessage::Failed(reason));
+            return;
+        }
+    };
+    let opened = CameraMessage::Opened {
+        name: camera.info().human_name().to_string(),
+        format: camera.camera_format(),
+    };
+
+    if tx.send(opened).is_ok() {
+        while active.load(Ordering::SeqCst) {
+            // Capture frame - this blocks until camera delivers next frame
+            // Do NOT add artificial delay, the camera controls the timing
+            match camera.frame() {
+                Ok(frame) => {
+                    // Pass the raw frame buffer on - NO DECODING. FFmpeg
+                    // converts it much faster than decode_image() would.
+                    if tx.send(CameraMessage::Frame(frame.buffer().to_vec())).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!("Failed to capture frame: {:?}", e);
+                    std::thread::sleep(FRAME_ERROR_BACKOFF);
+                }
+            }
+        }
+    }
+
+    if let Err(e) = camera.stop_stream() {
+        tracing::warn!("Error stopping camera stream: {:?}", e);
+    }
+}
+
+/// A camera that stopped delivering frames while recording
+struct Outage {
+    /// When the last frame arrived
+    since: Instant,
+    /// Frames repeated to fill the gap so far
+    filled: u64,
+    /// Attempts to reopen a camera so far
+    attempts: u32,
+    last_attempt: Instant,
+    /// Name of the camera the current attempt opened
+    reopened: Option<String>,
+}
+
+impl Outage {
+    fn new(since: Instant) -> Self {
+        Self {
+            since,
+            filled: 0,
+            attempts: 0,
+            // The first attempt is due right away
+            last_attempt: since,
+            reopened: None,
+        }
+    }
+
+    /// Repeat `frame` for the time since the last real frame, at `fps`.
+    /// Returns false if the encoder stopped accepting frames.
+    fn fill(&mut self, encoder: &FFmpegWebcamEncoder, frame: &[u8], fps: u32) -> bool {
+        if frame.is_empty() {
+            return true;
+        }
+        let due = (self.since.elapsed().as_secs_f64() * fps as f64) as u64;
+        while self.filled < due {
+            if !encoder.write_frame(frame) {
+                return false;
+            }
+            self.filled += 1;
+        }
+        true
+    }
+}
+
 /// Webcam capture channel using nokhwa
 pub struct WebcamCaptureChannel {
     /// Channel identifier
@@ -229,8 +431,23 @@ pub struct WebcamCaptureChannel {
     /// Capture FPS
     fps: u32,
 
+    /// Format to ask the camera for (None = its highest resolution)
+    format: Option<CameraFormat>,
+
+    /// Keyframe and B-frame settings for the encoder
+    video_encoding: VideoEncodingConfig,
+
+    /// Encoder quality preset (the camera keeps its own size and frame rate)
+    quality: CaptureQuality,
+
     /// Capture thread handle
     capture_thread: Option<std::thread::JoinHandle<()>>,
+
+    /// Set by the capture thread when the camera or encoder fails
+    health: Arc<ParkingMutex<ChannelHealth>>,
+
+    /// Reports the camera disconnecting and coming back
+    event_tx: Option<broadcast::Sender<RecordingEvent>>,
 }
 
 impl WebcamCaptureChannel {
@@ -246,23 +463,42 @@ impl WebcamCaptureChannel {
             width,
             height,
             fps,
+            format: None,
+            video_encoding: VideoEncodingConfig::default(),
+            quality: CaptureQuality::default(),
             capture_thread: None,
+            health: Arc::new(ParkingMutex::new(ChannelHealth::Healthy)),
+            event_tx: None,
         }
     }
 
+    /// Capture format to ask the camera for, instead of its highest
+    /// resolution
+    pub fn format(mut self, format: Option<CameraFormat>) -> Self {
+        if let Some(format) = &format {
+            self.width = format.width;
+            self.height = format.height;
+            self.fps = format.fps;
+        }
+        self.format = format;
+        self
+    }
+
+    /// Keyframe and B-frame settings for the encoder
+    pub fn video_encoding(mut self, encoding: VideoEncodingConfig) -> Self {
+        self.video_encoding = encoding;
+        self
+    }
+
+    /// Encoder quality preset
+    pub fn quality(mut self, quality: CaptureQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
     /// Get camera index from device_id
     fn get_camera_index(&self) -> CameraIndex {
-        match &self.device_id {
-            Some(id) => {
-                // Try to parse as integer first
-                if let Ok(idx) = id.parse::<u32>() {
-                    CameraIndex::Index(idx)
-                } else {
-                    CameraIndex::String(id.clone())
-                }
-            }
-            None => CameraIndex::Index(0), // Default to first camera
-        }
+        camera_index(self.device_id.as_deref())
     }
 }
 
@@ -282,7 +518,7 @@ impl RecordingChannel for WebcamCaptureChannel {
 
         // Check if FFmpeg is available
         if Command::new("ffmpeg").arg("-version").output().is_err() {
-            return Err(RecordingError::ConfigurationError(
+            return Err(RecordingError::FfmpegMissing(
                 "FFmpeg not found. Please install FFmpeg: brew install ffmpeg".to_string(),
             ));
         }
@@ -317,6 +553,7 @@ impl RecordingChannel for WebcamCaptureChannel {
         })?;
 
         self.is_recording.store(true, Ordering::SeqCst);
+        *self.health.lock() = ChannelHealth::Healthy;
 
         // Start capture in a background thread
         // We create the encoder inside the thread after we know the actual resolution
@@ -326,50 +563,55 @@ impl RecordingChannel for WebcamCaptureChannel {
         let requested_width = self.width;
         let requested_height = self.height;
         let fps = self.fps;
+        let format = self.format.clone();
+        let video_encoding = self.video_encoding;
+        let quality = self.quality;
         let session_index = self.session_index;
+        let health = self.health.clone();
+        let event_tx = self.event_tx.clone();
+        let channel_id = self.id.clone();
 
         let handle = std::thread::spawn(move || {
-            // Request highest resolution available - we'll get actual resolution after opening
-            let format = RequestedFormat::new::<RgbAFormat>(
-                RequestedFormatType::AbsoluteHighestResolution
-            );
-
-            // Open camera
-            let camera_result = Camera::new(camera_index.clone(), format);
+            // Failures end the thread but leave `is_recording` set, so the
+            // coordinator still stops the channel normally
+            let fail = |reason: String| {
+                tracing::error!("{}", reason);
+                *health.lock() = ChannelHealth::Failed { reason };
+            };
+            let send_event = |event: RecordingEvent| {
+                if let Some(ref tx) = event_tx {
+                    let _ = tx.send(event);
+                }
+            };
 
-            let mut camera = match camera_result {
-                Ok(c) => c,
-                Err(e) => {
-                    tracing::error!("Failed to open camera {:?}: {:?}", camera_index, e);
+            // Request the chosen format, or the highest resolution available -
+            // we'll get actual resolution after opening
+            let first = CameraThread::spawn(camera_index.clone(), format);
+            let (mut device_name, opened) = match first.frames.recv() {
+                Ok(CameraMessage::Opened { name, format }) => (name, format),
+                Ok(CameraMessage::Failed(reason)) => {
+                    fail(reason);
+                    return;
+                }
+                _ => {
+                    fail("Camera thread ended before opening the camera".to_string());
                     return;
                 }
             };
-
-            // Open the camera stream
-            if let Err(e) = camera.open_stream() {
-                tracing::error!("Failed to open camera stream: {:?}", e);
-                return;
-            }
+            let original_name = device_name.clone();
 
             // Get actual camera resolution and framerate
-            let camera_format = camera.camera_format();
-            let actual_width = camera_format.resolution().width();
-            let actual_height = camera_format.resolution().height();
-            let actual_fps = camera_format.frame_rate();
-            let frame_format = camera_format.format();
-            
+            let actual_width = opened.resolution().width();
+            let actual_height = opened.resolution().height();
+            let actual_fps = opened.frame_rate();
+            let frame_format = opened.format();
+
             // Map nokhwa FrameFormat to FFmpeg pixel format string
-            let ffmpeg_pix_fmt = match frame_format {
-                FrameFormat::YUYV => "yuyv422",
-                FrameFormat::NV12 => "nv12",
-                FrameFormat::RAWRGB => "rgb24",
-                FrameFormat::MJPEG => "mjpeg",  // FFmpeg can decode MJPEG
-                _ => {
-                    tracing::warn!("Unknown camera format {:?}, falling back to yuyv422", frame_format);
-                    "yuyv422"
-                }
-            };
-            
+            let ffmpeg_pix_fmt = pixel_format_name(frame_format).unwrap_or_else(|| {
+                tracing::warn!("Unknown camera format {:?}, falling back to yuyv422", frame_format);
+                "yuyv422"
+            });
+
             tracing::info!(
                 "Webcam opened: {}x{} @ {}fps, format={:?} -> ffmpeg pix_fmt={} (requested {}x{} @ {}fps)",
                 actual_width,
@@ -388,33 +630,84 @@ impl RecordingChannel for WebcamCaptureChannel {
                 actual_width,
                 actual_height,
                 actual_fps,
+                &video_encoding,
+                quality,
                 &output_dir,
                 session_index,
                 ffmpeg_pix_fmt,
             ) {
                 Ok(e) => Arc::new(e),
                 Err(e) => {
-                    tracing::error!("Failed to start FFmpeg encoder: {:?}", e);
-                    let _ = camera.stop_stream();
+                    fail(format!("Failed to start FFmpeg encoder: {}", e));
+                    first.stop();
                     return;
                 }
             };
 
             tracing::info!("Webcam capture started at {}fps (raw {} frames)", actual_fps, ffmpeg_pix_fmt);
 
+            // A camera opened after a disconnect has to match the encoder
+            let reopen_format = CameraFormat {
+                width: actual_width,
+                height: actual_height,
+                fps: actual_fps,
+                pixel_format: pixel_format_name(frame_format).map(str::to_string),
+            };
+
+            let mut camera = Some(first);
             let mut frame_logged = false;
             let mut frame_count: u64 = 0;
-            let capture_start = std::time::Instant::now();
+            let capture_start = Instant::now();
+            let mut last_frame = Instant::now();
+            let mut last_data: Vec<u8> = Vec::new();
+            let mut outage: Option<Outage> = None;
 
             while is_recording.load(Ordering::SeqCst) {
-                // Capture frame - this blocks until camera delivers next frame
-                // Do NOT add artificial delay, the camera controls the timing
-                match camera.frame() {
-                    Ok(frame) => {
-                        // Pass raw frame buffer directly to FFmpeg - NO DECODING
-                        // This is much faster than decode_image() which does CPU conversion
-                        let raw_data = frame.buffer();
-                        
+                let message = match &camera {
+                    Some(camera) => camera.frames.recv_timeout(FRAME_WAIT),
+                    None => {
+                        std::thread::sleep(FRAME_WAIT);
+                        Err(RecvTimeoutError::Disconnected)
+                    }
+                };
+
+                match message {
+                    Ok(CameraMessage::Frame(raw_data)) => {
+                        if let Some(lost) = outage.take() {
+                            let gap_ms = lost.since.elapsed().as_secs_f64() * 1000.0;
+                            let name = lost.reopened.unwrap_or_else(|| device_name.clone());
+                            if name == original_name {
+                                tracing::info!(
+                                    "Webcam '{}' reconnected ({:.0}ms gap filled)",
+                                    name,
+                                    gap_ms
+                                );
+                                send_event(RecordingEvent::DeviceRecovered {
+                                    channel: channel_id.clone(),
+                                    device: name.clone(),
+                                    gap_ms,
+                                });
+                            } else {
+                                tracing::warn!(
+                                    "Webcam fell back from '{}' to '{}' ({:.0}ms gap filled)",
+                                    device_name,
+                                    name,
+                                    gap_ms
+                                );
+                                send_event(RecordingEvent::DeviceFallback {
+                                    channel: channel_id.clone(),
+                                    from: device_name.clone(),
+                                    to: name.clone(),
+                                    gap_ms,
+                                });
+                            }
+                            device_name = name;
+                            let mut health = health.lock();
+                            if matches!(*health, ChannelHealth::Degraded { .. }) {
+                                *health = ChannelHealth::Healthy;
+                            }
+                        }
+
                         // Log first frame info
                         if !frame_logged {
                             // Calculate expected size based on format
@@ -432,16 +725,97 @@ impl RecordingChannel for WebcamCaptureChannel {
                             );
                             frame_logged = true;
                         }
-                        
-                        encoder.write_frame(raw_data);
+
+                        if !encoder.write_frame(&raw_data) {
+                            fail("Webcam encoder stopped accepting frames".to_string());
+                            break;
+                        }
                         frame_count += 1;
+                        last_frame = Instant::now();
+                        last_data = raw_data;
+                    }
+                    Ok(CameraMessage::Opened { name, format }) => {
+                        // A camera reopened after a disconnect
+                        let matches = format.resolution() == opened.resolution()
+                            && format.format() == opened.format();
+                        if let Some(lost) = outage.as_mut().filter(|_| matches) {
+                            lost.reopened = Some(name);
+                        } else {
+                            tracing::warn!(
+                                "Webcam '{}' reopened as {:?}, which doesn't match the recording",
+                                name,
+                                format
+                            );
+                            if let Some(camera) = camera.take() {
+                                camera.abandon();
+                            }
+                        }
+                    }
+                    Ok(CameraMessage::Failed(reason)) => {
+                        tracing::debug!("Reopening webcam failed: {}", reason);
+                        camera = None;
                     }
-                    Err(e) => {
-                        tracing::debug!("Failed to capture frame: {:?}", e);
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => camera = None,
+                }
+
+                // A disconnected camera stops delivering frames (Camera::frame
+                // may never return), so a stall is treated as the camera
+                // going away
+                let stall = if frame_logged { FRAME_STALL } else { FIRST_FRAME_STALL };
+                if outage.is_none() && last_frame.elapsed() >= stall {
+                    tracing::warn!("Webcam '{}' stopped delivering frames", device_name);
+                    *health.lock() = ChannelHealth::Degraded {
+                        reason: format!(
+                            "No frames from camera '{}' for over {}s",
+                            device_name,
+                            stall.as_secs()
+                        ),
+                    };
+                    send_event(RecordingEvent::DeviceLost {
+                        channel: channel_id.clone(),
+                        device: device_name.clone(),
+                    });
+                    if let Some(camera) = camera.take() {
+                        camera.abandon();
+                    }
+                    outage = Some(Outage::new(last_frame));
+                }
+
+                let Some(lost) = outage.as_mut() else {
+                    continue;
+                };
+
+                // Repeat the last frame to keep the track in sync with the others
+                if !lost.fill(&encoder, &last_data, actual_fps) {
+                    fail("Webcam encoder stopped accepting frames".to_string());
+                    break;
+                }
+
+                // Try the original camera and the default one in turn. An
+                // attempt that hangs while opening is given up on.
+                let waited = lost.last_attempt.elapsed();
+                if (camera.is_none() && waited >= REOPEN_INTERVAL) || waited >= REOPEN_TIMEOUT {
+                    if let Some(camera) = camera.take() {
+                        camera.abandon();
                     }
+                    let index = if lost.attempts % 2 == 0 {
+                        camera_index.clone()
+                    } else {
+                        CameraIndex::Index(0)
+                    };
+                    lost.attempts += 1;
+                    lost.last_attempt = Instant::now();
+                    lost.reopened = None;
+                    camera = Some(CameraThread::spawn(index, Some(reopen_format.clone())));
                 }
             }
-            
+
+            // Pad the track up to the stop time if the camera never came back
+            if let Some(lost) = outage.as_mut() {
+                lost.fill(&encoder, &last_data, actual_fps);
+            }
+
             let elapsed = capture_start.elapsed();
             let actual_capture_fps = frame_count as f64 / elapsed.as_secs_f64();
             tracing::info!(
@@ -451,9 +825,14 @@ impl RecordingChannel for WebcamCaptureChannel {
                 actual_capture_fps
             );
 
-            // Close the camera
-            if let Err(e) = camera.stop_stream() {
-                tracing::warn!("Error stopping camera stream: {:?}", e);
+            // Close the camera; one that's still reopening is left to finish
+            // on its own
+            if let Some(camera) = camera {
+                if outage.is_none() {
+                    camera.stop();
+                } else {
+                    camera.abandon();
+                }
             }
 
             // Finish encoding
@@ -512,4 +891,22 @@ impl RecordingChannel for WebcamCaptureChannel {
     fn output_files(&self) -> Vec<String> {
         self.output_files.lock().clone()
     }
+
+    fn health(&self) -> ChannelHealth {
+        self.health.lock().clone()
+    }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<RecordingEvent>) {
+        self.event_tx = Some(event_tx);
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: self.device_id.clone(),
+            width: Some(self.width),
+            height: Some(self.height),
+            fps: Some(self.fps),
+            ..Default::default()
+        }
+    }
 }
@@ -0,0 +1,528 @@
+//! macOS window capture using ScreenCaptureKit content filters
+//!
+//! Captures a single window through a desktop-independent `SCContentFilter`,
+//! so the window keeps recording while occluded or moved between displays.
+//! Resizes are tracked by polling the window frame and updating the stream
+//! configuration; frames are letterboxed into a fixed canvas for the encoder
+//! and every size change is written to `recording-{n}-window-resizes.json`
+//! so export can re-fit the content dynamically. Moves are written there
+//! too, with the window's origin on the display input is tracked on, so
+//! mouse positions can be brought into the window's video.
+
+use super::screen::FFmpegSegmentEncoder;
+use crate::capture::frame::{letterbox_into, ContentRect, WindowResizeEvent};
+use crate::capture::traits::{FrontmostWindow, WindowBounds, WindowInfo};
+use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::state::{CaptureQuality, VideoEncodingConfig};
+use async_trait::async_trait;
+use core_graphics::display::CGDisplay;
+use parking_lot::Mutex as ParkingMutex;
+use screencapturekit::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the window frame is polled for resizes
+const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Get list of capturable windows
+pub fn get_windows() -> Vec<WindowInfo> {
+    let Ok(content) = SCShareableContent::get() else {
+        return Vec::new();
+    };
+
+    content
+        .windows()
+        .iter()
+        .filter(|w| w.frame().width > 1.0 && w.frame().height > 1.0)
+        .map(|w| {
+            let frame = w.frame();
+            WindowInfo {
+                id: w.window_id(),
+                title: w.title().unwrap_or_default(),
+                app_name: w
+                    .owning_application()
+                    .map(|app| app.application_name())
+                    .unwrap_or_default(),
+                bounds: WindowBounds {
+                    x: frame.x as i32,
+                    y: frame.y as i32,
+                    width: frame.width as u32,
+                    height: frame.height as u32,
+                },
+                is_on_screen: w.is_on_screen(),
+            }
+        })
+        .collect()
+}
+
+/// Frontmost normal window that isn't one of ours, with its application
+pub fn frontmost_window() -> Option<FrontmostWindow> {
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::dictionary::CFDictionary;
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::{CFString, CFStringRef};
+    use core_graphics::geometry::CGRect;
+    use core_graphics::window::{
+        copy_window_info, kCGNullWindowID, kCGWindowBounds, kCGWindowLayer,
+        kCGWindowListExcludeDesktopElements, kCGWindowListOptionOnScreenOnly, kCGWindowName,
+        kCGWindowNumber, kCGWindowOwnerName, kCGWindowOwnerPID,
+    };
+
+    let own_pid = std::process::id() as i64;
+    let key = |k: CFStringRef| unsafe { CFString::wrap_under_get_rule(k) };
+
+    // Windows are listed front to back
+    let windows = copy_window_info(
+        kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements,
+        kCGNullWindowID,
+    )?;
+    windows.iter().find_map(|item| {
+        let info: CFDictionary<CFString, CFType> =
+            unsafe { CFDictionary::wrap_under_get_rule(*item as _) };
+        let number = |k: CFStringRef| {
+            info.find(key(k))
+                .and_then(|v| v.downcast::<CFNumber>())
+                .and_then(|n| n.to_i64())
+        };
+        let string = |k: CFStringRef| {
+            info.find(key(k))
+                .and_then(|v| v.downcast::<CFString>())
+                .map(|s| s.to_string())
+        };
+
+        // Layer 0 holds normal application windows (not menus or overlays)
+        let (layer, pid) = unsafe { (number(kCGWindowLayer), number(kCGWindowOwnerPID)) };
+        if layer != Some(0) || pid == Some(own_pid) {
+            return None;
+        }
+        // Titles are only reported with screen recording permission
+        let title = unsafe { string(kCGWindowName) }.filter(|t| !t.is_empty());
+        let id = unsafe { number(kCGWindowNumber) }.map(|n| n as u64);
+        let bounds = unsafe { info.find(key(kCGWindowBounds)) }
+            .and_then(|v| v.downcast::<CFDictionary>())
+            .and_then(|dict| CGRect::from_dict_representation(&dict))
+            .map(|rect| WindowBounds {
+                x: rect.origin.x as i32,
+                y: rect.origin.y as i32,
+                width: rect.size.width as u32,
+                height: rect.size.height as u32,
+            });
+        unsafe { string(kCGWindowOwnerName) }.map(|app| FrontmostWindow {
+            app,
+            title,
+            id,
+            bounds,
+        })
+    })
+}
+
+/// Find a window and its size in pixels (points scaled by its display)
+fn find_window(window_id: u32) -> RecordingResult<(SCWindow, u32, u32)> {
+    let content = SCShareableContent::get().map_err(|e| {
+        RecordingError::CaptureError(format!("Failed to get shareable content: {:?}", e))
+    })?;
+
+    let window = content
+        .windows()
+        .into_iter()
+        .find(|w| w.window_id() == window_id)
+        .ok_or_else(|| RecordingError::DeviceNotFound(format!("Window {} not found", window_id)))?;
+
+    let frame = window.frame();
+    let scale = content
+        .displays()
+        .iter()
+        .find(|d| {
+            let b = d.frame();
+            frame.x >= b.x && frame.x < b.x + b.width && frame.y >= b.y && frame.y < b.y + b.height
+        })
+        .map(|d| {
+            let display = CGDisplay::new(d.display_id());
+            display.pixels_wide() as f64 / display.bounds().size.width
+        })
+        .unwrap_or(1.0);
+
+    // H.264 with yuv420p needs even dimensions
+    let width = ((frame.width * scale) as u32).max(2) & !1;
+    let height = ((frame.height * scale) as u32).max(2) & !1;
+    Ok((window, width, height))
+}
+
+/// Top-left of a window on a display, in the display's pixels with a
+/// top-left origin, as input tracking records mouse positions
+fn window_origin(window: &SCWindow, display_id: u32) -> (f64, f64) {
+    let frame = window.frame();
+    let display = CGDisplay::new(display_id);
+    let bounds = display.bounds();
+    let scale = if bounds.size.height > 0.0 {
+        display.pixels_high() as f64 / bounds.size.height
+    } else {
+        1.0
+    };
+    (
+        (frame.x - bounds.origin.x) * scale,
+        (frame.y - bounds.origin.y) * scale,
+    )
+}
+
+fn stream_config(width: u32, height: u32, fps: u32, show_cursor: bool) -> SCStreamConfiguration {
+    SCStreamConfiguration::new()
+        .with_width(width)
+        .with_height(height)
+        .with_pixel_format(PixelFormat::BGRA)
+        .with_minimum_frame_interval(&CMTime::new(1, fps as i32))
+        // Unless asked for, the cursor is composited at export time from
+        // input tracking instead
+        .with_shows_cursor(show_cursor)
+}
+
+/// Receives window frames and letterboxes them into the encoder canvas
+struct WindowOutputHandler {
+    encoder: Arc<FFmpegSegmentEncoder>,
+    is_recording: Arc<AtomicBool>,
+    canvas_width: u32,
+    canvas_height: u32,
+    canvas: ParkingMutex<Vec<u8>>,
+    session_start: Instant,
+    /// Window origin on the tracked display, updated by the resize poll
+    origin: Arc<ParkingMutex<(f64, f64)>>,
+    last_placement: ParkingMutex<Option<(ContentRect, (f64, f64))>>,
+    resizes: Arc<ParkingMutex<Vec<WindowResizeEvent>>>,
+    dropped_frames: Arc<AtomicU64>,
+}
+
+impl SCStreamOutputTrait for WindowOutputHandler {
+    fn did_output_sample_buffer(&self, sample_buffer: CMSampleBuffer, of_type: SCStreamOutputType) {
+        if of_type != SCStreamOutputType::Screen || !self.is_recording.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // Idle/blank status frames carry no image
+        let Some(pixel_buffer) = sample_buffer.image_buffer() else {
+            return;
+        };
+        let Ok(guard) = pixel_buffer.lock_read_only() else {
+            self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+
+        let (width, height) = (pixel_buffer.width() as u32, pixel_buffer.height() as u32);
+        let mut canvas = self.canvas.lock();
+        let rect = letterbox_into(
+            guard.as_slice(),
+            width,
+            height,
+            pixel_buffer.bytes_per_row(),
+            &mut canvas,
+            self.canvas_width,
+            self.canvas_height,
+        );
+
+        let origin = *self.origin.lock();
+        let mut last_placement = self.last_placement.lock();
+        if *last_placement != Some((rect, origin)) {
+            self.resizes.lock().push(WindowResizeEvent {
+                process_time_ms: self.session_start.elapsed().as_secs_f64() * 1000.0,
+                source_width: width,
+                source_height: height,
+                content: rect,
+                window_origin: Some(origin),
+            });
+            *last_placement = Some((rect, origin));
+        }
+
+        if !self.encoder.write_frame(&canvas) {
+            self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Window capture channel using ScreenCaptureKit
+pub struct WindowCaptureChannel {
+    id: String,
+    window_id: u32,
+    /// Display input tracking records mouse positions on
+    display_id: u32,
+    is_recording: Arc<AtomicBool>,
+    output_dir: Option<PathBuf>,
+    session_index: usize,
+    output_files: Arc<ParkingMutex<Vec<String>>>,
+    encoder: Option<Arc<FFmpegSegmentEncoder>>,
+    stream: Arc<ParkingMutex<Option<SCStream>>>,
+    resize_handle: Option<tokio::task::JoinHandle<()>>,
+    resizes: Arc<ParkingMutex<Vec<WindowResizeEvent>>>,
+    width: u32,
+    height: u32,
+    fps: u32,
+    video_encoding: VideoEncodingConfig,
+    quality: CaptureQuality,
+    show_cursor: bool,
+    dropped_frames: Arc<AtomicU64>,
+}
+
+impl WindowCaptureChannel {
+    /// Create a new window capture channel
+    pub fn new(window_id: u32) -> Self {
+        Self {
+            id: format!("window-{}", window_id),
+            window_id,
+            display_id: CGDisplay::main().id,
+            is_recording: Arc::new(AtomicBool::new(false)),
+            output_dir: None,
+            session_index: 0,
+            output_files: Arc::new(ParkingMutex::new(Vec::new())),
+            encoder: None,
+            stream: Arc::new(ParkingMutex::new(None)),
+            resize_handle: None,
+            resizes: Arc::new(ParkingMutex::new(Vec::new())),
+            width: 1920,
+            height: 1080,
+            fps: 30,
+            video_encoding: VideoEncodingConfig::default(),
+            quality: CaptureQuality::default(),
+            show_cursor: false,
+            dropped_frames: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Keyframe and B-frame settings for the encoder
+    pub fn video_encoding(mut self, encoding: VideoEncodingConfig) -> Self {
+        self.video_encoding = encoding;
+        self
+    }
+
+    /// Frame rate, scaling and encoder quality preset
+    pub fn quality(mut self, quality: CaptureQuality) -> Self {
+        self.fps = quality.fps();
+        self.quality = quality;
+        self
+    }
+
+    /// Display input tracking records mouse positions on, which the window's
+    /// origin is measured on
+    pub fn display(mut self, display_id: u32) -> Self {
+        self.display_id = display_id;
+        self
+    }
+
+    /// Record the system cursor into the frames
+    pub fn show_cursor(mut self, show: bool) -> Self {
+        self.show_cursor = show;
+        self
+    }
+
+    fn write_resizes(&self, output_dir: &Path) -> RecordingResult<()> {
+        let path = output_dir.join(FileKind::WindowResizes.conventional_name(self.session_index));
+        let data = serde_json::to_vec_pretty(&*self.resizes.lock())
+            .map_err(|e| RecordingError::IoError(std::io::Error::other(e)))?;
+        std::fs::write(&path, data)?;
+        self.output_files.lock().push(path.to_string_lossy().to_string());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RecordingChannel for WindowCaptureChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Display
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        if !super::permissions::has_screen_recording_permission() {
+            super::permissions::request_screen_recording_permission();
+            return Err(RecordingError::PermissionDenied(
+                "Screen recording permission not granted. Please allow in System Preferences."
+                    .to_string(),
+            ));
+        }
+
+        // The canvas is sized from the window when the recording starts and
+        // stays fixed across resizes
+        if session_index == 0 {
+            let (_, width, height) = find_window(self.window_id)?;
+            self.width = width;
+            self.height = height;
+        }
+
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+
+        tracing::info!(
+            "Window capture channel initialized for window {} ({}x{})",
+            self.window_id,
+            self.width,
+            self.height
+        );
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if self.is_recording.load(Ordering::SeqCst) {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        let output_dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+
+        let (window, window_width, window_height) = find_window(self.window_id)?;
+
+        let encoder = Arc::new(
+            FFmpegSegmentEncoder::new(
+                self.width,
+                self.height,
+                self.fps,
+                &self.video_encoding,
+                self.quality,
+                &output_dir,
+                self.session_index,
+            )
+            .map_err(|e| RecordingError::CaptureError(format!("Failed to start FFmpeg: {}", e)))?,
+        );
+
+        // Desktop-independent window filter: follows the window across
+        // displays and keeps capturing when it is covered by other windows
+        let filter = SCContentFilter::create().with_window(&window).build();
+        let config = stream_config(window_width, window_height, self.fps, self.show_cursor);
+        let mut stream = SCStream::new(&filter, &config);
+
+        self.resizes.lock().clear();
+        self.is_recording.store(true, Ordering::SeqCst);
+        let origin = Arc::new(ParkingMutex::new(window_origin(&window, self.display_id)));
+
+        let handler = WindowOutputHandler {
+            encoder: encoder.clone(),
+            is_recording: self.is_recording.clone(),
+            canvas_width: self.width,
+            canvas_height: self.height,
+            canvas: ParkingMutex::new(vec![0; (self.width * self.height * 4) as usize]),
+            session_start: Instant::now(),
+            origin: origin.clone(),
+            last_placement: ParkingMutex::new(None),
+            resizes: self.resizes.clone(),
+            dropped_frames: self.dropped_frames.clone(),
+        };
+        stream.add_output_handler(handler, SCStreamOutputType::Screen);
+
+        stream.start_capture().map_err(|e| {
+            self.is_recording.store(false, Ordering::SeqCst);
+            RecordingError::CaptureError(format!("Failed to start window capture: {:?}", e))
+        })?;
+
+        *self.stream.lock() = Some(stream);
+        self.encoder = Some(encoder);
+
+        // Follow window resizes so frames keep the window's native size, and
+        // moves so mouse positions stay relative to the window
+        let is_recording = self.is_recording.clone();
+        let stream = self.stream.clone();
+        let (window_id, display_id) = (self.window_id, self.display_id);
+        let (fps, show_cursor) = (self.fps, self.show_cursor);
+        self.resize_handle = Some(tokio::spawn(async move {
+            let mut size = (window_width, window_height);
+            while is_recording.load(Ordering::SeqCst) {
+                tokio::time::sleep(RESIZE_POLL_INTERVAL).await;
+
+                let Ok((window, width, height)) = find_window(window_id) else {
+                    continue;
+                };
+                *origin.lock() = window_origin(&window, display_id);
+                if (width, height) == size {
+                    continue;
+                }
+
+                if let Some(stream) = stream.lock().as_ref() {
+                    let config = stream_config(width, height, fps, show_cursor);
+                    match stream.update_configuration(&config) {
+                        Ok(()) => {
+                            tracing::info!("Window {} resized to {}x{}", window_id, width, height);
+                            size = (width, height);
+                        }
+                        Err(e) => tracing::warn!("Failed to follow window resize: {:?}", e),
+                    }
+                }
+            }
+        }));
+
+        tracing::info!(
+            "Window capture started for window {} ({}x{} canvas @ {}fps)",
+            self.window_id,
+            self.width,
+            self.height,
+            self.fps
+        );
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> RecordingResult<()> {
+        if !self.is_recording.load(Ordering::SeqCst) {
+            return Err(RecordingError::NotRecording);
+        }
+
+        self.is_recording.store(false, Ordering::SeqCst);
+
+        if let Some(handle) = self.resize_handle.take() {
+            let _ = handle.await;
+        }
+
+        if let Some(stream) = self.stream.lock().take() {
+            if let Err(e) = stream.stop_capture() {
+                tracing::warn!("Error stopping window capture stream: {:?}", e);
+            }
+        }
+
+        if let Some(encoder) = self.encoder.take() {
+            let files = encoder.finish().map_err(|e| {
+                RecordingError::CaptureError(format!("Failed to finish encoding: {}", e))
+            })?;
+            self.output_files.lock().extend(files);
+        }
+
+        if let Some(output_dir) = self.output_dir.clone() {
+            self.write_resizes(&output_dir)?;
+        }
+
+        tracing::info!("Window capture stopped");
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> RecordingResult<()> {
+        self.stop().await
+    }
+
+    async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+        self.session_index = session_index;
+        self.start().await
+    }
+
+    fn is_recording(&self) -> bool {
+        self.is_recording.load(Ordering::SeqCst)
+    }
+
+    fn output_files(&self) -> Vec<String> {
+        self.output_files.lock().clone()
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: Some(format!("window-{}", self.window_id)),
+            width: Some(self.width),
+            height: Some(self.height),
+            fps: Some(self.fps),
+            downscale: Some(self.quality.downscale()).filter(|&n| n > 1),
+            shows_cursor: self.show_cursor,
+            ..Default::default()
+        }
+    }
+
+    fn dropped_frames(&self) -> Option<u64> {
+        Some(self.dropped_frames.load(Ordering::Relaxed))
+    }
+}
@@ -0,0 +1,458 @@
+//! Synthetic capture sources for automated testing
+//!
+//! Stand-ins for the display, webcam, audio and input channels that need no
+//! screen, microphone or camera, so the coordinator, bundle format and export
+//! pipeline can be exercised on headless CI machines. Video is a test pattern
+//! and audio a sine tone, rendered with FFmpeg's `lavfi` sources when each
+//! session stops; input is a scripted cursor path with a click every second.
+//!
+//! Enabled by building with the `mock-capture` feature or by setting
+//! `OPEN_SCREENSTUDIO_MOCK_CAPTURE=1`.
+
+use crate::capture::frame::Rotation;
+use crate::capture::frame_rate::display_fps;
+use crate::capture::input::types::{CursorInfo, MouseClick, MouseMove};
+use crate::capture::traits::DisplayInfo;
+use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::registry::{ChannelContext, ChannelRegistry};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Environment variable that switches capture to the mock sources
+pub const MOCK_CAPTURE_ENV: &str = "OPEN_SCREENSTUDIO_MOCK_CAPTURE";
+
+/// Display reported and recorded in mock mode
+pub const MOCK_DISPLAY_ID: u32 = 1;
+const MOCK_DISPLAY_SIZE: (u32, u32) = (1920, 1080);
+const MOCK_FPS: u32 = 30;
+
+/// Interval between synthetic mouse moves (60Hz)
+const INPUT_INTERVAL_MS: f64 = 1000.0 / 60.0;
+
+/// Whether capture should use the mock sources
+pub fn is_enabled() -> bool {
+    cfg!(feature = "mock-capture")
+        || std::env::var(MOCK_CAPTURE_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// The single display available in mock mode
+pub fn mock_display() -> DisplayInfo {
+    DisplayInfo {
+        id: MOCK_DISPLAY_ID,
+        name: "Mock Display".to_string(),
+        width: MOCK_DISPLAY_SIZE.0,
+        height: MOCK_DISPLAY_SIZE.1,
+        scale_factor: 1.0,
+        is_primary: true,
+        refresh_rate: Some(60),
+        rotation: Rotation::None,
+    }
+}
+
+/// What a mock channel produces
+#[derive(Debug, Clone, Copy)]
+pub enum MockSource {
+    /// Test pattern video
+    Video {
+        kind: FileKind,
+        width: u32,
+        height: u32,
+        fps: u32,
+    },
+    /// Sine tone audio
+    Tone { kind: FileKind, frequency: u32 },
+    /// Cursor moving in a circle around the display center, clicking once
+    /// a second
+    Input { width: u32, height: u32 },
+}
+
+impl MockSource {
+    fn channel_type(&self) -> ChannelType {
+        match self {
+            MockSource::Video {
+                kind: FileKind::WebcamVideo,
+                ..
+            } => ChannelType::Webcam,
+            MockSource::Video { .. } => ChannelType::Display,
+            MockSource::Tone {
+                kind: FileKind::SystemAudio,
+                ..
+            } => ChannelType::SystemAudio,
+            MockSource::Tone { .. } => ChannelType::Microphone,
+            MockSource::Input { .. } => ChannelType::Input,
+        }
+    }
+}
+
+/// Recording channel backed by a synthetic source
+pub struct MockChannel {
+    id: String,
+    source: MockSource,
+    output_dir: Option<PathBuf>,
+    session_index: usize,
+    started: Option<(Instant, u64)>,
+    output_files: Vec<String>,
+}
+
+impl MockChannel {
+    pub fn new(source: MockSource) -> Self {
+        Self {
+            id: format!("mock-{}", source.channel_type()),
+            source,
+            output_dir: None,
+            session_index: 0,
+            started: None,
+            output_files: Vec::new(),
+        }
+    }
+
+    /// Write the files for a session that lasted `duration_ms`
+    fn render(
+        &self,
+        dir: &Path,
+        duration_ms: f64,
+        start_unix_ms: u64,
+    ) -> RecordingResult<Vec<PathBuf>> {
+        let seconds = format!("{:.3}", duration_ms.max(1.0) / 1000.0);
+        match self.source {
+            MockSource::Video {
+                kind,
+                width,
+                height,
+                fps,
+            } => {
+                let path = dir.join(kind.conventional_name(self.session_index));
+                run_ffmpeg(
+                    &[
+                        "-f",
+                        "lavfi",
+                        "-i",
+                        &format!("testsrc2=size={}x{}:rate={}", width, height, fps),
+                        "-t",
+                        &seconds,
+                        "-c:v",
+                        "libx264",
+                        "-preset",
+                        "ultrafast",
+                        "-pix_fmt",
+                        "yuv420p",
+                    ],
+                    &path,
+                )?;
+                Ok(vec![path])
+            }
+            MockSource::Tone { kind, frequency } => {
+                let path = dir.join(kind.conventional_name(self.session_index));
+                run_ffmpeg(
+                    &[
+                        "-f",
+                        "lavfi",
+                        "-i",
+                        &format!("sine=frequency={}:sample_rate=48000", frequency),
+                        "-t",
+                        &seconds,
+                        "-c:a",
+                        "aac",
+                    ],
+                    &path,
+                )?;
+                Ok(vec![path])
+            }
+            MockSource::Input { width, height } => {
+                let (moves, clicks) = synthetic_input(width, height, duration_ms, start_unix_ms);
+                let cursors: HashMap<String, CursorInfo> = HashMap::new();
+                let files = [
+                    (FileKind::MouseMoves, serde_json::to_vec_pretty(&moves)),
+                    (FileKind::MouseClicks, serde_json::to_vec_pretty(&clicks)),
+                    (FileKind::Cursors, serde_json::to_vec_pretty(&cursors)),
+                ];
+
+                let mut paths = Vec::new();
+                for (kind, data) in files {
+                    let data = data.map_err(|e| RecordingError::EncodingError(e.to_string()))?;
+                    let path = dir.join(kind.conventional_name(self.session_index));
+                    std::fs::write(&path, data)?;
+                    paths.push(path);
+                }
+                Ok(paths)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RecordingChannel for MockChannel {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn channel_type(&self) -> ChannelType {
+        self.source.channel_type()
+    }
+
+    async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
+        std::fs::create_dir_all(output_dir)?;
+        self.output_dir = Some(output_dir.to_path_buf());
+        self.session_index = session_index;
+        if session_index == 0 {
+            self.output_files.clear();
+        }
+        Ok(())
+    }
+
+    async fn start(&mut self) -> RecordingResult<()> {
+        if self.started.is_some() {
+            return Err(RecordingError::AlreadyRecording);
+        }
+        self.started = Some((Instant::now(), now_unix_ms()));
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> RecordingResult<()> {
+        let (started, start_unix_ms) = self.started.take().ok_or(RecordingError::NotRecording)?;
+        let dir = self.output_dir.clone().ok_or_else(|| {
+            RecordingError::ConfigurationError("Output directory not set".to_string())
+        })?;
+
+        let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let paths = self.render(&dir, duration_ms, start_unix_ms)?;
+        self.output_files
+            .extend(paths.iter().map(|p| p.to_string_lossy().to_string()));
+
+        tracing::info!(
+            "{} wrote session {} ({:.0}ms)",
+            self.id,
+            self.session_index,
+            duration_ms
+        );
+        Ok(())
+    }
+
+    async fn pause(&mut self) -> RecordingResult<()> {
+        self.stop().await
+    }
+
+    async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+        self.session_index = session_index;
+        self.start().await
+    }
+
+    fn is_recording(&self) -> bool {
+        self.started.is_some()
+    }
+
+    fn output_files(&self) -> Vec<String> {
+        self.output_files.clone()
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        match self.source {
+            MockSource::Video {
+                width, height, fps, ..
+            } => ChannelSettings {
+                fps: Some(fps),
+                width: Some(width),
+                height: Some(height),
+                device: Some("mock".to_string()),
+                ..Default::default()
+            },
+            MockSource::Tone { .. } => ChannelSettings {
+                device: Some("mock".to_string()),
+                sample_rate: Some(48000),
+                channels: Some(1),
+                ..Default::default()
+            },
+            MockSource::Input { .. } => ChannelSettings::default(),
+        }
+    }
+}
+
+/// Replace the registry's capture channels with mock sources
+pub fn register_mock_channels(registry: &mut ChannelRegistry) {
+    let (width, height) = MOCK_DISPLAY_SIZE;
+
+    registry.register(ChannelType::Display, move |ctx: &ChannelContext| {
+        ctx.config.capture_screen.then(|| {
+            Box::new(MockChannel::new(MockSource::Video {
+                kind: FileKind::ScreenVideo,
+                width,
+                height,
+                fps: display_fps(
+                    ctx.config.quality,
+                    mock_display().refresh_rate,
+                    ctx.config.max_fps,
+                ),
+            })) as Box<dyn RecordingChannel>
+        })
+    });
+    registry.register(ChannelType::Input, move |ctx: &ChannelContext| {
+        ctx.config.capture_screen.then(|| {
+            Box::new(MockChannel::new(MockSource::Input { width, height }))
+                as Box<dyn RecordingChannel>
+        })
+    });
+    registry.register(ChannelType::Microphone, |ctx: &ChannelContext| {
+        ctx.config.capture_microphone.then(|| {
+            Box::new(MockChannel::new(MockSource::Tone {
+                kind: FileKind::MicAudio,
+                frequency: 440,
+            })) as Box<dyn RecordingChannel>
+        })
+    });
+    registry.register(ChannelType::SystemAudio, |ctx: &ChannelContext| {
+        ctx.config.capture_system_audio.then(|| {
+            Box::new(MockChannel::new(MockSource::Tone {
+                kind: FileKind::SystemAudio,
+                frequency: 660,
+            })) as Box<dyn RecordingChannel>
+        })
+    });
+    registry.register(ChannelType::Webcam, |ctx: &ChannelContext| {
+        ctx.config.capture_webcam.then(|| {
+            Box::new(MockChannel::new(MockSource::Video {
+                kind: FileKind::WebcamVideo,
+                width: 1280,
+                height: 720,
+                fps: MOCK_FPS,
+            })) as Box<dyn RecordingChannel>
+        })
+    });
+}
+
+/// Cursor path and clicks for a session
+fn synthetic_input(
+    width: u32,
+    height: u32,
+    duration_ms: f64,
+    start_unix_ms: u64,
+) -> (Vec<MouseMove>, Vec<MouseClick>) {
+    let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+    let radius = cx.min(cy) * 0.6;
+
+    let count = (duration_ms / INPUT_INTERVAL_MS).floor() as usize + 1;
+    let moves: Vec<MouseMove> = (0..count)
+        .map(|i| {
+            let t = i as f64 * INPUT_INTERVAL_MS;
+            // One revolution every four seconds
+            let angle = t / 4000.0 * std::f64::consts::TAU;
+            MouseMove {
+                x: cx + radius * angle.cos(),
+                y: cy + radius * angle.sin(),
+                cursor_id: "arrow".to_string(),
+                active_modifiers: Vec::new(),
+                process_time_ms: t,
+                unix_time_ms: start_unix_ms + t as u64,
+            }
+        })
+        .collect();
+
+    let clicks = moves
+        .iter()
+        .filter(|m| m.process_time_ms > 0.0 && m.process_time_ms % 1000.0 < INPUT_INTERVAL_MS)
+        .flat_map(|m| {
+            ["down", "up"]
+                .into_iter()
+                .enumerate()
+                .map(|(i, event_type)| MouseClick {
+                    x: m.x,
+                    y: m.y,
+                    button: "left".to_string(),
+                    event_type: event_type.to_string(),
+                    click_count: 1,
+                    active_modifiers: Vec::new(),
+                    process_time_ms: m.process_time_ms + i as f64 * 80.0,
+                    unix_time_ms: m.unix_time_ms + i as u64 * 80,
+                })
+        })
+        .collect();
+
+    (moves, clicks)
+}
+
+fn run_ffmpeg(input_args: &[&str], output: &Path) -> RecordingResult<()> {
+    let result = Command::new("ffmpeg")
+        .args(["-y", "-v", "error"])
+        .args(input_args)
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                RecordingError::FfmpegMissing("FFmpeg is required for mock capture".to_string())
+            } else {
+                RecordingError::IoError(e)
+            }
+        })?;
+
+    if !result.status.success() {
+        return Err(RecordingError::EncodingError(format!(
+            "Mock capture failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn now_unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_input_covers_session() {
+        let (moves, clicks) = synthetic_input(1920, 1080, 2500.0, 1_000);
+
+        assert_eq!(moves.len(), 151);
+        assert_eq!(moves[0].unix_time_ms, 1_000);
+        assert!(moves
+            .iter()
+            .all(|m| (0.0..1920.0).contains(&m.x) && (0.0..1080.0).contains(&m.y)));
+        // Clicks at 1s and 2s, each a down/up pair
+        assert_eq!(clicks.len(), 4);
+        assert_eq!(clicks[0].event_type, "down");
+        assert_eq!(clicks[1].event_type, "up");
+    }
+
+    #[tokio::test]
+    async fn test_input_channel_writes_every_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut channel = MockChannel::new(MockSource::Input {
+            width: 1280,
+            height: 720,
+        });
+
+        channel.initialize(dir.path(), 0).await.unwrap();
+        channel.start().await.unwrap();
+        channel.pause().await.unwrap();
+        channel.resume(1).await.unwrap();
+        channel.stop().await.unwrap();
+
+        assert_eq!(channel.output_files().len(), 6);
+        for session in 0..2 {
+            let moves: Vec<MouseMove> = serde_json::from_slice(
+                &std::fs::read(
+                    dir.path()
+                        .join(FileKind::MouseMoves.conventional_name(session)),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert!(!moves.is_empty());
+        }
+        assert!(matches!(
+            channel.stop().await,
+            Err(RecordingError::NotRecording)
+        ));
+    }
+}
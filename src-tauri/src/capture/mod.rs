@@ -3,8 +3,20 @@
 //! This module provides screen, audio, and input capture for each platform.
 
 pub mod traits;
+pub mod activity;
+pub mod annotation;
 pub mod audio;
+pub mod camera_format;
+pub mod camera_preview;
+pub mod channels;
+pub mod cues;
+pub mod display_watch;
+pub mod frame;
+pub mod frame_rate;
 pub mod input;
+pub mod level;
+pub mod mock;
+pub mod screenshot;
 
 #[cfg(target_os = "macos")]
 pub mod macos;
@@ -12,8 +24,11 @@ pub mod macos;
 #[cfg(target_os = "windows")]
 pub mod windows;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 // Re-export traits
-pub use traits::{DisplayInfo, WindowInfo, WindowBounds, AudioDeviceInfo, CameraInfo, Resolution};
+pub use traits::{DisplayInfo, WindowInfo, WindowBounds, AudioDeviceInfo, CameraFormat, CameraInfo, Resolution};
 
 // Re-export permission functions from traits (which delegates to platform)
 pub use traits::{has_screen_recording_permission, request_screen_recording_permission};
@@ -22,4 +37,10 @@ pub use traits::{has_screen_recording_permission, request_screen_recording_permi
 pub use audio::{get_audio_input_devices, MicrophoneCaptureChannel};
 
 // Re-export input channel
-pub use input::InputTrackingChannel;
+pub use input::{InputTrackingChannel, KeyboardTrackingChannel};
+
+// Re-export app activity channel
+pub use activity::AppActivityChannel;
+
+// Re-export annotation channel
+pub use annotation::{AnnotationChannel, AnnotationSink};
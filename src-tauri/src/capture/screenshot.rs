@@ -0,0 +1,346 @@
+//! Still screenshot capture
+//!
+//! Grabs a single full-resolution frame from a display and writes it as a PNG,
+//! optionally with the system cursor composited on top. Works both while a
+//! recording is running and standalone. A captured screenshot can then be
+//! framed with the project's background, padding and shadow.
+//!
+//! Small thumbnails of each display are captured the same way for the
+//! display picker.
+
+use crate::export::background::compose_on_background;
+use crate::project::schema::ProjectConfig;
+use crate::recorder::channel::{RecordingError, RecordingResult};
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// Width display thumbnails are scaled down to (never up)
+pub const THUMBNAIL_MAX_WIDTH: u32 = 480;
+
+/// Result of a screenshot capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenshotResult {
+    /// Path to the written PNG file
+    pub path: String,
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+    /// Display the screenshot was taken from
+    pub display_id: u32,
+}
+
+/// Result of framing a screenshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FramedScreenshot {
+    /// Path to the written PNG file
+    pub path: String,
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+}
+
+/// Current cursor image and position, in display pixel coordinates
+pub(crate) struct CursorSnapshot {
+    /// PNG-encoded cursor image
+    pub png_data: Vec<u8>,
+    /// Pointer X position (top-left origin)
+    pub x: f64,
+    /// Pointer Y position (top-left origin)
+    pub y: f64,
+    /// Hotspot X offset within the cursor image
+    pub hotspot_x: f64,
+    /// Hotspot Y offset within the cursor image
+    pub hotspot_y: f64,
+}
+
+/// Capture a screenshot of a display and save it as PNG
+pub fn capture_screenshot(
+    display_id: u32,
+    output_path: &Path,
+    include_cursor: bool,
+) -> RecordingResult<ScreenshotResult> {
+    let (bgra, width, height) = capture_frame(display_id).ok_or_else(|| {
+        RecordingError::CaptureError(format!("Failed to capture display {}", display_id))
+    })?;
+
+    let mut rgba = bgra_to_rgba(&bgra);
+
+    if include_cursor {
+        match cursor_snapshot(display_id) {
+            Some(snapshot) => match decode_png_rgba(&snapshot.png_data) {
+                Some((cursor, cw, ch)) => {
+                    let left = (snapshot.x - snapshot.hotspot_x).round() as i64;
+                    let top = (snapshot.y - snapshot.hotspot_y).round() as i64;
+                    blend_rgba(&mut rgba, width, height, &cursor, cw, ch, left, top);
+                }
+                None => tracing::warn!("Failed to decode cursor image, skipping cursor"),
+            },
+            None => tracing::warn!("Cursor snapshot unavailable, skipping cursor"),
+        }
+    }
+
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_png(output_path, &rgba, width, height)?;
+
+    tracing::info!(
+        "Screenshot saved: {:?} ({}x{})",
+        output_path,
+        width,
+        height
+    );
+
+    Ok(ScreenshotResult {
+        path: output_path.to_string_lossy().to_string(),
+        width,
+        height,
+        display_id,
+    })
+}
+
+/// Capture a display scaled down to `THUMBNAIL_MAX_WIDTH`, as PNG bytes
+pub fn capture_thumbnail(display_id: u32) -> RecordingResult<Vec<u8>> {
+    let (bgra, width, height) = capture_frame(display_id).ok_or_else(|| {
+        RecordingError::CaptureError(format!("Failed to capture display {}", display_id))
+    })?;
+    let (rgba, width, height) = downscale_to_rgba(&bgra, width, height, THUMBNAIL_MAX_WIDTH);
+
+    let mut png = Vec::new();
+    encode_png(&mut png, &rgba, width, height)?;
+    Ok(png)
+}
+
+/// Place a screenshot PNG on the project's background with its padding and
+/// shadow, and save the result as PNG
+pub fn frame_screenshot(
+    input_path: &Path,
+    output_path: &Path,
+    config: &ProjectConfig,
+) -> RecordingResult<FramedScreenshot> {
+    let data = std::fs::read(input_path)?;
+    let (rgba, width, height) = decode_png_rgba(&data).ok_or_else(|| {
+        RecordingError::EncodingError(format!("Failed to decode screenshot {:?}", input_path))
+    })?;
+
+    let (framed, width, height) = compose_on_background(&rgba, width, height, config);
+
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_png(output_path, &framed, width, height)?;
+
+    tracing::info!(
+        "Framed screenshot saved: {:?} ({}x{})",
+        output_path,
+        width,
+        height
+    );
+
+    Ok(FramedScreenshot {
+        path: output_path.to_string_lossy().to_string(),
+        width,
+        height,
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn capture_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+    crate::capture::macos::screen::capture_display_frame(display_id)
+}
+
+#[cfg(target_os = "windows")]
+fn capture_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+    crate::capture::windows::screen::capture_display_frame(display_id)
+}
+
+#[cfg(target_os = "linux")]
+fn capture_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+    crate::capture::linux::screen::capture_display_frame(display_id)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn capture_frame(_display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn cursor_snapshot(display_id: u32) -> Option<CursorSnapshot> {
+    crate::capture::macos::input::current_cursor_snapshot(display_id)
+}
+
+#[cfg(target_os = "windows")]
+fn cursor_snapshot(display_id: u32) -> Option<CursorSnapshot> {
+    crate::capture::windows::input::current_cursor_snapshot(display_id)
+}
+
+/// Cursor compositing isn't supported on Linux; screenshots are saved without it
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn cursor_snapshot(_display_id: u32) -> Option<CursorSnapshot> {
+    None
+}
+
+/// Convert a BGRA frame to opaque RGBA
+fn bgra_to_rgba(bgra: &[u8]) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(bgra.len());
+    for px in bgra.chunks_exact(4) {
+        rgba.extend_from_slice(&[px[2], px[1], px[0], 255]);
+    }
+    rgba
+}
+
+/// Convert a BGRA frame to opaque RGBA no wider than `max_width`, keeping
+/// its aspect ratio
+fn downscale_to_rgba(bgra: &[u8], width: u32, height: u32, max_width: u32) -> (Vec<u8>, u32, u32) {
+    let rgba = bgra_to_rgba(bgra);
+    if width <= max_width {
+        return (rgba, width, height);
+    }
+    let out_height =
+        ((height as u64 * max_width as u64 + width as u64 / 2) / width as u64).max(1) as u32;
+    let Some(image) = RgbaImage::from_raw(width, height, rgba) else {
+        return (Vec::new(), 0, 0);
+    };
+    let scaled = imageops::resize(&image, max_width, out_height, FilterType::Triangle);
+    (scaled.into_raw(), max_width, out_height)
+}
+
+/// Decode PNG bytes into RGBA pixels
+pub(crate) fn decode_png_rgba(data: &[u8]) -> Option<(Vec<u8>, u32, u32)> {
+    let decoder = png::Decoder::new(std::io::Cursor::new(data));
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).ok()?;
+    let pixels = &buf[..info.buffer_size()];
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|c| [c[0], c[1], c[2], 255])
+            .collect(),
+        _ => return None,
+    };
+
+    Some((rgba, info.width, info.height))
+}
+
+/// Alpha-blend an RGBA image onto an opaque RGBA canvas at (left, top)
+#[allow(clippy::too_many_arguments)]
+fn blend_rgba(
+    canvas: &mut [u8],
+    canvas_width: u32,
+    canvas_height: u32,
+    image: &[u8],
+    image_width: u32,
+    image_height: u32,
+    left: i64,
+    top: i64,
+) {
+    for iy in 0..image_height as i64 {
+        let cy = top + iy;
+        if cy < 0 || cy >= canvas_height as i64 {
+            continue;
+        }
+        for ix in 0..image_width as i64 {
+            let cx = left + ix;
+            if cx < 0 || cx >= canvas_width as i64 {
+                continue;
+            }
+
+            let src = ((iy * image_width as i64 + ix) * 4) as usize;
+            let dst = ((cy * canvas_width as i64 + cx) * 4) as usize;
+            let alpha = image[src + 3] as u32;
+            if alpha == 0 {
+                continue;
+            }
+
+            for c in 0..3 {
+                let s = image[src + c] as u32;
+                let d = canvas[dst + c] as u32;
+                canvas[dst + c] = ((s * alpha + d * (255 - alpha)) / 255) as u8;
+            }
+        }
+    }
+}
+
+/// Write RGBA pixels to a PNG file
+fn write_png(path: &Path, rgba: &[u8], width: u32, height: u32) -> RecordingResult<()> {
+    let file = std::fs::File::create(path)?;
+    encode_png(std::io::BufWriter::new(file), rgba, width, height)
+}
+
+/// Encode RGBA pixels as PNG
+fn encode_png<W: Write>(writer: W, rgba: &[u8], width: u32, height: u32) -> RecordingResult<()> {
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| RecordingError::EncodingError(e.to_string()))?;
+    writer
+        .write_image_data(rgba)
+        .map_err(|e| RecordingError::EncodingError(e.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bgra_to_rgba_swaps_channels() {
+        let bgra = vec![10, 20, 30, 0, 1, 2, 3, 4];
+        assert_eq!(bgra_to_rgba(&bgra), vec![30, 20, 10, 255, 3, 2, 1, 255]);
+    }
+
+    #[test]
+    fn test_blend_rgba_clips_to_canvas() {
+        let mut canvas = vec![0u8; 2 * 2 * 4];
+        let image = vec![255u8; 2 * 2 * 4];
+
+        // Place the image so only its top-left pixel lands on the canvas
+        blend_rgba(&mut canvas, 2, 2, &image, 2, 2, 1, 1);
+
+        assert_eq!(&canvas[0..4], &[0, 0, 0, 0]);
+        assert_eq!(&canvas[12..15], &[255, 255, 255]);
+    }
+
+    #[test]
+    fn test_downscale_keeps_aspect_ratio() {
+        let bgra: Vec<u8> = [0u8, 0, 0, 255, 255, 255, 255, 255].repeat(4);
+
+        // Already small enough
+        let (rgba, w, h) = downscale_to_rgba(&bgra, 4, 2, 480);
+        assert_eq!((w, h, rgba.len()), (4, 2, 32));
+
+        // Odd sizes still cover the whole frame
+        let bgra = vec![128u8; 1000 * 563 * 4];
+        let (rgba, w, h) = downscale_to_rgba(&bgra, 1000, 563, 480);
+        assert_eq!((w, h, rgba.len()), (480, 270, 480 * 270 * 4));
+        assert!(rgba.chunks_exact(4).all(|px| px == [128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn test_png_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shot.png");
+        let rgba = vec![1, 2, 3, 255, 4, 5, 6, 255];
+
+        write_png(&path, &rgba, 2, 1).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        let (decoded, w, h) = decode_png_rgba(&data).unwrap();
+
+        assert_eq!((w, h), (2, 1));
+        assert_eq!(decoded, rgba);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Platform-agnostic traits for capture sources.
 
+use crate::capture::frame::Rotation;
 use serde::{Deserialize, Serialize};
 
 /// Information about a display/screen
@@ -28,6 +29,11 @@ pub struct DisplayInfo {
     
     /// Refresh rate in Hz (if available)
     pub refresh_rate: Option<u32>,
+    
+    /// How the display is turned from its native orientation; width and
+    /// height are as shown on screen
+    #[serde(default)]
+    pub rotation: Rotation,
 }
 
 /// Information about a capture window
@@ -50,8 +56,28 @@ pub struct WindowInfo {
     pub is_on_screen: bool,
 }
 
+/// Frontmost application window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontmostWindow {
+    /// Application name
+    pub app: String,
+    
+    /// Window title, if the platform reports one
+    pub title: Option<String>,
+    
+    /// Window number (CGWindowID on macOS, HWND on Windows)
+    #[serde(default)]
+    pub id: Option<u64>,
+    
+    /// Where the window is on screen, in global screen coordinates (points
+    /// on macOS, pixels on Windows)
+    #[serde(default)]
+    pub bounds: Option<WindowBounds>,
+}
+
 /// Window bounds
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WindowBounds {
     pub x: i32,
     pub y: i32,
@@ -74,6 +100,72 @@ pub struct AudioDeviceInfo {
     
     /// Whether this is the default device
     pub is_default: bool,
+    
+    /// Sample rates the device can record at, out of the ones recordings
+    /// support (empty if unknown)
+    #[serde(default)]
+    pub sample_rates: Vec<u32>,
+}
+
+/// An application whose audio can be recorded on its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioCaptureApp {
+    /// Stable app ID: the bundle identifier on macOS, the executable name
+    /// on Windows
+    pub id: String,
+    
+    /// Display name
+    pub name: String,
+    
+    /// Process ID of the running app
+    pub pid: u32,
+}
+
+/// Device access that needs the user's permission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaPermission {
+    Camera,
+    Microphone,
+}
+
+/// Whether the user allowed access to a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionStatus {
+    Granted,
+    /// Refused by the user or blocked by policy; only System Settings can
+    /// change it
+    Denied,
+    /// Not asked yet; requesting shows the system prompt
+    Undetermined,
+}
+
+/// Whether system audio can be recorded on this machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemAudioSupport {
+    pub available: bool,
+    
+    /// Why system audio can't be recorded, for showing to the user
+    pub reason: Option<String>,
+}
+
+impl SystemAudioSupport {
+    pub fn available() -> Self {
+        Self {
+            available: true,
+            reason: None,
+        }
+    }
+    
+    pub fn unavailable(reason: impl Into<String>) -> Self {
+        Self {
+            available: false,
+            reason: Some(reason.into()),
+        }
+    }
 }
 
 /// Information about a camera/webcam
@@ -88,6 +180,11 @@ pub struct CameraInfo {
     
     /// Supported resolutions
     pub supported_resolutions: Vec<Resolution>,
+    
+    /// Formats the camera can capture in, largest and fastest first (empty
+    /// if they couldn't be queried)
+    #[serde(default)]
+    pub supported_formats: Vec<CameraFormat>,
 }
 
 /// Video resolution
@@ -97,8 +194,26 @@ pub struct Resolution {
     pub height: u32,
 }
 
+/// A camera capture format: resolution, frame rate and pixel format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraFormat {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// Pixel format the camera delivers, by its FFmpeg name ("mjpeg",
+    /// "yuyv422", "nv12", ...). When requesting a format, None accepts any.
+    #[serde(default)]
+    pub pixel_format: Option<String>,
+}
+
 /// Check if screen recording permission is granted
 pub fn has_screen_recording_permission() -> bool {
+    // Mock capture doesn't touch the screen
+    if crate::capture::mock::is_enabled() {
+        return true;
+    }
+    
     #[cfg(target_os = "macos")]
     {
         macos::permissions::has_screen_recording_permission()
@@ -110,12 +225,182 @@ pub fn has_screen_recording_permission() -> bool {
         true
     }
     
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(target_os = "linux")]
+    {
+        crate::capture::linux::permissions::has_screen_recording_permission()
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         false
     }
 }
 
+/// ID of the display the mouse cursor is currently on
+pub fn display_under_cursor() -> Option<u32> {
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::screen::display_under_cursor()
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        crate::capture::windows::screen::display_under_cursor()
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        crate::capture::linux::screen::display_under_cursor()
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+/// Whether the app may observe mouse and keyboard input (macOS
+/// Accessibility); input tracking records nothing without it
+pub fn has_accessibility_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::permissions::has_accessibility_permission()
+    }
+    
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+/// Ask for input monitoring access, showing the system prompt if needed.
+/// Returns whether access is granted now.
+pub fn request_accessibility_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::permissions::request_accessibility_permission()
+    }
+    
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+/// Current camera or microphone permission, without prompting
+pub fn media_permission_status(kind: MediaPermission) -> PermissionStatus {
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::permissions::media_permission_status(kind)
+    }
+    
+    #[cfg(not(target_os = "macos"))]
+    {
+        // Access is decided when the device is opened
+        let _ = kind;
+        PermissionStatus::Granted
+    }
+}
+
+/// Ask for camera or microphone access, prompting if the user hasn't
+/// decided yet. Blocks until they answer.
+pub fn request_media_permission(kind: MediaPermission) -> PermissionStatus {
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::permissions::request_media_permission(kind)
+    }
+    
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = kind;
+        PermissionStatus::Granted
+    }
+}
+
+/// Whether system audio can be recorded, checked at runtime (on macOS it
+/// depends on the OS version, not the SDK the app was built with)
+pub fn system_audio_support() -> SystemAudioSupport {
+    // Mock capture generates its own system audio
+    if crate::capture::mock::is_enabled() {
+        return SystemAudioSupport::available();
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::system_audio::system_audio_support()
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        crate::capture::windows::system_audio::system_audio_support()
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        crate::capture::linux::system_audio::system_audio_support()
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        SystemAudioSupport::unavailable("System audio capture isn't supported on this platform")
+    }
+}
+
+/// Output devices system audio can be recorded from (empty where the
+/// platform records everything the system plays)
+pub fn get_system_audio_devices() -> Vec<AudioDeviceInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        crate::capture::windows::system_audio::get_output_devices()
+    }
+    
+    #[cfg(not(target_os = "windows"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Running applications that can be picked for an audio app filter
+pub fn get_audio_capture_apps() -> Vec<AudioCaptureApp> {
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::system_audio::get_audio_capture_apps()
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        crate::capture::windows::system_audio::get_audio_capture_apps()
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        Vec::new()
+    }
+}
+
+/// Frontmost application window, ignoring this app's own windows
+pub fn frontmost_window() -> Option<FrontmostWindow> {
+    #[cfg(target_os = "macos")]
+    {
+        crate::capture::macos::window::frontmost_window()
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        crate::capture::windows::input::frontmost_window()
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+/// Application of the frontmost window, ignoring this app's own windows
+pub fn frontmost_app_name() -> Option<String> {
+    frontmost_window().map(|window| window.app)
+}
+
 /// Request screen recording permission
 pub fn request_screen_recording_permission() -> bool {
     #[cfg(target_os = "macos")]
@@ -128,7 +413,12 @@ pub fn request_screen_recording_permission() -> bool {
         true
     }
     
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(target_os = "linux")]
+    {
+        crate::capture::linux::permissions::request_screen_recording_permission()
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         false
     }
@@ -1,24 +1,779 @@
-use crate::capture::input::types::{CursorInfo, MouseClick, MouseMove};
+//! Windows input tracking
+//!
+//! A low-level mouse hook (`WH_MOUSE_LL`) reports button presses as they
+//! happen, and the cursor's position and shape are sampled with
+//! `GetCursorInfo` every poll interval. The hook is called through the
+//! message loop of the thread that installed it, so the tracking thread pumps
+//! messages between samples. The output matches the macOS implementation:
+//! positions in pixels relative to the recorded display, and each cursor
+//! image saved as PNG the first time it's shown.
+//!
+//! Keystrokes are recorded the same way on a thread of their own, from a
+//! low-level keyboard hook (`WH_KEYBOARD_LL`).
+
+use crate::capture::input::keyboard::KeystrokeRecorder;
+use crate::capture::input::types::{CursorInfo, CursorKind, KeyPress, MouseClick, MouseMove};
+use crate::capture::screenshot::CursorSnapshot;
+use crate::capture::traits::{FrontmostWindow, WindowBounds};
+use crate::capture::windows::screen::monitor_rects;
 use crate::recorder::channel::{RecordingError, RecordingResult};
 use parking_lot::Mutex as ParkingMutex;
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::atomic::AtomicBool;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use windows::Win32::Foundation::{HINSTANCE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::{
+    DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO, BITMAPINFOHEADER,
+    BI_RGB, DIB_RGB_COLORS, HBITMAP,
+};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetAsyncKeyState, GetDoubleClickTime, VIRTUAL_KEY, VK_CONTROL, VK_LWIN, VK_MENU, VK_RWIN,
+    VK_SHIFT,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, DispatchMessageW, GetCursorInfo, GetIconInfo, GetSystemMetrics, LoadCursorW,
+    PeekMessageW, SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx, CURSORINFO,
+    CURSOR_SHOWING, HCURSOR, HICON, ICONINFO, IDC_ARROW, IDC_CROSS, IDC_HAND, IDC_IBEAM, IDC_NO,
+    IDC_SIZENS, IDC_SIZEWE, KBDLLHOOKSTRUCT, MSG, MSLLHOOKSTRUCT, PM_REMOVE, SM_CXDOUBLECLK,
+    SM_CYDOUBLECLK, WH_KEYBOARD_LL, WH_MOUSE_LL, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN,
+    WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SYSKEYDOWN,
+    WM_SYSKEYUP,
+};
+
+/// Mouse button event reported by the hook
+struct HookEvent {
+    message: u32,
+    x: i32,
+    y: i32,
+    at: Instant,
+}
+
+thread_local! {
+    /// Button events collected by the hook on the tracking thread
+    static HOOK_EVENTS: RefCell<Vec<HookEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+unsafe extern "system" fn mouse_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 && button_event(wparam.0 as u32).is_some() {
+        let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+        HOOK_EVENTS.with(|events| {
+            events.borrow_mut().push(HookEvent {
+                message: wparam.0 as u32,
+                x: info.pt.x,
+                y: info.pt.y,
+                at: Instant::now(),
+            })
+        });
+    }
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+/// Key event reported by the keyboard hook
+struct KeyHookEvent {
+    vk: u32,
+    down: bool,
+    at: Instant,
+}
 
+thread_local! {
+    /// Key events collected by the hook on the keyboard thread
+    static KEY_EVENTS: RefCell<Vec<KeyHookEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+unsafe extern "system" fn keyboard_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let down = match wparam.0 as u32 {
+        WM_KEYDOWN | WM_SYSKEYDOWN => Some(true),
+        WM_KEYUP | WM_SYSKEYUP => Some(false),
+        _ => None,
+    };
+    if let Some(down) = down.filter(|_| code >= 0) {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        KEY_EVENTS.with(|events| {
+            events.borrow_mut().push(KeyHookEvent {
+                vk: info.vkCode,
+                down,
+                at: Instant::now(),
+            })
+        });
+    }
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+/// Button name and whether it was pressed, for mouse button messages
+fn button_event(message: u32) -> Option<(&'static str, bool)> {
+    match message {
+        WM_LBUTTONDOWN => Some(("left", true)),
+        WM_LBUTTONUP => Some(("left", false)),
+        WM_RBUTTONDOWN => Some(("right", true)),
+        WM_RBUTTONUP => Some(("right", false)),
+        WM_MBUTTONDOWN => Some(("middle", true)),
+        WM_MBUTTONUP => Some(("middle", false)),
+        _ => None,
+    }
+}
+
+/// Groups presses of the same button into double and triple clicks, using
+/// the system's double-click time and distance
+struct ClickCounter {
+    interval: Duration,
+    max_dx: i32,
+    max_dy: i32,
+    last: Option<(&'static str, i32, i32, Instant)>,
+    count: u32,
+}
+
+impl ClickCounter {
+    fn new() -> Self {
+        let (interval, max_dx, max_dy) = unsafe {
+            (
+                GetDoubleClickTime(),
+                GetSystemMetrics(SM_CXDOUBLECLK),
+                GetSystemMetrics(SM_CYDOUBLECLK),
+            )
+        };
+        Self {
+            interval: Duration::from_millis(interval as u64),
+            max_dx: max_dx / 2,
+            max_dy: max_dy / 2,
+            last: None,
+            count: 0,
+        }
+    }
+
+    /// Click count of a press
+    fn press(&mut self, button: &'static str, x: i32, y: i32, at: Instant) -> u32 {
+        let repeated = matches!(self.last, Some((b, lx, ly, lt))
+            if b == button
+                && (x - lx).abs() <= self.max_dx
+                && (y - ly).abs() <= self.max_dy
+                && at.duration_since(lt) <= self.interval);
+        self.count = if repeated { self.count + 1 } else { 1 };
+        self.last = Some((button, x, y, at));
+        self.count
+    }
+}
+
+/// Start input tracking thread (Windows)
 pub fn start_input_tracking(
-    _is_recording: Arc<AtomicBool>,
-    _mouse_moves: Arc<ParkingMutex<Vec<MouseMove>>>,
-    _mouse_clicks: Arc<ParkingMutex<Vec<MouseClick>>>,
-    _cursors: Arc<ParkingMutex<HashMap<String, CursorInfo>>>,
-    _cursors_dir: PathBuf,
-    _start_time: Instant,
-    _poll_interval: Duration,
-    _unix_ms_fn: fn() -> u64,
-    _display_id: u32,
+    is_recording: Arc<AtomicBool>,
+    mouse_moves: Arc<ParkingMutex<Vec<MouseMove>>>,
+    mouse_clicks: Arc<ParkingMutex<Vec<MouseClick>>>,
+    cursors: Arc<ParkingMutex<HashMap<String, CursorInfo>>>,
+    cursors_dir: PathBuf,
+    start_time: Instant,
+    poll_interval: Duration,
+    unix_ms_fn: fn() -> u64,
+    display_id: u32,
+) -> RecordingResult<std::thread::JoinHandle<()>> {
+    std::fs::create_dir_all(&cursors_dir)?;
+
+    // Positions are recorded relative to the display's top-left corner
+    let (origin_x, origin_y) = monitor_rects()
+        .get(display_id as usize)
+        .map(|r| (r.left, r.top))
+        .unwrap_or((0, 0));
+
+    tracing::info!(
+        "Input tracking coordinate transform: display_id={}, origin=({}, {})",
+        display_id,
+        origin_x,
+        origin_y
+    );
+
+    let handle = std::thread::spawn(move || {
+        tracing::info!(
+            "Windows input tracking started (poll_interval={:?})",
+            poll_interval
+        );
+
+        let hook = unsafe {
+            GetModuleHandleW(None).and_then(|module| {
+                SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook), HINSTANCE(module.0), 0)
+            })
+        };
+        if let Err(e) = &hook {
+            tracing::warn!(
+                "Failed to install mouse hook, clicks won't be recorded: {}",
+                e
+            );
+        }
+
+        let standard_kinds = standard_cursor_kinds();
+        // Cursor IDs by handle, so each cursor image is read only once
+        let mut cursor_ids: HashMap<usize, String> = HashMap::new();
+        let mut click_counter = ClickCounter::new();
+
+        while is_recording.load(Ordering::Relaxed) {
+            let loop_start = Instant::now();
+
+            // Run the hook for any pending mouse input
+            unsafe {
+                let mut msg = MSG::default();
+                while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+
+            let modifiers = active_modifiers();
+
+            let events = HOOK_EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()));
+            for event in events {
+                let Some((button, down)) = button_event(event.message) else {
+                    continue;
+                };
+                let click_count = if down {
+                    click_counter.press(button, event.x, event.y, event.at)
+                } else {
+                    click_counter.count.max(1)
+                };
+                let age_ms = event.at.elapsed().as_millis() as u64;
+                mouse_clicks.lock().push(MouseClick {
+                    x: (event.x - origin_x) as f64,
+                    y: (event.y - origin_y) as f64,
+                    button: button.to_string(),
+                    event_type: if down {
+                        "down".to_string()
+                    } else {
+                        "up".to_string()
+                    },
+                    click_count,
+                    active_modifiers: modifiers.clone(),
+                    process_time_ms: event.at.duration_since(start_time).as_secs_f64() * 1000.0,
+                    unix_time_ms: unix_ms_fn().saturating_sub(age_ms),
+                });
+            }
+
+            let mut info = CURSORINFO {
+                cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+                ..Default::default()
+            };
+            if unsafe { GetCursorInfo(&mut info) }.is_ok() {
+                let showing = (info.flags.0 & CURSOR_SHOWING.0) != 0 && !info.hCursor.is_invalid();
+                let cursor_id = if showing {
+                    cursor_ids
+                        .entry(info.hCursor.0 as usize)
+                        .or_insert_with(|| {
+                            let kind = standard_kinds.get(&(info.hCursor.0 as usize)).copied();
+                            match capture_cursor_png(info.hCursor, kind, &cursors_dir) {
+                                Some(cursor) => {
+                                    let id = cursor.id.clone();
+                                    cursors.lock().insert(id.clone(), cursor);
+                                    id
+                                }
+                                None => "unknown".to_string(),
+                            }
+                        })
+                        .clone()
+                } else {
+                    "unknown".to_string()
+                };
+
+                mouse_moves.lock().push(MouseMove {
+                    x: (info.ptScreenPos.x - origin_x) as f64,
+                    y: (info.ptScreenPos.y - origin_y) as f64,
+                    cursor_id,
+                    active_modifiers: modifiers,
+                    process_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
+                    unix_time_ms: unix_ms_fn(),
+                });
+            }
+
+            let elapsed = loop_start.elapsed();
+            if elapsed < poll_interval {
+                std::thread::sleep(poll_interval - elapsed);
+            }
+        }
+
+        if let Ok(hook) = hook {
+            let _ = unsafe { UnhookWindowsHookEx(hook) };
+        }
+        tracing::info!("Windows input tracking thread stopped");
+    });
+
+    Ok(handle)
+}
+
+/// Start keystroke tracking thread (Windows)
+///
+/// Held keys are tracked from the hook's own events, which give the
+/// modifiers of each key press and let auto-repeated key downs be skipped.
+pub fn start_keyboard_tracking(
+    is_recording: Arc<AtomicBool>,
+    keys: Arc<ParkingMutex<Vec<KeyPress>>>,
+    recorder: KeystrokeRecorder,
+    start_time: Instant,
+    unix_ms_fn: fn() -> u64,
 ) -> RecordingResult<std::thread::JoinHandle<()>> {
-    Err(RecordingError::PlatformError(
-        "Windows input tracking not implemented yet".to_string(),
-    ))
+    // The hook runs on the thread that installs it; report back whether
+    // installing it worked
+    let (started_tx, started_rx) = std::sync::mpsc::channel();
+
+    let handle = std::thread::spawn(move || {
+        let hook = unsafe {
+            GetModuleHandleW(None).and_then(|module| {
+                SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook), HINSTANCE(module.0), 0)
+            })
+        };
+        let hook = match hook {
+            Ok(hook) => hook,
+            Err(e) => {
+                let _ = started_tx.send(Err(format!("Failed to install the keyboard hook: {}", e)));
+                return;
+            }
+        };
+        let _ = started_tx.send(Ok(()));
+        tracing::info!("Windows keyboard tracking started");
+
+        let mut held: HashSet<&'static str> = HashSet::new();
+        while is_recording.load(Ordering::Relaxed) {
+            // Run the hook for any pending keyboard input
+            unsafe {
+                let mut msg = MSG::default();
+                while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+
+            let events = KEY_EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()));
+            for event in events {
+                let Some(key) = key_name(event.vk) else {
+                    continue;
+                };
+                let repeated = event.down && !held.insert(key);
+                if !event.down {
+                    held.remove(key);
+                }
+                if repeated {
+                    continue;
+                }
+
+                let active_modifiers = ["shift", "control", "alt", "meta"]
+                    .into_iter()
+                    .filter(|modifier| held.contains(modifier))
+                    .map(String::from)
+                    .collect();
+                let age_ms = event.at.elapsed().as_millis() as u64;
+                let press = KeyPress {
+                    key: key.to_string(),
+                    event_type: if event.down { "down" } else { "up" }.to_string(),
+                    active_modifiers,
+                    masked: false,
+                    process_time_ms: event.at.duration_since(start_time).as_secs_f64() * 1000.0,
+                    unix_time_ms: unix_ms_fn().saturating_sub(age_ms),
+                };
+                if let Some(press) = recorder.record(press) {
+                    keys.lock().push(press);
+                }
+            }
+
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let _ = unsafe { UnhookWindowsHookEx(hook) };
+        tracing::info!("Windows keyboard tracking thread stopped");
+    });
+
+    match started_rx.recv() {
+        Ok(Ok(())) => Ok(handle),
+        Ok(Err(reason)) => Err(RecordingError::PlatformError(reason)),
+        Err(_) => Err(RecordingError::PlatformError(
+            "Keyboard tracking thread exited".to_string(),
+        )),
+    }
+}
+
+/// Name of a virtual key, matching the macOS key names. Letters and digits
+/// follow the active keyboard layout; punctuation is named after the US
+/// layout.
+fn key_name(vk: u32) -> Option<&'static str> {
+    const LETTERS: [&str; 26] = [
+        "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r",
+        "s", "t", "u", "v", "w", "x", "y", "z",
+    ];
+    const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+    const FUNCTION_KEYS: [&str; 12] = [
+        "f1", "f2", "f3", "f4", "f5", "f6", "f7", "f8", "f9", "f10", "f11", "f12",
+    ];
+    Some(match vk {
+        0x41..=0x5A => LETTERS[(vk - 0x41) as usize],
+        0x30..=0x39 => DIGITS[(vk - 0x30) as usize],
+        0x70..=0x7B => FUNCTION_KEYS[(vk - 0x70) as usize],
+        0x10 | 0xA0 | 0xA1 => "shift",
+        0x11 | 0xA2 | 0xA3 => "control",
+        0x12 | 0xA4 | 0xA5 => "alt",
+        0x5B | 0x5C => "meta",
+        0x14 => "capsLock",
+        0x08 => "backspace",
+        0x09 => "tab",
+        0x0D => "return",
+        0x1B => "escape",
+        0x20 => "space",
+        0x21 => "pageUp",
+        0x22 => "pageDown",
+        0x23 => "end",
+        0x24 => "home",
+        0x25 => "arrowLeft",
+        0x26 => "arrowUp",
+        0x27 => "arrowRight",
+        0x28 => "arrowDown",
+        0x2E => "delete",
+        0xBA => ";",
+        0xBB => "=",
+        0xBC => ",",
+        0xBD => "-",
+        0xBE => ".",
+        0xBF => "/",
+        0xC0 => "`",
+        0xDB => "[",
+        0xDC => "\\",
+        0xDD => "]",
+        0xDE => "'",
+        _ => return None,
+    })
+}
+
+/// Modifier keys currently held, named like on macOS
+fn active_modifiers() -> Vec<String> {
+    let pressed =
+        |key: VIRTUAL_KEY| (unsafe { GetAsyncKeyState(key.0 as i32) } as u16 & 0x8000) != 0;
+    let mut v = Vec::new();
+    if pressed(VK_SHIFT) {
+        v.push("shift".to_string());
+    }
+    if pressed(VK_CONTROL) {
+        v.push("control".to_string());
+    }
+    if pressed(VK_MENU) {
+        v.push("alt".to_string());
+    }
+    if pressed(VK_LWIN) || pressed(VK_RWIN) {
+        v.push("meta".to_string());
+    }
+    v
+}
+
+/// Handles of the system's shared standard cursors, to recognize which one
+/// is shown
+fn standard_cursor_kinds() -> HashMap<usize, CursorKind> {
+    [
+        (IDC_ARROW, CursorKind::Arrow),
+        (IDC_HAND, CursorKind::Pointer),
+        (IDC_IBEAM, CursorKind::Text),
+        (IDC_CROSS, CursorKind::Crosshair),
+        (IDC_NO, CursorKind::NotAllowed),
+        (IDC_SIZEWE, CursorKind::ResizeLeftRight),
+        (IDC_SIZENS, CursorKind::ResizeUpDown),
+    ]
+    .into_iter()
+    .filter_map(|(name, kind)| {
+        let cursor = unsafe { LoadCursorW(None, name) }.ok()?;
+        Some((cursor.0 as usize, kind))
+    })
+    .collect()
+}
+
+/// Save a cursor's image as PNG, named after a hash of its pixels and hotspot
+fn capture_cursor_png(
+    cursor: HCURSOR,
+    kind: Option<CursorKind>,
+    cursors_dir: &Path,
+) -> Option<CursorInfo> {
+    let (rgba, width, height, hotspot_x, hotspot_y) = unsafe { cursor_rgba(cursor)? };
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    rgba.hash(&mut hasher);
+    (hotspot_x, hotspot_y).hash(&mut hasher);
+    let cursor_id = format!("cursor_{:016x}", hasher.finish());
+
+    let image_path = cursors_dir.join(format!("{}.png", cursor_id));
+    if let Err(e) = write_png(&image_path, &rgba, width, height) {
+        tracing::warn!("Failed to save cursor image {:?}: {}", image_path, e);
+        return None;
+    }
+
+    Some(CursorInfo {
+        id: cursor_id,
+        image_path: image_path.to_string_lossy().to_string(),
+        hotspot_x: hotspot_x as f64,
+        hotspot_y: hotspot_y as f64,
+        width,
+        height,
+        kind,
+    })
+}
+
+fn write_png(path: &Path, rgba: &[u8], width: u32, height: u32) -> Result<(), String> {
+    let data = encode_png(rgba, width, height)?;
+    std::fs::write(path, data).map_err(|e| e.to_string())
+}
+
+fn encode_png(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(rgba).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
+/// RGBA pixels, size and hotspot of a cursor
+unsafe fn cursor_rgba(cursor: HCURSOR) -> Option<(Vec<u8>, u32, u32, u32, u32)> {
+    let mut info = ICONINFO::default();
+    GetIconInfo(HICON(cursor.0), &mut info).ok()?;
+
+    let color = (!info.hbmColor.is_invalid())
+        .then(|| bitmap_bgra(info.hbmColor))
+        .flatten();
+    let mask = bitmap_bgra(info.hbmMask);
+    if !info.hbmColor.is_invalid() {
+        let _ = DeleteObject(info.hbmColor);
+    }
+    let _ = DeleteObject(info.hbmMask);
+
+    let (mask, mask_width, mask_height) = mask?;
+    let pixels = match color {
+        Some((color, width, height)) => (
+            color_cursor_rgba(&color, &mask, width, height),
+            width,
+            height,
+        ),
+        // Monochrome cursors stack the AND mask on top of the XOR mask
+        None => (
+            monochrome_cursor_rgba(&mask, mask_width, mask_height / 2),
+            mask_width,
+            mask_height / 2,
+        ),
+    };
+    Some((pixels.0, pixels.1, pixels.2, info.xHotspot, info.yHotspot))
+}
+
+/// Read a bitmap as top-down 32-bit BGRA
+unsafe fn bitmap_bgra(bitmap: HBITMAP) -> Option<(Vec<u8>, u32, u32)> {
+    let mut bm = BITMAP::default();
+    let size = std::mem::size_of::<BITMAP>() as i32;
+    if GetObjectW(bitmap, size, Some(&mut bm as *mut BITMAP as *mut _)) == 0 {
+        return None;
+    }
+    let (width, height) = (bm.bmWidth.max(0) as u32, bm.bmHeight.max(0) as u32);
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let mut bmi = BITMAPINFO::default();
+    bmi.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+    bmi.bmiHeader.biWidth = width as i32;
+    bmi.bmiHeader.biHeight = -(height as i32); // Top-down
+    bmi.bmiHeader.biPlanes = 1;
+    bmi.bmiHeader.biBitCount = 32;
+    bmi.bmiHeader.biCompression = BI_RGB.0;
+
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let hdc = GetDC(None);
+    let lines = GetDIBits(
+        hdc,
+        bitmap,
+        0,
+        height,
+        Some(pixels.as_mut_ptr() as *mut _),
+        &mut bmi,
+        DIB_RGB_COLORS,
+    );
+    ReleaseDC(None, hdc);
+
+    (lines == height as i32).then_some((pixels, width, height))
+}
+
+/// Combine a color cursor with its mask. Cursors without per-pixel alpha
+/// use the mask for transparency.
+fn color_cursor_rgba(color: &[u8], mask: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let has_alpha = color.chunks_exact(4).any(|px| px[3] != 0);
+    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+    for (i, px) in color.chunks_exact(4).enumerate() {
+        let transparent = mask.get(i * 4).is_some_and(|&m| m != 0);
+        let alpha = if has_alpha {
+            px[3]
+        } else if transparent {
+            0
+        } else {
+            255
+        };
+        rgba.extend_from_slice(&[px[2], px[1], px[0], alpha]);
+    }
+    rgba
+}
+
+/// Render a monochrome cursor (AND mask over XOR mask). Pixels that invert
+/// the screen are drawn black, as there's no screen to invert.
+fn monochrome_cursor_rgba(masks: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let plane = (width * height * 4) as usize;
+    let mut rgba = Vec::with_capacity(plane);
+    for i in (0..plane).step_by(4) {
+        let and = masks.get(i).is_some_and(|&m| m != 0);
+        let xor = masks.get(plane + i).is_some_and(|&m| m != 0);
+        rgba.extend_from_slice(match (and, xor) {
+            (false, false) => &[0, 0, 0, 255],
+            (false, true) => &[255, 255, 255, 255],
+            (true, false) => &[0, 0, 0, 0],
+            (true, true) => &[0, 0, 0, 255],
+        });
+    }
+    rgba
+}
+
+/// Snapshot the current system cursor and its position on the given display.
+///
+/// Uses the same coordinate transform as the input tracking loop, so the
+/// position is in display pixels relative to the monitor's top-left corner.
+pub(crate) fn current_cursor_snapshot(display_id: u32) -> Option<CursorSnapshot> {
+    let mut info = CURSORINFO {
+        cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+        ..Default::default()
+    };
+    unsafe { GetCursorInfo(&mut info) }.ok()?;
+    if (info.flags.0 & CURSOR_SHOWING.0) == 0 || info.hCursor.is_invalid() {
+        return None;
+    }
+
+    let (origin_x, origin_y) = monitor_rects()
+        .get(display_id as usize)
+        .map(|r| (r.left, r.top))
+        .unwrap_or((0, 0));
+
+    let (rgba, width, height, hotspot_x, hotspot_y) = unsafe { cursor_rgba(info.hCursor) }?;
+    let png_data = encode_png(&rgba, width, height).ok()?;
+
+    Some(CursorSnapshot {
+        png_data,
+        x: (info.ptScreenPos.x - origin_x) as f64,
+        y: (info.ptScreenPos.y - origin_y) as f64,
+        hotspot_x: hotspot_x as f64,
+        hotspot_y: hotspot_y as f64,
+    })
+}
+
+/// Topmost visible window that isn't one of ours, with its application
+/// named after its executable
+#[cfg(target_os = "windows")]
+pub(crate) fn frontmost_window() -> Option<FrontmostWindow> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{CloseHandle, BOOL, HWND, LPARAM, RECT};
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowRect, GetWindowTextLengthW, GetWindowTextW,
+        GetWindowThreadProcessId, IsWindowVisible,
+    };
+
+    // EnumWindows walks top-level windows in z-order, front to back
+    unsafe extern "system" fn find_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let found = &mut *(lparam.0 as *mut Option<(HWND, u32)>);
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if IsWindowVisible(hwnd).as_bool()
+            && GetWindowTextLengthW(hwnd) > 0
+            && pid != std::process::id()
+        {
+            *found = Some((hwnd, pid));
+            return BOOL::from(false);
+        }
+        BOOL::from(true)
+    }
+
+    let mut found: Option<(HWND, u32)> = None;
+    unsafe {
+        let _ = EnumWindows(
+            Some(find_window),
+            LPARAM(&mut found as *mut Option<(HWND, u32)> as isize),
+        );
+        let (hwnd, pid) = found?;
+
+        let mut title = vec![0u16; GetWindowTextLengthW(hwnd) as usize + 1];
+        let title_len = GetWindowTextW(hwnd, &mut title) as usize;
+        let title = String::from_utf16_lossy(&title[..title_len]);
+
+        let mut rect = RECT::default();
+        let bounds = GetWindowRect(hwnd, &mut rect).ok().map(|_| WindowBounds {
+            x: rect.left,
+            y: rect.top,
+            width: (rect.right - rect.left).max(0) as u32,
+            height: (rect.bottom - rect.top).max(0) as u32,
+        });
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let queried = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        queried.ok()?;
+
+        let path = String::from_utf16_lossy(&buffer[..len as usize]);
+        let app = std::path::Path::new(&path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())?;
+        Some(FrontmostWindow {
+            app,
+            title: Some(title).filter(|t| !t.is_empty()),
+            id: Some(hwnd.0 as u64),
+            bounds,
+        })
+    }
+}
+
+/// Whether keyboard focus is in a password edit control
+#[cfg(target_os = "windows")]
+pub(crate) fn is_secure_input_active() -> bool {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetGUIThreadInfo, GetWindowThreadProcessId, SendMessageTimeoutW,
+        GUITHREADINFO, SMTO_ABORTIFHUNG,
+    };
+
+    // Not exposed without the Win32_UI_Controls feature
+    const EM_GETPASSWORDCHAR: u32 = 0x00D2;
+
+    unsafe {
+        let foreground = GetForegroundWindow();
+        let thread_id = GetWindowThreadProcessId(foreground, None);
+        let mut info = GUITHREADINFO {
+            cbSize: std::mem::size_of::<GUITHREADINFO>() as u32,
+            ..Default::default()
+        };
+        if GetGUIThreadInfo(thread_id, &mut info).is_err() || info.hwndFocus.is_invalid() {
+            return false;
+        }
+
+        // Edit controls with ES_PASSWORD report a non-zero password character
+        let mut password_char = 0usize;
+        let sent = SendMessageTimeoutW(
+            info.hwndFocus,
+            EM_GETPASSWORDCHAR,
+            WPARAM(0),
+            LPARAM(0),
+            SMTO_ABORTIFHUNG,
+            50,
+            Some(&mut password_char as *mut usize),
+        );
+        sent.0 != 0 && password_char != 0
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn is_secure_input_active() -> bool {
+    false
 }
@@ -3,25 +3,35 @@
 //! This module provides screen capture functionality using the Windows GDI API.
 //! Frames are captured and encoded to H.264 using FFmpeg.
 
+use crate::capture::display_watch::DisplayWatcher;
+use crate::capture::frame::{crop_frame, region_in_frame, ContentRect, Rotation};
+use crate::capture::frame_rate::{display_fps, FrameClock, FrameRateMeter, DEFAULT_MAX_FPS};
 use crate::capture::traits::DisplayInfo;
 use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::coordinator::RecordingEvent;
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::segment::live_movflags_args;
+use crate::recorder::state::{CaptureQuality, VideoEncodingConfig};
+use crate::utils::process::{track, TrackedChild};
 use async_trait::async_trait;
 use parking_lot::Mutex as ParkingMutex;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
+use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
 #[cfg(target_os = "windows")]
 use windows::{
     Win32::Foundation::{BOOL, LPARAM, RECT},
     Win32::Graphics::Gdi::{
         BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject,
-        EnumDisplayMonitors, GetDIBits, GetMonitorInfoW, SelectObject, BITMAPINFO,
-        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HDC, HMONITOR, MONITORINFOEXW, SRCCOPY,
+        EnumDisplayMonitors, EnumDisplaySettingsW, GetDIBits, GetMonitorInfoW, SelectObject,
+        BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DEVMODEW, DIB_RGB_COLORS, ENUM_CURRENT_SETTINGS,
+        HDC, HMONITOR, MONITORINFOEXW, SRCCOPY,
     },
-    Win32::UI::WindowsAndMessaging::GetDesktopWindow,
+    Win32::UI::WindowsAndMessaging::{GetCursorPos, GetDesktopWindow},
 };
 
 /// Get list of available displays on Windows
@@ -57,6 +67,27 @@ pub fn get_displays() -> Vec<DisplayInfo> {
                 .unwrap_or(monitor_info.szDevice.len());
             let name = String::from_utf16_lossy(&monitor_info.szDevice[..name_len]);
 
+            // rcMonitor is already rotated; the display mode says how.
+            // DMDO_90 and up are clockwise quarter turns.
+            let mut mode: DEVMODEW = unsafe { zeroed() };
+            mode.dmSize = std::mem::size_of::<DEVMODEW>() as u16;
+            let (rotation, refresh_rate) = if unsafe {
+                EnumDisplaySettingsW(
+                    windows::core::PCWSTR(monitor_info.szDevice.as_ptr()),
+                    ENUM_CURRENT_SETTINGS,
+                    &mut mode,
+                )
+            }
+            .as_bool()
+            {
+                let orientation = unsafe { mode.Anonymous1.Anonymous2.dmDisplayOrientation };
+                // 0 and 1 mean the hardware's default rate
+                let frequency = Some(mode.dmDisplayFrequency).filter(|&hz| hz > 1);
+                (Rotation::from_degrees(orientation.0 as f64 * 90.0), frequency)
+            } else {
+                (Rotation::None, None)
+            };
+
             displays.push(DisplayInfo {
                 id: displays.len() as u32,
                 name: if is_primary {
@@ -68,7 +99,8 @@ pub fn get_displays() -> Vec<DisplayInfo> {
                 height,
                 scale_factor: 1.0, // TODO: Get actual DPI scaling
                 is_primary,
-                refresh_rate: Some(60), // TODO: Get actual refresh rate
+                refresh_rate,
+                rotation,
             });
         }
 
@@ -93,7 +125,8 @@ pub fn get_displays() -> Vec<DisplayInfo> {
             height: 1080,
             scale_factor: 1.0,
             is_primary: true,
-            refresh_rate: Some(60),
+            refresh_rate: None,
+            rotation: Rotation::None,
         });
     }
 
@@ -110,12 +143,74 @@ pub fn get_displays() -> Vec<DisplayInfo> {
         scale_factor: 1.0,
         is_primary: true,
         refresh_rate: Some(60),
+        rotation: Rotation::None,
     }]
 }
 
+/// ID of the display the mouse cursor is currently on
+///
+/// IDs are monitor enumeration indices, matching `get_displays`.
+#[cfg(target_os = "windows")]
+pub fn display_under_cursor() -> Option<u32> {
+    use windows::Win32::Foundation::POINT;
+
+    let mut cursor = POINT::default();
+    unsafe { GetCursorPos(&mut cursor).ok()? };
+
+    monitor_rects()
+        .iter()
+        .position(|r| cursor.x >= r.left && cursor.x < r.right && cursor.y >= r.top && cursor.y < r.bottom)
+        .map(|index| index as u32)
+}
+
+/// Desktop rectangles of all monitors, in `get_displays` order
+#[cfg(target_os = "windows")]
+pub(crate) fn monitor_rects() -> Vec<RECT> {
+    unsafe extern "system" fn collect_rects(
+        _hmonitor: HMONITOR,
+        _hdc: HDC,
+        rect: *mut RECT,
+        lparam: LPARAM,
+    ) -> BOOL {
+        let rects = &mut *(lparam.0 as *mut Vec<RECT>);
+        rects.push(*rect);
+        BOOL::from(true)
+    }
+
+    let mut rects: Vec<RECT> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(collect_rects),
+            LPARAM(&mut rects as *mut Vec<RECT> as isize),
+        );
+    }
+    rects
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn display_under_cursor() -> Option<u32> {
+    None
+}
+
+/// Size of a display, or None once it's no longer connected
+pub(crate) fn display_size(display_id: u32) -> Option<(u32, u32)> {
+    get_displays()
+        .get(display_id as usize)
+        .map(|d| (d.width, d.height))
+}
+
 /// Capture a single frame from the screen using BitBlt
 #[cfg(target_os = "windows")]
-fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+pub(crate) fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+    capture_frame(display_id, false)
+}
+
+/// Capture a frame, drawing the cursor into it if `show_cursor` is set
+/// (BitBlt leaves it out)
+#[cfg(target_os = "windows")]
+fn capture_frame(display_id: u32, show_cursor: bool) -> Option<(Vec<u8>, u32, u32)> {
     use std::mem::zeroed;
     use windows::Win32::Graphics::Gdi::GetDC;
 
@@ -168,6 +263,10 @@ fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
             return None;
         }
 
+        if show_cursor {
+            draw_cursor(hdc_mem);
+        }
+
         // Prepare bitmap info for GetDIBits
         let mut bmi: BITMAPINFO = zeroed();
         bmi.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
@@ -205,14 +304,63 @@ fn capture_display_frame(display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
     }
 }
 
+/// Draw the cursor as currently shown onto a frame captured from the
+/// desktop's origin
+#[cfg(target_os = "windows")]
+unsafe fn draw_cursor(hdc: HDC) {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DrawIconEx, GetCursorInfo, GetIconInfo, CURSORINFO, CURSOR_SHOWING, DI_NORMAL, HICON,
+        ICONINFO,
+    };
+
+    let mut info = CURSORINFO {
+        cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+        ..Default::default()
+    };
+    if GetCursorInfo(&mut info).is_err()
+        || (info.flags.0 & CURSOR_SHOWING.0) == 0
+        || info.hCursor.is_invalid()
+    {
+        return;
+    }
+
+    // The hotspot is what sits at the cursor position
+    let icon = HICON(info.hCursor.0);
+    let mut icon_info = ICONINFO::default();
+    if GetIconInfo(icon, &mut icon_info).is_err() {
+        return;
+    }
+    if !icon_info.hbmColor.is_invalid() {
+        let _ = DeleteObject(icon_info.hbmColor);
+    }
+    let _ = DeleteObject(icon_info.hbmMask);
+
+    let _ = DrawIconEx(
+        hdc,
+        info.ptScreenPos.x - icon_info.xHotspot as i32,
+        info.ptScreenPos.y - icon_info.yHotspot as i32,
+        icon,
+        0,
+        0,
+        0,
+        None,
+        DI_NORMAL,
+    );
+}
+
 #[cfg(not(target_os = "windows"))]
-fn capture_display_frame(_display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+pub(crate) fn capture_display_frame(_display_id: u32) -> Option<(Vec<u8>, u32, u32)> {
+    None
+}
+
+#[cfg(not(target_os = "windows"))]
+fn capture_frame(_display_id: u32, _show_cursor: bool) -> Option<(Vec<u8>, u32, u32)> {
     None
 }
 
 /// FFmpeg encoder for MP4 output
 struct FFmpegEncoder {
-    process: ParkingMutex<Option<Child>>,
+    process: ParkingMutex<Option<TrackedChild>>,
     frame_count: AtomicU64,
     running: AtomicBool,
     output_dir: PathBuf,
@@ -224,13 +372,15 @@ impl FFmpegEncoder {
         width: u32,
         height: u32,
         fps: u32,
+        encoding: &VideoEncodingConfig,
+        quality: CaptureQuality,
         output_dir: &Path,
         session_index: usize,
     ) -> Result<Self, std::io::Error> {
         std::fs::create_dir_all(output_dir)?;
 
         let output_file = output_dir
-            .join(format!("recording-{session_index}.mp4"))
+            .join(FileKind::ScreenVideo.conventional_name(session_index))
             .to_string_lossy()
             .to_string();
 
@@ -254,14 +404,11 @@ impl FFmpegEncoder {
                 "veryfast",
                 "-pix_fmt",
                 "yuv420p",
-                "-crf",
-                "18",
-                "-g",
-                &(fps * 2).to_string(),
-                "-movflags",
-                "+faststart",
-                &output_file,
             ])
+            .args(encoding.screen_filter_args(fps, quality, None))
+            .args(encoding.ffmpeg_args(fps, quality))
+            .args(live_movflags_args())
+            .arg(&output_file)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
@@ -276,7 +423,7 @@ impl FFmpegEncoder {
         );
 
         Ok(Self {
-            process: ParkingMutex::new(Some(process)),
+            process: ParkingMutex::new(Some(track(process, "screen encoder"))),
             frame_count: AtomicU64::new(0),
             running: AtomicBool::new(true),
             output_dir: output_dir.to_path_buf(),
@@ -319,7 +466,7 @@ impl FFmpegEncoder {
 
         let output_file = self
             .output_dir
-            .join(format!("recording-{}.mp4", self.session_index))
+            .join(FileKind::ScreenVideo.conventional_name(self.session_index))
             .to_string_lossy()
             .to_string();
 
@@ -351,6 +498,18 @@ pub struct DisplayCaptureChannel {
     width: u32,
     height: u32,
     fps: u32,
+    video_encoding: VideoEncodingConfig,
+    quality: CaptureQuality,
+    dropped_frames: Arc<AtomicU64>,
+    frame_rate: FrameRateMeter,
+    follow_cursor_on_resume: bool,
+    show_cursor: bool,
+    region: Option<ContentRect>,
+    /// `region` on the captured frames, once capture started
+    crop: Option<ContentRect>,
+    max_fps: u32,
+    event_tx: Option<broadcast::Sender<RecordingEvent>>,
+    watcher: Option<DisplayWatcher>,
 }
 
 impl DisplayCaptureChannel {
@@ -367,8 +526,58 @@ impl DisplayCaptureChannel {
             width: 1920,
             height: 1080,
             fps: 30,
+            video_encoding: VideoEncodingConfig::default(),
+            quality: CaptureQuality::default(),
+            dropped_frames: Arc::new(AtomicU64::new(0)),
+            frame_rate: FrameRateMeter::new(),
+            follow_cursor_on_resume: false,
+            show_cursor: false,
+            region: None,
+            crop: None,
+            max_fps: DEFAULT_MAX_FPS,
+            event_tx: None,
+            watcher: None,
         }
     }
+
+    /// Switch to the display under the cursor whenever recording resumes
+    pub fn follow_cursor_on_resume(mut self, follow: bool) -> Self {
+        self.follow_cursor_on_resume = follow;
+        self
+    }
+
+    /// Record only this part of the display, in the coordinates reported by
+    /// `get_displays`
+    pub fn region(mut self, region: Option<ContentRect>) -> Self {
+        self.region = region;
+        self
+    }
+
+    /// Keyframe and B-frame settings for the encoder
+    pub fn video_encoding(mut self, encoding: VideoEncodingConfig) -> Self {
+        self.video_encoding = encoding;
+        self
+    }
+
+    /// Frame rate, scaling and encoder quality preset
+    pub fn quality(mut self, quality: CaptureQuality) -> Self {
+        self.fps = quality.fps();
+        self.quality = quality;
+        self
+    }
+
+    /// Highest frame rate to follow the display's refresh rate up to
+    pub fn max_fps(mut self, max_fps: u32) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
+
+    /// Record the system cursor into the frames instead of leaving it to
+    /// be drawn at export time
+    pub fn show_cursor(mut self, show: bool) -> Self {
+        self.show_cursor = show;
+        self
+    }
 }
 
 #[async_trait]
@@ -384,26 +593,30 @@ impl RecordingChannel for DisplayCaptureChannel {
     async fn initialize(&mut self, output_dir: &Path, session_index: usize) -> RecordingResult<()> {
         // Check if FFmpeg is available
         if Command::new("ffmpeg").arg("-version").output().is_err() {
-            return Err(RecordingError::ConfigurationError(
+            return Err(RecordingError::FfmpegMissing(
                 "FFmpeg not found. Please install FFmpeg and add it to PATH.".to_string(),
             ));
         }
 
         // Get display info
         let displays = get_displays();
-        if let Some(display) = displays.get(self.display_id as usize) {
+        let display = displays.get(self.display_id as usize);
+        if let Some(display) = display {
             self.width = display.width;
             self.height = display.height;
         }
+        let refresh_rate = display.and_then(|d| d.refresh_rate);
+        self.fps = display_fps(self.quality, refresh_rate, self.max_fps);
 
         self.output_dir = Some(output_dir.to_path_buf());
         self.session_index = session_index;
 
         tracing::info!(
-            "Windows display capture initialized for display {} ({}x{})",
+            "Windows display capture initialized for display {} ({}x{} @ {}fps)",
             self.display_id,
             self.width,
-            self.height
+            self.height,
+            self.fps
         );
         Ok(())
     }
@@ -419,28 +632,65 @@ impl RecordingChannel for DisplayCaptureChannel {
             .ok_or_else(|| RecordingError::ConfigurationError("Output directory not set".to_string()))?;
 
         // Capture first frame to determine actual dimensions
-        let (first_frame, actual_width, actual_height) = capture_display_frame(self.display_id)
+        let first_frame = capture_frame(self.display_id, self.show_cursor);
+        let (first_frame, actual_width, actual_height) = first_frame
             .ok_or_else(|| RecordingError::CaptureError("Failed to capture initial frame".to_string()))?;
 
-        self.width = actual_width;
-        self.height = actual_height;
+        let crop = match self.region {
+            Some(region) => {
+                let display = get_displays().into_iter().nth(self.display_id as usize);
+                let (display_width, display_height) = display
+                    .map(|d| (d.width, d.height))
+                    .unwrap_or((actual_width, actual_height));
+                Some(
+                    region_in_frame(region, display_width, display_height, actual_width, actual_height)
+                        .ok_or_else(|| {
+                            RecordingError::ConfigurationError("Capture region is outside the display".to_string())
+                        })?,
+                )
+            }
+            None => None,
+        };
+        self.crop = crop;
+        self.width = crop.map_or(actual_width, |c| c.width);
+        self.height = crop.map_or(actual_height, |c| c.height);
 
         tracing::info!(
-            "Actual capture dimensions: {}x{} (from first frame)",
+            "Actual capture dimensions: {}x{} (from first frame), recording {}x{}",
             actual_width,
-            actual_height
+            actual_height,
+            self.width,
+            self.height
         );
 
         // Create FFmpeg encoder
         let encoder = Arc::new(
-            FFmpegEncoder::new(self.width, self.height, self.fps, &output_dir, self.session_index)
-                .map_err(|e| RecordingError::CaptureError(format!("Failed to start FFmpeg: {}", e)))?,
+            FFmpegEncoder::new(
+                self.width,
+                self.height,
+                self.fps,
+                &self.video_encoding,
+                self.quality,
+                &output_dir,
+                self.session_index,
+            )
+            .map_err(|e| RecordingError::CaptureError(format!("Failed to start FFmpeg: {}", e)))?,
         );
 
         // Write first frame
-        let expected_size = (self.width * self.height * 4) as usize;
-        if first_frame.len() >= expected_size {
-            encoder.write_frame(&first_frame[..expected_size]);
+        self.frame_rate.start();
+        match crop {
+            Some(rect) => {
+                if let Some(cropped) = crop_frame(&first_frame, actual_width as usize * 4, rect) {
+                    encoder.write_frame(&cropped);
+                }
+            }
+            None => {
+                let expected_size = (self.width * self.height * 4) as usize;
+                if first_frame.len() >= expected_size {
+                    encoder.write_frame(&first_frame[..expected_size]);
+                }
+            }
         }
 
         self.encoder = Some(encoder.clone());
@@ -449,21 +699,43 @@ impl RecordingChannel for DisplayCaptureChannel {
         // Start capture loop
         let is_recording = self.is_recording.clone();
         let display_id = self.display_id;
+        let show_cursor = self.show_cursor;
         let fps = self.fps;
         let width = self.width;
         let height = self.height;
+        let dropped_frames = self.dropped_frames.clone();
 
         let handle = tokio::spawn(async move {
-            let frame_interval = std::time::Duration::from_millis(1000 / fps as u64);
+            let frame_interval = std::time::Duration::from_secs_f64(1.0 / fps as f64);
             let expected_size = (width * height * 4) as usize;
+            let clock = FrameClock::new(fps);
+            let mut last = Vec::new();
 
             while is_recording.load(Ordering::SeqCst) {
                 let start = std::time::Instant::now();
 
-                if let Some((data, _w, _h)) = capture_display_frame(display_id) {
-                    if data.len() >= expected_size {
-                        encoder.write_frame(&data[..expected_size]);
+                let frame = match (capture_frame(display_id, show_cursor), crop) {
+                    (Some((data, w, _h)), Some(rect)) => crop_frame(&data, w as usize * 4, rect),
+                    (Some((mut data, _w, _h)), None) if data.len() >= expected_size => {
+                        data.truncate(expected_size);
+                        Some(data)
+                    }
+                    _ => None,
+                };
+                let written = frame.is_some_and(|frame| {
+                    // Repeat the last frame over any slots capture fell behind
+                    // on, so the video keeps time with the audio
+                    for _ in 0..clock.repeats_before_next(encoder.frame_count()) {
+                        if last.is_empty() || !encoder.write_frame(&last) {
+                            break;
+                        }
                     }
+                    let written = encoder.write_frame(&frame);
+                    last = frame;
+                    written
+                });
+                if !written {
+                    dropped_frames.fetch_add(1, Ordering::Relaxed);
                 }
 
                 let count = encoder.frame_count();
@@ -480,11 +752,18 @@ impl RecordingChannel for DisplayCaptureChannel {
                 let elapsed = start.elapsed();
                 if elapsed < frame_interval {
                     tokio::time::sleep(frame_interval - elapsed).await;
+                } else {
+                    let missed = (elapsed.as_nanos() / frame_interval.as_nanos()) as u64;
+                    dropped_frames.fetch_add(missed.saturating_sub(1), Ordering::Relaxed);
                 }
             }
         });
 
         self.capture_handle = Some(handle);
+        self.watcher = self
+            .event_tx
+            .clone()
+            .and_then(|tx| DisplayWatcher::start(&self.id, self.display_id, display_size, tx));
 
         tracing::info!(
             "Windows display capture started for display {} ({}x{} @ {}fps)",
@@ -502,12 +781,16 @@ impl RecordingChannel for DisplayCaptureChannel {
         }
 
         self.is_recording.store(false, Ordering::SeqCst);
+        if let Some(watcher) = self.watcher.take() {
+            watcher.stop();
+        }
 
         if let Some(handle) = self.capture_handle.take() {
             let _ = handle.await;
         }
 
         if let Some(ref encoder) = self.encoder {
+            self.frame_rate.stop(encoder.frame_count());
             let files = encoder
                 .finish()
                 .map_err(|e| RecordingError::CaptureError(format!("Failed to finish encoding: {}", e)))?;
@@ -525,6 +808,20 @@ impl RecordingChannel for DisplayCaptureChannel {
 
     async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
         self.session_index = session_index;
+        if self.follow_cursor_on_resume {
+            if let Some(display_id) = display_under_cursor().filter(|&id| id != self.display_id) {
+                tracing::info!(
+                    "Cursor moved to display {}, switching capture from display {}",
+                    display_id,
+                    self.display_id
+                );
+                self.display_id = display_id;
+                if let Some(display) = get_displays().get(display_id as usize) {
+                    self.width = display.width;
+                    self.height = display.height;
+                }
+            }
+        }
         self.start().await
     }
 
@@ -535,4 +832,27 @@ impl RecordingChannel for DisplayCaptureChannel {
     fn output_files(&self) -> Vec<String> {
         self.output_files.lock().clone()
     }
+
+    fn set_event_sender(&mut self, event_tx: broadcast::Sender<RecordingEvent>) {
+        self.event_tx = Some(event_tx);
+    }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: Some(self.display_id.to_string()),
+            width: Some(self.width),
+            height: Some(self.height),
+            fps: Some(self.fps),
+            region: self.region,
+            crop: self.crop,
+            downscale: Some(self.quality.downscale()).filter(|&n| n > 1),
+            achieved_fps: self.frame_rate.achieved_fps(),
+            shows_cursor: self.show_cursor,
+            ..Default::default()
+        }
+    }
+
+    fn dropped_frames(&self) -> Option<u64> {
+        Some(self.dropped_frames.load(Ordering::Relaxed))
+    }
 }
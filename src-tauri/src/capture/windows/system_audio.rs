@@ -1,10 +1,20 @@
 //! Windows System Audio Capture using WASAPI Loopback
 //!
 //! On Windows, we can capture system audio using WASAPI loopback mode,
-//! which captures the audio being played to an output device.
+//! which captures the audio being played to an output device: the default
+//! one, or the one chosen in `RecordingConfig::system_audio_device_id` (e.g.
+//! the headset rather than the speakers).
+//!
+//! With an `AudioAppFilter`, the process loopback virtual device is used
+//! instead: it records one process tree's audio, or everything except one
+//! process tree's. Apps are found through the default output device's audio
+//! sessions (`IAudioSessionManager2`).
 
-use crate::capture::audio::AudioEncoder;
+use crate::capture::audio::{convert_samples, AudioEncoder, AudioFormat};
+use crate::capture::traits::{AudioCaptureApp, AudioDeviceInfo, SystemAudioSupport};
 use crate::recorder::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use crate::recorder::manifest::{ChannelSettings, FileKind};
+use crate::recorder::state::{AudioAppFilter, AudioAppFilterMode, AudioEncodingConfig};
 use async_trait::async_trait;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, SampleFormat, StreamConfig};
@@ -12,6 +22,10 @@ use parking_lot::Mutex as ParkingMutex;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use windows::Win32::Media::Audio::{
+    PROCESS_LOOPBACK_MODE, PROCESS_LOOPBACK_MODE_EXCLUDE_TARGET_PROCESS_TREE,
+    PROCESS_LOOPBACK_MODE_INCLUDE_TARGET_PROCESS_TREE,
+};
 
 /// Get the default output device for loopback capture
 fn get_default_output_device() -> Option<Device> {
@@ -19,6 +33,394 @@ fn get_default_output_device() -> Option<Device> {
     host.default_output_device()
 }
 
+/// Output device to record from, by name (the default device if None)
+fn get_output_device(device_id: Option<&str>) -> Option<Device> {
+    let Some(id) = device_id else {
+        return get_default_output_device();
+    };
+    cpal::default_host()
+        .output_devices()
+        .ok()?
+        .find(|device| device.name().is_ok_and(|name| name == id))
+}
+
+/// Output devices whose audio can be recorded, for
+/// `RecordingConfig::system_audio_device_id`
+pub fn get_output_devices() -> Vec<AudioDeviceInfo> {
+    let host = cpal::default_host();
+    let default_name = host.default_output_device().and_then(|d| d.name().ok());
+    let Ok(devices) = host.output_devices() else {
+        return Vec::new();
+    };
+    devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| AudioDeviceInfo {
+            id: name.clone(),
+            is_default: default_name.as_ref() == Some(&name),
+            name,
+            is_input: false,
+            sample_rates: Vec::new(),
+        })
+        .collect()
+}
+
+/// Whether system audio can be recorded, and why not
+pub fn system_audio_support() -> SystemAudioSupport {
+    if get_default_output_device().is_some() {
+        SystemAudioSupport::available()
+    } else {
+        SystemAudioSupport::unavailable("No audio output device to record from")
+    }
+}
+
+/// Apps currently playing through the default output device, sorted by name.
+/// Each app is reported once, with the top process of its process tree.
+pub fn get_audio_capture_apps() -> Vec<AudioCaptureApp> {
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::{
+        eConsole, eRender, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator,
+        MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    let processes = process_table();
+    let own_pid = std::process::id();
+    let mut apps: Vec<AudioCaptureApp> = Vec::new();
+
+    unsafe {
+        let initialized = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+        let sessions = (|| -> windows::core::Result<Vec<u32>> {
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+            let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+            let sessions = manager.GetSessionEnumerator()?;
+
+            let mut pids = Vec::new();
+            for i in 0..sessions.GetCount()? {
+                let Ok(session) = sessions
+                    .GetSession(i)
+                    .and_then(|s| s.cast::<IAudioSessionControl2>())
+                else {
+                    continue;
+                };
+                // S_OK means it is the system sounds session
+                if session.IsSystemSoundsSession().0 == 0 {
+                    continue;
+                }
+                if let Ok(pid) = session.GetProcessId() {
+                    pids.push(pid);
+                }
+            }
+            Ok(pids)
+        })();
+        if initialized {
+            CoUninitialize();
+        }
+
+        let pids = match sessions {
+            Ok(pids) => pids,
+            Err(e) => {
+                tracing::warn!("Failed to list audio sessions: {}", e);
+                return Vec::new();
+            }
+        };
+        for pid in pids {
+            if pid == 0 || pid == own_pid {
+                continue;
+            }
+            let Some((_, exe)) = processes.get(&pid) else {
+                continue;
+            };
+            let id = app_id(exe);
+            if apps.iter().any(|app| app.id == id) {
+                continue;
+            }
+            apps.push(AudioCaptureApp {
+                name: id.clone(),
+                pid: root_process(&processes, pid),
+                id,
+            });
+        }
+    }
+
+    apps.sort_by_key(|app| app.name.to_lowercase());
+    apps
+}
+
+/// App ID of an executable: its file name without the extension
+fn app_id(exe: &str) -> String {
+    std::path::Path::new(exe)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| exe.to_string())
+}
+
+/// Parent process and executable name of every running process
+fn process_table() -> std::collections::HashMap<u32, (u32, String)> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+
+    let mut table = std::collections::HashMap::new();
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) else {
+            return table;
+        };
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut found = Process32FirstW(snapshot, &mut entry).is_ok();
+        while found {
+            let len = entry
+                .szExeFile
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(entry.szExeFile.len());
+            table.insert(
+                entry.th32ProcessID,
+                (
+                    entry.th32ParentProcessID,
+                    String::from_utf16_lossy(&entry.szExeFile[..len]),
+                ),
+            );
+            found = Process32NextW(snapshot, &mut entry).is_ok();
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    table
+}
+
+/// Walk up from `pid` while the parent is the same executable, so a
+/// multi-process app (browsers) is captured as a whole
+fn root_process(processes: &std::collections::HashMap<u32, (u32, String)>, pid: u32) -> u32 {
+    let mut root = pid;
+    // Bounded in case of a parent cycle from reused process IDs
+    for _ in 0..32 {
+        let Some((parent, exe)) = processes.get(&root) else {
+            break;
+        };
+        match processes.get(parent) {
+            Some((_, parent_exe)) if parent_exe.eq_ignore_ascii_case(exe) && *parent != root => {
+                root = *parent;
+            }
+            _ => break,
+        }
+    }
+    root
+}
+
+/// Process tree and mode for a process loopback stream, or None to capture
+/// all system audio
+fn loopback_target(
+    filter: &AudioAppFilter,
+) -> RecordingResult<Option<(u32, PROCESS_LOOPBACK_MODE)>> {
+    // The process loopback device takes a single process tree
+    if filter.app_ids.len() > 1 {
+        return Err(RecordingError::ConfigurationError(
+            "Windows can only include or exclude the audio of one app".to_string(),
+        ));
+    }
+    let app = get_audio_capture_apps()
+        .into_iter()
+        .find(|app| filter.matches(&app.id));
+
+    match (filter.mode, app) {
+        (AudioAppFilterMode::Include, Some(app)) => Ok(Some((
+            app.pid,
+            PROCESS_LOOPBACK_MODE_INCLUDE_TARGET_PROCESS_TREE,
+        ))),
+        (AudioAppFilterMode::Exclude, Some(app)) => Ok(Some((
+            app.pid,
+            PROCESS_LOOPBACK_MODE_EXCLUDE_TARGET_PROCESS_TREE,
+        ))),
+        (AudioAppFilterMode::Include, None) => Err(RecordingError::DeviceNotFound(format!(
+            "{} isn't playing audio",
+            filter
+                .app_ids
+                .first()
+                .map(String::as_str)
+                .unwrap_or("The app")
+        ))),
+        // Nothing to leave out
+        (AudioAppFilterMode::Exclude, None) => Ok(None),
+    }
+}
+
+/// Completion handler for `ActivateAudioInterfaceAsync`
+#[windows::core::implement(windows::Win32::Media::Audio::IActivateAudioInterfaceCompletionHandler)]
+struct ActivationHandler {
+    done: parking_lot::Mutex<Option<std::sync::mpsc::Sender<()>>>,
+}
+
+impl windows::Win32::Media::Audio::IActivateAudioInterfaceCompletionHandler_Impl
+    for ActivationHandler_Impl
+{
+    fn ActivateCompleted(
+        &self,
+        _operation: Option<&windows::Win32::Media::Audio::IActivateAudioInterfaceAsyncOperation>,
+    ) -> windows::core::Result<()> {
+        if let Some(done) = self.done.lock().take() {
+            let _ = done.send(());
+        }
+        Ok(())
+    }
+}
+
+/// Capture one process tree's audio (or everything but it) through the
+/// process loopback device until `is_recording` is cleared. COM must be
+/// initialized on the calling thread.
+fn run_process_loopback(
+    pid: u32,
+    mode: PROCESS_LOOPBACK_MODE,
+    native: AudioFormat,
+    target: AudioFormat,
+    encoder: &AudioEncoder,
+    is_recording: &AtomicBool,
+) -> windows::core::Result<()> {
+    use windows::core::{imp, IUnknown, Interface, HRESULT, PROPVARIANT};
+    use windows::Win32::Foundation::{CloseHandle, WAIT_OBJECT_0};
+    use windows::Win32::Media::Audio::{
+        ActivateAudioInterfaceAsync, IActivateAudioInterfaceCompletionHandler, IAudioCaptureClient,
+        IAudioClient, AUDCLNT_BUFFERFLAGS_SILENT, AUDCLNT_SHAREMODE_SHARED,
+        AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM, AUDCLNT_STREAMFLAGS_EVENTCALLBACK,
+        AUDCLNT_STREAMFLAGS_LOOPBACK, AUDIOCLIENT_ACTIVATION_PARAMS,
+        AUDIOCLIENT_ACTIVATION_PARAMS_0, AUDIOCLIENT_ACTIVATION_TYPE_PROCESS_LOOPBACK,
+        AUDIOCLIENT_PROCESS_LOOPBACK_PARAMS, VIRTUAL_AUDIO_DEVICE_PROCESS_LOOPBACK, WAVEFORMATEX,
+    };
+    use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+
+    // Not exposed by windows-core's raw PROPVARIANT bindings
+    const VT_BLOB: u16 = 65;
+    const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+    // 100ms, in 100ns units
+    const BUFFER_DURATION: i64 = 1_000_000;
+
+    unsafe {
+        let params = AUDIOCLIENT_ACTIVATION_PARAMS {
+            ActivationType: AUDIOCLIENT_ACTIVATION_TYPE_PROCESS_LOOPBACK,
+            Anonymous: AUDIOCLIENT_ACTIVATION_PARAMS_0 {
+                ProcessLoopbackParams: AUDIOCLIENT_PROCESS_LOOPBACK_PARAMS {
+                    TargetProcessId: pid,
+                    ProcessLoopbackMode: mode,
+                },
+            },
+        };
+        // The blob points at `params`, so the variant must never be cleared
+        let activation = std::mem::ManuallyDrop::new(PROPVARIANT::from_raw(imp::PROPVARIANT {
+            Anonymous: imp::PROPVARIANT_0 {
+                Anonymous: imp::PROPVARIANT_0_0 {
+                    vt: VT_BLOB,
+                    wReserved1: 0,
+                    wReserved2: 0,
+                    wReserved3: 0,
+                    Anonymous: imp::PROPVARIANT_0_0_0 {
+                        blob: imp::BLOB {
+                            cbSize: std::mem::size_of::<AUDIOCLIENT_ACTIVATION_PARAMS>() as u32,
+                            pBlobData: &params as *const _ as *mut u8,
+                        },
+                    },
+                },
+            },
+        }));
+
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let handler: IActivateAudioInterfaceCompletionHandler = ActivationHandler {
+            done: parking_lot::Mutex::new(Some(done_tx)),
+        }
+        .into();
+        let operation = ActivateAudioInterfaceAsync(
+            VIRTUAL_AUDIO_DEVICE_PROCESS_LOOPBACK,
+            &IAudioClient::IID,
+            Some(&*activation as *const PROPVARIANT),
+            &handler,
+        )?;
+        if done_rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .is_err()
+        {
+            return Err(windows::core::Error::new(
+                windows::Win32::Foundation::E_FAIL,
+                "Timed out activating the process loopback device",
+            ));
+        }
+        let mut result = HRESULT(0);
+        let mut activated: Option<IUnknown> = None;
+        operation.GetActivateResult(&mut result, &mut activated)?;
+        result.ok()?;
+        let client: IAudioClient = activated
+            .ok_or_else(|| windows::core::Error::from(windows::Win32::Foundation::E_FAIL))?
+            .cast()?;
+
+        // The process loopback device has no mix format; ask for the output
+        // device's rate and channels as float and let WASAPI convert
+        let block_align = native.channels * 4;
+        let format = WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_IEEE_FLOAT,
+            nChannels: native.channels,
+            nSamplesPerSec: native.sample_rate,
+            nAvgBytesPerSec: native.sample_rate * block_align as u32,
+            nBlockAlign: block_align,
+            wBitsPerSample: 32,
+            cbSize: 0,
+        };
+        client.Initialize(
+            AUDCLNT_SHAREMODE_SHARED,
+            AUDCLNT_STREAMFLAGS_LOOPBACK
+                | AUDCLNT_STREAMFLAGS_EVENTCALLBACK
+                | AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM,
+            BUFFER_DURATION,
+            0,
+            &format,
+            None,
+        )?;
+        let event = CreateEventW(None, false, false, windows::core::PCWSTR::null())?;
+        client.SetEventHandle(event)?;
+        let capture: IAudioCaptureClient = client.GetService()?;
+        client.Start()?;
+
+        tracing::info!("Process loopback stream started for process {}", pid);
+
+        let result = (|| -> windows::core::Result<()> {
+            while is_recording.load(Ordering::Relaxed) {
+                if WaitForSingleObject(event, 100) != WAIT_OBJECT_0 {
+                    continue;
+                }
+                while capture.GetNextPacketSize()? > 0 {
+                    let mut data = std::ptr::null_mut();
+                    let mut frames = 0u32;
+                    let mut flags = 0u32;
+                    capture.GetBuffer(&mut data, &mut frames, &mut flags, None, None)?;
+
+                    let sample_count = frames as usize * native.channels as usize;
+                    let samples: Vec<f32> = if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 {
+                        vec![0.0; sample_count]
+                    } else {
+                        std::slice::from_raw_parts(data as *const f32, sample_count).to_vec()
+                    };
+                    capture.ReleaseBuffer(frames)?;
+
+                    let bytes: Vec<u8> = convert_samples(&samples, native, target)
+                        .iter()
+                        .flat_map(|&sample| sample.to_le_bytes())
+                        .collect();
+                    encoder.write_samples(&bytes);
+                }
+            }
+            Ok(())
+        })();
+
+        let _ = client.Stop();
+        let _ = CloseHandle(event);
+        result
+    }
+}
+
 /// System audio capture channel for Windows
 ///
 /// Uses WASAPI loopback to capture system audio output.
@@ -30,19 +432,37 @@ pub struct SystemAudioCaptureChannel {
     output_files: Arc<ParkingMutex<Vec<String>>>,
     encoder: Arc<ParkingMutex<Option<Arc<AudioEncoder>>>>,
     stream_handle: Arc<ParkingMutex<Option<std::thread::JoinHandle<()>>>>,
-    sample_rate: u32,
-    channels: u16,
+    /// Output device to record (None = the default one)
+    device_id: Option<String>,
+    /// Name of the device resolved at initialization
+    device_name: Option<String>,
+    /// Format of the loopback stream
+    native: AudioFormat,
+    /// Format the track is encoded at
+    target: AudioFormat,
+    encoding: AudioEncodingConfig,
     available: bool,
+    /// Apps to record or leave out, instead of all system audio
+    app_filter: Option<AudioAppFilter>,
 }
 
 impl SystemAudioCaptureChannel {
     /// Create a new system audio capture channel
-    pub fn new() -> Self {
-        // Check if we can get the default output device
-        let available = get_default_output_device().is_some();
+    /// If device_id is None, records the default output device
+    pub fn new(
+        device_id: Option<String>,
+        encoding: AudioEncodingConfig,
+        app_filter: Option<AudioAppFilter>,
+    ) -> Self {
+        // Check if we can get the output device
+        let available = get_output_device(device_id.as_deref()).is_some();
         
         if !available {
-            tracing::warn!("No default output device found for system audio capture");
+            tracing::warn!("Output device {:?} not found for system audio capture", device_id);
+        }
+        if device_id.is_some() && app_filter.is_some() {
+            // Process loopback records the apps on every output device
+            tracing::warn!("App audio is recorded from every output device, not the chosen one");
         }
 
         Self {
@@ -53,9 +473,13 @@ impl SystemAudioCaptureChannel {
             output_files: Arc::new(ParkingMutex::new(Vec::new())),
             encoder: Arc::new(ParkingMutex::new(None)),
             stream_handle: Arc::new(ParkingMutex::new(None)),
-            sample_rate: 48000,
-            channels: 2,
+            device_id,
+            device_name: None,
+            native: DEFAULT_FORMAT,
+            target: DEFAULT_FORMAT.with_encoding(&encoding),
+            encoding,
             available,
+            app_filter,
         }
     }
 
@@ -67,10 +491,16 @@ impl SystemAudioCaptureChannel {
 
 impl Default for SystemAudioCaptureChannel {
     fn default() -> Self {
-        Self::new()
+        Self::new(None, AudioEncodingConfig::default(), None)
     }
 }
 
+/// Format assumed until the output device has been queried
+const DEFAULT_FORMAT: AudioFormat = AudioFormat {
+    sample_rate: 48000,
+    channels: 2,
+};
+
 #[async_trait]
 impl RecordingChannel for SystemAudioCaptureChannel {
     fn id(&self) -> &str {
@@ -87,27 +517,37 @@ impl RecordingChannel for SystemAudioCaptureChannel {
             return Ok(());
         }
 
-        let device = get_default_output_device().ok_or_else(|| {
-            RecordingError::DeviceNotFound("No default output device".to_string())
+        let device = get_output_device(self.device_id.as_deref()).ok_or_else(|| {
+            RecordingError::DeviceNotFound(match &self.device_id {
+                Some(id) => format!("Output device '{}' not found", id),
+                None => "No default output device".to_string(),
+            })
         })?;
 
         let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        self.device_name = Some(device_name.clone());
 
         // Get the output config (we'll use this for loopback)
         let config = device.default_output_config().map_err(|e| {
             RecordingError::ConfigurationError(format!("Failed to get audio config: {}", e))
         })?;
 
-        self.sample_rate = config.sample_rate().0;
-        self.channels = config.channels();
+        self.native = AudioFormat {
+            sample_rate: config.sample_rate().0,
+            channels: config.channels(),
+        };
+        self.target = self.native.with_encoding(&self.encoding);
         self.output_dir = Some(output_dir.to_path_buf());
         self.session_index = session_index;
 
         tracing::info!(
-            "System audio channel initialized: {} ({}Hz, {}ch)",
+            "System audio channel initialized: {} ({}Hz {}ch native, encoding {}Hz {}ch {}kbps)",
             device_name,
-            self.sample_rate,
-            self.channels
+            self.native.sample_rate,
+            self.native.channels,
+            self.target.sample_rate,
+            self.target.channels,
+            self.encoding.bitrate_kbps()
         );
         Ok(())
     }
@@ -126,14 +566,20 @@ impl RecordingChannel for SystemAudioCaptureChannel {
             RecordingError::ConfigurationError("Output directory not set".to_string())
         })?;
 
+        let loopback_target = match &self.app_filter {
+            Some(filter) => loopback_target(filter)?,
+            None => None,
+        };
+
         // Create encoder
         let encoder = Arc::new(
             AudioEncoder::new(
-                self.sample_rate,
-                self.channels,
+                self.target.sample_rate,
+                self.target.channels,
+                self.encoding.bitrate_kbps(),
                 &output_dir,
                 self.session_index,
-                "system",
+                FileKind::SystemAudio,
             )
             .map_err(|e| {
                 RecordingError::CaptureError(format!("Failed to start audio encoder: {}", e))
@@ -144,19 +590,36 @@ impl RecordingChannel for SystemAudioCaptureChannel {
         self.is_recording.store(true, Ordering::SeqCst);
 
         let is_recording = self.is_recording.clone();
-        let sample_rate = self.sample_rate;
-        let channels = self.channels;
+        let device_id = self.device_id.clone();
+        let native = self.native;
+        let target = self.target;
 
         // Spawn a thread to handle the audio capture
         // Note: On Windows, we need to use WASAPI loopback which requires
         // building an input stream on the output device
         let handle = std::thread::spawn(move || {
-            let host = cpal::default_host();
-            
-            let device = match host.default_output_device() {
+            if let Some((pid, mode)) = loopback_target {
+                use windows::Win32::System::Com::{
+                    CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED,
+                };
+
+                let initialized = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED).is_ok() };
+                if let Err(e) =
+                    run_process_loopback(pid, mode, native, target, &encoder, &is_recording)
+                {
+                    tracing::error!("Process loopback capture failed: {}", e);
+                }
+                if initialized {
+                    unsafe { CoUninitialize() };
+                }
+                tracing::info!("System audio stream stopped");
+                return;
+            }
+
+            let device = match get_output_device(device_id.as_deref()) {
                 Some(d) => d,
                 None => {
-                    tracing::error!("Failed to get default output device");
+                    tracing::error!("Failed to get output device {:?}", device_id);
                     return;
                 }
             };
@@ -164,8 +627,8 @@ impl RecordingChannel for SystemAudioCaptureChannel {
             // For WASAPI loopback, we need to create an input stream config
             // that matches the output device's format
             let stream_config = StreamConfig {
-                channels,
-                sample_rate: cpal::SampleRate(sample_rate),
+                channels: native.channels,
+                sample_rate: cpal::SampleRate(native.sample_rate),
                 buffer_size: cpal::BufferSize::Default,
             };
 
@@ -181,7 +644,7 @@ impl RecordingChannel for SystemAudioCaptureChannel {
                     &stream_config,
                     move |data: &[f32], _: &cpal::InputCallbackInfo| {
                         if is_rec.load(Ordering::Relaxed) {
-                            let bytes: Vec<u8> = data
+                            let bytes: Vec<u8> = convert_samples(data, native, target)
                                 .iter()
                                 .flat_map(|&sample| sample.to_le_bytes())
                                 .collect();
@@ -267,4 +730,14 @@ impl RecordingChannel for SystemAudioCaptureChannel {
     fn output_files(&self) -> Vec<String> {
         self.output_files.lock().clone()
     }
+
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings {
+            device: self.device_name.clone(),
+            sample_rate: Some(self.target.sample_rate),
+            channels: Some(self.target.channels),
+            bitrate_kbps: Some(self.encoding.bitrate_kbps()),
+            ..Default::default()
+        }
+    }
 }
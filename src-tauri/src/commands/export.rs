@@ -2,12 +2,35 @@
 //!
 //! This module provides Tauri commands for video export functionality.
 
-use crate::export::{export_with_edits, ExportOptions, ExportPipeline, ExportProgress, TrackEdits};
+use crate::commands::project::{get_projects_directory, AppState};
+use crate::export::benchmark::{self, BenchmarkConfig, BenchmarkReport, EncoderChoice};
+use crate::export::chapters;
+use crate::export::clip::{self, ClipPreset, ClipResult};
+use crate::export::gif::{self, GifFit};
+use crate::export::mixdown;
+use crate::export::share::{self, ShareLink, ShareSettings};
+use crate::export::sessions::SessionMedia;
+use crate::export::verify::{self, ExpectedOutput, ExportVerification};
+use crate::export::watch_folder::{self, BundleTracker, WatchFolderSettings};
+use crate::export::webcam_track;
+use crate::export::{archive, export_with_edits, ExportError, ExportOptions, ExportPipeline, ExportProgress, TrackEdits};
+use crate::processing::echo_cancellation;
+use crate::project::bundle::{read_markers, read_project};
+use crate::project::encryption::readable_path;
+use crate::project::schema::{AudioConfig, ColorLut, TrackMutes};
+use crate::recorder::manifest::{
+    resolve_primary_audio, resolve_primary_video, FileKind, RecordingManifest,
+};
+use crate::utils::error::{AppError, AppResult, ErrorResponse};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::time::MissedTickBehavior;
 
 /// State for tracking active export jobs
 #[derive(Default)]
@@ -18,6 +41,130 @@ pub struct ExportState {
     is_exporting: Arc<AtomicBool>,
 }
 
+impl ExportState {
+    /// Whether an export job is running
+    pub fn is_running(&self) -> bool {
+        self.is_exporting.load(Ordering::Relaxed)
+    }
+
+    /// Ask the running export to stop. It cleans up after itself and then
+    /// stops counting as running.
+    pub fn cancel(&self) {
+        self.cancel_flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Mark an export as running and return its cancel flag, or `Busy` if
+    /// another one already is. `finish` marks it as done.
+    fn begin(&self) -> AppResult<Arc<AtomicBool>> {
+        if self.is_exporting.swap(true, Ordering::Relaxed) {
+            return Err(AppError::Busy("An export is already in progress".to_string()));
+        }
+        self.cancel_flag.store(false, Ordering::Relaxed);
+        Ok(self.cancel_flag.clone())
+    }
+
+    fn finish(&self) {
+        self.is_exporting.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Mute/solo flags of each session's recording scene, or none if the
+/// project can't be read
+fn scene_track_mutes(project_dir: &str) -> BTreeMap<usize, TrackMutes> {
+    let mut mutes = BTreeMap::new();
+    if let Ok(project) = read_project(&PathBuf::from(project_dir)) {
+        for scene in project.scenes {
+            mutes.entry(scene.session_index).or_insert(scene.muted);
+        }
+    }
+    mutes
+}
+
+/// The project's color LUT, or none if the project can't be read
+fn project_color_lut(project_dir: &str) -> Option<ColorLut> {
+    read_project(&PathBuf::from(project_dir))
+        .ok()
+        .and_then(|project| project.config.color_lut)
+}
+
+/// The project's audio settings, or the defaults if the project can't be read
+fn project_audio(project_dir: &str) -> AudioConfig {
+    read_project(&PathBuf::from(project_dir))
+        .map(|project| project.config.audio)
+        .unwrap_or_default()
+}
+
+/// Report the parameters a GIF export was shrunk to
+fn emit_gif_fit(app: &AppHandle, fit: Option<GifFit>) {
+    if let Some(fit) = fit {
+        if let Err(e) = app.emit("export-gif-fit", &fit) {
+            tracing::warn!("Failed to emit export-gif-fit: {}", e);
+        }
+    }
+}
+
+/// Checksum and verify a finished export, for the `export-complete` event.
+/// None if the output couldn't be read.
+fn verify_output(options: &ExportOptions, expected: &ExpectedOutput) -> Option<ExportVerification> {
+    match verify::verify_export(options, expected) {
+        Ok(verification) => {
+            if !verification.passed {
+                tracing::warn!("Export verification failed: {:?}", verification.problems);
+            }
+            Some(verification)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to verify export: {}", e);
+            None
+        }
+    }
+}
+
+/// Apply the project's track mutes, volumes and color LUT, and the
+/// benchmarked encoder, to export options
+fn apply_project_settings(options: &mut ExportOptions, project_dir: &str) {
+    options.apply_track_mutes(scene_track_mutes(project_dir));
+    options.apply_audio_volumes(&project_audio(project_dir));
+    options.apply_color_lut(Path::new(project_dir), project_color_lut(project_dir).as_ref());
+    options.h264_encoder = benchmark::saved_hardware_encoder();
+}
+
+/// Render a project with the export pipeline, shrink a GIF to its size
+/// budget and verify the output. Blocks until the export is done.
+fn run_pipeline<F>(
+    project_dir: &str,
+    options: &ExportOptions,
+    cancel_flag: Arc<AtomicBool>,
+    emit_progress: F,
+) -> Result<(Option<GifFit>, Option<ExportVerification>), ExportError>
+where
+    F: Fn(ExportProgress) + Send + Sync,
+{
+    let pipeline = ExportPipeline::new(PathBuf::from(project_dir), options.clone(), cancel_flag);
+    let expected = pipeline.run(&emit_progress)?;
+    let gif_fit = gif::apply_size_budget(options, &emit_progress)?;
+    let verification = verify_output(options, &expected);
+    emit_progress(ExportProgress::complete());
+    Ok((gif_fit, verification))
+}
+
+/// Report export progress to the frontend
+fn emit_export_progress(app: &AppHandle, progress: ExportProgress) {
+    if let Err(e) = app.emit("export-progress", &progress) {
+        tracing::warn!("Failed to emit export progress: {}", e);
+    }
+}
+
+/// Report a failed export job to the frontend, ending its progress with an
+/// `Error` stage
+fn emit_export_error(app: &AppHandle, error: impl Into<AppError>) {
+    let response = ErrorResponse::from(error.into());
+    emit_export_progress(app, ExportProgress::error(response.message.clone()));
+    if let Err(e) = app.emit("export-error", &response) {
+        tracing::warn!("Failed to emit export-error: {}", e);
+    }
+}
+
 /// Start an export job
 ///
 /// This command starts the export process in a background task and
@@ -27,38 +174,23 @@ pub async fn start_export(
     app: AppHandle,
     state: State<'_, ExportState>,
     project_dir: String,
-    options: ExportOptions,
-) -> Result<(), String> {
-    // Check if already exporting
-    if state.is_exporting.load(Ordering::Relaxed) {
-        return Err("An export is already in progress".to_string());
-    }
-
-    // Reset cancel flag
-    state.cancel_flag.store(false, Ordering::Relaxed);
-    state.is_exporting.store(true, Ordering::Relaxed);
-
-    let cancel_flag = state.cancel_flag.clone();
+    mut options: ExportOptions,
+) -> AppResult<()> {
+    options.validate()?;
+    let cancel_flag = state.begin()?;
     let is_exporting = state.is_exporting.clone();
 
+    apply_project_settings(&mut options, &project_dir);
+
     tracing::info!("Starting export for project: {}", project_dir);
     tracing::info!("Export options: {:?}", options);
 
     // Run export in background task
     tauri::async_runtime::spawn(async move {
-        let pipeline = ExportPipeline::new(
-            PathBuf::from(&project_dir),
-            options,
-            cancel_flag,
-        );
-
         let app_handle = app.clone();
         let result = tokio::task::spawn_blocking(move || {
-            pipeline.run(|progress| {
-                // Emit progress event
-                if let Err(e) = app_handle.emit("export-progress", &progress) {
-                    tracing::warn!("Failed to emit export progress: {}", e);
-                }
+            run_pipeline(&project_dir, &options, cancel_flag, |progress| {
+                emit_export_progress(&app_handle, progress)
             })
         })
         .await;
@@ -68,23 +200,20 @@ pub async fn start_export(
 
         // Handle result
         match result {
-            Ok(Ok(())) => {
+            Ok(Ok((gif_fit, verification))) => {
                 tracing::info!("Export completed successfully");
-                if let Err(e) = app.emit("export-complete", ()) {
+                emit_gif_fit(&app, gif_fit);
+                if let Err(e) = app.emit("export-complete", &verification) {
                     tracing::warn!("Failed to emit export-complete: {}", e);
                 }
             }
             Ok(Err(e)) => {
                 tracing::error!("Export failed: {}", e);
-                if let Err(emit_err) = app.emit("export-error", e.to_string()) {
-                    tracing::warn!("Failed to emit export-error: {}", emit_err);
-                }
+                emit_export_error(&app, e);
             }
             Err(e) => {
                 tracing::error!("Export task panicked: {}", e);
-                if let Err(emit_err) = app.emit("export-error", format!("Export task panicked: {}", e)) {
-                    tracing::warn!("Failed to emit export-error: {}", emit_err);
-                }
+                emit_export_error(&app, AppError::Export(format!("Export task panicked: {}", e)));
             }
         }
     });
@@ -94,9 +223,9 @@ pub async fn start_export(
 
 /// Cancel the current export job
 #[tauri::command]
-pub fn cancel_export(state: State<'_, ExportState>) -> Result<(), String> {
+pub fn cancel_export(state: State<'_, ExportState>) -> AppResult<()> {
     if !state.is_exporting.load(Ordering::Relaxed) {
-        return Err("No export in progress".to_string());
+        return Err(AppError::InvalidInput("No export in progress".to_string()));
     }
 
     tracing::info!("Cancelling export");
@@ -113,27 +242,33 @@ pub fn is_exporting(state: State<'_, ExportState>) -> bool {
 /// Export with edits (trim/cut/speed) using FFmpeg filter_complex
 ///
 /// This is a simplified export that applies edits directly via FFmpeg,
-/// without frame-by-frame cursor compositing. Use this for exports
-/// that don't need cursor overlay, or when edits are specified.
+/// without frame-by-frame compositing: no cursor overlay, annotations or
+/// zoom. Use this for exports that don't need them, or when edits are
+/// specified.
 #[tauri::command]
 pub async fn start_export_with_edits(
     app: AppHandle,
     state: State<'_, ExportState>,
     project_dir: String,
-    options: ExportOptions,
+    mut options: ExportOptions,
     edits: TrackEdits,
-) -> Result<(), String> {
-    // Check if already exporting
-    if state.is_exporting.load(Ordering::Relaxed) {
-        return Err("An export is already in progress".to_string());
-    }
-
-    // Reset cancel flag
-    state.cancel_flag.store(false, Ordering::Relaxed);
-    state.is_exporting.store(true, Ordering::Relaxed);
-
+) -> AppResult<()> {
+    options.validate()?;
+    let cancel_flag = state.begin()?;
     let is_exporting = state.is_exporting.clone();
 
+    apply_project_settings(&mut options, &project_dir);
+    // Only the export pipeline (`start_export`) draws annotations and zooms
+    if options.include_annotations {
+        tracing::info!("Annotations are left out of exports with edits");
+        options.include_annotations = false;
+    }
+    let zooms = read_project(Path::new(&project_dir))
+        .is_ok_and(|project| project.scenes.iter().any(|s| !s.zoom_ranges.is_empty()));
+    if zooms {
+        tracing::info!("Zoom ranges are left out of exports with edits");
+    }
+
     tracing::info!("Starting export with edits for project: {}", project_dir);
     tracing::info!("Export options: {:?}", options);
     tracing::info!("Edits: {} segments", edits.segments.len());
@@ -145,57 +280,94 @@ pub async fn start_export_with_edits(
     // Build paths - recording files are in the "recording" subdirectory
     let project_path = PathBuf::from(&project_dir);
     let recording_dir = project_path.join("recording");
-    let video_path = recording_dir.join("recording-0.mp4");
-    let webcam_video_path = recording_dir.join("recording-0-webcam.mp4");
-    let mic_audio_path = recording_dir.join("recording-0-mic.m4a");
-    let system_audio_path = recording_dir.join("recording-0-system.m4a");
+    let media = SessionMedia::load(&recording_dir).mute_tracks(&options.track_mutes);
 
-    // Check video exists
-    if !video_path.exists() {
+    // Check something was recorded (camera-only bundles export the webcam
+    // recording, audio-only ones a black video)
+    let primary_kind = media.primary_kind();
+    let has_audio = [FileKind::MicAudio, FileKind::SystemAudio]
+        .into_iter()
+        .any(|kind| !media.session_files(kind).is_empty());
+    if media.session_files(primary_kind).is_empty() && !has_audio {
         is_exporting.store(false, Ordering::Relaxed);
-        return Err(format!("Video file not found: {:?}", video_path));
+        return Err(AppError::NotFound(format!("No video or audio found in {:?}", recording_dir)));
     }
+    let capture_fps = media
+        .manifest()
+        .and_then(|m| m.display_fps())
+        .filter(|_| primary_kind == FileKind::ScreenVideo);
 
     // Run export in background task
     tauri::async_runtime::spawn(async move {
-        // Start FFmpeg process
-        let result = export_with_edits(
-            &video_path,
-            if webcam_video_path.exists() {
-                Some(webcam_video_path.as_path())
-            } else {
-                None
-            },
-            if mic_audio_path.exists() {
-                Some(mic_audio_path.as_path())
-            } else {
-                None
-            },
-            if system_audio_path.exists() {
-                Some(system_audio_path.as_path())
-            } else {
-                None
-            },
-            &options,
-            &edits,
-        );
+        emit_export_progress(&app, ExportProgress::preparing());
+
+        // Start FFmpeg process, on decrypted copies of encrypted media. The
+        // sessions of a paused recording are joined first; edits are on the
+        // joined timeline.
+        let readable = |path: Option<PathBuf>| path.as_deref().map(readable_path).transpose();
+        let join_progress = |_: FileKind, fraction: f64| {
+            emit_export_progress(&app, ExportProgress::joining_sessions(fraction))
+        };
+        let media = media.observe(&cancel_flag, &join_progress);
+        let result = (|| -> Result<_, ExportError> {
+            // Holds the joined sessions and echo-cancelled microphone until
+            // FFmpeg is done with them
+            let audio_scratch = tempfile::tempdir()?;
+            let scratch = audio_scratch.path();
+            let video_path = media
+                .primary_video(scratch, capture_fps)?
+                .ok_or_else(|| ExportError::BundleNotFound("Video file not found".to_string()))?;
+            let webcam_video_path = match primary_kind {
+                FileKind::ScreenVideo => media.media_file(FileKind::WebcamVideo, scratch, None)?,
+                _ => None,
+            };
+            let system_audio = readable(media.media_file(FileKind::SystemAudio, scratch, None)?)?;
+            let mic_audio = readable(media.media_file(FileKind::MicAudio, scratch, None)?)?;
+            if echo_cancellation::needs_audio_pass(
+                &options,
+                mic_audio.as_deref(),
+                system_audio.as_deref(),
+            ) {
+                emit_export_progress(&app, ExportProgress::processing_audio());
+            }
+            let mic_audio = echo_cancellation::export_mic_track(
+                &options,
+                mic_audio.as_deref(),
+                system_audio.as_deref(),
+                audio_scratch.path(),
+            );
+            let webcam = readable(webcam_video_path)?;
+            let child = export_with_edits(
+                &readable_path(&video_path)?,
+                capture_fps,
+                webcam.as_deref(),
+                mic_audio.as_deref(),
+                system_audio.as_deref(),
+                &options,
+                &edits,
+            )?;
+            Ok((child, audio_scratch, [webcam, mic_audio, system_audio]))
+        })();
 
         match result {
-            Ok(mut child) => {
+            Ok((mut child, _audio_scratch, [webcam, mic_audio, system_audio])) => {
+                emit_export_progress(&app, ExportProgress::encoding(0, total_duration_ms));
+
                 // Parse progress from stdout
                 if let Some(stdout) = child.stdout.take() {
                     let reader = BufReader::new(stdout);
                     for line in reader.lines().map_while(Result::ok) {
+                        if cancel_flag.load(Ordering::Relaxed) {
+                            let _ = child.kill();
+                            break;
+                        }
                         if line.starts_with("out_time_us=") {
                             if let Ok(time_us) = line[12..].parse::<u64>() {
                                 let progress = ExportProgress::encoding(
                                     time_us / 1000, // Convert to ms as "current frame"
                                     total_duration_ms,
                                 );
-
-                                if let Err(e) = app.emit("export-progress", &progress) {
-                                    tracing::warn!("Failed to emit export progress: {}", e);
-                                }
+                                emit_export_progress(&app, progress);
                             }
                         }
                     }
@@ -204,13 +376,45 @@ pub async fn start_export_with_edits(
                 // Wait for FFmpeg to complete
                 match child.wait() {
                     Ok(status) if status.success() => {
-                        tracing::info!("Export with edits completed successfully");
-                        let _ = app.emit("export-progress", ExportProgress::complete());
-                        let _ = app.emit("export-complete", ());
+                        emit_export_progress(&app, ExportProgress::finalizing());
+                        chapters::embed_project_chapters(&project_path, &options, Some(&edits.segments));
+                        webcam_track::export_webcam_track(
+                            webcam.as_deref(),
+                            mic_audio.as_deref(),
+                            system_audio.as_deref(),
+                            &options,
+                            Some(&edits.segments),
+                        );
+                        match gif::apply_size_budget(&options, |progress| {
+                            emit_export_progress(&app, progress)
+                        }) {
+                            Ok(gif_fit) => {
+                                tracing::info!("Export with edits completed successfully");
+                                emit_gif_fit(&app, gif_fit);
+                                let expected = ExpectedOutput::new(
+                                    &options,
+                                    total_duration_ms as f64,
+                                    mic_audio.as_deref(),
+                                    system_audio.as_deref(),
+                                );
+                                let verification = verify_output(&options, &expected);
+                                emit_export_progress(&app, ExportProgress::complete());
+                                let _ = app.emit("export-complete", &verification);
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to fit GIF to size budget: {}", e);
+                                emit_export_error(&app, e);
+                            }
+                        }
+                    }
+                    Ok(_) if cancel_flag.load(Ordering::Relaxed) => {
+                        tracing::info!("Export with edits cancelled");
+                        emit_export_error(&app, ExportError::Cancelled);
                     }
                     Ok(status) => {
                         let stderr = child
                             .stderr
+                            .take()
                             .map(|s| {
                                 let mut buf = String::new();
                                 let _ = BufReader::new(s).read_line(&mut buf);
@@ -218,17 +422,17 @@ pub async fn start_export_with_edits(
                             })
                             .unwrap_or_default();
                         tracing::error!("FFmpeg exited with status {}: {}", status, stderr);
-                        let _ = app.emit("export-error", format!("FFmpeg failed: {}", stderr));
+                        emit_export_error(&app, AppError::Ffmpeg(stderr));
                     }
                     Err(e) => {
                         tracing::error!("Failed to wait for FFmpeg: {}", e);
-                        let _ = app.emit("export-error", e.to_string());
+                        emit_export_error(&app, e);
                     }
                 }
             }
             Err(e) => {
                 tracing::error!("Failed to start export: {}", e);
-                let _ = app.emit("export-error", e.to_string());
+                emit_export_error(&app, e);
             }
         }
 
@@ -238,3 +442,297 @@ pub async fn start_export_with_edits(
 
     Ok(())
 }
+
+/// Remux a recording bundle into a single multi-track MKV for archiving
+///
+/// Returns the path of the written archive.
+#[tauri::command]
+pub async fn archive_recording(
+    projects: State<'_, AppState>,
+    project_dir: String,
+) -> AppResult<String> {
+    tracing::info!("Archiving recording bundle: {}", project_dir);
+    // The archive is written next to the bundle, in the folder it was
+    // opened read-only from
+    projects.ensure_writable(Some(Path::new(&project_dir))).await?;
+
+    let path = tokio::task::spawn_blocking(move || archive::archive_recording(&PathBuf::from(project_dir)))
+        .await
+        .map_err(|e| AppError::Export(e.to_string()))??;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Cut a quick MP4 or GIF of `start_ms..end_ms` of a recording
+///
+/// Bypasses the export pipeline (no edits or effects) for instant sharing.
+/// Times are in the recording's timeline. Without `output_path` the clip is
+/// saved next to the bundle.
+#[tauri::command]
+pub async fn create_clip(
+    project_dir: String,
+    start_ms: f64,
+    end_ms: f64,
+    preset: ClipPreset,
+    output_path: Option<String>,
+) -> AppResult<ClipResult> {
+    let project_path = PathBuf::from(&project_dir);
+    let mic_offset_ms = read_project(&project_path)
+        .map(|project| project.config.audio.microphone_offset_ms)
+        .unwrap_or(0.0);
+    let output = output_path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| clip::default_clip_path(&project_path, start_ms, end_ms, preset));
+
+    let result = tokio::task::spawn_blocking(move || {
+        clip::create_clip(&project_path, start_ms, end_ms, preset, mic_offset_ms, &output)
+    })
+    .await
+    .map_err(|e| AppError::Export(e.to_string()))??;
+    Ok(result)
+}
+
+/// Encode a short synthetic clip with every available encoder and preset
+///
+/// Reports encode speed and output size for each, plus a recommendation for
+/// this machine, which is saved for MP4 exports to use. `config` defaults
+/// to five seconds of 1080p30. Counts as an export while it runs, so
+/// `cancel_export` stops it.
+#[tauri::command]
+pub async fn run_export_benchmark(
+    state: State<'_, ExportState>,
+    config: Option<BenchmarkConfig>,
+) -> AppResult<BenchmarkReport> {
+    // Timings would be skewed by (and would slow down) a running export
+    let cancel_flag = state.begin()?;
+
+    let config = config.unwrap_or_default();
+    let result =
+        tokio::task::spawn_blocking(move || benchmark::run_benchmark(&config, &cancel_flag))
+            .await;
+    state.finish();
+    let report = result.map_err(|e| AppError::Export(e.to_string()))??;
+
+    if let Some(choice) = EncoderChoice::from_report(&report) {
+        tracing::info!("Benchmark picked {} for MP4 exports", choice.encoder);
+        let saved = benchmark::choice_path()
+            .and_then(|path| benchmark::save_choice(&path, &choice));
+        if let Err(e) = saved {
+            tracing::warn!("Failed to save the benchmarked encoder: {}", e);
+        }
+    }
+    Ok(report)
+}
+
+/// Upload an exported file with the configured share provider
+///
+/// Returns the shareable link, which is also copied to the clipboard.
+/// `expiry_secs` overrides the default link expiry from the share settings.
+/// An `Uploading` stage is sent on `export-progress` while the upload runs,
+/// followed by `Complete` or `Error`.
+#[tauri::command]
+pub async fn share_export(
+    app: AppHandle,
+    path: String,
+    expiry_secs: Option<u64>,
+) -> AppResult<ShareLink> {
+    tracing::info!("Sharing export: {}", path);
+    emit_export_progress(&app, ExportProgress::uploading());
+
+    // Exports are written into project bundles or next to them
+    let export_dir = get_projects_directory()?;
+    let result = tokio::task::spawn_blocking(move || {
+        let settings = share::load_settings_with_secret(&share::settings_path()?)?;
+        share::share_file(&settings, &PathBuf::from(path), &export_dir, expiry_secs)
+    })
+    .await
+    .map_err(|e| AppError::Export(e.to_string()))
+    .and_then(|result| result.map_err(AppError::from));
+    match &result {
+        Ok(_) => emit_export_progress(&app, ExportProgress::complete()),
+        Err(e) => emit_export_progress(&app, ExportProgress::error(e.to_string())),
+    }
+    result
+}
+
+/// Get the share provider settings. The S3 secret key is never included.
+#[tauri::command]
+pub fn get_share_settings() -> AppResult<ShareSettings> {
+    Ok(share::load_settings_with_secret(&share::settings_path()?)?)
+}
+
+/// Save the share provider settings. An S3 secret key is stored in the
+/// keychain; leave it empty to keep the stored one.
+#[tauri::command]
+pub fn set_share_settings(mut settings: ShareSettings) -> AppResult<()> {
+    share::store_secret(&mut settings)?;
+    Ok(share::save_settings(&share::settings_path()?, &settings)?)
+}
+
+/// Get the watch-folder export settings
+#[tauri::command]
+pub fn get_watch_folder_settings() -> AppResult<WatchFolderSettings> {
+    Ok(watch_folder::load_settings(&watch_folder::settings_path()?)?)
+}
+
+/// Save the watch-folder export settings. They apply from the next scan.
+#[tauri::command]
+pub fn set_watch_folder_settings(settings: WatchFolderSettings) -> AppResult<()> {
+    settings.validate()?;
+    Ok(watch_folder::save_settings(&watch_folder::settings_path()?, &settings)?)
+}
+
+/// How often the watch folder is scanned for new bundles
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Outcome of a watch-folder export, sent as `watch-folder-export`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFolderExport {
+    pub bundle_path: String,
+    pub output_path: String,
+    /// None if the export succeeded
+    pub error: Option<ErrorResponse>,
+    pub verification: Option<ExportVerification>,
+}
+
+/// Export bundles dropped into the watch folder, for as long as the app
+/// runs. Settings are read again on every scan. A bundle waits while
+/// another export is running, and its progress isn't sent on
+/// `export-progress` so it doesn't show in an open export dialog.
+pub fn watch_folder_exports(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tracker = BundleTracker::default();
+        let mut ticker = tokio::time::interval(WATCH_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            let settings = match watch_folder::settings_path()
+                .and_then(|path| watch_folder::load_settings(&path))
+            {
+                Ok(settings) => settings,
+                Err(e) => {
+                    tracing::warn!("Failed to load watch folder settings: {}", e);
+                    continue;
+                }
+            };
+            let Some((folder, preset)) = settings.active() else {
+                continue;
+            };
+            let scan = match watch_folder::scan_folder(folder) {
+                Ok(scan) => scan,
+                Err(e) => {
+                    tracing::warn!("Failed to scan watch folder {:?}: {}", folder, e);
+                    continue;
+                }
+            };
+
+            for bundle in tracker.ready(scan) {
+                let mut options = settings.export_options(&bundle, preset);
+                if tracker.export_is_current(&bundle, Path::new(&options.output_path)) {
+                    tracker.mark_handled(&bundle);
+                    continue;
+                }
+                let state = app.state::<ExportState>();
+                // Try again on the next scan
+                let Ok(cancel_flag) = state.begin() else {
+                    break;
+                };
+                tracker.mark_handled(&bundle);
+
+                let project_dir = bundle.to_string_lossy().to_string();
+                apply_project_settings(&mut options, &project_dir);
+                tracing::info!("Exporting {} from the watch folder", project_dir);
+                let output_path = options.output_path.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    run_pipeline(&project_dir, &options, cancel_flag, |_| {})
+                })
+                .await;
+                state.finish();
+
+                let (error, verification) = match result {
+                    Ok(Ok((_, verification))) => (None, verification),
+                    Ok(Err(e)) => (Some(AppError::from(e)), None),
+                    Err(e) => (Some(AppError::Export(e.to_string())), None),
+                };
+                if let Some(e) = &error {
+                    tracing::error!("Watch folder export of {:?} failed: {}", bundle, e);
+                }
+                let event = WatchFolderExport {
+                    bundle_path: bundle.to_string_lossy().to_string(),
+                    output_path,
+                    error: error.map(ErrorResponse::from),
+                    verification,
+                };
+                if let Err(e) = app.emit("watch-folder-export", &event) {
+                    tracing::warn!("Failed to emit watch-folder-export: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// Format the project's markers as a YouTube description chapter list
+///
+/// Pass the same `edits` used for the export so timestamps match the video.
+/// Returns an empty string if the markers don't meet YouTube's chapter rules.
+#[tauri::command]
+pub async fn get_youtube_chapters(project_dir: String, edits: Option<TrackEdits>) -> AppResult<String> {
+    let project_path = PathBuf::from(&project_dir);
+    let markers = read_markers(&project_path)?;
+
+    let (markers, duration_ms) = match edits {
+        Some(edits) => (
+            chapters::remap_markers(&markers, &edits.segments),
+            edits.total_output_duration_ms() as f64,
+        ),
+        None => {
+            let recording_dir = project_path.join("recording");
+            let manifest = RecordingManifest::read(&recording_dir);
+            let media_path = resolve_primary_video(&recording_dir, manifest.as_ref(), 0)
+                .or_else(|| resolve_primary_audio(&recording_dir, manifest.as_ref(), 0))
+                .ok_or_else(|| {
+                    AppError::NotFound(format!("No video or audio found in {:?}", recording_dir))
+                })?;
+            let duration_ms = archive::probe_duration_ms(&readable_path(&media_path)?)?;
+            (markers, duration_ms)
+        }
+    };
+
+    Ok(chapters::youtube_description(&markers, duration_ms))
+}
+
+/// Render `start_ms..end_ms` of the output timeline as the export would mix
+/// it, for editor playback
+///
+/// `audio` carries the editor's current (possibly unsaved) volumes and
+/// defaults to the saved project's. Muted tracks are left out. Returns the
+/// path of a temporary WAV file.
+#[tauri::command]
+pub async fn render_playback_mixdown(
+    project_dir: String,
+    edits: Option<TrackEdits>,
+    start_ms: u64,
+    end_ms: u64,
+    audio: Option<AudioConfig>,
+) -> AppResult<String> {
+    let project_path = PathBuf::from(&project_dir);
+    let audio = match audio {
+        Some(audio) => audio,
+        None => read_project(&project_path)?.config.audio,
+    };
+    let media = SessionMedia::load(&project_path.join("recording"))
+        .mute_tracks(&scene_track_mutes(&project_dir));
+
+    let path = tokio::task::spawn_blocking(move || {
+        // Tracks are lined up with the video as export lines them up
+        let mic = mixdown::aligned_track(&media, FileKind::MicAudio)?;
+        let system = mixdown::aligned_track(&media, FileKind::SystemAudio)?;
+        let segments = edits.as_ref().map(|edits| edits.segments.as_slice());
+        mixdown::render_mixdown(mic.as_deref(), system.as_deref(), &audio, segments, start_ms, end_ms)
+    })
+    .await
+    .map_err(|e| AppError::Export(e.to_string()))??;
+
+    Ok(path.to_string_lossy().to_string())
+}
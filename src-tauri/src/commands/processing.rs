@@ -3,24 +3,40 @@
 //! These commands expose cursor smoothing and other post-processing
 //! functionality to the frontend.
 
-use crate::capture::input::types::MouseMove;
-use crate::processing::cursor_smoothing::{smooth_cursor_data, SmoothedMouseMove};
-use crate::project::schema::SpringConfig;
-use std::path::Path;
+use crate::capture::frame::WindowResizeEvent;
+use crate::capture::input::types::{MouseClick, MouseMove};
+use crate::export::ffmpeg::VideoDecoder;
+use crate::export::ExportError;
+use crate::processing::cursor_smoothing::{
+    smooth_cursor_data, smooth_cursor_for_slices, smooth_cursor_range, SmoothedMouseMove,
+};
+use crate::processing::scene_detection::{self, SceneDetectionConfig};
+use crate::processing::sync_report::{self, SyncReport};
+use crate::processing::zoom::{self, ZoomInputs, ZoomKeyframe};
+use crate::processing::zoom_timeline::resolve_zoom_ranges;
+use crate::project::bundle::read_project;
+use crate::project::encryption::readable_path;
+use crate::project::schema::{Marker, Slice, SmoothingPreset, SpringConfig};
+use crate::recorder::manifest::{resolve_file, resolve_primary_video, FileKind, RecordingManifest};
+use crate::utils::error::{AppError, AppResult};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
 /// Process raw mouse moves and return smoothed data
 ///
-/// This is used for real-time preview in the editor.
+/// This is used for real-time preview in the editor. With the timeline's
+/// `slices`, slowed-down slices get extra interpolated frames.
 #[tauri::command]
 pub async fn smooth_cursor(
     input_file: String,
     config: SpringConfig,
     output_fps: f64,
-) -> Result<Vec<SmoothedMouseMove>, String> {
+    slices: Option<Vec<Slice>>,
+) -> AppResult<Vec<SmoothedMouseMove>> {
     let path = Path::new(&input_file);
-    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read input file: {}", e))?;
+    let content = std::fs::read_to_string(path).map_err(|e| AppError::InvalidInput(format!("Failed to read input file: {}", e)))?;
     let raw_moves: Vec<MouseMove> =
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse mouse moves: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| AppError::InvalidInput(format!("Failed to parse mouse moves: {}", e)))?;
 
     tracing::info!(
         "Smoothing {} raw moves at {}fps with config: stiffness={}, damping={}, mass={}",
@@ -31,7 +47,10 @@ pub async fn smooth_cursor(
         config.mass
     );
 
-    let smoothed = smooth_cursor_data(&raw_moves, &config, output_fps);
+    let smoothed = match &slices {
+        Some(slices) => smooth_cursor_for_slices(&raw_moves, &config, output_fps, slices),
+        None => smooth_cursor_data(&raw_moves, &config, output_fps),
+    };
 
     tracing::info!("Generated {} smoothed frames", smoothed.len());
 
@@ -45,11 +64,12 @@ pub async fn process_cursor_smoothing(
     output_file: String,
     config: SpringConfig,
     output_fps: f64,
-) -> Result<(), String> {
+    slices: Option<Vec<Slice>>,
+) -> AppResult<()> {
     let path = Path::new(&input_file);
-    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read input file: {}", e))?;
+    let content = std::fs::read_to_string(path).map_err(|e| AppError::InvalidInput(format!("Failed to read input file: {}", e)))?;
     let raw_moves: Vec<MouseMove> =
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse mouse moves: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| AppError::InvalidInput(format!("Failed to parse mouse moves: {}", e)))?;
 
     tracing::info!(
         "Processing cursor smoothing: {} raw moves -> {} at {}fps",
@@ -58,10 +78,13 @@ pub async fn process_cursor_smoothing(
         output_fps
     );
 
-    let smoothed = smooth_cursor_data(&raw_moves, &config, output_fps);
-    let output = serde_json::to_vec_pretty(&smoothed).map_err(|e| format!("Failed to serialize: {}", e))?;
+    let smoothed = match &slices {
+        Some(slices) => smooth_cursor_for_slices(&raw_moves, &config, output_fps, slices),
+        None => smooth_cursor_data(&raw_moves, &config, output_fps),
+    };
+    let output = serde_json::to_vec_pretty(&smoothed)?;
 
-    std::fs::write(&output_file, output).map_err(|e| format!("Failed to write output file: {}", e))?;
+    std::fs::write(&output_file, output).map_err(|e| AppError::Project(format!("Failed to write output file: {}", e)))?;
 
     tracing::info!("Wrote {} smoothed frames to {}", smoothed.len(), output_file);
 
@@ -73,3 +96,163 @@ pub async fn process_cursor_smoothing(
 pub async fn get_default_spring_config() -> SpringConfig {
     SpringConfig::default()
 }
+
+/// A named smoothing preset and its spring parameters
+#[derive(Debug, Clone, Serialize)]
+pub struct SmoothingPresetInfo {
+    pub preset: SmoothingPreset,
+    pub spring: SpringConfig,
+}
+
+/// List the built-in smoothing presets
+#[tauri::command]
+pub async fn get_smoothing_presets() -> Vec<SmoothingPresetInfo> {
+    SmoothingPreset::ALL
+        .iter()
+        .map(|&preset| SmoothingPresetInfo {
+            preset,
+            spring: preset.spring(),
+        })
+        .collect()
+}
+
+/// Smooth a short time range with candidate spring parameters
+///
+/// Lets the editor show the effect of parameter changes while the user is
+/// still tuning, before they're written to `CursorSmoothingConfig`.
+/// `range` is `(start_ms, end_ms)` in recording time.
+#[tauri::command]
+pub async fn preview_cursor_smoothing(
+    input_file: String,
+    config: SpringConfig,
+    range: (f64, f64),
+    output_fps: f64,
+) -> AppResult<Vec<SmoothedMouseMove>> {
+    let content = std::fs::read_to_string(&input_file).map_err(|e| AppError::InvalidInput(format!("Failed to read input file: {}", e)))?;
+    let raw_moves: Vec<MouseMove> =
+        serde_json::from_str(&content).map_err(|e| AppError::InvalidInput(format!("Failed to parse mouse moves: {}", e)))?;
+
+    Ok(smooth_cursor_range(&raw_moves, &config, output_fps, range.0, range.1))
+}
+
+/// Suggest chapter markers from visual transitions in the screen recording
+///
+/// The suggestions are not saved; the frontend shows them for review and
+/// saves the accepted ones with `save_markers`.
+#[tauri::command]
+pub async fn suggest_chapters(
+    project_dir: String,
+    config: Option<SceneDetectionConfig>,
+) -> AppResult<Vec<Marker>> {
+    let recording_dir = PathBuf::from(&project_dir).join("recording");
+    let manifest = RecordingManifest::read(&recording_dir);
+    let video_path = resolve_primary_video(&recording_dir, manifest.as_ref(), 0)
+        .ok_or_else(|| AppError::NotFound(format!("Video file not found in {:?}", recording_dir)))?;
+    let config = config.unwrap_or_default();
+
+    tracing::info!("Detecting scene changes in {:?}", video_path);
+
+    // Encrypted recordings are analyzed from a decrypted copy
+    let markers = tokio::task::spawn_blocking(move || -> Result<_, ExportError> {
+        scene_detection::suggest_chapters(&readable_path(&video_path)?, &config)
+    })
+    .await
+    .map_err(|e| AppError::Export(e.to_string()))??;
+    Ok(markers)
+}
+
+/// Measure how the screen, webcam, microphone and system audio tracks of a
+/// finished recording line up
+///
+/// Diagnostic only: nothing in the project is changed. The report's
+/// suggested microphone offset can be applied as the project's
+/// `microphoneOffsetMs`.
+#[tauri::command]
+pub async fn analyze_recording_sync(
+    project_dir: String,
+    session_index: Option<usize>,
+) -> AppResult<SyncReport> {
+    tracing::info!("Analyzing sync of {}", project_dir);
+
+    let report = tokio::task::spawn_blocking(move || {
+        sync_report::analyze_bundle(&PathBuf::from(project_dir), session_index.unwrap_or(0))
+    })
+    .await
+    .map_err(|e| AppError::Export(e.to_string()))??;
+    Ok(report)
+}
+
+/// Default rate zoom paths are sampled at for the editor timeline
+const ZOOM_PATH_FPS: f64 = 30.0;
+
+/// Compute the zoom/pan transform of a scene over time
+///
+/// Uses the same zoom math as export on the same inputs (mouse positions
+/// brought onto the video, smoothed per slice at the rate the video is
+/// decoded at), so the editor can draw what follow-cursor and follow-clicks
+/// zooms will do, including the zoom-outs inserted at cuts and between
+/// overlapping ranges. `fps` is the sampling rate of the returned keyframes
+/// (defaults to 30).
+#[tauri::command]
+pub async fn render_zoom_path(
+    project_dir: String,
+    scene_id: String,
+    fps: Option<f64>,
+) -> AppResult<Vec<ZoomKeyframe>> {
+    let bundle_path = PathBuf::from(&project_dir);
+    let project = read_project(&bundle_path)?;
+    let scene = project
+        .scenes
+        .into_iter()
+        .find(|s| s.id == scene_id)
+        .ok_or_else(|| AppError::NotFound(format!("Scene not found: {}", scene_id)))?;
+    let spring = project.config.cursor.smoothing.spring;
+    let slices = if scene.screen_slices.is_empty() { scene.slices } else { scene.screen_slices };
+    let zoom_ranges = resolve_zoom_ranges(&scene.zoom_ranges, &slices);
+    let fps = fps.unwrap_or(ZOOM_PATH_FPS);
+
+    let recording_dir = bundle_path.join("recording");
+    let manifest = RecordingManifest::read(&recording_dir);
+    let find = |kind| resolve_file(&recording_dir, manifest.as_ref(), kind, scene.session_index);
+    let video_path =
+        find(FileKind::ScreenVideo).ok_or_else(|| AppError::NotFound(format!("Video file not found in {:?}", recording_dir)))?;
+    let moves_path = find(FileKind::MouseMoves);
+    let clicks_path = find(FileKind::MouseClicks);
+    let resizes_path = find(FileKind::WindowResizes);
+
+    tokio::task::spawn_blocking(move || -> AppResult<Vec<ZoomKeyframe>> {
+        let capture_fps = manifest.as_ref().and_then(|m| m.display_fps());
+        let (width, height, total_frames, source_fps) =
+            VideoDecoder::probe_at(&readable_path(&video_path)?, capture_fps)?;
+
+        let mut raw_moves: Vec<MouseMove> = read_json_or_default(moves_path.as_deref())?;
+        let mut clicks: Vec<MouseClick> = read_json_or_default(clicks_path.as_deref())?;
+        let window_resizes: Vec<WindowResizeEvent> = read_json_or_default(resizes_path.as_deref())?;
+        if let Some(manifest) = &manifest {
+            manifest.map_mouse_to_video(&window_resizes, &mut raw_moves, &mut clicks);
+        }
+        // Smooth at the decoded rate, as export does
+        let cursor = smooth_cursor_for_slices(&raw_moves, &spring, source_fps, &slices);
+
+        let inputs = ZoomInputs {
+            cursor: &cursor,
+            clicks: &clicks,
+            width,
+            height,
+        };
+        let duration_ms = total_frames as f64 / source_fps * 1000.0;
+        Ok(zoom::render_zoom_path(&zoom_ranges, &inputs, 0.0, duration_ms, fps))
+    })
+    .await
+    .map_err(|e| AppError::Export(e.to_string()))?
+}
+
+/// Read a JSON array file, treating a missing file as empty
+fn read_json_or_default<T: serde::de::DeserializeOwned>(path: Option<&Path>) -> AppResult<Vec<T>> {
+    let Some(path) = path else {
+        return Ok(Vec::new());
+    };
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Project(format!("Failed to read {:?}: {}", path, e)))?;
+    serde_json::from_str(&content).map_err(|e| AppError::Project(format!("Failed to parse {:?}: {}", path, e)))
+}
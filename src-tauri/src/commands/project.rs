@@ -6,14 +6,31 @@
 //! - Projects are automatically saved to ~/Movies/Open ScreenStudio/ when created
 //! - All edits are auto-saved to disk (no manual save button)
 
+use crate::export::cursor_pack::{self, CursorPackInfo};
+use crate::processing::audio_sync::{self, AudioSyncConfig};
 use crate::project::{
     bundle,
-    schema::{Layout, LayoutType, Point, Project, ProjectConfig, Scene, SceneType, Slice},
+    encryption::readable_path,
+    history::{self, Snapshot},
+    lut,
+    media::{self, ImportedMedia, ReplaceableTrack},
+    naming::{self, NamingContext, NamingSettings},
+    paths,
+    presentation,
+    schema::{
+        ColorLut, Layout, LayoutType, Marker, Point, Project, ProjectConfig, Scene, SceneType, Slice, TrackMutes,
+        UiState,
+    },
+    template::{self, ProjectTemplate},
 };
-use chrono::Utc;
+use crate::recorder::manifest::{
+    resolve_file, resolve_primary_audio, resolve_primary_video, FileKind, RecordingManifest,
+};
+use crate::utils::error::{AppError, AppResult};
+use chrono::{Local, Utc};
 use dirs;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::State;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -23,6 +40,9 @@ pub struct AppState {
     pub current_project: Mutex<Option<Project>>,
     pub current_project_path: Mutex<Option<PathBuf>>,
     pub temp_bundle_path: Mutex<Option<PathBuf>>,
+    /// The current project was opened with `open_project_readonly`, so
+    /// nothing may be written to its bundle
+    pub read_only: Mutex<bool>,
 }
 
 impl Default for AppState {
@@ -31,13 +51,52 @@ impl Default for AppState {
             current_project: Mutex::new(None),
             current_project_path: Mutex::new(None),
             temp_bundle_path: Mutex::new(None),
+            read_only: Mutex::new(false),
+        }
+    }
+}
+
+impl AppState {
+    /// Reject changes to the current project (or, given a path, to that
+    /// bundle) while it's open read-only. Every write into a bundle goes
+    /// through this.
+    pub(crate) async fn ensure_writable(&self, bundle_path: Option<&Path>) -> AppResult<()> {
+        if !*self.read_only.lock().await {
+            return Ok(());
         }
+        let blocked = match bundle_path {
+            Some(path) => self.is_current(path).await,
+            None => true,
+        };
+        if blocked {
+            return Err(AppError::ReadOnly(
+                "The project is open read-only; save a copy to make changes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether `bundle_path` is the current project's bundle, however it's
+    /// spelled
+    pub(crate) async fn is_current(&self, bundle_path: &Path) -> bool {
+        let current = self.current_project_path.lock().await;
+        current
+            .as_deref()
+            .is_some_and(|current| paths::canonical(current) == paths::canonical(bundle_path))
+    }
+
+    /// Make the project at `bundle_path` the current one
+    async fn set_current(&self, project: Project, bundle_path: &Path, read_only: bool) {
+        *self.current_project.lock().await = Some(project);
+        *self.current_project_path.lock().await = Some(paths::canonical(bundle_path));
+        *self.temp_bundle_path.lock().await = None;
+        *self.read_only.lock().await = read_only;
     }
 }
 
 /// Create a new project
 #[tauri::command]
-pub async fn create_project(name: Option<String>) -> Result<Project, String> {
+pub async fn create_project(name: Option<String>) -> AppResult<Project> {
     let project_name = name.unwrap_or_else(|| "Untitled Recording".to_string());
     let project = Project::new(project_name);
     
@@ -48,40 +107,72 @@ pub async fn create_project(name: Option<String>) -> Result<Project, String> {
 
 /// Open an existing project from a path
 #[tauri::command]
-pub async fn open_project(path: String) -> Result<Project, String> {
+pub async fn open_project(state: State<'_, AppState>, path: String) -> AppResult<Project> {
     let project_path = PathBuf::from(&path);
     
     tracing::info!("Opening project from: {:?}", project_path);
     
-    let project = bundle::read_project(&project_path)
-        .map_err(|e| format!("Failed to open project: {}", e))?;
+    let project = bundle::read_project(&project_path)?;
+    state.set_current(project.clone(), &project_path, false).await;
     
     Ok(project)
 }
 
+/// Open a project for review without modifying its bundle
+///
+/// Auto-save, template and media changes, and marker and view-state saves
+/// are rejected with a `READ_ONLY` error until another project is opened or
+/// this one is saved elsewhere with `save_project_to_path`.
+#[tauri::command]
+pub async fn open_project_readonly(
+    state: State<'_, AppState>,
+    path: String,
+) -> AppResult<Project> {
+    let project_path = PathBuf::from(&path);
+    
+    tracing::info!("Opening project read-only from: {:?}", project_path);
+    
+    let project = bundle::read_project(&project_path)?;
+    state.set_current(project.clone(), &project_path, true).await;
+    
+    Ok(project)
+}
+
+/// Whether the current project was opened read-only
+#[tauri::command]
+pub async fn is_project_read_only(state: State<'_, AppState>) -> AppResult<bool> {
+    Ok(*state.read_only.lock().await)
+}
+
 /// Save the current project to a path
 #[tauri::command]
-pub async fn save_project(project: Project, path: String) -> Result<(), String> {
+pub async fn save_project(
+    state: State<'_, AppState>,
+    project: Project,
+    path: String,
+) -> AppResult<()> {
     let project_path = PathBuf::from(&path);
+    state.ensure_writable(Some(&project_path)).await?;
     
     tracing::info!("Saving project to: {:?}", project_path);
     
-    bundle::write_project(&project, &project_path)
-        .map_err(|e| format!("Failed to save project: {}", e))?;
+    record_snapshot(&state, &project_path, &project).await;
+    bundle::write_project(&project, &project_path)?;
+    refresh_presentation(&state, &project_path, &project).await;
     
     Ok(())
 }
 
 /// Get the current project state
 #[tauri::command]
-pub async fn get_project(state: State<'_, AppState>) -> Result<Option<Project>, String> {
+pub async fn get_project(state: State<'_, AppState>) -> AppResult<Option<Project>> {
     let project = state.current_project.lock().await;
     Ok(project.clone())
 }
 
 /// Get the current project's saved path (None if unsaved/in temp)
 #[tauri::command]
-pub async fn get_project_path(state: State<'_, AppState>) -> Result<Option<String>, String> {
+pub async fn get_project_path(state: State<'_, AppState>) -> AppResult<Option<String>> {
     let path = state.current_project_path.lock().await;
     Ok(path.as_ref().map(|p| p.to_string_lossy().to_string()))
 }
@@ -89,45 +180,67 @@ pub async fn get_project_path(state: State<'_, AppState>) -> Result<Option<Strin
 /// Get the default projects directory
 /// Creates the directory if it doesn't exist
 #[tauri::command]
-pub async fn get_default_projects_dir() -> Result<String, String> {
+pub async fn get_default_projects_dir() -> AppResult<String> {
     let projects_dir = get_projects_directory()?;
     Ok(projects_dir.to_string_lossy().to_string())
 }
 
 /// Helper to get the default projects directory path
-fn get_projects_directory() -> Result<PathBuf, String> {
+pub(crate) fn get_projects_directory() -> AppResult<PathBuf> {
     // Use ~/Movies/Open ScreenStudio/ as the default location
     let movies_dir = dirs::video_dir()
         .or_else(|| dirs::home_dir().map(|h| h.join("Movies")))
-        .ok_or("Could not determine Movies directory")?;
+        .ok_or_else(|| AppError::Platform("Could not determine Movies directory".to_string()))?;
     
     let projects_dir = movies_dir.join("Open ScreenStudio");
     
     // Create the directory if it doesn't exist
     if !projects_dir.exists() {
         fs::create_dir_all(&projects_dir)
-            .map_err(|e| format!("Failed to create projects directory: {}", e))?;
+            .map_err(|e| AppError::Project(format!("Failed to create projects directory: {}", e)))?;
     }
     
     Ok(projects_dir)
 }
 
 /// Generate a project filename from the current timestamp
-fn generate_project_filename() -> String {
-    let now = Utc::now();
-    format!("Recording {}.osp", now.format("%Y-%m-%d %H-%M-%S"))
+/// Name a new project with the configured naming template
+fn project_name_for(manifest: Option<&RecordingManifest>) -> String {
+    let template = naming::settings_path()
+        .and_then(|path| naming::load_settings(&path))
+        .map(|settings| settings.template)
+        .unwrap_or_else(|e| {
+            tracing::warn!("Using the default naming template: {}", e);
+            naming::DEFAULT_NAME_TEMPLATE.to_string()
+        });
+    let recorded_at = manifest
+        .and_then(|m| m.sessions.first())
+        .and_then(|s| chrono::DateTime::from_timestamp_millis(s.unix_start_ms as i64))
+        .map(|t| t.with_timezone(&Local))
+        .unwrap_or_else(Local::now);
+
+    naming::resolve_name(
+        &template,
+        &NamingContext {
+            recorded_at,
+            display: manifest.and_then(|m| m.display_name.clone()),
+            app: manifest.and_then(|m| m.frontmost_app.clone()),
+        },
+    )
 }
 
 /// Create a project from a raw recording bundle and save to default location
 ///
 /// This converts a recording bundle (from /tmp) into a proper project,
 /// copies it to the default projects directory (~/Movies/Open ScreenStudio/),
-/// and returns the project with its saved path.
+/// and returns the project with its saved path. If `template_id` is given, the
+/// template's visual settings are applied to the new project.
 #[tauri::command]
 pub async fn create_project_from_recording(
     state: State<'_, AppState>,
     recording_bundle_path: String,
-) -> Result<(Project, String), String> {
+    template_id: Option<String>,
+) -> AppResult<(Project, String)> {
     let temp_bundle_path = PathBuf::from(&recording_bundle_path);
 
     tracing::info!(
@@ -137,10 +250,10 @@ pub async fn create_project_from_recording(
 
     // Verify the bundle exists
     if !temp_bundle_path.exists() {
-        return Err(format!(
+        return Err(AppError::NotFound(format!(
             "Recording bundle not found: {}",
             recording_bundle_path
-        ));
+        )));
     }
 
     // Find the recording directory (could be "recording" subdirectory or directly in bundle)
@@ -150,25 +263,42 @@ pub async fn create_project_from_recording(
         temp_bundle_path.clone()
     };
 
-    // Verify video file exists
-    let video_path = recording_dir.join("recording-0.mp4");
-    if !video_path.exists() {
-        return Err(format!("Video file not found in bundle: {:?}", video_path));
-    }
-
-    // Get video metadata for duration
-    let video_metadata =
-        crate::commands::recording::get_video_metadata(video_path.to_string_lossy().to_string())
-            .await?;
-
-    let duration_ms = video_metadata.duration_ms;
+    // Locate files via the recording manifest (falls back to naming convention)
+    let manifest = RecordingManifest::read(&recording_dir);
+
+    // Find the video (the webcam recording for camera-only bundles), or the
+    // audio of an audio-only bundle
+    let video_path = resolve_primary_video(&recording_dir, manifest.as_ref(), 0);
+    let media_path = video_path
+        .clone()
+        .or_else(|| resolve_primary_audio(&recording_dir, manifest.as_ref(), 0))
+        .ok_or_else(|| {
+            AppError::NotFound(format!("No video or audio found in bundle: {:?}", recording_dir))
+        })?;
+    let has_screen =
+        resolve_file(&recording_dir, manifest.as_ref(), FileKind::ScreenVideo, 0).is_some();
+
+    // Get the duration (probing a decrypted copy if encrypted)
+    let probe_path = tokio::task::spawn_blocking(move || readable_path(&media_path))
+        .await
+        .map_err(|e| AppError::Project(e.to_string()))??;
+    let duration_ms = if video_path.is_some() {
+        crate::commands::recording::get_video_metadata(probe_path.to_string_lossy().to_string())
+            .await?
+            .duration_ms
+    } else {
+        tokio::task::spawn_blocking(move || media::probe_media(&probe_path))
+            .await
+            .map_err(|e| AppError::Project(e.to_string()))??
+            .duration_ms
+    };
 
     // Check if webcam exists
-    let webcam_path = recording_dir.join("recording-0-webcam.mp4");
-    let has_webcam = webcam_path.exists();
+    let has_webcam =
+        resolve_file(&recording_dir, manifest.as_ref(), FileKind::WebcamVideo, 0).is_some();
 
     // Create default scene with timeline slices
-    let screen_slice = Slice {
+    let full_slice = || Slice {
         id: Uuid::new_v4().to_string(),
         source_start_ms: 0.0,
         source_end_ms: duration_ms,
@@ -177,58 +307,56 @@ pub async fn create_project_from_recording(
         hide_cursor: false,
         disable_cursor_smoothing: false,
     };
+    // Audio-only bundles are edited on the screen track, which plays black
+    let screen_slices = if has_screen || !has_webcam { vec![full_slice()] } else { Vec::new() };
+    let camera_slices = if has_webcam { vec![full_slice()] } else { Vec::new() };
 
-    let camera_slice = if has_webcam {
-        Some(Slice {
-            id: Uuid::new_v4().to_string(),
-            source_start_ms: 0.0,
-            source_end_ms: duration_ms,
-            time_scale: 1.0,
-            volume: 1.0,
-            hide_cursor: false,
-            disable_cursor_smoothing: false,
-        })
-    } else {
-        None
-    };
-
-    // Create default layout
+    // Create default layout (camera-only bundles show the webcam full frame,
+    // audio-only ones just the background)
     let default_layout = Layout {
         id: Uuid::new_v4().to_string(),
         start_time: 0.0,
         end_time: duration_ms,
-        layout_type: if has_webcam {
-            LayoutType::ScreenWithCamera
-        } else {
-            LayoutType::ScreenOnly
+        layout_type: match (has_screen, has_webcam) {
+            (false, true) => LayoutType::CameraOnly,
+            (false, false) => LayoutType::ScreenOnly,
+            (true, true) => LayoutType::ScreenWithCamera,
+            (true, false) => LayoutType::ScreenOnly,
         },
-        camera_size: 0.25,
+        camera_size: if has_screen { 0.25 } else { 1.0 },
         camera_position: Point { x: 0.95, y: 0.95 },
     };
 
-    // Combine screen and camera slices into a single slices list
-    let mut slices = vec![screen_slice];
-    if let Some(cam_slice) = camera_slice {
-        slices.push(cam_slice);
-    }
-
     let scene = Scene {
         id: Uuid::new_v4().to_string(),
         name: "Main".to_string(),
         scene_type: SceneType::Recording,
         session_index: 0,
-        slices,
+        slices: Vec::new(),
+        screen_slices,
+        camera_slices,
         zoom_ranges: Vec::new(),
         layouts: vec![default_layout],
+        muted: TrackMutes::default(),
     };
 
-    // Generate project name from timestamp
-    let project_name = generate_project_filename().replace(".osp", "");
+    // Name the project from the naming template
+    let project_name = project_name_for(manifest.as_ref());
 
     // Create the project
     let mut config = ProjectConfig::default();
     config.recording_range = (0.0, duration_ms);
     config.camera.enabled = has_webcam;
+    if let Some(offset_ms) = manifest.as_ref().and_then(|m| m.microphone_offset_ms) {
+        config.audio.microphone_offset_ms = offset_ms;
+    }
+
+    if let Some(template_id) = template_id {
+        let templates_dir = template::templates_dir()?;
+        let template = template::load_template(&templates_dir, &template_id)?;
+        template.apply_to(&mut config);
+        tracing::info!("Applied template '{}' to new project", template.name);
+    }
 
     let project = Project {
         id: Uuid::new_v4().to_string(),
@@ -240,38 +368,26 @@ pub async fn create_project_from_recording(
 
     // Determine the destination path in default projects directory
     let projects_dir = get_projects_directory()?;
-    let dest_path = projects_dir.join(generate_project_filename());
+    let dest_path = naming::unique_path(&projects_dir, &project_name, bundle::BUNDLE_EXTENSION);
 
     tracing::info!("Saving project to: {:?}", dest_path);
 
     // Create destination directory
     if !dest_path.exists() {
         fs::create_dir_all(&dest_path)
-            .map_err(|e| format!("Failed to create project directory: {}", e))?;
+            .map_err(|e| AppError::Project(format!("Failed to create project directory: {}", e)))?;
     }
 
     // Copy all files from temp bundle to destination
     copy_dir_contents(&temp_bundle_path, &dest_path)
-        .map_err(|e| format!("Failed to copy bundle: {}", e))?;
+        .map_err(|e| AppError::Project(format!("Failed to copy bundle: {}", e)))?;
 
     // Write project.json to the destination
-    bundle::write_project(&project, &dest_path)
-        .map_err(|e| format!("Failed to write project: {}", e))?;
+    bundle::write_project(&project, &dest_path)?;
+    refresh_presentation(&state, &dest_path, &project).await;
 
-    // Store in app state - project is now saved
-    {
-        let mut current_project = state.current_project.lock().await;
-        *current_project = Some(project.clone());
-    }
-    {
-        let mut saved_path = state.current_project_path.lock().await;
-        *saved_path = Some(dest_path.clone());
-    }
-    {
-        // Clear temp path since we're now at a permanent location
-        let mut temp_path = state.temp_bundle_path.lock().await;
-        *temp_path = None;
-    }
+    // Store in app state - project is now saved at a permanent location
+    state.set_current(project.clone(), &dest_path, false).await;
 
     let dest_path_str = dest_path.to_string_lossy().to_string();
     tracing::info!("Project '{}' saved to {}", project.name, dest_path_str);
@@ -286,13 +402,14 @@ pub async fn create_project_from_recording(
 pub async fn save_project_to_path(
     state: State<'_, AppState>,
     dest_path: String,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let dest = PathBuf::from(&dest_path);
+    state.ensure_writable(Some(&dest)).await?;
 
     // Get current project
     let project = {
         let proj = state.current_project.lock().await;
-        proj.clone().ok_or("No project currently open")?
+        proj.clone().ok_or_else(|| AppError::Project("No project currently open".to_string()))?
     };
 
     // Determine source path (either temp or previously saved location)
@@ -301,7 +418,7 @@ pub async fn save_project_to_path(
         let temp = state.temp_bundle_path.lock().await;
         saved.clone().or_else(|| temp.clone())
     }
-    .ok_or("No source bundle path found")?;
+    .ok_or_else(|| AppError::Project("No source bundle path found".to_string()))?;
 
     tracing::info!(
         "Saving project to {:?} (source: {:?})",
@@ -310,34 +427,26 @@ pub async fn save_project_to_path(
     );
 
     // If destination is different from source, copy the entire bundle
-    if dest != source_path {
+    if paths::canonical(&dest) != paths::canonical(&source_path) {
         // Create destination directory
         if !dest.exists() {
             fs::create_dir_all(&dest)
-                .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+                .map_err(|e| AppError::Project(format!("Failed to create destination directory: {}", e)))?;
         }
 
         // Copy all files from source to destination
         copy_dir_contents(&source_path, &dest)
-            .map_err(|e| format!("Failed to copy bundle: {}", e))?;
+            .map_err(|e| AppError::Project(format!("Failed to copy bundle: {}", e)))?;
     }
 
     // Update project.json with current project state and timestamp
     // Note: We don't have an updated_at field in Project, but we update meta.json via bundle::write_project
-    bundle::write_project(&project, &dest)
-        .map_err(|e| format!("Failed to write project: {}", e))?;
+    bundle::write_project(&project, &dest)?;
+    refresh_presentation(&state, &dest, &project).await;
 
-    // Update app state with new saved path
-    {
-        let mut saved_path = state.current_project_path.lock().await;
-        *saved_path = Some(dest.clone());
-    }
-
-    // Clear temp path since we now have a saved location
-    {
-        let mut temp_path = state.temp_bundle_path.lock().await;
-        *temp_path = None;
-    }
+    // Update app state with the new saved location. A copy of a read-only
+    // project is the user's own to edit.
+    state.set_current(project, &dest, false).await;
 
     tracing::info!("Project saved successfully to {:?}", dest);
 
@@ -347,23 +456,28 @@ pub async fn save_project_to_path(
 /// Auto-save the current project in place
 /// This is called automatically after any edit - no user action required
 #[tauri::command]
-pub async fn auto_save_project(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn auto_save_project(state: State<'_, AppState>) -> AppResult<()> {
+    state.ensure_writable(None).await?;
+
     let saved_path = {
         let path = state.current_project_path.lock().await;
         path.clone()
     };
 
-    let saved_path = saved_path.ok_or("Project has no saved path - cannot auto-save")?;
+    let saved_path = saved_path.ok_or_else(|| {
+        AppError::Project("Project has no saved path - cannot auto-save".to_string())
+    })?;
 
     let project = {
         let proj = state.current_project.lock().await;
-        proj.clone().ok_or("No project currently open")?
+        proj.clone().ok_or_else(|| AppError::Project("No project currently open".to_string()))?
     };
 
     tracing::debug!("Auto-saving project to {:?}", saved_path);
 
-    bundle::write_project(&project, &saved_path)
-        .map_err(|e| format!("Failed to auto-save project: {}", e))?;
+    record_snapshot(&state, &saved_path, &project).await;
+    bundle::write_project(&project, &saved_path)?;
+    refresh_presentation(&state, &saved_path, &project).await;
 
     tracing::debug!("Project auto-saved successfully");
 
@@ -375,12 +489,320 @@ pub async fn auto_save_project(state: State<'_, AppState>) -> Result<(), String>
 pub async fn update_project(
     state: State<'_, AppState>,
     project: Project,
-) -> Result<(), String> {
+) -> AppResult<()> {
+    state.ensure_writable(None).await?;
     let mut current = state.current_project.lock().await;
     *current = Some(project);
     Ok(())
 }
 
+/// Save a project's visual settings as a named template
+#[tauri::command]
+pub async fn save_project_template(
+    name: String,
+    config: ProjectConfig,
+) -> AppResult<ProjectTemplate> {
+    let templates_dir = template::templates_dir()?;
+    let template = ProjectTemplate::from_config(name, &config);
+
+    template::save_template(&templates_dir, &template)?;
+
+    tracing::info!("Saved project template '{}' ({})", template.name, template.id);
+    Ok(template)
+}
+
+/// List saved project templates
+#[tauri::command]
+pub async fn list_project_templates() -> AppResult<Vec<ProjectTemplate>> {
+    let templates_dir = template::templates_dir()?;
+    Ok(template::list_templates(&templates_dir)?)
+}
+
+/// Delete a saved project template
+#[tauri::command]
+pub async fn delete_project_template(template_id: String) -> AppResult<()> {
+    let templates_dir = template::templates_dir()?;
+    Ok(template::delete_template(&templates_dir, &template_id)?)
+}
+
+/// List installed cursor packs
+#[tauri::command]
+pub async fn list_cursor_packs() -> AppResult<Vec<CursorPackInfo>> {
+    let packs_dir = cursor_pack::packs_dir()?;
+    Ok(cursor_pack::list_packs(&packs_dir)?)
+}
+
+/// Install a cursor pack from a directory containing a `pack.json`
+#[tauri::command]
+pub async fn install_cursor_pack(path: String) -> AppResult<CursorPackInfo> {
+    let packs_dir = cursor_pack::packs_dir()?;
+    Ok(cursor_pack::install_pack(&packs_dir, Path::new(&path))?)
+}
+
+/// Apply a template to the current project and return the updated project
+#[tauri::command]
+pub async fn apply_project_template(
+    state: State<'_, AppState>,
+    template_id: String,
+) -> AppResult<Project> {
+    state.ensure_writable(None).await?;
+    let templates_dir = template::templates_dir()?;
+    let template = template::load_template(&templates_dir, &template_id)?;
+
+    let mut current = state.current_project.lock().await;
+    let project = current.as_mut().ok_or_else(|| AppError::Project("No project currently open".to_string()))?;
+    template.apply_to(&mut project.config);
+
+    tracing::info!("Applied template '{}' to project {}", template.name, project.id);
+    Ok(project.clone())
+}
+
+/// Replace the webcam or microphone track of a project with an external file
+///
+/// The file is copied into the bundle and registered in the recording manifest.
+/// Camera slices are clamped to the new file's duration; for microphone
+/// replacements `offset_ms` is stored so exports line the new take up with
+/// the screen recording.
+#[tauri::command]
+pub async fn replace_project_media(
+    state: State<'_, AppState>,
+    project_dir: String,
+    track: ReplaceableTrack,
+    source_path: String,
+    offset_ms: Option<f64>,
+    session_index: Option<usize>,
+) -> AppResult<(Project, ImportedMedia)> {
+    let bundle_path = PathBuf::from(&project_dir);
+    state.ensure_writable(Some(&bundle_path)).await?;
+    let session_index = session_index.unwrap_or(0);
+
+    let mut imported = media::import_track(
+        &bundle_path,
+        track,
+        &PathBuf::from(&source_path),
+        session_index,
+    )
+    ?;
+
+    // Without an explicit offset, line an imported voiceover up with the
+    // scratch microphone recorded alongside the screen
+    if track == ReplaceableTrack::Microphone && offset_ms.is_none() {
+        let scratch = bundle_path
+            .join("recording")
+            .join(FileKind::MicAudio.conventional_name(session_index));
+        if scratch.is_file() {
+            let take = PathBuf::from(&imported.path);
+            let sync = tokio::task::spawn_blocking(move || {
+                let scratch = readable_path(&scratch)?;
+                audio_sync::sync_audio(&scratch, &take, &AudioSyncConfig::default())
+            })
+            .await
+            .map_err(|e| AppError::Project(e.to_string()))?;
+
+            match sync {
+                Ok(sync) => imported.sync = Some(sync),
+                Err(e) => tracing::warn!("Could not align imported audio: {}", e),
+            }
+        }
+    }
+
+    let mut project = bundle::read_project(&bundle_path)?;
+
+    match track {
+        ReplaceableTrack::Webcam => {
+            for scene in project
+                .scenes
+                .iter_mut()
+                .filter(|s| s.session_index == session_index)
+            {
+                if scene.camera_slices.is_empty() {
+                    scene.camera_slices = scene
+                        .screen_slices
+                        .iter()
+                        .map(|s| Slice {
+                            id: Uuid::new_v4().to_string(),
+                            ..s.clone()
+                        })
+                        .collect();
+                }
+                media::fit_slices_to_duration(&mut scene.camera_slices, imported.info.duration_ms);
+            }
+            project.config.camera.enabled = true;
+        }
+        ReplaceableTrack::Microphone => {
+            let synced = imported
+                .sync
+                .filter(|sync| sync.is_confident())
+                .map(|sync| sync.offset_ms);
+            project.config.audio.microphone_offset_ms = offset_ms.or(synced).unwrap_or(0.0);
+        }
+    }
+
+    bundle::write_project(&project, &bundle_path)?;
+    refresh_presentation(&state, &bundle_path, &project).await;
+
+    // Keep the in-memory project in sync if it's the one being edited
+    if state.is_current(&bundle_path).await {
+        *state.current_project.lock().await = Some(project.clone());
+    }
+
+    tracing::info!("Replaced {:?} track of {}", track, project_dir);
+
+    Ok((project, imported))
+}
+
+/// Attach a `.cube` LUT to a project, applied to the screen and/or camera
+/// track at export
+///
+/// The file is validated and copied into the bundle's assets. Clear the
+/// project's `colorLut` to stop applying it.
+#[tauri::command]
+pub async fn attach_color_lut(
+    state: State<'_, AppState>,
+    project_dir: String,
+    lut_path: String,
+    apply_to_screen: Option<bool>,
+    apply_to_camera: Option<bool>,
+) -> AppResult<Project> {
+    let bundle_path = PathBuf::from(&project_dir);
+    state.ensure_writable(Some(&bundle_path)).await?;
+
+    let file = lut::attach_lut(&bundle_path, &PathBuf::from(&lut_path))?;
+    let mut project = bundle::read_project(&bundle_path)?;
+    project.config.color_lut = Some(ColorLut {
+        file,
+        apply_to_screen: apply_to_screen.unwrap_or(true),
+        apply_to_camera: apply_to_camera.unwrap_or(true),
+    });
+    bundle::write_project(&project, &bundle_path)?;
+
+    if state.is_current(&bundle_path).await {
+        *state.current_project.lock().await = Some(project.clone());
+    }
+
+    tracing::info!("Attached color LUT {} to {}", lut_path, project_dir);
+
+    Ok(project)
+}
+
+/// Before `project` is saved to a bundle, snapshot the project it replaces
+/// into the bundle's history when one is due. Snapshots are a backup, so
+/// failing to write one doesn't fail the save.
+async fn record_snapshot(state: &AppState, bundle_path: &Path, project: &Project) {
+    if state.ensure_writable(Some(bundle_path)).await.is_err() {
+        return;
+    }
+    // A new bundle has nothing to keep
+    let Ok(saved) = bundle::read_project(bundle_path) else {
+        return;
+    };
+    match history::snapshot_if_due(bundle_path, &saved, project, Utc::now().timestamp_millis()) {
+        Ok(Some(snapshot)) => tracing::debug!("Saved project snapshot {}", snapshot.timestamp),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to snapshot project in {:?}: {}", bundle_path, e),
+    }
+}
+
+/// List the backup snapshots of the current project, newest first
+#[tauri::command]
+pub async fn list_project_snapshots(state: State<'_, AppState>) -> AppResult<Vec<Snapshot>> {
+    let Some(bundle_path) = state.current_project_path.lock().await.clone() else {
+        return Ok(Vec::new());
+    };
+    Ok(history::list_snapshots(&bundle_path)?)
+}
+
+/// Roll the current project back to a snapshot. The state being replaced is
+/// snapshotted first, so a restore can itself be undone.
+#[tauri::command]
+pub async fn restore_snapshot(state: State<'_, AppState>, timestamp: i64) -> AppResult<Project> {
+    state.ensure_writable(None).await?;
+    let bundle_path = state
+        .current_project_path
+        .lock()
+        .await
+        .clone()
+        .ok_or_else(|| AppError::Project("Project has no saved path".to_string()))?;
+
+    let restored = history::read_snapshot(&bundle_path, timestamp)?;
+    let mut current = state.current_project.lock().await;
+    if let Some(project) = current.as_ref() {
+        history::write_snapshot(&bundle_path, project, Utc::now().timestamp_millis())?;
+    }
+    bundle::write_project(&restored, &bundle_path)?;
+    *current = Some(restored.clone());
+
+    tracing::info!("Restored project snapshot {} in {:?}", timestamp, bundle_path);
+
+    Ok(restored)
+}
+
+/// Update a bundle's Finder/Explorer thumbnail and icon in the background.
+/// Failures only cost the preview, so they're logged rather than returned.
+/// Bundles open read-only are left as they are.
+async fn refresh_presentation(state: &AppState, bundle_path: &Path, project: &Project) {
+    if state.ensure_writable(Some(bundle_path)).await.is_err() {
+        return;
+    }
+    let bundle_path = bundle_path.to_path_buf();
+    let name = project.name.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = presentation::refresh_bundle_presentation(&bundle_path, &name) {
+            tracing::warn!("Failed to refresh presentation of {:?}: {}", bundle_path, e);
+        }
+    });
+}
+
+/// Get the markers saved in a project bundle
+#[tauri::command]
+pub async fn get_markers(project_dir: String) -> AppResult<Vec<Marker>> {
+    Ok(bundle::read_markers(&PathBuf::from(project_dir))?)
+}
+
+/// Replace the markers saved in a project bundle
+#[tauri::command]
+pub async fn save_markers(
+    state: State<'_, AppState>,
+    project_dir: String,
+    markers: Vec<Marker>,
+) -> AppResult<()> {
+    let bundle_path = PathBuf::from(project_dir);
+    state.ensure_writable(Some(&bundle_path)).await?;
+    Ok(bundle::write_markers(&markers, &bundle_path)?)
+}
+
+/// Get the editor view state saved in a project bundle
+#[tauri::command]
+pub async fn get_ui_state(project_dir: String) -> AppResult<UiState> {
+    Ok(bundle::read_ui_state(&PathBuf::from(project_dir))?)
+}
+
+/// Save the editor view state to a project bundle
+///
+/// Kept out of project.json, so this doesn't mark the project as modified.
+#[tauri::command]
+pub async fn save_ui_state(
+    state: State<'_, AppState>,
+    project_dir: String,
+    ui_state: UiState,
+) -> AppResult<()> {
+    let bundle_path = PathBuf::from(project_dir);
+    state.ensure_writable(Some(&bundle_path)).await?;
+    Ok(bundle::write_ui_state(&ui_state, &bundle_path)?)
+}
+
+/// Get the template new projects are named with
+#[tauri::command]
+pub fn get_naming_settings() -> AppResult<NamingSettings> {
+    Ok(naming::load_settings(&naming::settings_path()?)?)
+}
+
+/// Save the template new projects are named with
+#[tauri::command]
+pub fn set_naming_settings(settings: NamingSettings) -> AppResult<()> {
+    Ok(naming::save_settings(&naming::settings_path()?, &settings)?)
+}
+
 /// Helper function to recursively copy directory contents
 fn copy_dir_contents(src: &PathBuf, dst: &PathBuf) -> std::io::Result<()> {
     if !dst.exists() {
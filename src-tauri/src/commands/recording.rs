@@ -1,35 +1,233 @@
 //! Recording-related Tauri commands
 
+use crate::capture::annotation::{Annotation, AnnotationInput, AnnotationSink};
 use crate::capture::audio::get_audio_input_devices;
-use crate::capture::traits::{AudioDeviceInfo, CameraInfo, DisplayInfo, has_screen_recording_permission, request_screen_recording_permission};
+use crate::capture::camera_preview::CameraPreview;
+use crate::capture::cues::SystemCuePlayer;
+use crate::capture::frame::{ContentRect, WindowResizeEvent};
+use crate::capture::input::{KeyPress, MouseClick, MouseMove};
+use crate::capture::screenshot::{FramedScreenshot, ScreenshotResult};
+use crate::capture::traits::{AudioCaptureApp, AudioDeviceInfo, CameraFormat, CameraInfo, DisplayInfo, MediaPermission, PermissionStatus, SystemAudioSupport, WindowInfo, display_under_cursor, frontmost_app_name, has_screen_recording_permission, request_screen_recording_permission};
+use crate::commands::project::{get_projects_directory, AppState};
+use crate::commands::window::{
+    close_countdown_window, open_countdown_window, set_windows_excluded_from_capture,
+};
+use crate::export::sessions::blank_video_for;
+use crate::export::ExportError;
+use crate::project::bundle::read_project;
+use crate::project::encryption::{encrypt_media_in_place, readable_path, BundleKey};
+use crate::project::paths;
+use crate::project::schema::{Marker, ProjectConfig};
 use crate::recorder::state::{RecordingConfig, RecordingResult as RecordingOutput, RecordingState};
-use crate::recorder::RecordingCoordinator;
+use crate::recorder::manifest::{
+    resolve_file, resolve_primary_audio, resolve_primary_video, FileKind, RecordingManifest,
+};
+use crate::recorder::calibration::{calibration_path, load_calibration, run_calibration, save_calibration, CalibrationResult, CalibrationStore};
+use crate::recorder::self_test::{run_self_test, SelfTestReport};
+use crate::recorder::coordinator::{watch_display_changes, AutoStopReason, RecordingEvent};
+use crate::recorder::permissions::{Permission, PermissionReport};
+use crate::recorder::recovery::{self, RecoverableRecording};
+use crate::recorder::segment::LivePreview;
+use crate::recorder::sessions::SessionList;
+use crate::recorder::{ChannelContext, ChannelRegistry, RecordingCoordinator};
+use crate::utils::error::{AppError, AppResult};
+use parking_lot::Mutex as ParkingMutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tauri::State;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tauri::ipc::{Channel, InvokeResponseBody, Response};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::time::MissedTickBehavior;
 
 /// Application state for recording
 pub struct RecorderState {
     pub coordinator: Arc<Mutex<RecordingCoordinator>>,
+    /// Entry point for annotations drawn on screen while recording
+    pub annotations: AnnotationSink,
+    /// Channels that can be recorded on this platform
+    pub channels: ChannelRegistry,
+    /// Camera preview shown while setting up a recording
+    pub camera_preview: ParkingMutex<Option<CameraPreview>>,
+    /// Cancels the countdown before a recording starts, while one runs
+    pub countdown: ParkingMutex<Option<oneshot::Sender<()>>>,
+    /// Shortcut registered for adding markers, while recording
+    pub marker_shortcut: ParkingMutex<Option<Shortcut>>,
+    /// Set while a capture self-test or A/V calibration holds the devices.
+    /// It's set and checked with the coordinator locked, so a recording can't
+    /// start while one runs.
+    pub self_test_running: AtomicBool,
 }
 
 impl Default for RecorderState {
     fn default() -> Self {
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.set_cue_player(Arc::new(SystemCuePlayer));
         Self {
-            coordinator: Arc::new(Mutex::new(RecordingCoordinator::new())),
+            coordinator: Arc::new(Mutex::new(coordinator)),
+            annotations: AnnotationSink::new(),
+            channels: channel_registry(),
+            camera_preview: ParkingMutex::new(None),
+            countdown: ParkingMutex::new(None),
+            marker_shortcut: ParkingMutex::new(None),
+            self_test_running: AtomicBool::new(false),
         }
     }
 }
 
+/// How often `recording-event` progress updates are sent while recording
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Forward the coordinator's events to the frontend as `recording-event`,
+/// adding progress updates while recording, for as long as the app runs.
+/// Channel health and the automatic stop (maximum duration, stop time) are
+/// checked on the same tick.
+pub fn forward_recording_events(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let coordinator = app.state::<RecorderState>().coordinator.clone();
+        let mut events = coordinator.lock().await.subscribe();
+        let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        // Recording or paused; a scheduled stop applies to both
+        let mut active = false;
+        loop {
+            let event = tokio::select! {
+                received = events.recv() => match received {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = ticker.tick(), if active => {
+                    // Skip the update rather than wait while a command holds the
+                    // coordinator (e.g. stopping)
+                    let Ok(mut coordinator) = coordinator.try_lock() else {
+                        continue;
+                    };
+                    if let Some(reason) = coordinator.auto_stop_reason(chrono::Utc::now()) {
+                        active = false;
+                        auto_stop(app.clone(), reason);
+                        continue;
+                    }
+                    if coordinator.state() != RecordingState::Recording {
+                        continue;
+                    }
+                    // Changes are sent as events of their own
+                    coordinator.check_channel_health();
+                    RecordingEvent::Progress(coordinator.duration_ms())
+                }
+            };
+            match event {
+                RecordingEvent::Started | RecordingEvent::Resumed => active = true,
+                RecordingEvent::Stopped => active = false,
+                _ => {}
+            }
+            let emitted = match &event {
+                RecordingEvent::MicLevel(level) => app.emit("mic-level", level),
+                _ => app.emit("recording-event", &event),
+            };
+            if let Err(e) = emitted {
+                tracing::warn!("Failed to emit recording event: {}", e);
+            }
+        }
+    });
+}
+
+/// Stop a recording that reached its maximum duration or stop time, as
+/// `stop_recording` would, and report it with an `AutoStopped` event
+fn auto_stop(app: AppHandle, reason: AutoStopReason) {
+    tauri::async_runtime::spawn(async move {
+        tracing::info!("Stopping recording automatically: {:?}", reason);
+        match stop_recording(app.clone(), app.state()).await {
+            Ok(result) => {
+                let coordinator = app.state::<RecorderState>().coordinator.clone();
+                coordinator.lock().await.report_auto_stop(reason, result);
+            }
+            Err(e) => tracing::error!("Failed to stop recording automatically: {}", e),
+        }
+    });
+}
+
+/// Label of markers added with the marker shortcut
+const SHORTCUT_MARKER_LABEL: &str = "Marker";
+
+/// Register the marker shortcut for a recording, replacing the previous
+/// one, or just unregister it when `accelerator` is None
+fn set_marker_shortcut(app: &AppHandle, state: &RecorderState, accelerator: Option<&str>) {
+    let shortcuts = app.global_shortcut();
+    if let Some(previous) = state.marker_shortcut.lock().take() {
+        if let Err(e) = shortcuts.unregister(previous) {
+            tracing::warn!("Failed to unregister marker shortcut: {}", e);
+        }
+    }
+    let Some(accelerator) = accelerator else {
+        return;
+    };
+
+    let registered = accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| e.to_string())
+        .and_then(|shortcut| {
+            shortcuts.register(shortcut).map_err(|e| e.to_string())?;
+            Ok(shortcut)
+        });
+    match registered {
+        Ok(shortcut) => *state.marker_shortcut.lock() = Some(shortcut),
+        // Another app may own it; markers can still be added from the UI
+        Err(e) => tracing::warn!("Failed to register marker shortcut {}: {}", accelerator, e),
+    }
+}
+
+/// Global shortcut handler: the marker shortcut marks the current moment
+pub fn on_global_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    let state = app.state::<RecorderState>();
+    if event.state() != ShortcutState::Pressed
+        || state.marker_shortcut.lock().as_ref() != Some(shortcut)
+    {
+        return;
+    }
+
+    let coordinator = state.coordinator.clone();
+    tauri::async_runtime::spawn(async move {
+        let added = coordinator
+            .lock()
+            .await
+            .add_marker(SHORTCUT_MARKER_LABEL.to_string());
+        if let Err(e) = added {
+            tracing::warn!("Failed to add marker from shortcut: {}", e);
+        }
+    });
+}
+
+/// Channels to record with: the platform's, or synthetic ones in mock mode
+fn channel_registry() -> ChannelRegistry {
+    let mut registry = crate::capture::channels::platform_registry();
+    if crate::capture::mock::is_enabled() {
+        tracing::info!("Mock capture enabled, recording synthetic sources");
+        crate::capture::mock::register_mock_channels(&mut registry);
+    }
+    registry
+}
+
 /// Get list of available audio input devices (microphones)
 #[tauri::command]
-pub async fn get_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
+pub async fn get_audio_devices() -> AppResult<Vec<AudioDeviceInfo>> {
     Ok(get_audio_input_devices())
 }
 
+/// Get the output devices system audio can be recorded from (empty where
+/// the device can't be chosen)
+#[tauri::command]
+pub async fn get_system_audio_devices() -> AppResult<Vec<AudioDeviceInfo>> {
+    tokio::task::spawn_blocking(crate::capture::traits::get_system_audio_devices)
+        .await
+        .map_err(|e| AppError::Recording(e.to_string()))
+}
+
 /// Get list of available cameras/webcams
 #[tauri::command]
-pub async fn get_cameras() -> Result<Vec<CameraInfo>, String> {
+pub async fn get_cameras() -> AppResult<Vec<CameraInfo>> {
     #[cfg(target_os = "macos")]
     {
         Ok(crate::capture::macos::webcam::get_cameras())
@@ -47,9 +245,51 @@ pub async fn get_cameras() -> Result<Vec<CameraInfo>, String> {
     }
 }
 
+/// Stream the camera to `on_frame` as JPEG frames (raw bytes, at most
+/// `PREVIEW_MAX_WIDTH` wide) until `stop_camera_preview` is called or a
+/// recording starts. Replaces a preview that's already running. `format` is
+/// the webcam format chosen for recording, if any.
+#[tauri::command]
+pub async fn start_camera_preview(
+    state: State<'_, RecorderState>,
+    device_id: Option<String>,
+    format: Option<CameraFormat>,
+    on_frame: Channel<InvokeResponseBody>,
+) -> AppResult<()> {
+    // Release the camera first; it may be the one being opened again
+    stop_preview(&state).await;
+    let preview = tokio::task::spawn_blocking(move || {
+        CameraPreview::start(device_id, format.as_ref(), move |jpeg| {
+            // Stops the preview once the webview drops the channel
+            on_frame.send(InvokeResponseBody::Raw(jpeg)).is_ok()
+        })
+    })
+    .await
+    .map_err(|e| AppError::Recording(e.to_string()))??;
+    if let Some(replaced) = state.camera_preview.lock().replace(preview) {
+        tokio::task::spawn_blocking(move || replaced.stop());
+    }
+    Ok(())
+}
+
+/// Stop the camera preview and release the camera
+#[tauri::command]
+pub async fn stop_camera_preview(state: State<'_, RecorderState>) -> AppResult<()> {
+    stop_preview(&state).await;
+    Ok(())
+}
+
+/// Stop the camera preview if one is running, once the camera is released
+async fn stop_preview(state: &RecorderState) {
+    let preview = state.camera_preview.lock().take();
+    if let Some(preview) = preview {
+        let _ = tokio::task::spawn_blocking(move || preview.stop()).await;
+    }
+}
+
 /// Check if camera permission is granted
 #[tauri::command]
-pub async fn check_camera_permission() -> Result<bool, String> {
+pub async fn check_camera_permission() -> AppResult<bool> {
     #[cfg(target_os = "macos")]
     {
         Ok(crate::capture::macos::permissions::has_camera_permission())
@@ -69,10 +309,13 @@ pub async fn check_camera_permission() -> Result<bool, String> {
 
 /// Request camera permission
 #[tauri::command]
-pub async fn request_camera_permission() -> Result<bool, String> {
+pub async fn request_camera_permission() -> AppResult<bool> {
     #[cfg(target_os = "macos")]
     {
-        Ok(crate::capture::macos::permissions::request_camera_permission())
+        // Waits for the user to answer the system prompt
+        tokio::task::spawn_blocking(crate::capture::macos::permissions::request_camera_permission)
+            .await
+            .map_err(|e| AppError::Recording(e.to_string()))
     }
     
     #[cfg(target_os = "windows")]
@@ -86,173 +329,582 @@ pub async fn request_camera_permission() -> Result<bool, String> {
     }
 }
 
-/// Check if system audio capture is available
+/// Check if system audio capture is available, and if not, why
 #[tauri::command]
-pub async fn check_system_audio_available() -> Result<bool, String> {
+pub async fn check_system_audio_available() -> AppResult<SystemAudioSupport> {
+    tokio::task::spawn_blocking(crate::capture::traits::system_audio_support)
+        .await
+        .map_err(|e| AppError::Recording(e.to_string()))
+}
+
+/// Get the running apps whose audio can be recorded on its own, for the
+/// `audioAppFilter` recording setting
+#[tauri::command]
+pub async fn get_audio_capture_apps() -> AppResult<Vec<AudioCaptureApp>> {
+    tokio::task::spawn_blocking(crate::capture::traits::get_audio_capture_apps)
+        .await
+        .map_err(|e| AppError::Recording(e.to_string()))
+}
+
+/// Get list of available displays
+#[tauri::command]
+pub async fn get_displays() -> AppResult<Vec<DisplayInfo>> {
+    if crate::capture::mock::is_enabled() {
+        return Ok(vec![crate::capture::mock::mock_display()]);
+    }
+    
     #[cfg(target_os = "macos")]
     {
-        Ok(crate::capture::macos::system_audio::is_system_audio_available())
+        Ok(crate::capture::macos::screen::get_displays())
     }
     
     #[cfg(target_os = "windows")]
     {
-        // Windows WASAPI loopback is generally available
-        Ok(true)
+        Ok(crate::capture::windows::screen::get_displays())
     }
     
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(target_os = "linux")]
     {
-        Ok(false)
+        Ok(crate::capture::linux::screen::get_displays())
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        Ok(vec![])
     }
 }
 
-/// Get list of available displays
+/// Get list of windows that can be captured
 #[tauri::command]
-pub async fn get_displays() -> Result<Vec<DisplayInfo>, String> {
+pub async fn get_windows() -> AppResult<Vec<WindowInfo>> {
     #[cfg(target_os = "macos")]
     {
-        Ok(crate::capture::macos::screen::get_displays())
-    }
-    
-    #[cfg(target_os = "windows")]
-    {
-        Ok(crate::capture::windows::screen::get_displays())
+        Ok(crate::capture::macos::window::get_windows())
     }
     
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(not(target_os = "macos"))]
     {
         Ok(vec![])
     }
 }
 
+/// Check if accessibility permission, needed for input tracking, is granted
+#[tauri::command]
+pub async fn check_accessibility_permission() -> AppResult<bool> {
+    Ok(crate::capture::traits::has_accessibility_permission())
+}
+
+/// Request accessibility permission, showing the system prompt if needed
+#[tauri::command]
+pub async fn request_accessibility_permission() -> AppResult<bool> {
+    Ok(crate::capture::traits::request_accessibility_permission())
+}
+
+/// Current camera or microphone permission: granted, denied or
+/// undetermined (not asked yet)
+#[tauri::command]
+pub async fn check_media_permission(kind: MediaPermission) -> AppResult<PermissionStatus> {
+    Ok(crate::capture::traits::media_permission_status(kind))
+}
+
+/// Ask for camera or microphone access, showing the system prompt if the
+/// user hasn't decided yet
+#[tauri::command]
+pub async fn request_media_permission(kind: MediaPermission) -> AppResult<PermissionStatus> {
+    tokio::task::spawn_blocking(move || crate::capture::traits::request_media_permission(kind))
+        .await
+        .map_err(|e| AppError::Recording(e.to_string()))
+}
+
 /// Check if screen recording permission is granted
 #[tauri::command]
-pub async fn check_screen_permission() -> Result<bool, String> {
+pub async fn check_screen_permission() -> AppResult<bool> {
     Ok(has_screen_recording_permission())
 }
 
 /// Request screen recording permission
 #[tauri::command]
-pub async fn request_screen_permission() -> Result<bool, String> {
+pub async fn request_screen_permission() -> AppResult<bool> {
     Ok(request_screen_recording_permission())
 }
 
+fn screen_permission_error() -> AppError {
+    AppError::PermissionDenied(
+        "Screen recording permission not granted. Please allow in System Preferences and try again.".to_string(),
+    )
+}
+
+/// Replace `display_id` with the display under the cursor if requested
+fn resolve_display(config: &mut RecordingConfig) {
+    if !config.display_under_cursor {
+        return;
+    }
+    match display_under_cursor() {
+        Some(display_id) => {
+            tracing::info!("Recording display {} (under cursor)", display_id);
+            config.display_id = display_id;
+        }
+        None => tracing::warn!(
+            "Could not find the display under the cursor, using display {}",
+            config.display_id
+        ),
+    }
+}
+
 /// Start recording
 #[tauri::command]
 pub async fn start_recording(
+    app: AppHandle,
     state: State<'_, RecorderState>,
-    config: RecordingConfig,
-) -> Result<(), String> {
-    // Check permission first
-    if !has_screen_recording_permission() {
-        request_screen_recording_permission();
-        return Err("Screen recording permission not granted. Please allow in System Preferences and try again.".to_string());
+    mut config: RecordingConfig,
+) -> AppResult<PermissionReport> {
+    // Check permissions first; with a degraded start (always, for input
+    // tracking), the sources missing one are left out and reported back
+    let permissions = check_permissions(&config);
+    if !permissions.is_complete() {
+        let allowed = config.allow_degraded_start || !permissions.is_blocking();
+        if !allowed || !permissions.can_degrade {
+            if permissions.is_missing(Permission::ScreenRecording) {
+                request_screen_recording_permission();
+            }
+            return Err(AppError::PermissionDenied(format!(
+                "Recording needs permissions that were refused: {}. \
+                 Please allow them in System Preferences and try again.",
+                permissions.describe()
+            )));
+        }
+        tracing::warn!("Recording without refused permissions: {}", permissions.describe());
+        permissions.degrade(&mut config);
     }
     
+    config.validate().map_err(AppError::InvalidInput)?;
+    resolve_display(&mut config);
+    if config.microphone_offset_ms.is_none() && config.capture_screen && config.capture_microphone {
+        config.microphone_offset_ms = calibrated_offset(config.display_id);
+    }
+    if config.capture_screen && config.display_name.is_none() {
+        config.display_name = get_displays()
+            .await?
+            .into_iter()
+            .find(|d| d.id == config.display_id)
+            .map(|d| d.name);
+    }
+    if config.frontmost_app.is_none() {
+        config.frontmost_app = frontmost_app_name();
+    }
+    
+    // The webcam channel can't open a camera the preview is holding
+    stop_preview(&state).await;
     let mut coordinator = state.coordinator.lock().await;
+    if state.self_test_running.load(Ordering::SeqCst) {
+        return Err(AppError::Busy("A self-test or calibration is running".to_string()));
+    }
     
-    // Clear existing channels and add display capture
     coordinator.clear_channels();
-    
-    #[cfg(target_os = "macos")]
-    {
-        let display_channel = Box::new(crate::capture::macos::screen::DisplayCaptureChannel::new(config.display_id));
-        coordinator.add_channel(display_channel);
+    let context = ChannelContext {
+        config: &config,
+        annotations: Some(&state.annotations),
+    };
+    for channel in state.channels.build(&context) {
+        coordinator.add_channel(channel);
     }
     
-    #[cfg(target_os = "windows")]
-    {
-        let display_channel = Box::new(crate::capture::windows::screen::DisplayCaptureChannel::new(config.display_id));
-        coordinator.add_channel(display_channel);
+    // Exclude the toolbar and overlays before the first frame is captured
+    let exclude_windows = config.capture_screen && config.exclude_app_windows;
+    if exclude_windows {
+        set_windows_excluded_from_capture(&app, true);
     }
     
-    // Add input tracking channel (always-on for MVP)
-    // Note: Windows implementation is currently stubbed.
-    #[cfg(target_os = "macos")]
+    let output_dir = config.output_dir.clone();
+    let marker_shortcut = config.marker_shortcut.clone();
+    let result = coordinator.start(config).await.map_err(AppError::from);
+    if result.is_ok() {
+        set_marker_shortcut(&app, &state, marker_shortcut.as_deref());
+        // Restart or pause capture if the display changes under the recording
+        watch_display_changes(state.coordinator.clone(), coordinator.subscribe());
+        let registered = recovery::index_path()
+            .and_then(|index| recovery::register(&index, Path::new(&output_dir)));
+        if let Err(e) = registered {
+            tracing::warn!("Failed to register recording for crash recovery: {}", e);
+        }
+    } else if exclude_windows {
+        set_windows_excluded_from_capture(&app, false);
+    }
+    result.map(|()| permissions)
+}
+
+/// Longest countdown `start_recording_with_countdown` accepts, in seconds
+const MAX_COUNTDOWN_SECONDS: u32 = 10;
+
+/// `recording-countdown` payload, sent every second of a countdown
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountdownTick {
+    /// Seconds left; 0 as recording starts
+    pub remaining: u32,
+    pub total: u32,
+    /// The countdown was cancelled and recording won't start
+    pub cancelled: bool,
+}
+
+/// Count down for `seconds` in an overlay, then start recording as
+/// `start_recording` does. The overlay and the toolbar follow the
+/// `recording-countdown` events; `cancel_recording_countdown` stops it early.
+#[tauri::command]
+pub async fn start_recording_with_countdown(
+    app: AppHandle,
+    state: State<'_, RecorderState>,
+    config: RecordingConfig,
+    seconds: u32,
+) -> AppResult<PermissionReport> {
+    if seconds > MAX_COUNTDOWN_SECONDS {
+        return Err(AppError::InvalidInput(format!(
+            "Countdown can be at most {} seconds",
+            MAX_COUNTDOWN_SECONDS
+        )));
+    }
+    // Fail now rather than at the end of the countdown
+    config.validate().map_err(AppError::InvalidInput)?;
+    if state.coordinator.lock().await.state() != RecordingState::Idle {
+        return Err(AppError::Busy("Already recording".to_string()));
+    }
+
+    let (cancel_tx, mut cancel_rx) = oneshot::channel();
     {
-        let input_channel = Box::new(crate::capture::InputTrackingChannel::new(config.display_id));
-        coordinator.add_channel(input_channel);
+        let mut countdown = state.countdown.lock();
+        if countdown.is_some() {
+            return Err(AppError::Busy("A countdown is already running".to_string()));
+        }
+        *countdown = Some(cancel_tx);
     }
 
-    // Add microphone channel if enabled
-    if config.capture_microphone {
-        let mic_channel = Box::new(crate::capture::audio::MicrophoneCaptureChannel::new(
-            config.microphone_device_id.clone(),
-        ));
-        coordinator.add_channel(mic_channel);
+    let tick = |remaining: u32, cancelled: bool| {
+        let tick = CountdownTick {
+            remaining,
+            total: seconds,
+            cancelled,
+        };
+        if let Err(e) = app.emit("recording-countdown", &tick) {
+            tracing::warn!("Failed to emit countdown tick: {}", e);
+        }
+    };
+
+    // The toolbar still shows the countdown if the overlay can't open
+    if seconds > 0 {
+        if let Err(e) = open_countdown_window(&app, seconds) {
+            tracing::warn!("Failed to open countdown window: {}", e);
+        }
     }
-    
-    // Add system audio channel if enabled
-    if config.capture_system_audio {
-        #[cfg(target_os = "macos")]
-        {
-            let system_audio_channel = Box::new(crate::capture::macos::system_audio::SystemAudioCaptureChannel::new(config.display_id));
-            coordinator.add_channel(system_audio_channel);
+    let mut cancelled = false;
+    for remaining in (1..=seconds).rev() {
+        tick(remaining, false);
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+            _ = &mut cancel_rx => {
+                cancelled = true;
+                break;
+            }
+        }
+    }
+    state.countdown.lock().take();
+    close_countdown_window(&app);
+
+    if cancelled {
+        tick(0, true);
+        tracing::info!("Recording countdown cancelled");
+        return Err(AppError::Cancelled);
+    }
+    tick(0, false);
+    start_recording(app, state, config).await
+}
+
+/// Stop a running countdown so its recording never starts. Returns whether
+/// there was one.
+#[tauri::command]
+pub async fn cancel_recording_countdown(state: State<'_, RecorderState>) -> AppResult<bool> {
+    let countdown = state.countdown.lock().take();
+    Ok(countdown.is_some_and(|cancel| cancel.send(()).is_ok()))
+}
+
+/// Permissions a recording with `config` would need but were refused, and
+/// whether it could start without them (`allowDegradedStart`)
+#[tauri::command]
+pub async fn check_recording_permissions(config: RecordingConfig) -> AppResult<PermissionReport> {
+    Ok(check_permissions(&config))
+}
+
+/// Look up every permission `config` needs, without prompting
+fn check_permissions(config: &RecordingConfig) -> PermissionReport {
+    let granted_if = |granted: bool| {
+        if granted {
+            PermissionStatus::Granted
+        } else {
+            PermissionStatus::Denied
+        }
+    };
+    PermissionReport::check(config, |permission| match permission {
+        Permission::ScreenRecording => granted_if(has_screen_recording_permission()),
+        Permission::Accessibility => {
+            granted_if(crate::capture::traits::has_accessibility_permission())
+        }
+        Permission::Camera => {
+            crate::capture::traits::media_permission_status(MediaPermission::Camera)
         }
-        
-        #[cfg(target_os = "windows")]
-        {
-            let system_audio_channel = Box::new(crate::capture::windows::system_audio::SystemAudioCaptureChannel::new());
-            coordinator.add_channel(system_audio_channel);
+        Permission::Microphone => {
+            crate::capture::traits::media_permission_status(MediaPermission::Microphone)
         }
+    })
+}
+
+/// Start recording a rectangle of the display. `region` is in the display
+/// coordinates reported by `get_displays`; the rest of `config` is as for
+/// `start_recording`.
+#[tauri::command]
+pub async fn start_region_recording(
+    app: AppHandle,
+    state: State<'_, RecorderState>,
+    mut config: RecordingConfig,
+    region: ContentRect,
+) -> AppResult<PermissionReport> {
+    if !config.capture_screen {
+        return Err(AppError::InvalidInput(
+            "Region recording requires screen capture".to_string(),
+        ));
     }
-    
-    // Add webcam channel if enabled
-    if config.capture_webcam {
-        #[cfg(target_os = "macos")]
-        {
-            // Default to 1280x720 @ 30fps for webcam
-            let webcam_channel = Box::new(crate::capture::macos::webcam::WebcamCaptureChannel::new(
-                config.webcam_device_id.clone(),
-                1280,
-                720,
-                30,
-            ));
-            coordinator.add_channel(webcam_channel);
+    config.region = Some(region);
+    start_recording(app, state, config).await
+}
+
+/// Holds the capture devices for a self-test or calibration, clearing
+/// `self_test_running` when dropped, however the run ends
+struct DeviceTestGuard<'a>(&'a AtomicBool);
+
+impl<'a> DeviceTestGuard<'a> {
+    /// Devices can't be shared with a running recording, and none can start
+    /// until the test is done with them
+    async fn acquire(state: &'a RecorderState, action: &str) -> AppResult<Self> {
+        let coordinator = state.coordinator.lock().await;
+        if coordinator.state() != RecordingState::Idle {
+            return Err(AppError::Busy(format!("Cannot {} while recording", action)));
         }
-        
-        #[cfg(target_os = "windows")]
-        {
-            // TODO: Implement Windows webcam capture
-            tracing::warn!("Webcam capture not yet implemented on Windows");
+        if state.self_test_running.swap(true, Ordering::SeqCst) {
+            return Err(AppError::Busy(
+                "A self-test or calibration is already running".to_string(),
+            ));
         }
+        Ok(Self(&state.self_test_running))
+    }
+}
+
+impl Drop for DeviceTestGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Record a few seconds with the given configuration and report, per channel,
+/// whether frames and audio were actually captured
+#[tauri::command]
+pub async fn run_capture_self_test(
+    state: State<'_, RecorderState>,
+    mut config: RecordingConfig,
+) -> AppResult<SelfTestReport> {
+    if config.capture_screen && !has_screen_recording_permission() {
+        request_screen_recording_permission();
+        return Err(screen_permission_error());
+    }
+    
+    config.validate().map_err(AppError::InvalidInput)?;
+    resolve_display(&mut config);
+    
+    let _devices = DeviceTestGuard::acquire(&state, "run a self-test").await?;
+    
+    let channels = state.channels.build(&ChannelContext {
+        config: &config,
+        annotations: None,
+    });
+    Ok(run_self_test(channels, config).await?)
+}
+
+/// Measure the audio/video offset between a display and the microphone and
+/// save it as the default microphone offset for recordings of that display.
+///
+/// Emits `av-calibration-cue` each time the UI should flash the screen white
+/// and play a beep together.
+#[tauri::command]
+pub async fn run_av_calibration(
+    app: AppHandle,
+    state: State<'_, RecorderState>,
+    mut config: RecordingConfig,
+) -> AppResult<CalibrationResult> {
+    if !has_screen_recording_permission() {
+        request_screen_recording_permission();
+        return Err(screen_permission_error());
     }
     
-    coordinator.start(config).await.map_err(|e| e.to_string())
+    // Only the display and microphone are needed, and the flash has to be seen
+    config.capture_screen = true;
+    config.capture_microphone = true;
+    config.capture_system_audio = false;
+    config.capture_webcam = false;
+    config.track_input = false;
+    config.window_id = None;
+    config.exclude_app_windows = false;
+    config.encrypt_media = false;
+    config.validate().map_err(AppError::InvalidInput)?;
+    resolve_display(&mut config);
+    
+    let _devices = DeviceTestGuard::acquire(&state, "calibrate").await?;
+    
+    let channels = state.channels.build(&ChannelContext {
+        config: &config,
+        annotations: None,
+    });
+    let result = run_calibration(channels, config, |cue| {
+        if let Err(e) = app.emit("av-calibration-cue", cue) {
+            tracing::warn!("Failed to emit calibration cue: {}", e);
+        }
+    })
+    .await?;
+    
+    let path = calibration_path()?;
+    let mut store = load_calibration(&path).unwrap_or_default();
+    store.record(&result);
+    save_calibration(&path, &store)?;
+    
+    Ok(result)
+}
+
+/// Get the saved A/V calibration of every display
+#[tauri::command]
+pub async fn get_av_calibration() -> AppResult<CalibrationStore> {
+    Ok(load_calibration(&calibration_path()?)?)
+}
+
+/// Saved microphone offset for a display, if it has been calibrated
+fn calibrated_offset(display_id: u32) -> Option<f64> {
+    let store = calibration_path()
+        .and_then(|path| load_calibration(&path))
+        .map_err(|e| tracing::warn!("Ignoring A/V calibration: {}", e))
+        .ok()?;
+    store.offset_for(display_id)
 }
 
 /// Stop recording
 #[tauri::command]
 pub async fn stop_recording(
+    app: AppHandle,
     state: State<'_, RecorderState>,
-) -> Result<RecordingOutput, String> {
+) -> AppResult<RecordingOutput> {
     let mut coordinator = state.coordinator.lock().await;
-    coordinator.stop().await.map_err(|e| e.to_string())
+    let encrypt_media = coordinator.config().is_some_and(|c| c.encrypt_media);
+    let result = coordinator.stop().await.map_err(AppError::from);
+    set_windows_excluded_from_capture(&app, false);
+    set_marker_shortcut(&app, &state, None);
+    let result = result?;
+    
+    if encrypt_media {
+        encrypt_recording(&app, &result).await?;
+        // Left behind if encrypting failed, so recovery encrypts it instead
+        if let Some(lock) = coordinator.take_lock() {
+            lock.release();
+        }
+    }
+    
+    Ok(result)
+}
+
+/// Encrypt the media of a finished recording with a new bundle key
+async fn encrypt_recording(app: &AppHandle, result: &RecordingOutput) -> AppResult<()> {
+    let bundle = PathBuf::from(&result.bundle_path);
+    app.state::<AppState>().ensure_writable(Some(&bundle)).await?;
+    let recording_dir = bundle.join("recording");
+    tokio::task::spawn_blocking(move || {
+        let key = BundleKey::create()?;
+        encrypt_media_in_place(&recording_dir, &key)
+    })
+    .await
+    .map_err(|e| AppError::Recording(e.to_string()))??;
+    Ok(())
+}
+
+/// Recordings cut short by a crash or power loss, found from the ones that
+/// were in progress. Meant to be checked once at launch.
+#[tauri::command]
+pub async fn list_recoverable_recordings() -> AppResult<Vec<RecoverableRecording>> {
+    tokio::task::spawn_blocking(|| recovery::list_recoverable(&recovery::index_path()?))
+        .await
+        .map_err(|e| AppError::Recording(e.to_string()))?
+        .map_err(AppError::from)
+}
+
+/// Finalize an interrupted recording so it can be opened like one that was
+/// stopped, encrypting its media if it was meant to be. The result is the
+/// same as `stop_recording`'s.
+#[tauri::command]
+pub async fn recover_recording(
+    projects: State<'_, AppState>,
+    bundle_path: String,
+) -> AppResult<RecordingOutput> {
+    let bundle = PathBuf::from(&bundle_path);
+    projects.ensure_writable(Some(&bundle)).await?;
+    tokio::task::spawn_blocking(move || recovery::recover(&recovery::index_path()?, &bundle))
+        .await
+        .map_err(|e| AppError::Recording(e.to_string()))?
+        .map_err(AppError::from)
+}
+
+/// Encrypt the media of interrupted recordings that were meant to be
+/// encrypted right away, so it doesn't stay readable until the user gets to
+/// them. They stay listed for recovery. Run once at launch.
+pub fn seal_interrupted_recordings() {
+    tauri::async_runtime::spawn_blocking(|| {
+        let index = match recovery::index_path() {
+            Ok(index) => index,
+            Err(e) => return tracing::warn!("No recovery index: {}", e),
+        };
+        let bundles = recovery::list_recoverable(&index).unwrap_or_else(|e| {
+            tracing::warn!("Failed to list interrupted recordings: {}", e);
+            Vec::new()
+        });
+        for recording in bundles {
+            let bundle = PathBuf::from(&recording.bundle_path);
+            match recovery::seal(&bundle) {
+                Ok(0) => {}
+                Ok(count) => {
+                    tracing::info!("Encrypted {} files of interrupted recording {:?}", count, bundle)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to encrypt interrupted recording {:?}: {}", bundle, e)
+                }
+            }
+        }
+    });
 }
 
 /// Pause recording
 #[tauri::command]
 pub async fn pause_recording(
     state: State<'_, RecorderState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut coordinator = state.coordinator.lock().await;
-    coordinator.pause().await.map_err(|e| e.to_string())
+    Ok(coordinator.pause().await?)
 }
 
 /// Resume recording
 #[tauri::command]
 pub async fn resume_recording(
     state: State<'_, RecorderState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut coordinator = state.coordinator.lock().await;
-    coordinator.resume().await.map_err(|e| e.to_string())
+    Ok(coordinator.resume().await?)
 }
 
 /// Get current recording state
 #[tauri::command]
 pub async fn get_recording_state(
     state: State<'_, RecorderState>,
-) -> Result<RecordingState, String> {
+) -> AppResult<RecordingState> {
     let coordinator = state.coordinator.lock().await;
     Ok(coordinator.state())
 }
@@ -261,11 +913,148 @@ pub async fn get_recording_state(
 #[tauri::command]
 pub async fn get_recording_duration(
     state: State<'_, RecorderState>,
-) -> Result<f64, String> {
+) -> AppResult<f64> {
     let coordinator = state.coordinator.lock().await;
     Ok(coordinator.duration_ms())
 }
 
+/// Video of the recording in progress, playable before it's stopped.
+/// `window_secs` asks for only the last part of the current session.
+#[tauri::command]
+pub async fn get_recording_preview(
+    state: State<'_, RecorderState>,
+    window_secs: Option<f64>,
+) -> AppResult<LivePreview> {
+    if window_secs.is_some_and(|secs| !secs.is_finite() || secs <= 0.0) {
+        return Err(AppError::InvalidInput("Preview window must be positive".to_string()));
+    }
+    let coordinator = state.coordinator.lock().await;
+    Ok(coordinator.live_preview(window_secs.map(|secs| secs * 1000.0))?)
+}
+
+/// Mark the current moment of the recording (e.g. from the toolbar or the
+/// tray; `RecordingConfig::marker_shortcut` does the same) so it's easy to
+/// find when editing
+#[tauri::command]
+pub async fn add_recording_marker(
+    state: State<'_, RecorderState>,
+    label: String,
+) -> AppResult<Marker> {
+    let coordinator = state.coordinator.lock().await;
+    Ok(coordinator.add_marker(label)?)
+}
+
+/// Add an annotation drawn on screen during recording
+#[tauri::command]
+pub async fn add_annotation(
+    state: State<'_, RecorderState>,
+    annotation: AnnotationInput,
+) -> AppResult<Annotation> {
+    Ok(state.annotations.push(annotation)?)
+}
+
+/// Capture a still screenshot (PNG) of a display
+///
+/// When `display_id` is omitted, the display being recorded is used (or the
+/// primary display when idle). During a recording the screenshot is saved into
+/// the bundle's `screenshots/` folder unless `output_path` is given; otherwise
+/// it goes to the default projects directory.
+#[tauri::command]
+pub async fn capture_screenshot(
+    state: State<'_, RecorderState>,
+    display_id: Option<u32>,
+    output_path: Option<String>,
+    include_cursor: bool,
+) -> AppResult<ScreenshotResult> {
+    let (recording_display, bundle_dir) = {
+        let coordinator = state.coordinator.lock().await;
+        (
+            coordinator.config().map(|c| c.display_id),
+            coordinator.output_dir().cloned(),
+        )
+    };
+
+    let display_id = match display_id.or(recording_display) {
+        Some(id) => id,
+        None => get_displays()
+            .await?
+            .into_iter()
+            .find(|d| d.is_primary)
+            .map(|d| d.id)
+            .ok_or_else(|| AppError::DeviceNotFound("No display available".to_string()))?,
+    };
+
+    let output_path = match (output_path, bundle_dir) {
+        (Some(path), _) => PathBuf::from(path),
+        (None, Some(bundle_dir)) => {
+            let unix_ms = chrono::Utc::now().timestamp_millis();
+            bundle_dir
+                .join("screenshots")
+                .join(format!("screenshot-{}.png", unix_ms))
+        }
+        (None, None) => {
+            let filename = chrono::Local::now()
+                .format("Screenshot %Y-%m-%d %H-%M-%S.png")
+                .to_string();
+            get_projects_directory()?.join(filename)
+        }
+    };
+
+    let screenshot = tokio::task::spawn_blocking(move || {
+        crate::capture::screenshot::capture_screenshot(display_id, &output_path, include_cursor)
+    })
+    .await
+    .map_err(|e| AppError::Recording(e.to_string()))??;
+    Ok(screenshot)
+}
+
+/// Capture a small PNG thumbnail of a display for the display picker
+#[tauri::command]
+pub async fn get_display_thumbnail(display_id: u32) -> AppResult<Response> {
+    let png = tokio::task::spawn_blocking(move || {
+        crate::capture::screenshot::capture_thumbnail(display_id)
+    })
+    .await
+    .map_err(|e| AppError::Recording(e.to_string()))??;
+    Ok(Response::new(png))
+}
+
+/// Frame a captured screenshot with a project's background, padding and
+/// shadow ("beautiful screenshot")
+///
+/// Uses the default styling when `project_path` is omitted. The result is
+/// saved next to the screenshot as `<name>-framed.png` unless `output_path`
+/// is given.
+#[tauri::command]
+pub async fn frame_screenshot(
+    screenshot_path: String,
+    project_path: Option<String>,
+    output_path: Option<String>,
+) -> AppResult<FramedScreenshot> {
+    let input_path = PathBuf::from(&screenshot_path);
+    let config = match project_path {
+        Some(path) => read_project(&PathBuf::from(path))?.config,
+        None => ProjectConfig::default(),
+    };
+    let output_path = match output_path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let stem = input_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "screenshot".to_string());
+            input_path.with_file_name(format!("{}-framed.png", stem))
+        }
+    };
+
+    let framed = tokio::task::spawn_blocking(move || {
+        crate::capture::screenshot::frame_screenshot(&input_path, &output_path, &config)
+    })
+    .await
+    .map_err(|e| AppError::Recording(e.to_string()))??;
+    Ok(framed)
+}
+
 /// Video metadata returned from FFprobe
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -279,7 +1068,7 @@ pub struct VideoMetadata {
 
 /// Get video metadata using FFprobe
 #[tauri::command]
-pub async fn get_video_metadata(path: String) -> Result<VideoMetadata, String> {
+pub async fn get_video_metadata(path: String) -> AppResult<VideoMetadata> {
     use std::process::Command;
     
     // Run ffprobe to get video stream info in JSON format
@@ -293,26 +1082,26 @@ pub async fn get_video_metadata(path: String) -> Result<VideoMetadata, String> {
             &path,
         ])
         .output()
-        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run ffprobe", e))?;
     
     if !output.status.success() {
-        return Err(format!(
+        return Err(AppError::Ffmpeg(format!(
             "ffprobe failed: {}",
             String::from_utf8_lossy(&output.stderr)
-        ));
+        )));
     }
     
     let json_str = String::from_utf8_lossy(&output.stdout);
     let json: serde_json::Value = serde_json::from_str(&json_str)
-        .map_err(|e| format!("Failed to parse ffprobe output: {}", e))?;
+        .map_err(|e| AppError::Ffmpeg(format!("Failed to parse ffprobe output: {}", e)))?;
     
     // Extract video stream info
     let streams = json.get("streams")
         .and_then(|s| s.as_array())
-        .ok_or("No streams found in video")?;
+        .ok_or_else(|| AppError::InvalidInput("No streams found in video".to_string()))?;
     
     let video_stream = streams.first()
-        .ok_or("No video stream found")?;
+        .ok_or_else(|| AppError::InvalidInput("No video stream found".to_string()))?;
     
     let width = video_stream.get("width")
         .and_then(|v| v.as_u64())
@@ -364,32 +1153,6 @@ pub async fn get_video_metadata(path: String) -> Result<VideoMetadata, String> {
     })
 }
 
-/// Mouse move event from recording
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MouseMoveEvent {
-    pub x: f64,
-    pub y: f64,
-    pub cursor_id: String,
-    pub active_modifiers: Vec<String>,
-    pub process_time_ms: f64,
-    pub unix_time_ms: u64,
-}
-
-/// Mouse click event from recording
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MouseClickEvent {
-    pub x: f64,
-    pub y: f64,
-    pub button: String,
-    pub event_type: String,
-    pub click_count: u32,
-    pub active_modifiers: Vec<String>,
-    pub process_time_ms: f64,
-    pub unix_time_ms: u64,
-}
-
 /// Cursor image info from recording
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -411,15 +1174,23 @@ pub struct RecordingBundle {
     pub webcam_video_path: Option<String>,
     pub mic_audio_path: Option<String>,
     pub system_audio_path: Option<String>,
-    pub mouse_moves: Vec<MouseMoveEvent>,
-    pub mouse_clicks: Vec<MouseClickEvent>,
+    pub mouse_moves: Vec<MouseMove>,
+    pub mouse_clicks: Vec<MouseClick>,
     pub cursors: std::collections::HashMap<String, CursorInfo>,
+    pub annotations: Vec<Annotation>,
+    pub keystrokes: Vec<KeyPress>,
     pub video_metadata: VideoMetadata,
+    /// Whether the system cursor was recorded into the screen video, so the
+    /// editor shouldn't draw another on top (as export doesn't)
+    pub video_shows_cursor: bool,
+    /// Every session of a paused and resumed recording. The paths and data
+    /// above are session 0.
+    pub sessions: Option<SessionList>,
 }
 
 /// Load a recording bundle from disk
 #[tauri::command]
-pub async fn load_recording_bundle(bundle_path: String) -> Result<RecordingBundle, String> {
+pub async fn load_recording_bundle(bundle_path: String) -> AppResult<RecordingBundle> {
     use std::collections::HashMap;
     use std::fs;
     use std::path::Path;
@@ -433,82 +1204,136 @@ pub async fn load_recording_bundle(bundle_path: String) -> Result<RecordingBundl
         bundle_dir.to_path_buf()
     };
     
-    // Find video file
-    let video_path = recording_dir.join("recording-0.mp4");
-    if !video_path.exists() {
-        return Err(format!("Video file not found: {:?}", video_path));
-    }
+    // Locate files via the recording manifest (falls back to naming convention)
+    let manifest = RecordingManifest::read(&recording_dir);
+    let find = |kind: FileKind| resolve_file(&recording_dir, manifest.as_ref(), kind, 0);
+    
+    // Find video file (the webcam recording for camera-only bundles).
+    // Audio-only bundles play over a black video as long as their audio.
+    let video_path = match resolve_primary_video(&recording_dir, manifest.as_ref(), 0) {
+        Some(path) => path,
+        None => {
+            let audio = resolve_primary_audio(&recording_dir, manifest.as_ref(), 0).ok_or_else(
+                || AppError::NotFound(format!("No video or audio found in {:?}", recording_dir)),
+            )?;
+            tokio::task::spawn_blocking(move || blank_video_for(&audio))
+                .await
+                .map_err(|e| AppError::Project(e.to_string()))??
+        }
+    };
+    
+    // Find webcam and audio files
+    let webcam_video_path = find(FileKind::WebcamVideo).filter(|p| *p != video_path);
+    let mic_audio_path = find(FileKind::MicAudio);
+    let system_audio_path = find(FileKind::SystemAudio);
+    
+    // Encrypted media is decrypted into a private cache for playback
+    let (video_path, webcam_video_path, mic_audio_path, system_audio_path) =
+        tokio::task::spawn_blocking(move || -> AppResult<_> {
+            let readable = |path: Option<PathBuf>| path.as_deref().map(readable_path).transpose();
+            Ok((
+                readable_path(&video_path)?,
+                readable(webcam_video_path)?,
+                readable(mic_audio_path)?,
+                readable(system_audio_path)?,
+            ))
+        })
+        .await
+        .map_err(|e| AppError::Project(e.to_string()))??;
     
     // Get video metadata
     let video_metadata = get_video_metadata(video_path.to_string_lossy().to_string()).await?;
     
     // Load mouse moves
-    let mouse_moves_path = recording_dir.join("recording-0-mouse-moves.json");
-    let mouse_moves: Vec<MouseMoveEvent> = if mouse_moves_path.exists() {
+    let mut mouse_moves: Vec<MouseMove> = if let Some(mouse_moves_path) = find(FileKind::MouseMoves) {
         let content = fs::read_to_string(&mouse_moves_path)
-            .map_err(|e| format!("Failed to read mouse moves: {}", e))?;
+            .map_err(|e| AppError::Project(format!("Failed to read mouse moves: {}", e)))?;
         serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse mouse moves: {}", e))?
+            .map_err(|e| AppError::Project(format!("Failed to parse mouse moves: {}", e)))?
     } else {
         Vec::new()
     };
     
     // Load mouse clicks
-    let mouse_clicks_path = recording_dir.join("recording-0-mouse-clicks.json");
-    let mouse_clicks: Vec<MouseClickEvent> = if mouse_clicks_path.exists() {
+    let mut mouse_clicks: Vec<MouseClick> = if let Some(mouse_clicks_path) = find(FileKind::MouseClicks) {
         let content = fs::read_to_string(&mouse_clicks_path)
-            .map_err(|e| format!("Failed to read mouse clicks: {}", e))?;
+            .map_err(|e| AppError::Project(format!("Failed to read mouse clicks: {}", e)))?;
         serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse mouse clicks: {}", e))?
+            .map_err(|e| AppError::Project(format!("Failed to parse mouse clicks: {}", e)))?
     } else {
         Vec::new()
     };
+    if let Some(manifest) = &manifest {
+        // Window captures place the window by its resize events
+        let window_resizes: Vec<WindowResizeEvent> = find(FileKind::WindowResizes)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        manifest.map_mouse_to_video(&window_resizes, &mut mouse_moves, &mut mouse_clicks);
+    }
     
     // Load cursor info
-    let cursors_path = recording_dir.join("recording-0-cursors.json");
-    let cursors: HashMap<String, CursorInfo> = if cursors_path.exists() {
+    let cursors: HashMap<String, CursorInfo> = if let Some(cursors_path) = find(FileKind::Cursors) {
         let content = fs::read_to_string(&cursors_path)
-            .map_err(|e| format!("Failed to read cursors: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse cursors: {}", e))?
+            .map_err(|e| AppError::Project(format!("Failed to read cursors: {}", e)))?;
+        let mut cursors: HashMap<String, CursorInfo> = serde_json::from_str(&content)
+            .map_err(|e| AppError::Project(format!("Failed to parse cursors: {}", e)))?;
+        // Image paths are saved relative to the cursors directory; the editor
+        // loads them by absolute path
+        let cursors_dir = cursors_path.with_extension("");
+        for info in cursors.values_mut() {
+            info.image_path = paths::resolve(&cursors_dir, &info.image_path)
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default();
+        }
+        cursors
     } else {
         HashMap::new()
     };
     
-    // Find webcam and audio files
-    let webcam_video_path = recording_dir.join("recording-0-webcam.mp4");
-    let mic_audio_path = recording_dir.join("recording-0-mic.m4a");
-    let system_audio_path = recording_dir.join("recording-0-system.m4a");
+    // Load annotations
+    let annotations: Vec<Annotation> = if let Some(annotations_path) = find(FileKind::Annotations) {
+        let content = fs::read_to_string(&annotations_path)
+            .map_err(|e| AppError::Project(format!("Failed to read annotations: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::Project(format!("Failed to parse annotations: {}", e)))?
+    } else {
+        Vec::new()
+    };
+    
+    // Load keystrokes
+    // Encrypted along with the media (see `encrypt_media_in_place`)
+    let keystrokes: Vec<KeyPress> = if let Some(keystrokes_path) = find(FileKind::Keystrokes) {
+        let content = fs::read_to_string(readable_path(&keystrokes_path)?)
+            .map_err(|e| AppError::Project(format!("Failed to read keystrokes: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::Project(format!("Failed to parse keystrokes: {}", e)))?
+    } else {
+        Vec::new()
+    };
     
     tracing::info!(
-        "Loaded recording bundle: {} mouse moves, {} clicks, {} cursors, webcam={}",
+        "Loaded recording bundle: {} mouse moves, {} clicks, {} cursors, {} keys, webcam={}",
         mouse_moves.len(),
         mouse_clicks.len(),
         cursors.len(),
-        webcam_video_path.exists()
+        keystrokes.len(),
+        webcam_video_path.is_some()
     );
     
     Ok(RecordingBundle {
         bundle_path: bundle_path.clone(),
         video_path: video_path.to_string_lossy().to_string(),
-        webcam_video_path: if webcam_video_path.exists() {
-            Some(webcam_video_path.to_string_lossy().to_string())
-        } else {
-            None
-        },
-        mic_audio_path: if mic_audio_path.exists() {
-            Some(mic_audio_path.to_string_lossy().to_string())
-        } else {
-            None
-        },
-        system_audio_path: if system_audio_path.exists() {
-            Some(system_audio_path.to_string_lossy().to_string())
-        } else {
-            None
-        },
+        webcam_video_path: webcam_video_path.map(|p| p.to_string_lossy().to_string()),
+        mic_audio_path: mic_audio_path.map(|p| p.to_string_lossy().to_string()),
+        system_audio_path: system_audio_path.map(|p| p.to_string_lossy().to_string()),
         mouse_moves,
         mouse_clicks,
         cursors,
+        annotations,
+        keystrokes,
         video_metadata,
+        video_shows_cursor: manifest.as_ref().is_some_and(|m| m.display_shows_cursor()),
+        sessions: SessionList::load(&recording_dir),
     })
 }
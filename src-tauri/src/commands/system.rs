@@ -2,6 +2,8 @@
 //!
 //! These commands provide system information like displays, audio devices, etc.
 
+use crate::utils::error::AppResult;
+use crate::utils::process::{active_processes, ProcessInfo};
 use serde::{Deserialize, Serialize};
 
 /// Display information
@@ -24,6 +26,13 @@ pub struct SystemInfo {
     pub arch: String,
 }
 
+/// List child processes (FFmpeg encoders, decoders, ...) that are still
+/// running, for diagnosing stuck captures and exports
+#[tauri::command]
+pub async fn list_active_subprocesses() -> AppResult<Vec<ProcessInfo>> {
+    Ok(active_processes())
+}
+
 /// Get basic system information
 #[tauri::command]
 pub async fn get_system_info() -> Result<SystemInfo, String> {
@@ -3,8 +3,12 @@
 //! Commands for creating, managing, and switching between windows
 //! (recording toolbar, editor, post-recording popup, etc.)
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
 
+/// Whether the app's windows are currently hidden from screen capture
+static EXCLUDED_FROM_CAPTURE: AtomicBool = AtomicBool::new(false);
+
 /// Open the editor window for a specific recording
 #[tauri::command]
 pub async fn open_editor_window(
@@ -44,6 +48,51 @@ pub async fn open_editor_window(
     Ok(())
 }
 
+/// Label of the countdown overlay shown before recording starts
+const COUNTDOWN_WINDOW: &str = "countdown";
+
+/// Open the transparent countdown overlay, centered on screen, starting at
+/// `seconds`. It follows `recording-countdown` events from there and ignores
+/// the mouse, so clicks reach what's underneath.
+pub(crate) fn open_countdown_window(app: &AppHandle, seconds: u32) -> Result<(), String> {
+    if app.get_webview_window(COUNTDOWN_WINDOW).is_some() {
+        return Ok(());
+    }
+
+    let window = WebviewWindowBuilder::new(
+        app,
+        COUNTDOWN_WINDOW,
+        WebviewUrl::App(format!("index.html?window=countdown&seconds={}", seconds).into()),
+    )
+    .title("Open ScreenStudio - Countdown")
+    .inner_size(240.0, 240.0)
+    .resizable(false)
+    .decorations(false)
+    .transparent(true)
+    .shadow(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .focused(false)
+    .center()
+    .build()
+    .map_err(|e| e.to_string())?;
+    window
+        .set_ignore_cursor_events(true)
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!("Opened countdown window");
+    Ok(())
+}
+
+/// Close the countdown overlay, if it's open
+pub(crate) fn close_countdown_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(COUNTDOWN_WINDOW) {
+        if let Err(e) = window.close() {
+            tracing::warn!("Failed to close countdown window: {}", e);
+        }
+    }
+}
+
 /// Close the recording toolbar window
 #[tauri::command]
 pub async fn close_toolbar_window(app: AppHandle) -> Result<(), String> {
@@ -67,6 +116,32 @@ pub async fn set_toolbar_visible(app: AppHandle, visible: bool) -> Result<(), St
     Ok(())
 }
 
+/// Hide or show all of the app's windows to screen capture.
+///
+/// Uses the platform's content protection (`NSWindowSharingNone` on macOS,
+/// `SetWindowDisplayAffinity` on Windows), so the windows stay visible to the
+/// user but are left out of captured frames. Windows opened while excluded
+/// are excluded too, through `exclude_new_window`.
+pub(crate) fn set_windows_excluded_from_capture(app: &AppHandle, excluded: bool) {
+    EXCLUDED_FROM_CAPTURE.store(excluded, Ordering::SeqCst);
+    for (label, window) in app.webview_windows() {
+        if let Err(e) = window.set_content_protected(excluded) {
+            tracing::warn!("Failed to update capture exclusion for window {}: {}", label, e);
+        }
+    }
+}
+
+/// Hide a window that's just been opened (a countdown, an overlay, the
+/// editor) from capture if a recording is excluding the app's windows
+pub(crate) fn exclude_new_window(window: &tauri::Window) {
+    if !EXCLUDED_FROM_CAPTURE.load(Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = window.set_content_protected(true) {
+        tracing::warn!("Failed to exclude window {} from capture: {}", window.label(), e);
+    }
+}
+
 /// Get the current window label
 #[tauri::command]
 pub fn get_window_label(window: tauri::Window) -> String {
@@ -0,0 +1,459 @@
+//! End-to-end recording and export checks
+//!
+//! Built with the `e2e` feature, which also turns on `mock-capture`. A
+//! scenario records a bundle from the mock channels through the real
+//! coordinator, pausing and resuming as scripted, checks the bundle layout
+//! (manifest, `sessions.json` and every session's files), then exports it
+//! through both export paths, the compositing `ExportPipeline` and the
+//! FFmpeg-only `export_with_edits`, and checks each output with ffprobe.
+//! This catches filter graphs FFmpeg rejects and bundle layout changes the
+//! loaders don't follow, which unit tests of either side alone miss.
+//!
+//! FFmpeg and ffprobe must be on the PATH. Run the scenarios with
+//! `cargo test --features e2e e2e::`.
+
+use crate::capture::mock::{register_mock_channels, MOCK_DISPLAY_ID};
+use crate::export::ffmpeg::VideoDecoder;
+use crate::export::sessions::SessionMedia;
+use crate::export::verify::{verify_export, ExpectedOutput, ExportVerification};
+use crate::export::{
+    export_with_edits, ExportError, ExportOptions, ExportPipeline, ExportSegment, TrackEdits,
+};
+use crate::recorder::channel::RecordingError;
+use crate::recorder::manifest::{resolve_primary_video, FileKind, RecordingManifest};
+use crate::recorder::sessions::SessionList;
+use crate::recorder::state::{RecordingConfig, RecordingResult};
+use crate::recorder::{ChannelContext, ChannelRegistry, RecordingCoordinator};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// How far a recorded session's length may be from the scripted one. Mock
+/// channels render their media when a session stops, which the coordinator
+/// waits for before the next session starts.
+const SESSION_TOLERANCE_MS: f64 = 1000.0;
+
+/// Errors that stop a scenario before its outputs can be checked
+#[derive(Error, Debug)]
+pub enum HarnessError {
+    #[error("Recording failed: {0}")]
+    Recording(#[from] RecordingError),
+
+    #[error("Export failed: {0}")]
+    Export(#[from] ExportError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A scripted recording and the exports made from it
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub config: RecordingConfig,
+    /// Length of each session in milliseconds. Every session after the
+    /// first starts by resuming from a pause.
+    pub sessions_ms: Vec<u64>,
+    /// Segments for the `export_with_edits` export, on the timeline of all
+    /// sessions joined; the whole recording if empty
+    pub edits: Vec<ExportSegment>,
+}
+
+impl Scenario {
+    /// Screen, both audio sources and input, recorded in sessions of these
+    /// lengths
+    pub fn new(name: &str, sessions_ms: &[u64]) -> Self {
+        let config = serde_json::from_value(serde_json::json!({
+            "displayId": MOCK_DISPLAY_ID,
+            "captureSystemAudio": true,
+            "captureMicrophone": true,
+            "microphoneDeviceId": null,
+            "captureWebcam": false,
+            "webcamDeviceId": null,
+            "trackInput": true,
+            "outputDir": "",
+        }))
+        .expect("mock recording config is valid");
+        Self {
+            name: name.to_string(),
+            config,
+            sessions_ms: sessions_ms.to_vec(),
+            edits: Vec::new(),
+        }
+    }
+
+    /// Also record the webcam
+    pub fn webcam(mut self, webcam: bool) -> Self {
+        self.config.capture_webcam = webcam;
+        self
+    }
+
+    /// Record the webcam without the screen
+    pub fn camera_only(mut self) -> Self {
+        self.config.capture_screen = false;
+        self.config.track_input = false;
+        self.config.capture_webcam = true;
+        self
+    }
+
+    /// Record just the audio, with no screen or webcam
+    pub fn audio_only(mut self) -> Self {
+        self.config.capture_screen = false;
+        self.config.track_input = false;
+        self.config.capture_webcam = false;
+        self
+    }
+
+    pub fn edits(mut self, edits: Vec<ExportSegment>) -> Self {
+        self.edits = edits;
+        self
+    }
+
+    /// Files every session of the scenario should have
+    fn expected_files(&self) -> Vec<FileKind> {
+        let mut kinds = Vec::new();
+        if self.config.capture_screen {
+            // The mock input channel follows the screen
+            kinds.extend([
+                FileKind::ScreenVideo,
+                FileKind::MouseMoves,
+                FileKind::MouseClicks,
+                FileKind::Cursors,
+            ]);
+        }
+        if self.config.capture_webcam {
+            kinds.push(FileKind::WebcamVideo);
+        }
+        if self.config.capture_microphone {
+            kinds.push(FileKind::MicAudio);
+        }
+        if self.config.capture_system_audio {
+            kinds.push(FileKind::SystemAudio);
+        }
+        kinds
+    }
+}
+
+/// Outcome of a scenario
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioReport {
+    pub name: String,
+    pub recording: RecordingResult,
+    /// Problems with the recorded bundle's layout
+    pub bundle_problems: Vec<String>,
+    pub pipeline_export: ExportVerification,
+    pub edits_export: ExportVerification,
+}
+
+impl ScenarioReport {
+    /// Every problem found, prefixed with where it was found
+    pub fn problems(&self) -> Vec<String> {
+        let bundle = self
+            .bundle_problems
+            .iter()
+            .map(|p| format!("bundle: {}", p));
+        let pipeline = self
+            .pipeline_export
+            .problems
+            .iter()
+            .map(|p| format!("pipeline export: {}", p));
+        let edits = self
+            .edits_export
+            .problems
+            .iter()
+            .map(|p| format!("edits export: {}", p));
+        bundle.chain(pipeline).chain(edits).collect()
+    }
+
+    pub fn passed(&self) -> bool {
+        self.problems().is_empty()
+    }
+}
+
+/// Record, check and export a scenario in `work_dir`, which becomes the
+/// recording bundle (and project directory)
+pub async fn run_scenario(
+    scenario: &Scenario,
+    work_dir: &Path,
+) -> Result<ScenarioReport, HarnessError> {
+    tracing::info!("Running scenario {:?} in {:?}", scenario.name, work_dir);
+
+    let recording = record(scenario, work_dir).await?;
+    let recording_dir = work_dir.join("recording");
+    let bundle_problems = check_bundle(scenario, &recording_dir);
+
+    let project_dir = work_dir.to_path_buf();
+    let pipeline_options = export_options(scenario, &work_dir.join("pipeline-export.mp4"));
+    let pipeline_export =
+        tokio::task::spawn_blocking(move || export_pipeline(&project_dir, &pipeline_options))
+            .await
+            .map_err(|e| ExportError::Encoding(format!("Export task panicked: {}", e)))??;
+
+    let edits_options = export_options(scenario, &work_dir.join("edits-export.mp4"));
+    let edits = scenario.edits.clone();
+    let edits_export =
+        tokio::task::spawn_blocking(move || export_edits(&recording_dir, &edits_options, edits))
+            .await
+            .map_err(|e| ExportError::Encoding(format!("Export task panicked: {}", e)))??;
+
+    Ok(ScenarioReport {
+        name: scenario.name.clone(),
+        recording,
+        bundle_problems,
+        pipeline_export,
+        edits_export,
+    })
+}
+
+/// Record the scenario's sessions with the mock channels
+async fn record(scenario: &Scenario, work_dir: &Path) -> Result<RecordingResult, HarnessError> {
+    let mut config = scenario.config.clone();
+    config.output_dir = work_dir.to_string_lossy().to_string();
+
+    let mut registry = ChannelRegistry::new();
+    register_mock_channels(&mut registry);
+    let mut coordinator = RecordingCoordinator::new();
+    for channel in registry.build(&ChannelContext {
+        config: &config,
+        annotations: None,
+    }) {
+        coordinator.add_channel(channel);
+    }
+
+    coordinator.start(config).await?;
+    for (i, &session_ms) in scenario.sessions_ms.iter().enumerate() {
+        if i > 0 {
+            coordinator.resume().await?;
+        }
+        tokio::time::sleep(Duration::from_millis(session_ms)).await;
+        if i + 1 < scenario.sessions_ms.len() {
+            coordinator.pause().await?;
+        }
+    }
+    Ok(coordinator.stop().await?)
+}
+
+/// Check the manifest and session list against what the scenario recorded
+fn check_bundle(scenario: &Scenario, recording_dir: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+    if RecordingManifest::read(recording_dir).is_none() {
+        problems.push("manifest.json is missing or unreadable".to_string());
+    }
+    let Some(sessions) = SessionList::read(recording_dir) else {
+        problems.push("sessions.json is missing or unreadable".to_string());
+        return problems;
+    };
+
+    if sessions.sessions.len() != scenario.sessions_ms.len() {
+        problems.push(format!(
+            "Expected {} sessions, found {}",
+            scenario.sessions_ms.len(),
+            sessions.sessions.len()
+        ));
+    }
+    let mut timeline_ms = 0.0;
+    for (session, &scripted_ms) in sessions.sessions.iter().zip(&scenario.sessions_ms) {
+        if (session.duration_ms - scripted_ms as f64).abs() > SESSION_TOLERANCE_MS {
+            problems.push(format!(
+                "Session {} lasted {:.0}ms, expected {}ms",
+                session.index, session.duration_ms, scripted_ms
+            ));
+        }
+        if (session.timeline_start_ms - timeline_ms).abs() > 1.0 {
+            problems.push(format!(
+                "Session {} starts at {:.0}ms on the timeline, expected {:.0}ms",
+                session.index, session.timeline_start_ms, timeline_ms
+            ));
+        }
+        timeline_ms += session.duration_ms;
+
+        for kind in scenario.expected_files() {
+            match session.file(kind) {
+                Some(file) if recording_dir.join(file).exists() => {}
+                Some(file) => problems.push(format!(
+                    "Session {} lists {} but it doesn't exist",
+                    session.index, file
+                )),
+                None => problems.push(format!("Session {} has no {:?} file", session.index, kind)),
+            }
+        }
+    }
+    problems
+}
+
+/// Options both exports use: MP4 at draft quality with everything recorded
+fn export_options(scenario: &Scenario, output_path: &Path) -> ExportOptions {
+    serde_json::from_value(serde_json::json!({
+        "format": "mp4",
+        "quality": "draft",
+        "width": null,
+        "height": null,
+        "fps": null,
+        "outputPath": output_path.to_string_lossy(),
+        "includeCursor": scenario.config.track_input,
+        "includeWebcam": scenario.config.capture_webcam,
+        "includeMicAudio": scenario.config.capture_microphone,
+        "includeSystemAudio": scenario.config.capture_system_audio,
+        "screenEdits": null,
+        "cameraEdits": null,
+    }))
+    .expect("export options are valid")
+}
+
+/// Export through the compositing pipeline
+fn export_pipeline(
+    project_dir: &Path,
+    options: &ExportOptions,
+) -> Result<ExportVerification, ExportError> {
+    let pipeline = ExportPipeline::new(
+        project_dir.to_path_buf(),
+        options.clone(),
+        Arc::new(AtomicBool::new(false)),
+    );
+    let expected = pipeline.run(|_| {})?;
+    let recording_dir = project_dir.join("recording");
+    let scratch = tempfile::tempdir()?;
+    let source = match resolve_primary_video(&recording_dir, None, 0) {
+        Some(video) => Some(video),
+        // Audio-only recordings are exported over a blank video
+        None => SessionMedia::load(&recording_dir).primary_video(scratch.path(), None)?,
+    }
+    .ok_or_else(|| ExportError::BundleNotFound("No video in session 0".to_string()))?;
+    check_export(options, &expected, &source)
+}
+
+/// Export through `export_with_edits`, as `start_export_with_edits` does
+fn export_edits(
+    recording_dir: &Path,
+    options: &ExportOptions,
+    mut segments: Vec<ExportSegment>,
+) -> Result<ExportVerification, ExportError> {
+    let media = SessionMedia::load(recording_dir);
+    let scratch = tempfile::tempdir()?;
+    let primary_kind = media.primary_kind();
+    let capture_fps = media
+        .manifest()
+        .and_then(|m| m.display_fps())
+        .filter(|_| primary_kind == FileKind::ScreenVideo);
+    let video = media
+        .primary_video(scratch.path(), capture_fps)?
+        .ok_or_else(|| ExportError::BundleNotFound("No video recorded".to_string()))?;
+    let webcam = match primary_kind {
+        FileKind::ScreenVideo => media.media_file(FileKind::WebcamVideo, scratch.path(), None)?,
+        _ => None,
+    };
+    let mic_audio = media.media_file(FileKind::MicAudio, scratch.path(), None)?;
+    let system_audio = media.media_file(FileKind::SystemAudio, scratch.path(), None)?;
+
+    if segments.is_empty() {
+        let (_, _, frames, stream_fps) = VideoDecoder::probe_video(&video)?;
+        let fps = capture_fps.unwrap_or(stream_fps);
+        segments.push(ExportSegment {
+            source_start_ms: 0,
+            source_end_ms: (frames as f64 / fps * 1000.0) as u64,
+            time_scale: 1.0,
+        });
+    }
+    let edits = TrackEdits { segments };
+
+    let output = export_with_edits(
+        &video,
+        capture_fps,
+        webcam.as_deref(),
+        mic_audio.as_deref(),
+        system_audio.as_deref(),
+        options,
+        &edits,
+    )?
+    .wait_with_output()?;
+    if !output.status.success() {
+        return Err(ExportError::Ffmpeg(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let expected = ExpectedOutput::new(
+        options,
+        edits.total_output_duration_ms() as f64,
+        mic_audio.as_deref(),
+        system_audio.as_deref(),
+    );
+    check_export(options, &expected, &video)
+}
+
+/// Verify an export, adding a check of its frame size against the source
+fn check_export(
+    options: &ExportOptions,
+    expected: &ExpectedOutput,
+    source: &Path,
+) -> Result<ExportVerification, ExportError> {
+    let mut verification = verify_export(options, expected)?;
+
+    let (source_width, source_height, _, _) = VideoDecoder::probe_video(source)?;
+    let expected_size = options.output_dimensions(source_width, source_height);
+    let output = PathBuf::from(&options.output_path);
+    match VideoDecoder::probe_video(&output) {
+        Ok((width, height, _, _)) if (width, height) == expected_size => {}
+        Ok((width, height, _, _)) => verification.problems.push(format!(
+            "Frame size is {}x{}, expected {}x{}",
+            width, height, expected_size.0, expected_size.1
+        )),
+        Err(e) => verification
+            .problems
+            .push(format!("Frame size could not be read: {}", e)),
+    }
+    verification.passed = verification.problems.is_empty();
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn assert_passes(scenario: Scenario) {
+        let dir = tempfile::tempdir().unwrap();
+        let report = run_scenario(&scenario, dir.path()).await.unwrap();
+        assert!(
+            report.passed(),
+            "{} failed:\n{}",
+            report.name,
+            report.problems().join("\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_single_session_with_webcam() {
+        assert_passes(Scenario::new("single session", &[2000]).webcam(true)).await;
+    }
+
+    #[tokio::test]
+    async fn test_paused_recording_with_cut_and_speed_up() {
+        let scenario = Scenario::new("paused", &[1500, 1000, 1000]).edits(vec![
+            ExportSegment {
+                source_start_ms: 0,
+                source_end_ms: 500,
+                time_scale: 1.0,
+            },
+            ExportSegment {
+                source_start_ms: 700,
+                source_end_ms: 1400,
+                time_scale: 2.0,
+            },
+        ]);
+        assert_passes(scenario).await;
+    }
+
+    #[tokio::test]
+    async fn test_camera_only() {
+        assert_passes(Scenario::new("camera only", &[1500]).camera_only()).await;
+    }
+
+    #[tokio::test]
+    async fn test_audio_only_with_pause() {
+        assert_passes(Scenario::new("audio only", &[1500, 1000]).audio_only()).await;
+    }
+}
@@ -0,0 +1,178 @@
+//! Lining recorded audio up with the video
+//!
+//! The screen, webcam, microphone and system audio are captured from devices
+//! with their own clocks, started one after another, so audio doesn't begin
+//! exactly where the video does and drifts from it over a long recording.
+//! The coordinator anchors every channel's capture on one monotonic clock
+//! (`ChannelAnchor`), and export uses the anchors to put each session's audio
+//! on the video's timeline: audio that started after the video is delayed by
+//! the difference and audio that started before has its head cut. Audio that
+//! came out longer or shorter than the time it was captured for, by as much
+//! as a drifting clock would, is stretched to fit with `atempo`, which keeps
+//! its pitch. Whatever remains is padded with silence or cut to the
+//! session's length when sessions are joined. Offsets too small to hear and
+//! captures too short to tell drift from start/stop jitter are left alone,
+//! so most recordings export their audio as recorded.
+//!
+//! This is the only correction for how channels started: export, clips and
+//! the editor's playback mixdown all apply it, and the sync report doesn't
+//! suggest a microphone offset for anchored audio. `microphone_offset_ms` is
+//! left for latency the anchors can't see.
+
+use crate::recorder::manifest::ChannelAnchor;
+
+/// Smallest start offset worth correcting, well under what's heard as
+/// audio out of sync
+const MIN_LEAD_MS: f64 = 20.0;
+
+/// Smallest length difference stretched away
+const MIN_DRIFT_MS: f64 = 20.0;
+
+/// Shortest capture whose length difference is put down to clock drift.
+/// Over a shorter one it's the jitter of starting and stopping the device.
+const MIN_DRIFT_CAPTURE_MS: f64 = 60_000.0;
+
+/// Largest speed difference treated as clock drift. Audio further off lost
+/// or gained samples some other way (e.g. a device dropping out) and is
+/// padded or cut instead.
+const MAX_DRIFT: f64 = 0.01;
+
+/// How a session's audio is moved onto the video timeline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioAlignment {
+    /// How much later than the video the audio started; negative if it
+    /// started earlier
+    pub lead_ms: f64,
+    /// Speed the audio plays at so it lasts as long as it was captured for
+    pub tempo: f64,
+}
+
+impl Default for AudioAlignment {
+    fn default() -> Self {
+        Self {
+            lead_ms: 0.0,
+            tempo: 1.0,
+        }
+    }
+}
+
+impl AudioAlignment {
+    /// Alignment of audio anchored at `audio` to video anchored at `video`,
+    /// given the recorded audio's length if it could be probed
+    pub fn new(
+        video: &ChannelAnchor,
+        audio: &ChannelAnchor,
+        audio_duration_ms: Option<f64>,
+    ) -> Self {
+        let tempo = match (audio.stop_ms, audio_duration_ms) {
+            (Some(stop_ms), Some(duration_ms)) => {
+                drift_tempo(duration_ms, stop_ms - audio.start_ms)
+            }
+            _ => 1.0,
+        };
+        Self {
+            lead_ms: audio.start_ms - video.start_ms,
+            tempo,
+        }
+    }
+
+    /// Whether the audio can't be used as recorded
+    pub fn is_needed(&self) -> bool {
+        self.lead_ms.abs() >= MIN_LEAD_MS || self.tempo != 1.0
+    }
+
+    /// Position in the recorded audio heard at `video_ms` into the video
+    pub fn audio_position_ms(&self, video_ms: f64) -> f64 {
+        let lead_ms = if self.lead_ms.abs() >= MIN_LEAD_MS {
+            self.lead_ms
+        } else {
+            0.0
+        };
+        (video_ms - lead_ms) * self.tempo
+    }
+
+    /// `atempo` filter correcting drift, if the audio drifted
+    pub fn tempo_filter(&self) -> Option<String> {
+        (self.tempo != 1.0).then(|| format!("atempo={:.6}", self.tempo))
+    }
+
+    /// FFmpeg audio filters applying the alignment
+    pub fn filters(&self) -> Vec<String> {
+        let mut filters: Vec<String> = self.tempo_filter().into_iter().collect();
+        if self.lead_ms >= MIN_LEAD_MS {
+            filters.push(format!("adelay={:.0}:all=1", self.lead_ms));
+        } else if self.lead_ms <= -MIN_LEAD_MS {
+            filters.push(format!(
+                "atrim=start={:.3},asetpts=PTS-STARTPTS",
+                -self.lead_ms / 1000.0
+            ));
+        }
+        filters
+    }
+}
+
+/// Speed turning `recorded_ms` of audio into the `captured_ms` it was
+/// captured over, or 1 if the difference isn't drift
+fn drift_tempo(recorded_ms: f64, captured_ms: f64) -> f64 {
+    if recorded_ms <= 0.0 || captured_ms < MIN_DRIFT_CAPTURE_MS {
+        return 1.0;
+    }
+    let tempo = recorded_ms / captured_ms;
+    if (recorded_ms - captured_ms).abs() < MIN_DRIFT_MS || (tempo - 1.0).abs() > MAX_DRIFT {
+        1.0
+    } else {
+        tempo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor(start_ms: f64, stop_ms: f64) -> ChannelAnchor {
+        ChannelAnchor {
+            session: 0,
+            start_ms,
+            stop_ms: Some(stop_ms),
+        }
+    }
+
+    #[test]
+    fn test_audio_is_moved_onto_the_video_timeline() {
+        let video = anchor(300.0, 600_300.0);
+
+        // Started 80ms late and ran 0.1% fast over ten minutes
+        let alignment = AudioAlignment::new(&video, &anchor(380.0, 600_380.0), Some(600_600.0));
+        assert_eq!(alignment.lead_ms, 80.0);
+        assert!((alignment.tempo - 1.001).abs() < 1e-9);
+        assert_eq!(alignment.filters(), ["atempo=1.001000", "adelay=80:all=1"]);
+
+        // Started 250ms early, with no measurable drift
+        let alignment = AudioAlignment::new(&video, &anchor(50.0, 600_300.0), Some(600_252.0));
+        assert_eq!(alignment.tempo, 1.0);
+        assert_eq!(
+            alignment.filters(),
+            ["atrim=start=0.250,asetpts=PTS-STARTPTS"]
+        );
+
+        // Lost a minute to a device dropping out: padded, not stretched
+        let alignment = AudioAlignment::new(&video, &anchor(300.0, 600_300.0), Some(540_000.0));
+        assert!(!alignment.is_needed());
+        assert!(alignment.filters().is_empty());
+
+        // Unknown length: only the start is corrected
+        let alignment = AudioAlignment::new(&video, &anchor(340.0, 600_300.0), None);
+        assert_eq!(alignment.filters(), ["adelay=40:all=1"]);
+        assert_eq!(alignment.audio_position_ms(1_000.0), 960.0);
+
+        // A few milliseconds off is left as recorded
+        let alignment = AudioAlignment::new(&video, &anchor(305.0, 600_300.0), Some(599_995.0));
+        assert!(!alignment.is_needed());
+        assert_eq!(alignment.audio_position_ms(1_000.0), 1_000.0);
+
+        // A short capture 30ms long isn't drifting, however fast that looks
+        let short = anchor(0.0, 5_000.0);
+        let alignment = AudioAlignment::new(&short, &short, Some(5_030.0));
+        assert!(!alignment.is_needed());
+    }
+}
@@ -0,0 +1,273 @@
+//! Annotation rendering
+//!
+//! Rasterizes annotations recorded during capture (pen strokes, arrows,
+//! highlights) onto RGBA export frames.
+
+use crate::capture::annotation::{Annotation, AnnotationPoint, AnnotationShape};
+
+/// How long an annotation stays visible when the overlay didn't specify a duration
+pub const DEFAULT_ANNOTATION_DURATION_MS: f64 = 3000.0;
+
+/// Opacity used for highlight rectangles when the color has no explicit alpha
+const HIGHLIGHT_OPACITY: u8 = 90;
+
+/// Arrow head length relative to stroke width
+const ARROW_HEAD_SCALE: f64 = 4.0;
+
+/// Check whether an annotation is on screen at the given session time
+pub fn is_visible(annotation: &Annotation, time_ms: f64) -> bool {
+    let duration = annotation
+        .duration_ms
+        .unwrap_or(DEFAULT_ANNOTATION_DURATION_MS);
+    time_ms >= annotation.process_time_ms && time_ms < annotation.process_time_ms + duration
+}
+
+/// Parse a CSS hex color (#RGB, #RRGGBB or #RRGGBBAA) into RGBA
+pub fn parse_hex_color(color: &str) -> Option<[u8; 4]> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+    match hex.len() {
+        3 => {
+            let mut rgba = [255u8; 4];
+            for (i, c) in hex.chars().enumerate() {
+                let v = c.to_digit(16)? as u8;
+                rgba[i] = v * 17;
+            }
+            Some(rgba)
+        }
+        6 => Some([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Some([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => None,
+    }
+}
+
+/// Draw all annotations visible at `time_ms` onto an RGBA frame
+pub fn draw_annotations(
+    frame: &mut [u8],
+    frame_width: u32,
+    frame_height: u32,
+    annotations: &[Annotation],
+    time_ms: f64,
+) {
+    for annotation in annotations.iter().filter(|a| is_visible(a, time_ms)) {
+        let Some(color) = parse_hex_color(&annotation.color) else {
+            continue;
+        };
+        let radius = (annotation.stroke_width / 2.0).max(0.5);
+        let mut canvas = Canvas {
+            frame: &mut *frame,
+            width: frame_width,
+            height: frame_height,
+        };
+
+        match &annotation.shape {
+            AnnotationShape::Pen { points } => {
+                if let [only] = points.as_slice() {
+                    canvas.fill_circle(only.x, only.y, radius, color);
+                }
+                for pair in points.windows(2) {
+                    canvas.draw_line(pair[0], pair[1], radius, color);
+                }
+            }
+            AnnotationShape::Arrow { from, to } => {
+                canvas.draw_line(*from, *to, radius, color);
+
+                let angle = (to.y - from.y).atan2(to.x - from.x);
+                let head = annotation.stroke_width.max(1.0) * ARROW_HEAD_SCALE;
+                for side in [-1.0, 1.0] {
+                    let a = angle + std::f64::consts::PI - side * std::f64::consts::FRAC_PI_6;
+                    let tip = AnnotationPoint {
+                        x: to.x + head * a.cos(),
+                        y: to.y + head * a.sin(),
+                    };
+                    canvas.draw_line(*to, tip, radius, color);
+                }
+            }
+            AnnotationShape::Highlight {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let mut fill = color;
+                if annotation.color.trim_start_matches('#').len() != 8 {
+                    fill[3] = HIGHLIGHT_OPACITY;
+                }
+                canvas.fill_rect(*x, *y, *width, *height, fill);
+            }
+        }
+    }
+}
+
+/// Mutable view over an RGBA frame
+struct Canvas<'a> {
+    frame: &'a mut [u8],
+    width: u32,
+    height: u32,
+}
+
+impl Canvas<'_> {
+    fn blend_pixel(&mut self, x: i64, y: i64, color: [u8; 4]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let idx = ((y as u32 * self.width + x as u32) * 4) as usize;
+        if idx + 3 >= self.frame.len() {
+            return;
+        }
+
+        let alpha = color[3] as u32;
+        for (dst, &src) in self.frame[idx..idx + 3].iter_mut().zip(&color[..3]) {
+            *dst = ((src as u32 * alpha + *dst as u32 * (255 - alpha)) / 255) as u8;
+        }
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: [u8; 4]) {
+        // Normalize rectangles drawn right-to-left or bottom-to-top
+        let (x0, x1) = (x.min(x + width), x.max(x + width));
+        let (y0, y1) = (y.min(y + height), y.max(y + height));
+
+        for py in y0.round() as i64..y1.round() as i64 {
+            for px in x0.round() as i64..x1.round() as i64 {
+                self.blend_pixel(px, py, color);
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, cx: f64, cy: f64, radius: f64, color: [u8; 4]) {
+        let r2 = radius * radius;
+        for py in (cy - radius).floor() as i64..=(cy + radius).ceil() as i64 {
+            for px in (cx - radius).floor() as i64..=(cx + radius).ceil() as i64 {
+                let dx = px as f64 - cx;
+                let dy = py as f64 - cy;
+                if dx * dx + dy * dy <= r2 {
+                    self.blend_pixel(px, py, color);
+                }
+            }
+        }
+    }
+
+    /// Draw a thick line segment with round caps
+    fn draw_line(&mut self, from: AnnotationPoint, to: AnnotationPoint, radius: f64, color: [u8; 4]) {
+        let min_x = (from.x.min(to.x) - radius).floor() as i64;
+        let max_x = (from.x.max(to.x) + radius).ceil() as i64;
+        let min_y = (from.y.min(to.y) - radius).floor() as i64;
+        let max_y = (from.y.max(to.y) + radius).ceil() as i64;
+
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let len2 = dx * dx + dy * dy;
+        let r2 = radius * radius;
+
+        // Test each pixel's distance to the segment once, so overlapping
+        // caps don't double-blend translucent strokes
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let (qx, qy) = (px as f64 - from.x, py as f64 - from.y);
+                let t = if len2 > 0.0 {
+                    ((qx * dx + qy * dy) / len2).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let (ex, ey) = (qx - t * dx, qy - t * dy);
+                if ex * ex + ey * ey <= r2 {
+                    self.blend_pixel(px, py, color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation(shape: AnnotationShape, color: &str, start_ms: f64) -> Annotation {
+        Annotation {
+            id: "a".to_string(),
+            shape,
+            color: color.to_string(),
+            stroke_width: 2.0,
+            duration_ms: Some(1000.0),
+            process_time_ms: start_ms,
+            unix_time_ms: 0,
+        }
+    }
+
+    fn pixel(frame: &[u8], width: u32, x: u32, y: u32) -> [u8; 3] {
+        let i = ((y * width + x) * 4) as usize;
+        [frame[i], frame[i + 1], frame[i + 2]]
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#ff0000"), Some([255, 0, 0, 255]));
+        assert_eq!(parse_hex_color("#00ff0080"), Some([0, 255, 0, 128]));
+        assert_eq!(parse_hex_color("#fff"), Some([255, 255, 255, 255]));
+        assert_eq!(parse_hex_color("red"), None);
+    }
+
+    #[test]
+    fn test_visibility_window() {
+        let a = annotation(
+            AnnotationShape::Highlight {
+                x: 0.0,
+                y: 0.0,
+                width: 1.0,
+                height: 1.0,
+            },
+            "#ff0000",
+            500.0,
+        );
+        assert!(!is_visible(&a, 499.0));
+        assert!(is_visible(&a, 500.0));
+        assert!(is_visible(&a, 1499.0));
+        assert!(!is_visible(&a, 1500.0));
+    }
+
+    #[test]
+    fn test_draw_pen_stroke() {
+        let (w, h) = (10, 10);
+        let mut frame = vec![0u8; (w * h * 4) as usize];
+        let a = annotation(
+            AnnotationShape::Pen {
+                points: vec![
+                    AnnotationPoint { x: 1.0, y: 5.0 },
+                    AnnotationPoint { x: 8.0, y: 5.0 },
+                ],
+            },
+            "#ff0000",
+            0.0,
+        );
+
+        draw_annotations(&mut frame, w, h, std::slice::from_ref(&a), 100.0);
+        assert_eq!(pixel(&frame, w, 4, 5), [255, 0, 0]);
+        assert_eq!(pixel(&frame, w, 4, 0), [0, 0, 0]);
+
+        // Not drawn outside its time window
+        let mut untouched = vec![0u8; (w * h * 4) as usize];
+        draw_annotations(&mut untouched, w, h, &[a], 5000.0);
+        assert!(untouched.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_highlight_is_translucent() {
+        let (w, h) = (4, 4);
+        let mut frame = vec![0u8; (w * h * 4) as usize];
+        let a = annotation(
+            AnnotationShape::Highlight {
+                x: 0.0,
+                y: 0.0,
+                width: 2.0,
+                height: 2.0,
+            },
+            "#ffffff",
+            0.0,
+        );
+
+        draw_annotations(&mut frame, w, h, &[a], 0.0);
+        let [r, _, _] = pixel(&frame, w, 1, 1);
+        assert!(r > 0 && r < 255);
+        assert_eq!(pixel(&frame, w, 3, 3), [0, 0, 0]);
+    }
+}
@@ -0,0 +1,311 @@
+//! Bundle archiving
+//!
+//! Remuxes every recorded track of a bundle (screen, webcam, microphone,
+//! system audio) into a single multi-track MKV without re-encoding. Project
+//! markers become MKV chapters so the archive stays navigable outside the app.
+//! Every stream of each file is mapped and labelled from what ffprobe finds
+//! in it, and an existing archive is never overwritten.
+
+use crate::export::types::ExportError;
+use crate::project::bundle::read_markers;
+use crate::project::encryption::readable_path;
+use crate::project::naming::unique_path;
+use crate::project::schema::Marker;
+use crate::recorder::manifest::{resolve_file, resolve_primary_video, FileKind, RecordingManifest};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Tracks included in an archive, in output stream order
+const ARCHIVE_TRACKS: [(FileKind, &str); 4] = [
+    (FileKind::ScreenVideo, "Screen"),
+    (FileKind::WebcamVideo, "Webcam"),
+    (FileKind::MicAudio, "Microphone"),
+    (FileKind::SystemAudio, "System Audio"),
+];
+
+/// Stream types that can be copied into an MKV (data streams such as MP4
+/// timecodes can't)
+const ARCHIVED_STREAM_TYPES: [&str; 3] = ["video", "audio", "subtitle"];
+
+/// A recorded file to mux into the archive
+#[derive(Debug, Clone)]
+pub struct ArchiveInput {
+    pub path: PathBuf,
+    pub title: String,
+    /// `codec_type` of each of the file's streams, in stream order
+    pub stream_types: Vec<String>,
+}
+
+/// Archive a recording bundle into `<bundle name>.mkv` next to the bundle,
+/// or `<bundle name> 2.mkv` etc. if that already exists
+pub fn archive_recording(project_dir: &Path) -> Result<PathBuf, ExportError> {
+    let recording_dir = project_dir.join("recording");
+    if !recording_dir.exists() {
+        return Err(ExportError::BundleNotFound(
+            recording_dir.to_string_lossy().to_string(),
+        ));
+    }
+
+    let manifest = RecordingManifest::read(&recording_dir);
+    // Encrypted media is archived from decrypted copies
+    let inputs: Vec<ArchiveInput> = ARCHIVE_TRACKS
+        .iter()
+        .filter_map(|(kind, title)| {
+            resolve_file(&recording_dir, manifest.as_ref(), *kind, 0).map(|path| {
+                let path = readable_path(&path)?;
+                Ok(ArchiveInput {
+                    stream_types: probe_stream_types(&path)?,
+                    path,
+                    title: title.to_string(),
+                })
+            })
+        })
+        .collect::<Result<_, ExportError>>()?;
+
+    // Camera-only bundles are archived with the webcam as their only video
+    if resolve_primary_video(&recording_dir, manifest.as_ref(), 0).is_none() {
+        return Err(ExportError::BundleNotFound(format!(
+            "Video file not found in {:?}",
+            recording_dir
+        )));
+    }
+
+    let markers = read_markers(project_dir).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read markers, archiving without chapters: {}", e);
+        Vec::new()
+    });
+
+    let duration_ms = match manifest.as_ref().map(|m| m.total_duration_ms) {
+        Some(ms) if ms > 0.0 => ms,
+        _ => probe_duration_ms(&inputs[0].path)?,
+    };
+
+    let metadata_path = std::env::temp_dir().join(format!("archive-{}.ffmeta", uuid::Uuid::new_v4()));
+    std::fs::write(&metadata_path, build_chapters_metadata(&markers, duration_ms))?;
+
+    let bundle_name = project_dir
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "recording".to_string());
+    let output_path = unique_path(project_dir.parent().unwrap_or(project_dir), &bundle_name, "mkv");
+
+    tracing::info!(
+        "Archiving {} tracks and {} chapters to {:?}",
+        inputs.len(),
+        markers.len(),
+        output_path
+    );
+
+    let result = Command::new("ffmpeg")
+        .args(build_archive_args(&inputs, &metadata_path, &output_path))
+        .output();
+    let _ = std::fs::remove_file(&metadata_path);
+
+    let output = result.map_err(|e| ExportError::ffmpeg_spawn("Failed to start FFmpeg", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ExportError::Ffmpeg(format!("Archive remux failed: {}", stderr)));
+    }
+
+    Ok(output_path)
+}
+
+/// Build FFmpeg arguments that stream-copy all inputs into one MKV.
+///
+/// Each input's video, audio and subtitle streams are mapped one by one and
+/// titled after their track, with the stream type added when a file has
+/// several. The chapters metadata file is added as the last input and only
+/// its chapters are mapped. FFmpeg fails rather than overwrite the output.
+pub fn build_archive_args(inputs: &[ArchiveInput], metadata_path: &Path, output_path: &Path) -> Vec<String> {
+    let mut args = vec!["-n".to_string()];
+
+    for input in inputs {
+        args.extend(["-i".to_string(), input.path.to_string_lossy().to_string()]);
+    }
+    args.extend([
+        "-f".to_string(),
+        "ffmetadata".to_string(),
+        "-i".to_string(),
+        metadata_path.to_string_lossy().to_string(),
+    ]);
+
+    let mut output_stream = 0;
+    for (i, input) in inputs.iter().enumerate() {
+        let streams: Vec<(usize, &str)> = input
+            .stream_types
+            .iter()
+            .enumerate()
+            .filter(|(_, kind)| ARCHIVED_STREAM_TYPES.contains(&kind.as_str()))
+            .map(|(index, kind)| (index, kind.as_str()))
+            .collect();
+        for (index, kind) in &streams {
+            let title = if streams.len() == 1 {
+                input.title.clone()
+            } else {
+                // Number streams of a type the file has more than one of
+                let same_kind = streams.iter().filter(|(_, k)| k == kind).count();
+                let nth = streams.iter().filter(|(s, k)| k == kind && s <= index).count();
+                if same_kind == 1 {
+                    format!("{} ({})", input.title, kind)
+                } else {
+                    format!("{} ({} {})", input.title, kind, nth)
+                }
+            };
+            args.extend([
+                "-map".to_string(),
+                format!("{}:{}", i, index),
+                format!("-metadata:s:{}", output_stream),
+                format!("title={}", title),
+            ]);
+            output_stream += 1;
+        }
+    }
+
+    args.extend([
+        "-map_chapters".to_string(),
+        inputs.len().to_string(),
+        "-c".to_string(),
+        "copy".to_string(),
+        output_path.to_string_lossy().to_string(),
+    ]);
+
+    args
+}
+
+/// Build an FFmpeg metadata file with one chapter per marker.
+///
+/// Each chapter runs until the next marker (or the end of the recording).
+/// Times are in milliseconds.
+pub fn build_chapters_metadata(markers: &[Marker], duration_ms: f64) -> String {
+    let mut sorted: Vec<&Marker> = markers
+        .iter()
+        .filter(|m| m.time >= 0.0 && m.time < duration_ms)
+        .collect();
+    sorted.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+    let mut out = String::from(";FFMETADATA1\n");
+    for (i, marker) in sorted.iter().enumerate() {
+        let end = sorted.get(i + 1).map(|m| m.time).unwrap_or(duration_ms);
+        out.push_str("\n[CHAPTER]\nTIMEBASE=1/1000\n");
+        out.push_str(&format!("START={}\n", marker.time.round() as u64));
+        out.push_str(&format!("END={}\n", end.round() as u64));
+        out.push_str(&format!("title={}\n", escape_metadata(&marker.label)));
+    }
+    out
+}
+
+/// Escape special characters in an ffmetadata value
+fn escape_metadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `codec_type` of each stream of a media file, in stream order
+fn probe_stream_types(path: &Path) -> Result<Vec<String>, ExportError> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "stream=codec_type", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run ffprobe", e))?;
+    if !output.status.success() {
+        return Err(ExportError::Ffmpeg(format!(
+            "Could not read the streams of {:?}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().trim_end_matches(',').to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// Probe a media file's duration in milliseconds
+pub(crate) fn probe_duration_ms(path: &Path) -> Result<f64, ExportError> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "csv=p=0",
+            path.to_str().unwrap_or(""),
+        ])
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run ffprobe", e))?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .map(|secs| secs * 1000.0)
+        .map_err(|_| ExportError::Ffmpeg(format!("Could not read duration of {:?}", path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(time: f64, label: &str) -> Marker {
+        Marker {
+            id: label.to_string(),
+            time,
+            label: label.to_string(),
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_chapters_follow_sorted_markers() {
+        let markers = vec![marker(5000.0, "Demo"), marker(0.0, "Intro"), marker(20000.0, "Late")];
+        let meta = build_chapters_metadata(&markers, 12000.0);
+
+        assert!(meta.starts_with(";FFMETADATA1\n"));
+        assert_eq!(meta.matches("[CHAPTER]").count(), 2);
+        assert!(meta.contains("START=0\nEND=5000\ntitle=Intro\n"));
+        assert!(meta.contains("START=5000\nEND=12000\ntitle=Demo\n"));
+    }
+
+    #[test]
+    fn test_chapter_titles_are_escaped() {
+        let meta = build_chapters_metadata(&[marker(0.0, "a=b; #1")], 1000.0);
+        assert!(meta.contains("title=a\\=b\\; \\#1\n"));
+    }
+
+    #[test]
+    fn test_archive_args_map_every_track() {
+        let types = |types: &[&str]| types.iter().map(|t| t.to_string()).collect();
+        let inputs = vec![
+            ArchiveInput {
+                path: PathBuf::from("screen.mp4"),
+                title: "Screen".to_string(),
+                // With a timecode track, which MKV can't hold
+                stream_types: types(&["video", "data", "audio", "audio"]),
+            },
+            ArchiveInput {
+                path: PathBuf::from("mic.m4a"),
+                title: "Microphone".to_string(),
+                stream_types: types(&["audio"]),
+            },
+        ];
+        let args = build_archive_args(&inputs, Path::new("chapters.ffmeta"), Path::new("out.mkv"));
+        let joined = args.join(" ");
+
+        assert_eq!(args[0], "-n");
+        assert!(joined.contains("-map 0:0 -metadata:s:0 title=Screen (video)"));
+        assert!(joined.contains("-map 0:2 -metadata:s:1 title=Screen (audio 1)"));
+        assert!(joined.contains("-map 0:3 -metadata:s:2 title=Screen (audio 2)"));
+        assert!(joined.contains("-map 1:0 -metadata:s:3 title=Microphone "));
+        assert!(!joined.contains("0:1"));
+        assert!(joined.contains("-f ffmetadata -i chapters.ffmeta"));
+        assert!(joined.ends_with("-map_chapters 2 -c copy out.mkv"));
+    }
+}
@@ -0,0 +1,358 @@
+//! Background, padding and shadow compositing
+//!
+//! Places content (a frame or a screenshot) on the project's background with
+//! its padding, rounded corners and drop shadow, all on RGBA buffers.
+
+use crate::export::annotations::parse_hex_color;
+use crate::project::schema::{Background, GradientConfig, ProjectConfig, ShadowConfig};
+use std::path::Path;
+
+/// Corner radius of the content, relative to its shorter side
+const CONTENT_CORNER_RADIUS: f64 = 0.02;
+
+/// Shadow distance and blur are given for a 1080px tall output
+const SHADOW_REFERENCE_SIZE: f64 = 1080.0;
+
+/// Where the content sits on the composed canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentPlacement {
+    pub canvas_width: u32,
+    pub canvas_height: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+/// Size the canvas for content of `width`x`height`: padding (percent of the
+/// content's shorter side) around it, then widened or heightened to the
+/// output aspect ratio with the content centered
+pub fn place_content(width: u32, height: u32, config: &ProjectConfig) -> ContentPlacement {
+    let unit = width.min(height) as f64 / 100.0;
+    let pad = |percent: f64| (percent.max(0.0) * unit).round() as u32;
+    let (top, right, bottom, left) = (
+        pad(config.padding.top),
+        pad(config.padding.right),
+        pad(config.padding.bottom),
+        pad(config.padding.left),
+    );
+
+    let mut canvas_width = width + left + right;
+    let mut canvas_height = height + top + bottom;
+    let (mut x, mut y) = (left, top);
+
+    let ratio = &config.output_aspect_ratio;
+    if ratio.x > 0 && ratio.y > 0 {
+        let target = ratio.x as f64 / ratio.y as f64;
+        let current = canvas_width as f64 / canvas_height as f64;
+        if current < target {
+            let widened = (canvas_height as f64 * target).round() as u32;
+            x += (widened - canvas_width) / 2;
+            canvas_width = widened;
+        } else if current > target {
+            let heightened = (canvas_width as f64 / target).round() as u32;
+            y += (heightened - canvas_height) / 2;
+            canvas_height = heightened;
+        }
+    }
+
+    ContentPlacement {
+        canvas_width,
+        canvas_height,
+        x,
+        y,
+    }
+}
+
+/// Compose RGBA content onto the project's background, with padding, rounded
+/// corners and drop shadow. Returns the canvas and its size.
+pub fn compose_on_background(
+    content: &[u8],
+    width: u32,
+    height: u32,
+    config: &ProjectConfig,
+) -> (Vec<u8>, u32, u32) {
+    let placement = place_content(width, height, config);
+    let (cw, ch) = (placement.canvas_width, placement.canvas_height);
+    let mut canvas = vec![0u8; (cw * ch * 4) as usize];
+
+    fill_background(&mut canvas, cw, ch, &config.background);
+
+    let radius = (width.min(height) as f64 * CONTENT_CORNER_RADIUS) as i32;
+    let scale = ch as f64 / SHADOW_REFERENCE_SIZE;
+    draw_shadow(
+        &mut canvas,
+        cw,
+        ch,
+        (
+            placement.x as f64,
+            placement.y as f64,
+            width as f64,
+            height as f64,
+        ),
+        radius as f64,
+        &config.shadow,
+        scale,
+    );
+
+    for y in 0..height {
+        for x in 0..width {
+            if !is_inside_rounded_rect(x as i32, y as i32, width as i32, height as i32, radius) {
+                continue;
+            }
+            let src = ((y * width + x) * 4) as usize;
+            let dest = (((placement.y + y) * cw + placement.x + x) * 4) as usize;
+            if src + 3 >= content.len() || dest + 3 >= canvas.len() {
+                continue;
+            }
+            blend_pixel(&mut canvas[dest..dest + 4], &content[src..src + 4]);
+        }
+    }
+
+    (canvas, cw, ch)
+}
+
+/// Fill an RGBA canvas with a background. Image backgrounds that can't be
+/// loaded fall back to the default gradient.
+pub fn fill_background(canvas: &mut [u8], width: u32, height: u32, background: &Background) {
+    match background {
+        Background::Solid { color } => {
+            let rgba = parse_hex_color(color).unwrap_or([0, 0, 0, 255]);
+            for px in canvas.chunks_exact_mut(4) {
+                px.copy_from_slice(&[rgba[0], rgba[1], rgba[2], 255]);
+            }
+        }
+        Background::Gradient { gradient } => fill_gradient(canvas, width, height, gradient),
+        Background::Image { image_url } => {
+            if !fill_image(canvas, width, height, image_url) {
+                tracing::warn!(
+                    "Failed to load background image {}, using default",
+                    image_url
+                );
+                fill_background(canvas, width, height, &Background::default());
+            }
+        }
+    }
+}
+
+/// Fill with a linear gradient between normalized start and end points
+fn fill_gradient(canvas: &mut [u8], width: u32, height: u32, gradient: &GradientConfig) {
+    let mut stops: Vec<(f64, [u8; 4])> = gradient
+        .stops
+        .iter()
+        .filter_map(|s| Some((s.at, parse_hex_color(&s.color)?)))
+        .collect();
+    if stops.is_empty() {
+        stops.push((0.0, [0, 0, 0, 255]));
+    }
+    stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let (dx, dy) = (
+        gradient.end.x - gradient.start.x,
+        gradient.end.y - gradient.start.y,
+    );
+    let length_sq = (dx * dx + dy * dy).max(f64::EPSILON);
+
+    for y in 0..height {
+        let v = (y as f64 + 0.5) / height as f64;
+        for x in 0..width {
+            let u = (x as f64 + 0.5) / width as f64;
+            let t = ((u - gradient.start.x) * dx + (v - gradient.start.y) * dy) / length_sq;
+            let color = gradient_color(&stops, t.clamp(0.0, 1.0));
+            let idx = ((y * width + x) * 4) as usize;
+            canvas[idx..idx + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+/// Color of sorted gradient stops at `t`
+fn gradient_color(stops: &[(f64, [u8; 4])], t: f64) -> [u8; 4] {
+    let after = stops
+        .iter()
+        .position(|s| s.0 >= t)
+        .unwrap_or(stops.len() - 1);
+    if after == 0 {
+        return opaque(stops[0].1);
+    }
+    let (a, b) = (stops[after - 1], stops[after]);
+    let span = (b.0 - a.0).max(f64::EPSILON);
+    let k = ((t - a.0) / span).clamp(0.0, 1.0);
+    let mix = |i: usize| (a.1[i] as f64 + (b.1[i] as f64 - a.1[i] as f64) * k).round() as u8;
+    [mix(0), mix(1), mix(2), 255]
+}
+
+fn opaque(color: [u8; 4]) -> [u8; 4] {
+    [color[0], color[1], color[2], 255]
+}
+
+/// Fill with a local PNG scaled to cover the canvas. Returns false if the
+/// image couldn't be loaded.
+fn fill_image(canvas: &mut [u8], width: u32, height: u32, image_url: &str) -> bool {
+    let path = image_url.strip_prefix("file://").unwrap_or(image_url);
+    let Some((image, iw, ih)) = std::fs::read(Path::new(path))
+        .ok()
+        .and_then(|data| crate::capture::screenshot::decode_png_rgba(&data))
+    else {
+        return false;
+    };
+    if iw == 0 || ih == 0 {
+        return false;
+    }
+
+    let scale = (width as f64 / iw as f64).max(height as f64 / ih as f64);
+    let offset_x = (iw as f64 * scale - width as f64) / 2.0;
+    let offset_y = (ih as f64 * scale - height as f64) / 2.0;
+    for y in 0..height {
+        let sy = (((y as f64 + offset_y) / scale) as u32).min(ih - 1);
+        for x in 0..width {
+            let sx = (((x as f64 + offset_x) / scale) as u32).min(iw - 1);
+            let src = ((sy * iw + sx) * 4) as usize;
+            let dest = ((y * width + x) * 4) as usize;
+            canvas[dest..dest + 4].copy_from_slice(&[
+                image[src],
+                image[src + 1],
+                image[src + 2],
+                255,
+            ]);
+        }
+    }
+    true
+}
+
+/// Draw a soft drop shadow for a rounded rect (x, y, width, height).
+/// The shadow's distance and blur are multiplied by `scale`.
+pub fn draw_shadow(
+    canvas: &mut [u8],
+    width: u32,
+    height: u32,
+    rect: (f64, f64, f64, f64),
+    radius: f64,
+    shadow: &ShadowConfig,
+    scale: f64,
+) {
+    let intensity = shadow.intensity.clamp(0.0, 1.0);
+    if intensity <= 0.0 {
+        return;
+    }
+    let angle = shadow.angle.to_radians();
+    let distance = shadow.distance * scale;
+    let blur = (shadow.blur * scale).max(1.0);
+    let (x, y, w, h) = rect;
+    let (sx, sy) = (x + distance * angle.cos(), y + distance * angle.sin());
+
+    // Only pixels within the blur of the shadow rect are touched
+    let x0 = (sx - blur).floor().max(0.0) as u32;
+    let y0 = (sy - blur).floor().max(0.0) as u32;
+    let x1 = ((sx + w + blur).ceil().max(0.0) as u32).min(width);
+    let y1 = ((sy + h + blur).ceil().max(0.0) as u32).min(height);
+
+    for py in y0..y1 {
+        for px in x0..x1 {
+            let d = rounded_rect_distance(px as f64 + 0.5 - sx, py as f64 + 0.5 - sy, w, h, radius);
+            let falloff = 1.0 - (d / blur).clamp(0.0, 1.0);
+            let alpha = intensity * falloff * falloff;
+            if alpha <= 0.0 {
+                continue;
+            }
+            let idx = ((py * width + px) * 4) as usize;
+            for c in &mut canvas[idx..idx + 3] {
+                *c = (*c as f64 * (1.0 - alpha)).round() as u8;
+            }
+        }
+    }
+}
+
+/// Distance from a point to a rounded rect at the origin (0 inside)
+fn rounded_rect_distance(x: f64, y: f64, width: f64, height: f64, radius: f64) -> f64 {
+    let radius = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+    let qx = (x - width / 2.0).abs() - (width / 2.0 - radius);
+    let qy = (y - height / 2.0).abs() - (height / 2.0 - radius);
+    (qx.max(0.0).hypot(qy.max(0.0)) - radius).max(0.0)
+}
+
+/// Check if a point is inside a rounded rectangle
+pub fn is_inside_rounded_rect(x: i32, y: i32, width: i32, height: i32, radius: i32) -> bool {
+    // Check corners
+    // Top-left corner
+    if x < radius && y < radius {
+        let dx = radius - x;
+        let dy = radius - y;
+        return dx * dx + dy * dy <= radius * radius;
+    }
+    // Top-right corner
+    if x >= width - radius && y < radius {
+        let dx = x - (width - radius - 1);
+        let dy = radius - y;
+        return dx * dx + dy * dy <= radius * radius;
+    }
+    // Bottom-left corner
+    if x < radius && y >= height - radius {
+        let dx = radius - x;
+        let dy = y - (height - radius - 1);
+        return dx * dx + dy * dy <= radius * radius;
+    }
+    // Bottom-right corner
+    if x >= width - radius && y >= height - radius {
+        let dx = x - (width - radius - 1);
+        let dy = y - (height - radius - 1);
+        return dx * dx + dy * dy <= radius * radius;
+    }
+    // Inside the rect (not in corner regions)
+    true
+}
+
+/// Alpha-blend an RGBA source pixel over an opaque destination pixel
+fn blend_pixel(dest: &mut [u8], src: &[u8]) {
+    let alpha = src[3] as f64 / 255.0;
+    for i in 0..3 {
+        dest[i] = (src[i] as f64 * alpha + dest[i] as f64 * (1.0 - alpha)).round() as u8;
+    }
+    dest[3] = 255;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::schema::{AspectRatio, Padding};
+
+    #[test]
+    fn test_compose_on_background() {
+        let config = ProjectConfig {
+            background: Background::Solid {
+                color: "#FF0000".to_string(),
+            },
+            padding: Padding {
+                top: 10.0,
+                right: 10.0,
+                bottom: 10.0,
+                left: 10.0,
+            },
+            output_aspect_ratio: AspectRatio { x: 2, y: 1 },
+            ..Default::default()
+        };
+
+        // 100x100 content, 10px padding, then widened to 2:1
+        let placement = place_content(100, 100, &config);
+        assert_eq!(
+            placement,
+            ContentPlacement {
+                canvas_width: 240,
+                canvas_height: 120,
+                x: 70,
+                y: 10,
+            }
+        );
+
+        let content = vec![255u8; 100 * 100 * 4];
+        let (canvas, width, height) = compose_on_background(&content, 100, 100, &config);
+        assert_eq!((width, height), (240, 120));
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * width + x) * 4) as usize;
+            [canvas[i], canvas[i + 1], canvas[i + 2], canvas[i + 3]]
+        };
+        // Background far from the shadow, and the content itself
+        assert_eq!(pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(120, 60), [255, 255, 255, 255]);
+        // The shadow falls below the content (angle 90°)
+        let below = pixel(120, 112);
+        assert!(below[0] < 255 && below[1] == 0);
+    }
+}
@@ -0,0 +1,501 @@
+//! Encoder benchmark
+//!
+//! Encodes a short synthetic clip (FFmpeg's `testsrc2` pattern) with every
+//! encoder/preset combination this machine's FFmpeg supports and reports how
+//! fast each ran and how large its output was. Hardware encoders that FFmpeg
+//! lists but can't open (no GPU, missing driver) are reported as failed rather
+//! than aborting the run.
+//!
+//! Every encode targets the same bitrate (what a Medium quality export would
+//! use), so presets differ only in how much work they put into it. The clip
+//! is rendered once up front and the time spent decoding it is subtracted, so
+//! only the encode is timed. The H.264 encoder picked is saved, and MP4
+//! exports use it when it's a hardware encoder.
+
+use crate::export::types::{ExportError, ExportQuality};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Encodes at least this many times faster than real time are considered
+/// fast enough, and then the one putting the most work into the same
+/// bitrate wins
+const MIN_REALTIME_FACTOR: f64 = 2.0;
+
+/// An encoder and the presets worth comparing for it
+struct Candidate {
+    encoder: &'static str,
+    codec: &'static str,
+    hardware: bool,
+    extension: &'static str,
+    /// (label, encoder arguments)
+    presets: &'static [(&'static str, &'static [&'static str])],
+}
+
+const CANDIDATES: &[Candidate] = &[
+    Candidate {
+        encoder: "libx264",
+        codec: "h264",
+        hardware: false,
+        extension: "mp4",
+        presets: &[
+            ("ultrafast", &["-preset", "ultrafast"]),
+            ("veryfast", &["-preset", "veryfast"]),
+            ("medium", &["-preset", "medium"]),
+        ],
+    },
+    Candidate {
+        encoder: "libx265",
+        codec: "hevc",
+        hardware: false,
+        extension: "mp4",
+        presets: &[
+            ("ultrafast", &["-preset", "ultrafast"]),
+            ("medium", &["-preset", "medium"]),
+        ],
+    },
+    Candidate {
+        encoder: "libvpx-vp9",
+        codec: "vp9",
+        hardware: false,
+        extension: "webm",
+        presets: &[("realtime", &["-deadline", "realtime", "-cpu-used", "8"])],
+    },
+    Candidate {
+        encoder: "h264_videotoolbox",
+        codec: "h264",
+        hardware: true,
+        extension: "mp4",
+        presets: &[("default", &[])],
+    },
+    Candidate {
+        encoder: "hevc_videotoolbox",
+        codec: "hevc",
+        hardware: true,
+        extension: "mp4",
+        presets: &[("default", &["-tag:v", "hvc1"])],
+    },
+    Candidate {
+        encoder: "h264_nvenc",
+        codec: "h264",
+        hardware: true,
+        extension: "mp4",
+        presets: &[("p1", &["-preset", "p1"]), ("p4", &["-preset", "p4"])],
+    },
+    Candidate {
+        encoder: "hevc_nvenc",
+        codec: "hevc",
+        hardware: true,
+        extension: "mp4",
+        presets: &[("p4", &["-preset", "p4"])],
+    },
+    Candidate {
+        encoder: "h264_qsv",
+        codec: "h264",
+        hardware: true,
+        extension: "mp4",
+        presets: &[
+            ("veryfast", &["-preset", "veryfast"]),
+            ("medium", &["-preset", "medium"]),
+        ],
+    },
+    Candidate {
+        encoder: "h264_amf",
+        codec: "h264",
+        hardware: true,
+        extension: "mp4",
+        presets: &[
+            ("speed", &["-quality", "speed"]),
+            ("balanced", &["-quality", "balanced"]),
+        ],
+    },
+];
+
+/// Synthetic clip to encode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkConfig {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub duration_ms: u64,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            fps: 30,
+            duration_ms: 5000,
+        }
+    }
+}
+
+/// Outcome of encoding the clip with one encoder/preset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    pub encoder: String,
+    pub preset: String,
+    /// "h264", "hevc" or "vp9"
+    pub codec: String,
+    pub hardware: bool,
+    /// Frames encoded per second of wall-clock time
+    pub encode_fps: f64,
+    /// How many times faster than real time the clip was encoded
+    pub realtime_factor: f64,
+    pub output_bytes: u64,
+    pub bitrate_kbps: f64,
+    /// Why the encoder couldn't be used, if it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BenchmarkResult {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Results for every available encoder/preset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub config: BenchmarkConfig,
+    pub results: Vec<BenchmarkResult>,
+    /// Index into `results` of the suggested encoder/preset
+    pub recommended: Option<usize>,
+}
+
+/// Encoder names listed by `ffmpeg -encoders`
+pub fn parse_encoders(output: &str) -> Vec<String> {
+    // Entries look like " V....D libx264   libx264 H.264 / AVC ..." after a
+    // legend terminated by " ------"
+    output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("---"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let flags = fields.next()?;
+            let name = fields.next()?;
+            (flags.len() == 6 && flags.starts_with('V')).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Pick the H.264 encode (what MP4 exports use) that is fast enough and
+/// slowest, as at the same bitrate the extra time goes into quality, or the
+/// fastest one if none are fast enough
+pub fn recommend(results: &[BenchmarkResult]) -> Option<usize> {
+    let succeeded = || {
+        results
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.succeeded() && r.codec == "h264")
+    };
+
+    succeeded()
+        .filter(|(_, r)| r.realtime_factor >= MIN_REALTIME_FACTOR)
+        .min_by(|a, b| a.1.realtime_factor.total_cmp(&b.1.realtime_factor))
+        .or_else(|| succeeded().max_by(|a, b| a.1.encode_fps.total_cmp(&b.1.encode_fps)))
+        .map(|(i, _)| i)
+}
+
+/// Bitrate every encode targets, in kbit/s
+fn target_bitrate_kbps(config: &BenchmarkConfig) -> u64 {
+    ExportQuality::Medium.bitrate_kbps(config.width, config.height, config.fps)
+}
+
+/// Benchmark every encoder/preset FFmpeg supports on this machine, stopping
+/// with `Cancelled` between encodes once `cancel` is set
+pub fn run_benchmark(
+    config: &BenchmarkConfig,
+    cancel: &AtomicBool,
+) -> Result<BenchmarkReport, ExportError> {
+    if config.width == 0 || config.height == 0 || config.fps == 0 || config.duration_ms == 0 {
+        return Err(ExportError::InvalidConfig(
+            "Benchmark clip dimensions, frame rate and duration must be positive".to_string(),
+        ));
+    }
+
+    let available = available_encoders()?;
+    let scratch = tempfile::tempdir()?;
+    let source = scratch.path().join("source.mkv");
+    render_source(config, &source)?;
+    let decode_secs = decode_time(&source)?;
+
+    let mut results = Vec::new();
+    for candidate in CANDIDATES
+        .iter()
+        .filter(|c| available.iter().any(|name| name == c.encoder))
+    {
+        for (label, args) in candidate.presets {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(ExportError::Cancelled);
+            }
+            let output = scratch.path().join(format!(
+                "{}-{}.{}",
+                candidate.encoder, label, candidate.extension
+            ));
+            let result =
+                encode_clip(config, &source, decode_secs, candidate, label, args, &output);
+            match &result.error {
+                None => tracing::info!(
+                    "Benchmark {} {}: {:.1} fps, {} bytes",
+                    result.encoder,
+                    result.preset,
+                    result.encode_fps,
+                    result.output_bytes
+                ),
+                Some(e) => tracing::info!(
+                    "Benchmark {} {} failed: {}",
+                    result.encoder,
+                    result.preset,
+                    e
+                ),
+            }
+            results.push(result);
+            let _ = std::fs::remove_file(&output);
+        }
+    }
+
+    let recommended = recommend(&results);
+    Ok(BenchmarkReport {
+        config: config.clone(),
+        results,
+        recommended,
+    })
+}
+
+/// Path of the saved encoder choice
+pub fn choice_path() -> Result<PathBuf, ExportError> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        ExportError::InvalidConfig("Could not determine data directory".to_string())
+    })?;
+    Ok(data_dir.join("Open ScreenStudio").join("encoder.json"))
+}
+
+/// The encoder MP4 exports should use, as picked by the last benchmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderChoice {
+    pub encoder: String,
+    pub hardware: bool,
+}
+
+impl EncoderChoice {
+    /// The report's recommendation, if it has one
+    pub fn from_report(report: &BenchmarkReport) -> Option<Self> {
+        let result = &report.results[report.recommended?];
+        Some(Self {
+            encoder: result.encoder.clone(),
+            hardware: result.hardware,
+        })
+    }
+}
+
+/// Save the encoder a benchmark picked
+pub fn save_choice(path: &Path, choice: &EncoderChoice) -> Result<(), ExportError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(choice)
+        .map_err(|e| ExportError::InvalidConfig(e.to_string()))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Hardware H.264 encoder saved by the last benchmark, if it picked one.
+/// Software picks aren't returned: libx264 exports keep their quality's
+/// preset.
+pub fn saved_hardware_encoder() -> Option<String> {
+    let content = std::fs::read_to_string(choice_path().ok()?).ok()?;
+    let choice: EncoderChoice = serde_json::from_str(&content).ok()?;
+    choice.hardware.then_some(choice.encoder)
+}
+
+/// Render the synthetic clip losslessly, so every encoder reads the same
+/// frames and generating them isn't timed
+fn render_source(config: &BenchmarkConfig, output: &Path) -> Result<(), ExportError> {
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-v", "error", "-f", "lavfi", "-i"])
+        .arg(format!(
+            "testsrc2=size={}x{}:rate={}",
+            config.width, config.height, config.fps
+        ))
+        .args([
+            "-t",
+            &format!("{:.3}", config.duration_ms as f64 / 1000.0),
+            "-pix_fmt",
+            "yuv420p",
+            "-c:v",
+            "ffv1",
+        ])
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+    if !status.success() {
+        return Err(ExportError::Ffmpeg(
+            "Could not render the benchmark clip".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Seconds FFmpeg takes to decode `source` without encoding it, which is
+/// taken off every encode's time
+fn decode_time(source: &Path) -> Result<f64, ExportError> {
+    let started = Instant::now();
+    let status = Command::new("ffmpeg")
+        .args(["-v", "error", "-i"])
+        .arg(source)
+        .args(["-f", "null", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+    if !status.success() {
+        return Err(ExportError::Ffmpeg(
+            "Could not decode the benchmark clip".to_string(),
+        ));
+    }
+    Ok(started.elapsed().as_secs_f64())
+}
+
+fn available_encoders() -> Result<Vec<String>, ExportError> {
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-encoders"])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+    if !output.status.success() {
+        return Err(ExportError::Ffmpeg(
+            "Could not list FFmpeg encoders".to_string(),
+        ));
+    }
+    Ok(parse_encoders(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn encode_clip(
+    config: &BenchmarkConfig,
+    source: &Path,
+    decode_secs: f64,
+    candidate: &Candidate,
+    preset: &str,
+    encoder_args: &[&str],
+    output: &Path,
+) -> BenchmarkResult {
+    let mut result = BenchmarkResult {
+        encoder: candidate.encoder.to_string(),
+        preset: preset.to_string(),
+        codec: candidate.codec.to_string(),
+        hardware: candidate.hardware,
+        encode_fps: 0.0,
+        realtime_factor: 0.0,
+        output_bytes: 0,
+        bitrate_kbps: 0.0,
+        error: None,
+    };
+
+    let duration_secs = config.duration_ms as f64 / 1000.0;
+    let bitrate = format!("{}k", target_bitrate_kbps(config));
+    let started = Instant::now();
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-v", "error", "-i"])
+        .arg(source)
+        .args(["-an", "-pix_fmt", "yuv420p"])
+        .args(["-c:v", candidate.encoder, "-b:v", &bitrate])
+        .args(encoder_args)
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output();
+    let elapsed = (started.elapsed().as_secs_f64() - decode_secs).max(1e-3);
+
+    match status {
+        Ok(out) if out.status.success() => {
+            let frames = duration_secs * config.fps as f64;
+            result.encode_fps = frames / elapsed;
+            result.realtime_factor = duration_secs / elapsed;
+            result.output_bytes = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
+            result.bitrate_kbps = result.output_bytes as f64 * 8.0 / 1000.0 / duration_secs;
+        }
+        Ok(out) => {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            result.error = Some(
+                stderr
+                    .lines()
+                    .last()
+                    .unwrap_or("Encoder failed")
+                    .trim()
+                    .to_string(),
+            );
+        }
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(
+        encoder: &str,
+        realtime_factor: f64,
+        output_bytes: u64,
+        failed: bool,
+    ) -> BenchmarkResult {
+        BenchmarkResult {
+            encoder: encoder.to_string(),
+            preset: "default".to_string(),
+            codec: if encoder.starts_with("hevc") { "hevc" } else { "h264" }.to_string(),
+            hardware: false,
+            encode_fps: realtime_factor * 30.0,
+            realtime_factor,
+            output_bytes,
+            bitrate_kbps: 0.0,
+            error: failed.then(|| "Cannot load nvcuda.dll".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_encoders() {
+        let output = "Encoders:
+ V..... = Video
+ A..... = Audio
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ V....D h264_videotoolbox    VideoToolbox H.264 Encoder (codec h264)
+ A....D aac                  AAC (Advanced Audio Coding)
+";
+        assert_eq!(parse_encoders(output), vec!["libx264", "h264_videotoolbox"]);
+    }
+
+    #[test]
+    fn test_recommend_prefers_slowest_fast_enough_h264_encode() {
+        let results = vec![
+            result("ultrafast", 12.0, 3_000_000, false),
+            result("medium", 2.5, 3_000_000, false),
+            result("slow", 0.8, 3_000_000, false),
+            result("nvenc", 40.0, 3_000_000, true),
+            result("hevc_medium", 2.1, 3_000_000, false),
+        ];
+        assert_eq!(recommend(&results), Some(1));
+
+        // Nothing fast enough: take the fastest
+        let results = vec![result("a", 0.5, 1, false), result("b", 1.5, 2, false)];
+        assert_eq!(recommend(&results), Some(1));
+
+        assert_eq!(recommend(&[result("nvenc", 40.0, 1, true)]), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Export chapters
+//!
+//! Project markers become chapters in exported MP4s and can be formatted as a
+//! YouTube description timestamp list. Markers are in source time, so when an
+//! export applies edits they are first remapped through the kept segments.
+
+use crate::export::archive::{build_chapters_metadata, probe_duration_ms};
+use crate::export::types::{ExportError, ExportFormat, ExportOptions, ExportSegment};
+use crate::project::bundle::read_markers;
+use crate::project::schema::Marker;
+use std::path::Path;
+use std::process::Command;
+
+/// Shortest chapter YouTube accepts
+const YOUTUBE_MIN_CHAPTER_MS: f64 = 10_000.0;
+
+/// Fewest chapters YouTube needs before it shows them
+const YOUTUBE_MIN_CHAPTERS: usize = 3;
+
+/// Map markers from source time to output time.
+///
+/// Markers inside cut regions are dropped; markers inside a sped-up or
+/// slowed-down segment are scaled with it.
+pub fn remap_markers(markers: &[Marker], segments: &[ExportSegment]) -> Vec<Marker> {
+    markers
+        .iter()
+        .filter_map(|marker| {
+            let mut output_start = 0.0;
+            for segment in segments {
+                let start = segment.source_start_ms as f64;
+                let end = segment.source_end_ms as f64;
+                if marker.time >= start && marker.time < end {
+                    return Some(Marker {
+                        time: output_start + (marker.time - start) / segment.time_scale,
+                        ..marker.clone()
+                    });
+                }
+                output_start += segment.output_duration_ms() as f64;
+            }
+            None
+        })
+        .collect()
+}
+
+/// Rewrite an MP4 in place with one chapter per marker (no re-encoding)
+pub fn embed_chapters(video_path: &Path, markers: &[Marker], duration_ms: f64) -> Result<(), ExportError> {
+    let metadata_path = video_path.with_extension("chapters.ffmeta");
+    let temp_path = video_path.with_extension("chapters.mp4");
+    std::fs::write(&metadata_path, build_chapters_metadata(markers, duration_ms))?;
+
+    let output = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-i")
+        .arg(video_path)
+        .args(["-f", "ffmetadata", "-i"])
+        .arg(&metadata_path)
+        .args(["-map", "0", "-map_chapters", "1", "-c", "copy", "-movflags", "+faststart"])
+        .arg(&temp_path)
+        .output();
+
+    let _ = std::fs::remove_file(&metadata_path);
+    let output = output.map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(ExportError::Ffmpeg(format!(
+            "Failed to add chapters: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    std::fs::rename(&temp_path, video_path)?;
+    Ok(())
+}
+
+/// Add the project's markers as chapters to a finished export.
+///
+/// Only applies to MP4 exports with chapters enabled. Failures are logged
+/// rather than returned, since the export itself already succeeded.
+pub fn embed_project_chapters(project_dir: &Path, options: &ExportOptions, segments: Option<&[ExportSegment]>) {
+    if options.format != ExportFormat::Mp4 || !options.include_chapters {
+        return;
+    }
+
+    let markers = match read_markers(project_dir) {
+        Ok(markers) => markers,
+        Err(e) => {
+            tracing::warn!("Failed to read markers, exporting without chapters: {}", e);
+            return;
+        }
+    };
+    let markers = match segments {
+        Some(segments) => remap_markers(&markers, segments),
+        None => markers,
+    };
+    if markers.is_empty() {
+        return;
+    }
+
+    let output_path = Path::new(&options.output_path);
+    let result = probe_duration_ms(output_path)
+        .and_then(|duration_ms| embed_chapters(output_path, &markers, duration_ms));
+    match result {
+        Ok(()) => tracing::info!("Added {} chapters to {:?}", markers.len(), output_path),
+        Err(e) => tracing::warn!("Failed to add chapters to export: {}", e),
+    }
+}
+
+/// Format milliseconds as `m:ss`, or `h:mm:ss` past an hour
+fn format_timestamp(ms: f64) -> String {
+    let total_secs = (ms / 1000.0).floor().max(0.0) as u64;
+    let (hours, minutes, seconds) = (total_secs / 3600, (total_secs / 60) % 60, total_secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Build a YouTube description chapter list (`0:00 Intro` per line).
+///
+/// YouTube only shows chapters when the first starts at 0:00, there are at
+/// least three, and each is at least ten seconds long. The first marker is
+/// moved to 0:00 and markers too close to the previous one are dropped; an
+/// empty string is returned if fewer than three chapters remain.
+pub fn youtube_description(markers: &[Marker], duration_ms: f64) -> String {
+    let mut sorted: Vec<&Marker> = markers
+        .iter()
+        .filter(|m| m.time >= 0.0 && m.time < duration_ms)
+        .collect();
+    sorted.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+    let mut chapters: Vec<(f64, &str)> = Vec::new();
+    for marker in sorted {
+        match chapters.last() {
+            None => chapters.push((0.0, &marker.label)),
+            Some((last, _)) if marker.time - last >= YOUTUBE_MIN_CHAPTER_MS => {
+                chapters.push((marker.time, &marker.label))
+            }
+            _ => {}
+        }
+    }
+
+    // The last chapter runs to the end of the video
+    if chapters.last().is_some_and(|(last, _)| duration_ms - last < YOUTUBE_MIN_CHAPTER_MS) {
+        chapters.pop();
+    }
+    if chapters.len() < YOUTUBE_MIN_CHAPTERS {
+        return String::new();
+    }
+
+    chapters
+        .iter()
+        .map(|(time, label)| format!("{} {}", format_timestamp(*time), label))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(time: f64, label: &str) -> Marker {
+        Marker {
+            id: label.to_string(),
+            time,
+            label: label.to_string(),
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_remap_markers_through_edits() {
+        let segments = vec![
+            ExportSegment {
+                source_start_ms: 0,
+                source_end_ms: 10_000,
+                time_scale: 1.0,
+            },
+            // 10-20s cut, 20-40s played at 2x
+            ExportSegment {
+                source_start_ms: 20_000,
+                source_end_ms: 40_000,
+                time_scale: 2.0,
+            },
+        ];
+        let markers = vec![marker(5_000.0, "A"), marker(15_000.0, "Cut"), marker(30_000.0, "B")];
+        let remapped = remap_markers(&markers, &segments);
+
+        assert_eq!(remapped.len(), 2);
+        assert_eq!(remapped[0].time, 5_000.0);
+        assert_eq!(remapped[1].label, "B");
+        assert_eq!(remapped[1].time, 15_000.0);
+    }
+
+    #[test]
+    fn test_youtube_description_follows_youtube_rules() {
+        let markers = vec![
+            marker(1_500.0, "Intro"),
+            marker(12_000.0, "Setup"),
+            marker(15_000.0, "Too close"),
+            marker(75_000.0, "Demo"),
+            marker(3_700_000.0, "Wrap-up"),
+        ];
+        assert_eq!(
+            youtube_description(&markers, 3_800_000.0),
+            "0:00 Intro\n0:12 Setup\n1:15 Demo\n1:01:40 Wrap-up"
+        );
+
+        // Too few chapters for YouTube to show
+        assert_eq!(youtube_description(&markers[..2], 60_000.0), "");
+    }
+}
@@ -0,0 +1,403 @@
+//! Quick highlight clips
+//!
+//! Cuts a short MP4 or GIF of a moment in a recording without going through
+//! the full export pipeline: no edits, cursor overlay or background are
+//! applied. The `Copy` preset doesn't re-encode the video, so the clip starts
+//! at the keyframe before the requested time and the audio is cut from that
+//! keyframe; `Draft` re-encodes with the fastest settings for frame-exact
+//! cuts. Times are in the recording's own timeline (session 0), not the
+//! edited output. Audio is always re-encoded so it can be cut to the sample:
+//! the microphone and system audio are lined up with the video as export
+//! lines them up and mixed into one track.
+
+use crate::export::alignment::AudioAlignment;
+use crate::export::downmix::{self, probe_channels, MixLayout};
+use crate::export::gif::{self, GifParams, GIF_MAX_FPS};
+use crate::export::sessions::SessionMedia;
+use crate::export::types::{ExportError, ExportQuality};
+use crate::project::encryption::readable_path;
+use crate::recorder::manifest::{resolve_file, resolve_primary_video, FileKind, RecordingManifest};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Widest GIF clip, matching the GIF export path
+const GIF_MAX_WIDTH: u32 = 800;
+
+/// How a clip is produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipPreset {
+    /// Video stream copy into MP4 (instant, keyframe-aligned start)
+    Copy,
+    /// Fast re-encode into MP4 at draft quality
+    Draft,
+    /// Animated GIF (video only)
+    Gif,
+}
+
+impl ClipPreset {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ClipPreset::Copy | ClipPreset::Draft => "mp4",
+            ClipPreset::Gif => "gif",
+        }
+    }
+}
+
+/// A finished clip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipResult {
+    pub path: String,
+    pub preset: ClipPreset,
+    pub start_ms: f64,
+    pub end_ms: f64,
+    pub size_bytes: u64,
+}
+
+/// Default clip path next to the bundle: `<bundle> 0m12s-0m18s.<ext>`
+pub fn default_clip_path(
+    project_dir: &Path,
+    start_ms: f64,
+    end_ms: f64,
+    preset: ClipPreset,
+) -> PathBuf {
+    let stem = project_dir
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Recording".to_string());
+    let name = format!(
+        "{} {}-{}.{}",
+        stem,
+        format_timestamp(start_ms),
+        format_timestamp(end_ms),
+        preset.extension()
+    );
+    project_dir.parent().unwrap_or(project_dir).join(name)
+}
+
+fn format_timestamp(ms: f64) -> String {
+    let secs = (ms / 1000.0).floor() as u64;
+    format!("{}m{:02}s", secs / 60, secs % 60)
+}
+
+/// Cut `start_ms..end_ms` of a bundle's recording into `output`
+///
+/// `mic_offset_ms` is the project's microphone offset (positive delays the
+/// microphone), applied so the clip's audio matches the editor. The result's
+/// `start_ms` is where the clip really starts, which for `Copy` is the
+/// keyframe before the requested time.
+pub fn create_clip(
+    project_dir: &Path,
+    start_ms: f64,
+    end_ms: f64,
+    preset: ClipPreset,
+    mic_offset_ms: f64,
+    output: &Path,
+) -> Result<ClipResult, ExportError> {
+    if !(start_ms >= 0.0 && end_ms > start_ms) {
+        return Err(ExportError::InvalidConfig(format!(
+            "Invalid clip range {:.0}ms-{:.0}ms",
+            start_ms, end_ms
+        )));
+    }
+
+    let recording_dir = project_dir.join("recording");
+    let manifest = RecordingManifest::read(&recording_dir);
+    let video = resolve_primary_video(&recording_dir, manifest.as_ref(), 0).ok_or_else(|| {
+        ExportError::BundleNotFound(format!("Video file not found in {:?}", recording_dir))
+    })?;
+    let video = readable_path(&video)?;
+
+    // A stream copy can only start on a keyframe, so the audio has to be
+    // cut from the same point
+    let start_ms = match preset {
+        ClipPreset::Copy => keyframe_at_or_before(&video, start_ms).unwrap_or(start_ms),
+        _ => start_ms,
+    };
+
+    let mut tracks = Vec::new();
+    if preset != ClipPreset::Gif {
+        let media = SessionMedia::load(&recording_dir);
+        let kinds = [(FileKind::MicAudio, mic_offset_ms), (FileKind::SystemAudio, 0.0)];
+        for (kind, offset_ms) in kinds {
+            if let Some(path) = resolve_file(&recording_dir, manifest.as_ref(), kind, 0) {
+                let path = readable_path(&path)?;
+                let channels = probe_channels(&path);
+                tracks.push(ClipAudio {
+                    path,
+                    offset_ms,
+                    alignment: media.alignment(kind, 0)?,
+                    channels,
+                });
+            }
+        }
+    }
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let duration = format!("{:.3}", (end_ms - start_ms) / 1000.0);
+    let mut command = Command::new("ffmpeg");
+    command
+        .args([
+            "-y",
+            "-v",
+            "error",
+            "-ss",
+            &format!("{:.3}", start_ms / 1000.0),
+            "-t",
+            &duration,
+            "-i",
+        ])
+        .arg(&video);
+
+    for track in &tracks {
+        command
+            .args(audio_input_args(start_ms, track.offset_ms, &track.alignment))
+            .args(downmix::input_args(track.channels))
+            .arg("-t")
+            .arg(&duration)
+            .arg("-i")
+            .arg(&track.path);
+    }
+    command.args(["-map", "0:v:0"]);
+    if !tracks.is_empty() {
+        let (filter, label) = audio_filter(&tracks);
+        command
+            .args(["-filter_complex", &filter, "-map", &label])
+            .args(["-c:a", "aac", "-shortest"]);
+    }
+
+    match preset {
+        ClipPreset::Copy => {
+            command.args(["-c:v", "copy", "-avoid_negative_ts", "make_zero"]);
+        }
+        ClipPreset::Draft => {
+            let quality = ExportQuality::Draft;
+            command.args([
+                "-vf",
+                &format!(
+                    "scale=trunc(iw*{0}/2)*2:trunc(ih*{0}/2)*2",
+                    quality.resolution_scale()
+                ),
+                "-c:v",
+                "libx264",
+                "-preset",
+                quality.h264_preset(),
+                "-crf",
+                &quality.crf().to_string(),
+                "-pix_fmt",
+                "yuv420p",
+            ]);
+        }
+        ClipPreset::Gif => {
+            let (width, _, _, _) = crate::export::ffmpeg::VideoDecoder::probe_video(&video)?;
+            let params = GifParams {
+                fps: GIF_MAX_FPS,
+                width: width.min(GIF_MAX_WIDTH),
+                max_colors: 256,
+            };
+            command.args(["-vf", &gif::gif_filter(&params), "-loop", "0"]);
+        }
+    }
+    if preset != ClipPreset::Gif {
+        command.args(["-movflags", "+faststart"]);
+    }
+    command.arg(output);
+
+    tracing::info!(
+        "Creating {:?} clip {:.0}ms-{:.0}ms of {:?}",
+        preset,
+        start_ms,
+        end_ms,
+        project_dir
+    );
+    let result = command
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+    if !result.status.success() {
+        return Err(ExportError::Ffmpeg(format!(
+            "Clip creation failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+
+    Ok(ClipResult {
+        path: output.to_string_lossy().to_string(),
+        preset,
+        start_ms,
+        end_ms,
+        size_bytes: std::fs::metadata(output)?.len(),
+    })
+}
+
+/// An audio track cut into a clip
+struct ClipAudio {
+    path: PathBuf,
+    /// Project offset (positive delays the track)
+    offset_ms: f64,
+    alignment: AudioAlignment,
+    channels: u32,
+}
+
+/// Seek (or delay) arguments for an audio input so it lines up with the
+/// video cut at `start_ms`
+fn audio_input_args(start_ms: f64, offset_ms: f64, alignment: &AudioAlignment) -> Vec<String> {
+    let audio_start_ms = alignment.audio_position_ms(start_ms - offset_ms);
+    if audio_start_ms >= 0.0 {
+        vec!["-ss".to_string(), format!("{:.3}", audio_start_ms / 1000.0)]
+    } else {
+        // The track starts partway into the clip
+        vec![
+            "-itsoffset".to_string(),
+            format!("{:.3}", -audio_start_ms / alignment.tempo / 1000.0),
+        ]
+    }
+}
+
+/// Filter graph correcting drift on each audio input (inputs 1.. after the
+/// video) and mixing them, with the label of the output to map
+fn audio_filter(tracks: &[ClipAudio]) -> (String, String) {
+    let mut filters = Vec::new();
+    let mut labels = Vec::new();
+    for (i, track) in tracks.iter().enumerate() {
+        let chain: Vec<String> = track
+            .alignment
+            .tempo_filter()
+            .into_iter()
+            .chain(MixLayout::Stereo.convert_filter(track.channels))
+            .collect();
+        let chain = if chain.is_empty() { "anull".to_string() } else { chain.join(",") };
+        filters.push(format!("[{}:a]{}[a{}]", i + 1, chain, i));
+        labels.push(format!("[a{}]", i));
+    }
+    if labels.len() == 1 {
+        return (filters.remove(0), labels.remove(0));
+    }
+    filters.push(format!(
+        "{}amix=inputs={}:duration=longest[aout]",
+        labels.join(""),
+        labels.len()
+    ));
+    (filters.join(";"), "[aout]".to_string())
+}
+
+/// Time of the last video keyframe at or before `start_ms`, which is where a
+/// stream copy seeking to `start_ms` really starts
+fn keyframe_at_or_before(video: &Path, start_ms: f64) -> Option<f64> {
+    let start = start_ms / 1000.0;
+    // Keyframes are rarely more than a few seconds apart in recordings
+    let interval = format!("{:.3}%{:.3}", (start - 30.0).max(0.0), start + 0.001);
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-skip_frame",
+            "nokey",
+            "-read_intervals",
+            &interval,
+            "-show_entries",
+            "frame=pts_time",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(video)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    last_keyframe_ms(&String::from_utf8_lossy(&output.stdout), start_ms)
+}
+
+/// Latest of ffprobe's keyframe times (seconds, one per line) at or before
+/// `start_ms`
+fn last_keyframe_ms(pts_times: &str, start_ms: f64) -> Option<f64> {
+    pts_times
+        .lines()
+        .filter_map(|line| line.trim().trim_end_matches(',').parse::<f64>().ok())
+        .map(|secs| secs * 1000.0)
+        .filter(|&ms| ms <= start_ms + 0.5)
+        .max_by(f64::total_cmp)
+        .map(|ms| ms.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_clip_path() {
+        let path = default_clip_path(
+            Path::new("/videos/Demo.osp"),
+            12_400.0,
+            78_000.0,
+            ClipPreset::Gif,
+        );
+        assert_eq!(path, PathBuf::from("/videos/Demo 0m12s-1m18s.gif"));
+    }
+
+    #[test]
+    fn test_audio_input_args_follow_offset() {
+        let recorded = AudioAlignment::default();
+        assert_eq!(audio_input_args(5000.0, 0.0, &recorded), vec!["-ss", "5.000"]);
+        // Delayed mic: read from earlier in the file
+        assert_eq!(audio_input_args(5000.0, 1500.0, &recorded), vec!["-ss", "3.500"]);
+        // Mic delayed past the clip start: it starts 1s into the clip
+        assert_eq!(audio_input_args(500.0, 1500.0, &recorded), vec!["-itsoffset", "1.000"]);
+
+        // A mic that started 100ms after the video, as export corrects it
+        let late = AudioAlignment {
+            lead_ms: 100.0,
+            tempo: 1.0,
+        };
+        assert_eq!(audio_input_args(5000.0, 0.0, &late), vec!["-ss", "4.900"]);
+    }
+
+    #[test]
+    fn test_audio_filter_mixes_mic_and_system() {
+        let track = |tempo: f64, channels: u32| ClipAudio {
+            path: PathBuf::from("a.m4a"),
+            offset_ms: 0.0,
+            alignment: AudioAlignment { lead_ms: 0.0, tempo },
+            channels,
+        };
+
+        let (filter, label) = audio_filter(&[track(1.0, 1)]);
+        assert_eq!(filter, "[1:a]anull[a0]");
+        assert_eq!(label, "[a0]");
+
+        // Drifted mic with 5.1 system audio
+        let (filter, label) = audio_filter(&[track(1.001, 1), track(1.0, 6)]);
+        assert_eq!(
+            filter,
+            "[1:a]atempo=1.001000[a0];[2:a]aformat=channel_layouts=stereo[a1];\
+             [a0][a1]amix=inputs=2:duration=longest[aout]"
+        );
+        assert_eq!(label, "[aout]");
+    }
+
+    #[test]
+    fn test_last_keyframe_before_start() {
+        let probed = "0.000000\n2.000000\n4.000000\n";
+        assert_eq!(last_keyframe_ms(probed, 3500.0), Some(2000.0));
+        assert_eq!(last_keyframe_ms(probed, 4000.0), Some(4000.0));
+        assert_eq!(last_keyframe_ms("", 3500.0), None);
+    }
+
+    #[test]
+    fn test_rejects_empty_range() {
+        let err = create_clip(
+            Path::new("/nonexistent"),
+            2000.0,
+            2000.0,
+            ClipPreset::Copy,
+            0.0,
+            Path::new("/tmp/x.mp4"),
+        );
+        assert!(matches!(err, Err(ExportError::InvalidConfig(_))));
+    }
+}
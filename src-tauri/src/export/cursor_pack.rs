@@ -0,0 +1,341 @@
+//! Cursor packs
+//!
+//! A cursor pack is a directory of themed cursor PNGs with a `pack.json`
+//! giving each cursor's image and hotspot by kind (arrow, pointer, text, ...).
+//! Installed packs live in the app's data directory and are read from disk
+//! whenever they're listed or used, so a pack that is edited or dropped in
+//! place is picked up without restarting. At export, each recorded cursor
+//! whose kind the pack provides is drawn with the pack's image instead.
+
+use crate::capture::input::types::{CursorInfo, CursorKind};
+use crate::export::pipeline::{load_png_image, CursorImage};
+use crate::export::types::ExportError;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the file describing a pack
+pub const PACK_MANIFEST: &str = "pack.json";
+
+/// One cursor of a pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackCursor {
+    /// PNG file, relative to the pack directory
+    pub image: String,
+    /// Hotspot in the PNG's pixel coordinates
+    pub hotspot_x: f64,
+    pub hotspot_y: f64,
+}
+
+/// Contents of `pack.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackManifest {
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub cursors: HashMap<CursorKind, PackCursor>,
+}
+
+/// An installed cursor pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorPackInfo {
+    /// Directory name, stored in `CursorConfig::pack`
+    pub id: String,
+    pub name: String,
+    pub author: Option<String>,
+    /// Cursor kinds the pack replaces
+    pub kinds: Vec<CursorKind>,
+}
+
+/// Default directory cursor packs are installed to
+pub fn packs_dir() -> Result<PathBuf, ExportError> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        ExportError::InvalidConfig("Could not determine data directory".to_string())
+    })?;
+    Ok(data_dir.join("Open ScreenStudio").join("cursor-packs"))
+}
+
+fn pack_path(dir: &Path, id: &str) -> Result<PathBuf, ExportError> {
+    // IDs are directory names; reject anything that could escape `dir`
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ExportError::InvalidConfig(format!(
+            "Invalid cursor pack id: {}",
+            id
+        )));
+    }
+    Ok(dir.join(id))
+}
+
+/// Read and check a pack's manifest: every image must be inside the pack
+/// and exist
+fn read_manifest(pack_dir: &Path) -> Result<PackManifest, ExportError> {
+    let content = fs::read_to_string(pack_dir.join(PACK_MANIFEST))?;
+    let manifest: PackManifest = serde_json::from_str(&content)
+        .map_err(|e| ExportError::InvalidConfig(format!("Invalid {}: {}", PACK_MANIFEST, e)))?;
+
+    for cursor in manifest.cursors.values() {
+        let image = Path::new(&cursor.image);
+        if image.is_absolute() || image.components().any(|c| c.as_os_str() == "..") {
+            return Err(ExportError::InvalidConfig(format!(
+                "Cursor image outside the pack: {}",
+                cursor.image
+            )));
+        }
+        if !pack_dir.join(image).is_file() {
+            return Err(ExportError::InvalidConfig(format!(
+                "Missing cursor image: {}",
+                cursor.image
+            )));
+        }
+    }
+    Ok(manifest)
+}
+
+fn pack_info(id: String, manifest: PackManifest) -> CursorPackInfo {
+    let mut kinds: Vec<CursorKind> = manifest.cursors.into_keys().collect();
+    kinds.sort();
+    CursorPackInfo {
+        id,
+        name: manifest.name,
+        author: manifest.author,
+        kinds,
+    }
+}
+
+/// List the packs installed in `dir`, sorted by name. Invalid packs are
+/// skipped.
+pub fn list_packs(dir: &Path) -> Result<Vec<CursorPackInfo>, ExportError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut packs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(id) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+        if !path.is_dir() {
+            continue;
+        }
+        match read_manifest(&path) {
+            Ok(manifest) => packs.push(pack_info(id, manifest)),
+            Err(e) => tracing::warn!("Skipping invalid cursor pack {:?}: {}", path, e),
+        }
+    }
+
+    packs.sort_by_key(|p| p.name.to_lowercase());
+    Ok(packs)
+}
+
+/// Install the pack in `source` into `dir`, replacing an installed pack with
+/// the same ID. Only the manifest and the images it references are copied.
+pub fn install_pack(dir: &Path, source: &Path) -> Result<CursorPackInfo, ExportError> {
+    let manifest = read_manifest(source)?;
+
+    let id: String = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let id = id.trim_matches('-').to_string();
+    let target = pack_path(dir, &id)?;
+
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::create_dir_all(&target)?;
+    fs::copy(source.join(PACK_MANIFEST), target.join(PACK_MANIFEST))?;
+    for cursor in manifest.cursors.values() {
+        let destination = target.join(&cursor.image);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source.join(&cursor.image), destination)?;
+    }
+
+    tracing::info!("Installed cursor pack '{}' ({})", manifest.name, id);
+    Ok(pack_info(id, manifest))
+}
+
+/// Replace recorded cursors with the pack's cursor of the same kind, scaled
+/// to the recorded cursor's height so it matches the display's scale.
+/// Returns how many cursors were replaced.
+pub fn apply_pack(
+    dir: &Path,
+    id: &str,
+    cursor_info: &mut HashMap<String, CursorInfo>,
+    cursor_images: &mut HashMap<String, CursorImage>,
+) -> Result<usize, ExportError> {
+    let pack_dir = pack_path(dir, id)?;
+    let manifest = read_manifest(&pack_dir)?;
+
+    let mut loaded: HashMap<CursorKind, CursorImage> = HashMap::new();
+    let mut replaced = 0;
+    for (cursor_id, info) in cursor_info.iter_mut() {
+        let Some(kind) = info.kind else {
+            continue;
+        };
+        let Some(pack_cursor) = manifest.cursors.get(&kind) else {
+            continue;
+        };
+        let image = match loaded.entry(kind) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(load_png_image(&pack_dir.join(&pack_cursor.image))?)
+            }
+        };
+
+        let scale = info.height as f64 / image.height.max(1) as f64;
+        let width = ((image.width as f64 * scale).round() as u32).max(1);
+        let height = ((image.height as f64 * scale).round() as u32).max(1);
+
+        cursor_images.insert(cursor_id.clone(), resize_image(image, width, height));
+        info.hotspot_x = pack_cursor.hotspot_x * scale;
+        info.hotspot_y = pack_cursor.hotspot_y * scale;
+        info.width = width;
+        info.height = height;
+        replaced += 1;
+    }
+
+    tracing::info!(
+        "Cursor pack '{}' replaced {} cursors",
+        manifest.name,
+        replaced
+    );
+    Ok(replaced)
+}
+
+/// Bilinear resize of an RGBA image
+fn resize_image(image: &CursorImage, width: u32, height: u32) -> CursorImage {
+    if image.width == width && image.height == height {
+        return CursorImage {
+            data: image.data.clone(),
+            width,
+            height,
+        };
+    }
+
+    let (sw, sh) = (image.width.max(1), image.height.max(1));
+    let pixel = |x: u32, y: u32, c: usize| -> f64 {
+        let idx = ((y.min(sh - 1) * sw + x.min(sw - 1)) * 4) as usize + c;
+        image.data.get(idx).copied().unwrap_or(0) as f64
+    };
+
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        let fy = ((y as f64 + 0.5) * sh as f64 / height as f64 - 0.5).max(0.0);
+        let (y0, ty) = (fy.floor() as u32, fy.fract());
+        for x in 0..width {
+            let fx = ((x as f64 + 0.5) * sw as f64 / width as f64 - 0.5).max(0.0);
+            let (x0, tx) = (fx.floor() as u32, fx.fract());
+            for c in 0..4 {
+                let top = pixel(x0, y0, c) * (1.0 - tx) + pixel(x0 + 1, y0, c) * tx;
+                let bottom = pixel(x0, y0 + 1, c) * (1.0 - tx) + pixel(x0 + 1, y0 + 1, c) * tx;
+                data.push((top * (1.0 - ty) + bottom * ty).round() as u8);
+            }
+        }
+    }
+
+    CursorImage {
+        data,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_png(path: &Path, width: u32, height: u32) {
+        let file = fs::File::create(path).unwrap();
+        let mut encoder = png::Encoder::new(file, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&vec![255u8; (width * height * 4) as usize])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_install_list_and_apply_pack() {
+        let source_root = tempfile::tempdir().unwrap();
+        let source = source_root.path().join("Neon Cursors");
+        fs::create_dir_all(&source).unwrap();
+        write_png(&source.join("arrow.png"), 32, 32);
+        fs::write(
+            source.join(PACK_MANIFEST),
+            r#"{"name": "Neon", "cursors": {"arrow": {"image": "arrow.png", "hotspotX": 4, "hotspotY": 2}}}"#,
+        )
+        .unwrap();
+
+        let packs = tempfile::tempdir().unwrap();
+        let installed = install_pack(packs.path(), &source).unwrap();
+        assert_eq!(installed.id, "neon-cursors");
+        assert_eq!(installed.kinds, vec![CursorKind::Arrow]);
+        // Broken packs are skipped when listing
+        fs::create_dir_all(packs.path().join("broken")).unwrap();
+        let listed = list_packs(packs.path()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "Neon");
+
+        let recorded = |kind| CursorInfo {
+            id: String::new(),
+            image_path: String::new(),
+            hotspot_x: 0.0,
+            hotspot_y: 0.0,
+            width: 64,
+            height: 64,
+            kind,
+        };
+        let mut cursor_info = HashMap::from([
+            ("a".to_string(), recorded(Some(CursorKind::Arrow))),
+            ("b".to_string(), recorded(Some(CursorKind::Text))),
+            ("c".to_string(), recorded(None)),
+        ]);
+        let mut cursor_images = HashMap::new();
+        let replaced = apply_pack(
+            packs.path(),
+            "neon-cursors",
+            &mut cursor_info,
+            &mut cursor_images,
+        )
+        .unwrap();
+
+        // Only the arrow is replaced, at the recorded (2x) size
+        assert_eq!(replaced, 1);
+        assert_eq!(cursor_images["a"].width, 64);
+        assert_eq!(cursor_images["a"].data.len(), 64 * 64 * 4);
+        assert_eq!(
+            (cursor_info["a"].hotspot_x, cursor_info["a"].hotspot_y),
+            (8.0, 4.0)
+        );
+        assert!(!cursor_images.contains_key("b"));
+        assert!(apply_pack(
+            packs.path(),
+            "../escape",
+            &mut cursor_info,
+            &mut cursor_images
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Cursor trail rendering
+//!
+//! Draws a fading, tapering trail along the recent cursor path so viewers can
+//! follow fast mouse travel. The trail is built from the same smoothed cursor
+//! positions the cursor itself is drawn at.
+
+use crate::export::annotations::parse_hex_color;
+use crate::export::types::CursorTrail;
+use crate::processing::cursor_smoothing::SmoothedMouseMove;
+
+/// Trail width at its oldest end, relative to the width at the cursor
+const TAIL_WIDTH_RATIO: f64 = 0.3;
+
+/// Draw the trail leading up to `time_ms` onto an RGBA frame.
+///
+/// `raw` draws along the unsmoothed positions, for segments where cursor
+/// smoothing is disabled. Each pixel is blended once with the strongest
+/// segment covering it, so overlapping segments don't build up opacity.
+pub fn draw_cursor_trail(
+    frame: &mut [u8],
+    frame_width: u32,
+    frame_height: u32,
+    cursor: &[SmoothedMouseMove],
+    time_ms: f64,
+    trail: &CursorTrail,
+    raw: bool,
+) {
+    if trail.length_ms <= 0.0 || trail.opacity <= 0.0 {
+        return;
+    }
+    let Some(color) = parse_hex_color(&trail.color) else {
+        return;
+    };
+
+    let start = cursor.partition_point(|m| m.process_time_ms <= time_ms - trail.length_ms);
+    let end = cursor.partition_point(|m| m.process_time_ms <= time_ms);
+    let points: Vec<(f64, f64, f64)> = cursor[start..end]
+        .iter()
+        .map(|m| {
+            let (x, y) = if raw { (m.raw_x, m.raw_y) } else { (m.x, m.y) };
+            // Freshness: 1.0 at the cursor, 0.0 at the end of the trail
+            (x, y, 1.0 - (time_ms - m.process_time_ms) / trail.length_ms)
+        })
+        .collect();
+    if points.len() < 2 {
+        return;
+    }
+
+    let max_radius = trail.width.max(1.0) / 2.0;
+    let pad = max_radius.ceil() as i64 + 1;
+    let min_x = (points.iter().map(|p| p.0).fold(f64::MAX, f64::min) as i64 - pad).max(0);
+    let max_x = (points.iter().map(|p| p.0).fold(f64::MIN, f64::max) as i64 + pad)
+        .min(frame_width as i64 - 1);
+    let min_y = (points.iter().map(|p| p.1).fold(f64::MAX, f64::min) as i64 - pad).max(0);
+    let max_y = (points.iter().map(|p| p.1).fold(f64::MIN, f64::max) as i64 + pad)
+        .min(frame_height as i64 - 1);
+    if min_x > max_x || min_y > max_y {
+        return;
+    }
+
+    // Strongest trail opacity covering each pixel of the bounding box
+    let mask_width = (max_x - min_x + 1) as usize;
+    let mut mask = vec![0.0f64; mask_width * (max_y - min_y + 1) as usize];
+
+    for pair in points.windows(2) {
+        let (ax, ay, a_fresh) = pair[0];
+        let (bx, by, b_fresh) = pair[1];
+        let radius = max_radius * (TAIL_WIDTH_RATIO + (1.0 - TAIL_WIDTH_RATIO) * b_fresh);
+        let (dx, dy) = (bx - ax, by - ay);
+        let len2 = dx * dx + dy * dy;
+
+        let x0 = ((ax.min(bx) - radius).floor() as i64).max(min_x);
+        let x1 = ((ax.max(bx) + radius).ceil() as i64).min(max_x);
+        let y0 = ((ay.min(by) - radius).floor() as i64).max(min_y);
+        let y1 = ((ay.max(by) + radius).ceil() as i64).min(max_y);
+
+        for py in y0..=y1 {
+            for px in x0..=x1 {
+                let (qx, qy) = (px as f64 - ax, py as f64 - ay);
+                let t = if len2 > 0.0 {
+                    ((qx * dx + qy * dy) / len2).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let (ex, ey) = (qx - t * dx, qy - t * dy);
+                if ex * ex + ey * ey <= radius * radius {
+                    let alpha = trail.opacity.min(1.0)
+                        * (a_fresh + (b_fresh - a_fresh) * t).clamp(0.0, 1.0);
+                    let cell =
+                        &mut mask[(py - min_y) as usize * mask_width + (px - min_x) as usize];
+                    *cell = cell.max(alpha);
+                }
+            }
+        }
+    }
+
+    let color_alpha = color[3] as f64 / 255.0;
+    for (i, &alpha) in mask.iter().enumerate() {
+        if alpha <= 0.0 {
+            continue;
+        }
+        let x = min_x as usize + i % mask_width;
+        let y = min_y as usize + i / mask_width;
+        let idx = (y * frame_width as usize + x) * 4;
+        if idx + 3 >= frame.len() {
+            continue;
+        }
+
+        let alpha = alpha * color_alpha;
+        for (dst, &src) in frame[idx..idx + 3].iter_mut().zip(&color[..3]) {
+            *dst = (src as f64 * alpha + *dst as f64 * (1.0 - alpha)).round() as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(time: f64, x: f64) -> SmoothedMouseMove {
+        SmoothedMouseMove {
+            x,
+            y: 10.0,
+            raw_x: x,
+            raw_y: 20.0,
+            cursor_id: String::new(),
+            process_time_ms: time,
+        }
+    }
+
+    fn red(frame: &[u8], width: u32, x: u32, y: u32) -> u8 {
+        frame[((y * width + x) * 4) as usize]
+    }
+
+    #[test]
+    fn test_trail_fades_toward_the_tail() {
+        let (width, height) = (100, 30);
+        // Cursor moving right 10px every 100ms
+        let cursor: Vec<_> = (0..10)
+            .map(|i| point(i as f64 * 100.0, i as f64 * 10.0))
+            .collect();
+        let trail = CursorTrail {
+            length_ms: 400.0,
+            opacity: 1.0,
+            width: 4.0,
+            color: "#FF0000".to_string(),
+        };
+
+        let mut frame = vec![0u8; (width * height * 4) as usize];
+        draw_cursor_trail(&mut frame, width, height, &cursor, 900.0, &trail, false);
+
+        // Strongest at the cursor, fading toward the tail, absent before it
+        assert!(red(&frame, width, 89, 10) > red(&frame, width, 70, 10));
+        assert!(red(&frame, width, 70, 10) > red(&frame, width, 55, 10));
+        assert_eq!(red(&frame, width, 40, 10), 0);
+        // Nothing drawn at the raw positions
+        assert_eq!(red(&frame, width, 80, 20), 0);
+
+        let mut raw_frame = vec![0u8; (width * height * 4) as usize];
+        draw_cursor_trail(&mut raw_frame, width, height, &cursor, 900.0, &trail, true);
+        assert!(red(&raw_frame, width, 80, 20) > 0);
+        assert_eq!(red(&raw_frame, width, 80, 10), 0);
+    }
+}
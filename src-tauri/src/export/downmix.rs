@@ -0,0 +1,127 @@
+//! Channel layouts for mixed audio
+//!
+//! System audio recorded from an external interface can be 5.1 or 7.1.
+//! Feeding that straight into `amix` next to a mono microphone gives a mix
+//! with the voice in one speaker and the surround channels summed at full
+//! level. Instead every track is converted to a common layout first with
+//! `aformat`, which folds surround down with FFmpeg's standard matrix (centre
+//! and surrounds at -3dB, LFE dropped). Exports are stereo unless surround is
+//! kept, in which case every track is brought up to the widest track's
+//! layout.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Channel layout audio tracks are mixed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixLayout {
+    Stereo,
+    /// Surround with this many channels, kept from a surround track
+    Surround(u32),
+}
+
+impl MixLayout {
+    /// Layout for mixing tracks with these channel counts
+    pub fn for_tracks(channels: &[u32], preserve_surround: bool) -> Self {
+        match channels.iter().copied().max() {
+            Some(widest) if preserve_surround && widest > 2 => MixLayout::Surround(widest.min(8)),
+            _ => MixLayout::Stereo,
+        }
+    }
+
+    /// FFmpeg's name for the layout
+    pub fn name(&self) -> &'static str {
+        match self {
+            MixLayout::Stereo => "stereo",
+            MixLayout::Surround(3) => "3.0",
+            MixLayout::Surround(4) => "quad",
+            MixLayout::Surround(5) => "5.0",
+            MixLayout::Surround(6) => "5.1",
+            MixLayout::Surround(7) => "6.1",
+            MixLayout::Surround(_) => "7.1",
+        }
+    }
+
+    /// Encoder bitrate for the layout
+    pub fn bitrate(&self) -> &'static str {
+        match self {
+            MixLayout::Stereo => "192k",
+            MixLayout::Surround(n) if *n <= 6 => "384k",
+            MixLayout::Surround(_) => "512k",
+        }
+    }
+
+    /// Filter converting a track with `channels` channels to this layout, if
+    /// it needs converting. Mono and stereo tracks already mix to stereo.
+    pub fn convert_filter(&self, channels: u32) -> Option<String> {
+        let needed = match self {
+            MixLayout::Stereo => channels > 2,
+            MixLayout::Surround(n) => channels != *n,
+        };
+        needed.then(|| format!("aformat=channel_layouts={}", self.name()))
+    }
+}
+
+/// Input options for a track with `channels` channels. FFmpeg only guesses
+/// the layout of mono and stereo files that don't declare one, and can't
+/// downmix a track without a layout.
+pub fn input_args(channels: u32) -> Vec<String> {
+    if channels > 2 {
+        vec!["-guess_layout_max".to_string(), "8".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Channels in the first audio stream of `path`, assuming stereo if it can't
+/// be probed
+pub fn probe_channels(path: &Path) -> u32 {
+    Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "stream=channels",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+        .unwrap_or(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_surround_is_downmixed_unless_preserved() {
+        // Mono mic with 5.1 system audio
+        let stereo = MixLayout::for_tracks(&[1, 6], false);
+        assert_eq!(stereo, MixLayout::Stereo);
+        assert_eq!(stereo.convert_filter(1), None);
+        assert_eq!(
+            stereo.convert_filter(6).as_deref(),
+            Some("aformat=channel_layouts=stereo")
+        );
+
+        let surround = MixLayout::for_tracks(&[1, 6], true);
+        assert_eq!(surround, MixLayout::Surround(6));
+        assert_eq!(surround.bitrate(), "384k");
+        assert_eq!(
+            surround.convert_filter(1).as_deref(),
+            Some("aformat=channel_layouts=5.1")
+        );
+        assert_eq!(surround.convert_filter(6), None);
+
+        // Nothing to preserve
+        assert_eq!(MixLayout::for_tracks(&[2, 2], true), MixLayout::Stereo);
+        assert_eq!(MixLayout::for_tracks(&[], true), MixLayout::Stereo);
+        assert!(input_args(2).is_empty());
+    }
+}
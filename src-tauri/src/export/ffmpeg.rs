@@ -3,14 +3,18 @@
 //! This module provides FFmpeg-based video decoding and encoding
 //! for the export pipeline.
 
+use crate::capture::frame::Rotation;
+use crate::export::downmix::{self, probe_channels};
 use crate::export::types::{ExportError, ExportFormat, ExportOptions, ExportSegment, TrackEdits};
+use crate::project::schema::AudioTrack;
+use crate::utils::process::{track, TrackedChild};
 use std::io::{BufReader, Read, Write};
 use std::path::Path;
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::process::{ChildStdin, ChildStdout, Command, Stdio};
 
 /// Video decoder using FFmpeg to read frames from a video file
 pub struct VideoDecoder {
-    process: Child,
+    process: TrackedChild,
     stdout: BufReader<ChildStdout>,
     width: u32,
     height: u32,
@@ -23,24 +27,69 @@ pub struct VideoDecoder {
 impl VideoDecoder {
     /// Open a video file for decoding
     pub fn open(video_path: &Path) -> Result<Self, ExportError> {
+        Self::open_with_lut(video_path, None)
+    }
+
+    /// Open a video file for decoding, color graded with a 3D LUT
+    pub fn open_with_lut(video_path: &Path, lut: Option<&Path>) -> Result<Self, ExportError> {
+        Self::open_at(video_path, lut, None)
+    }
+
+    /// Open a video file for decoding at `fps` frames per second instead of
+    /// its stream's rate. Screen recordings stamp frames at the rate capture
+    /// asked for, so when it achieved another rate, from the manifest, the
+    /// frames are retimed to it rather than dropped or repeated.
+    pub fn open_at(
+        video_path: &Path,
+        lut: Option<&Path>,
+        fps: Option<f64>,
+    ) -> Result<Self, ExportError> {
         // First, probe the video to get metadata
-        let (width, height, total_frames, fps) = Self::probe_video(video_path)?;
+        let (width, height, stream_frames, stream_fps) = Self::probe_stream(video_path)?;
+        let input_rate = capture_rate_args(fps, stream_fps);
+        let fps = decoded_fps(fps, stream_fps);
+        let total_frames = stream_frames;
+
+        // Frames are turned upright here rather than by FFmpeg's autorotate,
+        // so the size below always matches what's decoded
+        let rotation = probe_rotation(video_path);
+        let (width, height) = rotation.rotated_size(width, height);
+        let filters: Vec<String> = rotation
+            .ffmpeg_filter()
+            .map(str::to_string)
+            .into_iter()
+            .chain(lut.map(lut3d_filter))
+            .collect();
+        let filter_args = if filters.is_empty() {
+            Vec::new()
+        } else {
+            vec!["-vf".to_string(), filters.join(",")]
+        };
 
         tracing::info!(
-            "Opening video decoder for {:?}: {}x{}, {} frames @ {}fps",
+            "Opening video decoder for {:?}: {}x{}, {} frames @ {}fps, rotated {}°",
             video_path,
             width,
             height,
             total_frames,
-            fps
+            fps,
+            rotation.degrees()
         );
 
         // Start FFmpeg to decode video to raw RGBA frames
         // IMPORTANT: Must specify -s to ensure exact dimensions without padding
-        let mut process = Command::new("ffmpeg")
+        // Screen recordings skip unchanged frames, so repeat frames over the
+        // gaps to get one frame per 1/fps again
+        let process = Command::new("ffmpeg")
+            .arg("-noautorotate")
+            .args(input_rate)
+            .args(["-i", video_path.to_str().unwrap_or("")])
+            .args(filter_args)
             .args([
-                "-i",
-                video_path.to_str().unwrap_or(""),
+                "-fps_mode",
+                "cfr",
+                "-r",
+                &fps.to_string(),
                 "-f",
                 "rawvideo",
                 "-pix_fmt",
@@ -53,7 +102,8 @@ impl VideoDecoder {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| ExportError::Ffmpeg(format!("Failed to start FFmpeg decoder: {}", e)))?;
+            .map_err(|e| ExportError::ffmpeg_spawn("Failed to start FFmpeg decoder", e))?;
+        let mut process = track(process, "export decoder");
 
         let frame_size = (width * height * 4) as usize; // RGBA = 4 bytes per pixel
 
@@ -74,8 +124,29 @@ impl VideoDecoder {
         })
     }
 
-    /// Probe video file to get metadata
-    fn probe_video(video_path: &Path) -> Result<(u32, u32, u64, f64), ExportError> {
+    /// Probe video file to get metadata: (width, height, total frames, fps).
+    /// The size is as displayed, i.e. after any rotation in the file's
+    /// metadata, matching what FFmpeg decodes by default.
+    pub(crate) fn probe_video(video_path: &Path) -> Result<(u32, u32, u64, f64), ExportError> {
+        let (width, height, total_frames, fps) = Self::probe_stream(video_path)?;
+        let (width, height) = probe_rotation(video_path).rotated_size(width, height);
+        Ok((width, height, total_frames, fps))
+    }
+
+    /// Metadata of the frames `open_at` decodes at `fps`: (width, height,
+    /// total frames, fps)
+    pub(crate) fn probe_at(
+        video_path: &Path,
+        fps: Option<f64>,
+    ) -> Result<(u32, u32, u64, f64), ExportError> {
+        let (width, height, total_frames, stream_fps) = Self::probe_video(video_path)?;
+        Ok((width, height, total_frames, decoded_fps(fps, stream_fps)))
+    }
+
+    /// Metadata of the stored (unrotated) video stream. The frame count is
+    /// at the constant frame rate, so variable frame rate recordings count
+    /// the frames they skipped.
+    fn probe_stream(video_path: &Path) -> Result<(u32, u32, u64, f64), ExportError> {
         let output = Command::new("ffprobe")
             .args([
                 "-v",
@@ -84,13 +155,13 @@ impl VideoDecoder {
                 "v:0",
                 "-count_packets",
                 "-show_entries",
-                "stream=width,height,nb_read_packets,r_frame_rate",
+                "stream=width,height,nb_read_packets,r_frame_rate:format=duration",
                 "-of",
                 "csv=p=0",
                 video_path.to_str().unwrap_or(""),
             ])
             .output()
-            .map_err(|e| ExportError::Ffmpeg(format!("Failed to run ffprobe: {}", e)))?;
+            .map_err(|e| ExportError::ffmpeg_spawn("Failed to run ffprobe", e))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -98,7 +169,9 @@ impl VideoDecoder {
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let parts: Vec<&str> = stdout.trim().split(',').collect();
+        let mut lines = stdout.trim().lines();
+        let parts: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+        let duration_secs: Option<f64> = lines.next().and_then(|d| d.trim().parse().ok());
 
         if parts.len() < 4 {
             return Err(ExportError::Ffmpeg(format!(
@@ -124,7 +197,10 @@ impl VideoDecoder {
             parts[2].parse().unwrap_or(30.0)
         };
 
-        let total_frames: u64 = parts[3].parse().unwrap_or(0);
+        let packets: u64 = parts[3].parse().unwrap_or(0);
+        let total_frames = duration_secs
+            .map(|secs| (secs * fps).round() as u64)
+            .map_or(packets, |frames| frames.max(packets));
 
         Ok((width, height, total_frames, fps))
     }
@@ -177,9 +253,49 @@ impl Drop for VideoDecoder {
     }
 }
 
+/// How a video's frames must be turned to show them upright, from the
+/// stream's display matrix or its older `rotate` tag. None if it can't be
+/// probed.
+pub(crate) fn probe_rotation(video_path: &Path) -> Rotation {
+    Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream_side_data=rotation:stream_tags=rotate",
+            "-of",
+            "default=noprint_wrappers=1",
+        ])
+        .arg(video_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_rotation(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Parse `probe_rotation`'s ffprobe output. Display matrix rotations are
+/// counter-clockwise, `rotate` tags clockwise.
+fn parse_rotation(output: &str) -> Rotation {
+    let degrees = |value: &str| value.trim().parse::<f64>().ok();
+    let matrix = output
+        .lines()
+        .find_map(|line| line.strip_prefix("rotation=").and_then(degrees));
+    let tag = output
+        .lines()
+        .find_map(|line| line.strip_prefix("TAG:rotate=").and_then(degrees));
+    match (matrix, tag) {
+        (Some(ccw), _) => Rotation::from_degrees(-ccw),
+        (None, Some(cw)) => Rotation::from_degrees(cw),
+        (None, None) => Rotation::None,
+    }
+}
+
 /// Video encoder using FFmpeg for export output
 pub struct VideoEncoder {
-    process: Child,
+    process: TrackedChild,
     stdin: ChildStdin,
     frame_count: u64,
 }
@@ -188,11 +304,9 @@ impl VideoEncoder {
     /// Create a new encoder for video-only output (no audio)
     pub fn new_video_only(options: &ExportOptions, source_width: u32, source_height: u32, source_fps: f64) -> Result<Self, ExportError> {
         let crf = options.quality.crf();
-        let preset = options.quality.h264_preset();
 
         // Calculate output dimensions - use source if not specified
-        let output_width = options.width.unwrap_or(source_width);
-        let output_height = options.height.unwrap_or(source_height);
+        let (output_width, output_height) = options.output_dimensions(source_width, source_height);
         let output_fps = options.fps.unwrap_or(source_fps as u32);
 
         // Build scaling filter if dimensions differ
@@ -227,13 +341,8 @@ impl VideoEncoder {
         // Add codec-specific options based on format
         match options.format {
             ExportFormat::Mp4 => {
+                args.extend(options.h264_args(output_width, output_height, output_fps));
                 args.extend([
-                    "-c:v".to_string(),
-                    "libx264".to_string(),
-                    "-preset".to_string(),
-                    preset.to_string(),
-                    "-crf".to_string(),
-                    crf.to_string(),
                     "-pix_fmt".to_string(),
                     "yuv420p".to_string(),
                     "-movflags".to_string(),
@@ -249,6 +358,7 @@ impl VideoEncoder {
                     "-b:v".to_string(),
                     "0".to_string(),
                 ]);
+                args.extend(options.quality.vp9_speed_args().iter().map(|a| a.to_string()));
             }
             ExportFormat::Gif => {
                 // GIF needs a different pipeline with palette generation
@@ -269,13 +379,14 @@ impl VideoEncoder {
 
         tracing::info!("Starting FFmpeg encoder: {:?}", args);
 
-        let mut process = Command::new("ffmpeg")
+        let process = Command::new("ffmpeg")
             .args(&args)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| ExportError::Ffmpeg(format!("Failed to start FFmpeg encoder: {}", e)))?;
+            .map_err(|e| ExportError::ffmpeg_spawn("Failed to start FFmpeg encoder", e))?;
+        let mut process = track(process, "export encoder");
 
         let stdin = process
             .stdin
@@ -299,7 +410,6 @@ impl VideoEncoder {
         system_audio_path: Option<&Path>,
     ) -> Result<Self, ExportError> {
         let crf = options.quality.crf();
-        let preset = options.quality.h264_preset();
 
         // IMPORTANT: Use source_fps for input frame rate, not options.fps
         // The -r flag before -i specifies the INPUT frame rate
@@ -323,40 +433,61 @@ impl VideoEncoder {
 
         if let Some(mic_path) = mic_audio_path {
             if options.include_mic_audio && mic_path.exists() {
+                let channels = probe_channels(mic_path);
+                args.extend(mic_offset_args(options));
+                args.extend(downmix::input_args(channels));
                 args.extend(["-i".to_string(), mic_path.to_string_lossy().to_string()]);
-                audio_inputs.push(input_index);
+                audio_inputs.push((input_index, channels, AudioTrack::Microphone));
                 input_index += 1;
             }
         }
 
         if let Some(system_path) = system_audio_path {
             if options.include_system_audio && system_path.exists() {
+                let channels = probe_channels(system_path);
+                args.extend(downmix::input_args(channels));
                 args.extend(["-i".to_string(), system_path.to_string_lossy().to_string()]);
-                audio_inputs.push(input_index);
+                audio_inputs.push((input_index, channels, AudioTrack::SystemAudio));
             }
         }
 
-        // Build filter complex for audio mixing if we have multiple audio tracks
-        let filter_complex = if audio_inputs.len() > 1 {
-            let audio_refs: Vec<String> = audio_inputs.iter().map(|i| format!("[{}:a]", i)).collect();
-            Some(format!(
-                "{}amix=inputs={}:duration=longest[aout]",
-                audio_refs.join(""),
-                audio_inputs.len()
-            ))
-        } else {
-            None
+        // Bring the audio tracks to a common layout and mix them
+        let channels: Vec<u32> = audio_inputs.iter().map(|&(_, channels, _)| channels).collect();
+        let layout = options.mix_layout(&channels);
+        let mut audio_filters = Vec::new();
+        let mut audio_refs = Vec::new();
+        for &(index, channels, track) in &audio_inputs {
+            match track_mix_filter(layout.convert_filter(channels), options.volume(track)) {
+                Some(convert) => {
+                    audio_filters.push(format!("[{}:a]{}[a{}]", index, convert, index));
+                    audio_refs.push(format!("[a{}]", index));
+                }
+                None => audio_refs.push(format!("[{}:a]", index)),
+            }
+        }
+        let audio_map = match audio_refs.len() {
+            0 => None,
+            1 if audio_filters.is_empty() => Some(format!("{}:a", audio_inputs[0].0)),
+            1 => Some(audio_refs.remove(0)),
+            n => {
+                audio_filters.push(format!(
+                    "{}amix=inputs={}:duration=longest[aout]",
+                    audio_refs.join(""),
+                    n
+                ));
+                Some("[aout]".to_string())
+            }
         };
 
-        if let Some(ref filter) = filter_complex {
-            args.extend(["-filter_complex".to_string(), filter.clone()]);
+        if !audio_filters.is_empty() {
+            args.extend(["-filter_complex".to_string(), audio_filters.join(";")]);
         }
 
         // Scaling filter - only if explicit dimensions are provided AND differ from source
         // Use aspect-ratio-preserving scaling with padding to avoid distortion
-        let output_width = options.width.unwrap_or(source_width);
-        let output_height = options.height.unwrap_or(source_height);
-        
+        let (output_width, output_height) = options.output_dimensions(source_width, source_height);
+        let output_fps = options.fps.unwrap_or(source_fps as u32);
+
         if output_width != source_width || output_height != source_height {
             // Use FFmpeg's aspect-ratio-preserving scale with padding
             // This scales the video to fit within the target dimensions while preserving aspect ratio,
@@ -382,13 +513,8 @@ impl VideoEncoder {
         // Video codec options
         match options.format {
             ExportFormat::Mp4 => {
+                args.extend(options.h264_args(output_width, output_height, output_fps));
                 args.extend([
-                    "-c:v".to_string(),
-                    "libx264".to_string(),
-                    "-preset".to_string(),
-                    preset.to_string(),
-                    "-crf".to_string(),
-                    crf.to_string(),
                     "-pix_fmt".to_string(),
                     "yuv420p".to_string(),
                     "-movflags".to_string(),
@@ -404,6 +530,7 @@ impl VideoEncoder {
                     "-b:v".to_string(),
                     "0".to_string(),
                 ]);
+                args.extend(options.quality.vp9_speed_args().iter().map(|a| a.to_string()));
             }
             ExportFormat::Gif => {
                 // GIF doesn't support audio, fall back to video only
@@ -412,36 +539,24 @@ impl VideoEncoder {
         }
 
         // Audio codec options
-        if !audio_inputs.is_empty() {
-            if filter_complex.is_some() {
-                args.extend(["-map".to_string(), "0:v".to_string()]);
-                args.extend(["-map".to_string(), "[aout]".to_string()]);
-            } else if audio_inputs.len() == 1 {
-                args.extend(["-map".to_string(), "0:v".to_string()]);
-                args.extend([
-                    "-map".to_string(),
-                    format!("{}:a", audio_inputs[0]),
-                ]);
-            }
-            args.extend([
-                "-c:a".to_string(),
-                "aac".to_string(),
-                "-b:a".to_string(),
-                "192k".to_string(),
-            ]);
+        if let Some(audio_map) = audio_map {
+            args.extend(["-map".to_string(), "0:v".to_string()]);
+            args.extend(["-map".to_string(), audio_map]);
+            args.extend(options.audio_codec_args(layout));
         }
 
         args.push(options.output_path.clone());
 
         tracing::info!("Starting FFmpeg encoder with audio: {:?}", args);
 
-        let mut process = Command::new("ffmpeg")
+        let process = Command::new("ffmpeg")
             .args(&args)
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| ExportError::Ffmpeg(format!("Failed to start FFmpeg encoder: {}", e)))?;
+            .map_err(|e| ExportError::ffmpeg_spawn("Failed to start FFmpeg encoder", e))?;
+        let mut process = track(process, "export encoder");
 
         let stdin = process
             .stdin
@@ -493,6 +608,75 @@ impl VideoEncoder {
     }
 }
 
+/// Input offset arguments for the microphone track (placed before its `-i`)
+pub(super) fn mic_offset_args(options: &ExportOptions) -> Vec<String> {
+    input_offset_args(options.mic_offset_ms)
+}
+
+/// Arguments shifting the next input by `offset_ms` (positive delays it)
+pub(super) fn input_offset_args(offset_ms: f64) -> Vec<String> {
+    if offset_ms == 0.0 {
+        return Vec::new();
+    }
+    vec![
+        "-itsoffset".to_string(),
+        format!("{:.3}", offset_ms / 1000.0),
+    ]
+}
+
+/// Input arguments reading a video at `capture_fps` rather than its stream
+/// rate, or none when they match to within rounding. Each frame keeps its
+/// place in the sequence and only its timing changes.
+pub(crate) fn capture_rate_args(capture_fps: Option<f64>, stream_fps: f64) -> Vec<String> {
+    match capture_fps {
+        Some(fps) if fps > 0.0 && (fps - stream_fps).abs() >= 0.5 => {
+            vec!["-r".to_string(), fps.to_string()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Rate a stream is decoded at: the capture rate, when it's read at that
+/// rate (see `capture_rate_args`)
+fn decoded_fps(capture_fps: Option<f64>, stream_fps: f64) -> f64 {
+    capture_fps
+        .filter(|_| !capture_rate_args(capture_fps, stream_fps).is_empty())
+        .unwrap_or(stream_fps)
+}
+
+/// `lut3d` filter applying a `.cube` file
+pub(super) fn lut3d_filter(lut: &Path) -> String {
+    format!("lut3d=file={}", escape_filter_value(&lut.to_string_lossy()))
+}
+
+/// Filters bringing an audio track into the mix: `convert` (a layout
+/// conversion) and the track's volume, or None if the track goes in as is.
+/// Every mix (export, clips, playback) goes through this, so a track sounds
+/// the same in all of them.
+pub fn track_mix_filter(convert: Option<String>, volume: f64) -> Option<String> {
+    let volume = ((volume - 1.0).abs() > 1e-6).then(|| format!("volume={:.3}", volume.max(0.0)));
+    match (convert, volume) {
+        (Some(convert), Some(volume)) => Some(format!("{},{}", convert, volume)),
+        (convert, volume) => convert.or(volume),
+    }
+}
+
+/// Escape a value for use as a filter option inside a filtergraph: once for
+/// the option parser and once more for the graph parser
+fn escape_filter_value(value: &str) -> String {
+    let escape = |s: &str, special: &[char]| {
+        s.chars().fold(String::new(), |mut out, c| {
+            if c == '\\' || special.contains(&c) {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        })
+    };
+    let option = escape(value, &['\'', ':']);
+    escape(&option, &['\'', '[', ']', ',', ';'])
+}
+
 /// Build atempo filter chain for arbitrary speed changes
 /// atempo only accepts 0.5-2.0, so chain multiple for larger changes
 fn build_atempo_chain(time_scale: f64) -> String {
@@ -524,7 +708,7 @@ fn build_atempo_chain(time_scale: f64) -> String {
 }
 
 /// Build filter_complex for video segments with trim/concat
-fn build_video_filter(segments: &[ExportSegment], input_index: usize) -> (String, String) {
+pub(super) fn build_video_filter(segments: &[ExportSegment], input_index: usize) -> (String, String) {
     let mut filters = Vec::new();
     let mut concat_inputs = Vec::new();
 
@@ -567,7 +751,7 @@ fn build_video_filter(segments: &[ExportSegment], input_index: usize) -> (String
 }
 
 /// Build filter_complex for audio segments with trim/concat
-fn build_audio_filter(
+pub(super) fn build_audio_filter(
     segments: &[ExportSegment],
     input_index: usize,
     prefix: &str,
@@ -614,32 +798,34 @@ fn build_audio_filter(
 /// frames in Rust.
 pub fn export_with_edits(
     video_path: &Path,
+    capture_fps: Option<f64>,
     webcam_path: Option<&Path>,
     mic_audio_path: Option<&Path>,
     system_audio_path: Option<&Path>,
     options: &ExportOptions,
     edits: &TrackEdits,
-) -> Result<std::process::Child, ExportError> {
+) -> Result<TrackedChild, ExportError> {
     // Get source video metadata for scaling decisions
-    let (source_width, source_height, _, source_fps) = VideoDecoder::probe_video(video_path)?;
+    let (source_width, source_height, _, stream_fps) = VideoDecoder::probe_video(video_path)?;
+    let input_rate = capture_rate_args(capture_fps, stream_fps);
+    let source_fps = capture_fps.filter(|_| !input_rate.is_empty()).unwrap_or(stream_fps);
 
-    let output_width = options.width.unwrap_or(source_width);
-    let output_height = options.height.unwrap_or(source_height);
+    let (output_width, output_height) = options.output_dimensions(source_width, source_height);
     let output_fps = options.fps.unwrap_or(source_fps as u32);
 
     let crf = options.quality.crf();
-    let preset = options.quality.h264_preset();
 
     // Build input args
     let mut args = vec!["-y".to_string()];
 
-    // Input 0: video
+    // Input 0: video, retimed to the rate it was captured at
+    args.extend(input_rate);
     args.extend(["-i".to_string(), video_path.to_string_lossy().to_string()]);
 
     // Track input indices
     let mut webcam_input_index: Option<usize> = None;
-    let mut mic_input_index: Option<usize> = None;
-    let mut system_input_index: Option<usize> = None;
+    let mut mic_input: Option<(usize, u32)> = None;
+    let mut system_input: Option<(usize, u32)> = None;
     let mut next_input = 1;
 
     // Input 1: webcam (if included)
@@ -651,21 +837,32 @@ pub fn export_with_edits(
         }
     }
 
-    // Input 2+: audio files
+    // Input 2+: audio files, with their channel counts
     if let Some(mic_path) = mic_audio_path {
         if options.include_mic_audio && mic_path.exists() {
+            let channels = probe_channels(mic_path);
+            args.extend(mic_offset_args(options));
+            args.extend(downmix::input_args(channels));
             args.extend(["-i".to_string(), mic_path.to_string_lossy().to_string()]);
-            mic_input_index = Some(next_input);
+            mic_input = Some((next_input, channels));
             next_input += 1;
         }
     }
 
     if let Some(system_path) = system_audio_path {
         if options.include_system_audio && system_path.exists() {
+            let channels = probe_channels(system_path);
+            args.extend(downmix::input_args(channels));
             args.extend(["-i".to_string(), system_path.to_string_lossy().to_string()]);
-            system_input_index = Some(next_input);
+            system_input = Some((next_input, channels));
         }
     }
+    let channels: Vec<u32> = [mic_input, system_input]
+        .into_iter()
+        .flatten()
+        .map(|(_, channels)| channels)
+        .collect();
+    let layout = options.mix_layout(&channels);
 
     // Build filter_complex
     let mut filter_parts = Vec::new();
@@ -675,6 +872,15 @@ pub fn export_with_edits(
     let (video_filter, video_label) = build_video_filter(&edits.segments, 0);
     filter_parts.push(video_filter);
 
+    // Color grade the screen before scaling
+    let video_label = match &options.screen_lut {
+        Some(lut) => {
+            filter_parts.push(format!("[{}]{}[vlut]", video_label, lut3d_filter(lut)));
+            "vlut".to_string()
+        }
+        None => video_label,
+    };
+
     // Add scaling and fps conversion
     // If webcam is included, output to intermediate label; otherwise output to [vout]
     let video_scaled_label = if webcam_input_index.is_some() {
@@ -726,10 +932,15 @@ pub fn export_with_edits(
             wc_segment_labels[0][1..wc_segment_labels[0].len() - 1].to_string()
         };
 
-        // Scale webcam video
+        // Color grade and scale webcam video
+        let wc_lut = options
+            .camera_lut
+            .as_deref()
+            .map(|lut| format!("{},", lut3d_filter(lut)))
+            .unwrap_or_default();
         filter_parts.push(format!(
-            "[{}]scale={}:-1[wc_scaled]",
-            wc_concat_label, webcam_width
+            "[{}]{}scale={}:-1[wc_scaled]",
+            wc_concat_label, wc_lut, webcam_width
         ));
 
         // Overlay webcam on main video with 'shortest' to match main video duration
@@ -739,18 +950,24 @@ pub fn export_with_edits(
         ));
     }
 
-    // Mic audio filter
-    if let Some(mic_idx) = mic_input_index {
-        let (audio_filter, audio_label) = build_audio_filter(&edits.segments, mic_idx, "mic");
-        filter_parts.push(audio_filter);
-        audio_outputs.push(format!("[{}]", audio_label));
-    }
-
-    // System audio filter
-    if let Some(sys_idx) = system_input_index {
-        let (audio_filter, audio_label) = build_audio_filter(&edits.segments, sys_idx, "sys");
+    // Mic and system audio filters, converted to the mix's layout
+    let audio_tracks = [
+        (mic_input, "mic", AudioTrack::Microphone),
+        (system_input, "sys", AudioTrack::SystemAudio),
+    ];
+    for (input, prefix, track) in audio_tracks {
+        let Some((index, channels)) = input else {
+            continue;
+        };
+        let (audio_filter, audio_label) = build_audio_filter(&edits.segments, index, prefix);
         filter_parts.push(audio_filter);
-        audio_outputs.push(format!("[{}]", audio_label));
+        match track_mix_filter(layout.convert_filter(channels), options.volume(track)) {
+            Some(convert) => {
+                filter_parts.push(format!("[{}]{}[{}fmt]", audio_label, convert, prefix));
+                audio_outputs.push(format!("[{}fmt]", prefix));
+            }
+            None => audio_outputs.push(format!("[{}]", audio_label)),
+        }
     }
 
     // Mix audio if multiple sources
@@ -780,13 +997,8 @@ pub fn export_with_edits(
     // Video codec options
     match options.format {
         ExportFormat::Mp4 => {
+            args.extend(options.h264_args(output_width, output_height, output_fps));
             args.extend([
-                "-c:v".to_string(),
-                "libx264".to_string(),
-                "-preset".to_string(),
-                preset.to_string(),
-                "-crf".to_string(),
-                crf.to_string(),
                 "-pix_fmt".to_string(),
                 "yuv420p".to_string(),
                 "-movflags".to_string(),
@@ -802,6 +1014,7 @@ pub fn export_with_edits(
                 "-b:v".to_string(),
                 "0".to_string(),
             ]);
+            args.extend(options.quality.vp9_speed_args().iter().map(|a| a.to_string()));
         }
         ExportFormat::Gif => {
             // GIF handling - simplified
@@ -810,13 +1023,8 @@ pub fn export_with_edits(
     }
 
     // Audio codec
-    if mic_input_index.is_some() || system_input_index.is_some() {
-        args.extend([
-            "-c:a".to_string(),
-            "aac".to_string(),
-            "-b:a".to_string(),
-            "192k".to_string(),
-        ]);
+    if mic_input.is_some() || system_input.is_some() {
+        args.extend(options.audio_codec_args(layout));
     }
 
     // Progress output for tracking
@@ -833,15 +1041,41 @@ pub fn export_with_edits(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .map_err(|e| ExportError::Ffmpeg(format!("Failed to start FFmpeg: {}", e)))?;
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to start FFmpeg", e))?;
 
-    Ok(process)
+    Ok(track(process, "export"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_lut_path_is_escaped_for_filtergraph() {
+        assert_eq!(
+            lut3d_filter(Path::new("/luts/Brand: Teal.cube")),
+            "lut3d=file=/luts/Brand\\\\: Teal.cube"
+        );
+    }
+
+    #[test]
+    fn test_capture_rate_retimes_only_when_it_differs() {
+        assert!(capture_rate_args(None, 60.0).is_empty());
+        assert!(capture_rate_args(Some(60.0), 60.0).is_empty());
+        assert!(capture_rate_args(Some(60.0), 59.94).is_empty());
+        assert_eq!(capture_rate_args(Some(48.0), 60.0), ["-r", "48"]);
+    }
+
+    #[test]
+    fn test_track_mix_filter_applies_volume() {
+        assert_eq!(track_mix_filter(None, 1.0), None);
+        assert_eq!(track_mix_filter(None, 0.5).as_deref(), Some("volume=0.500"));
+        assert_eq!(
+            track_mix_filter(Some("pan=stereo|c0=c0|c1=c1".to_string()), 1.2).as_deref(),
+            Some("pan=stereo|c0=c0|c1=c1,volume=1.200")
+        );
+    }
+
     #[test]
     fn test_atempo_chain_normal() {
         let chain = build_atempo_chain(1.0);
@@ -908,4 +1142,42 @@ mod tests {
         let (filter, _) = build_video_filter(&segments, 0);
         assert!(filter.contains("setpts=(PTS-STARTPTS)/2"));
     }
+
+    #[test]
+    fn test_parse_rotation_prefers_display_matrix() {
+        // A portrait recording stored landscape, turned a quarter clockwise
+        assert_eq!(parse_rotation("rotation=-90\n"), Rotation::Cw90);
+        assert_eq!(parse_rotation("TAG:rotate=270\n"), Rotation::Cw270);
+        assert_eq!(parse_rotation("rotation=90\nTAG:rotate=90\n"), Rotation::Cw270);
+        assert_eq!(parse_rotation(""), Rotation::None);
+    }
+
+    #[test]
+    fn test_draft_quality_halves_resolution() {
+        let mut options: ExportOptions = serde_json::from_value(serde_json::json!({
+            "format": "mp4",
+            "quality": "draft",
+            "width": null,
+            "height": null,
+            "fps": null,
+            "outputPath": "/tmp/out.mp4",
+            "includeCursor": false,
+            "includeWebcam": false,
+            "includeMicAudio": false,
+            "includeSystemAudio": false,
+            "screenEdits": null,
+            "cameraEdits": null
+        }))
+        .unwrap();
+
+        assert_eq!(options.output_dimensions(1919, 1080), (958, 540));
+
+        // Explicit sizes always win
+        options.width = Some(1280);
+        assert_eq!(options.output_dimensions(1919, 1080), (1280, 540));
+
+        options.quality = crate::export::types::ExportQuality::High;
+        options.width = None;
+        assert_eq!(options.output_dimensions(1919, 1080), (1919, 1080));
+    }
 }
@@ -0,0 +1,323 @@
+//! Bounded queue of composited frames waiting for the encoder
+//!
+//! Frames are composited on the export thread and written to FFmpeg on a
+//! writer thread, so compositing and encoding overlap. A 4K RGBA frame is
+//! about 33 MB, so the queue is bounded by bytes rather than frame count: once
+//! the memory budget is used up, frames are either spilled to a temporary file
+//! (if enabled) or compositing waits for the encoder to catch up. The spill
+//! file is only emptied once every frame in it has been read back, so it is
+//! capped at `MAX_SPILL_BYTES` in all, after which compositing waits too.
+//! It is kept in the private decrypted media cache, as its frames may show
+//! an encrypted recording.
+
+use super::types::ExportError;
+use crate::project::encryption::{create_private_dir, decrypted_cache_dir};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+/// Largest the spill file grows before it's emptied
+const MAX_SPILL_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+/// A queued frame, in memory or in the spill file
+enum Slot {
+    Memory(Vec<u8>),
+    Spilled { offset: u64, len: usize },
+}
+
+/// Temporary file holding frames that didn't fit in memory
+struct SpillFile {
+    file: File,
+    /// Where the next spilled frame is written
+    end: u64,
+    /// Bytes of spilled frames still queued
+    queued: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    frames: VecDeque<Slot>,
+    memory_bytes: usize,
+    spill: Option<SpillFile>,
+    closed: bool,
+    aborted: bool,
+}
+
+/// Frame queue bounded by a memory budget
+pub struct FrameQueue {
+    inner: Mutex<Inner>,
+    changed: Condvar,
+    memory_budget: usize,
+    spill_to_disk: bool,
+    max_spill_bytes: u64,
+}
+
+impl FrameQueue {
+    pub fn new(memory_budget: usize, spill_to_disk: bool) -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            changed: Condvar::new(),
+            memory_budget,
+            spill_to_disk,
+            max_spill_bytes: MAX_SPILL_BYTES,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue a frame, waiting while the budget is used up.
+    ///
+    /// A frame is always accepted into an empty queue, so a budget smaller
+    /// than one frame still makes progress. Returns false if the consumer
+    /// aborted the queue.
+    pub fn push(&self, mut frame: Vec<u8>) -> io::Result<bool> {
+        let mut inner = self.lock();
+        loop {
+            if inner.aborted {
+                return Ok(false);
+            }
+            match self.try_queue(&mut inner, frame)? {
+                None => break,
+                Some(rejected) => frame = rejected,
+            }
+            inner = self.changed.wait(inner).unwrap_or_else(|e| e.into_inner());
+        }
+        self.changed.notify_all();
+        Ok(true)
+    }
+
+    /// Queue a frame in memory or the spill file if there's room, otherwise
+    /// hand it back
+    fn try_queue(&self, inner: &mut Inner, frame: Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+        if inner.memory_bytes == 0 || inner.memory_bytes + frame.len() <= self.memory_budget {
+            inner.memory_bytes += frame.len();
+            inner.frames.push_back(Slot::Memory(frame));
+            return Ok(None);
+        }
+        // Frames already read back still take up the file until it's
+        // emptied, so the cap is on its end rather than what's queued
+        let spill_end = inner.spill.as_ref().map_or(0, |s| s.end);
+        if self.spill_to_disk && spill_end + frame.len() as u64 <= self.max_spill_bytes {
+            let slot = spill(&mut inner.spill, &frame)?;
+            inner.frames.push_back(slot);
+            return Ok(None);
+        }
+        Ok(Some(frame))
+    }
+
+    /// Take the oldest frame, waiting for one to be queued.
+    ///
+    /// Returns None once the queue is closed and drained, or aborted.
+    pub fn pop(&self) -> io::Result<Option<Vec<u8>>> {
+        let mut inner = self.lock();
+        loop {
+            if inner.aborted {
+                return Ok(None);
+            }
+            if let Some(slot) = inner.frames.pop_front() {
+                let frame = match slot {
+                    Slot::Memory(frame) => {
+                        inner.memory_bytes -= frame.len();
+                        frame
+                    }
+                    Slot::Spilled { offset, len } => unspill(&mut inner.spill, offset, len)?,
+                };
+                self.changed.notify_all();
+                return Ok(Some(frame));
+            }
+            if inner.closed {
+                return Ok(None);
+            }
+            inner = self.changed.wait(inner).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// No more frames will be pushed; `pop` drains what's queued
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.changed.notify_all();
+    }
+
+    /// Drop everything queued and wake both sides
+    pub fn abort(&self) {
+        let mut inner = self.lock();
+        inner.aborted = true;
+        inner.frames.clear();
+        inner.memory_bytes = 0;
+        inner.spill = None;
+        drop(inner);
+        self.changed.notify_all();
+    }
+}
+
+/// Append a frame to the spill file, creating it on first use
+fn spill(spill: &mut Option<SpillFile>, frame: &[u8]) -> io::Result<Slot> {
+    let spill = match spill {
+        Some(spill) => spill,
+        None => spill.insert(SpillFile {
+            file: {
+                let dir = decrypted_cache_dir();
+                create_private_dir(&dir)?;
+                tempfile::tempfile_in(dir)?
+            },
+            end: 0,
+            queued: 0,
+        }),
+    };
+    let offset = spill.end;
+    spill.file.seek(SeekFrom::Start(offset))?;
+    spill.file.write_all(frame)?;
+    spill.end += frame.len() as u64;
+    spill.queued += frame.len() as u64;
+    Ok(Slot::Spilled {
+        offset,
+        len: frame.len(),
+    })
+}
+
+/// Read a spilled frame back, emptying the file once nothing is left in it
+fn unspill(spill: &mut Option<SpillFile>, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let spill = spill
+        .as_mut()
+        .ok_or_else(|| io::Error::other("spill file is gone"))?;
+    let mut frame = vec![0; len];
+    spill.file.seek(SeekFrom::Start(offset))?;
+    spill.file.read_exact(&mut frame)?;
+    spill.queued -= len as u64;
+    if spill.queued == 0 {
+        spill.file.set_len(0)?;
+        spill.end = 0;
+    }
+    Ok(frame)
+}
+
+/// Writer thread feeding queued frames to a sink (the encoder)
+///
+/// Dropping the writer before `finish` aborts the queue and drops the sink,
+/// e.g. when the export is cancelled.
+pub struct FrameWriter<T> {
+    queue: Arc<FrameQueue>,
+    thread: Option<JoinHandle<Result<T, ExportError>>>,
+}
+
+impl<T: Send + 'static> FrameWriter<T> {
+    pub fn spawn<W>(queue: FrameQueue, mut sink: T, mut write: W) -> Self
+    where
+        W: FnMut(&mut T, &[u8]) -> Result<(), ExportError> + Send + 'static,
+    {
+        let queue = Arc::new(queue);
+        let thread = {
+            let queue = queue.clone();
+            std::thread::spawn(move || {
+                let mut drain = || -> Result<(), ExportError> {
+                    while let Some(frame) = queue.pop()? {
+                        write(&mut sink, &frame)?;
+                    }
+                    Ok(())
+                };
+                let result = drain();
+                if result.is_err() {
+                    // Unblock the producer
+                    queue.abort();
+                }
+                result.map(|()| sink)
+            })
+        };
+        Self {
+            queue,
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue a frame for writing, failing with the writer's error if it stopped
+    pub fn push(&mut self, frame: Vec<u8>) -> Result<(), ExportError> {
+        if self.queue.push(frame)? {
+            return Ok(());
+        }
+        self.join()?;
+        Err(ExportError::Encoding(
+            "Frame writer stopped early".to_string(),
+        ))
+    }
+
+    /// Write out everything queued and hand back the sink
+    pub fn finish(mut self) -> Result<T, ExportError> {
+        self.queue.close();
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<T, ExportError> {
+        let thread = self
+            .thread
+            .take()
+            .ok_or_else(|| ExportError::Encoding("Frame writer already stopped".to_string()))?;
+        thread
+            .join()
+            .map_err(|_| ExportError::Encoding("Frame writer panicked".to_string()))?
+    }
+}
+
+impl<T> Drop for FrameWriter<T> {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.queue.abort();
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_over_budget_spill_and_keep_order() {
+        // Room for two frames in memory; the rest go to disk
+        let queue = FrameQueue::new(8, true);
+        for i in 0..5u8 {
+            assert!(queue.push(vec![i; 4]).unwrap());
+        }
+        assert_eq!(queue.lock().memory_bytes, 8);
+        assert_eq!(queue.lock().spill.as_ref().unwrap().queued, 12);
+
+        let mut writer = FrameWriter::spawn(queue, Vec::new(), |frames: &mut Vec<u8>, frame| {
+            frames.push(frame[0]);
+            Ok(())
+        });
+        writer.push(vec![5; 4]).unwrap();
+        assert_eq!(writer.finish().unwrap(), vec![0, 1, 2, 3, 4, 5]);
+
+        // A failing sink unblocks the producer with its error
+        let mut writer = FrameWriter::spawn(FrameQueue::new(4, false), (), |_, _| {
+            Err(ExportError::Encoding("broken pipe".to_string()))
+        });
+        let result = (0..3).try_for_each(|_| writer.push(vec![0; 4]));
+        assert!(matches!(result, Err(ExportError::Encoding(e)) if e == "broken pipe"));
+    }
+
+    #[test]
+    fn test_spill_file_is_capped_until_emptied() {
+        // One frame in memory and two in the spill file
+        let mut queue = FrameQueue::new(4, true);
+        queue.max_spill_bytes = 8;
+        for i in 0..3u8 {
+            assert!(queue.push(vec![i; 4]).unwrap());
+        }
+        assert_eq!(queue.pop().unwrap(), Some(vec![0; 4]));
+        assert!(queue.push(vec![3; 4]).unwrap());
+
+        // Only one frame is left in the spill file, but the file is full
+        assert_eq!(queue.pop().unwrap(), Some(vec![1; 4]));
+        let rejected = queue.try_queue(&mut queue.lock(), vec![4; 4]).unwrap();
+        assert_eq!(rejected, Some(vec![4; 4]));
+
+        // Reading the last spilled frame back empties it
+        assert_eq!(queue.pop().unwrap(), Some(vec![2; 4]));
+        assert_eq!(queue.try_queue(&mut queue.lock(), vec![4; 4]).unwrap(), None);
+        assert_eq!(queue.lock().spill.as_ref().unwrap().end, 4);
+    }
+}
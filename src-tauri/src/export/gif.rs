@@ -0,0 +1,219 @@
+//! GIF size budgets
+//!
+//! GIFs for READMEs and pull requests have to stay under upload limits. When
+//! an export sets `max_size_mb`, the finished GIF is re-encoded with lower
+//! frame rate, width and palette size until it fits, and the parameters that
+//! were used are reported back.
+
+use crate::export::ffmpeg::VideoDecoder;
+use crate::export::types::{ExportError, ExportFormat, ExportOptions, ExportProgress};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// Highest frame rate GIF exports use
+pub const GIF_MAX_FPS: u32 = 15;
+
+/// Frame rate is lowered to this before anything else is reduced
+const GIF_PREFERRED_MIN_FPS: u32 = 10;
+const GIF_MIN_FPS: u32 = 5;
+const GIF_MIN_WIDTH: u32 = 240;
+const GIF_MAX_COLORS: u32 = 256;
+const GIF_MIN_COLORS: u32 = 32;
+
+/// Re-encodes tried before giving up on the budget
+const MAX_ATTEMPTS: usize = 8;
+
+/// Aim below the budget since size doesn't scale exactly with the parameters
+const SIZE_MARGIN: f64 = 0.9;
+
+/// Encoding parameters of a GIF
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GifParams {
+    pub fps: u32,
+    pub width: u32,
+    pub max_colors: u32,
+}
+
+/// Outcome of fitting a GIF export to its size budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GifFit {
+    /// Parameters of the final file
+    pub params: GifParams,
+    pub size_bytes: u64,
+    pub budget_bytes: u64,
+    /// False if the smallest parameters tried still exceed the budget
+    pub fits_budget: bool,
+}
+
+/// Next, smaller parameters to try for a GIF of `size_bytes`.
+///
+/// Assumes size scales with frame rate and pixel count. Frame rate is cut to
+/// 10fps first, then width, then palette size and finally frame rate down to
+/// 5fps. Returns `None` once every parameter is at its minimum.
+pub fn next_params(current: &GifParams, size_bytes: u64, budget_bytes: u64) -> Option<GifParams> {
+    let mut reduction = budget_bytes as f64 / size_bytes.max(1) as f64 * SIZE_MARGIN;
+    let mut next = *current;
+
+    if next.fps > GIF_PREFERRED_MIN_FPS {
+        let fps = ((next.fps as f64 * reduction).round() as u32).clamp(GIF_PREFERRED_MIN_FPS, next.fps);
+        reduction *= next.fps as f64 / fps as f64;
+        next.fps = fps;
+    }
+
+    if reduction < 1.0 && next.width > GIF_MIN_WIDTH {
+        let width = ((next.width as f64 * reduction.sqrt()) as u32 & !1).clamp(GIF_MIN_WIDTH, next.width);
+        reduction *= (next.width as f64 / width as f64).powi(2);
+        next.width = width;
+    }
+
+    if reduction < 1.0 {
+        if next.max_colors > GIF_MIN_COLORS {
+            next.max_colors = (next.max_colors / 2).max(GIF_MIN_COLORS);
+        } else if next.fps > GIF_MIN_FPS {
+            next.fps = (next.fps * 2 / 3).max(GIF_MIN_FPS);
+        }
+    }
+
+    (next != *current).then_some(next)
+}
+
+/// FFmpeg filter graph encoding a GIF with `params`
+pub(crate) fn gif_filter(params: &GifParams) -> String {
+    format!(
+        "fps={},scale={}:-2:flags=lanczos,split[s0][s1];[s0]palettegen=max_colors={}[p];[s1][p]paletteuse=dither=bayer",
+        params.fps, params.width, params.max_colors
+    )
+}
+
+/// Encode `source` to a GIF at `output`, returning the output size in bytes
+pub fn encode_gif(source: &Path, output: &Path, params: &GifParams) -> Result<u64, ExportError> {
+    let result = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-i")
+        .arg(source)
+        .args(["-vf", &gif_filter(params), "-loop", "0"])
+        .arg(output)
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+
+    if !result.status.success() {
+        return Err(ExportError::Ffmpeg(format!(
+            "GIF encoding failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+
+    Ok(std::fs::metadata(output)?.len())
+}
+
+/// Shrink a finished GIF export until it fits `options.max_size_mb`.
+///
+/// Does nothing (and returns `None`) for other formats or without a budget.
+/// Every attempt re-encodes the original export into a temporary file next
+/// to it, and the export is only replaced once an attempt succeeded, so a
+/// failed or cancelled attempt leaves the original in place. If no
+/// parameters fit, the smallest attempt is kept and reported with
+/// `fits_budget: false`. Each re-encode is reported as a `GeneratingPalette`
+/// stage.
+pub fn apply_size_budget<F>(
+    options: &ExportOptions,
+    progress_callback: F,
+) -> Result<Option<GifFit>, ExportError>
+where
+    F: Fn(ExportProgress),
+{
+    let Some(max_size_mb) = options.max_size_mb.filter(|_| options.format == ExportFormat::Gif) else {
+        return Ok(None);
+    };
+    if max_size_mb <= 0.0 {
+        return Err(ExportError::InvalidConfig("GIF size budget must be positive".to_string()));
+    }
+
+    let budget_bytes = (max_size_mb * 1024.0 * 1024.0) as u64;
+    let output = Path::new(&options.output_path);
+    let mut size_bytes = std::fs::metadata(output)?.len();
+    let (width, _, _, _) = VideoDecoder::probe_video(output)?;
+    let mut params = GifParams {
+        fps: options.fps.unwrap_or(GIF_MAX_FPS).min(GIF_MAX_FPS),
+        width,
+        max_colors: GIF_MAX_COLORS,
+    };
+
+    if size_bytes > budget_bytes {
+        let dir = output.parent().unwrap_or(Path::new("."));
+        // Latest (smallest) attempt; dropping it removes the file
+        let mut reencoded: Option<tempfile::NamedTempFile> = None;
+        for attempt in 1..=MAX_ATTEMPTS {
+            let Some(next) = next_params(&params, size_bytes, budget_bytes) else {
+                break;
+            };
+            progress_callback(ExportProgress::generating_palette(
+                attempt as u32,
+                MAX_ATTEMPTS as u32,
+            ));
+            let attempt_file = tempfile::Builder::new()
+                .prefix(".gif-attempt-")
+                .suffix(".gif")
+                .tempfile_in(dir)?;
+            let size = encode_gif(output, attempt_file.path(), &next)?;
+            tracing::info!("GIF attempt {:?}: {} bytes (budget {})", next, size, budget_bytes);
+            params = next;
+            size_bytes = size;
+            reencoded = Some(attempt_file);
+            if size_bytes <= budget_bytes {
+                break;
+            }
+        }
+
+        if let Some(reencoded) = reencoded {
+            reencoded.persist(output).map_err(|e| e.error)?;
+        }
+    }
+
+    let fit = GifFit {
+        params,
+        size_bytes,
+        budget_bytes,
+        fits_budget: size_bytes <= budget_bytes,
+    };
+    if fit.fits_budget {
+        tracing::info!("GIF fits size budget: {:?}", fit);
+    } else {
+        tracing::warn!("GIF exceeds size budget at minimum settings: {:?}", fit);
+    }
+    Ok(Some(fit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_params_reduces_fps_then_width_then_palette() {
+        let start = GifParams {
+            fps: 15,
+            width: 800,
+            max_colors: 256,
+        };
+
+        // Slightly over budget: frame rate alone is enough
+        let next = next_params(&start, 10_000_000, 9_000_000).unwrap();
+        assert_eq!((next.fps, next.width, next.max_colors), (12, 800, 256));
+
+        // Far over budget: width shrinks too, but not below the minimum
+        let next = next_params(&start, 100_000_000, 5_000_000).unwrap();
+        assert_eq!(next.fps, 10);
+        assert_eq!(next.width, GIF_MIN_WIDTH);
+        assert_eq!(next.max_colors, 128);
+
+        let floor = GifParams {
+            fps: GIF_MIN_FPS,
+            width: GIF_MIN_WIDTH,
+            max_colors: GIF_MIN_COLORS,
+        };
+        assert_eq!(next_params(&floor, 10_000_000, 1_000_000), None);
+    }
+}
@@ -0,0 +1,364 @@
+//! Editor playback mixdown
+//!
+//! Rather than approximating the mix in the webview, the editor plays audio
+//! rendered here. The microphone and system audio go through the same steps
+//! as an export (lining up with the video, echo cancellation, microphone
+//! offset, cuts and speed changes, FFmpeg's `amix`) with the project's track
+//! volumes applied, so playback sounds like the exported file. Each render
+//! covers a range of output time and is written as WAV to a scratch
+//! directory; renders older than `MIXDOWN_MAX_AGE`, and lined-up or
+//! echo-cancelled tracks unused for `CACHE_MAX_AGE`, are removed whenever a
+//! new one is made. Playback is always stereo, so surround tracks are mixed
+//! down.
+//!
+//! The scratch directory sits in the private decrypted media cache, since
+//! its audio may come from an encrypted bundle, and is cleared with it.
+
+use super::downmix::{self, probe_channels, MixLayout};
+use super::ffmpeg::{build_audio_filter, input_offset_args, track_mix_filter};
+use super::sessions::SessionMedia;
+use super::types::{ExportError, ExportSegment};
+use crate::processing::echo_cancellation::cancel_echo;
+use crate::project::encryption::{
+    create_private_dir, create_private_file, decrypted_cache_dir, readable_path,
+};
+use crate::project::schema::AudioConfig;
+use crate::recorder::manifest::FileKind;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+
+/// Mixdowns older than this are deleted when a new one is rendered
+const MIXDOWN_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Lined-up and echo-cancelled tracks unused for this long are deleted when
+/// a mixdown is rendered
+const CACHE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Prefix of rendered mixdown files
+const MIXDOWN_PREFIX: &str = "mix-";
+
+/// Prefixes of the lined-up track directories and echo-cancelled microphone
+/// files reused across mixdowns
+const ALIGNED_PREFIX: &str = "aligned-";
+const ECHO_PREFIX: &str = "echo-";
+
+/// Directory mixdowns (and echo-cancelled microphone tracks) are written to
+pub fn mixdown_dir() -> PathBuf {
+    decrypted_cache_dir().join("mixdown")
+}
+
+/// FFmpeg arguments mixing `mic` and `system` like an export does and writing
+/// `start_ms..end_ms` of the output timeline to `output` as WAV. Pass None
+/// for muted tracks. `segments` of None keeps the full recording.
+pub fn mixdown_args(
+    mic: Option<&Path>,
+    system: Option<&Path>,
+    audio: &AudioConfig,
+    segments: Option<&[ExportSegment]>,
+    start_ms: u64,
+    end_ms: u64,
+    output: &Path,
+) -> Result<Vec<String>, ExportError> {
+    if end_ms <= start_ms {
+        return Err(ExportError::InvalidConfig(format!(
+            "Invalid mixdown range: {}ms to {}ms",
+            start_ms, end_ms
+        )));
+    }
+
+    let mut args = vec!["-y".to_string(), "-v".to_string(), "error".to_string()];
+    let mut inputs = Vec::new();
+    if let Some(mic) = mic {
+        let channels = probe_channels(mic);
+        args.extend(input_offset_args(audio.microphone_offset_ms));
+        args.extend(downmix::input_args(channels));
+        args.extend(["-i".to_string(), mic.to_string_lossy().to_string()]);
+        inputs.push((inputs.len(), "mic", audio.microphone_volume, channels));
+    }
+    if let Some(system) = system {
+        let channels = probe_channels(system);
+        args.extend(downmix::input_args(channels));
+        args.extend(["-i".to_string(), system.to_string_lossy().to_string()]);
+        inputs.push((inputs.len(), "sys", audio.system_volume, channels));
+    }
+    if inputs.is_empty() {
+        return Err(ExportError::InvalidConfig(
+            "No audio tracks to mix".to_string(),
+        ));
+    }
+
+    let mut filters = Vec::new();
+    let mut labels = Vec::new();
+    for &(index, prefix, volume, channels) in &inputs {
+        let label = match segments {
+            Some(segments) => {
+                let (filter, label) = build_audio_filter(segments, index, prefix);
+                filters.push(filter);
+                label
+            }
+            None => {
+                filters.push(format!("[{}:a]anull[{}]", index, prefix));
+                prefix.to_string()
+            }
+        };
+        let mix = track_mix_filter(MixLayout::Stereo.convert_filter(channels), volume);
+        filters.push(format!(
+            "[{}]{}[{}vol]",
+            label,
+            mix.as_deref().unwrap_or("anull"),
+            prefix
+        ));
+        labels.push(format!("[{}vol]", prefix));
+    }
+    let mixed = if labels.len() > 1 {
+        filters.push(format!(
+            "{}amix=inputs={}:duration=longest[aout]",
+            labels.join(""),
+            labels.len()
+        ));
+        "[aout]".to_string()
+    } else {
+        labels.remove(0)
+    };
+    filters.push(format!(
+        "{}atrim=start={}:end={},asetpts=PTS-STARTPTS[mix]",
+        mixed,
+        start_ms as f64 / 1000.0,
+        end_ms as f64 / 1000.0
+    ));
+
+    args.extend(["-filter_complex".to_string(), filters.join(";")]);
+    args.extend(["-map", "[mix]", "-c:a", "pcm_s16le"].map(String::from));
+    args.push(output.to_string_lossy().to_string());
+    Ok(args)
+}
+
+/// Render `start_ms..end_ms` of the output timeline to a new WAV file in
+/// `mixdown_dir()` and return its path
+pub fn render_mixdown(
+    mic: Option<&Path>,
+    system: Option<&Path>,
+    audio: &AudioConfig,
+    segments: Option<&[ExportSegment]>,
+    start_ms: u64,
+    end_ms: u64,
+) -> Result<PathBuf, ExportError> {
+    let dir = mixdown_dir();
+    create_private_dir(&dir)?;
+    remove_stale_mixdowns(&dir);
+
+    let echo_cancelled = match (mic, system) {
+        (Some(mic), Some(system)) if audio.cancel_echo => echo_cancelled_mic(&dir, mic, system),
+        _ => None,
+    };
+    let mic = echo_cancelled.as_deref().or(mic);
+
+    let output = dir.join(format!("{}{}.wav", MIXDOWN_PREFIX, uuid::Uuid::new_v4()));
+    // Created up front so FFmpeg overwrites a file only the user can read
+    create_private_file(&output)?;
+    let args = mixdown_args(mic, system, audio, segments, start_ms, end_ms, &output)?;
+    tracing::debug!("Rendering playback mixdown: {:?}", args);
+
+    let result = Command::new("ffmpeg")
+        .args(&args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+    if !result.status.success() {
+        return Err(ExportError::Ffmpeg(format!(
+            "Mixdown failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+    Ok(output)
+}
+
+/// Track of `kind` across the recording, lined up with the video as export
+/// lines it up. Lining up rewrites the track, so the result is kept in
+/// `mixdown_dir()` and reused until the recording changes.
+pub fn aligned_track(media: &SessionMedia, kind: FileKind) -> Result<Option<PathBuf>, ExportError> {
+    let files = media.session_files(kind);
+    if files.is_empty() {
+        return Ok(None);
+    }
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut hasher = DefaultHasher::new();
+    for file in &files {
+        (file, modified(file)).hash(&mut hasher);
+    }
+    let dir = mixdown_dir().join(format!("{}{:016x}", ALIGNED_PREFIX, hasher.finish()));
+    let cached = |dir: &Path| fs::read_dir(dir).ok()?.flatten().next().map(|e| e.path());
+    if let Some(track) = cached(&dir) {
+        touch(&track);
+        return Ok(Some(track));
+    }
+
+    // Written aside and moved into place, so a render running alongside
+    // never sees a partial track
+    let partial = mixdown_dir().join(format!("partial-{}", uuid::Uuid::new_v4()));
+    create_private_dir(&partial)?;
+    let track = match media.media_file(kind, &partial, None) {
+        Ok(Some(track)) if track.starts_with(&partial) => track,
+        other => {
+            let _ = fs::remove_dir_all(&partial);
+            // Used as recorded
+            return Ok(other?.map(|track| readable_path(&track)).transpose()?);
+        }
+    };
+    if fs::rename(&partial, &dir).is_err() {
+        let _ = fs::remove_dir_all(&partial);
+    }
+    Ok(cached(&dir).or_else(|| track.file_name().map(|name| dir.join(name))))
+}
+
+/// Echo-cancelled copy of `mic`, reused across mixdowns until the recording
+/// changes. Falls back to the recorded track (None) like export does.
+fn echo_cancelled_mic(dir: &Path, mic: &Path, system: &Path) -> Option<PathBuf> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut hasher = DefaultHasher::new();
+    (mic, system, modified(mic), modified(system)).hash(&mut hasher);
+    let cached = dir.join(format!("{}{:016x}.wav", ECHO_PREFIX, hasher.finish()));
+    if cached.exists() {
+        touch(&cached);
+        return Some(cached);
+    }
+
+    // Written aside and moved into place, so a render running alongside
+    // never sees a partial track
+    let partial = dir.join(format!("partial-{}.wav", uuid::Uuid::new_v4()));
+    let written = create_private_file(&partial)
+        .map_err(ExportError::from)
+        .and_then(|_| cancel_echo(mic, system, &partial))
+        .and_then(|_| fs::rename(&partial, &cached).map_err(ExportError::from));
+    match written {
+        Ok(()) => Some(cached),
+        Err(e) => {
+            tracing::warn!("Mixing microphone without echo cancellation: {}", e);
+            let _ = fs::remove_file(&partial);
+            None
+        }
+    }
+}
+
+/// Delete mixdowns old enough that the editor has moved on from them, and
+/// lined-up or echo-cancelled tracks no mixdown has used for a while
+fn remove_stale_mixdowns(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    let older_than = |path: &Path, max_age: Duration| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(|modified| now.duration_since(modified).unwrap_or_default() > max_age)
+            .unwrap_or(false)
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if name.starts_with(MIXDOWN_PREFIX) {
+            if older_than(&path, MIXDOWN_MAX_AGE) {
+                let _ = fs::remove_file(&path);
+            }
+        } else if name.starts_with(ECHO_PREFIX) {
+            if older_than(&path, CACHE_MAX_AGE) {
+                let _ = fs::remove_file(&path);
+            }
+        } else if name.starts_with(ALIGNED_PREFIX) {
+            // Aged by the track inside, which is touched on reuse
+            let track = fs::read_dir(&path)
+                .ok()
+                .and_then(|mut tracks| tracks.next())
+                .and_then(|track| track.ok())
+                .map(|track| track.path());
+            if track.is_none_or(|track| older_than(&track, CACHE_MAX_AGE)) {
+                let _ = fs::remove_dir_all(&path);
+            }
+        }
+    }
+}
+
+/// Mark a cached track as just used, so it isn't evicted as stale
+fn touch(path: &Path) {
+    let touched = fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+    if let Err(e) = touched {
+        tracing::debug!("Failed to touch {:?}: {}", path, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixdown_matches_export_mix() {
+        let audio = AudioConfig {
+            system_volume: 0.5,
+            microphone_volume: 1.2,
+            microphone_offset_ms: 250.0,
+            ..Default::default()
+        };
+        let segments = [
+            ExportSegment {
+                source_start_ms: 0,
+                source_end_ms: 1000,
+                time_scale: 1.0,
+            },
+            ExportSegment {
+                source_start_ms: 3000,
+                source_end_ms: 4000,
+                time_scale: 2.0,
+            },
+        ];
+        let args = mixdown_args(
+            Some(Path::new("mic.m4a")),
+            Some(Path::new("system.m4a")),
+            &audio,
+            Some(&segments),
+            500,
+            1500,
+            Path::new("mix.wav"),
+        )
+        .unwrap();
+        assert!(args.windows(2).any(|w| w == ["-itsoffset", "0.250"]));
+        let filter = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert!(filter.contains("[micconcat]volume=1.200[micvol]"));
+        assert!(filter.contains("[sysconcat]volume=0.500[sysvol]"));
+        assert!(filter.contains("[micvol][sysvol]amix=inputs=2:duration=longest[aout]"));
+        assert!(filter.ends_with("[aout]atrim=start=0.5:end=1.5,asetpts=PTS-STARTPTS[mix]"));
+
+        // A single unedited track skips amix
+        let args = mixdown_args(
+            None,
+            Some(Path::new("system.m4a")),
+            &audio,
+            None,
+            0,
+            1000,
+            Path::new("mix.wav"),
+        )
+        .unwrap();
+        let filter = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert!(!filter.contains("amix"));
+        assert!(filter.contains("[0:a]anull[sys];[sys]volume=0.500[sysvol];[sysvol]atrim"));
+
+        assert!(mixdown_args(None, None, &audio, None, 0, 1000, Path::new("mix.wav")).is_err());
+        assert!(mixdown_args(
+            None,
+            Some(Path::new("s.m4a")),
+            &audio,
+            None,
+            1000,
+            1000,
+            Path::new("mix.wav")
+        )
+        .is_err());
+    }
+}
@@ -3,13 +3,31 @@
 //! This module provides functionality for exporting recordings to various
 //! video formats with cursor overlay, audio mixing, and other effects.
 
+pub mod alignment;
+pub mod annotations;
+pub mod archive;
+pub mod background;
+pub mod benchmark;
+pub mod chapters;
+pub mod clip;
+pub mod cursor_pack;
+pub mod cursor_trail;
+pub mod downmix;
 pub mod ffmpeg;
+pub mod frame_queue;
+pub mod gif;
+pub mod mixdown;
 pub mod pipeline;
+pub mod sessions;
+pub mod share;
 pub mod types;
+pub mod verify;
+pub mod watch_folder;
+pub mod webcam_track;
 
 pub use ffmpeg::export_with_edits;
 pub use pipeline::ExportPipeline;
 pub use types::{
-    ExportError, ExportFormat, ExportOptions, ExportProgress, ExportQuality, ExportSegment,
-    ExportStage, TrackEdits,
+    AudioCodec, CursorTrail, ExportError, ExportFormat, ExportOptions, ExportProgress,
+    ExportQuality, ExportSegment, ExportStage, TrackEdits,
 };
@@ -3,11 +3,30 @@
 //! This module coordinates the full export process including
 //! decoding, cursor compositing, and encoding.
 
-use crate::capture::input::types::{CursorInfo, MouseMove};
+use crate::capture::annotation::Annotation;
+use crate::capture::frame::{content_rect_at, refit_content, WindowResizeEvent};
+use crate::capture::input::types::{CursorInfo, MouseClick, MouseMove};
+use crate::export::annotations::draw_annotations;
+use crate::export::background::is_inside_rounded_rect;
+use crate::export::chapters;
+use crate::export::cursor_pack;
+use crate::export::cursor_trail::draw_cursor_trail;
 use crate::export::ffmpeg::{VideoDecoder, VideoEncoder};
+use crate::export::frame_queue::{FrameQueue, FrameWriter};
 use crate::export::types::{ExportError, ExportOptions, ExportProgress};
-use crate::processing::cursor_smoothing::{smooth_cursor_data, SmoothedMouseMove};
-use crate::project::schema::SpringConfig;
+use crate::export::verify::ExpectedOutput;
+use crate::export::webcam_track::export_webcam_track;
+use crate::processing::cursor_smoothing::{smooth_cursor_for_slices, SmoothedMouseMove};
+use crate::processing::echo_cancellation::{export_mic_track, needs_audio_pass};
+use crate::processing::zoom::{crop_rect, zoom_at, ZoomInputs};
+use crate::processing::zoom_timeline::resolve_zoom_ranges;
+use crate::project::bundle::read_project;
+use crate::project::encryption::readable_path;
+use crate::project::paths;
+use crate::export::sessions::SessionMedia;
+use crate::project::schema::{CursorConfig, SceneType, Slice, ZoomRange};
+use crate::recorder::manifest::FileKind;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -25,10 +44,19 @@ pub struct RecordingBundle {
     pub webcam_video: Option<PathBuf>,
     /// Mouse movement data
     pub mouse_moves: Vec<MouseMove>,
+    /// Mouse click data
+    pub mouse_clicks: Vec<MouseClick>,
     /// Cursor images keyed by cursor ID
     pub cursor_images: HashMap<String, CursorImage>,
     /// Cursor metadata
     pub cursor_info: HashMap<String, CursorInfo>,
+    /// Annotations drawn during recording
+    pub annotations: Vec<Annotation>,
+    /// Content rect changes of a captured window (empty for display capture)
+    pub window_resizes: Vec<WindowResizeEvent>,
+    /// Frame rate the screen was captured at, from the manifest (None for
+    /// camera-only recordings)
+    pub capture_fps: Option<f64>,
 }
 
 /// Loaded cursor image data
@@ -41,6 +69,72 @@ pub struct CursorImage {
     pub height: u32,
 }
 
+/// Load a PNG image as RGBA data
+pub(crate) fn load_png_image(path: &Path) -> Result<CursorImage, ExportError> {
+    let file = std::fs::File::open(path)?;
+    let decoder = png::Decoder::new(file);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| ExportError::Decoding(format!("PNG decode error: {}", e)))?;
+
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|e| ExportError::Decoding(format!("PNG frame error: {}", e)))?;
+
+    // Convert to RGBA if needed
+    let data = match info.color_type {
+        png::ColorType::Rgba => buf[..info.buffer_size()].to_vec(),
+        png::ColorType::Rgb => {
+            // Add alpha channel
+            let rgb = &buf[..info.buffer_size()];
+            let mut rgba = Vec::with_capacity(rgb.len() / 3 * 4);
+            for chunk in rgb.chunks(3) {
+                rgba.extend_from_slice(chunk);
+                rgba.push(255);
+            }
+            rgba
+        }
+        _ => {
+            return Err(ExportError::Decoding(format!(
+                "Unsupported PNG color type: {:?}",
+                info.color_type
+            )));
+        }
+    };
+
+    Ok(CursorImage {
+        data,
+        width: info.width,
+        height: info.height,
+    })
+}
+
+/// How the cursor is drawn at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CursorMode {
+    Hidden,
+    Raw,
+    Smoothed,
+}
+
+/// Cursor mode at a source time, from the slice covering it.
+///
+/// Slices can hide the cursor or disable smoothing for their segment; times
+/// outside every slice use the project-wide smoothing setting.
+fn cursor_mode_at(slices: &[Slice], smoothing_enabled: bool, time_ms: f64) -> CursorMode {
+    let slice = slices
+        .iter()
+        .find(|s| time_ms >= s.source_start_ms && time_ms < s.source_end_ms);
+
+    match slice {
+        Some(s) if s.hide_cursor => CursorMode::Hidden,
+        Some(s) if s.disable_cursor_smoothing => CursorMode::Raw,
+        _ if smoothing_enabled => CursorMode::Smoothed,
+        _ => CursorMode::Raw,
+    }
+}
+
 /// Export pipeline for processing and encoding video
 pub struct ExportPipeline {
     project_dir: PathBuf,
@@ -58,32 +152,85 @@ impl ExportPipeline {
         }
     }
 
-    /// Run the export pipeline
-    pub fn run<F>(&self, progress_callback: F) -> Result<(), ExportError>
+    /// Run the export pipeline, returning what the output should contain
+    /// for verification. Progress is reported up to `Finalizing`; the caller
+    /// reports completion once any passes over the output are done.
+    pub fn run<F>(&self, progress_callback: F) -> Result<ExpectedOutput, ExportError>
     where
-        F: Fn(ExportProgress) + Send,
+        F: Fn(ExportProgress) + Send + Sync,
     {
         tracing::info!("Starting export pipeline for {:?}", self.project_dir);
 
-        // 1. Load recording bundle
+        // 1. Load recording bundle, joining the sessions of a paused recording
         progress_callback(ExportProgress::preparing());
-        let bundle = self.load_bundle()?;
+        let session_scratch = tempfile::tempdir()?;
+        let join_progress = |_: FileKind, fraction: f64| {
+            progress_callback(ExportProgress::joining_sessions(fraction))
+        };
+        let media = SessionMedia::load(&self.project_dir.join("recording"))
+            .mute_tracks(&self.options.track_mutes)
+            .observe(&self.cancel_flag, &join_progress);
+        let mut bundle = self.load_bundle(&media, session_scratch.path())?;
+
+        if self.is_cancelled() {
+            return Err(ExportError::Cancelled);
+        }
+
+        // 1b. Remove speaker echo from the microphone, if requested
+        let audio_scratch = tempfile::tempdir()?;
+        if needs_audio_pass(
+            &self.options,
+            bundle.mic_audio.as_deref(),
+            bundle.system_audio.as_deref(),
+        ) {
+            progress_callback(ExportProgress::processing_audio());
+        }
+        let mic_audio = export_mic_track(
+            &self.options,
+            bundle.mic_audio.as_deref(),
+            bundle.system_audio.as_deref(),
+            audio_scratch.path(),
+        );
 
         if self.is_cancelled() {
             return Err(ExportError::Cancelled);
         }
 
         // 2. Open video decoder to get source metadata
-        let mut decoder = VideoDecoder::open(&bundle.screen_video)?;
+        let mut decoder = VideoDecoder::open_at(
+            &bundle.screen_video,
+            self.options.screen_lut.as_deref(),
+            bundle.capture_fps,
+        )?;
         let (source_width, source_height) = decoder.dimensions();
         let total_frames = decoder.frame_count();
         let source_fps = decoder.fps();
 
-        // 3. Smooth cursor data (if cursor is enabled) - using source FPS
+        // 3. Smooth cursor data (if the cursor is drawn or zooms follow it) -
+        // using source FPS. Raw positions are kept alongside for slices that
+        // disable smoothing.
         progress_callback(ExportProgress::smoothing_cursor(5.0));
-        let smoothed_cursor = if self.options.include_cursor && !bundle.mouse_moves.is_empty() {
-            let config = SpringConfig::default();
-            smooth_cursor_data(&bundle.mouse_moves, &config, source_fps)
+        let (cursor_config, screen_slices, zoom_ranges) = self.load_scene_settings(&media);
+        // A cursor recorded into the video can't be taken out again, and
+        // drawing another on top would show two
+        let draw_cursor = self.options.include_cursor
+            && !media.manifest().is_some_and(|m| m.display_shows_cursor());
+        if let Some(pack) = cursor_config.pack.as_deref().filter(|_| draw_cursor) {
+            // A missing or broken pack falls back to the recorded cursors
+            if let Err(e) = cursor_pack::packs_dir().and_then(|dir| {
+                cursor_pack::apply_pack(&dir, pack, &mut bundle.cursor_info, &mut bundle.cursor_images)
+            }) {
+                tracing::warn!("Failed to apply cursor pack '{}', using recorded cursors: {}", pack, e);
+            }
+        }
+        let needs_cursor = draw_cursor || !zoom_ranges.is_empty();
+        let smoothed_cursor = if needs_cursor && !bundle.mouse_moves.is_empty() {
+            smooth_cursor_for_slices(
+                &bundle.mouse_moves,
+                &cursor_config.smoothing.spring,
+                source_fps,
+                &screen_slices,
+            )
         } else {
             vec![]
         };
@@ -110,7 +257,7 @@ impl ExportPipeline {
         let mut webcam_decoder = if self.options.include_webcam {
             if let Some(ref webcam_path) = bundle.webcam_video {
                 tracing::info!("Attempting to open webcam video: {:?}", webcam_path);
-                match VideoDecoder::open(webcam_path) {
+                match VideoDecoder::open_with_lut(webcam_path, self.options.camera_lut.as_deref()) {
                     Ok(dec) => {
                         tracing::info!(
                             "Webcam video opened successfully: {}x{}, {} frames @ {}fps",
@@ -136,15 +283,25 @@ impl ExportPipeline {
         };
 
         // 4. Create encoder with source FPS (not requested output FPS)
-        let mut encoder = VideoEncoder::new_with_audio(
+        let encoder = VideoEncoder::new_with_audio(
             &self.options,
             source_width,
             source_height,
             source_fps,
-            bundle.mic_audio.as_deref(),
+            mic_audio.as_deref(),
             bundle.system_audio.as_deref(),
         )?;
 
+        // Frames are written to FFmpeg on their own thread, with the queue
+        // between them capped so large exports can't exhaust memory
+        let queue = FrameQueue::new(
+            self.options.frame_memory_bytes(),
+            self.options.spill_frames_to_disk,
+        );
+        let mut writer = FrameWriter::spawn(queue, encoder, |encoder: &mut VideoEncoder, frame| {
+            encoder.write_frame(frame)
+        });
+
         // 5. Process frames
         let mut frame_idx: u64 = 0;
 
@@ -152,6 +309,13 @@ impl ExportPipeline {
         let webcam_scale = 0.125; // 1/8 of screen width
         let webcam_margin = 20u32; // pixels from edge
 
+        let zoom_inputs = ZoomInputs {
+            cursor: &smoothed_cursor,
+            clicks: &bundle.mouse_clicks,
+            width: source_width,
+            height: source_height,
+        };
+
         // Track webcam frames for debugging
         let mut webcam_frames_drawn = 0u64;
         let mut webcam_frames_missed = 0u64;
@@ -188,7 +352,77 @@ impl ExportPipeline {
                 );
             }
 
-            // Composite webcam overlay (before cursor so cursor appears on top)
+            // Re-fit window content that was letterboxed at capture time
+            let frame_time_ms = (frame_idx as f64 / source_fps) * 1000.0;
+            if let Some(rect) = content_rect_at(&bundle.window_resizes, frame_time_ms) {
+                refit_content(&mut frame, source_width, source_height, rect);
+            }
+
+            // Composite annotations (below the cursor)
+            if self.options.include_annotations && !bundle.annotations.is_empty() {
+                let frame_time_ms = (frame_idx as f64 / source_fps) * 1000.0;
+                draw_annotations(
+                    &mut frame,
+                    source_width,
+                    source_height,
+                    &bundle.annotations,
+                    frame_time_ms,
+                );
+            }
+
+            // Composite cursor overlay, honoring per-slice cursor settings
+            if draw_cursor && !smoothed_cursor.is_empty() {
+                let frame_time_ms = (frame_idx as f64 / source_fps) * 1000.0;
+                let mode = cursor_mode_at(&screen_slices, cursor_config.smoothing.enabled, frame_time_ms);
+                if let Some(ref trail) = self.options.cursor_trail {
+                    if mode != CursorMode::Hidden {
+                        draw_cursor_trail(
+                            &mut frame,
+                            source_width,
+                            source_height,
+                            &smoothed_cursor,
+                            frame_time_ms,
+                            trail,
+                            mode == CursorMode::Raw,
+                        );
+                    }
+                }
+                let cursor_pos = match mode {
+                    CursorMode::Hidden => None,
+                    CursorMode::Raw => self.find_cursor_at_time(&smoothed_cursor, frame_time_ms).map(|m| {
+                        Cow::Owned(SmoothedMouseMove {
+                            x: m.raw_x,
+                            y: m.raw_y,
+                            ..m.clone()
+                        })
+                    }),
+                    CursorMode::Smoothed => {
+                        self.find_cursor_at_time(&smoothed_cursor, frame_time_ms).map(Cow::Borrowed)
+                    }
+                };
+                if let Some(cursor_pos) = cursor_pos {
+                    self.draw_cursor(
+                        &mut frame,
+                        source_width,
+                        source_height,
+                        &cursor_pos,
+                        &bundle.cursor_images,
+                        &bundle.cursor_info,
+                    );
+                }
+            }
+
+            // Apply the zoom/pan transform to the screen, annotations and cursor
+            if !zoom_ranges.is_empty() {
+                let frame_time_ms = (frame_idx as f64 / source_fps) * 1000.0;
+                let zoom = zoom_at(&zoom_ranges, &zoom_inputs, frame_time_ms);
+                if zoom.scale > 1.0 {
+                    let rect = crop_rect(&zoom, source_width, source_height);
+                    refit_content(&mut frame, source_width, source_height, rect);
+                }
+            }
+
+            // Composite webcam overlay last so it isn't zoomed
             if let Some(ref mut webcam_dec) = webcam_decoder {
                 match webcam_dec.read_frame() {
                     Ok(Some(webcam_frame)) => {
@@ -231,24 +465,8 @@ impl ExportPipeline {
                 }
             }
 
-            // Composite cursor overlay
-            if self.options.include_cursor && !smoothed_cursor.is_empty() {
-                let frame_time_ms = (frame_idx as f64 / source_fps) * 1000.0;
-                if let Some(cursor_pos) = self.find_cursor_at_time(&smoothed_cursor, frame_time_ms)
-                {
-                    self.draw_cursor(
-                        &mut frame,
-                        source_width,
-                        source_height,
-                        cursor_pos,
-                        &bundle.cursor_images,
-                        &bundle.cursor_info,
-                    );
-                }
-            }
-
-            // Write frame to encoder
-            encoder.write_frame(&frame)?;
+            // Queue frame for the encoder
+            writer.push(frame)?;
 
             frame_idx += 1;
 
@@ -270,16 +488,77 @@ impl ExportPipeline {
             );
         }
         
-        encoder.finish()?;
+        writer.finish()?.finish()?;
+        chapters::embed_project_chapters(&self.project_dir, &self.options, None);
+        export_webcam_track(
+            bundle.webcam_video.as_deref(),
+            mic_audio.as_deref(),
+            bundle.system_audio.as_deref(),
+            &self.options,
+            None,
+        );
 
-        progress_callback(ExportProgress::complete());
         tracing::info!(
             "Export complete: {} frames written to {:?}",
             frame_idx,
             self.options.output_path
         );
 
-        Ok(())
+        Ok(ExpectedOutput::new(
+            &self.options,
+            total_frames as f64 / source_fps * 1000.0,
+            mic_audio.as_deref(),
+            bundle.system_audio.as_deref(),
+        ))
+    }
+
+    /// Load the project's cursor settings and the recording scenes' screen
+    /// slices and zoom ranges, resolved against the slices so zooms don't
+    /// overlap or run across cuts. Each scene is placed on the timeline at
+    /// its session's start. Falls back to defaults (no slices, no zoom) if
+    /// the project can't be read.
+    fn load_scene_settings(
+        &self,
+        media: &SessionMedia,
+    ) -> (CursorConfig, Vec<Slice>, Vec<ZoomRange>) {
+        let project = match read_project(&self.project_dir) {
+            Ok(project) => project,
+            Err(e) => {
+                tracing::warn!("Failed to read project, using default scene settings: {}", e);
+                return (CursorConfig::default(), Vec::new(), Vec::new());
+            }
+        };
+
+        let mut slices = Vec::new();
+        let mut zoom_ranges = Vec::new();
+        let recordings = project
+            .scenes
+            .into_iter()
+            .filter(|s| matches!(s.scene_type, SceneType::Recording));
+        for scene in recordings {
+            let Some(offset_ms) = media.timeline_offset_ms(scene.session_index) else {
+                tracing::warn!("Skipping scene for unrecorded session {}", scene.session_index);
+                continue;
+            };
+            let scene_slices = if scene.screen_slices.is_empty() {
+                scene.slices
+            } else {
+                scene.screen_slices
+            };
+            let scene_zooms = resolve_zoom_ranges(&scene.zoom_ranges, &scene_slices);
+            slices.extend(scene_slices.into_iter().map(|mut s| {
+                s.source_start_ms += offset_ms;
+                s.source_end_ms += offset_ms;
+                s
+            }));
+            zoom_ranges.extend(scene_zooms.into_iter().map(|mut z| {
+                z.start_time += offset_ms;
+                z.end_time += offset_ms;
+                z
+            }));
+        }
+
+        (project.config.cursor, slices, zoom_ranges)
     }
 
     /// Check if export was cancelled
@@ -287,8 +566,13 @@ impl ExportPipeline {
         self.cancel_flag.load(Ordering::Relaxed)
     }
 
-    /// Load the recording bundle from the project directory
-    fn load_bundle(&self) -> Result<RecordingBundle, ExportError> {
+    /// Load the recording bundle from the project directory. Media of
+    /// recordings with several sessions is joined into `scratch`.
+    fn load_bundle(
+        &self,
+        media: &SessionMedia,
+        scratch: &Path,
+    ) -> Result<RecordingBundle, ExportError> {
         let recording_dir = self.project_dir.join("recording");
 
         if !recording_dir.exists() {
@@ -298,49 +582,91 @@ impl ExportPipeline {
             )));
         }
 
-        // Find the screen video (session 0)
-        let screen_video = recording_dir.join("recording-0.mp4");
-        if !screen_video.exists() {
-            return Err(ExportError::BundleNotFound(format!(
-                "Screen video not found: {:?}",
-                screen_video
-            )));
-        }
+        // Locate files via the recording manifest (falls back to naming convention)
+        let manifest = media.manifest();
+        let capture_fps = manifest.and_then(|m| m.display_fps());
+
+        // Find the screen video. Camera-only bundles use the webcam
+        // recording as the main video, with no webcam overlay, played at its
+        // own rate; audio-only bundles a black video.
+        let primary_kind = media.primary_kind();
+        let screen_fps = capture_fps.filter(|_| primary_kind == FileKind::ScreenVideo);
+        let screen_video = media.primary_video(scratch, screen_fps)?.ok_or_else(|| {
+            ExportError::BundleNotFound(format!(
+                "Screen video not found in {:?}",
+                recording_dir
+            ))
+        })?;
 
         // Find optional audio files
-        let mic_audio = {
-            let path = recording_dir.join("recording-0-mic.m4a");
-            if path.exists() {
-                Some(path)
-            } else {
-                None
-            }
-        };
+        let mic_audio = media.media_file(FileKind::MicAudio, scratch, None)?;
+        let system_audio = media.media_file(FileKind::SystemAudio, scratch, None)?;
 
-        let system_audio = {
-            let path = recording_dir.join("recording-0-system.m4a");
-            if path.exists() {
-                Some(path)
-            } else {
-                None
-            }
+        let webcam_video = match primary_kind {
+            FileKind::ScreenVideo => media.media_file(FileKind::WebcamVideo, scratch, None)?,
+            _ => None,
         };
+        tracing::info!("Webcam video: {:?}", webcam_video);
 
-        let webcam_video = {
-            let path = recording_dir.join("recording-0-webcam.mp4");
-            tracing::info!("Checking for webcam video at: {:?}, exists={}", path, path.exists());
-            if path.exists() {
-                Some(path)
-            } else {
-                None
+        // Load mouse moves and clicks
+        let mut mouse_moves = media.events(
+            FileKind::MouseMoves,
+            |path| self.load_mouse_moves(path),
+            |m, offset_ms| m.process_time_ms += offset_ms,
+        )?;
+        let mut mouse_clicks = media.events(
+            FileKind::MouseClicks,
+            |path| self.load_mouse_clicks(path),
+            |c, offset_ms| c.process_time_ms += offset_ms,
+        )?;
+
+        // Load cursor info and images. Sessions mostly share cursors; the
+        // first session's image wins.
+        let mut cursor_info = HashMap::new();
+        let mut cursor_images = HashMap::new();
+        let cursor_files = media.session_files(FileKind::Cursors);
+        if cursor_files.is_empty() {
+            tracing::warn!("Cursors metadata file not found");
+        }
+        for path in cursor_files {
+            let (info, images) = self.load_cursors(Some(path))?;
+            for (id, info) in info {
+                cursor_info.entry(id).or_insert(info);
             }
-        };
+            for (id, image) in images {
+                cursor_images.entry(id).or_insert(image);
+            }
+        }
 
-        // Load mouse moves
-        let mouse_moves = self.load_mouse_moves(&recording_dir)?;
+        // Load annotations
+        let annotations = media.events(
+            FileKind::Annotations,
+            |path| self.load_annotations(path),
+            |a, offset_ms| a.process_time_ms += offset_ms,
+        )?;
 
-        // Load cursor info and images
-        let (cursor_info, cursor_images) = self.load_cursors(&recording_dir)?;
+        // Load window resize metadata (window capture only)
+        let mut window_resizes = media.events(
+            FileKind::WindowResizes,
+            |path| self.load_window_resizes(path),
+            |r, offset_ms| r.process_time_ms += offset_ms,
+        )?;
+
+        // Positions are in captured display pixels; bring them onto the
+        // recorded window or region, and down to a video that was scaled
+        // down while recording
+        if let Some(manifest) = manifest {
+            manifest.map_mouse_to_video(&window_resizes, &mut mouse_moves, &mut mouse_clicks);
+        }
+        let downscale = manifest.map_or(1, |m| m.display_downscale());
+        if downscale > 1 {
+            for r in &mut window_resizes {
+                r.content.x /= downscale;
+                r.content.y /= downscale;
+                r.content.width /= downscale;
+                r.content.height /= downscale;
+            }
+        }
 
         tracing::info!(
             "Loaded recording bundle: video={:?}, mic={:?}, system={:?}, webcam={:?}, mouse_moves={}, cursors={}",
@@ -352,25 +678,56 @@ impl ExportPipeline {
             cursor_info.len()
         );
 
+        // Encrypted media is decrypted into a private cache for FFmpeg
+        let readable = |path: Option<PathBuf>| path.as_deref().map(readable_path).transpose();
+
         Ok(RecordingBundle {
-            screen_video,
-            mic_audio,
-            system_audio,
-            webcam_video,
+            screen_video: readable_path(&screen_video)?,
+            mic_audio: readable(mic_audio)?,
+            system_audio: readable(system_audio)?,
+            webcam_video: readable(webcam_video)?,
             mouse_moves,
+            mouse_clicks,
             cursor_images,
             cursor_info,
+            annotations,
+            window_resizes,
+            capture_fps: screen_fps,
         })
     }
 
-    /// Load mouse movement data from JSON
-    fn load_mouse_moves(&self, recording_dir: &Path) -> Result<Vec<MouseMove>, ExportError> {
-        let path = recording_dir.join("recording-0-mouse-moves.json");
+    /// Load window resize events from JSON (optional)
+    fn load_window_resizes(&self, path: Option<PathBuf>) -> Result<Vec<WindowResizeEvent>, ExportError> {
+        let Some(path) = path else {
+            return Ok(vec![]);
+        };
 
-        if !path.exists() {
-            tracing::warn!("Mouse moves file not found: {:?}", path);
+        let content = std::fs::read_to_string(&path)?;
+        let resizes: Vec<WindowResizeEvent> = serde_json::from_str(&content)
+            .map_err(|e| ExportError::BundleNotFound(format!("Failed to parse window resizes: {}", e)))?;
+
+        Ok(resizes)
+    }
+
+    /// Load annotations from JSON (optional)
+    fn load_annotations(&self, path: Option<PathBuf>) -> Result<Vec<Annotation>, ExportError> {
+        let Some(path) = path else {
             return Ok(vec![]);
-        }
+        };
+
+        let content = std::fs::read_to_string(&path)?;
+        let annotations: Vec<Annotation> = serde_json::from_str(&content)
+            .map_err(|e| ExportError::BundleNotFound(format!("Failed to parse annotations: {}", e)))?;
+
+        Ok(annotations)
+    }
+
+    /// Load mouse movement data from JSON
+    fn load_mouse_moves(&self, path: Option<PathBuf>) -> Result<Vec<MouseMove>, ExportError> {
+        let Some(path) = path else {
+            tracing::warn!("Mouse moves file not found");
+            return Ok(vec![]);
+        };
 
         let content = std::fs::read_to_string(&path)?;
         let moves: Vec<MouseMove> = serde_json::from_str(&content)
@@ -379,21 +736,34 @@ impl ExportPipeline {
         Ok(moves)
     }
 
+    /// Load mouse click data from JSON (optional)
+    fn load_mouse_clicks(&self, path: Option<PathBuf>) -> Result<Vec<MouseClick>, ExportError> {
+        let Some(path) = path else {
+            return Ok(vec![]);
+        };
+
+        let content = std::fs::read_to_string(&path)?;
+        let clicks: Vec<MouseClick> = serde_json::from_str(&content)
+            .map_err(|e| ExportError::BundleNotFound(format!("Failed to parse mouse clicks: {}", e)))?;
+
+        Ok(clicks)
+    }
+
     /// Load cursor metadata and images
     fn load_cursors(
         &self,
-        recording_dir: &Path,
+        cursors_json: Option<PathBuf>,
     ) -> Result<(HashMap<String, CursorInfo>, HashMap<String, CursorImage>), ExportError> {
-        let cursors_json = recording_dir.join("recording-0-cursors.json");
-        let cursors_dir = recording_dir.join("recording-0-cursors");
-
         let mut cursor_info = HashMap::new();
         let mut cursor_images = HashMap::new();
 
-        if !cursors_json.exists() {
-            tracing::warn!("Cursors metadata file not found: {:?}", cursors_json);
+        let Some(cursors_json) = cursors_json else {
+            tracing::warn!("Cursors metadata file not found");
             return Ok((cursor_info, cursor_images));
-        }
+        };
+
+        // Cursor images live in a sibling directory named after the JSON file
+        let cursors_dir = cursors_json.with_extension("");
 
         // Load cursor metadata
         let content = std::fs::read_to_string(&cursors_json)?;
@@ -402,10 +772,8 @@ impl ExportPipeline {
 
         // Load cursor images
         for (id, info) in info_list {
-            let image_path = cursors_dir.join(&info.image_path);
-
-            if image_path.exists() {
-                match self.load_png_image(&image_path) {
+            if let Some(image_path) = paths::resolve(&cursors_dir, &info.image_path) {
+                match load_png_image(&image_path) {
                     Ok(image) => {
                         cursor_images.insert(id.clone(), image);
                     }
@@ -421,47 +789,6 @@ impl ExportPipeline {
         Ok((cursor_info, cursor_images))
     }
 
-    /// Load a PNG image as RGBA data
-    fn load_png_image(&self, path: &Path) -> Result<CursorImage, ExportError> {
-        let file = std::fs::File::open(path)?;
-        let decoder = png::Decoder::new(file);
-        let mut reader = decoder
-            .read_info()
-            .map_err(|e| ExportError::Decoding(format!("PNG decode error: {}", e)))?;
-
-        let mut buf = vec![0; reader.output_buffer_size()];
-        let info = reader
-            .next_frame(&mut buf)
-            .map_err(|e| ExportError::Decoding(format!("PNG frame error: {}", e)))?;
-
-        // Convert to RGBA if needed
-        let data = match info.color_type {
-            png::ColorType::Rgba => buf[..info.buffer_size()].to_vec(),
-            png::ColorType::Rgb => {
-                // Add alpha channel
-                let rgb = &buf[..info.buffer_size()];
-                let mut rgba = Vec::with_capacity(rgb.len() / 3 * 4);
-                for chunk in rgb.chunks(3) {
-                    rgba.extend_from_slice(chunk);
-                    rgba.push(255);
-                }
-                rgba
-            }
-            _ => {
-                return Err(ExportError::Decoding(format!(
-                    "Unsupported PNG color type: {:?}",
-                    info.color_type
-                )));
-            }
-        };
-
-        Ok(CursorImage {
-            data,
-            width: info.width,
-            height: info.height,
-        })
-    }
-
     /// Find the cursor position at a given time
     fn find_cursor_at_time<'a>(
         &self,
@@ -580,7 +907,7 @@ impl ExportPipeline {
         for dy in 0..scaled_height {
             for dx in 0..scaled_width {
                 // Check if this pixel is within rounded corners
-                if !self.is_inside_rounded_rect(
+                if !is_inside_rounded_rect(
                     dx as i32,
                     dy as i32,
                     scaled_width as i32,
@@ -619,42 +946,37 @@ impl ExportPipeline {
             }
         }
     }
+}
 
-    /// Check if a point is inside a rounded rectangle
-    fn is_inside_rounded_rect(
-        &self,
-        x: i32,
-        y: i32,
-        width: i32,
-        height: i32,
-        radius: i32,
-    ) -> bool {
-        // Check corners
-        // Top-left corner
-        if x < radius && y < radius {
-            let dx = radius - x;
-            let dy = radius - y;
-            return dx * dx + dy * dy <= radius * radius;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slice(start: f64, end: f64, hide_cursor: bool, disable_cursor_smoothing: bool) -> Slice {
+        Slice {
+            id: format!("{}-{}", start, end),
+            source_start_ms: start,
+            source_end_ms: end,
+            time_scale: 1.0,
+            volume: 1.0,
+            hide_cursor,
+            disable_cursor_smoothing,
         }
-        // Top-right corner
-        if x >= width - radius && y < radius {
-            let dx = x - (width - radius - 1);
-            let dy = radius - y;
-            return dx * dx + dy * dy <= radius * radius;
-        }
-        // Bottom-left corner
-        if x < radius && y >= height - radius {
-            let dx = radius - x;
-            let dy = y - (height - radius - 1);
-            return dx * dx + dy * dy <= radius * radius;
-        }
-        // Bottom-right corner
-        if x >= width - radius && y >= height - radius {
-            let dx = x - (width - radius - 1);
-            let dy = y - (height - radius - 1);
-            return dx * dx + dy * dy <= radius * radius;
-        }
-        // Inside the rect (not in corner regions)
-        true
+    }
+
+    #[test]
+    fn test_cursor_mode_follows_slices() {
+        let slices = vec![
+            slice(0.0, 1000.0, false, false),
+            slice(1000.0, 2000.0, true, false),
+            slice(2000.0, 3000.0, false, true),
+        ];
+
+        assert_eq!(cursor_mode_at(&slices, true, 500.0), CursorMode::Smoothed);
+        assert_eq!(cursor_mode_at(&slices, true, 1000.0), CursorMode::Hidden);
+        assert_eq!(cursor_mode_at(&slices, true, 2500.0), CursorMode::Raw);
+        // Outside every slice the project-wide setting applies
+        assert_eq!(cursor_mode_at(&slices, true, 5000.0), CursorMode::Smoothed);
+        assert_eq!(cursor_mode_at(&slices, false, 500.0), CursorMode::Raw);
     }
 }
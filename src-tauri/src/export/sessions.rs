@@ -0,0 +1,816 @@
+//! Exporting recordings with several sessions
+//!
+//! Pausing ends a session and every channel writes new files when recording
+//! resumes. Export works on a single timeline, so each track's session files
+//! are joined into one file in a scratch directory. Every session is padded
+//! or trimmed to its recorded length so the tracks stay in step and each
+//! session's audio starts where its session does. A session missing a file
+//! (e.g. a webcam that failed to reopen on resume) is filled with black or
+//! silence. Input events are shifted by their session's start. Tracks a
+//! session's scene mutes are left out of that session the same way.
+//!
+//! Recording scenes belong to a session and their slices and zoom ranges are
+//! relative to its start; `timeline_offset_ms` places them on the joined
+//! timeline. Audio is lined up with the video as it's joined (see
+//! `alignment`). Single-session recordings use their files as recorded,
+//! unless their audio needs lining up. Audio-only recordings are exported
+//! over a black video as long as the recording.
+//!
+//! Video sessions recorded with the same codec, size and frame rate, each
+//! at least as long as its session, are joined by stream copy; the others
+//! are re-encoded. Joins report their progress and stop when cancelled, if
+//! the export asked to be told (`observe`).
+
+use super::alignment::AudioAlignment;
+use super::archive::probe_duration_ms;
+use super::downmix::{self, probe_channels, MixLayout};
+use super::ffmpeg::{capture_rate_args, VideoDecoder};
+use super::types::ExportError;
+use crate::project::encryption::{create_private_dir, decrypted_cache_dir, readable_path};
+use crate::project::schema::{AudioTrack, TrackMutes};
+use crate::recorder::manifest::{resolve_file, ChannelAnchor, FileKind, RecordingManifest};
+use crate::recorder::sessions::{SessionEntry, SessionList};
+use crate::utils::process::track;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Sample rate joined audio is resampled to
+const JOINED_SAMPLE_RATE: u32 = 48_000;
+
+/// Size and frame rate of the black video audio-only recordings export over
+const BLANK_VIDEO_SIZE: (u32, u32) = (1920, 1080);
+const BLANK_VIDEO_FPS: f64 = 30.0;
+
+/// Told how far through joining the sessions of a track of `kind` is,
+/// from 0 to 1
+pub type JoinProgress<'a> = &'a (dyn Fn(FileKind, f64) + Sync);
+
+/// The files of a recording directory, across its sessions
+pub struct SessionMedia<'a> {
+    recording_dir: PathBuf,
+    manifest: Option<RecordingManifest>,
+    /// Sessions with recorded time, in order
+    sessions: Vec<SessionEntry>,
+    /// Mute/solo state of each session's recording scene
+    mutes: BTreeMap<usize, TrackMutes>,
+    /// Stops a join partway once set
+    cancel: Option<&'a AtomicBool>,
+    on_progress: Option<JoinProgress<'a>>,
+}
+
+impl<'a> SessionMedia<'a> {
+    pub fn load(recording_dir: &Path) -> Self {
+        let sessions = SessionList::load(recording_dir)
+            .map(|list| list.sessions)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|s| s.duration_ms > 0.0)
+            .collect();
+        Self {
+            recording_dir: recording_dir.to_path_buf(),
+            manifest: RecordingManifest::read(recording_dir),
+            sessions,
+            mutes: BTreeMap::new(),
+            cancel: None,
+            on_progress: None,
+        }
+    }
+
+    /// Report the progress of joins to `on_progress`, and stop them with
+    /// `Cancelled` once `cancel` is set
+    pub fn observe<'b>(
+        self,
+        cancel: &'b AtomicBool,
+        on_progress: JoinProgress<'b>,
+    ) -> SessionMedia<'b> {
+        SessionMedia {
+            recording_dir: self.recording_dir,
+            manifest: self.manifest,
+            sessions: self.sessions,
+            mutes: self.mutes,
+            cancel: Some(cancel),
+            on_progress: Some(on_progress),
+        }
+    }
+
+    /// Leave out the tracks each session's scene mutes, as if they weren't
+    /// recorded in that session
+    pub fn mute_tracks(mut self, mutes: &BTreeMap<usize, TrackMutes>) -> Self {
+        self.mutes = mutes.clone();
+        self
+    }
+
+    pub fn manifest(&self) -> Option<&RecordingManifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Whether the session files need joining
+    pub fn is_joined(&self) -> bool {
+        self.sessions.len() > 1
+    }
+
+    /// Where a session starts on the joined timeline, or None if it wasn't
+    /// recorded
+    pub fn timeline_offset_ms(&self, session_index: usize) -> Option<f64> {
+        if self.sessions.is_empty() {
+            // Bundles from before the manifest only have session 0
+            return (session_index == 0).then_some(0.0);
+        }
+        self.sessions
+            .iter()
+            .find(|s| s.index == session_index)
+            .map(|s| s.timeline_start_ms)
+    }
+
+    /// Recorded file of `kind` in a session, or None if the session's
+    /// scene mutes it
+    pub fn session_file(&self, kind: FileKind, session_index: usize) -> Option<PathBuf> {
+        if self.is_muted(kind, session_index) {
+            return None;
+        }
+        resolve_file(&self.recording_dir, self.manifest.as_ref(), kind, session_index)
+    }
+
+    /// Whether a session's scene mutes its track of `kind`. The camera of a
+    /// camera-only recording is its main video, so it's never muted.
+    fn is_muted(&self, kind: FileKind, session_index: usize) -> bool {
+        let Some(mutes) = self.mutes.get(&session_index) else {
+            return false;
+        };
+        match kind {
+            FileKind::MicAudio => !mutes.is_audible(AudioTrack::Microphone),
+            FileKind::SystemAudio => !mutes.is_audible(AudioTrack::SystemAudio),
+            FileKind::WebcamVideo => mutes.camera && self.primary_kind() == FileKind::ScreenVideo,
+            _ => false,
+        }
+    }
+
+    /// Kind of the main video: the screen, or the webcam for camera-only
+    /// recordings
+    pub fn primary_kind(&self) -> FileKind {
+        let has_screen = self
+            .session_indices()
+            .any(|index| self.session_file(FileKind::ScreenVideo, index).is_some());
+        if has_screen {
+            FileKind::ScreenVideo
+        } else {
+            FileKind::WebcamVideo
+        }
+    }
+
+    /// The main video covering the whole recording (see `primary_kind`).
+    /// Audio-only recordings have none, so a black video as long as the
+    /// recording is written to `scratch` in its place. None if nothing was
+    /// recorded.
+    pub fn primary_video(
+        &self,
+        scratch: &Path,
+        fps: Option<f64>,
+    ) -> Result<Option<PathBuf>, ExportError> {
+        let kind = self.primary_kind();
+        if !self.session_files(kind).is_empty() {
+            return self.media_file(kind, scratch, fps);
+        }
+        let duration_ms: f64 = self.sessions.iter().map(|s| s.duration_ms).sum();
+        if duration_ms <= 0.0 {
+            return Ok(None);
+        }
+        let output = scratch.join("blank.mp4");
+        write_blank_video(duration_ms, &output)?;
+        Ok(Some(output))
+    }
+
+    /// Media file of `kind` covering the whole recording, or None if no
+    /// session recorded one. Files of several sessions are joined into
+    /// `scratch`, as is audio that needs lining up with the video; `fps` is
+    /// the rate the video was captured at, which its frames are read at and
+    /// the joined video written at, by default the first session's rate.
+    pub fn media_file(
+        &self,
+        kind: FileKind,
+        scratch: &Path,
+        fps: Option<f64>,
+    ) -> Result<Option<PathBuf>, ExportError> {
+        let primary = self.primary_kind();
+        let parts = if self.is_joined() {
+            self.sessions
+                .iter()
+                .map(|session| self.join_part(kind, primary, session))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            let index = self.session_indices().next().unwrap_or(0);
+            let file = self.session_file(kind, index);
+            let anchored = self.anchors(kind, primary, index).is_some();
+            let Some(session) = self.sessions.first().filter(|_| anchored) else {
+                return Ok(file);
+            };
+            let part = self.join_part(kind, primary, session)?;
+            if !part.alignment.is_needed() {
+                return Ok(file);
+            }
+            vec![part]
+        };
+        let Some(first) = parts.iter().find_map(|p| p.path.clone()) else {
+            return Ok(None);
+        };
+
+        let output = scratch.join(joined_name(kind));
+        let args = match kind {
+            FileKind::ScreenVideo | FileKind::WebcamVideo => {
+                let (width, height, _, stream_fps) = VideoDecoder::probe_video(&first)?;
+                if can_copy_join(&parts, fps) {
+                    let list = scratch.join(format!("{}.ffconcat", joined_name(kind)));
+                    std::fs::write(&list, concat_list(&parts))?;
+                    copy_join_args(&list, &output)
+                } else {
+                    join_video_args(&parts, width, height, fps.unwrap_or(stream_fps), fps, &output)
+                }
+            }
+            FileKind::MicAudio | FileKind::SystemAudio => {
+                let channels: Vec<u32> = parts
+                    .iter()
+                    .map(|p| p.path.as_deref().map_or(1, probe_channels))
+                    .collect();
+                join_audio_args(&parts, &channels, &output)
+            }
+            _ => {
+                return Err(ExportError::InvalidConfig(format!(
+                    "{:?} files can't be joined",
+                    kind
+                )))
+            }
+        };
+
+        tracing::info!(
+            "Joining {} sessions of {:?} into {:?}",
+            parts.len(),
+            kind,
+            output
+        );
+        let total_ms: f64 = parts.iter().map(|p| p.duration_ms).sum();
+        self.run_join(kind, args, total_ms)?;
+        Ok(Some(output))
+    }
+
+    /// Run a join's FFmpeg `args`, reporting how far through the `total_ms`
+    /// of output it is and killing it if the join is cancelled
+    fn run_join(
+        &self,
+        kind: FileKind,
+        mut args: Vec<String>,
+        total_ms: f64,
+    ) -> Result<(), ExportError> {
+        let output = args.pop().unwrap_or_default();
+        args.extend(["-progress", "pipe:1", "-nostats"].map(String::from));
+        args.push(output);
+
+        let child = Command::new("ffmpeg")
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+        let mut child = track(child, "session join");
+
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if self.cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(ExportError::Cancelled);
+                }
+                let time_us = line
+                    .strip_prefix("out_time_us=")
+                    .and_then(|t| t.parse::<f64>().ok());
+                if let (Some(time_us), Some(on_progress)) = (time_us, self.on_progress) {
+                    on_progress(kind, (time_us / 1000.0 / total_ms.max(1.0)).clamp(0.0, 1.0));
+                }
+            }
+        }
+
+        let result = child.wait_with_output()?;
+        if !result.status.success() {
+            return Err(ExportError::Ffmpeg(format!(
+                "Joining sessions failed: {}",
+                String::from_utf8_lossy(&result.stderr)
+            )));
+        }
+        Ok(())
+    }
+
+    /// How a session's audio of `kind` lines up with the main video, as
+    /// export lines it up
+    pub fn alignment(
+        &self,
+        kind: FileKind,
+        session_index: usize,
+    ) -> Result<AudioAlignment, ExportError> {
+        let anchors = self.anchors(kind, self.primary_kind(), session_index);
+        match (anchors, self.session_file(kind, session_index)) {
+            (Some((video, audio)), Some(file)) => {
+                let duration_ms = probe_duration_ms(&readable_path(&file)?).ok();
+                Ok(AudioAlignment::new(video, audio, duration_ms))
+            }
+            _ => Ok(AudioAlignment::default()),
+        }
+    }
+
+    /// A session's file of `kind` to join, with how its audio lines up
+    /// with the `primary` video
+    fn join_part(
+        &self,
+        kind: FileKind,
+        primary: FileKind,
+        session: &SessionEntry,
+    ) -> Result<JoinPart, ExportError> {
+        let Some(file) = self.session_file(kind, session.index) else {
+            return Ok(JoinPart {
+                duration_ms: session.duration_ms,
+                path: None,
+                alignment: AudioAlignment::default(),
+            });
+        };
+        // Joining reads the media, so encrypted files are decrypted
+        let path = readable_path(&file)?;
+        let alignment = match self.anchors(kind, primary, session.index) {
+            Some((video, audio)) => {
+                AudioAlignment::new(video, audio, probe_duration_ms(&path).ok())
+            }
+            None => AudioAlignment::default(),
+        };
+        Ok(JoinPart {
+            duration_ms: session.duration_ms,
+            path: Some(path),
+            alignment,
+        })
+    }
+
+    /// Anchors of the `primary` video and the audio of `kind` in a session,
+    /// if `kind` is audio and both were anchored
+    fn anchors(
+        &self,
+        kind: FileKind,
+        primary: FileKind,
+        session_index: usize,
+    ) -> Option<(&ChannelAnchor, &ChannelAnchor)> {
+        if !matches!(kind, FileKind::MicAudio | FileKind::SystemAudio) {
+            return None;
+        }
+        let manifest = self.manifest.as_ref()?;
+        Some((
+            manifest.anchor(primary, session_index)?,
+            manifest.anchor(kind, session_index)?,
+        ))
+    }
+
+    /// Events of every session, loaded with `load` from each session's file
+    /// of `kind` and moved onto the joined timeline with `shift`
+    pub fn events<T>(
+        &self,
+        kind: FileKind,
+        load: impl Fn(Option<PathBuf>) -> Result<Vec<T>, ExportError>,
+        shift: impl Fn(&mut T, f64),
+    ) -> Result<Vec<T>, ExportError> {
+        if !self.is_joined() {
+            let index = self.session_indices().next().unwrap_or(0);
+            return load(self.session_file(kind, index));
+        }
+
+        let mut events = Vec::new();
+        for session in &self.sessions {
+            let mut session_events = load(self.session_file(kind, session.index))?;
+            for event in &mut session_events {
+                shift(event, session.timeline_start_ms);
+            }
+            events.extend(session_events);
+        }
+        Ok(events)
+    }
+
+    /// Files of `kind` from every session, in order
+    pub fn session_files(&self, kind: FileKind) -> Vec<PathBuf> {
+        self.session_indices()
+            .filter_map(|index| self.session_file(kind, index))
+            .collect()
+    }
+
+    fn session_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let legacy = self.sessions.is_empty().then_some(0);
+        self.sessions.iter().map(|s| s.index).chain(legacy)
+    }
+}
+
+/// One session's file in a join
+struct JoinPart {
+    duration_ms: f64,
+    /// None when the session didn't record this track
+    path: Option<PathBuf>,
+    /// Where audio sits relative to the session's video
+    alignment: AudioAlignment,
+}
+
+/// Run FFmpeg with `args`, describing a failure as `what` failed
+fn run_ffmpeg(args: &[String], what: &str) -> Result<(), ExportError> {
+    let result = Command::new("ffmpeg")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+    if !result.status.success() {
+        return Err(ExportError::Ffmpeg(format!(
+            "{} failed: {}",
+            what,
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+    Ok(())
+}
+
+/// Write a black video `duration_ms` long to `output`, to stand in for the
+/// screen of an audio-only recording
+fn write_blank_video(duration_ms: f64, output: &Path) -> Result<(), ExportError> {
+    tracing::info!("Writing a {}ms blank video to {:?}", duration_ms, output);
+    run_ffmpeg(&blank_video_args(duration_ms, output), "Writing a blank video")
+}
+
+/// A black video as long as `audio`, for playing an audio-only recording in
+/// the editor. Kept with the decrypted media and cleared with it on exit.
+pub fn blank_video_for(audio: &Path) -> Result<PathBuf, ExportError> {
+    let duration_ms = probe_duration_ms(&readable_path(audio)?)?.round();
+    let dir = decrypted_cache_dir();
+    let output = dir.join(format!("blank-{}ms.mp4", duration_ms));
+    if output.exists() {
+        return Ok(output);
+    }
+    create_private_dir(&dir)?;
+    // Write next to the final path so a failed write isn't reused
+    let partial = dir.join(format!("blank-{}ms.partial.mp4", duration_ms));
+    if let Err(e) = write_blank_video(duration_ms, &partial) {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, &output)?;
+    Ok(output)
+}
+
+/// FFmpeg arguments for `write_blank_video`
+fn blank_video_args(duration_ms: f64, output: &Path) -> Vec<String> {
+    let part = JoinPart {
+        duration_ms,
+        path: None,
+        alignment: AudioAlignment::default(),
+    };
+    let (width, height) = BLANK_VIDEO_SIZE;
+    join_video_args(&[part], width, height, BLANK_VIDEO_FPS, None, output)
+}
+
+/// Whether video parts can be joined by stream copy: every session has a
+/// file long enough to cut to its length, all with the same codec, size and
+/// frame rate, and none needs retiming to `input_fps`
+fn can_copy_join(parts: &[JoinPart], input_fps: Option<f64>) -> bool {
+    let mut formats = Vec::new();
+    for part in parts {
+        let Some(path) = &part.path else {
+            return false;
+        };
+        let (Ok((width, height, _, fps)), Some(codec), Ok(duration_ms)) = (
+            VideoDecoder::probe_video(path),
+            probe_codec(path),
+            probe_duration_ms(path),
+        ) else {
+            return false;
+        };
+        // Padding a short session needs new frames
+        let too_short = duration_ms + 1000.0 / fps < part.duration_ms;
+        if too_short || !capture_rate_args(input_fps, fps).is_empty() {
+            return false;
+        }
+        formats.push((codec, width, height, (fps * 1000.0).round() as u64));
+    }
+    formats.windows(2).all(|pair| pair[0] == pair[1])
+}
+
+/// Codec and pixel format of a file's video stream
+fn probe_codec(path: &Path) -> Option<String> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=codec_name,pix_fmt",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let codec = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!codec.is_empty()).then_some(codec)
+}
+
+/// Concat demuxer script cutting each part's file to its session's length
+fn concat_list(parts: &[JoinPart]) -> String {
+    let mut list = "ffconcat version 1.0\n".to_string();
+    for part in parts {
+        if let Some(path) = &part.path {
+            let path = path.to_string_lossy().replace('\'', "'\\''");
+            list.push_str(&format!(
+                "file '{}'\noutpoint {:.3}\n",
+                path,
+                part.duration_ms / 1000.0
+            ));
+        }
+    }
+    list
+}
+
+/// FFmpeg arguments joining the files in a concat `list` by stream copy
+fn copy_join_args(list: &Path, output: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["-y", "-v", "error", "-f", "concat", "-safe", "0", "-i"]
+        .map(String::from)
+        .into();
+    args.push(list.to_string_lossy().to_string());
+    args.extend(["-map", "0:v:0", "-an", "-c", "copy"].map(String::from));
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+/// Scratch file name for a joined track
+fn joined_name(kind: FileKind) -> String {
+    let name = format!("joined{}", kind.suffix());
+    match kind {
+        // Audio stays uncompressed until the final encode
+        FileKind::MicAudio | FileKind::SystemAudio => {
+            Path::new(&name).with_extension("wav").to_string_lossy().to_string()
+        }
+        _ => name,
+    }
+}
+
+/// FFmpeg arguments joining video sessions at `width`x`height` and `fps`.
+/// Sessions are read at `input_fps` when given, retiming frames stamped at
+/// another rate. Each session is scaled into the frame, then held on its
+/// last frame or cut to its recorded length.
+fn join_video_args(
+    parts: &[JoinPart],
+    width: u32,
+    height: u32,
+    fps: f64,
+    input_fps: Option<f64>,
+    output: &Path,
+) -> Vec<String> {
+    let mut args = vec!["-y".to_string(), "-v".to_string(), "error".to_string()];
+    let mut filters = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        let secs = part.duration_ms / 1000.0;
+        match &part.path {
+            Some(path) => {
+                if let Some(input_fps) = input_fps {
+                    args.extend(["-r".to_string(), input_fps.to_string()]);
+                }
+                args.extend(["-i".to_string(), path.to_string_lossy().to_string()]);
+            }
+            None => args.extend([
+                "-f".to_string(),
+                "lavfi".to_string(),
+                "-i".to_string(),
+                format!("color=c=black:s={}x{}:r={}:d={}", width, height, fps, secs),
+            ]),
+        }
+        filters.push(format!(
+            "[{i}:v]scale={w}:{h}:force_original_aspect_ratio=decrease,\
+             pad={w}:{h}:(ow-iw)/2:(oh-ih)/2:black,setsar=1,fps={fps},\
+             tpad=stop_mode=clone:stop_duration={secs},trim=duration={secs},\
+             setpts=PTS-STARTPTS[v{i}]",
+            i = i,
+            w = width,
+            h = height,
+            fps = fps,
+            secs = secs
+        ));
+    }
+    let labels: String = (0..parts.len()).map(|i| format!("[v{}]", i)).collect();
+    filters.push(format!("{}concat=n={}:v=1:a=0[v]", labels, parts.len()));
+
+    args.extend(["-filter_complex".to_string(), filters.join(";")]);
+    args.extend(
+        [
+            "-map", "[v]", "-an", "-c:v", "libx264", "-preset", "ultrafast", "-crf", "12",
+            "-pix_fmt", "yuv420p",
+        ]
+        .map(String::from),
+    );
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+/// FFmpeg arguments joining audio sessions, each lined up with its video and
+/// padded with silence or cut to its recorded length. `channels` are the
+/// parts' channel counts; the join keeps the widest layout.
+fn join_audio_args(parts: &[JoinPart], channels: &[u32], output: &Path) -> Vec<String> {
+    let widest = channels.iter().copied().max().unwrap_or(1);
+    let layout = match widest {
+        1 => "mono",
+        2 => "stereo",
+        n => MixLayout::Surround(n).name(),
+    };
+
+    let mut args = vec!["-y".to_string(), "-v".to_string(), "error".to_string()];
+    let mut filters = Vec::new();
+    for (i, (part, &channels)) in parts.iter().zip(channels).enumerate() {
+        match &part.path {
+            Some(path) => {
+                args.extend(downmix::input_args(channels));
+                args.extend(["-i".to_string(), path.to_string_lossy().to_string()]);
+            }
+            None => args.extend([
+                "-f".to_string(),
+                "lavfi".to_string(),
+                "-i".to_string(),
+                format!("anullsrc=r={}:cl={}", JOINED_SAMPLE_RATE, layout),
+            ]),
+        }
+        let aligned: String = part
+            .alignment
+            .filters()
+            .into_iter()
+            .map(|filter| filter + ",")
+            .collect();
+        filters.push(format!(
+            "[{}:a]aresample={},aformat=sample_fmts=fltp:channel_layouts={},{}apad,\
+             atrim=duration={},asetpts=PTS-STARTPTS[a{}]",
+            i,
+            JOINED_SAMPLE_RATE,
+            layout,
+            aligned,
+            part.duration_ms / 1000.0,
+            i
+        ));
+    }
+    let labels: String = (0..parts.len()).map(|i| format!("[a{}]", i)).collect();
+    filters.push(format!("{}concat=n={}:v=0:a=1[a]", labels, parts.len()));
+
+    args.extend(["-filter_complex".to_string(), filters.join(";")]);
+    // RF64 once the WAV passes the 4GB a RIFF header can describe
+    args.extend(["-map", "[a]", "-c:a", "pcm_s16le", "-rf64", "auto"].map(String::from));
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_are_joined_at_their_recorded_length() {
+        let parts = [
+            JoinPart {
+                duration_ms: 3000.0,
+                path: Some(PathBuf::from("/rec/recording-0-webcam.mp4")),
+                alignment: AudioAlignment::default(),
+            },
+            JoinPart {
+                duration_ms: 1500.0,
+                path: None,
+                alignment: AudioAlignment::default(),
+            },
+        ];
+        let args = join_video_args(&parts, 1280, 720, 30.0, None, Path::new("/tmp/joined.mp4"));
+        let joined = args.join(" ");
+        assert!(joined.contains("-i /rec/recording-0-webcam.mp4 -f lavfi"));
+        // The missing session is black for as long as it lasted
+        assert!(joined.contains("-i color=c=black:s=1280x720:r=30:d=1.5"));
+        assert!(joined.contains("tpad=stop_mode=clone:stop_duration=3,trim=duration=3,"));
+        assert!(joined.contains("[v0][v1]concat=n=2:v=1:a=0[v]"));
+        // Captured frames are read at the rate capture achieved
+        let args = join_video_args(&parts, 1280, 720, 48.0, Some(48.0), Path::new("/tmp/j.mp4"));
+        let joined = args.join(" ");
+        assert!(joined.contains("-r 48 -i /rec/recording-0-webcam.mp4 -f lavfi"));
+
+        let parts = [
+            JoinPart {
+                duration_ms: 2000.0,
+                path: Some(PathBuf::from("/rec/recording-0-mic.m4a")),
+                alignment: AudioAlignment::default(),
+            },
+            JoinPart {
+                duration_ms: 500.0,
+                path: Some(PathBuf::from("/rec/recording-1-mic.m4a")),
+                alignment: AudioAlignment {
+                    lead_ms: 40.0,
+                    tempo: 1.0,
+                },
+            },
+        ];
+        let args = join_audio_args(&parts, &[1, 2], Path::new("/tmp/joined-mic.wav"));
+        let joined = args.join(" ");
+        assert!(joined.contains(
+            "[0:a]aresample=48000,aformat=sample_fmts=fltp:channel_layouts=stereo,apad,\
+             atrim=duration=2,asetpts=PTS-STARTPTS[a0]"
+        ));
+        // The second session's mic started 40ms after its video
+        assert!(joined.contains("stereo,adelay=40:all=1,apad,atrim=duration=0.5"));
+        assert!(joined.ends_with("-c:a pcm_s16le -rf64 auto /tmp/joined-mic.wav"));
+
+        assert_eq!(joined_name(FileKind::SystemAudio), "joined-system.wav");
+        assert_eq!(joined_name(FileKind::WebcamVideo), "joined-webcam.mp4");
+    }
+
+    #[test]
+    fn test_copy_join_cuts_each_session_to_its_length() {
+        let parts = [
+            JoinPart {
+                duration_ms: 3000.0,
+                path: Some(PathBuf::from("/rec/recording-0-display.mp4")),
+                alignment: AudioAlignment::default(),
+            },
+            JoinPart {
+                duration_ms: 1250.0,
+                path: Some(PathBuf::from("/rec/Bob's/recording-1-display.mp4")),
+                alignment: AudioAlignment::default(),
+            },
+        ];
+        assert_eq!(
+            concat_list(&parts),
+            "ffconcat version 1.0\n\
+             file '/rec/recording-0-display.mp4'\noutpoint 3.000\n\
+             file '/rec/Bob'\\''s/recording-1-display.mp4'\noutpoint 1.250\n"
+        );
+        let args = copy_join_args(Path::new("/tmp/list"), Path::new("/tmp/joined.mp4"));
+        assert_eq!(
+            args.join(" "),
+            "-y -v error -f concat -safe 0 -i /tmp/list -map 0:v:0 -an -c copy /tmp/joined.mp4"
+        );
+        // A session without a file can't be copied
+        assert!(!can_copy_join(
+            &[JoinPart {
+                duration_ms: 1000.0,
+                path: None,
+                alignment: AudioAlignment::default(),
+            }],
+            None
+        ));
+    }
+
+    #[test]
+    fn test_blank_video_covers_the_recording() {
+        let args = blank_video_args(2500.0, Path::new("/tmp/blank.mp4"));
+        let joined = args.join(" ");
+        assert!(joined.contains("-f lavfi -i color=c=black:s=1920x1080:r=30:d=2.5 "));
+        assert!(joined.contains("trim=duration=2.5,"));
+        assert!(joined.ends_with("-pix_fmt yuv420p /tmp/blank.mp4"));
+    }
+
+    #[test]
+    fn test_muted_tracks_are_left_out_of_their_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let kinds = [
+            FileKind::ScreenVideo,
+            FileKind::WebcamVideo,
+            FileKind::MicAudio,
+            FileKind::SystemAudio,
+        ];
+        for kind in kinds {
+            std::fs::write(dir.path().join(kind.conventional_name(0)), b"").unwrap();
+        }
+        let muted = |mutes: TrackMutes| {
+            SessionMedia::load(dir.path()).mute_tracks(&BTreeMap::from([(0, mutes)]))
+        };
+
+        let media = muted(TrackMutes {
+            microphone: true,
+            camera: true,
+            ..Default::default()
+        });
+        assert!(media.session_file(FileKind::MicAudio, 0).is_none());
+        assert!(media.session_file(FileKind::WebcamVideo, 0).is_none());
+        assert!(media.session_file(FileKind::SystemAudio, 0).is_some());
+        // Mutes belong to their session
+        let later = BTreeMap::from([(1, media.mutes[&0].clone())]);
+        let media = SessionMedia::load(dir.path()).mute_tracks(&later);
+        assert!(media.session_file(FileKind::MicAudio, 0).is_some());
+
+        // Soloing a track silences the other
+        let media = muted(TrackMutes {
+            solo: Some(AudioTrack::Microphone),
+            ..Default::default()
+        });
+        assert!(media.session_file(FileKind::MicAudio, 0).is_some());
+        assert!(media.session_file(FileKind::SystemAudio, 0).is_none());
+
+        // The camera of a camera-only recording is its video
+        std::fs::remove_file(dir.path().join(FileKind::ScreenVideo.conventional_name(0))).unwrap();
+        let media = muted(TrackMutes {
+            camera: true,
+            ..Default::default()
+        });
+        assert!(media.session_file(FileKind::WebcamVideo, 0).is_some());
+    }
+}
@@ -3,7 +3,14 @@
 //! This module defines the types used for video export configuration,
 //! progress tracking, and error handling.
 
+use crate::export::downmix::MixLayout;
+use crate::project::encryption::EncryptionError;
+use crate::project::lut::resolve_lut;
+use crate::project::schema::{AudioConfig, AudioTrack, ColorLut, TrackMutes};
+use crate::recorder::state::SUPPORTED_AUDIO_SAMPLE_RATES;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Export format options
@@ -35,10 +42,71 @@ impl ExportFormat {
     }
 }
 
+/// Audio codec of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCodec {
+    Aac,
+    Opus,
+    /// Lossless
+    Flac,
+}
+
+impl AudioCodec {
+    /// Codec used when an export doesn't pick one: what the format's
+    /// players most widely support
+    pub fn default_for(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Webm => AudioCodec::Opus,
+            ExportFormat::Mp4 | ExportFormat::Gif => AudioCodec::Aac,
+        }
+    }
+
+    /// Get the FFmpeg audio encoder for this codec
+    pub fn encoder(&self) -> &'static str {
+        match self {
+            AudioCodec::Aac => "aac",
+            AudioCodec::Opus => "libopus",
+            AudioCodec::Flac => "flac",
+        }
+    }
+
+    /// Whether the format's container can hold the codec. WebM only allows
+    /// Vorbis and Opus.
+    pub fn fits(&self, format: ExportFormat) -> bool {
+        match format {
+            ExportFormat::Mp4 => true,
+            ExportFormat::Webm => *self == AudioCodec::Opus,
+            ExportFormat::Gif => false,
+        }
+    }
+
+    /// Whether the encoder takes audio at `sample_rate`
+    pub fn supports_sample_rate(&self, sample_rate: u32) -> bool {
+        match self {
+            AudioCodec::Opus => [8000, 12000, 16000, 24000, 48000].contains(&sample_rate),
+            AudioCodec::Aac | AudioCodec::Flac => {
+                SUPPORTED_AUDIO_SAMPLE_RATES.contains(&sample_rate)
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            AudioCodec::Aac => "AAC",
+            AudioCodec::Opus => "Opus",
+            AudioCodec::Flac => "FLAC",
+        }
+    }
+}
+
 /// Export quality levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ExportQuality {
+    /// Fast review pass: half resolution, fastest encoder settings, and no
+    /// decorative compositing
+    Draft,
     Low,
     Medium,
     High,
@@ -50,6 +118,7 @@ impl ExportQuality {
     /// Lower values = higher quality, larger files
     pub fn crf(&self) -> u8 {
         match self {
+            ExportQuality::Draft => 32,
             ExportQuality::Low => 28,
             ExportQuality::Medium => 23,
             ExportQuality::High => 18,
@@ -62,12 +131,42 @@ impl ExportQuality {
     /// Get the FFmpeg preset for H.264 encoding
     pub fn h264_preset(&self) -> &'static str {
         match self {
+            ExportQuality::Draft => "ultrafast",
             ExportQuality::Low => "faster",
             ExportQuality::Medium => "medium",
             ExportQuality::High => "slow",
             ExportQuality::Lossless => "veryslow",
         }
     }
+
+    /// Extra VP9 arguments trading quality for encode speed
+    pub fn vp9_speed_args(&self) -> &'static [&'static str] {
+        match self {
+            ExportQuality::Draft => &["-deadline", "realtime", "-cpu-used", "8"],
+            _ => &[],
+        }
+    }
+
+    /// Bitrate for encoders without a constant quality mode (hardware
+    /// encoders), in kbit/s, scaled to the output's pixel rate
+    pub fn bitrate_kbps(&self, width: u32, height: u32, fps: u32) -> u64 {
+        let bits_per_pixel = match self {
+            ExportQuality::Draft => 0.04,
+            ExportQuality::Low => 0.06,
+            ExportQuality::Medium => 0.1,
+            ExportQuality::High => 0.16,
+            ExportQuality::Lossless => 0.3,
+        };
+        (width as f64 * height as f64 * fps as f64 * bits_per_pixel / 1000.0) as u64
+    }
+
+    /// Factor applied to the source resolution when no explicit size is requested
+    pub fn resolution_scale(&self) -> f64 {
+        match self {
+            ExportQuality::Draft => 0.5,
+            _ => 1.0,
+        }
+    }
 }
 
 /// A single segment to include in export (represents trim/cut edits)
@@ -163,6 +262,261 @@ pub struct ExportOptions {
     pub screen_edits: Option<TrackEdits>,
     /// Camera track edits (optional - if None, use full source)
     pub camera_edits: Option<TrackEdits>,
+    /// Whether to render annotations drawn during recording. Only the
+    /// export pipeline draws them; exports with edits leave them out.
+    #[serde(default = "default_include_annotations")]
+    pub include_annotations: bool,
+    /// Microphone offset in milliseconds (positive delays the mic track)
+    #[serde(default)]
+    pub mic_offset_ms: f64,
+    /// Whether to remove speaker echo from the microphone, using the system
+    /// audio as the reference
+    #[serde(default)]
+    pub cancel_echo: bool,
+    /// Microphone volume (None = the project's)
+    #[serde(default)]
+    pub microphone_volume: Option<f64>,
+    /// System audio volume (None = the project's)
+    #[serde(default)]
+    pub system_volume: Option<f64>,
+    /// Whether to add project markers as chapters (MP4 only)
+    #[serde(default = "default_include_chapters")]
+    pub include_chapters: bool,
+    /// Size budget for GIF exports in megabytes (None = no limit)
+    #[serde(default)]
+    pub max_size_mb: Option<f64>,
+    /// Fading trail drawn behind the cursor (None = no trail)
+    #[serde(default)]
+    pub cursor_trail: Option<CursorTrail>,
+    /// 3D LUT applied to the screen track, filled in from the project's
+    /// color LUT by `apply_color_lut` when the export starts. Never taken
+    /// from the frontend, which could otherwise point FFmpeg at any file.
+    #[serde(skip)]
+    pub screen_lut: Option<PathBuf>,
+    /// 3D LUT applied to the camera track, filled in like `screen_lut`
+    #[serde(skip)]
+    pub camera_lut: Option<PathBuf>,
+    /// Whether to also write the webcam track, with its edits and the
+    /// audio, to its own file next to the export
+    #[serde(default)]
+    pub export_webcam_track: bool,
+    /// Memory the queue of frames waiting for the encoder may use, in
+    /// megabytes (None = `DEFAULT_FRAME_MEMORY_MB`)
+    #[serde(default)]
+    pub frame_memory_mb: Option<u32>,
+    /// Whether frames that don't fit in the memory budget are written to a
+    /// temporary file instead of making compositing wait for the encoder
+    #[serde(default)]
+    pub spill_frames_to_disk: bool,
+    /// Whether surround system audio stays surround instead of being mixed
+    /// down to stereo (MP4 only)
+    #[serde(default)]
+    pub preserve_surround: bool,
+    /// Audio codec (None = `AudioCodec::default_for` the format)
+    #[serde(default)]
+    pub audio_codec: Option<AudioCodec>,
+    /// Audio sample rate in Hz (None = the recorded rate)
+    #[serde(default)]
+    pub audio_sample_rate: Option<u32>,
+    /// Hardware H.264 encoder the export benchmark picked for this machine,
+    /// filled in when the export starts (None = libx264)
+    #[serde(skip)]
+    pub h264_encoder: Option<String>,
+    /// Mute/solo state of each session's recording scene, filled in from
+    /// the project by `apply_track_mutes` when the export starts
+    #[serde(skip)]
+    pub track_mutes: BTreeMap<usize, TrackMutes>,
+}
+
+/// Fading trail behind the cursor, for following fast mouse travel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorTrail {
+    /// How far back the trail reaches in milliseconds
+    #[serde(default = "default_trail_length_ms")]
+    pub length_ms: f64,
+    /// Opacity at the cursor (0.0-1.0), fading to 0 at the end of the trail
+    #[serde(default = "default_trail_opacity")]
+    pub opacity: f64,
+    /// Trail width in pixels at the cursor
+    #[serde(default = "default_trail_width")]
+    pub width: f64,
+    /// CSS hex color
+    #[serde(default = "default_trail_color")]
+    pub color: String,
+}
+
+impl Default for CursorTrail {
+    fn default() -> Self {
+        Self {
+            length_ms: default_trail_length_ms(),
+            opacity: default_trail_opacity(),
+            width: default_trail_width(),
+            color: default_trail_color(),
+        }
+    }
+}
+
+fn default_trail_length_ms() -> f64 {
+    400.0
+}
+
+fn default_trail_opacity() -> f64 {
+    0.6
+}
+
+fn default_trail_width() -> f64 {
+    8.0
+}
+
+fn default_trail_color() -> String {
+    "#FFFFFF".to_string()
+}
+
+fn default_include_annotations() -> bool {
+    true
+}
+
+fn default_include_chapters() -> bool {
+    true
+}
+
+/// Default memory budget for queued export frames (about 15 frames at 4K)
+pub const DEFAULT_FRAME_MEMORY_MB: u32 = 512;
+
+impl ExportOptions {
+    /// Drop tracks muted in the editor (or silenced by a soloed track) from
+    /// the sessions whose scenes mute them (see `SessionMedia::mute_tracks`)
+    pub fn apply_track_mutes(&mut self, mutes: BTreeMap<usize, TrackMutes>) {
+        self.track_mutes = mutes;
+    }
+
+    /// Use the project's track volumes where none were given
+    pub fn apply_audio_volumes(&mut self, audio: &AudioConfig) {
+        self.microphone_volume.get_or_insert(audio.microphone_volume);
+        self.system_volume.get_or_insert(audio.system_volume);
+    }
+
+    /// Volume an audio track is mixed at
+    pub fn volume(&self, track: AudioTrack) -> f64 {
+        match track {
+            AudioTrack::Microphone => self.microphone_volume,
+            AudioTrack::SystemAudio => self.system_volume,
+        }
+        .unwrap_or(1.0)
+    }
+
+    /// Apply the project's color LUT to the tracks it's enabled for
+    pub fn apply_color_lut(&mut self, bundle_path: &Path, lut: Option<&ColorLut>) {
+        let Some(lut) = lut else {
+            return;
+        };
+        let path = resolve_lut(bundle_path, lut);
+        self.screen_lut = path.clone().filter(|_| lut.apply_to_screen);
+        self.camera_lut = path.filter(|_| lut.apply_to_camera);
+    }
+
+    /// Check the audio settings suit the format. GIFs have no audio, so
+    /// anything goes.
+    pub fn validate(&self) -> Result<(), ExportError> {
+        if self.format == ExportFormat::Gif {
+            return Ok(());
+        }
+        let codec = self.audio_codec();
+        if !codec.fits(self.format) {
+            return Err(ExportError::InvalidConfig(format!(
+                "{} audio can't be exported to {}",
+                codec.name(),
+                self.format.extension().to_uppercase()
+            )));
+        }
+        if let Some(rate) = self.audio_sample_rate {
+            if !codec.supports_sample_rate(rate) {
+                return Err(ExportError::InvalidConfig(format!(
+                    "{} audio can't be exported at {}Hz",
+                    codec.name(),
+                    rate
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Codec the audio is encoded with
+    pub fn audio_codec(&self) -> AudioCodec {
+        self.audio_codec.unwrap_or_else(|| AudioCodec::default_for(self.format))
+    }
+
+    /// FFmpeg output options encoding the audio, mixed to `layout`
+    pub fn audio_codec_args(&self, layout: MixLayout) -> Vec<String> {
+        let codec = self.audio_codec();
+        let mut args = vec!["-c:a".to_string(), codec.encoder().to_string()];
+        if codec != AudioCodec::Flac {
+            args.extend(["-b:a".to_string(), layout.bitrate().to_string()]);
+        }
+        if let Some(rate) = self.audio_sample_rate {
+            args.extend(["-ar".to_string(), rate.to_string()]);
+        }
+        // FFmpeg before 6.0 treats Opus and FLAC in MP4 as experimental
+        if self.format == ExportFormat::Mp4 && codec != AudioCodec::Aac {
+            args.extend(["-strict".to_string(), "experimental".to_string()]);
+        }
+        args
+    }
+
+    /// FFmpeg output options encoding MP4 video of this size. Lossless
+    /// exports always use libx264, as hardware encoders have no constant
+    /// quality mode.
+    pub fn h264_args(&self, width: u32, height: u32, fps: u32) -> Vec<String> {
+        match self.h264_encoder.as_deref() {
+            Some(encoder) if self.quality != ExportQuality::Lossless => {
+                let kbps = self.quality.bitrate_kbps(width, height, fps);
+                vec![
+                    "-c:v".to_string(),
+                    encoder.to_string(),
+                    "-b:v".to_string(),
+                    format!("{}k", kbps),
+                    "-maxrate".to_string(),
+                    format!("{}k", kbps * 2),
+                    "-bufsize".to_string(),
+                    format!("{}k", kbps * 2),
+                ]
+            }
+            _ => vec![
+                "-c:v".to_string(),
+                "libx264".to_string(),
+                "-preset".to_string(),
+                self.quality.h264_preset().to_string(),
+                "-crf".to_string(),
+                self.quality.crf().to_string(),
+            ],
+        }
+    }
+
+    /// Layout to mix audio tracks with these channel counts to
+    pub fn mix_layout(&self, channels: &[u32]) -> MixLayout {
+        let preserve = self.preserve_surround && self.format == ExportFormat::Mp4;
+        MixLayout::for_tracks(channels, preserve)
+    }
+
+    /// Memory budget for frames queued for the encoder, in bytes
+    pub fn frame_memory_bytes(&self) -> usize {
+        self.frame_memory_mb.unwrap_or(DEFAULT_FRAME_MEMORY_MB) as usize * 1024 * 1024
+    }
+
+    /// Output dimensions for a source size.
+    ///
+    /// Explicit width/height win; otherwise the quality's resolution scale is
+    /// applied (rounded down to even values, as required by yuv420p).
+    pub fn output_dimensions(&self, source_width: u32, source_height: u32) -> (u32, u32) {
+        let scale = self.quality.resolution_scale();
+        let scaled = |v: u32| (((v as f64 * scale) as u32) & !1).max(2);
+
+        (
+            self.width.unwrap_or_else(|| if scale < 1.0 { scaled(source_width) } else { source_width }),
+            self.height.unwrap_or_else(|| if scale < 1.0 { scaled(source_height) } else { source_height }),
+        )
+    }
 }
 
 /// Export progress stages
@@ -171,12 +525,21 @@ pub struct ExportOptions {
 pub enum ExportStage {
     /// Preparing to export (loading files, etc.)
     Preparing,
+    /// Joining the sessions of a paused recording into one timeline
+    JoiningSessions,
+    /// Removing speaker echo from the microphone before encoding
+    ProcessingAudio,
     /// Smoothing cursor data
     SmoothingCursor,
     /// Encoding video frames
     Encoding,
     /// Finalizing output file
     Finalizing,
+    /// Re-encoding a GIF with a smaller palette and size to fit its size
+    /// budget; `attempt` counts from 1
+    GeneratingPalette { attempt: u32 },
+    /// Uploading the export to the share provider
+    Uploading,
     /// Export completed successfully
     Complete,
     /// Export failed with error
@@ -207,6 +570,26 @@ impl ExportProgress {
         }
     }
 
+    /// Joining sessions, `fraction` of the way through the current track.
+    /// `current_frame` is the percentage done, out of 100.
+    pub fn joining_sessions(fraction: f64) -> Self {
+        Self {
+            percent: 2.0 * fraction as f32,
+            stage: ExportStage::JoiningSessions,
+            current_frame: (fraction * 100.0).round() as u64,
+            total_frames: 100,
+        }
+    }
+
+    pub fn processing_audio() -> Self {
+        Self {
+            percent: 2.0,
+            stage: ExportStage::ProcessingAudio,
+            current_frame: 0,
+            total_frames: 0,
+        }
+    }
+
     pub fn smoothing_cursor(percent: f32) -> Self {
         Self {
             percent,
@@ -239,6 +622,26 @@ impl ExportProgress {
         }
     }
 
+    /// GIF size-budget pass `attempt` of at most `max_attempts`
+    pub fn generating_palette(attempt: u32, max_attempts: u32) -> Self {
+        Self {
+            percent: 95.0 + 4.0 * attempt.min(max_attempts) as f32 / max_attempts.max(1) as f32,
+            stage: ExportStage::GeneratingPalette { attempt },
+            current_frame: 0,
+            total_frames: 0,
+        }
+    }
+
+    /// The upload's progress isn't known, only that it's running
+    pub fn uploading() -> Self {
+        Self {
+            percent: 100.0,
+            stage: ExportStage::Uploading,
+            current_frame: 0,
+            total_frames: 0,
+        }
+    }
+
     pub fn complete() -> Self {
         Self {
             percent: 100.0,
@@ -267,6 +670,9 @@ pub enum ExportError {
     #[error("FFmpeg error: {0}")]
     Ffmpeg(String),
 
+    #[error("FFmpeg not found. Please install FFmpeg and make sure it is in your PATH")]
+    FfmpegMissing,
+
     #[error("Recording bundle not found: {0}")]
     BundleNotFound(String),
 
@@ -288,3 +694,23 @@ impl From<ExportError> for String {
         e.to_string()
     }
 }
+
+impl From<EncryptionError> for ExportError {
+    fn from(e: EncryptionError) -> Self {
+        match e {
+            EncryptionError::Io(e) => ExportError::Io(e),
+            other => ExportError::Decoding(other.to_string()),
+        }
+    }
+}
+
+impl ExportError {
+    /// Error for a failed FFmpeg spawn, distinguishing a missing binary
+    pub fn ffmpeg_spawn(context: &str, error: std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            ExportError::FfmpegMissing
+        } else {
+            ExportError::Ffmpeg(format!("{}: {}", context, error))
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Export verification
+//!
+//! Once an export has finished, the output file is checksummed and checked
+//! against what the export was asked to produce: its duration, the number of
+//! video and audio streams and, for MP4, that the `moov` index was written.
+//! The result is sent with `export-complete` so automated pipelines can tell
+//! a usable file from a truncated one. A failed check doesn't fail the
+//! export; it is listed in `problems`.
+
+use super::types::{ExportError, ExportFormat, ExportOptions};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::process::Command;
+
+/// Durations may differ from the expected one by this much...
+const DURATION_TOLERANCE_MS: f64 = 500.0;
+
+/// ...or by this fraction of it, whichever is larger
+const DURATION_TOLERANCE_RATIO: f64 = 0.02;
+
+/// What an export should have produced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpectedOutput {
+    pub duration_ms: f64,
+    /// Whether an audio stream should be present
+    pub audio: bool,
+}
+
+impl ExpectedOutput {
+    /// Expectation for an export of `duration_ms`, which has audio when an
+    /// included audio track exists (and the format can carry it)
+    pub fn new(
+        options: &ExportOptions,
+        duration_ms: f64,
+        mic_audio: Option<&Path>,
+        system_audio: Option<&Path>,
+    ) -> Self {
+        let included =
+            |path: Option<&Path>, include: bool| include && path.is_some_and(Path::exists);
+        Self {
+            duration_ms,
+            audio: options.format != ExportFormat::Gif
+                && (included(mic_audio, options.include_mic_audio)
+                    || included(system_audio, options.include_system_audio)),
+        }
+    }
+}
+
+/// Checksum and checks of a finished export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportVerification {
+    pub path: String,
+    /// Hex-encoded SHA-256 of the file
+    pub sha256: String,
+    pub size_bytes: u64,
+    /// Duration reported by the container, if it could be read
+    pub duration_ms: Option<f64>,
+    pub expected_duration_ms: f64,
+    pub video_streams: usize,
+    pub audio_streams: usize,
+    /// Whether the MP4 index was found (None for other formats)
+    pub has_moov: Option<bool>,
+    /// Checks that failed; empty if the export verified
+    pub problems: Vec<String>,
+    pub passed: bool,
+}
+
+/// Checksum and check the output of an export
+pub fn verify_export(
+    options: &ExportOptions,
+    expected: &ExpectedOutput,
+) -> Result<ExportVerification, ExportError> {
+    let path = Path::new(&options.output_path);
+    let size_bytes = std::fs::metadata(path)?.len();
+    let sha256 = sha256_file(path)?;
+    let has_moov = match options.format {
+        ExportFormat::Mp4 => Some(has_top_level_box(
+            &mut BufReader::new(File::open(path)?),
+            b"moov",
+        )?),
+        _ => None,
+    };
+
+    let (duration_ms, video_streams, audio_streams) = match probe(path) {
+        Ok(probed) => probed,
+        Err(e) => {
+            tracing::warn!("Failed to probe export {:?}: {}", path, e);
+            (None, 0, 0)
+        }
+    };
+
+    let mut verification = ExportVerification {
+        path: options.output_path.clone(),
+        sha256,
+        size_bytes,
+        duration_ms,
+        expected_duration_ms: expected.duration_ms,
+        video_streams,
+        audio_streams,
+        has_moov,
+        problems: Vec::new(),
+        passed: false,
+    };
+    verification.problems = problems(&verification, expected);
+    verification.passed = verification.problems.is_empty();
+    Ok(verification)
+}
+
+/// Checks `verification` fails against `expected`, as messages for the user
+fn problems(verification: &ExportVerification, expected: &ExpectedOutput) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    match verification.duration_ms {
+        Some(duration_ms) => {
+            let tolerance =
+                DURATION_TOLERANCE_MS.max(expected.duration_ms * DURATION_TOLERANCE_RATIO);
+            if (duration_ms - expected.duration_ms).abs() > tolerance {
+                problems.push(format!(
+                    "Duration is {:.0}ms, expected {:.0}ms",
+                    duration_ms, expected.duration_ms
+                ));
+            }
+        }
+        None => problems.push("Duration could not be read".to_string()),
+    }
+
+    if verification.video_streams != 1 {
+        problems.push(format!(
+            "Expected 1 video stream, found {}",
+            verification.video_streams
+        ));
+    }
+    let expected_audio = usize::from(expected.audio);
+    if verification.audio_streams != expected_audio {
+        problems.push(format!(
+            "Expected {} audio stream(s), found {}",
+            expected_audio, verification.audio_streams
+        ));
+    }
+
+    if verification.has_moov == Some(false) {
+        problems.push("MP4 index (moov atom) is missing".to_string());
+    }
+    problems
+}
+
+/// Hex-encoded SHA-256 of a file
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Whether an ISO-BMFF (MP4/MOV) file has a top-level box of type `name`
+pub(crate) fn has_top_level_box<R: Read + Seek>(reader: &mut R, name: &[u8; 4]) -> io::Result<bool> {
+    let mut header = [0u8; 8];
+    loop {
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        }
+        if &header[4..] == name {
+            return Ok(true);
+        }
+
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let body = match size {
+            // Box runs to the end of the file
+            0 => return Ok(false),
+            // 64-bit size follows the type
+            1 => {
+                let mut large = [0u8; 8];
+                reader.read_exact(&mut large)?;
+                u64::from_be_bytes(large).checked_sub(16)
+            }
+            _ => size.checked_sub(8),
+        };
+        let Some(body) = body else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid MP4 box size",
+            ));
+        };
+        reader.seek(SeekFrom::Current(body as i64))?;
+    }
+}
+
+/// Container duration and the number of video and audio streams
+fn probe(path: &Path) -> Result<(Option<f64>, usize, usize), ExportError> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration:stream=codec_type",
+            "-of",
+            "default=noprint_wrappers=1",
+            path.to_str().unwrap_or(""),
+        ])
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run ffprobe", e))?;
+    if !output.status.success() {
+        return Err(ExportError::Ffmpeg(format!(
+            "ffprobe failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(parse_probe(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_probe(output: &str) -> (Option<f64>, usize, usize) {
+    let mut duration_ms = None;
+    let (mut video, mut audio) = (0, 0);
+    for line in output.lines() {
+        match line.trim().split_once('=') {
+            Some(("codec_type", "video")) => video += 1,
+            Some(("codec_type", "audio")) => audio += 1,
+            Some(("duration", secs)) => {
+                duration_ms = secs.parse::<f64>().ok().map(|secs| secs * 1000.0);
+            }
+            _ => {}
+        }
+    }
+    (duration_ms, video, audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mp4_box(kind: &[u8; 4], body_len: usize) -> Vec<u8> {
+        let mut data = ((body_len + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.resize(body_len + 8, 0);
+        data
+    }
+
+    #[test]
+    fn test_verification_checks() {
+        let mut file = [mp4_box(b"ftyp", 16), mp4_box(b"mdat", 1024)].concat();
+        assert!(!has_top_level_box(&mut Cursor::new(&file), b"moov").unwrap());
+        file.extend(mp4_box(b"moov", 64));
+        assert!(has_top_level_box(&mut Cursor::new(&file), b"moov").unwrap());
+
+        let (duration_ms, video, audio) =
+            parse_probe("codec_type=video\ncodec_type=audio\nduration=12.500000\n");
+        assert_eq!((duration_ms, video, audio), (Some(12500.0), 1, 1));
+
+        let expected = ExpectedOutput {
+            duration_ms: 12000.0,
+            audio: true,
+        };
+        let mut verification = ExportVerification {
+            path: "export.mp4".to_string(),
+            sha256: String::new(),
+            size_bytes: 0,
+            duration_ms,
+            expected_duration_ms: expected.duration_ms,
+            video_streams: video,
+            audio_streams: audio,
+            has_moov: Some(true),
+            problems: Vec::new(),
+            passed: false,
+        };
+        assert!(problems(&verification, &expected).is_empty());
+
+        verification.duration_ms = Some(6000.0);
+        verification.audio_streams = 0;
+        verification.has_moov = Some(false);
+        assert_eq!(problems(&verification, &expected).len(), 3);
+    }
+}
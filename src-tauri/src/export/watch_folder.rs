@@ -0,0 +1,306 @@
+//! Watch-folder exports
+//!
+//! A folder can be watched for `.osp` bundles dropped into it (e.g. copied
+//! over from another machine), and each one is exported with a chosen
+//! preset as soon as it has finished copying. Together with a shared folder
+//! this makes a simple render farm: one machine records, another exports.
+//!
+//! There is no file-system notification API in use here; the folder is
+//! scanned periodically and a bundle counts as copied once its files stopped
+//! changing between two scans. Bundles whose export is newer than all their
+//! files are left alone, so restarting the app doesn't export everything
+//! again, while a bundle copied over again is exported again.
+
+use crate::export::types::{ExportError, ExportOptions};
+use crate::project::bundle::BUNDLE_EXTENSION;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Persisted watch-folder settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFolderSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Folder to watch for bundles
+    #[serde(default)]
+    pub folder: Option<PathBuf>,
+    /// Where exports are written (None = next to the bundles)
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Options every bundle is exported with. `output_path` is ignored; each
+    /// export is named after its bundle.
+    #[serde(default)]
+    pub preset: Option<ExportOptions>,
+}
+
+impl WatchFolderSettings {
+    /// Folder and preset to export with, if watching is enabled and set up
+    pub fn active(&self) -> Option<(&Path, &ExportOptions)> {
+        if !self.enabled {
+            return None;
+        }
+        Some((self.folder.as_deref()?, self.preset.as_ref()?))
+    }
+
+    /// Check the settings can be used before they're saved
+    pub fn validate(&self) -> Result<(), ExportError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let folder = self
+            .folder
+            .as_deref()
+            .ok_or_else(|| ExportError::InvalidConfig("No watch folder chosen".to_string()))?;
+        if !folder.is_dir() {
+            return Err(ExportError::InvalidConfig(format!(
+                "Watch folder not found: {:?}",
+                folder
+            )));
+        }
+        self.preset
+            .as_ref()
+            .ok_or_else(|| ExportError::InvalidConfig("No export preset chosen".to_string()))?
+            .validate()
+    }
+
+    /// Options to export `bundle` with
+    pub fn export_options(&self, bundle: &Path, preset: &ExportOptions) -> ExportOptions {
+        let stem = bundle
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Recording".to_string());
+        let dir = self
+            .output_dir
+            .as_deref()
+            .or(bundle.parent())
+            .unwrap_or(bundle);
+        let mut options = preset.clone();
+        options.output_path = dir
+            .join(format!("{}.{}", stem, preset.format.extension()))
+            .to_string_lossy()
+            .to_string();
+        options
+    }
+}
+
+/// Path of the watch-folder settings file
+pub fn settings_path() -> Result<PathBuf, ExportError> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        ExportError::InvalidConfig("Could not determine data directory".to_string())
+    })?;
+    Ok(data_dir.join("Open ScreenStudio").join("watch-folder.json"))
+}
+
+/// Load watch-folder settings, returning defaults (not watching) if none
+/// were saved
+pub fn load_settings(path: &Path) -> Result<WatchFolderSettings, ExportError> {
+    if !path.exists() {
+        return Ok(WatchFolderSettings::default());
+    }
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| ExportError::InvalidConfig(format!("Invalid watch folder settings: {}", e)))
+}
+
+/// Save watch-folder settings
+pub fn save_settings(path: &Path, settings: &WatchFolderSettings) -> Result<(), ExportError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| ExportError::InvalidConfig(e.to_string()))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Contents of a bundle at one scan, to tell when it stops changing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundleSnapshot {
+    pub files: u64,
+    pub bytes: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Bundles directly inside `folder` and what they contain
+pub fn scan_folder(folder: &Path) -> std::io::Result<Vec<(PathBuf, BundleSnapshot)>> {
+    let mut bundles = Vec::new();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        let is_bundle = path.extension().is_some_and(|ext| ext == BUNDLE_EXTENSION);
+        if is_bundle && path.is_dir() {
+            let mut snapshot = BundleSnapshot {
+                files: 0,
+                bytes: 0,
+                modified: None,
+            };
+            add_to_snapshot(&path, &mut snapshot)?;
+            bundles.push((path, snapshot));
+        }
+    }
+    bundles.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(bundles)
+}
+
+fn add_to_snapshot(dir: &Path, snapshot: &mut BundleSnapshot) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            add_to_snapshot(&entry.path(), snapshot)?;
+        } else {
+            snapshot.files += 1;
+            snapshot.bytes += metadata.len();
+            snapshot.modified = snapshot.modified.max(metadata.modified().ok());
+        }
+    }
+    Ok(())
+}
+
+/// Picks the bundles that finished copying and haven't been exported yet
+#[derive(Debug, Default)]
+pub struct BundleTracker {
+    /// Bundles as of the last scan
+    seen: HashMap<PathBuf, BundleSnapshot>,
+    /// Bundles already exported (or failed), as they were then. One that
+    /// changes afterwards, e.g. copied over again, is exported again.
+    handled: HashMap<PathBuf, BundleSnapshot>,
+}
+
+impl BundleTracker {
+    /// Bundles of `scan` that are unchanged since the previous scan and not
+    /// handled yet, in scan order
+    pub fn ready(&mut self, scan: Vec<(PathBuf, BundleSnapshot)>) -> Vec<PathBuf> {
+        let mut ready = Vec::new();
+        let mut seen = HashMap::new();
+        for (path, snapshot) in scan {
+            let settled = snapshot.files > 0 && self.seen.get(&path) == Some(&snapshot);
+            if settled && self.handled.get(&path) != Some(&snapshot) {
+                ready.push(path.clone());
+            }
+            seen.insert(path, snapshot);
+        }
+        self.seen = seen;
+        ready
+    }
+
+    /// Whether `output` was written after `bundle` last changed, so exporting
+    /// it again would give the same file
+    pub fn export_is_current(&self, bundle: &Path, output: &Path) -> bool {
+        let Some(changed) = self.seen.get(bundle).and_then(|snapshot| snapshot.modified) else {
+            return false;
+        };
+        std::fs::metadata(output)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|written| written >= changed)
+    }
+
+    /// Don't return `bundle` again unless it changes
+    pub fn mark_handled(&mut self, bundle: &Path) {
+        if let Some(snapshot) = self.seen.get(bundle) {
+            self.handled.insert(bundle.to_path_buf(), *snapshot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundles_export_once_copied() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("Demo.osp");
+        std::fs::create_dir_all(bundle.join("recording")).unwrap();
+        std::fs::write(bundle.join("project.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a bundle").unwrap();
+
+        let mut tracker = BundleTracker::default();
+        // Seen for the first time: might still be copying
+        assert!(tracker.ready(scan_folder(dir.path()).unwrap()).is_empty());
+        // Still copying
+        std::fs::write(bundle.join("recording").join("screen.mp4"), [0u8; 64]).unwrap();
+        assert!(tracker.ready(scan_folder(dir.path()).unwrap()).is_empty());
+        // Unchanged since the last scan
+        assert_eq!(
+            tracker.ready(scan_folder(dir.path()).unwrap()),
+            vec![bundle.clone()]
+        );
+        assert_eq!(
+            tracker.ready(scan_folder(dir.path()).unwrap()),
+            vec![bundle.clone()]
+        );
+
+        tracker.mark_handled(&bundle);
+        assert!(tracker.ready(scan_folder(dir.path()).unwrap()).is_empty());
+        // Copied over again
+        std::fs::write(bundle.join("recording").join("mic.m4a"), [0u8; 16]).unwrap();
+        assert!(tracker.ready(scan_folder(dir.path()).unwrap()).is_empty());
+        assert_eq!(
+            tracker.ready(scan_folder(dir.path()).unwrap()),
+            vec![bundle.clone()]
+        );
+    }
+
+    #[test]
+    fn test_bundles_copied_over_again_are_exported_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("Demo.osp");
+        std::fs::create_dir_all(&bundle).unwrap();
+        let project = bundle.join("project.json");
+        std::fs::write(&project, "{}").unwrap();
+        let output = dir.path().join("Demo.mp4");
+
+        let mut tracker = BundleTracker::default();
+        tracker.ready(scan_folder(dir.path()).unwrap());
+        assert!(!tracker.export_is_current(&bundle, &output));
+
+        // Exported after the bundle was copied, e.g. before a restart
+        let copied = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        let file = std::fs::File::options().write(true).open(&project).unwrap();
+        file.set_modified(copied).unwrap();
+        std::fs::write(&output, "video").unwrap();
+        tracker.ready(scan_folder(dir.path()).unwrap());
+        assert!(tracker.export_is_current(&bundle, &output));
+
+        // Copied over again since
+        let recopied = SystemTime::now() + std::time::Duration::from_secs(60);
+        file.set_modified(recopied).unwrap();
+        tracker.ready(scan_folder(dir.path()).unwrap());
+        assert!(!tracker.export_is_current(&bundle, &output));
+    }
+
+    #[test]
+    fn test_settings_name_exports_after_bundles() {
+        let preset: ExportOptions = serde_json::from_str(
+            r#"{"format": "mp4", "quality": "high", "width": null, "height": null,
+                "fps": null, "outputPath": "", "includeCursor": true,
+                "includeWebcam": true, "includeMicAudio": true,
+                "includeSystemAudio": true, "screenEdits": null, "cameraEdits": null}"#,
+        )
+        .unwrap();
+        let mut settings = WatchFolderSettings {
+            enabled: true,
+            folder: Some(PathBuf::from("/farm/in")),
+            output_dir: None,
+            preset: Some(preset.clone()),
+        };
+        let bundle = Path::new("/farm/in/Demo.osp");
+        assert_eq!(
+            settings.export_options(bundle, &preset).output_path,
+            "/farm/in/Demo.mp4"
+        );
+        settings.output_dir = Some(PathBuf::from("/farm/out"));
+        assert_eq!(
+            settings.export_options(bundle, &preset).output_path,
+            "/farm/out/Demo.mp4"
+        );
+
+        assert!(settings.active().is_some());
+        settings.enabled = false;
+        assert!(settings.active().is_none());
+        assert!(settings.validate().is_ok());
+    }
+}
@@ -0,0 +1,319 @@
+//! Standalone webcam export
+//!
+//! Alongside the main export, the webcam recording can be written to a file
+//! of its own, with the same cuts, speed changes and audio, for reusing the
+//! talking-head footage elsewhere. It is written next to the main output as
+//! `<name>-webcam.<ext>` once the main export has finished. Failures are
+//! logged rather than returned, since the main export already succeeded.
+
+use super::downmix::{self, probe_channels};
+use super::ffmpeg::{
+    build_audio_filter, build_video_filter, lut3d_filter, mic_offset_args, track_mix_filter,
+};
+use super::types::{ExportError, ExportFormat, ExportOptions, ExportSegment};
+use crate::project::schema::AudioTrack;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Where the webcam track of an export is written
+pub fn webcam_track_path(output_path: &Path) -> PathBuf {
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "export".to_string());
+    let extension = output_path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mp4".to_string());
+    output_path.with_file_name(format!("{}-webcam.{}", stem, extension))
+}
+
+/// FFmpeg arguments writing `webcam` with the export's edits and audio to
+/// `output`. `segments` of None keeps the full recording.
+pub fn webcam_track_args(
+    webcam: &Path,
+    mic_audio: Option<&Path>,
+    system_audio: Option<&Path>,
+    options: &ExportOptions,
+    segments: Option<&[ExportSegment]>,
+    output: &Path,
+) -> Vec<String> {
+    let mut args = vec![
+        "-y".to_string(),
+        "-i".to_string(),
+        webcam.to_string_lossy().to_string(),
+    ];
+
+    let mut audio_inputs = Vec::new();
+    if let Some(mic) = mic_audio.filter(|_| options.include_mic_audio) {
+        let channels = probe_channels(mic);
+        args.extend(mic_offset_args(options));
+        args.extend(downmix::input_args(channels));
+        args.extend(["-i".to_string(), mic.to_string_lossy().to_string()]);
+        audio_inputs.push((audio_inputs.len() + 1, "mic", channels, AudioTrack::Microphone));
+    }
+    if let Some(system) = system_audio.filter(|_| options.include_system_audio) {
+        let channels = probe_channels(system);
+        args.extend(downmix::input_args(channels));
+        args.extend(["-i".to_string(), system.to_string_lossy().to_string()]);
+        audio_inputs.push((audio_inputs.len() + 1, "sys", channels, AudioTrack::SystemAudio));
+    }
+    let channels: Vec<u32> = audio_inputs.iter().map(|&(_, _, channels, _)| channels).collect();
+    let layout = options.mix_layout(&channels);
+
+    let mut filters = Vec::new();
+    let video_label = match segments {
+        Some(segments) => {
+            let (filter, label) = build_video_filter(segments, 0);
+            filters.push(filter);
+            label
+        }
+        None => "0:v".to_string(),
+    };
+    let lut = options
+        .camera_lut
+        .as_deref()
+        .map(|lut| format!("{},", lut3d_filter(lut)))
+        .unwrap_or_default();
+    // yuv420p needs even dimensions
+    filters.push(format!(
+        "[{}]{}scale=trunc(iw/2)*2:trunc(ih/2)*2[vout]",
+        video_label, lut
+    ));
+
+    let mut audio_labels = Vec::new();
+    for &(index, prefix, channels, track) in &audio_inputs {
+        let label = match segments {
+            Some(segments) => {
+                let (filter, label) = build_audio_filter(segments, index, prefix);
+                filters.push(filter);
+                label
+            }
+            None => {
+                filters.push(format!("[{}:a]anull[{}]", index, prefix));
+                prefix.to_string()
+            }
+        };
+        match track_mix_filter(layout.convert_filter(channels), options.volume(track)) {
+            Some(convert) => {
+                filters.push(format!("[{}]{}[{}fmt]", label, convert, prefix));
+                audio_labels.push(format!("[{}fmt]", prefix));
+            }
+            None => audio_labels.push(format!("[{}]", label)),
+        }
+    }
+    let audio_label = match audio_labels.len() {
+        0 => None,
+        1 => Some(audio_labels.remove(0)),
+        n => {
+            filters.push(format!(
+                "{}amix=inputs={}:duration=longest[aout]",
+                audio_labels.join(""),
+                n
+            ));
+            Some("[aout]".to_string())
+        }
+    };
+    args.extend(["-filter_complex".to_string(), filters.join(";")]);
+    args.extend(["-map".to_string(), "[vout]".to_string()]);
+    if let Some(label) = &audio_label {
+        args.extend(["-map".to_string(), label.clone()]);
+    }
+
+    let crf = options.quality.crf().to_string();
+    match options.format {
+        ExportFormat::Webm => {
+            args.extend(
+                ["-c:v", "libvpx-vp9", "-crf", crf.as_str(), "-b:v", "0"].map(String::from),
+            );
+            args.extend(
+                options
+                    .quality
+                    .vp9_speed_args()
+                    .iter()
+                    .map(|a| a.to_string()),
+            );
+            if audio_label.is_some() {
+                args.extend(options.audio_codec_args(layout));
+            }
+        }
+        _ => {
+            let preset = options.quality.h264_preset();
+            args.extend(
+                [
+                    "-c:v",
+                    "libx264",
+                    "-preset",
+                    preset,
+                    "-crf",
+                    crf.as_str(),
+                    "-pix_fmt",
+                    "yuv420p",
+                    "-movflags",
+                    "+faststart",
+                ]
+                .map(String::from),
+            );
+            if audio_label.is_some() {
+                args.extend(options.audio_codec_args(layout));
+            }
+        }
+    }
+
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+/// Write the webcam track next to the main export when the options ask for
+/// it, returning its path
+pub fn export_webcam_track(
+    webcam: Option<&Path>,
+    mic_audio: Option<&Path>,
+    system_audio: Option<&Path>,
+    options: &ExportOptions,
+    segments: Option<&[ExportSegment]>,
+) -> Option<PathBuf> {
+    if !options.export_webcam_track {
+        return None;
+    }
+    let Some(webcam) = webcam else {
+        tracing::warn!("Webcam track requested but the recording has no webcam video");
+        return None;
+    };
+    if options.format == ExportFormat::Gif {
+        tracing::info!("Skipping webcam track for GIF export");
+        return None;
+    }
+
+    let output = webcam_track_path(Path::new(&options.output_path));
+    // Explicit camera edits win over the screen's
+    let segments = options
+        .camera_edits
+        .as_ref()
+        .map(|edits| edits.segments.as_slice())
+        .or(segments);
+    let args = webcam_track_args(webcam, mic_audio, system_audio, options, segments, &output);
+    tracing::info!("Exporting webcam track: {:?}", args);
+
+    let result = Command::new("ffmpeg")
+        .args(&args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))
+        .and_then(|output| {
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(ExportError::Ffmpeg(
+                    String::from_utf8_lossy(&output.stderr).to_string(),
+                ))
+            }
+        });
+    match result {
+        Ok(()) => {
+            tracing::info!("Webcam track written to {:?}", output);
+            Some(output)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to export webcam track: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::types::AudioCodec;
+
+    #[test]
+    fn test_webcam_track_applies_edits_and_mixes_audio() {
+        assert_eq!(
+            webcam_track_path(Path::new("/exports/Demo.mp4")),
+            PathBuf::from("/exports/Demo-webcam.mp4")
+        );
+
+        let mut options: ExportOptions = serde_json::from_value(serde_json::json!({
+            "format": "mp4",
+            "quality": "high",
+            "width": null,
+            "height": null,
+            "fps": null,
+            "outputPath": "/exports/Demo.mp4",
+            "includeCursor": false,
+            "includeWebcam": true,
+            "includeMicAudio": true,
+            "includeSystemAudio": true,
+            "screenEdits": null,
+            "cameraEdits": null
+        }))
+        .unwrap();
+        let segments = [
+            ExportSegment {
+                source_start_ms: 0,
+                source_end_ms: 1000,
+                time_scale: 1.0,
+            },
+            ExportSegment {
+                source_start_ms: 3000,
+                source_end_ms: 4000,
+                time_scale: 2.0,
+            },
+        ];
+        let args = webcam_track_args(
+            Path::new("webcam.mp4"),
+            Some(Path::new("mic.m4a")),
+            Some(Path::new("system.m4a")),
+            &options,
+            Some(&segments),
+            Path::new("/exports/Demo-webcam.mp4"),
+        );
+        let filter = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert!(filter.contains("concat=n=2:v=1:a=0[vconcat]"));
+        assert!(filter.contains("[vconcat]scale=trunc(iw/2)*2:trunc(ih/2)*2[vout]"));
+        assert!(filter.contains("[micconcat][sysconcat]amix=inputs=2"));
+        assert!(args.windows(2).any(|w| w == ["-map", "[aout]"]));
+
+        // Unedited, with only the microphone
+        options.include_system_audio = false;
+        let args = webcam_track_args(
+            Path::new("webcam.mp4"),
+            Some(Path::new("mic.m4a")),
+            None,
+            &options,
+            None,
+            Path::new("/exports/Demo-webcam.mp4"),
+        );
+        let filter = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert_eq!(
+            filter,
+            "[0:v]scale=trunc(iw/2)*2:trunc(ih/2)*2[vout];[1:a]anull[mic]"
+        );
+        assert!(args.windows(2).any(|w| w == ["-map", "[mic]"]));
+        assert!(args.windows(4).any(|w| w == ["-c:a", "aac", "-b:a", "192k"]));
+
+        // Audio codec and sample rate picked for the export
+        options.audio_codec = Some(AudioCodec::Flac);
+        options.audio_sample_rate = Some(96000);
+        assert!(options.validate().is_ok());
+        let args = webcam_track_args(
+            Path::new("webcam.mp4"),
+            Some(Path::new("mic.m4a")),
+            None,
+            &options,
+            None,
+            Path::new("/exports/Demo-webcam.mp4"),
+        );
+        let audio = &args[args.iter().position(|a| a == "-c:a").unwrap()..];
+        assert_eq!(audio[..6], ["-c:a", "flac", "-ar", "96000", "-strict", "experimental"]);
+
+        // WebM only holds Opus, which only takes some sample rates
+        options.format = ExportFormat::Webm;
+        assert!(options.validate().is_err());
+        options.audio_codec = None;
+        assert_eq!(options.audio_codec(), AudioCodec::Opus);
+        assert!(options.validate().is_err());
+        options.audio_sample_rate = Some(48000);
+        assert!(options.validate().is_ok());
+    }
+}
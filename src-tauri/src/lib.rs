@@ -3,17 +3,23 @@
 //! This is the main library crate for the Open ScreenStudio application.
 //! It provides the Tauri application setup and all backend functionality.
 
+pub mod automation;
 pub mod capture;
 pub mod commands;
+#[cfg(feature = "e2e")]
+pub mod e2e;
 pub mod export;
 pub mod processing;
 pub mod project;
 pub mod recorder;
+pub mod shutdown;
 pub mod utils;
+pub mod waveform;
 
 use commands::export::ExportState;
 use commands::project::AppState;
 use commands::recording::RecorderState;
+use waveform::ScrubState;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 /// Initialize the application
@@ -30,17 +36,28 @@ pub fn run() {
 
     tracing::info!("Starting Open ScreenStudio v{}", env!("CARGO_PKG_VERSION"));
 
+    // Track FFmpeg children so none outlive the app
+    utils::process::install_supervisor();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(commands::recording::on_global_shortcut)
+                .build(),
+        )
         .manage(RecorderState::default())
         .manage(ExportState::default())
         .manage(AppState::default())
+        .manage(ScrubState::default())
         .invoke_handler(tauri::generate_handler![
             // Project commands
             commands::project::create_project,
             commands::project::open_project,
+            commands::project::open_project_readonly,
+            commands::project::is_project_read_only,
             commands::project::save_project,
             commands::project::get_project,
             commands::project::get_project_path,
@@ -49,29 +66,75 @@ pub fn run() {
             commands::project::save_project_to_path,
             commands::project::auto_save_project,
             commands::project::update_project,
+            commands::project::replace_project_media,
+            commands::project::attach_color_lut,
+            commands::project::list_project_snapshots,
+            commands::project::restore_snapshot,
+            commands::project::save_project_template,
+            commands::project::list_project_templates,
+            commands::project::delete_project_template,
+            commands::project::apply_project_template,
+            commands::project::list_cursor_packs,
+            commands::project::install_cursor_pack,
+            commands::project::get_markers,
+            commands::project::save_markers,
+            commands::project::get_ui_state,
+            commands::project::save_ui_state,
+            commands::project::get_naming_settings,
+            commands::project::set_naming_settings,
             // System commands
             commands::system::get_system_info,
+            commands::system::list_active_subprocesses,
             // Recording commands
             commands::recording::get_displays,
+            commands::recording::get_windows,
             commands::recording::get_audio_devices,
+            commands::recording::get_system_audio_devices,
             commands::recording::get_cameras,
+            commands::recording::start_camera_preview,
+            commands::recording::stop_camera_preview,
             commands::recording::check_system_audio_available,
+            commands::recording::get_audio_capture_apps,
             commands::recording::check_screen_permission,
             commands::recording::request_screen_permission,
             commands::recording::check_camera_permission,
             commands::recording::request_camera_permission,
+            commands::recording::check_media_permission,
+            commands::recording::request_media_permission,
+            commands::recording::check_accessibility_permission,
+            commands::recording::request_accessibility_permission,
             commands::recording::start_recording,
+            commands::recording::start_recording_with_countdown,
+            commands::recording::cancel_recording_countdown,
+            commands::recording::check_recording_permissions,
+            commands::recording::start_region_recording,
+            commands::recording::run_capture_self_test,
+            commands::recording::run_av_calibration,
+            commands::recording::get_av_calibration,
             commands::recording::stop_recording,
             commands::recording::pause_recording,
             commands::recording::resume_recording,
             commands::recording::get_recording_state,
             commands::recording::get_recording_duration,
+            commands::recording::get_recording_preview,
+            commands::recording::list_recoverable_recordings,
+            commands::recording::recover_recording,
+            commands::recording::capture_screenshot,
+            commands::recording::get_display_thumbnail,
+            commands::recording::frame_screenshot,
+            commands::recording::add_annotation,
+            commands::recording::add_recording_marker,
             commands::recording::get_video_metadata,
             commands::recording::load_recording_bundle,
             // Processing commands
             commands::processing::smooth_cursor,
             commands::processing::process_cursor_smoothing,
             commands::processing::get_default_spring_config,
+            commands::processing::get_smoothing_presets,
+            commands::processing::preview_cursor_smoothing,
+            commands::processing::suggest_chapters,
+            commands::processing::analyze_recording_sync,
+            commands::processing::render_zoom_path,
             // Window commands
             commands::window::open_editor_window,
             commands::window::close_toolbar_window,
@@ -81,9 +144,28 @@ pub fn run() {
             commands::window::restore_toolbar,
             // Export commands
             commands::export::start_export,
+            commands::export::start_export_with_edits,
             commands::export::cancel_export,
             commands::export::is_exporting,
+            commands::export::archive_recording,
+            commands::export::share_export,
+            commands::export::get_share_settings,
+            commands::export::set_share_settings,
+            commands::export::get_watch_folder_settings,
+            commands::export::set_watch_folder_settings,
+            commands::export::get_youtube_chapters,
+            commands::export::render_playback_mixdown,
+            commands::export::run_export_benchmark,
+            commands::export::create_clip,
+            // Waveform commands
+            waveform::get_waveform,
+            waveform::get_scrub_chunk,
+            waveform::prefetch_scrub_chunks,
         ])
+        // Windows opened mid-recording start out visible to capture
+        .on_page_load(|webview, _payload| {
+            commands::window::exclude_new_window(&webview.window());
+        })
         .setup(|app| {
             // Set up transparent background for toolbar window on macOS
             #[cfg(target_os = "macos")]
@@ -106,8 +188,36 @@ pub fn run() {
                     }
                 }
             }
+
+            // Decrypted copies left by a crash, and media of encrypted
+            // recordings cut short, shouldn't stay readable
+            project::encryption::clear_decrypted_cache();
+            commands::recording::seal_interrupted_recordings();
+
+            commands::recording::forward_recording_events(app.handle().clone());
+            commands::export::watch_folder_exports(app.handle().clone());
+
+            if automation::is_enabled() {
+                if let Err(e) = automation::start(app.handle().clone()) {
+                    tracing::error!("Failed to start automation server: {}", e);
+                }
+            }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Finish recordings and exports before letting the app quit
+            tauri::RunEvent::ExitRequested { api, .. } => {
+                shutdown::on_exit_requested(app, &api);
+            }
+            tauri::RunEvent::Exit => {
+                utils::process::shutdown();
+                project::encryption::clear_decrypted_cache();
+                if automation::is_enabled() {
+                    automation::stop();
+                }
+            }
+            _ => {}
+        });
 }
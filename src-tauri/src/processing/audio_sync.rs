@@ -0,0 +1,282 @@
+//! Automatic alignment of externally recorded audio
+//!
+//! Finds where a separately recorded voiceover lines up with the scratch
+//! microphone track captured alongside the screen. Both takes contain the
+//! same speech, so their onsets (syllables, pauses) line up once the right
+//! offset is applied, even though the microphones and levels differ. The
+//! offset is found by cross-correlating onset envelopes, refined to below
+//! the envelope resolution by interpolating around the peak.
+
+use crate::export::ExportError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Rate audio is decoded at for analysis
+const ANALYSIS_RATE: u32 = 8000;
+
+/// Samples per envelope frame (10ms at the analysis rate)
+const FRAME_SAMPLES: usize = 80;
+const FRAME_MS: f64 = FRAME_SAMPLES as f64 * 1000.0 / ANALYSIS_RATE as f64;
+
+/// Fraction of the shorter take that must overlap for a lag to count, so a
+/// few coincident onsets at the edges can't win
+const MIN_OVERLAP_RATIO: f64 = 0.25;
+
+/// Correlation below which an alignment is not applied automatically
+pub const MIN_SYNC_CONFIDENCE: f64 = 0.3;
+
+/// Tuning for audio alignment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioSyncConfig {
+    /// Largest offset searched in either direction, in milliseconds
+    pub max_offset_ms: f64,
+    /// Audio analyzed from the start of each take, in milliseconds
+    pub analysis_ms: f64,
+}
+
+impl Default for AudioSyncConfig {
+    fn default() -> Self {
+        Self {
+            max_offset_ms: 30_000.0,
+            analysis_ms: 180_000.0,
+        }
+    }
+}
+
+/// Alignment of a take relative to a reference track
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioSync {
+    /// Delay to apply to the take in milliseconds (positive delays it), as
+    /// stored in `AudioConfig::microphone_offset_ms`
+    pub offset_ms: f64,
+    /// Correlation of the aligned onset envelopes (0.0-1.0)
+    pub confidence: f64,
+}
+
+impl AudioSync {
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= MIN_SYNC_CONFIDENCE
+    }
+}
+
+/// Onset strength per frame: the rise in log loudness from the previous
+/// frame. Insensitive to the overall level and tone of each microphone.
+pub fn onset_envelope(samples: &[f32]) -> Vec<f32> {
+    let loudness: Vec<f32> = samples
+        .chunks(FRAME_SAMPLES)
+        .map(|frame| {
+            let energy = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+            (energy + 1e-8).ln()
+        })
+        .collect();
+
+    std::iter::once(0.0)
+        .chain(loudness.windows(2).map(|w| (w[1] - w[0]).max(0.0)))
+        .collect()
+}
+
+/// Pearson correlation of `reference[i]` with `take[i - lag]` over their
+/// overlap, or None if they overlap too little
+fn correlation_at(reference: &[f32], take: &[f32], lag: isize, min_overlap: usize) -> Option<f64> {
+    let start = lag.max(0) as usize;
+    let end = (take.len() as isize + lag).min(reference.len() as isize);
+    if end - (start as isize) < min_overlap as isize {
+        return None;
+    }
+    let end = end as usize;
+
+    let n = (end - start) as f64;
+    let (mut sx, mut sy, mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for i in start..end {
+        let x = reference[i] as f64;
+        let y = take[(i as isize - lag) as usize] as f64;
+        sx += x;
+        sy += y;
+        sxy += x * y;
+        sxx += x * x;
+        syy += y * y;
+    }
+
+    let covariance = sxy - sx * sy / n;
+    let variance = (sxx - sx * sx / n) * (syy - sy * sy / n);
+    (variance > 0.0).then(|| covariance / variance.sqrt())
+}
+
+/// Find the lag (in envelope frames, possibly fractional) at which `take`
+/// best matches `reference`, searching up to `max_lag` frames either way
+pub fn find_lag(reference: &[f32], take: &[f32], max_lag: usize) -> Option<(f64, f64)> {
+    let min_overlap =
+        ((reference.len().min(take.len()) as f64 * MIN_OVERLAP_RATIO) as usize).max(1);
+    let max_lag = max_lag as isize;
+
+    let scores: Vec<(isize, f64)> = (-max_lag..=max_lag)
+        .filter_map(|lag| correlation_at(reference, take, lag, min_overlap).map(|c| (lag, c)))
+        .collect();
+    let best = scores
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+        .map(|(i, _)| i)?;
+    let (lag, peak) = scores[best];
+
+    // Parabolic interpolation between the neighbouring lags
+    let neighbour = |i: Option<usize>, expected: isize| {
+        i.and_then(|i| scores.get(i))
+            .filter(|(l, _)| *l == expected)
+            .map(|(_, c)| *c)
+    };
+    let refinement = match (
+        neighbour(best.checked_sub(1), lag - 1),
+        neighbour(Some(best + 1), lag + 1),
+    ) {
+        (Some(before), Some(after)) => {
+            let curvature = before - 2.0 * peak + after;
+            if curvature < 0.0 {
+                (0.5 * (before - after) / curvature).clamp(-0.5, 0.5)
+            } else {
+                0.0
+            }
+        }
+        _ => 0.0,
+    };
+
+    Some((lag as f64 + refinement, peak.max(0.0)))
+}
+
+/// Align `take` (e.g. an imported voiceover) with `reference` (the scratch
+/// microphone track)
+pub fn sync_audio(
+    reference: &Path,
+    take: &Path,
+    config: &AudioSyncConfig,
+) -> Result<AudioSync, ExportError> {
+    if config.max_offset_ms < 0.0 || config.analysis_ms <= 0.0 {
+        return Err(ExportError::InvalidConfig(
+            "Audio sync durations must be positive".to_string(),
+        ));
+    }
+
+    // The reference is decoded further so a late take still overlaps fully
+    let reference = onset_envelope(&decode_mono(
+        reference,
+        config.analysis_ms + config.max_offset_ms,
+    )?);
+    let take = onset_envelope(&decode_mono(take, config.analysis_ms)?);
+
+    let max_lag = (config.max_offset_ms / FRAME_MS).round() as usize;
+    let (lag, confidence) = find_lag(&reference, &take, max_lag)
+        .ok_or_else(|| ExportError::Decoding("Not enough audio to align".to_string()))?;
+
+    let sync = AudioSync {
+        offset_ms: lag * FRAME_MS,
+        confidence,
+    };
+    tracing::info!(
+        "Audio sync: offset {:.1}ms, confidence {:.2}",
+        sync.offset_ms,
+        sync.confidence
+    );
+    Ok(sync)
+}
+
+/// Decode up to `max_ms` of audio as mono f32 at the analysis rate
+fn decode_mono(path: &Path, max_ms: f64) -> Result<Vec<f32>, ExportError> {
+    let output = Command::new("ffmpeg")
+        .args([
+            "-v",
+            "error",
+            "-t",
+            &format!("{:.3}", max_ms / 1000.0),
+            "-i",
+        ])
+        .arg(path)
+        .args([
+            "-vn",
+            "-ac",
+            "1",
+            "-ar",
+            &ANALYSIS_RATE.to_string(),
+            "-f",
+            "f32le",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+
+    if !output.status.success() {
+        return Err(ExportError::Decoding(format!(
+            "Audio could not be decoded: {:?}",
+            path
+        )));
+    }
+
+    Ok(output
+        .stdout
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bursts of noise at irregular intervals, like syllables
+    fn speech_like(seconds: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1u32 << 24) as f32
+        };
+
+        let mut samples = vec![0.0f32; seconds * ANALYSIS_RATE as usize];
+        let mut i = 0;
+        while i < samples.len() {
+            let burst = 400 + (next() * 2000.0) as usize;
+            let gap = 200 + (next() * 3000.0) as usize;
+            for s in samples.iter_mut().skip(i).take(burst) {
+                *s = (next() - 0.5) * 0.8;
+            }
+            i += burst + gap;
+        }
+        samples
+    }
+
+    #[test]
+    fn test_finds_offset_of_quieter_shifted_take() {
+        let reference = speech_like(20, 7);
+        // The take starts 1.234s into the reference, recorded 6dB quieter
+        // with its own noise floor
+        let shift = (1.234 * ANALYSIS_RATE as f64) as usize;
+        let noise = speech_like(20, 99);
+        let take: Vec<f32> = reference[shift..]
+            .iter()
+            .zip(&noise)
+            .map(|(s, n)| s * 0.5 + n * 0.02)
+            .collect();
+
+        let (lag, confidence) =
+            find_lag(&onset_envelope(&reference), &onset_envelope(&take), 300).unwrap();
+        let sync = AudioSync {
+            offset_ms: lag * FRAME_MS,
+            confidence,
+        };
+
+        assert!(
+            (sync.offset_ms - 1234.0).abs() < 10.0,
+            "offset {}",
+            sync.offset_ms
+        );
+        assert!(sync.is_confident(), "confidence {}", sync.confidence);
+
+        // Unrelated audio doesn't align
+        let (_, unrelated) =
+            find_lag(&onset_envelope(&reference), &onset_envelope(&noise), 300).unwrap();
+        assert!(unrelated < MIN_SYNC_CONFIDENCE, "confidence {}", unrelated);
+    }
+}
@@ -6,7 +6,7 @@
 
 use crate::capture::input::types::MouseMove;
 use crate::processing::spring::Spring2D;
-use crate::project::schema::SpringConfig;
+use crate::project::schema::{Slice, SpringConfig};
 use serde::{Deserialize, Serialize};
 
 /// Smoothed mouse position with both raw and smoothed coordinates
@@ -55,25 +55,99 @@ pub fn smooth_cursor_data_with_teleport(
     output_fps: f64,
     teleport_threshold: f64,
 ) -> Vec<SmoothedMouseMove> {
-    if raw_moves.is_empty() {
-        return vec![];
+    let frames: Vec<(f64, bool)> = frame_grid(raw_moves, output_fps)
+        .map(|t| (t, false))
+        .collect();
+    smooth_at_times(raw_moves, config, output_fps, &frames, teleport_threshold)
+}
+
+/// Smooth cursor data for a timeline whose slices may be slowed down
+///
+/// A slice played at `time_scale` 0.25 shows each 1/60s of recording for
+/// four output frames, so the regular grid (and the ~60-120Hz input) would
+/// repeat positions and look steppy. Inside slices with a time scale below
+/// 1.0 extra frames are added at the slowed-down output rate, and their
+/// raw positions are interpolated between input samples (never across a
+/// teleport). Times stay in recording time, so frames outside slowed
+/// slices match `smooth_cursor_data`.
+pub fn smooth_cursor_for_slices(
+    raw_moves: &[MouseMove],
+    config: &SpringConfig,
+    output_fps: f64,
+    slices: &[Slice],
+) -> Vec<SmoothedMouseMove> {
+    let slowed: Vec<&Slice> = slices
+        .iter()
+        .filter(|s| s.time_scale > 0.0 && s.time_scale < 1.0)
+        .collect();
+    let in_slowed = |t: f64| {
+        slowed
+            .iter()
+            .any(|s| t >= s.source_start_ms && t < s.source_end_ms)
+    };
+
+    let mut frames: Vec<(f64, bool)> = frame_grid(raw_moves, output_fps)
+        .map(|t| (t, in_slowed(t)))
+        .collect();
+    let total_duration_ms = raw_moves.last().map(|m| m.process_time_ms).unwrap_or(0.0);
+    for slice in &slowed {
+        let step_ms = slice.time_scale * 1000.0 / output_fps;
+        let end_ms = slice.source_end_ms.min(total_duration_ms);
+        let mut t = slice.source_start_ms.max(0.0);
+        while t < end_ms {
+            frames.push((t, true));
+            t += step_ms;
+        }
     }
+    frames.sort_by(|a, b| a.0.total_cmp(&b.0));
+    frames.dedup_by(|b, a| (b.0 - a.0).abs() < 1e-6);
+
+    smooth_at_times(
+        raw_moves,
+        config,
+        output_fps,
+        &frames,
+        DEFAULT_TELEPORT_THRESHOLD,
+    )
+}
 
+/// Frame times at `output_fps` covering the recording
+fn frame_grid(raw_moves: &[MouseMove], output_fps: f64) -> impl Iterator<Item = f64> {
     let frame_duration_ms = 1000.0 / output_fps;
     let total_duration_ms = raw_moves.last().map(|m| m.process_time_ms).unwrap_or(0.0);
-    
+
     // Always have at least 1 frame for non-empty input
-    let frame_count = ((total_duration_ms / frame_duration_ms).ceil() as usize).max(1);
+    let frame_count = if raw_moves.is_empty() {
+        0
+    } else {
+        ((total_duration_ms / frame_duration_ms).ceil() as usize).max(1)
+    };
+    (0..frame_count).map(move |frame| frame as f64 * frame_duration_ms)
+}
+
+/// Step the spring through `frames`, given as `(time_ms, interpolate)` in
+/// ascending order. Interpolated frames blend the surrounding raw moves;
+/// the others take the latest move at or before the frame.
+fn smooth_at_times(
+    raw_moves: &[MouseMove],
+    config: &SpringConfig,
+    output_fps: f64,
+    frames: &[(f64, bool)],
+    teleport_threshold: f64,
+) -> Vec<SmoothedMouseMove> {
+    if raw_moves.is_empty() {
+        return vec![];
+    }
 
-    let mut result = Vec::with_capacity(frame_count);
+    let mut result = Vec::with_capacity(frames.len());
     let mut spring = Spring2D::new(raw_moves[0].x, raw_moves[0].y);
     let mut raw_index = 0;
     let mut last_raw_x = raw_moves[0].x;
     let mut last_raw_y = raw_moves[0].y;
+    // The first frame steps by a whole frame, like every other
+    let mut last_frame_time_ms = -1000.0 / output_fps;
 
-    for frame in 0..frame_count {
-        let frame_time_ms = frame as f64 * frame_duration_ms;
-
+    for &(frame_time_ms, interpolate) in frames {
         // Find the raw move closest to this frame time
         while raw_index + 1 < raw_moves.len()
             && raw_moves[raw_index + 1].process_time_ms <= frame_time_ms
@@ -82,31 +156,45 @@ pub fn smooth_cursor_data_with_teleport(
         }
 
         let raw = &raw_moves[raw_index];
+        let (raw_x, raw_y) = match raw_moves.get(raw_index + 1) {
+            Some(next)
+                if interpolate
+                    && next.process_time_ms > raw.process_time_ms
+                    && !detect_teleport(raw, next, teleport_threshold) =>
+            {
+                let t = ((frame_time_ms - raw.process_time_ms)
+                    / (next.process_time_ms - raw.process_time_ms))
+                    .clamp(0.0, 1.0);
+                (raw.x + (next.x - raw.x) * t, raw.y + (next.y - raw.y) * t)
+            }
+            _ => (raw.x, raw.y),
+        };
 
         // Detect teleport (large jump) and reset spring if needed
-        let dx = raw.x - last_raw_x;
-        let dy = raw.y - last_raw_y;
+        let dx = raw_x - last_raw_x;
+        let dy = raw_y - last_raw_y;
         let distance = (dx * dx + dy * dy).sqrt();
 
         if distance > teleport_threshold {
             // Teleport detected - reset spring to new position instantly
-            spring.reset(raw.x, raw.y);
+            spring.reset(raw_x, raw_y);
         } else {
             // Normal movement - step spring toward raw position
-            let dt = frame_duration_ms / 1000.0; // Convert to seconds
-            spring.step(raw.x, raw.y, config, dt);
+            let dt = (frame_time_ms - last_frame_time_ms) / 1000.0; // Convert to seconds
+            spring.step(raw_x, raw_y, config, dt);
         }
 
-        last_raw_x = raw.x;
-        last_raw_y = raw.y;
+        last_raw_x = raw_x;
+        last_raw_y = raw_y;
+        last_frame_time_ms = frame_time_ms;
 
         let (smooth_x, smooth_y) = spring.position();
 
         result.push(SmoothedMouseMove {
             x: smooth_x,
             y: smooth_y,
-            raw_x: raw.x,
-            raw_y: raw.y,
+            raw_x,
+            raw_y,
             cursor_id: raw.cursor_id.clone(),
             process_time_ms: frame_time_ms,
         });
@@ -115,6 +203,56 @@ pub fn smooth_cursor_data_with_teleport(
     result
 }
 
+/// Lead-in smoothed before a preview range so the spring has settled
+const PREVIEW_WARMUP_MS: f64 = 1000.0;
+
+/// Smooth only the frames within `start_ms..=end_ms`.
+///
+/// Used for live previews while tuning spring parameters: instead of the whole
+/// recording, only the range plus a short lead-in is simulated. Frame times
+/// stay on the same grid as `smooth_cursor_data`, so results line up.
+pub fn smooth_cursor_range(
+    raw_moves: &[MouseMove],
+    config: &SpringConfig,
+    output_fps: f64,
+    start_ms: f64,
+    end_ms: f64,
+) -> Vec<SmoothedMouseMove> {
+    if raw_moves.is_empty() || end_ms < start_ms {
+        return vec![];
+    }
+
+    let frame_duration_ms = 1000.0 / output_fps;
+    let warmup_start = ((start_ms - PREVIEW_WARMUP_MS).max(0.0) / frame_duration_ms).floor() * frame_duration_ms;
+
+    // Begin at the last move before the lead-in and stop after the first move past the range
+    let first = raw_moves
+        .iter()
+        .rposition(|m| m.process_time_ms <= warmup_start)
+        .unwrap_or(0);
+    let last = raw_moves
+        .iter()
+        .position(|m| m.process_time_ms > end_ms)
+        .unwrap_or(raw_moves.len() - 1);
+
+    let shifted: Vec<MouseMove> = raw_moves[first..=last.max(first)]
+        .iter()
+        .map(|m| MouseMove {
+            process_time_ms: (m.process_time_ms - warmup_start).max(0.0),
+            ..m.clone()
+        })
+        .collect();
+
+    smooth_cursor_data(&shifted, config, output_fps)
+        .into_iter()
+        .map(|mut m| {
+            m.process_time_ms += warmup_start;
+            m
+        })
+        .filter(|m| m.process_time_ms >= start_ms && m.process_time_ms <= end_ms)
+        .collect()
+}
+
 /// Detect if cursor teleported between two positions
 pub fn detect_teleport(prev: &MouseMove, curr: &MouseMove, threshold_px: f64) -> bool {
     let dx = curr.x - prev.x;
@@ -259,4 +397,65 @@ mod tests {
             smoothed_30fps.len()
         );
     }
+
+    #[test]
+    fn test_range_preview_matches_full_smoothing() {
+        // Steady diagonal movement over 4 seconds at 120Hz
+        let moves: Vec<MouseMove> = (0..480)
+            .map(|i| make_move(i as f64 * 2.0, i as f64, i as f64 * 8.33))
+            .collect();
+
+        let full = smooth_cursor_data(&moves, &default_config(), 30.0);
+        let range = smooth_cursor_range(&moves, &default_config(), 30.0, 2000.0, 2500.0);
+
+        assert!(!range.is_empty());
+        assert!(range.iter().all(|m| m.process_time_ms >= 2000.0 && m.process_time_ms <= 2500.0));
+        for preview in &range {
+            let reference = full
+                .iter()
+                .find(|m| (m.process_time_ms - preview.process_time_ms).abs() < 0.01)
+                .expect("preview frames should be on the full frame grid");
+            assert!((reference.x - preview.x).abs() < 1.0);
+            assert!((reference.y - preview.y).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_slowed_slices_get_interpolated_frames() {
+        // 60Hz input moving 10px per sample over 2 seconds
+        let moves: Vec<MouseMove> = (0..=120)
+            .map(|i| make_move(i as f64 * 10.0, 0.0, i as f64 * 1000.0 / 60.0))
+            .collect();
+        let slice = |start, end, time_scale| Slice {
+            id: "s".to_string(),
+            source_start_ms: start,
+            source_end_ms: end,
+            time_scale,
+            volume: 1.0,
+            hide_cursor: false,
+            disable_cursor_smoothing: false,
+        };
+        let slices = [slice(0.0, 1000.0, 1.0), slice(1000.0, 2000.0, 0.25)];
+
+        let plain = smooth_cursor_data(&moves, &default_config(), 60.0);
+        let dense = smooth_cursor_for_slices(&moves, &default_config(), 60.0, &slices);
+
+        // Four frames per source frame in the slowed second
+        let slowed = dense
+            .iter()
+            .filter(|m| m.process_time_ms >= 1000.0)
+            .count();
+        assert_eq!(slowed, 240);
+        // Unslowed frames are untouched
+        for (a, b) in plain.iter().zip(&dense).take(60) {
+            assert_eq!(a.process_time_ms, b.process_time_ms);
+            assert!((a.x - b.x).abs() < 1e-9);
+        }
+        // Raw positions between samples are interpolated
+        let between = dense
+            .iter()
+            .find(|m| (m.process_time_ms - (1000.0 + 1000.0 / 120.0)).abs() < 0.01)
+            .unwrap();
+        assert!((between.raw_x - 605.0).abs() < 0.01);
+    }
 }
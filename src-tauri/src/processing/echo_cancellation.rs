@@ -0,0 +1,175 @@
+//! Acoustic echo cancellation for the microphone track
+//!
+//! When a call is recorded with speakers rather than headphones, the other
+//! side's voice plays out of the speakers, into the microphone and ends up in
+//! the export twice: cleanly in the system audio track and again, delayed and
+//! colored by the room, in the microphone. With echo cancellation enabled,
+//! export first runs the microphone through FFmpeg's `anlms` adaptive filter
+//! with the system audio as the reference. The filter learns how the room
+//! turns the reference into the echo and subtracts its estimate, leaving the
+//! local speaker.
+//!
+//! The filter only spans `ECHO_TAIL_MS`, so the reference is first delayed
+//! by the bulk latency between the tracks (output buffering, the speaker
+//! path), measured by cross-correlating them with `audio_sync`.
+
+use crate::export::types::{ExportError, ExportOptions};
+use crate::processing::audio_sync::{self, AudioSync, AudioSyncConfig};
+use crate::project::media::probe_media;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Rate the filter runs at and the cleaned track is written at
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Room reverberation the adaptive filter models after the bulk delay
+const ECHO_TAIL_MS: f64 = 60.0;
+
+/// How much of the filter precedes the measured delay, so a slightly
+/// late estimate still covers the start of the echo
+const DELAY_LEAD_MS: f64 = 10.0;
+
+/// Largest speaker-to-microphone latency searched for
+const MAX_ECHO_DELAY_MS: f64 = 1_000.0;
+
+/// Audio analyzed when measuring the latency
+const DELAY_ANALYSIS_MS: f64 = 60_000.0;
+
+/// NLMS step size: how quickly the filter adapts to the room
+const STEP_SIZE: f64 = 0.5;
+
+/// Delay applied to the reference so the filter window starts just before
+/// the echo. `sync` aligns the microphone against the system audio; the
+/// echo arrives late, so a confident match has a negative offset.
+pub fn reference_delay_ms(sync: Option<&AudioSync>) -> f64 {
+    sync.filter(|s| s.is_confident())
+        .map(|s| (-s.offset_ms - DELAY_LEAD_MS).max(0.0))
+        .unwrap_or(0.0)
+}
+
+/// Filter graph taking the system audio as input 0 and the microphone as
+/// input 1, producing the echo-cancelled microphone as `[out]`
+pub fn echo_filter(reference_delay_ms: f64) -> String {
+    let format = format!(
+        "aformat=sample_fmts=flt:sample_rates={}:channel_layouts=mono",
+        SAMPLE_RATE
+    );
+    let order = (ECHO_TAIL_MS + DELAY_LEAD_MS) * SAMPLE_RATE as f64 / 1000.0;
+    format!(
+        "[0:a]{format},adelay={:.0}:all=1,apad[ref];\
+         [1:a]{format}[mic];\
+         [ref][mic]anlms=order={:.0}:mu={}:eps=1:out_mode=n[out]",
+        reference_delay_ms, order, STEP_SIZE
+    )
+}
+
+/// Write an echo-cancelled copy of `mic` to `output` (WAV), using `system`
+/// as the reference. Returns the reference delay applied.
+pub fn cancel_echo(mic: &Path, system: &Path, output: &Path) -> Result<f64, ExportError> {
+    let mic_duration_ms = probe_media(mic)
+        .map_err(|e| ExportError::Decoding(e.to_string()))?
+        .duration_ms;
+
+    let config = AudioSyncConfig {
+        max_offset_ms: MAX_ECHO_DELAY_MS,
+        analysis_ms: DELAY_ANALYSIS_MS,
+    };
+    let sync = match audio_sync::sync_audio(system, mic, &config) {
+        Ok(sync) => Some(sync),
+        Err(e) => {
+            tracing::warn!("Could not measure echo delay, assuming none: {}", e);
+            None
+        }
+    };
+    let delay_ms = reference_delay_ms(sync.as_ref());
+    tracing::info!(
+        "Cancelling echo in {:?} (reference delay {:.0}ms)",
+        mic,
+        delay_ms
+    );
+
+    let result = Command::new("ffmpeg")
+        .args(["-y", "-v", "error", "-i"])
+        .arg(system)
+        .arg("-i")
+        .arg(mic)
+        .args([
+            "-filter_complex",
+            &echo_filter(delay_ms),
+            "-map",
+            "[out]",
+            // The padded reference never ends; stop with the microphone
+            "-t",
+            &format!("{:.3}", mic_duration_ms / 1000.0),
+            "-c:a",
+            "pcm_s16le",
+        ])
+        .arg(output)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+    if !result.status.success() {
+        return Err(ExportError::Ffmpeg(format!(
+            "Echo cancellation failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+    Ok(delay_ms)
+}
+
+/// Whether `export_mic_track` processes the microphone before encoding,
+/// rather than using the recorded track as is
+pub fn needs_audio_pass(
+    options: &ExportOptions,
+    mic: Option<&Path>,
+    system: Option<&Path>,
+) -> bool {
+    options.cancel_echo && options.include_mic_audio && mic.is_some() && system.is_some()
+}
+
+/// Microphone track to export: an echo-cancelled copy in `scratch_dir` when
+/// the options ask for one and there's system audio to cancel against,
+/// otherwise the recorded track. Failures fall back to the recorded track
+/// rather than failing the export.
+pub fn export_mic_track(
+    options: &ExportOptions,
+    mic: Option<&Path>,
+    system: Option<&Path>,
+    scratch_dir: &Path,
+) -> Option<PathBuf> {
+    let mic = mic?;
+    let (true, Some(system)) = (needs_audio_pass(options, Some(mic), system), system) else {
+        return Some(mic.to_path_buf());
+    };
+
+    let output = scratch_dir.join("mic-echo-cancelled.wav");
+    match cancel_echo(mic, system, &output) {
+        Ok(_) => Some(output),
+        Err(e) => {
+            tracing::warn!("Exporting microphone without echo cancellation: {}", e);
+            Some(mic.to_path_buf())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_delay_follows_confident_match() {
+        let sync = |offset_ms, confidence| AudioSync {
+            offset_ms,
+            confidence,
+        };
+        // Echo 120ms late: the mic needs advancing by 120ms
+        assert_eq!(reference_delay_ms(Some(&sync(-120.0, 0.8))), 110.0);
+        assert_eq!(reference_delay_ms(Some(&sync(-120.0, 0.1))), 0.0);
+        assert_eq!(reference_delay_ms(Some(&sync(40.0, 0.8))), 0.0);
+        assert_eq!(reference_delay_ms(None), 0.0);
+
+        let filter = echo_filter(110.0);
+        assert!(filter.contains("adelay=110:all=1"));
+        assert!(filter.contains("anlms=order=3360:"));
+    }
+}
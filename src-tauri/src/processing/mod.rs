@@ -1,10 +1,17 @@
 //! Processing module for post-recording transformations
 //!
 //! This module contains algorithms for cursor smoothing, zoom detection,
-//! and other post-processing operations applied during playback and export.
+//! scene change detection, and other post-processing operations applied
+//! during playback and export.
 
+pub mod audio_sync;
 pub mod cursor_smoothing;
+pub mod echo_cancellation;
+pub mod scene_detection;
 pub mod spring;
+pub mod sync_report;
+pub mod zoom;
+pub mod zoom_timeline;
 
 pub use cursor_smoothing::{smooth_cursor_data, SmoothedMouseMove};
 pub use spring::{Spring2D, SpringState};
@@ -0,0 +1,175 @@
+//! Scene change detection
+//!
+//! Finds large visual transitions in a screen recording (switching apps,
+//! advancing slides, navigating to a new page) by comparing downscaled
+//! grayscale frames. Each transition becomes a suggested chapter marker the
+//! user can accept or edit before exporting.
+
+use crate::export::ExportError;
+use crate::project::schema::Marker;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use uuid::Uuid;
+
+/// Size frames are scaled to before comparison
+const ANALYSIS_WIDTH: usize = 64;
+const ANALYSIS_HEIGHT: usize = 36;
+
+/// Per-pixel luma change (0-255) below which a pixel counts as unchanged.
+/// Filters out encoder noise and cursor movement.
+const PIXEL_CHANGE_THRESHOLD: u8 = 24;
+
+/// Tuning for scene detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneDetectionConfig {
+    /// Fraction of pixels (0.0-1.0) that must change for a transition
+    pub threshold: f64,
+    /// Minimum length of a chapter in milliseconds
+    pub min_chapter_ms: f64,
+    /// Frames sampled per second of video
+    pub sample_fps: f64,
+}
+
+impl Default for SceneDetectionConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.35,
+            min_chapter_ms: 10_000.0,
+            sample_fps: 2.0,
+        }
+    }
+}
+
+/// Fraction of pixels that changed noticeably between two grayscale frames
+pub fn frame_difference(a: &[u8], b: &[u8]) -> f64 {
+    let len = a.len().min(b.len());
+    if len == 0 {
+        return 0.0;
+    }
+    let changed = a
+        .iter()
+        .zip(b)
+        .filter(|(x, y)| x.abs_diff(**y) > PIXEL_CHANGE_THRESHOLD)
+        .count();
+    changed as f64 / len as f64
+}
+
+/// Times (ms) of transitions in a sequence of evenly spaced frames.
+///
+/// A transition is kept only if it starts at least `min_chapter_ms` after the
+/// previous one (or the start of the video), so bursts of changes such as an
+/// animated app switch yield a single chapter.
+pub fn detect_transitions(frames: &[Vec<u8>], frame_interval_ms: f64, config: &SceneDetectionConfig) -> Vec<f64> {
+    let mut transitions = Vec::new();
+    let mut last = 0.0;
+
+    for (i, pair) in frames.windows(2).enumerate() {
+        let time_ms = (i + 1) as f64 * frame_interval_ms;
+        if frame_difference(&pair[0], &pair[1]) >= config.threshold && time_ms - last >= config.min_chapter_ms {
+            transitions.push(time_ms);
+            last = time_ms;
+        }
+    }
+
+    transitions
+}
+
+/// Turn transition times into chapter markers, starting with one at 0
+pub fn chapters_from_transitions(transitions: &[f64]) -> Vec<Marker> {
+    std::iter::once(0.0)
+        .chain(transitions.iter().copied())
+        .enumerate()
+        .map(|(i, time)| Marker {
+            id: Uuid::new_v4().to_string(),
+            time,
+            label: if i == 0 {
+                "Intro".to_string()
+            } else {
+                format!("Chapter {}", i + 1)
+            },
+            color: None,
+        })
+        .collect()
+}
+
+/// Analyze a video file and suggest chapter markers
+pub fn suggest_chapters(video_path: &Path, config: &SceneDetectionConfig) -> Result<Vec<Marker>, ExportError> {
+    if config.sample_fps <= 0.0 {
+        return Err(ExportError::InvalidConfig("Sample rate must be positive".to_string()));
+    }
+
+    let frames = decode_luma_frames(video_path, config.sample_fps)?;
+    let transitions = detect_transitions(&frames, 1000.0 / config.sample_fps, config);
+
+    tracing::info!(
+        "Scene detection: {} frames sampled, {} transitions found",
+        frames.len(),
+        transitions.len()
+    );
+
+    Ok(chapters_from_transitions(&transitions))
+}
+
+/// Decode grayscale thumbnails at `fps`
+fn decode_luma_frames(path: &Path, fps: f64) -> Result<Vec<Vec<u8>>, ExportError> {
+    let output = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(path)
+        .args([
+            "-vf",
+            &format!("fps={},scale={}:{},format=gray", fps, ANALYSIS_WIDTH, ANALYSIS_HEIGHT),
+            "-f",
+            "rawvideo",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| ExportError::ffmpeg_spawn("Failed to run FFmpeg", e))?;
+
+    if !output.status.success() {
+        return Err(ExportError::Decoding("Video could not be decoded".to_string()));
+    }
+
+    Ok(output
+        .stdout
+        .chunks_exact(ANALYSIS_WIDTH * ANALYSIS_HEIGHT)
+        .map(|f| f.to_vec())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_difference_ignores_noise() {
+        let a = vec![100u8; 10];
+        let mut b = vec![110u8; 10];
+        assert_eq!(frame_difference(&a, &b), 0.0);
+
+        b[..4].copy_from_slice(&[250; 4]);
+        assert!((frame_difference(&a, &b) - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_transitions_respect_min_chapter_length() {
+        let dark = vec![0u8; 16];
+        let light = vec![255u8; 16];
+        // One frame per second: changes at 12s, 13s (too close) and 30s
+        let mut frames = vec![dark.clone(); 12];
+        frames.push(light.clone());
+        frames.extend(vec![dark; 17]);
+        frames.push(light);
+
+        let config = SceneDetectionConfig::default();
+        assert_eq!(detect_transitions(&frames, 1000.0, &config), vec![12000.0, 30000.0]);
+
+        let chapters = chapters_from_transitions(&[12000.0, 30000.0]);
+        let labels: Vec<&str> = chapters.iter().map(|m| m.label.as_str()).collect();
+        assert_eq!(labels, vec!["Intro", "Chapter 2", "Chapter 3"]);
+        assert_eq!(chapters[0].time, 0.0);
+    }
+}
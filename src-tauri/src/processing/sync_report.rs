@@ -0,0 +1,287 @@
+//! Sync diagnostics for recorded bundles
+//!
+//! Measures how the tracks of a finished recording line up with the video
+//! the session is built around (the screen recording, or the webcam for
+//! camera-only bundles), two ways:
+//!
+//! - From embedded timing: when each channel started according to the
+//!   recording manifest, and how long each file actually is. A track that
+//!   started late or ended up shorter than the video is usually out of sync
+//!   by about that much.
+//! - By cross-correlating the audio tracks with each other (see
+//!   `audio_sync`): system audio played through the speakers also reaches
+//!   the microphone, so their relative offset can be measured directly.
+//!
+//! The report gives users concrete numbers when something sounds off and
+//! suggests a microphone offset for `AudioConfig::microphone_offset_ms`.
+//! Export already lines up audio whose start was anchored (see
+//! `export::alignment`), so an offset is only suggested for audio it can't.
+
+use crate::export::ExportError;
+use crate::processing::audio_sync::{self, AudioSyncConfig};
+use crate::project::encryption::readable_path;
+use crate::project::media::probe_media;
+use crate::recorder::channel::ChannelType;
+use crate::recorder::manifest::{resolve_file, FileKind, RecordingManifest};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Largest audio offset searched for when correlating tracks
+const MAX_CORRELATION_OFFSET_MS: f64 = 2_000.0;
+
+/// Audio analyzed from the start of each track
+const CORRELATION_ANALYSIS_MS: f64 = 60_000.0;
+
+/// Media tracks a report covers, in report order
+const MEDIA_TRACKS: [(FileKind, ChannelType); 4] = [
+    (FileKind::ScreenVideo, ChannelType::Display),
+    (FileKind::WebcamVideo, ChannelType::Webcam),
+    (FileKind::MicAudio, ChannelType::Microphone),
+    (FileKind::SystemAudio, ChannelType::SystemAudio),
+];
+
+/// Timing of one recorded track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackTiming {
+    pub channel_type: ChannelType,
+    pub path: String,
+    /// Unix time the channel started recording, from the manifest
+    pub started_at_ms: Option<u64>,
+    /// How much later than the reference video this track started
+    pub start_offset_ms: Option<f64>,
+    pub duration_ms: Option<f64>,
+    /// How much shorter than the reference video this track is
+    pub duration_shortfall_ms: Option<f64>,
+    pub has_audio: bool,
+    /// Whether export lines the track up from where capture anchored it
+    pub anchored: bool,
+}
+
+/// Measured offset between two audio tracks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackCorrelation {
+    pub reference: ChannelType,
+    pub track: ChannelType,
+    /// Delay that lines `track` up with `reference` (positive delays it)
+    pub offset_ms: f64,
+    /// Correlation of the aligned tracks (0.0-1.0)
+    pub confidence: f64,
+    pub confident: bool,
+}
+
+/// Sync measurements for one session of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub session_index: usize,
+    /// Track the others are measured against
+    pub reference: Option<ChannelType>,
+    pub tracks: Vec<TrackTiming>,
+    pub correlations: Vec<TrackCorrelation>,
+    /// Microphone offset the timing suggests, for
+    /// `AudioConfig::microphone_offset_ms`
+    pub suggested_microphone_offset_ms: Option<f64>,
+    /// Measurements that couldn't be made
+    pub warnings: Vec<String>,
+}
+
+/// Analyze one session of a bundle. Channel start times are only known for
+/// the first session, so later sessions are compared by duration alone.
+pub fn analyze_bundle(project_dir: &Path, session_index: usize) -> Result<SyncReport, ExportError> {
+    let recording_dir = project_dir.join("recording");
+    if !recording_dir.is_dir() {
+        return Err(ExportError::BundleNotFound(format!(
+            "No recording in {:?}",
+            project_dir
+        )));
+    }
+    let manifest = RecordingManifest::read(&recording_dir);
+    let mut warnings = Vec::new();
+
+    // Readable copies of every recorded track, probed for duration and audio
+    let mut tracks: Vec<(TrackTiming, PathBuf)> = Vec::new();
+    for (kind, channel_type) in MEDIA_TRACKS {
+        let Some(path) = resolve_file(&recording_dir, manifest.as_ref(), kind, session_index)
+        else {
+            continue;
+        };
+        let started_at_ms = manifest
+            .as_ref()
+            .filter(|_| session_index == 0)
+            .and_then(|m| m.channels.iter().find(|c| c.channel_type == channel_type))
+            .and_then(|c| c.started_at_ms);
+
+        let readable = readable_path(&path)?;
+        let info = match probe_media(&readable) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                warnings.push(format!("Could not probe {} track: {}", channel_type, e));
+                None
+            }
+        };
+
+        tracks.push((
+            TrackTiming {
+                channel_type,
+                path: path.to_string_lossy().to_string(),
+                started_at_ms,
+                start_offset_ms: None,
+                duration_ms: info.as_ref().map(|i| i.duration_ms),
+                duration_shortfall_ms: None,
+                has_audio: info.as_ref().is_some_and(|i| i.has_audio),
+                anchored: manifest
+                    .as_ref()
+                    .is_some_and(|m| m.anchor(kind, session_index).is_some()),
+            },
+            readable,
+        ));
+    }
+
+    let mut timings: Vec<TrackTiming> = tracks.iter().map(|(t, _)| t.clone()).collect();
+    let reference = compare_to_reference(&mut timings);
+    if reference.is_none() {
+        warnings.push("No video to compare against".to_string());
+    }
+
+    let correlations = correlate_audio(&tracks, &mut warnings);
+    let suggested_microphone_offset_ms = suggest_microphone_offset(&timings);
+
+    Ok(SyncReport {
+        session_index,
+        reference,
+        tracks: timings,
+        correlations,
+        suggested_microphone_offset_ms,
+        warnings,
+    })
+}
+
+/// Fill in each track's start offset and duration shortfall relative to the
+/// screen video (or the webcam without one), returning the reference used
+pub fn compare_to_reference(tracks: &mut [TrackTiming]) -> Option<ChannelType> {
+    let reference = [ChannelType::Display, ChannelType::Webcam]
+        .into_iter()
+        .find_map(|t| tracks.iter().find(|track| track.channel_type == t))?;
+    let (reference_type, reference_start, reference_duration) = (
+        reference.channel_type,
+        reference.started_at_ms,
+        reference.duration_ms,
+    );
+
+    for track in tracks.iter_mut() {
+        track.start_offset_ms = track
+            .started_at_ms
+            .zip(reference_start)
+            .map(|(start, reference)| start as f64 - reference as f64);
+        track.duration_shortfall_ms = track
+            .duration_ms
+            .zip(reference_duration)
+            .map(|(duration, reference)| reference - duration);
+    }
+    Some(reference_type)
+}
+
+/// Microphone offset that compensates for a late start: a microphone that
+/// started `n` ms after the video needs delaying by `n` ms. Falls back to
+/// the duration shortfall (which assumes the tracks stopped together) when
+/// start times weren't recorded. None when export already lines the
+/// microphone up with the video, as an offset would correct it twice.
+pub fn suggest_microphone_offset(tracks: &[TrackTiming]) -> Option<f64> {
+    let mic = tracks
+        .iter()
+        .find(|t| t.channel_type == ChannelType::Microphone)?;
+    let reference = [ChannelType::Display, ChannelType::Webcam]
+        .into_iter()
+        .find_map(|t| tracks.iter().find(|track| track.channel_type == t));
+    if mic.anchored && reference.is_some_and(|r| r.anchored) {
+        return None;
+    }
+    mic.start_offset_ms.or(mic.duration_shortfall_ms)
+}
+
+/// Cross-correlate every pair of tracks that carry audio
+fn correlate_audio(
+    tracks: &[(TrackTiming, PathBuf)],
+    warnings: &mut Vec<String>,
+) -> Vec<TrackCorrelation> {
+    let config = AudioSyncConfig {
+        max_offset_ms: MAX_CORRELATION_OFFSET_MS,
+        analysis_ms: CORRELATION_ANALYSIS_MS,
+    };
+    let audio: Vec<_> = tracks.iter().filter(|(t, _)| t.has_audio).collect();
+
+    let mut correlations = Vec::new();
+    for (i, (reference, reference_path)) in audio.iter().enumerate() {
+        for (track, track_path) in &audio[i + 1..] {
+            match audio_sync::sync_audio(reference_path, track_path, &config) {
+                Ok(sync) => correlations.push(TrackCorrelation {
+                    reference: reference.channel_type,
+                    track: track.channel_type,
+                    offset_ms: sync.offset_ms,
+                    confidence: sync.confidence,
+                    confident: sync.is_confident(),
+                }),
+                Err(e) => warnings.push(format!(
+                    "Could not correlate {} with {}: {}",
+                    track.channel_type, reference.channel_type, e
+                )),
+            }
+        }
+    }
+    correlations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(
+        channel_type: ChannelType,
+        started_at_ms: Option<u64>,
+        duration_ms: f64,
+    ) -> TrackTiming {
+        TrackTiming {
+            channel_type,
+            path: String::new(),
+            started_at_ms,
+            start_offset_ms: None,
+            duration_ms: Some(duration_ms),
+            duration_shortfall_ms: None,
+            has_audio: channel_type != ChannelType::Display,
+            anchored: false,
+        }
+    }
+
+    #[test]
+    fn test_offsets_relative_to_screen() {
+        let mut tracks = vec![
+            track(ChannelType::Display, Some(1_000_000), 60_000.0),
+            track(ChannelType::Microphone, Some(1_000_180), 59_830.0),
+            track(ChannelType::SystemAudio, Some(999_950), 60_040.0),
+        ];
+        assert_eq!(
+            compare_to_reference(&mut tracks),
+            Some(ChannelType::Display)
+        );
+        assert_eq!(tracks[1].start_offset_ms, Some(180.0));
+        assert_eq!(tracks[1].duration_shortfall_ms, Some(170.0));
+        assert_eq!(tracks[2].start_offset_ms, Some(-50.0));
+        assert_eq!(suggest_microphone_offset(&tracks), Some(180.0));
+        // Anchored tracks are lined up on export, so nothing's suggested
+        for track in &mut tracks {
+            track.anchored = true;
+        }
+        assert_eq!(suggest_microphone_offset(&tracks), None);
+
+        // Without start times, fall back to the duration shortfall against
+        // the webcam of a camera-only recording
+        let mut tracks = vec![
+            track(ChannelType::Microphone, None, 29_900.0),
+            track(ChannelType::Webcam, None, 30_000.0),
+        ];
+        assert_eq!(compare_to_reference(&mut tracks), Some(ChannelType::Webcam));
+        assert_eq!(suggest_microphone_offset(&tracks), Some(100.0));
+    }
+}
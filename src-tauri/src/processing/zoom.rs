@@ -0,0 +1,305 @@
+//! Zoom path computation
+//!
+//! Turns a scene's zoom ranges into a zoom/pan transform over time. Export
+//! applies the transform to each frame and the editor requests sampled
+//! keyframes to draw the path on the timeline, so both use this one
+//! implementation.
+//!
+//! Range times are source times in milliseconds. Centers and manual target
+//! points are normalized to the frame (0.0-1.0).
+
+use crate::capture::frame::ContentRect;
+use crate::capture::input::types::MouseClick;
+use crate::processing::cursor_smoothing::SmoothedMouseMove;
+use crate::project::schema::{ZoomRange, ZoomType};
+use serde::{Deserialize, Serialize};
+
+/// Duration of the zoom-in and zoom-out animations
+pub const ZOOM_TRANSITION_MS: f64 = 400.0;
+
+/// Share of the zoomed view kept between the target and the view's border
+/// when snapping moves the view off the target
+const SNAP_TARGET_MARGIN: f64 = 0.1;
+
+/// Zoom transform at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoomKeyframe {
+    pub time_ms: f64,
+    /// Magnification (1.0 = no zoom)
+    pub scale: f64,
+    /// Normalized center of the visible area
+    pub center_x: f64,
+    pub center_y: f64,
+}
+
+/// Cursor and click data a zoom follows
+pub struct ZoomInputs<'a> {
+    /// Smoothed cursor positions, sorted by time
+    pub cursor: &'a [SmoothedMouseMove],
+    pub clicks: &'a [MouseClick],
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Ease-in-out curve for zoom transitions
+fn ease(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// How far (0.0-1.0) a range is zoomed in at `time_ms`
+fn zoom_amount(range: &ZoomRange, time_ms: f64) -> f64 {
+    if range.instant {
+        return if time_ms >= range.start_time && time_ms < range.end_time { 1.0 } else { 0.0 };
+    }
+    if time_ms < range.start_time || time_ms >= range.end_time + ZOOM_TRANSITION_MS {
+        0.0
+    } else if time_ms < range.end_time {
+        ease((time_ms - range.start_time) / ZOOM_TRANSITION_MS)
+    } else {
+        ease(1.0 - (time_ms - range.end_time) / ZOOM_TRANSITION_MS)
+    }
+}
+
+/// Normalized cursor position at `time_ms`
+fn cursor_at(inputs: &ZoomInputs, time_ms: f64) -> Option<(f64, f64)> {
+    let idx = inputs.cursor.partition_point(|m| m.process_time_ms <= time_ms);
+    let m = inputs.cursor.get(idx.saturating_sub(1))?;
+    Some((m.x / inputs.width as f64, m.y / inputs.height as f64))
+}
+
+/// Point a range keeps in view at `time_ms`
+fn target_at(range: &ZoomRange, inputs: &ZoomInputs, time_ms: f64) -> (f64, f64) {
+    // Zoom-out keeps the framing the range ended with
+    let time_ms = time_ms.min(range.end_time);
+    let target = match range.zoom_type {
+        ZoomType::FollowCursor => cursor_at(inputs, time_ms),
+        ZoomType::FollowClicks => {
+            let in_range = |c: &&MouseClick| {
+                c.event_type == "down" && c.process_time_ms >= range.start_time && c.process_time_ms < range.end_time
+            };
+            // Frame the latest click, or the first upcoming one before any click
+            inputs
+                .clicks
+                .iter()
+                .rev()
+                .find(|c| in_range(c) && c.process_time_ms <= time_ms)
+                .or_else(|| inputs.clicks.iter().find(in_range))
+                .map(|c| (c.x / inputs.width as f64, c.y / inputs.height as f64))
+                .or_else(|| cursor_at(inputs, time_ms))
+        }
+        ZoomType::Manual => range.target_point.as_ref().map(|p| (p.x, p.y)),
+    };
+    target.unwrap_or((0.5, 0.5))
+}
+
+/// Center of the zoomed view on one axis, normalized, for a view `half` its
+/// size either side of the center. The view is centered on `target` and
+/// clamped inside the frame. With `snap` above 0, a view that comes within
+/// `snap` of its own size from an edge is pulled toward it, flush within
+/// half that distance, so zooms near an edge line up with it instead of
+/// hovering just off it. The pull never leaves the target in the view's
+/// outer `SNAP_TARGET_MARGIN`.
+fn frame_axis(target: f64, half: f64, snap: f64) -> f64 {
+    let center = target.clamp(half, 1.0 - half);
+    let reach = snap.clamp(0.0, 1.0) * 2.0 * half;
+    if reach <= 0.0 {
+        return center;
+    }
+
+    // Snap toward the nearer edge, easing in across the outer half of reach
+    let to_start = center - half;
+    let to_end = 1.0 - half - center;
+    let (distance, flush) = if to_start <= to_end {
+        (to_start, half)
+    } else {
+        (to_end, 1.0 - half)
+    };
+    let pull = ((reach - distance) / (reach / 2.0)).clamp(0.0, 1.0);
+    let snapped = center + (flush - center) * pull;
+
+    let slack = half * (1.0 - 2.0 * SNAP_TARGET_MARGIN);
+    snapped
+        .clamp(target - slack, target + slack)
+        .clamp(half, 1.0 - half)
+}
+
+/// Zoom transform at `time_ms`.
+///
+/// A range zooms in over `ZOOM_TRANSITION_MS` from its start and zooms out
+/// over the same duration after its end (both immediate for instant ranges).
+/// The center is clamped so the visible area never leaves the frame, and
+/// snapped toward the frame's edges by the range's `snap_to_edges`.
+pub fn zoom_at(ranges: &[ZoomRange], inputs: &ZoomInputs, time_ms: f64) -> ZoomKeyframe {
+    // A range that is still active wins over another's zoom-out
+    let active = ranges
+        .iter()
+        .find(|r| time_ms >= r.start_time && time_ms < r.end_time)
+        .or_else(|| ranges.iter().find(|r| zoom_amount(r, time_ms) > 0.0));
+
+    let Some(range) = active else {
+        return ZoomKeyframe {
+            time_ms,
+            scale: 1.0,
+            center_x: 0.5,
+            center_y: 0.5,
+        };
+    };
+
+    let scale = 1.0 + (range.zoom.max(1.0) - 1.0) * zoom_amount(range, time_ms);
+    let (x, y) = target_at(range, inputs, time_ms);
+    let half = 0.5 / scale;
+
+    ZoomKeyframe {
+        time_ms,
+        scale,
+        center_x: frame_axis(x, half, range.snap_to_edges),
+        center_y: frame_axis(y, half, range.snap_to_edges),
+    }
+}
+
+/// Sample the zoom path between `start_ms` and `end_ms` at `fps`
+pub fn render_zoom_path(
+    ranges: &[ZoomRange],
+    inputs: &ZoomInputs,
+    start_ms: f64,
+    end_ms: f64,
+    fps: f64,
+) -> Vec<ZoomKeyframe> {
+    if fps <= 0.0 || end_ms < start_ms {
+        return Vec::new();
+    }
+    let frame_ms = 1000.0 / fps;
+    let frames = ((end_ms - start_ms) / frame_ms).floor() as usize;
+    (0..=frames)
+        .map(|i| zoom_at(ranges, inputs, start_ms + i as f64 * frame_ms))
+        .collect()
+}
+
+/// Source pixels visible under a zoom transform
+pub fn crop_rect(keyframe: &ZoomKeyframe, width: u32, height: u32) -> ContentRect {
+    let crop_w = (width as f64 / keyframe.scale).round().clamp(1.0, width as f64);
+    let crop_h = (height as f64 / keyframe.scale).round().clamp(1.0, height as f64);
+    let x = (keyframe.center_x * width as f64 - crop_w / 2.0).round().clamp(0.0, width as f64 - crop_w);
+    let y = (keyframe.center_y * height as f64 - crop_h / 2.0).round().clamp(0.0, height as f64 - crop_h);
+
+    ContentRect {
+        x: x as u32,
+        y: y as u32,
+        width: crop_w as u32,
+        height: crop_h as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::schema::Point;
+
+    fn range(start: f64, end: f64, zoom_type: ZoomType) -> ZoomRange {
+        ZoomRange {
+            id: "z".to_string(),
+            start_time: start,
+            end_time: end,
+            zoom: 2.0,
+            zoom_type,
+            target_point: None,
+            snap_to_edges: 0.0,
+            instant: false,
+        }
+    }
+
+    fn cursor(time: f64, x: f64, y: f64) -> SmoothedMouseMove {
+        SmoothedMouseMove {
+            x,
+            y,
+            raw_x: x,
+            raw_y: y,
+            cursor_id: String::new(),
+            process_time_ms: time,
+        }
+    }
+
+    #[test]
+    fn test_zoom_eases_in_and_out() {
+        let ranges = vec![range(1000.0, 3000.0, ZoomType::Manual)];
+        let inputs = ZoomInputs {
+            cursor: &[],
+            clicks: &[],
+            width: 100,
+            height: 100,
+        };
+
+        assert_eq!(zoom_at(&ranges, &inputs, 500.0).scale, 1.0);
+        assert_eq!(zoom_at(&ranges, &inputs, 1200.0).scale, 1.5);
+        assert_eq!(zoom_at(&ranges, &inputs, 2000.0).scale, 2.0);
+        assert_eq!(zoom_at(&ranges, &inputs, 3200.0).scale, 1.5);
+        assert_eq!(zoom_at(&ranges, &inputs, 3400.0).scale, 1.0);
+
+        let path = render_zoom_path(&ranges, &inputs, 0.0, 1000.0, 10.0);
+        assert_eq!(path.len(), 11);
+        assert_eq!(path[10].time_ms, 1000.0);
+    }
+
+    #[test]
+    fn test_zoom_center_follows_target_within_frame() {
+        let moves = vec![cursor(0.0, 50.0, 50.0), cursor(1000.0, 100.0, 0.0)];
+        let inputs = ZoomInputs {
+            cursor: &moves,
+            clicks: &[],
+            width: 200,
+            height: 100,
+        };
+
+        let follow = vec![range(0.0, 5000.0, ZoomType::FollowCursor)];
+        let kf = zoom_at(&follow, &inputs, 2000.0);
+        // Cursor at the top edge: clamped so the crop stays inside the frame
+        assert_eq!((kf.center_x, kf.center_y), (0.5, 0.25));
+        assert_eq!(
+            crop_rect(&kf, 200, 100),
+            ContentRect {
+                x: 50,
+                y: 0,
+                width: 100,
+                height: 50
+            }
+        );
+
+        let mut manual = range(0.0, 5000.0, ZoomType::Manual);
+        manual.target_point = Some(Point { x: 0.6, y: 0.4 });
+        let kf = zoom_at(&[manual], &inputs, 2000.0);
+        assert_eq!((kf.center_x, kf.center_y), (0.6, 0.4));
+    }
+
+    #[test]
+    fn test_zoom_snaps_to_nearby_edges() {
+        let inputs = ZoomInputs {
+            cursor: &[],
+            clicks: &[],
+            width: 100,
+            height: 100,
+        };
+        let mut manual = range(0.0, 5000.0, ZoomType::Manual);
+        manual.snap_to_edges = 0.5;
+
+        // Within a quarter of the view from the left edge: flush with it
+        manual.target_point = Some(Point { x: 0.3, y: 0.5 });
+        let kf = zoom_at(&[manual.clone()], &inputs, 2000.0);
+        assert_eq!((kf.center_x, kf.center_y), (0.25, 0.5));
+
+        // Further out the pull eases off, and far away it's gone
+        manual.target_point = Some(Point { x: 0.4, y: 0.5 });
+        let kf = zoom_at(&[manual.clone()], &inputs, 2000.0);
+        assert!((kf.center_x - 0.28).abs() < 1e-9);
+        manual.target_point = Some(Point { x: 0.5, y: 0.5 });
+        assert_eq!(zoom_at(&[manual.clone()], &inputs, 2000.0).center_x, 0.5);
+
+        // A strong snap stops short of pushing the target out of view
+        manual.snap_to_edges = 1.0;
+        manual.target_point = Some(Point { x: 0.5, y: 0.5 });
+        let kf = zoom_at(&[manual], &inputs, 2000.0);
+        assert!((kf.center_x - 0.3).abs() < 1e-9);
+        assert_eq!(frame_axis(0.5, 0.25, 0.0), 0.5);
+    }
+}
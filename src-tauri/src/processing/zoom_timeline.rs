@@ -0,0 +1,191 @@
+//! Zoom timeline resolution
+//!
+//! Zoom ranges are edited freely on the timeline, so they can overlap each
+//! other or run across a cut between slices. Composited as-is, both make the
+//! output jump: a range that starts while another is still zooming out snaps
+//! back to 1x, and a range cut by a slice boundary jumps from zoomed-in
+//! footage straight to the next slice.
+//!
+//! `resolve_zoom_ranges` normalizes the ranges before they reach `zoom_at`:
+//!
+//! - Ranges are clamped to the slices they overlap and split where they
+//!   cross a cut, each piece ending early enough that its zoom-out finishes
+//!   before the cut.
+//! - A range is shortened so it has fully zoomed out before the next one
+//!   starts. When that would leave nothing of it, the next range is delayed
+//!   instead.
+//!
+//! Slices that continue where the previous one ended (e.g. a speed change)
+//! aren't cuts and don't interrupt a zoom.
+
+use crate::processing::zoom::ZOOM_TRANSITION_MS;
+use crate::project::schema::{Slice, ZoomRange};
+
+/// Source gap below which consecutive slices are treated as continuous
+const CONTINUOUS_SLICE_EPSILON_MS: f64 = 1.0;
+
+/// Shortest range worth keeping once clamped
+const MIN_RANGE_MS: f64 = 50.0;
+
+/// Time a range takes to zoom back out after its end
+fn zoom_out_ms(range: &ZoomRange) -> f64 {
+    if range.instant {
+        0.0
+    } else {
+        ZOOM_TRANSITION_MS
+    }
+}
+
+/// Continuous source spans of the slices, in source order
+fn source_segments(slices: &[Slice]) -> Vec<(f64, f64)> {
+    let mut spans: Vec<(f64, f64)> = slices
+        .iter()
+        .filter(|s| s.source_end_ms > s.source_start_ms)
+        .map(|s| (s.source_start_ms, s.source_end_ms))
+        .collect();
+    spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut segments: Vec<(f64, f64)> = Vec::new();
+    for (start, end) in spans {
+        match segments.last_mut() {
+            Some(last) if start <= last.1 + CONTINUOUS_SLICE_EPSILON_MS => last.1 = last.1.max(end),
+            _ => segments.push((start, end)),
+        }
+    }
+    segments
+}
+
+/// Split and clamp a range to the segments it overlaps
+fn clamp_to_segments(range: &ZoomRange, segments: &[(f64, f64)]) -> Vec<ZoomRange> {
+    segments
+        .iter()
+        .filter_map(|&(seg_start, seg_end)| {
+            let start = range.start_time.max(seg_start);
+            let end = range.end_time.min(seg_end - zoom_out_ms(range));
+            (end - start >= MIN_RANGE_MS).then(|| ZoomRange {
+                start_time: start,
+                end_time: end,
+                ..range.clone()
+            })
+        })
+        .collect()
+}
+
+/// Normalize a scene's zoom ranges against its screen slices so zooms never
+/// overlap or run across a cut. Returns ranges sorted by start time. An empty
+/// slice list leaves the ranges unclamped.
+pub fn resolve_zoom_ranges(ranges: &[ZoomRange], slices: &[Slice]) -> Vec<ZoomRange> {
+    let segments = source_segments(slices);
+    let mut pieces: Vec<ZoomRange> = if segments.is_empty() {
+        ranges
+            .iter()
+            .filter(|r| r.end_time - r.start_time >= MIN_RANGE_MS)
+            .cloned()
+            .collect()
+    } else {
+        ranges
+            .iter()
+            .flat_map(|r| clamp_to_segments(r, &segments))
+            .collect()
+    };
+    pieces.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    let mut resolved: Vec<ZoomRange> = Vec::with_capacity(pieces.len());
+    for mut range in pieces {
+        if let Some(prev) = resolved.last_mut() {
+            let zoomed_out_at = prev.end_time + zoom_out_ms(prev);
+            if range.start_time < zoomed_out_at {
+                let shortened_end = range.start_time - zoom_out_ms(prev);
+                if shortened_end - prev.start_time >= MIN_RANGE_MS {
+                    prev.end_time = shortened_end;
+                } else {
+                    range.start_time = zoomed_out_at;
+                }
+            }
+        }
+        if range.end_time - range.start_time >= MIN_RANGE_MS {
+            resolved.push(range);
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::schema::ZoomType;
+
+    fn range(id: &str, start: f64, end: f64) -> ZoomRange {
+        ZoomRange {
+            id: id.to_string(),
+            start_time: start,
+            end_time: end,
+            zoom: 2.0,
+            zoom_type: ZoomType::Manual,
+            target_point: None,
+            snap_to_edges: 0.0,
+            instant: false,
+        }
+    }
+
+    fn slice(start: f64, end: f64) -> Slice {
+        Slice {
+            id: String::new(),
+            source_start_ms: start,
+            source_end_ms: end,
+            time_scale: 1.0,
+            volume: 1.0,
+            hide_cursor: false,
+            disable_cursor_smoothing: false,
+        }
+    }
+
+    fn times(ranges: &[ZoomRange]) -> Vec<(&str, f64, f64)> {
+        ranges
+            .iter()
+            .map(|r| (r.id.as_str(), r.start_time, r.end_time))
+            .collect()
+    }
+
+    #[test]
+    fn test_ranges_split_and_zoom_out_at_cuts() {
+        // 0-5s kept, 5-8s cut out, 8-10s and a continuous 10-12s kept
+        let slices = vec![
+            slice(0.0, 5000.0),
+            slice(8000.0, 10_000.0),
+            slice(10_000.0, 12_000.0),
+        ];
+        let ranges = vec![range("a", 3000.0, 9000.0), range("b", 9200.0, 11_000.0)];
+
+        let resolved = resolve_zoom_ranges(&ranges, &slices);
+        assert_eq!(
+            times(&resolved),
+            vec![
+                // Zooms out before the cut, then back in after it
+                ("a", 3000.0, 4600.0),
+                // Shortened so it's zoomed out before "b" starts
+                ("a", 8000.0, 8800.0),
+                // Crosses the speed-change boundary untouched
+                ("b", 9200.0, 11_000.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_range_is_delayed_when_previous_is_too_short() {
+        let ranges = vec![range("a", 1000.0, 2000.0), range("b", 1200.0, 4000.0)];
+        // Shortening "a" to end 400ms before "b" would remove it entirely
+        assert_eq!(
+            times(&resolve_zoom_ranges(&ranges, &[])),
+            vec![("a", 1000.0, 2000.0), ("b", 2400.0, 4000.0)]
+        );
+
+        // Instant ranges don't need room for a transition
+        let mut instant = vec![range("a", 0.0, 1000.0), range("b", 800.0, 2000.0)];
+        instant.iter_mut().for_each(|r| r.instant = true);
+        assert_eq!(
+            times(&resolve_zoom_ranges(&instant, &[])),
+            vec![("a", 0.0, 800.0), ("b", 800.0, 2000.0)]
+        );
+    }
+}
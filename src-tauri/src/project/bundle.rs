@@ -5,9 +5,13 @@
 //! - meta.json: Version and metadata
 //! - project.json: Project configuration and scenes
 //! - markers.json: User-defined markers
+//! - ui-state.json: Editor view state (playhead, timeline zoom, selection, panels)
 //! - recording/: Directory with recorded media and data
+//! - assets/: Files attached to the project, such as color LUTs
+//! - history/: Backup snapshots of project.json
 
-use super::schema::{Marker, Project, ProjectMeta};
+use super::paths;
+use super::schema::{Marker, Project, ProjectMeta, UiState};
 use std::fs;
 use std::path::Path;
 use thiserror::Error;
@@ -94,8 +98,12 @@ pub fn write_project(project: &Project, bundle_path: &Path) -> Result<(), Bundle
         fs::create_dir_all(&recording_path)?;
     }
     
-    // Write project.json
-    let project_content = serde_json::to_string_pretty(project)?;
+    // Write project.json, with file references in their portable form
+    let mut project = project.clone();
+    if let Some(lut) = project.config.color_lut.as_mut() {
+        lut.file = paths::normalize(&lut.file);
+    }
+    let project_content = serde_json::to_string_pretty(&project)?;
     fs::write(bundle_path.join("project.json"), project_content)?;
     
     // Write or update meta.json
@@ -122,6 +130,41 @@ pub fn write_markers(markers: &[Marker], bundle_path: &Path) -> Result<(), Bundl
     Ok(())
 }
 
+/// Read the editor view state from a bundle
+///
+/// Missing or unreadable state falls back to the defaults; it's only a
+/// convenience and shouldn't keep a project from opening.
+pub fn read_ui_state(bundle_path: &Path) -> Result<UiState, BundleError> {
+    let ui_state_path = bundle_path.join("ui-state.json");
+    
+    if !ui_state_path.exists() {
+        return Ok(UiState::default());
+    }
+    
+    let ui_state_content = fs::read_to_string(&ui_state_path)?;
+    match serde_json::from_str(&ui_state_content) {
+        Ok(ui_state) => Ok(ui_state),
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable {:?}: {}", ui_state_path, e);
+            Ok(UiState::default())
+        }
+    }
+}
+
+/// Write the editor view state to a bundle
+pub fn write_ui_state(ui_state: &UiState, bundle_path: &Path) -> Result<(), BundleError> {
+    if !bundle_path.is_dir() {
+        return Err(BundleError::InvalidBundle(
+            "Path is not a directory".to_string(),
+        ));
+    }
+    
+    let ui_state_content = serde_json::to_string_pretty(ui_state)?;
+    fs::write(bundle_path.join("ui-state.json"), ui_state_content)?;
+    
+    Ok(())
+}
+
 /// Check if a path is a valid project bundle
 pub fn is_valid_bundle(path: &Path) -> bool {
     if !path.is_dir() {
@@ -173,4 +216,28 @@ mod tests {
         fs::write(valid_path.join("project.json"), "{}").unwrap();
         assert!(is_valid_bundle(&valid_path));
     }
+    
+    #[test]
+    fn test_ui_state_roundtrip() {
+        let dir = tempdir().unwrap();
+        let bundle_path = dir.path().join("test.osp");
+        write_project(&Project::new("Test Project".to_string()), &bundle_path).unwrap();
+        
+        // No saved state yet
+        assert_eq!(read_ui_state(&bundle_path).unwrap(), UiState::default());
+        
+        let mut ui_state = UiState {
+            playhead_ms: 12_500.0,
+            timeline_zoom: 2.5,
+            selected_slice_id: Some("slice-1".to_string()),
+            ..Default::default()
+        };
+        ui_state.panels.sizes.insert("timeline".to_string(), 240.0);
+        write_ui_state(&ui_state, &bundle_path).unwrap();
+        assert_eq!(read_ui_state(&bundle_path).unwrap(), ui_state);
+        
+        // Corrupt state is ignored rather than failing the open
+        fs::write(bundle_path.join("ui-state.json"), "{").unwrap();
+        assert_eq!(read_ui_state(&bundle_path).unwrap(), UiState::default());
+    }
 }
@@ -0,0 +1,502 @@
+//! Encryption of recording media at rest
+//!
+//! Bundles recorded with `encrypt_media` have their video and audio files,
+//! and their keystrokes, encrypted in place with AES-256-GCM once recording
+//! stops. Each bundle gets
+//! its own key, kept in the OS keychain rather than in the bundle. Encrypted
+//! files keep their names and start with a header naming their key, so they
+//! are recognized wherever they are read: loaders, the preview and export
+//! pass media paths through `readable_path`, which decrypts into a private
+//! temporary cache and returns unencrypted files unchanged.
+//!
+//! File format: header (`MAGIC`, key id, nonce prefix, chunk size) followed
+//! by chunks of `chunk size` plaintext bytes, each sealed separately with a
+//! nonce of the prefix and the chunk index. The last chunk is always shorter
+//! than a full chunk and sealed with different associated data, so truncated
+//! or reordered files fail to decrypt.
+
+use crate::recorder::manifest::FileKind;
+use crate::utils::keychain;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Identifies an encrypted media file
+const MAGIC: &[u8; 8] = b"OSSENC01";
+
+/// Plaintext bytes per sealed chunk
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Largest chunk size accepted when reading a header
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+const TAG_SIZE: usize = 16;
+const NONCE_PREFIX_SIZE: usize = 8;
+
+/// Associated data of every chunk but the last, and of the last one
+const AAD_MORE: &[u8] = b"more";
+const AAD_LAST: &[u8] = b"last";
+
+/// Files encrypted by `encrypt_media_in_place`
+const MEDIA_EXTENSIONS: &[&str] = &["mp4", "mov", "m4a", "wav", "webm", "mkv"];
+
+/// Other files encrypted by `encrypt_media_in_place`, by name suffix.
+/// Keystrokes can hold everything the user typed.
+const SENSITIVE_SUFFIXES: &[&str] = &[FileKind::Keystrokes.suffix()];
+
+/// Keychain accounts of bundle keys are prefixed with this
+const KEYCHAIN_ACCOUNT_PREFIX: &str = "bundle-key-";
+
+/// Encryption-related errors
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Keychain error: {0}")]
+    Keychain(String),
+
+    #[error("Invalid encrypted file: {0}")]
+    InvalidFile(String),
+
+    #[error("File was encrypted with a different key")]
+    WrongKey,
+
+    #[error("Decryption failed: the file is corrupt or incomplete")]
+    Decrypt,
+
+    #[error("Encryption failed")]
+    Encrypt,
+}
+
+/// The key media of one bundle is encrypted with
+pub struct BundleKey {
+    id: String,
+    key: Key<Aes256Gcm>,
+}
+
+impl BundleKey {
+    /// Generate a new random key
+    pub fn generate() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            key: Aes256Gcm::generate_key(OsRng),
+        }
+    }
+
+    pub fn from_bytes(id: impl Into<String>, bytes: &[u8]) -> Result<Self, EncryptionError> {
+        if bytes.len() != 32 {
+            return Err(EncryptionError::Keychain(format!(
+                "Expected a 256-bit key, got {} bytes",
+                bytes.len()
+            )));
+        }
+        Ok(Self {
+            id: id.into(),
+            key: *Key::<Aes256Gcm>::from_slice(bytes),
+        })
+    }
+
+    /// Generate a key for a new bundle and store it in the keychain
+    pub fn create() -> Result<Self, EncryptionError> {
+        let key = Self::generate();
+        keychain::store_secret(&keychain_account(&key.id), key.key.as_slice())
+            .map_err(|e| EncryptionError::Keychain(e.to_string()))?;
+        Ok(key)
+    }
+
+    /// Load a bundle key from the keychain
+    pub fn load(id: &str) -> Result<Self, EncryptionError> {
+        let bytes = keychain::load_secret(&keychain_account(id))
+            .map_err(|e| EncryptionError::Keychain(e.to_string()))?;
+        Self::from_bytes(id, &bytes)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&self.key)
+    }
+}
+
+fn keychain_account(key_id: &str) -> String {
+    format!("{}{}", KEYCHAIN_ACCOUNT_PREFIX, key_id)
+}
+
+struct Header {
+    key_id: String,
+    nonce_prefix: [u8; NONCE_PREFIX_SIZE],
+    chunk_size: usize,
+}
+
+fn write_header(writer: &mut impl Write, header: &Header) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(header.key_id.len() as u16).to_be_bytes())?;
+    writer.write_all(header.key_id.as_bytes())?;
+    writer.write_all(&header.nonce_prefix)?;
+    writer.write_all(&(header.chunk_size as u32).to_be_bytes())
+}
+
+/// Read the header of an encrypted file. Returns None for other files.
+fn read_header(reader: &mut impl Read) -> Result<Option<Header>, EncryptionError> {
+    let mut magic = [0u8; 8];
+    if read_full(reader, &mut magic)? < magic.len() || &magic != MAGIC {
+        return Ok(None);
+    }
+
+    let mut len = [0u8; 2];
+    reader.read_exact(&mut len)?;
+    let mut key_id = vec![0u8; u16::from_be_bytes(len) as usize];
+    reader.read_exact(&mut key_id)?;
+    let key_id = String::from_utf8(key_id)
+        .map_err(|_| EncryptionError::InvalidFile("key id is not UTF-8".to_string()))?;
+
+    let mut nonce_prefix = [0u8; NONCE_PREFIX_SIZE];
+    reader.read_exact(&mut nonce_prefix)?;
+
+    let mut chunk_size = [0u8; 4];
+    reader.read_exact(&mut chunk_size)?;
+    let chunk_size = u32::from_be_bytes(chunk_size) as usize;
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(EncryptionError::InvalidFile(format!(
+            "chunk size {}",
+            chunk_size
+        )));
+    }
+
+    Ok(Some(Header {
+        key_id,
+        nonce_prefix,
+        chunk_size,
+    }))
+}
+
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_SIZE], index: u32) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_SIZE].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_SIZE..].copy_from_slice(&index.to_be_bytes());
+    nonce
+}
+
+/// Fill `buf` as far as the reader allows. Returns the bytes read, which is
+/// less than `buf.len()` only at the end of the stream.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Encrypt `src` into `dst`
+pub fn encrypt_file(key: &BundleKey, src: &Path, dst: &Path) -> Result<(), EncryptionError> {
+    let mut reader = BufReader::new(File::open(src)?);
+    let mut writer = BufWriter::new(create_private_file(dst)?);
+
+    let mut nonce_prefix = [0u8; NONCE_PREFIX_SIZE];
+    OsRng.fill_bytes(&mut nonce_prefix);
+    write_header(
+        &mut writer,
+        &Header {
+            key_id: key.id.clone(),
+            nonce_prefix,
+            chunk_size: CHUNK_SIZE,
+        },
+    )?;
+
+    let cipher = key.cipher();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut index = 0u32;
+    loop {
+        let len = read_full(&mut reader, &mut buf)?;
+        // A file that is a multiple of the chunk size ends with an empty chunk
+        let last = len < CHUNK_SIZE;
+        let payload = Payload {
+            msg: &buf[..len],
+            aad: if last { AAD_LAST } else { AAD_MORE },
+        };
+        let sealed = cipher
+            .encrypt(
+                Nonce::from_slice(&chunk_nonce(&nonce_prefix, index)),
+                payload,
+            )
+            .map_err(|_| EncryptionError::Encrypt)?;
+        writer.write_all(&sealed)?;
+
+        if last {
+            break;
+        }
+        index += 1;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Decrypt `src` into `dst`
+pub fn decrypt_file(key: &BundleKey, src: &Path, dst: &Path) -> Result<(), EncryptionError> {
+    let mut reader = BufReader::new(File::open(src)?);
+    let header = read_header(&mut reader)?
+        .ok_or_else(|| EncryptionError::InvalidFile(src.to_string_lossy().to_string()))?;
+    if header.key_id != key.id {
+        return Err(EncryptionError::WrongKey);
+    }
+
+    let mut writer = BufWriter::new(create_private_file(dst)?);
+    let cipher = key.cipher();
+    let mut buf = vec![0u8; header.chunk_size + TAG_SIZE];
+    let mut index = 0u32;
+    loop {
+        let len = read_full(&mut reader, &mut buf)?;
+        let last = len < buf.len();
+        let payload = Payload {
+            msg: &buf[..len],
+            aad: if last { AAD_LAST } else { AAD_MORE },
+        };
+        let plain = cipher
+            .decrypt(
+                Nonce::from_slice(&chunk_nonce(&header.nonce_prefix, index)),
+                payload,
+            )
+            .map_err(|_| EncryptionError::Decrypt)?;
+        writer.write_all(&plain)?;
+
+        if last {
+            break;
+        }
+        index += 1;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Id of the key a file is encrypted with, or None if it isn't encrypted
+pub fn encrypted_key_id(path: &Path) -> Result<Option<String>, EncryptionError> {
+    let mut file = File::open(path)?;
+    Ok(read_header(&mut file)?.map(|h| h.key_id))
+}
+
+/// Encrypt the media files (and keystrokes) of a recording directory in
+/// place. Files that are already encrypted are skipped. Returns the number
+/// of files encrypted.
+pub fn encrypt_media_in_place(
+    recording_dir: &Path,
+    key: &BundleKey,
+) -> Result<usize, EncryptionError> {
+    let mut count = 0;
+
+    for entry in fs::read_dir(recording_dir)? {
+        let path = entry?.path();
+        let is_media = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| MEDIA_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+            || path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| SENSITIVE_SUFFIXES.iter().any(|suffix| n.ends_with(suffix)));
+        if !is_media || !path.is_file() || encrypted_key_id(&path)?.is_some() {
+            continue;
+        }
+
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".encrypting");
+        let temp_path = path.with_file_name(temp_name);
+
+        if let Err(e) = encrypt_file(key, &path, &temp_path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e);
+        }
+        fs::rename(&temp_path, &path)?;
+        count += 1;
+    }
+
+    tracing::info!("Encrypted {} media files in {:?}", count, recording_dir);
+    Ok(count)
+}
+
+/// Directory decrypted copies of media are written to
+pub fn decrypted_cache_dir() -> PathBuf {
+    std::env::temp_dir().join("open-screenstudio-decrypted")
+}
+
+/// Path of a media file that can be read directly: `path` itself if it isn't
+/// encrypted, otherwise a decrypted copy in the cache
+pub fn readable_path(path: &Path) -> Result<PathBuf, EncryptionError> {
+    readable_path_in(path, &decrypted_cache_dir(), BundleKey::load)
+}
+
+fn readable_path_in(
+    path: &Path,
+    cache_dir: &Path,
+    load_key: impl FnOnce(&str) -> Result<BundleKey, EncryptionError>,
+) -> Result<PathBuf, EncryptionError> {
+    let Some(key_id) = encrypted_key_id(path)? else {
+        return Ok(path.to_path_buf());
+    };
+
+    // Keep the file name (and extension) so FFmpeg and the webview can tell
+    // the format, prefixed with a hash of the full path to avoid collisions
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    let file_name = format!(
+        "{:016x}-{}",
+        hasher.finish(),
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let dir = cache_dir.join(&key_id);
+    let decrypted = dir.join(file_name);
+
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    if let (Some(cached), Some(source)) = (modified(&decrypted), modified(path)) {
+        if cached >= source {
+            return Ok(decrypted);
+        }
+    }
+
+    let key = load_key(&key_id)?;
+    create_private_dir(&dir)?;
+
+    // Decrypt next to the final path so readers never see a partial file
+    let partial = decrypted.with_extension("partial");
+    if let Err(e) = decrypt_file(&key, path, &partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &decrypted)?;
+
+    tracing::debug!("Decrypted {:?} to {:?}", path, decrypted);
+    Ok(decrypted)
+}
+
+/// Delete all decrypted copies, along with the playback mixdowns rendered
+/// from them. Called when the app starts and exits.
+pub fn clear_decrypted_cache() {
+    let dir = decrypted_cache_dir();
+    if dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            tracing::warn!("Failed to clear decrypted media cache: {}", e);
+        }
+    }
+}
+
+/// Create a file only the current user can read
+pub fn create_private_file(path: &Path) -> io::Result<File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Create a directory (and its parents) only the current user can access
+pub fn create_private_dir(path: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_media_round_trips_through_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let recording_dir = dir.path().join("recording");
+        fs::create_dir(&recording_dir).unwrap();
+
+        // Exactly two chunks, so the file ends with an empty final chunk
+        let video: Vec<u8> = (0..CHUNK_SIZE * 2).map(|i| (i % 251) as u8).collect();
+        let video_path = recording_dir.join("recording-0.mp4");
+        let moves_path = recording_dir.join("recording-0-mouse-moves.json");
+        let keys_path = recording_dir.join("recording-0-keystrokes.json");
+        fs::write(&video_path, &video).unwrap();
+        fs::write(&moves_path, b"[]").unwrap();
+        fs::write(&keys_path, b"[]").unwrap();
+
+        let key = BundleKey::generate();
+        // Keystrokes are encrypted along with the media
+        assert_eq!(encrypt_media_in_place(&recording_dir, &key).unwrap(), 2);
+        // Already encrypted files are left alone
+        assert_eq!(encrypt_media_in_place(&recording_dir, &key).unwrap(), 0);
+        assert_eq!(
+            encrypted_key_id(&video_path).unwrap().as_deref(),
+            Some(key.id())
+        );
+        assert_eq!(encrypted_key_id(&moves_path).unwrap(), None);
+        assert!(encrypted_key_id(&keys_path).unwrap().is_some());
+        assert_ne!(fs::read(&video_path).unwrap()[..16], video[..16]);
+
+        let cache = dir.path().join("cache");
+        let load = |id: &str| BundleKey::from_bytes(id, key.key.as_slice());
+        let readable = readable_path_in(&video_path, &cache, load).unwrap();
+        assert!(readable.starts_with(&cache));
+        assert!(readable.to_string_lossy().ends_with("recording-0.mp4"));
+        assert_eq!(fs::read(&readable).unwrap(), video);
+
+        // Plain files are returned as they are
+        assert_eq!(
+            readable_path_in(&moves_path, &cache, load).unwrap(),
+            moves_path
+        );
+    }
+
+    #[test]
+    fn test_tampered_or_truncated_files_fail_to_decrypt() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("audio.m4a");
+        let encrypted = dir.path().join("audio.enc");
+        let out = dir.path().join("audio.out");
+        fs::write(&src, vec![7u8; CHUNK_SIZE + 100]).unwrap();
+
+        let key = BundleKey::generate();
+        encrypt_file(&key, &src, &encrypted).unwrap();
+        let sealed = fs::read(&encrypted).unwrap();
+
+        // Dropping the final chunk
+        fs::write(&encrypted, &sealed[..sealed.len() - 100 - TAG_SIZE]).unwrap();
+        assert!(matches!(
+            decrypt_file(&key, &encrypted, &out),
+            Err(EncryptionError::Decrypt)
+        ));
+
+        // Flipping a byte
+        let mut flipped = sealed.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        fs::write(&encrypted, &flipped).unwrap();
+        assert!(matches!(
+            decrypt_file(&key, &encrypted, &out),
+            Err(EncryptionError::Decrypt)
+        ));
+
+        // Another bundle's key
+        fs::write(&encrypted, &sealed).unwrap();
+        assert!(matches!(
+            decrypt_file(&BundleKey::generate(), &encrypted, &out),
+            Err(EncryptionError::WrongKey)
+        ));
+    }
+}
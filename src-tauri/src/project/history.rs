@@ -0,0 +1,234 @@
+//! Project backup snapshots
+//!
+//! Saves keep a trail of the `project.json` they replace in the bundle's
+//! `history/` folder, so a project can be rolled back to an earlier state
+//! after the in-memory undo stack is gone (the app was closed, or the edits
+//! were undone past). The saved project is snapshotted before a save that
+//! changes the timeline structure (scenes, slices or zoom ranges added or
+//! removed) or when the last snapshot is older than `SNAPSHOT_INTERVAL_MS`,
+//! and only the newest `MAX_SNAPSHOTS` are kept. Snapshots are written
+//! without pretty-printing to stay small.
+
+use super::bundle::BundleError;
+use super::schema::Project;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snapshot directory, relative to the bundle
+pub const HISTORY_DIR: &str = "history";
+
+/// Longest time between snapshots while a project is being edited
+const SNAPSHOT_INTERVAL_MS: i64 = 5 * 60 * 1000;
+
+/// Snapshots kept per bundle
+const MAX_SNAPSHOTS: usize = 50;
+
+/// A snapshot in a bundle's history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// Unix time the snapshot was taken, in milliseconds; identifies it
+    pub timestamp: i64,
+    pub size_bytes: u64,
+}
+
+fn snapshot_path(bundle_path: &Path, timestamp: i64) -> PathBuf {
+    bundle_path
+        .join(HISTORY_DIR)
+        .join(format!("project-{}.json", timestamp))
+}
+
+/// Snapshots in a bundle, newest first
+pub fn list_snapshots(bundle_path: &Path) -> Result<Vec<Snapshot>, BundleError> {
+    let dir = bundle_path.join(HISTORY_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(timestamp) = name
+            .strip_prefix("project-")
+            .and_then(|rest| rest.strip_suffix(".json"))
+            .and_then(|t| t.parse::<i64>().ok())
+        else {
+            continue;
+        };
+        snapshots.push(Snapshot {
+            timestamp,
+            size_bytes: entry.metadata()?.len(),
+        });
+    }
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+    Ok(snapshots)
+}
+
+/// Read the project stored in a snapshot
+pub fn read_snapshot(bundle_path: &Path, timestamp: i64) -> Result<Project, BundleError> {
+    let path = snapshot_path(bundle_path, timestamp);
+    if !path.exists() {
+        return Err(BundleError::MissingFile(format!(
+            "{}/project-{}.json",
+            HISTORY_DIR, timestamp
+        )));
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Write a snapshot of `project` taken at `timestamp` and drop the oldest
+/// beyond `MAX_SNAPSHOTS`
+pub fn write_snapshot(
+    bundle_path: &Path,
+    project: &Project,
+    timestamp: i64,
+) -> Result<Snapshot, BundleError> {
+    let path = snapshot_path(bundle_path, timestamp);
+    fs::create_dir_all(bundle_path.join(HISTORY_DIR))?;
+    let content = serde_json::to_vec(project)?;
+    fs::write(&path, &content)?;
+
+    for old in list_snapshots(bundle_path)?.iter().skip(MAX_SNAPSHOTS) {
+        fs::remove_file(snapshot_path(bundle_path, old.timestamp))?;
+    }
+
+    Ok(Snapshot {
+        timestamp,
+        size_bytes: content.len() as u64,
+    })
+}
+
+/// Whether the timeline's structure differs: scenes, slices or zoom ranges
+/// added or removed. Dragging a handle or tweaking a setting doesn't count.
+/// Older projects keep their screen slices in the legacy `slices` field.
+fn is_significant_change(previous: &Project, current: &Project) -> bool {
+    let shape = |project: &Project| -> Vec<(usize, usize, usize, usize)> {
+        project
+            .scenes
+            .iter()
+            .map(|s| {
+                (
+                    s.slices.len(),
+                    s.screen_slices.len(),
+                    s.camera_slices.len(),
+                    s.zoom_ranges.len(),
+                )
+            })
+            .collect()
+    };
+    shape(previous) != shape(current)
+}
+
+/// Before `incoming` is saved over `saved` (the project on disk), snapshot
+/// `saved` if the save changes the timeline structure or the latest
+/// snapshot is older than the snapshot interval. Restoring the snapshot then
+/// undoes the save. Returns the new snapshot, if one was taken.
+pub fn snapshot_if_due(
+    bundle_path: &Path,
+    saved: &Project,
+    incoming: &Project,
+    now_ms: i64,
+) -> Result<Option<Snapshot>, BundleError> {
+    // Saves without changes never need a snapshot
+    let saved_value = serde_json::to_value(saved)?;
+    if saved_value == serde_json::to_value(incoming)? {
+        return Ok(None);
+    }
+
+    let due = match list_snapshots(bundle_path)?.into_iter().next() {
+        Some(latest) => {
+            match read_snapshot(bundle_path, latest.timestamp) {
+                // Already kept
+                Ok(previous) if serde_json::to_value(&previous)? == saved_value => {
+                    return Ok(None)
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Unreadable snapshot {}: {}", latest.timestamp, e),
+            }
+            now_ms - latest.timestamp >= SNAPSHOT_INTERVAL_MS
+        }
+        None => true,
+    };
+    if due || is_significant_change(saved, incoming) {
+        write_snapshot(bundle_path, saved, now_ms).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::schema::Scene;
+
+    #[test]
+    fn test_snapshots_taken_on_interval_or_significant_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        let original = Project::new("Demo".to_string());
+
+        // Unchanged: nothing to snapshot
+        assert!(snapshot_if_due(bundle, &original, &original, 1_000)
+            .unwrap()
+            .is_none());
+
+        // The first change keeps the state it replaces
+        let mut renamed = original.clone();
+        renamed.name = "Renamed".to_string();
+        assert!(snapshot_if_due(bundle, &original, &renamed, 1_000)
+            .unwrap()
+            .is_some());
+        assert_eq!(read_snapshot(bundle, 1_000).unwrap().name, "Demo");
+
+        // A minor edit waits for the interval
+        let mut edited = renamed.clone();
+        edited.name = "Edited".to_string();
+        assert!(snapshot_if_due(bundle, &renamed, &edited, 2_000)
+            .unwrap()
+            .is_none());
+        let snapshot = snapshot_if_due(bundle, &renamed, &edited, 1_000 + SNAPSHOT_INTERVAL_MS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(read_snapshot(bundle, snapshot.timestamp).unwrap().name, "Renamed");
+
+        assert_eq!(
+            list_snapshots(bundle)
+                .unwrap()
+                .iter()
+                .map(|s| s.timestamp)
+                .collect::<Vec<_>>(),
+            vec![snapshot.timestamp, 1_000]
+        );
+        assert!(read_snapshot(bundle, 42).is_err());
+    }
+
+    #[test]
+    fn test_legacy_slices_are_significant() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        let scene = |slices: usize| -> Scene {
+            let slice = serde_json::json!({
+                "id": "s", "sourceStartMs": 0.0, "sourceEndMs": 1000.0, "timeScale": 1.0,
+                "volume": 1.0, "hideCursor": false, "disableCursorSmoothing": false,
+            });
+            serde_json::from_value(serde_json::json!({
+                "id": "scene", "name": "Scene", "type": "recording", "sessionIndex": 0,
+                "slices": vec![slice; slices], "screenSlices": [], "cameraSlices": [],
+                "zoomRanges": [], "layouts": [],
+            }))
+            .unwrap()
+        };
+        let mut saved = Project::new("Demo".to_string());
+        saved.scenes.push(scene(1));
+        let mut incoming = saved.clone();
+        snapshot_if_due(bundle, &Project::new("Old".to_string()), &saved, 1_000).unwrap();
+
+        // Splitting a legacy scene snapshots right away
+        incoming.scenes[0] = scene(2);
+        assert!(snapshot_if_due(bundle, &saved, &incoming, 2_000)
+            .unwrap()
+            .is_some());
+    }
+}
@@ -0,0 +1,150 @@
+//! Color lookup tables
+//!
+//! A project can carry a 3D LUT in Adobe/Resolve `.cube` format so every
+//! export gets the same color treatment. The file is copied into the
+//! bundle's `assets/luts` directory when attached, so the bundle stays
+//! self-contained, and export applies it to the screen and/or camera track
+//! with FFmpeg's `lut3d` filter.
+
+use super::bundle::BundleError;
+use super::naming;
+use super::paths;
+use super::schema::ColorLut;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where attached LUTs are stored, relative to the bundle
+pub const LUT_DIR: &str = "assets/luts";
+
+/// Largest cube FFmpeg's `lut3d` accepts
+const MAX_CUBE_SIZE: usize = 256;
+
+/// Check that `content` is a 3D `.cube` LUT FFmpeg can apply, returning
+/// its size (points per axis)
+pub fn validate_cube(content: &str) -> Result<usize, BundleError> {
+    let invalid =
+        |reason: String| BundleError::InvalidBundle(format!("Invalid .cube LUT: {}", reason));
+
+    let mut size = None;
+    let mut entries = 0usize;
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let keyword = fields.next().unwrap_or_default();
+        match keyword {
+            "LUT_3D_SIZE" => {
+                let n = fields
+                    .next()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .filter(|n| (2..=MAX_CUBE_SIZE).contains(n))
+                    .ok_or_else(|| invalid(format!("unsupported size '{}'", line)))?;
+                size = Some(n);
+            }
+            "LUT_1D_SIZE" => return Err(invalid("1D LUTs are not supported".to_string())),
+            "TITLE" | "DOMAIN_MIN" | "DOMAIN_MAX" | "LUT_3D_INPUT_RANGE" => {}
+            _ => {
+                let values: Vec<&str> = line.split_whitespace().collect();
+                if values.len() != 3 || values.iter().any(|v| v.parse::<f32>().is_err()) {
+                    return Err(invalid(format!("unexpected line '{}'", line)));
+                }
+                entries += 1;
+            }
+        }
+    }
+
+    let size = size.ok_or_else(|| invalid("missing LUT_3D_SIZE".to_string()))?;
+    if entries != size.pow(3) {
+        return Err(invalid(format!(
+            "expected {} entries for size {}, found {}",
+            size.pow(3),
+            size,
+            entries
+        )));
+    }
+    Ok(size)
+}
+
+/// Copy a `.cube` file into the bundle, returning its path relative to the
+/// bundle for `ColorLut::file`
+pub fn attach_lut(bundle_path: &Path, source: &Path) -> Result<String, BundleError> {
+    let content = fs::read_to_string(source)?;
+    validate_cube(&content)?;
+
+    let dir = bundle_path.join(LUT_DIR);
+    fs::create_dir_all(&dir)?;
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "lut".to_string());
+    let dest = naming::unique_path(&dir, &stem, "cube");
+    fs::copy(source, &dest)?;
+
+    let file_name = dest.file_name().unwrap_or_default().to_string_lossy();
+    Ok(format!("{}/{}", LUT_DIR, file_name))
+}
+
+/// Absolute path of a project's LUT, if the file exists inside the bundle
+pub fn resolve_lut(bundle_path: &Path, lut: &ColorLut) -> Option<PathBuf> {
+    // Only files inside the bundle, so a project can't point export at
+    // arbitrary paths
+    if paths::normalize(&lut.file).split('/').any(|part| part == "..") {
+        tracing::warn!("Ignoring LUT outside the bundle: {}", lut.file);
+        return None;
+    }
+    let path = paths::resolve(bundle_path, &lut.file).filter(|path| path.is_file());
+    if path.is_none() {
+        tracing::warn!("LUT not found in bundle: {}", lut.file);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(size: usize) -> String {
+        let mut content = format!("TITLE \"Brand\"\nLUT_3D_SIZE {}\n", size);
+        for i in 0..size.pow(3) {
+            let v = i as f32 / size.pow(3) as f32;
+            content.push_str(&format!("{:.4} {:.4} {:.4}\n", v, v, v));
+        }
+        content
+    }
+
+    #[test]
+    fn test_validate_cube() {
+        assert_eq!(validate_cube(&cube(2)).unwrap(), 2);
+        assert!(validate_cube(&cube(2).replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 3")).is_err());
+        assert!(validate_cube("LUT_1D_SIZE 4\n0 0 0\n").is_err());
+        assert!(validate_cube("0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn test_attach_and_resolve_lut() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("Brand Look.cube");
+        fs::write(&source, cube(2)).unwrap();
+        let bundle = dir.path().join("Demo.osp");
+        fs::create_dir(&bundle).unwrap();
+
+        let file = attach_lut(&bundle, &source).unwrap();
+        assert_eq!(file, "assets/luts/Brand Look.cube");
+        let lut = ColorLut {
+            file,
+            apply_to_screen: true,
+            apply_to_camera: false,
+        };
+        assert_eq!(
+            resolve_lut(&bundle, &lut),
+            Some(bundle.join("assets/luts/Brand Look.cube"))
+        );
+
+        let escaping = ColorLut {
+            file: "../Brand Look.cube".to_string(),
+            ..lut
+        };
+        assert_eq!(resolve_lut(&bundle, &escaping), None);
+    }
+}
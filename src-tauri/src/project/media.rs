@@ -0,0 +1,244 @@
+//! External media import
+//!
+//! Replaces a recorded webcam or microphone track with an externally provided
+//! file (e.g. a re-recorded voiceover). The file is copied into the bundle's
+//! `recording/` directory and registered in the recording manifest, so every
+//! loader that resolves files through the manifest picks it up.
+
+use super::bundle::BundleError;
+use super::schema::Slice;
+use crate::processing::audio_sync::AudioSync;
+use crate::recorder::channel::ChannelType;
+use crate::recorder::manifest::{FileKind, ManifestFile, RecordingManifest, MANIFEST_VERSION};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// Recorded track that can be swapped for an external file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplaceableTrack {
+    Webcam,
+    Microphone,
+}
+
+impl ReplaceableTrack {
+    pub fn file_kind(&self) -> FileKind {
+        match self {
+            ReplaceableTrack::Webcam => FileKind::WebcamVideo,
+            ReplaceableTrack::Microphone => FileKind::MicAudio,
+        }
+    }
+
+    pub fn channel_type(&self) -> ChannelType {
+        match self {
+            ReplaceableTrack::Webcam => ChannelType::Webcam,
+            ReplaceableTrack::Microphone => ChannelType::Microphone,
+        }
+    }
+
+    fn file_label(&self) -> &'static str {
+        match self {
+            ReplaceableTrack::Webcam => "webcam",
+            ReplaceableTrack::Microphone => "mic",
+        }
+    }
+}
+
+/// Stream information for an imported file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaInfo {
+    pub duration_ms: f64,
+    pub has_video: bool,
+    pub has_audio: bool,
+}
+
+/// Result of importing an external track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedMedia {
+    pub track: ReplaceableTrack,
+    /// Absolute path of the copy inside the bundle
+    pub path: String,
+    pub info: MediaInfo,
+    /// Automatic alignment against the scratch microphone track, for
+    /// imported microphone audio
+    #[serde(default)]
+    pub sync: Option<AudioSync>,
+}
+
+/// Copy an external file into the bundle and register it as the given track
+pub fn import_track(
+    bundle_path: &Path,
+    track: ReplaceableTrack,
+    source: &Path,
+    session: usize,
+) -> Result<ImportedMedia, BundleError> {
+    if !source.is_file() {
+        return Err(BundleError::MissingFile(source.to_string_lossy().to_string()));
+    }
+
+    let info = probe_media(source)?;
+    match track {
+        ReplaceableTrack::Webcam if !info.has_video => {
+            return Err(BundleError::InvalidBundle(
+                "Replacement webcam file has no video stream".to_string(),
+            ));
+        }
+        ReplaceableTrack::Microphone if !info.has_audio => {
+            return Err(BundleError::InvalidBundle(
+                "Replacement microphone file has no audio stream".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    let recording_dir = bundle_path.join("recording");
+    std::fs::create_dir_all(&recording_dir)?;
+
+    let extension = source
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| "bin".to_string());
+    let file_name = format!(
+        "imported-{}-{}-{}.{}",
+        session,
+        track.file_label(),
+        Utc::now().timestamp_millis(),
+        extension
+    );
+    let dest = recording_dir.join(&file_name);
+    std::fs::copy(source, &dest)?;
+
+    let mut manifest = RecordingManifest::read(&recording_dir).unwrap_or_else(|| RecordingManifest {
+        version: MANIFEST_VERSION,
+        created_at: Utc::now(),
+        total_duration_ms: 0.0,
+        sessions: Vec::new(),
+        channels: Vec::new(),
+        microphone_offset_ms: None,
+        display_name: None,
+        frontmost_app: None,
+    });
+    manifest.replace_file(
+        track.channel_type(),
+        ManifestFile {
+            path: file_name,
+            session,
+            kind: Some(track.file_kind()),
+        },
+    );
+    manifest.write(&recording_dir)?;
+
+    tracing::info!(
+        "Imported {:?} track from {:?} ({:.0}ms)",
+        track,
+        source,
+        info.duration_ms
+    );
+
+    Ok(ImportedMedia {
+        track,
+        path: dest.to_string_lossy().to_string(),
+        info,
+        sync: None,
+    })
+}
+
+/// Probe an audio/video file with ffprobe
+pub fn probe_media(path: &Path) -> Result<MediaInfo, BundleError> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_streams", "-show_format"])
+        .arg(path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(BundleError::InvalidBundle(format!(
+            "ffprobe failed for {:?}",
+            path
+        )));
+    }
+
+    parse_probe_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse ffprobe JSON output into `MediaInfo`
+fn parse_probe_output(json: &str) -> Result<MediaInfo, BundleError> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+
+    let codec_types: Vec<&str> = value
+        .get("streams")
+        .and_then(|s| s.as_array())
+        .map(|streams| {
+            streams
+                .iter()
+                .filter_map(|s| s.get("codec_type").and_then(|t| t.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let duration_ms = value
+        .get("format")
+        .and_then(|f| f.get("duration"))
+        .and_then(|d| d.as_str())
+        .and_then(|d| d.parse::<f64>().ok())
+        .map(|secs| secs * 1000.0)
+        .ok_or_else(|| BundleError::InvalidBundle("Could not determine media duration".to_string()))?;
+
+    Ok(MediaInfo {
+        duration_ms,
+        has_video: codec_types.contains(&"video"),
+        has_audio: codec_types.contains(&"audio"),
+    })
+}
+
+/// Clamp slices to a new source duration, dropping slices that start past it
+pub fn fit_slices_to_duration(slices: &mut Vec<Slice>, duration_ms: f64) {
+    slices.retain(|s| s.source_start_ms < duration_ms);
+    for slice in slices.iter_mut() {
+        slice.source_end_ms = slice.source_end_ms.min(duration_ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slice(start: f64, end: f64) -> Slice {
+        Slice {
+            id: format!("{}-{}", start, end),
+            source_start_ms: start,
+            source_end_ms: end,
+            time_scale: 1.0,
+            volume: 1.0,
+            hide_cursor: false,
+            disable_cursor_smoothing: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_probe_output() {
+        let json = r#"{
+            "streams": [{"codec_type": "audio"}],
+            "format": {"duration": "12.500000"}
+        }"#;
+        let info = parse_probe_output(json).unwrap();
+        assert_eq!(info.duration_ms, 12500.0);
+        assert!(info.has_audio);
+        assert!(!info.has_video);
+
+        assert!(parse_probe_output(r#"{"streams": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_fit_slices_to_shorter_media() {
+        let mut slices = vec![slice(0.0, 4000.0), slice(4000.0, 9000.0), slice(9500.0, 12000.0)];
+        fit_slices_to_duration(&mut slices, 8000.0);
+
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[0].source_end_ms, 4000.0);
+        assert_eq!(slices[1].source_end_ms, 8000.0);
+    }
+}
@@ -3,4 +3,12 @@
 //! This module handles project file format, reading, writing, and migration.
 
 pub mod bundle;
+pub mod encryption;
+pub mod history;
+pub mod lut;
+pub mod media;
+pub mod naming;
+pub mod paths;
+pub mod presentation;
 pub mod schema;
+pub mod template;
@@ -0,0 +1,303 @@
+//! Project naming templates
+//!
+//! New projects are named from a user-configurable template such as
+//! `"{app} {date} {time}"`. Supported tokens:
+//!
+//! - `{date}` / `{time}`: local recording start, as `2024-05-01` / `14-03-22`
+//! - `{date:FORMAT}` / `{time:FORMAT}`: the same with a strftime format, so
+//!   dates can be written the way the user's locale expects
+//! - `{display}`: name of the recorded display
+//! - `{app}`: application of the frontmost window when recording started
+//!
+//! Tokens without a value resolve to nothing. Unknown tokens and unbalanced
+//! braces are rejected when the template is saved; a saved template that
+//! doesn't parse falls back to the default. The result is made safe to use
+//! as a file name.
+
+use super::bundle::BundleError;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Template used until the user configures one
+pub const DEFAULT_NAME_TEMPLATE: &str = "Recording {date} {time}";
+
+/// Longest generated name, leaving room for the extension and a suffix
+const MAX_NAME_LEN: usize = 120;
+
+/// Persisted naming settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingSettings {
+    #[serde(default = "default_template")]
+    pub template: String,
+}
+
+fn default_template() -> String {
+    DEFAULT_NAME_TEMPLATE.to_string()
+}
+
+impl Default for NamingSettings {
+    fn default() -> Self {
+        Self {
+            template: default_template(),
+        }
+    }
+}
+
+/// Values the template's tokens resolve to
+#[derive(Debug, Clone)]
+pub struct NamingContext {
+    pub recorded_at: DateTime<Local>,
+    pub display: Option<String>,
+    pub app: Option<String>,
+}
+
+/// Path of the naming settings file
+pub fn settings_path() -> Result<PathBuf, BundleError> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        BundleError::InvalidBundle("Could not determine data directory".to_string())
+    })?;
+    Ok(data_dir
+        .join("Open ScreenStudio")
+        .join("naming-settings.json"))
+}
+
+/// Load naming settings, returning defaults if none were saved or the saved
+/// template isn't valid (e.g. edited by hand)
+pub fn load_settings(path: &Path) -> Result<NamingSettings, BundleError> {
+    if !path.exists() {
+        return Ok(NamingSettings::default());
+    }
+    let content = std::fs::read_to_string(path)?;
+    let settings: NamingSettings = serde_json::from_str(&content)?;
+    if let Err(e) = validate_template(&settings.template) {
+        tracing::warn!("Using the default naming template: {}", e);
+        return Ok(NamingSettings::default());
+    }
+    Ok(settings)
+}
+
+/// Save naming settings
+pub fn save_settings(path: &Path, settings: &NamingSettings) -> Result<(), BundleError> {
+    validate_template(&settings.template)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(settings)?)?;
+    Ok(())
+}
+
+/// Reject templates that can't produce a name
+pub fn validate_template(template: &str) -> Result<(), BundleError> {
+    if template.trim().is_empty() {
+        return Err(BundleError::InvalidBundle(
+            "Naming template is empty".to_string(),
+        ));
+    }
+    parse_template(template).map_err(|reason| {
+        BundleError::InvalidBundle(format!("Invalid naming template {:?}: {}", template, reason))
+    })?;
+    Ok(())
+}
+
+/// A piece of a parsed template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part<'a> {
+    Text(&'a str),
+    /// A known token, with its format if one was given
+    Token(&'a str, Option<&'a str>),
+}
+
+/// Split a template into text and tokens, or say why it can't be
+fn parse_template(template: &str) -> Result<Vec<Part<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+
+    while let Some(brace) = rest.find(['{', '}']) {
+        if rest[brace..].starts_with('}') {
+            return Err("'}' without a matching '{'".to_string());
+        }
+        if brace > 0 {
+            parts.push(Part::Text(&rest[..brace]));
+        }
+        let after = &rest[brace + 1..];
+        let close = match after.find(['{', '}']) {
+            Some(close) if after[close..].starts_with('}') => close,
+            _ => return Err("'{' without a matching '}'".to_string()),
+        };
+        let token = &after[..close];
+        let (key, format) = match token.split_once(':') {
+            Some((key, format)) => (key, Some(format)),
+            None => (token, None),
+        };
+        match (key, format) {
+            ("date" | "time", _) | ("display" | "app", None) => {
+                parts.push(Part::Token(key, format))
+            }
+            _ => return Err(format!("unknown token {{{}}}", token)),
+        }
+        rest = &after[close + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+/// Resolve a template into a file-system safe project name (no extension)
+pub fn resolve_name(template: &str, context: &NamingContext) -> String {
+    let parts = match parse_template(template) {
+        Ok(parts) => parts,
+        Err(reason) => {
+            tracing::warn!(
+                "Invalid naming template {:?}, using the default: {}",
+                template,
+                reason
+            );
+            return resolve_name(DEFAULT_NAME_TEMPLATE, context);
+        }
+    };
+
+    let mut name = String::new();
+    for part in parts {
+        match part {
+            Part::Text(text) => name.push_str(text),
+            Part::Token(key, format) => name.push_str(&resolve_token(key, format, context)),
+        }
+    }
+
+    let name = sanitize(&name);
+    if name.is_empty() {
+        resolve_name(DEFAULT_NAME_TEMPLATE, context)
+    } else {
+        name
+    }
+}
+
+/// Value of a single token (empty if it has none)
+fn resolve_token(key: &str, format: Option<&str>, context: &NamingContext) -> String {
+    match key {
+        "date" => format_time(&context.recorded_at, format.unwrap_or("%Y-%m-%d")),
+        "time" => format_time(&context.recorded_at, format.unwrap_or("%H-%M-%S")),
+        "display" => context.display.clone().unwrap_or_default(),
+        "app" => context.app.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Format a time, falling back to nothing for an invalid format string
+fn format_time(time: &DateTime<Local>, format: &str) -> String {
+    let mut out = String::new();
+    if write!(out, "{}", time.format(format)).is_err() {
+        tracing::warn!("Invalid date format in naming template: {}", format);
+        out.clear();
+    }
+    out
+}
+
+/// Replace characters that aren't allowed in file names, collapse the
+/// whitespace left by empty tokens and trim to a sane length
+fn sanitize(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let collapsed = replaced.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed: String = collapsed.chars().take(MAX_NAME_LEN).collect();
+    // Leading dots hide files; trailing dots and spaces are dropped on Windows
+    trimmed
+        .trim_start_matches('.')
+        .trim_end_matches(['.', ' '])
+        .to_string()
+}
+
+/// First path in `dir` named `<name>.<extension>`, `<name> 2.<extension>`,
+/// ... that doesn't exist yet
+pub fn unique_path(dir: &Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.{}", name, extension));
+    let mut counter = 2;
+    while path.exists() {
+        path = dir.join(format!("{} {}.{}", name, counter, extension));
+        counter += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn context(app: Option<&str>) -> NamingContext {
+        NamingContext {
+            recorded_at: Local.with_ymd_and_hms(2024, 5, 1, 14, 3, 22).unwrap(),
+            display: Some("Built-in Retina Display".to_string()),
+            app: app.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_resolve_name_tokens() {
+        assert_eq!(
+            resolve_name(DEFAULT_NAME_TEMPLATE, &context(None)),
+            "Recording 2024-05-01 14-03-22"
+        );
+        assert_eq!(
+            resolve_name("Aufnahme {date:%d.%m.%Y} {time:%H.%M}", &context(None)),
+            "Aufnahme 01.05.2024 14.03"
+        );
+        assert_eq!(
+            resolve_name("{app} - {display}", &context(Some("Safari"))),
+            "Safari - Built-in Retina Display"
+        );
+        // Missing values don't leave stray whitespace
+        assert_eq!(resolve_name("{app} {date}", &context(None)), "2024-05-01");
+        // Characters that aren't valid in file names are replaced
+        assert_eq!(
+            resolve_name("{app} {time:%H:%M}", &context(Some("A/B"))),
+            "A-B 14-03"
+        );
+        // Nothing left falls back to the default template
+        assert_eq!(
+            resolve_name("{app}", &context(None)),
+            "Recording 2024-05-01 14-03-22"
+        );
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        let invalid = ["{app", "Demo {date", "}{", "{app}}", "{{app}}", "{unknown}", "{app:%Y}"];
+        for template in invalid {
+            assert!(validate_template(template).is_err(), "{}", template);
+            // Falls back to the default rather than keeping stray braces
+            assert_eq!(
+                resolve_name(template, &context(Some("Safari"))),
+                "Recording 2024-05-01 14-03-22"
+            );
+        }
+        assert!(validate_template("{app} {date:%d.%m.%Y}").is_ok());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("naming-settings.json");
+        std::fs::write(&path, r#"{"template": "Demo {date"}"#).unwrap();
+        assert_eq!(load_settings(&path).unwrap().template, DEFAULT_NAME_TEMPLATE);
+    }
+
+    #[test]
+    fn test_unique_path_appends_counter() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = unique_path(dir.path(), "Demo", "osp");
+        assert_eq!(first, dir.path().join("Demo.osp"));
+        std::fs::create_dir(&first).unwrap();
+        assert_eq!(
+            unique_path(dir.path(), "Demo", "osp"),
+            dir.path().join("Demo 2.osp")
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Portable references between the files of a bundle
+//!
+//! Files in a bundle point at each other: the recording manifest at the
+//! recorded media, `cursors.json` at the cursor images, the project at its
+//! LUT. Bundles move between machines and operating systems, so these
+//! references are saved relative to the directory they're resolved against,
+//! with `/` separators, and resolved leniently when loaded:
+//! - `\` separators (written on Windows) work everywhere
+//! - absolute paths baked in by older versions, possibly from another OS or
+//!   a temporary directory, are matched by their trailing components
+//! - a name that doesn't match exactly falls back to a case-insensitive
+//!   match, for bundles made on a case-insensitive file system
+//!
+//! A reference never resolves outside the directory it's relative to.
+
+use std::path::{Path, PathBuf};
+
+/// Normalize a saved reference: `/` separators, without `.` components
+pub fn normalize(reference: &str) -> String {
+    let unified = reference.replace('\\', "/");
+    let absolute = unified.starts_with('/');
+    let parts: Vec<&str> = unified
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    let joined = parts.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+/// Reference to `path` to save in a file that is resolved against `base`:
+/// relative to `base` if it's inside it, and normalized either way
+pub fn to_relative(base: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    normalize(&relative.to_string_lossy())
+}
+
+/// Existing file or directory a saved reference points at inside `base`
+pub fn resolve(base: &Path, reference: &str) -> Option<PathBuf> {
+    let normalized = normalize(reference);
+    let mut parts: Vec<&str> = normalized.split('/').filter(|p| !p.is_empty()).collect();
+    if parts.is_empty() || parts.contains(&"..") {
+        return None;
+    }
+
+    if !is_absolute(&normalized) {
+        return find(base, &parts);
+    }
+    // Drop a Windows drive; then the longest tail that exists in `base`
+    if parts[0].ends_with(':') {
+        parts.remove(0);
+    }
+    (0..parts.len()).find_map(|start| find(base, &parts[start..]))
+}
+
+/// `path` made absolute with symlinks and `.`/`..` resolved, so two
+/// spellings of one bundle compare equal. A path that doesn't exist yet
+/// (e.g. a Save As destination) is resolved through its parent.
+pub fn canonical(path: &Path) -> PathBuf {
+    if let Ok(resolved) = std::fs::canonicalize(path) {
+        return resolved;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            canonical(parent).join(name)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Absolute on any OS: `/…`, `//server/share/…` or `C:/…`
+fn is_absolute(normalized: &str) -> bool {
+    let bytes = normalized.as_bytes();
+    normalized.starts_with('/')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+/// Follow `parts` down from `base`, matching names case-insensitively when
+/// there's no exact match
+fn find(base: &Path, parts: &[&str]) -> Option<PathBuf> {
+    let mut path = base.to_path_buf();
+    for part in parts {
+        let exact = path.join(part);
+        path = if exact.exists() {
+            exact
+        } else {
+            std::fs::read_dir(&path)
+                .ok()?
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .find(|candidate| {
+                    candidate
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case(part))
+                })?
+        };
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_references() {
+        assert_eq!(normalize("cursors\\abc.png"), "cursors/abc.png");
+        assert_eq!(normalize("./recording-0.mp4"), "recording-0.mp4");
+        assert_eq!(normalize("/tmp//x/./y.png"), "/tmp/x/y.png");
+        assert_eq!(
+            to_relative(
+                Path::new("/b/Demo.osp/recording"),
+                Path::new("/b/Demo.osp/recording/recording-0.mp4")
+            ),
+            "recording-0.mp4"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let cursors = dir.path().join("recording-0-cursors");
+        std::fs::create_dir(&cursors).unwrap();
+        std::fs::write(cursors.join("Arrow.png"), b"png").unwrap();
+        let image = Some(cursors.join("Arrow.png"));
+
+        assert_eq!(resolve(dir.path(), "recording-0-cursors/Arrow.png"), image);
+        assert_eq!(resolve(dir.path(), "recording-0-cursors\\Arrow.png"), image);
+        assert_eq!(resolve(dir.path(), "RECORDING-0-CURSORS/arrow.png"), image);
+        // Absolute paths from another machine
+        assert_eq!(
+            resolve(
+                &cursors,
+                "/Users/me/Movies/Demo.osp/recording/recording-0-cursors/Arrow.png"
+            ),
+            image
+        );
+        assert_eq!(
+            resolve(
+                dir.path(),
+                "C:\\Users\\me\\AppData\\Local\\Temp\\x\\recording-0-cursors\\Arrow.png"
+            ),
+            image
+        );
+
+        assert_eq!(resolve(dir.path(), "missing.png"), None);
+        assert_eq!(resolve(&cursors, "../recording-0-cursors/Arrow.png"), None);
+        assert_eq!(resolve(dir.path(), ""), None);
+    }
+
+    #[test]
+    fn test_canonical_paths_compare_equal() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("Demo.osp");
+        std::fs::create_dir_all(bundle.join("recording")).unwrap();
+
+        let roundabout = bundle.join("recording").join("..").join(".");
+        assert_eq!(canonical(&roundabout), canonical(&bundle));
+        // Not created yet: resolved through the parent
+        assert_eq!(
+            canonical(&bundle.join("recording").join("..").join("Copy.osp")),
+            canonical(&bundle).join("Copy.osp")
+        );
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("Link.osp");
+            std::os::unix::fs::symlink(&bundle, &link).unwrap();
+            assert_eq!(canonical(&link), canonical(&bundle));
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! How project bundles look in Finder and Explorer
+//!
+//! A `.osp` bundle is a directory. The macOS app declares it as a document
+//! package (see `Info.plist`), so Finder shows it as a single document; the
+//! files this module maintains inside the bundle make it look like the
+//! recording it holds:
+//!
+//! - `QuickLook/Thumbnail.png`: a frame of the recording, which Quick Look
+//!   and Finder's icon previews use for packages
+//! - macOS: the same frame set as the bundle's custom icon
+//! - Windows: a hidden `desktop.ini` giving the folder the app's icon and a
+//!   tooltip naming the project
+//!
+//! `refresh_bundle_presentation` runs after saves. The thumbnail is only
+//! regenerated when it's missing or older than the recording, so calling it
+//! on every save is cheap. Encrypted recordings never get one: it would
+//! leave a readable frame next to the encrypted media.
+
+use super::bundle::BundleError;
+use super::encryption::encrypted_key_id;
+use super::media::probe_media;
+use crate::recorder::manifest::{resolve_primary_video, RecordingManifest};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Uniform type identifier the app registers for bundles
+pub const BUNDLE_TYPE_IDENTIFIER: &str = "com.crafterstation.open-screenstudio.project";
+
+/// Thumbnail location, relative to the bundle
+pub const THUMBNAIL_PATH: &str = "QuickLook/Thumbnail.png";
+
+/// Widest thumbnail generated
+const THUMBNAIL_MAX_WIDTH: u32 = 512;
+
+/// Time of the frame used, unless the recording is shorter than twice this
+const THUMBNAIL_TIME_MS: f64 = 1000.0;
+
+/// Bring a bundle's thumbnail and icon up to date with its recording
+pub fn refresh_bundle_presentation(
+    bundle_path: &Path,
+    project_name: &str,
+) -> Result<(), BundleError> {
+    if !bundle_path.is_dir() {
+        return Err(BundleError::InvalidBundle(
+            "Path is not a directory".to_string(),
+        ));
+    }
+
+    #[cfg(target_os = "windows")]
+    windows_folder::write_desktop_ini(bundle_path, project_name)?;
+    #[cfg(not(target_os = "windows"))]
+    let _ = project_name;
+
+    let recording_dir = bundle_path.join("recording");
+    let manifest = RecordingManifest::read(&recording_dir);
+    let Some(video) = resolve_primary_video(&recording_dir, manifest.as_ref(), 0) else {
+        return Ok(());
+    };
+
+    let thumbnail = bundle_path.join(THUMBNAIL_PATH);
+    if encrypted_key_id(&video)
+        .map_err(|e| BundleError::InvalidBundle(e.to_string()))?
+        .is_some()
+    {
+        if thumbnail.exists() {
+            tracing::info!("Removing thumbnail of encrypted bundle {:?}", bundle_path);
+            fs::remove_file(&thumbnail)?;
+        }
+        return Ok(());
+    }
+
+    if is_stale(&thumbnail, &video) {
+        generate_thumbnail(&video, &thumbnail)?;
+        #[cfg(target_os = "macos")]
+        set_custom_icon(bundle_path, &thumbnail);
+        tracing::debug!("Updated thumbnail of {:?}", bundle_path);
+    }
+    Ok(())
+}
+
+/// Whether `derived` is missing or older than the file it was made from
+fn is_stale(derived: &Path, source: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(derived), modified(source)) {
+        (Some(derived), Some(source)) => derived < source,
+        (Some(_), None) => false,
+        (None, _) => true,
+    }
+}
+
+/// Extract a frame near the start of `video` as a PNG
+fn generate_thumbnail(video: &Path, output: &Path) -> Result<(), BundleError> {
+    let duration_ms = probe_media(video)?.duration_ms;
+    let time_ms = THUMBNAIL_TIME_MS.min(duration_ms / 2.0);
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Written next to the thumbnail and renamed, so a failed extraction
+    // never leaves a broken image behind
+    let partial = partial_path(output);
+    let result = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-v",
+            "error",
+            "-ss",
+            &format!("{:.3}", time_ms / 1000.0),
+            "-i",
+        ])
+        .arg(video)
+        .args([
+            "-frames:v",
+            "1",
+            "-vf",
+            &format!("scale='min({},iw)':-2", THUMBNAIL_MAX_WIDTH),
+            "-f",
+            "image2",
+            "-c:v",
+            "png",
+        ])
+        .arg(&partial)
+        .stdin(Stdio::null())
+        .output()?;
+    if !result.status.success() || !partial.exists() {
+        let _ = fs::remove_file(&partial);
+        return Err(BundleError::InvalidBundle(format!(
+            "Thumbnail extraction failed: {}",
+            String::from_utf8_lossy(&result.stderr)
+        )));
+    }
+    fs::rename(&partial, output)?;
+    Ok(())
+}
+
+fn partial_path(output: &Path) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    output.with_file_name(name)
+}
+
+/// Use the thumbnail as the bundle's Finder icon
+#[cfg(target_os = "macos")]
+fn set_custom_icon(bundle_path: &Path, image_path: &Path) {
+    use objc2::ClassType;
+    use objc2_app_kit::{NSImage, NSWorkspace, NSWorkspaceIconCreationOptions};
+    use objc2_foundation::NSString;
+
+    let image_path = NSString::from_str(&image_path.to_string_lossy());
+    let bundle = NSString::from_str(&bundle_path.to_string_lossy());
+    // NSImage/NSWorkspace calls require unsafe in objc2 v0.2
+    unsafe {
+        let Some(image) = NSImage::initWithContentsOfFile(NSImage::alloc(), &image_path) else {
+            tracing::warn!("Could not load thumbnail of {:?} as an icon", bundle_path);
+            return;
+        };
+        let workspace = NSWorkspace::sharedWorkspace();
+        if !workspace.setIcon_forFile_options(
+            Some(&image),
+            &bundle,
+            NSWorkspaceIconCreationOptions(0),
+        ) {
+            tracing::warn!("Could not set the icon of {:?}", bundle_path);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_folder {
+    use super::BundleError;
+    use std::fs;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileAttributesW, SetFileAttributesW, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_READONLY,
+        FILE_ATTRIBUTE_SYSTEM, FILE_FLAGS_AND_ATTRIBUTES, INVALID_FILE_ATTRIBUTES,
+    };
+
+    /// Give the bundle folder the app's icon and a tooltip naming the
+    /// project. Explorer only reads `desktop.ini` from folders marked
+    /// read-only or system, which doesn't stop the app writing into it.
+    pub fn write_desktop_ini(bundle_path: &Path, project_name: &str) -> Result<(), BundleError> {
+        let exe = std::env::current_exe()?;
+        let content = format!(
+            "[.ShellClassInfo]\r\nIconResource={},0\r\nInfoTip={}\r\n",
+            exe.display(),
+            project_name.replace(['\r', '\n'], " ")
+        );
+
+        let ini = bundle_path.join("desktop.ini");
+        if fs::read_to_string(&ini).is_ok_and(|existing| existing == content) {
+            return Ok(());
+        }
+        // Hidden/system files can't be overwritten in place
+        if ini.exists() {
+            set_attributes(&ini, FILE_FLAGS_AND_ATTRIBUTES(0));
+            fs::remove_file(&ini)?;
+        }
+        fs::write(&ini, content)?;
+        set_attributes(&ini, FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM);
+        add_attributes(bundle_path, FILE_ATTRIBUTE_READONLY);
+        Ok(())
+    }
+
+    fn wide(path: &Path) -> Vec<u16> {
+        path.as_os_str().encode_wide().chain(Some(0)).collect()
+    }
+
+    fn set_attributes(path: &Path, attributes: FILE_FLAGS_AND_ATTRIBUTES) {
+        let path_w = wide(path);
+        if let Err(e) = unsafe { SetFileAttributesW(PCWSTR(path_w.as_ptr()), attributes) } {
+            tracing::warn!("Failed to set attributes of {:?}: {}", path, e);
+        }
+    }
+
+    fn add_attributes(path: &Path, attributes: FILE_FLAGS_AND_ATTRIBUTES) {
+        let path_w = wide(path);
+        let current = unsafe { GetFileAttributesW(PCWSTR(path_w.as_ptr())) };
+        if current == INVALID_FILE_ATTRIBUTES {
+            return;
+        }
+        set_attributes(path, FILE_FLAGS_AND_ATTRIBUTES(current) | attributes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_staleness_follows_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let video = dir.path().join("display.mp4");
+        let thumbnail = dir.path().join("Thumbnail.png");
+
+        fs::write(&video, b"video").unwrap();
+        assert!(is_stale(&thumbnail, &video));
+
+        fs::write(&thumbnail, b"png").unwrap();
+        let older = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&video)
+            .unwrap()
+            .set_modified(older)
+            .unwrap();
+        assert!(!is_stale(&thumbnail, &video));
+
+        // Replaced media is newer than the thumbnail
+        fs::File::options()
+            .write(true)
+            .open(&video)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+        assert!(is_stale(&thumbnail, &video));
+    }
+}
@@ -5,6 +5,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 // =============================================================================
@@ -128,10 +129,46 @@ impl Default for SpringConfig {
     }
 }
 
+/// Named spring settings offered in the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmoothingPreset {
+    /// Follows the pointer closely, only removing jitter
+    Subtle,
+    /// Balanced default
+    Smooth,
+    /// Slow, floaty movement for polished demos
+    Cinematic,
+}
+
+impl SmoothingPreset {
+    pub const ALL: [SmoothingPreset; 3] = [Self::Subtle, Self::Smooth, Self::Cinematic];
+
+    /// Spring parameters for this preset (all close to critically damped)
+    pub fn spring(&self) -> SpringConfig {
+        match self {
+            SmoothingPreset::Subtle => SpringConfig {
+                stiffness: 900.0,
+                damping: 70.0,
+                mass: 1.5,
+            },
+            SmoothingPreset::Smooth => SpringConfig::default(),
+            SmoothingPreset::Cinematic => SpringConfig {
+                stiffness: 200.0,
+                damping: 56.0,
+                mass: 4.0,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CursorSmoothingConfig {
     pub enabled: bool,
     pub spring: SpringConfig,
+    /// Preset the spring was last set from (None once tuned by hand)
+    #[serde(default)]
+    pub preset: Option<SmoothingPreset>,
 }
 
 impl Default for CursorSmoothingConfig {
@@ -139,6 +176,7 @@ impl Default for CursorSmoothingConfig {
         Self {
             enabled: true,
             spring: SpringConfig::default(),
+            preset: Some(SmoothingPreset::Smooth),
         }
     }
 }
@@ -149,6 +187,9 @@ pub struct CursorConfig {
     pub size: f64,
     pub smoothing: CursorSmoothingConfig,
     pub hide_after_ms: Option<u64>,
+    /// Installed cursor pack to draw cursors with (None keeps the recorded ones)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<String>,
 }
 
 impl Default for CursorConfig {
@@ -157,6 +198,7 @@ impl Default for CursorConfig {
             size: 1.5,
             smoothing: CursorSmoothingConfig::default(),
             hide_after_ms: None,
+            pack: None,
         }
     }
 }
@@ -212,6 +254,14 @@ pub struct AudioConfig {
     pub system_volume: f64,
     pub microphone_volume: f64,
     pub enhance_microphone: bool,
+    /// Offset applied to the microphone track in milliseconds (positive delays it),
+    /// used to line up externally imported voiceovers
+    #[serde(default)]
+    pub microphone_offset_ms: f64,
+    /// Remove speaker echo from the microphone at export, using the system
+    /// audio as the reference
+    #[serde(default)]
+    pub cancel_echo: bool,
 }
 
 impl Default for AudioConfig {
@@ -220,6 +270,54 @@ impl Default for AudioConfig {
             system_volume: 1.0,
             microphone_volume: 1.0,
             enhance_microphone: true,
+            microphone_offset_ms: 0.0,
+            cancel_echo: false,
+        }
+    }
+}
+
+// =============================================================================
+// Color
+// =============================================================================
+
+/// 3D LUT (`.cube`) stored in the bundle and applied at export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorLut {
+    /// Path of the LUT relative to the bundle (under `assets/luts`)
+    pub file: String,
+    #[serde(default = "default_true")]
+    pub apply_to_screen: bool,
+    #[serde(default = "default_true")]
+    pub apply_to_camera: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// =============================================================================
+// Zoom Defaults
+// =============================================================================
+
+/// Settings used for newly created zoom ranges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoomDefaults {
+    pub zoom: f64,
+    #[serde(rename = "type")]
+    pub zoom_type: ZoomType,
+    pub snap_to_edges: f64,
+    pub instant: bool,
+}
+
+impl Default for ZoomDefaults {
+    fn default() -> Self {
+        Self {
+            zoom: 2.0,
+            zoom_type: ZoomType::FollowCursor,
+            snap_to_edges: 0.0,
+            instant: false,
         }
     }
 }
@@ -270,6 +368,11 @@ pub struct ProjectConfig {
     pub audio: AudioConfig,
     pub recording_range: (f64, f64),
     pub output_aspect_ratio: AspectRatio,
+    #[serde(default)]
+    pub zoom: ZoomDefaults,
+    /// Color treatment applied at export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_lut: Option<ColorLut>,
 }
 
 impl Default for ProjectConfig {
@@ -283,6 +386,8 @@ impl Default for ProjectConfig {
             audio: AudioConfig::default(),
             recording_range: (0.0, 0.0),
             output_aspect_ratio: AspectRatio::default(),
+            zoom: ZoomDefaults::default(),
+            color_lut: None,
         }
     }
 }
@@ -321,6 +426,8 @@ pub struct ZoomRange {
     #[serde(rename = "type")]
     pub zoom_type: ZoomType,
     pub target_point: Option<Point>,
+    /// How readily the zoomed view snaps flush to the frame's edges, from 0
+    /// (never) to 1: the share of the view's size it has to come within
     pub snap_to_edges: f64,
     pub instant: bool,
 }
@@ -354,6 +461,38 @@ pub enum SceneType {
     Transition,
 }
 
+/// Audio track of a scene that can be soloed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AudioTrack {
+    Microphone,
+    SystemAudio,
+}
+
+/// Non-destructive mute/solo state of a scene's tracks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrackMutes {
+    pub microphone: bool,
+    pub system_audio: bool,
+    pub camera: bool,
+    /// When set, only this audio track is heard, regardless of mute flags
+    pub solo: Option<AudioTrack>,
+}
+
+impl TrackMutes {
+    /// Whether an audio track should be included in the export
+    pub fn is_audible(&self, track: AudioTrack) -> bool {
+        match self.solo {
+            Some(solo) => solo == track,
+            None => match track {
+                AudioTrack::Microphone => !self.microphone,
+                AudioTrack::SystemAudio => !self.system_audio,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Scene {
@@ -369,6 +508,8 @@ pub struct Scene {
     pub camera_slices: Vec<Slice>,
     pub zoom_ranges: Vec<ZoomRange>,
     pub layouts: Vec<Layout>,
+    #[serde(default)]
+    pub muted: TrackMutes,
 }
 
 // =============================================================================
@@ -409,3 +550,46 @@ pub struct Marker {
     pub label: String,
     pub color: Option<String>,
 }
+
+// =============================================================================
+// Editor UI State
+// =============================================================================
+
+/// Editor view state, kept in ui-state.json rather than project.json so
+/// scrolling and selecting don't mark the project as edited
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UiState {
+    /// Playhead position in output time
+    pub playhead_ms: f64,
+    /// Timeline zoom (1.0 = 100%)
+    pub timeline_zoom: f64,
+    /// Horizontal timeline scroll (px)
+    pub timeline_scroll_x: f64,
+    pub selected_slice_id: Option<String>,
+    pub selected_layout_id: Option<String>,
+    pub panels: PanelLayout,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            playhead_ms: 0.0,
+            timeline_zoom: 1.0,
+            timeline_scroll_x: 0.0,
+            selected_slice_id: None,
+            selected_layout_id: None,
+            panels: PanelLayout::default(),
+        }
+    }
+}
+
+/// Sizes and visibility of the editor's resizable panels
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PanelLayout {
+    /// Panel size in px, keyed by panel id (e.g. "timeline", "inspector")
+    pub sizes: BTreeMap<String, f64>,
+    /// Ids of collapsed panels
+    pub collapsed: Vec<String>,
+}
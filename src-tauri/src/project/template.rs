@@ -0,0 +1,183 @@
+//! Project templates
+//!
+//! A template is a named snapshot of a project's visual settings (background,
+//! padding, shadow, cursor, camera style, zoom defaults, aspect ratio). Teams
+//! save one from a styled project and apply it to new recordings to keep a
+//! consistent look across videos. Templates are stored as one JSON file each
+//! in the app's data directory.
+
+use super::bundle::BundleError;
+use super::schema::{
+    AspectRatio, Background, CameraConfig, CursorConfig, Padding, ProjectConfig, ShadowConfig,
+    ZoomDefaults,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Named set of visual project settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTemplate {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub background: Background,
+    pub padding: Padding,
+    pub shadow: ShadowConfig,
+    pub cursor: CursorConfig,
+    pub camera: CameraConfig,
+    pub zoom: ZoomDefaults,
+    pub output_aspect_ratio: AspectRatio,
+}
+
+impl ProjectTemplate {
+    /// Capture the visual settings of a project config
+    pub fn from_config(name: String, config: &ProjectConfig) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            created_at: Utc::now(),
+            background: config.background.clone(),
+            padding: config.padding.clone(),
+            shadow: config.shadow.clone(),
+            cursor: config.cursor.clone(),
+            camera: config.camera.clone(),
+            zoom: config.zoom.clone(),
+            output_aspect_ratio: config.output_aspect_ratio.clone(),
+        }
+    }
+
+    /// Apply the template's settings to a project config.
+    ///
+    /// Whether the camera is shown depends on the recording having a webcam
+    /// track, so `camera.enabled` is left as is; only the camera style changes.
+    pub fn apply_to(&self, config: &mut ProjectConfig) {
+        config.background = self.background.clone();
+        config.padding = self.padding.clone();
+        config.shadow = self.shadow.clone();
+        config.cursor = self.cursor.clone();
+        config.camera = CameraConfig {
+            enabled: config.camera.enabled,
+            ..self.camera.clone()
+        };
+        config.zoom = self.zoom.clone();
+        config.output_aspect_ratio = self.output_aspect_ratio.clone();
+    }
+}
+
+/// Default directory templates are stored in
+pub fn templates_dir() -> Result<PathBuf, BundleError> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| BundleError::InvalidBundle("Could not determine data directory".to_string()))?;
+    Ok(data_dir.join("Open ScreenStudio").join("templates"))
+}
+
+fn template_path(dir: &Path, id: &str) -> Result<PathBuf, BundleError> {
+    // IDs are UUIDs; reject anything that could escape the directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(BundleError::InvalidBundle(format!("Invalid template id: {}", id)));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+/// Write a template to `dir`, replacing any template with the same ID
+pub fn save_template(dir: &Path, template: &ProjectTemplate) -> Result<(), BundleError> {
+    fs::create_dir_all(dir)?;
+    let content = serde_json::to_string_pretty(template)?;
+    fs::write(template_path(dir, &template.id)?, content)?;
+    Ok(())
+}
+
+/// Read a template by ID
+pub fn load_template(dir: &Path, id: &str) -> Result<ProjectTemplate, BundleError> {
+    let path = template_path(dir, id)?;
+    if !path.exists() {
+        return Err(BundleError::MissingFile(format!("template {}", id)));
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// List all templates in `dir`, sorted by name. Unreadable files are skipped.
+pub fn list_templates(dir: &Path) -> Result<Vec<ProjectTemplate>, BundleError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut templates = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match fs::read_to_string(&path)
+            .map_err(BundleError::from)
+            .and_then(|c| serde_json::from_str::<ProjectTemplate>(&c).map_err(BundleError::from))
+        {
+            Ok(template) => templates.push(template),
+            Err(e) => tracing::warn!("Skipping unreadable template {:?}: {}", path, e),
+        }
+    }
+
+    templates.sort_by_key(|t| t.name.to_lowercase());
+    Ok(templates)
+}
+
+/// Delete a template by ID
+pub fn delete_template(dir: &Path, id: &str) -> Result<(), BundleError> {
+    let path = template_path(dir, id)?;
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_template_keeps_camera_visibility() {
+        let mut styled = ProjectConfig::default();
+        styled.padding.top = 48.0;
+        styled.camera.roundness = 1.0;
+        styled.zoom.zoom = 3.0;
+        let template = ProjectTemplate::from_config("Brand".to_string(), &styled);
+
+        let mut config = ProjectConfig::default();
+        config.camera.enabled = false;
+        config.recording_range = (0.0, 5000.0);
+        template.apply_to(&mut config);
+
+        assert_eq!(config.padding.top, 48.0);
+        assert_eq!(config.camera.roundness, 1.0);
+        assert_eq!(config.zoom.zoom, 3.0);
+        assert!(!config.camera.enabled);
+        assert_eq!(config.recording_range, (0.0, 5000.0));
+    }
+
+    #[test]
+    fn test_template_round_trip_and_listing() {
+        let dir = tempfile::tempdir().unwrap();
+        let b = ProjectTemplate::from_config("b-side".to_string(), &ProjectConfig::default());
+        let a = ProjectTemplate::from_config("Aurora".to_string(), &ProjectConfig::default());
+        save_template(dir.path(), &b).unwrap();
+        save_template(dir.path(), &a).unwrap();
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let names: Vec<String> = list_templates(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Aurora", "b-side"]);
+
+        assert_eq!(load_template(dir.path(), &a.id).unwrap().name, "Aurora");
+        delete_template(dir.path(), &a.id).unwrap();
+        assert!(load_template(dir.path(), &a.id).is_err());
+        assert!(load_template(dir.path(), "../escape").is_err());
+    }
+}
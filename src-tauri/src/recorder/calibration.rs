@@ -0,0 +1,421 @@
+//! Audio/video latency calibration
+//!
+//! Records a few seconds of a display and the microphone while the UI
+//! flashes the screen white and plays a beep at the same instant, a few
+//! times over. Comparing when each flash appears in the screen recording
+//! with when its beep appears in the microphone recording gives this
+//! machine's audio/video offset for that display. The offset is saved per
+//! display and becomes the default microphone offset of later recordings.
+
+use super::channel::{RecordingChannel, RecordingError, RecordingResult};
+use super::coordinator::RecordingCoordinator;
+use super::cues::CueConfig;
+use super::manifest::{resolve_file, FileKind, RecordingManifest};
+use super::state::RecordingConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Number of flash/beep cues per calibration run
+pub const CALIBRATION_CUES: usize = 4;
+
+/// Time between cues, comfortably longer than any plausible offset
+const CUE_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Recording before the first cue, letting the devices settle
+const LEAD_IN: Duration = Duration::from_millis(1500);
+
+/// Recording after the last cue
+const TAIL: Duration = Duration::from_millis(1000);
+
+/// Cues matched to fewer than this many flash/beep pairs are rejected
+const MIN_MATCHED_CUES: usize = 2;
+
+/// A flash and a beep further apart than this aren't the same cue
+const MAX_PAIR_DISTANCE_MS: f64 = 450.0;
+
+/// Two onsets closer than this belong to the same flash or beep
+const REFRACTORY_MS: f64 = 300.0;
+
+/// Frame rate the screen recording is resampled to for flash detection
+const ANALYSIS_FPS: u32 = 60;
+
+/// Microphone analysis sample rate and envelope window
+const ANALYSIS_SAMPLE_RATE: u32 = 8000;
+const ENVELOPE_WINDOW_MS: u32 = 5;
+
+/// Smallest mean-luma swing (0-255) that counts as a flash
+const MIN_FLASH_RANGE: f64 = 6.0;
+
+/// Smallest envelope swing (0.0-1.0) that counts as a beep
+const MIN_BEEP_RANGE: f64 = 0.01;
+
+/// Emitted when the UI should flash the screen and beep
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationCue {
+    pub index: usize,
+    pub count: usize,
+}
+
+/// Outcome of a calibration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationResult {
+    pub display_id: u32,
+    /// Microphone offset that lines the beeps up with the flashes (positive
+    /// delays the microphone, as in `AudioConfig::microphone_offset_ms`)
+    pub offset_ms: f64,
+    pub cues_sent: usize,
+    pub cues_matched: usize,
+    /// Difference between the largest and smallest per-cue offset
+    pub spread_ms: f64,
+}
+
+/// Saved offset of one display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayCalibration {
+    pub offset_ms: f64,
+    pub cues_matched: usize,
+    pub measured_at: DateTime<Utc>,
+}
+
+/// Per-machine calibration, keyed by display ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationStore {
+    #[serde(default)]
+    pub displays: BTreeMap<u32, DisplayCalibration>,
+}
+
+impl CalibrationStore {
+    /// Saved microphone offset for a display
+    pub fn offset_for(&self, display_id: u32) -> Option<f64> {
+        self.displays.get(&display_id).map(|c| c.offset_ms)
+    }
+
+    pub fn record(&mut self, result: &CalibrationResult) {
+        self.displays.insert(
+            result.display_id,
+            DisplayCalibration {
+                offset_ms: result.offset_ms,
+                cues_matched: result.cues_matched,
+                measured_at: Utc::now(),
+            },
+        );
+    }
+}
+
+/// Path of the calibration file
+pub fn calibration_path() -> RecordingResult<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| {
+        RecordingError::ConfigurationError("Could not determine data directory".to_string())
+    })?;
+    Ok(data_dir
+        .join("Open ScreenStudio")
+        .join("av-calibration.json"))
+}
+
+/// Load the calibration, returning an empty one if none was saved
+pub fn load_calibration(path: &Path) -> RecordingResult<CalibrationStore> {
+    if !path.exists() {
+        return Ok(CalibrationStore::default());
+    }
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| RecordingError::ConfigurationError(format!("Invalid calibration: {}", e)))
+}
+
+/// Save the calibration
+pub fn save_calibration(path: &Path, store: &CalibrationStore) -> RecordingResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(store)
+        .map_err(|e| RecordingError::ConfigurationError(e.to_string()))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Record the display and microphone while `on_cue` asks the UI for each
+/// flash and beep, then measure the offset between them.
+///
+/// Like the self-test, this uses its own coordinator and a temporary
+/// directory. The channels must capture the screen (with the app's windows
+/// visible) and the microphone.
+pub async fn run_calibration<F>(
+    channels: Vec<Box<dyn RecordingChannel>>,
+    mut config: RecordingConfig,
+    on_cue: F,
+) -> RecordingResult<CalibrationResult>
+where
+    F: Fn(CalibrationCue),
+{
+    let temp_dir = tempfile::tempdir()?;
+    config.output_dir = temp_dir.path().to_string_lossy().to_string();
+    // A start cue would be picked up as an extra beep
+    config.cues = CueConfig::default();
+    let display_id = config.display_id;
+
+    let mut coordinator = RecordingCoordinator::new();
+    for channel in channels {
+        coordinator.add_channel(channel);
+    }
+
+    tracing::info!("Running A/V calibration of display {}", display_id);
+
+    coordinator.start(config).await?;
+    tokio::time::sleep(LEAD_IN).await;
+    for index in 0..CALIBRATION_CUES {
+        if index > 0 {
+            tokio::time::sleep(CUE_INTERVAL).await;
+        }
+        on_cue(CalibrationCue {
+            index,
+            count: CALIBRATION_CUES,
+        });
+    }
+    tokio::time::sleep(TAIL).await;
+    coordinator.stop().await?;
+
+    let recording_dir = temp_dir.path().join("recording");
+    let manifest = RecordingManifest::read(&recording_dir);
+    let video = resolve_file(&recording_dir, manifest.as_ref(), FileKind::ScreenVideo, 0)
+        .ok_or_else(|| RecordingError::CaptureError("No screen recording was made".to_string()))?;
+    let mic = resolve_file(&recording_dir, manifest.as_ref(), FileKind::MicAudio, 0).ok_or_else(
+        || RecordingError::CaptureError("No microphone recording was made".to_string()),
+    )?;
+
+    let luma = decode_mean_luma(&video).await?;
+    let envelope = decode_envelope(&mic).await?;
+    let flashes = rising_edges(&luma, 1000.0 / ANALYSIS_FPS as f64, MIN_FLASH_RANGE);
+    let beeps = rising_edges(&envelope, ENVELOPE_WINDOW_MS as f64, MIN_BEEP_RANGE);
+    tracing::debug!("Calibration flashes at {:?}, beeps at {:?}", flashes, beeps);
+
+    let offsets = pair_offsets(&flashes, &beeps);
+    if offsets.len() < MIN_MATCHED_CUES {
+        return Err(RecordingError::CaptureError(format!(
+            "Detected {} flashes and {} beeps but could only match {} of them; \
+             make sure the flash is on the recorded display and the volume is up",
+            flashes.len(),
+            beeps.len(),
+            offsets.len()
+        )));
+    }
+
+    let spread_ms = offsets.last().unwrap() - offsets.first().unwrap();
+    let result = CalibrationResult {
+        display_id,
+        offset_ms: median(&offsets),
+        cues_sent: CALIBRATION_CUES,
+        cues_matched: offsets.len(),
+        spread_ms,
+    };
+    tracing::info!(
+        "Display {} A/V offset {:.1}ms ({} cues, spread {:.1}ms)",
+        display_id,
+        result.offset_ms,
+        result.cues_matched,
+        result.spread_ms
+    );
+    Ok(result)
+}
+
+/// Times (ms) at which a signal rises through the midpoint of its range,
+/// interpolated between samples. Returns nothing for signals whose range is
+/// below `min_range`.
+pub fn rising_edges(signal: &[f64], step_ms: f64, min_range: f64) -> Vec<f64> {
+    let (min, max) = signal
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    if signal.len() < 2 || max - min < min_range {
+        return Vec::new();
+    }
+
+    let threshold = (min + max) / 2.0;
+    let mut edges: Vec<f64> = Vec::new();
+    for (i, pair) in signal.windows(2).enumerate() {
+        if pair[0] < threshold && pair[1] >= threshold {
+            let fraction = (threshold - pair[0]) / (pair[1] - pair[0]);
+            let time = (i as f64 + fraction) * step_ms;
+            if edges
+                .last()
+                .is_none_or(|&last| time - last >= REFRACTORY_MS)
+            {
+                edges.push(time);
+            }
+        }
+    }
+    edges
+}
+
+/// Microphone offsets (flash time - beep time) of every flash that has a
+/// beep close enough to belong to it, sorted
+pub fn pair_offsets(flashes: &[f64], beeps: &[f64]) -> Vec<f64> {
+    let mut offsets: Vec<f64> = flashes
+        .iter()
+        .filter_map(|&flash| {
+            beeps
+                .iter()
+                .map(|&beep| flash - beep)
+                .filter(|d| d.abs() <= MAX_PAIR_DISTANCE_MS)
+                .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+        })
+        .collect();
+    offsets.sort_by(f64::total_cmp);
+    offsets
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    (sorted[(n - 1) / 2] + sorted[n / 2]) / 2.0
+}
+
+/// Mean luma of each frame, resampled to `ANALYSIS_FPS`
+async fn decode_mean_luma(path: &Path) -> RecordingResult<Vec<f64>> {
+    // A tiny thumbnail is enough: only the overall brightness matters
+    const WIDTH: usize = 16;
+    const HEIGHT: usize = 9;
+
+    let stdout = run_ffmpeg(
+        path,
+        &[
+            "-vf",
+            &format!(
+                "fps={},scale={}:{},format=gray",
+                ANALYSIS_FPS, WIDTH, HEIGHT
+            ),
+            "-f",
+            "rawvideo",
+            "-",
+        ],
+    )
+    .await?;
+
+    Ok(stdout
+        .chunks_exact(WIDTH * HEIGHT)
+        .map(|frame| frame.iter().map(|&v| v as f64).sum::<f64>() / frame.len() as f64)
+        .collect())
+}
+
+/// Peak amplitude of each `ENVELOPE_WINDOW_MS` window of mono audio
+async fn decode_envelope(path: &Path) -> RecordingResult<Vec<f64>> {
+    let stdout = run_ffmpeg(
+        path,
+        &[
+            "-ac",
+            "1",
+            "-ar",
+            &ANALYSIS_SAMPLE_RATE.to_string(),
+            "-f",
+            "s16le",
+            "-acodec",
+            "pcm_s16le",
+            "-",
+        ],
+    )
+    .await?;
+
+    let window = (ANALYSIS_SAMPLE_RATE * ENVELOPE_WINDOW_MS / 1000) as usize;
+    let samples: Vec<f64> = stdout
+        .chunks_exact(2)
+        .map(|c| (i16::from_le_bytes([c[0], c[1]]) as f64 / i16::MAX as f64).abs())
+        .collect();
+    Ok(samples
+        .chunks(window)
+        .map(|w| w.iter().copied().fold(0.0, f64::max))
+        .collect())
+}
+
+async fn run_ffmpeg(input: &Path, output_args: &[&str]) -> RecordingResult<Vec<u8>> {
+    let output = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(input)
+        .args(output_args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                RecordingError::FfmpegMissing("FFmpeg is required for calibration".to_string())
+            }
+            _ => RecordingError::EncodingError(format!("Failed to run FFmpeg: {}", e)),
+        })?;
+
+    if !output.status.success() {
+        return Err(RecordingError::EncodingError(format!(
+            "Could not decode {:?}",
+            input.file_name().unwrap_or_default()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flash_and_beep_detection() {
+        // Dark screen flashing white for 100ms at 1.0s and 2.0s (60fps)
+        let luma: Vec<f64> = (0..180)
+            .map(|i| {
+                if (60..66).contains(&i) || (120..126).contains(&i) {
+                    230.0
+                } else {
+                    20.0
+                }
+            })
+            .collect();
+        let flashes = rising_edges(&luma, 1000.0 / 60.0, MIN_FLASH_RANGE);
+        assert_eq!(flashes.len(), 2);
+        assert!((flashes[0] - 991.7).abs() < 1.0);
+
+        // Beeps arriving ~70ms after each flash, with a cough in between
+        let mut envelope = vec![0.002; 600];
+        for start in [212, 412] {
+            envelope[start..start + 20].fill(0.4);
+        }
+        envelope[300] = 0.1;
+        let beeps = rising_edges(&envelope, 5.0, MIN_BEEP_RANGE);
+        assert_eq!(beeps.len(), 2);
+
+        let offsets = pair_offsets(&flashes, &beeps);
+        assert_eq!(offsets.len(), 2);
+        assert!((median(&offsets) + 70.0).abs() < 10.0);
+
+        // A flat signal has no edges
+        assert!(rising_edges(&[20.0; 100], 16.7, MIN_FLASH_RANGE).is_empty());
+    }
+
+    #[test]
+    fn test_store_keeps_latest_offset_per_display() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("av-calibration.json");
+        assert!(load_calibration(&path).unwrap().offset_for(1).is_none());
+
+        let mut store = CalibrationStore::default();
+        for offset_ms in [-40.0, -55.0] {
+            store.record(&CalibrationResult {
+                display_id: 1,
+                offset_ms,
+                cues_sent: CALIBRATION_CUES,
+                cues_matched: 3,
+                spread_ms: 4.0,
+            });
+        }
+        save_calibration(&path, &store).unwrap();
+
+        let loaded = load_calibration(&path).unwrap();
+        assert_eq!(loaded.offset_for(1), Some(-55.0));
+        assert_eq!(loaded.offset_for(2), None);
+    }
+}
@@ -2,9 +2,13 @@
 //!
 //! Defines the interface for different recording channels (display, audio, webcam, input).
 
+use super::coordinator::RecordingEvent;
+use super::manifest::ChannelSettings;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use thiserror::Error;
+use tokio::sync::broadcast;
 
 /// Errors that can occur during recording
 #[derive(Error, Debug)]
@@ -35,6 +39,9 @@ pub enum RecordingError {
 
     #[error("Configuration error: {0}")]
     ConfigurationError(String),
+
+    #[error("{0}")]
+    FfmpegMissing(String),
 }
 
 /// Result type for recording operations
@@ -91,10 +98,77 @@ pub trait RecordingChannel: Send + Sync {
     
     /// Get output files created by this channel
     fn output_files(&self) -> Vec<String>;
+    
+    /// Give the channel a sender for reporting events (device changes, etc.)
+    /// while recording. Channels that don't report anything can ignore it.
+    fn set_event_sender(&mut self, _event_tx: broadcast::Sender<RecordingEvent>) {}
+    
+    /// Capture settings for the recording manifest (fps, resolution, device, ...)
+    fn settings(&self) -> ChannelSettings {
+        ChannelSettings::default()
+    }
+    
+    /// Number of frames dropped so far, for channels that can tell
+    fn dropped_frames(&self) -> Option<u64> {
+        None
+    }
+    
+    /// Whether capture is working, for channels whose capture can fail after
+    /// `start` returned (e.g. on a background thread)
+    fn health(&self) -> ChannelHealth {
+        ChannelHealth::Healthy
+    }
+}
+
+/// How well a channel is capturing
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum ChannelHealth {
+    #[default]
+    Healthy,
+    /// Still recording, but with gaps or dropped data
+    Degraded { reason: String },
+    /// Stopped capturing; the rest of the recording continues without it
+    Failed { reason: String },
+}
+
+impl ChannelHealth {
+    fn severity(&self) -> u8 {
+        match self {
+            ChannelHealth::Healthy => 0,
+            ChannelHealth::Degraded { .. } => 1,
+            ChannelHealth::Failed { .. } => 2,
+        }
+    }
+
+    /// The more severe of `self` and `other`, keeping `self` on a tie
+    pub fn worst(self, other: ChannelHealth) -> ChannelHealth {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Outcome of one channel, reported when the recording stops
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelStatus {
+    pub id: String,
+    pub channel_type: ChannelType,
+    /// Worst health seen during the recording
+    pub health: ChannelHealth,
+    pub output_files: usize,
+    /// Settings the channel actually recorded with, e.g. the microphone's
+    /// sample rate when the one asked for wasn't supported
+    #[serde(default)]
+    pub settings: ChannelSettings,
 }
 
 /// Types of recording channels
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ChannelType {
     /// Screen/display capture
     Display,
@@ -106,6 +180,12 @@ pub enum ChannelType {
     Webcam,
     /// Input tracking (mouse, keyboard)
     Input,
+    /// On-screen annotations (pen, arrows, highlights)
+    Annotation,
+    /// Frontmost application over time
+    AppActivity,
+    /// Key presses
+    Keyboard,
 }
 
 impl std::fmt::Display for ChannelType {
@@ -116,6 +196,9 @@ impl std::fmt::Display for ChannelType {
             ChannelType::Microphone => write!(f, "microphone"),
             ChannelType::Webcam => write!(f, "webcam"),
             ChannelType::Input => write!(f, "input"),
+            ChannelType::Annotation => write!(f, "annotation"),
+            ChannelType::AppActivity => write!(f, "app-activity"),
+            ChannelType::Keyboard => write!(f, "keyboard"),
         }
     }
 }
@@ -2,16 +2,38 @@
 //!
 //! Orchestrates multiple recording channels and manages the recording lifecycle.
 
-use super::channel::{RecordingChannel, RecordingError, RecordingResult};
-use super::state::{RecordingConfig, RecordingResult as RecordingOutput, RecordingSession, RecordingState};
+use super::channel::{
+    ChannelHealth, ChannelStatus, ChannelType, RecordingChannel, RecordingError, RecordingResult,
+};
+use super::cues::{Cue, CuePlayer};
+use super::manifest::{ChannelAnchor, ChannelManifest, RecordingManifest, MANIFEST_VERSION};
+use super::recovery::RecordingLock;
+use super::segment::{self, LivePreview};
+use super::sessions::SessionList;
+use super::state::{
+    RecordingConfig, RecordingResult as RecordingOutput, RecordingSession, RecordingState,
+    SessionBoundary,
+};
+use crate::capture::level::AudioLevel;
+use crate::project::bundle::{read_markers, write_markers, BundleError};
+use crate::project::schema::Marker;
+use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
+use serde::Serialize;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, Mutex};
 
-/// Events emitted during recording
-#[derive(Debug, Clone)]
+/// Events emitted during recording. Forwarded to the frontend as
+/// `recording-event`, e.g. `{"type": "progress", "data": 1520.0}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "type",
+    content = "data",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
 pub enum RecordingEvent {
     /// Recording started
     Started,
@@ -25,6 +47,83 @@ pub enum RecordingEvent {
     Error(String),
     /// Recording progress update (duration in ms)
     Progress(f64),
+    /// A channel lost its capture device
+    DeviceLost { channel: String, device: String },
+    /// A channel switched to a fallback device after losing its original one
+    DeviceFallback {
+        channel: String,
+        from: String,
+        to: String,
+        /// Length of the silence/gap inserted to keep sync (ms)
+        gap_ms: f64,
+    },
+    /// A channel's lost device came back and it's capturing from it again
+    DeviceRecovered {
+        channel: String,
+        device: String,
+        /// Length of the silence/gap inserted to keep sync (ms)
+        gap_ms: f64,
+    },
+    /// The display a channel records changed resolution
+    DisplayChanged {
+        channel: String,
+        display: String,
+        width: u32,
+        height: u32,
+    },
+    /// The display a channel records was unplugged
+    DisplayDisconnected { channel: String, display: String },
+    /// A channel is still recording but losing data
+    ChannelDegraded { channel: String, reason: String },
+    /// A channel stopped capturing; the recording continues without it
+    ChannelFailed { channel: String, reason: String },
+    /// Microphone level over the last `LEVEL_INTERVAL`, sent to the
+    /// frontend as `mic-level` rather than `recording-event`
+    MicLevel(AudioLevel),
+    /// A marker was added to the recording (`add_marker`)
+    MarkerAdded(Marker),
+    /// The recording reached its maximum duration or scheduled end and was
+    /// stopped, with the same result as `stop_recording`
+    AutoStopped {
+        reason: AutoStopReason,
+        result: Box<RecordingOutput>,
+    },
+}
+
+/// Why a recording was stopped automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AutoStopReason {
+    /// `max_duration_ms` of recorded time was reached
+    MaxDuration,
+    /// The `stop_at` time was reached
+    Scheduled,
+}
+
+/// Health of one channel over the recording
+#[derive(Debug, Clone, Default)]
+struct HealthRecord {
+    /// Last health reported
+    current: ChannelHealth,
+    /// Worst health reported so far
+    worst: ChannelHealth,
+    /// Left out of the recording after failing to start, stop or resume, until it
+    /// next resumes
+    isolated: bool,
+}
+
+/// Where the coordinator reads the time from, so tests can control it
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
 }
 
 /// Manages multiple recording channels
@@ -47,8 +146,30 @@ pub struct RecordingCoordinator {
     /// Time when recording started (for process time calculation)
     start_time: Option<Instant>,
     
+    /// Clock `start_time` and session times are read from
+    clock: Arc<dyn Clock>,
+    
+    /// Configuration of the current recording
+    config: Option<RecordingConfig>,
+    
+    /// Unix start/stop times per channel (same order as `channels`)
+    channel_times: Vec<(Option<u64>, Option<u64>)>,
+    
+    /// When each channel captured in every session, on the `start_time`
+    /// clock (same order as `channels`)
+    channel_anchors: Vec<Vec<ChannelAnchor>>,
+    
+    /// Health per channel (same order as `channels`)
+    channel_health: Vec<HealthRecord>,
+    
     /// Event broadcaster
     event_tx: broadcast::Sender<RecordingEvent>,
+    
+    /// Plays start/stop/pause/resume cues, if enabled in the config
+    cue_player: Option<Arc<dyn CuePlayer>>,
+    
+    /// Marks the recording as in progress until it stops cleanly
+    lock: Option<RecordingLock>,
 }
 
 impl RecordingCoordinator {
@@ -62,13 +183,31 @@ impl RecordingCoordinator {
             current_session: 0,
             output_dir: None,
             start_time: None,
+            clock: Arc::new(SystemClock),
+            config: None,
+            channel_times: Vec::new(),
+            channel_anchors: Vec::new(),
+            channel_health: Vec::new(),
             event_tx,
+            cue_player: None,
+            lock: None,
         }
     }
     
+    /// Set the player used for recording cues
+    pub fn set_cue_player(&mut self, player: Arc<dyn CuePlayer>) {
+        self.cue_player = Some(player);
+    }
+    
+    /// Set the clock recording times are read from
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+    
     /// Add a recording channel
-    pub fn add_channel(&mut self, channel: Box<dyn RecordingChannel>) {
+    pub fn add_channel(&mut self, mut channel: Box<dyn RecordingChannel>) {
         tracing::info!("Adding channel: {}", channel.id());
+        channel.set_event_sender(self.event_tx.clone());
         self.channels.push(channel);
     }
     
@@ -77,15 +216,40 @@ impl RecordingCoordinator {
         *self.state.read()
     }
     
+    /// Get the configuration of the current recording, if any
+    pub fn config(&self) -> Option<&RecordingConfig> {
+        self.config.as_ref()
+    }
+    
+    /// Get the output directory of the current recording, if any
+    pub fn output_dir(&self) -> Option<&PathBuf> {
+        self.output_dir.as_ref()
+    }
+    
     /// Subscribe to recording events
     pub fn subscribe(&self) -> broadcast::Receiver<RecordingEvent> {
         self.event_tx.subscribe()
     }
     
+    /// Play a cue and wait for it to finish. Only called while no channel is
+    /// capturing, so the cue isn't recorded.
+    async fn play_cue(&self, cue: Cue, config: Option<&RecordingConfig>) {
+        let (Some(player), Some(config)) = (self.cue_player.clone(), config) else {
+            return;
+        };
+        if !config.cues.is_enabled() {
+            return;
+        }
+        let cues = config.cues.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || player.play(cue, &cues)).await {
+            tracing::warn!("Failed to play {:?} cue: {}", cue, e);
+        }
+    }
+    
     /// Get the current process time in milliseconds
     fn process_time_ms(&self) -> f64 {
         self.start_time
-            .map(|t| t.elapsed().as_secs_f64() * 1000.0)
+            .map(|t| self.clock.now().duration_since(t).as_secs_f64() * 1000.0)
             .unwrap_or(0.0)
     }
     
@@ -96,6 +260,10 @@ impl RecordingCoordinator {
             return Err(RecordingError::AlreadyRecording);
         }
         
+        if self.channels.is_empty() {
+            return Err(RecordingError::ConfigurationError("No channels to record".to_string()));
+        }
+        
         tracing::info!("Starting recording to: {}", config.output_dir);
         
         // Set up output directory
@@ -107,7 +275,7 @@ impl RecordingCoordinator {
         std::fs::create_dir_all(&recording_dir)?;
         
         self.output_dir = Some(output_dir);
-        self.start_time = Some(Instant::now());
+        self.start_time = Some(self.clock.now());
         self.current_session = 0;
         self.sessions.clear();
         
@@ -115,16 +283,51 @@ impl RecordingCoordinator {
         let session = RecordingSession::new(0, 0.0);
         self.sessions.push(session);
         
+        // Needed by `is_optional` from here on
+        self.config = Some(config.clone());
+        self.channel_health = self.channels.iter().map(|_| HealthRecord::default()).collect();
+        
         // Two-phase channel startup for synchronized recording:
-        // Phase 1: Initialize all channels (device checks, config, no FFmpeg yet)
-        for channel in &mut self.channels {
-            channel.initialize(&recording_dir, 0).await?;
+        // Phase 1: Initialize all channels (device checks, config, no FFmpeg yet).
+        // An optional channel that can't (e.g. input tracking without
+        // Accessibility) is left out with a warning.
+        for i in 0..self.channels.len() {
+            if let Err(e) = self.channels[i].initialize(&recording_dir, 0).await {
+                if let Err(e) = self.isolate_failure(i, e) {
+                    self.config = None;
+                    return Err(e);
+                }
+            }
         }
         
+        // Left locked if the app crashes, so the recording can be recovered
+        self.lock = RecordingLock::acquire(&recording_dir, &config)
+            .inspect_err(|e| tracing::warn!("Failed to lock recording for recovery: {}", e))
+            .ok();
+        
+        // The start cue plays before capture begins
+        self.play_cue(Cue::Start, Some(&config)).await;
+        
         // Phase 2: Start all channels (FFmpeg spawns happen here, close together)
         // This ensures all encoders start at nearly the same time for proper A/V sync
-        for channel in &mut self.channels {
-            channel.start().await?;
+        self.channel_times.clear();
+        self.channel_anchors.clear();
+        for i in 0..self.channels.len() {
+            let isolated = self.channel_health[i].isolated;
+            if !isolated {
+                if let Err(e) = self.channels[i].start().await {
+                    if let Err(e) = self.isolate_failure(i, e) {
+                        self.abort_start(i).await;
+                        return Err(e);
+                    }
+                }
+            }
+            self.channel_times.push((Some(unix_ms()), None));
+            self.channel_anchors.push(vec![ChannelAnchor {
+                session: 0,
+                start_ms: self.process_time_ms(),
+                stop_ms: None,
+            }]);
         }
         
         *self.state.write() = RecordingState::Recording;
@@ -134,6 +337,29 @@ impl RecordingCoordinator {
         Ok(())
     }
     
+    /// Undo a `start` that failed while starting channel `failed`: stop the
+    /// channels started before it and release the lock, as nothing usable
+    /// was recorded
+    async fn abort_start(&mut self, failed: usize) {
+        for i in 0..failed {
+            if self.channel_health[i].isolated {
+                continue;
+            }
+            if let Err(e) = self.channels[i].stop().await {
+                tracing::warn!("Failed to stop channel {}: {}", self.channels[i].id(), e);
+            }
+        }
+        if let Some(lock) = self.lock.take() {
+            lock.release();
+        }
+        self.channel_times.clear();
+        self.channel_anchors.clear();
+        self.sessions.clear();
+        self.output_dir = None;
+        self.start_time = None;
+        self.config = None;
+    }
+    
     /// Stop recording
     pub async fn stop(&mut self) -> RecordingResult<RecordingOutput> {
         let current_state = *self.state.read();
@@ -143,15 +369,31 @@ impl RecordingCoordinator {
         
         tracing::info!("Stopping recording");
         
-        // End current session
-        let end_time = self.process_time_ms();
-        if let Some(session) = self.sessions.last_mut() {
-            session.end(end_time);
-        }
+        // Every channel is stopped even if a required one fails, and the
+        // first such failure is returned once the recording is wound down
+        let mut failure = None;
         
-        // Stop all channels
-        for channel in &mut self.channels {
-            channel.stop().await?;
+        // When paused, the last session already ended and every channel was
+        // stopped (and its files finalized) by `pause`
+        if current_state == RecordingState::Recording {
+            let end_time = self.process_time_ms();
+            if let Some(session) = self.sessions.last_mut() {
+                session.end(end_time);
+            }
+            
+            self.check_channel_health();
+            for i in 0..self.channels.len() {
+                self.mark_capture_stopped(i);
+                if let Err(e) = self.channels[i].stop().await {
+                    if let Err(e) = self.isolate_failure(i, e) {
+                        failure.get_or_insert(e);
+                    }
+                }
+                if let Some(times) = self.channel_times.get_mut(i) {
+                    times.1 = Some(unix_ms());
+                }
+            }
+            self.finalize_session_media().await;
         }
         
         // Collect output files
@@ -162,6 +404,24 @@ impl RecordingCoordinator {
         
         // Calculate total duration
         let total_duration_ms: f64 = self.sessions.iter().map(|s| s.duration_ms).sum();
+        let channels = self.channel_statuses();
+        
+        // Describe the recording so loaders don't have to guess file names
+        if let Err(e) = self.write_manifest(total_duration_ms) {
+            tracing::warn!("Failed to write recording manifest: {}", e);
+        }
+        // An encrypted recording stays recoverable until its media is
+        // encrypted; see `take_lock`
+        let encrypt_media = self.config.as_ref().is_some_and(|c| c.encrypt_media);
+        if failure.is_some() {
+            // Dropped, not released: the failed channel's files may be
+            // unfinished, so the recording is left to be recovered
+            self.lock = None;
+        } else if !encrypt_media {
+            if let Some(lock) = self.lock.take() {
+                lock.release();
+            }
+        }
         
         let result = RecordingOutput {
             bundle_path: self.output_dir
@@ -171,20 +431,33 @@ impl RecordingCoordinator {
             total_duration_ms,
             session_count: self.sessions.len(),
             output_files,
+            channels,
         };
         
         *self.state.write() = RecordingState::Complete;
         let _ = self.event_tx.send(RecordingEvent::Stopped);
+        self.play_cue(Cue::Stop, self.config.as_ref()).await;
         
         // Reset state
         self.output_dir = None;
         self.start_time = None;
+        self.config = None;
         *self.state.write() = RecordingState::Idle;
         
+        if let Some(e) = failure {
+            return Err(e);
+        }
         tracing::info!("Recording stopped. Duration: {}ms", total_duration_ms);
         Ok(result)
     }
     
+    /// Lock of the last recording, still held after `stop` if its media is
+    /// to be encrypted. Release it once that's done; dropping it instead
+    /// leaves the recording to be recovered.
+    pub fn take_lock(&mut self) -> Option<RecordingLock> {
+        self.lock.take()
+    }
+    
     /// Pause recording
     pub async fn pause(&mut self) -> RecordingResult<()> {
         let current_state = *self.state.read();
@@ -193,20 +466,11 @@ impl RecordingCoordinator {
         }
         
         tracing::info!("Pausing recording");
-        
-        // End current session
-        let end_time = self.process_time_ms();
-        if let Some(session) = self.sessions.last_mut() {
-            session.end(end_time);
-        }
-        
-        // Pause all channels
-        for channel in &mut self.channels {
-            channel.pause().await?;
-        }
+        self.end_session().await?;
         
         *self.state.write() = RecordingState::Paused;
         let _ = self.event_tx.send(RecordingEvent::Paused);
+        self.play_cue(Cue::Pause, self.config.as_ref()).await;
         
         Ok(())
     }
@@ -219,23 +483,275 @@ impl RecordingCoordinator {
         }
         
         tracing::info!("Resuming recording");
+        self.play_cue(Cue::Resume, self.config.as_ref()).await;
+        self.start_next_session().await?;
         
-        // Create new session
-        self.current_session += 1;
-        let session = RecordingSession::new(self.current_session, self.process_time_ms());
-        self.sessions.push(session);
+        *self.state.write() = RecordingState::Recording;
+        let _ = self.event_tx.send(RecordingEvent::Resumed);
         
-        // Resume all channels
-        for channel in &mut self.channels {
-            channel.resume(self.current_session).await?;
+        Ok(())
+    }
+    
+    /// End the current session and continue in a new one without pausing,
+    /// so every channel restarts (e.g. encoders at a new display size). The
+    /// boundary is recorded on the new session in the manifest.
+    pub async fn split_session(&mut self, boundary: SessionBoundary) -> RecordingResult<()> {
+        let current_state = *self.state.read();
+        if current_state != RecordingState::Recording {
+            return Err(RecordingError::NotRecording);
         }
         
-        *self.state.write() = RecordingState::Recording;
-        let _ = self.event_tx.send(RecordingEvent::Resumed);
+        tracing::info!("Starting a new session: {:?}", boundary);
+        self.end_session().await?;
+        if let Err(e) = self.start_next_session().await {
+            // Every channel is stopped; leave the recording paused so it can
+            // still be resumed or stopped
+            *self.state.write() = RecordingState::Paused;
+            let _ = self.event_tx.send(RecordingEvent::Paused);
+            return Err(e);
+        }
+        if let Some(session) = self.sessions.last_mut() {
+            session.boundary = Some(boundary);
+        }
+        
+        Ok(())
+    }
+    
+    /// End the current session and stop every channel
+    async fn end_session(&mut self) -> RecordingResult<()> {
+        let end_time = self.process_time_ms();
+        if let Some(session) = self.sessions.last_mut() {
+            session.end(end_time);
+        }
+        
+        self.check_channel_health();
+        for i in 0..self.channels.len() {
+            self.mark_capture_stopped(i);
+            if let Err(e) = self.channels[i].pause().await {
+                self.isolate_failure(i, e)?;
+            }
+            if let Some(times) = self.channel_times.get_mut(i) {
+                times.1 = Some(unix_ms());
+            }
+        }
+        self.finalize_session_media().await;
+        Ok(())
+    }
+    
+    /// Note the time channel `i` is asked to stop capturing, before it
+    /// finalizes its files
+    fn mark_capture_stopped(&mut self, i: usize) {
+        let now = self.process_time_ms();
+        let open = self
+            .channel_anchors
+            .get_mut(i)
+            .and_then(|anchors| anchors.last_mut())
+            .filter(|anchor| anchor.stop_ms.is_none());
+        if let Some(anchor) = open {
+            anchor.stop_ms = Some(now);
+        }
+    }
+    
+    /// Turn the fragmented video and audio files of the session that just
+    /// ended into regular MP4s, the screen video as long as the session
+    async fn finalize_session_media(&self) {
+        let Some(output_dir) = self.output_dir.as_ref() else {
+            return;
+        };
+        let recording_dir = output_dir.join("recording");
+        let session = self.current_session;
+        let duration_ms = self.sessions.last().map(|s| s.duration_ms);
+        if let Err(e) = tokio::task::spawn_blocking(move || {
+            segment::finalize_session(&recording_dir, session, duration_ms)
+        })
+        .await
+        {
+            tracing::warn!("Failed to finalize session {} videos: {}", session, e);
+        }
+    }
+    
+    /// Resume every channel in a new session
+    async fn start_next_session(&mut self) -> RecordingResult<()> {
+        // Sessions are numbered in the order the manifest lists them, so
+        // indices never skip. A resume that failed part-way didn't add its
+        // session, and the next attempt records into the same index.
+        let next_session = self.sessions.len();
         
+        // Resume all channels. An optional channel that fails is left out; if
+        // any other fails, pause the ones already resumed so every channel
+        // stays on the same session and the recording can still be stopped
+        // cleanly.
+        for i in 0..self.channels.len() {
+            if let Err(e) = self.channels[i].resume(next_session).await {
+                if self.is_optional(i) {
+                    let _ = self.isolate_failure(i, e);
+                    continue;
+                }
+                tracing::error!(
+                    "Channel {} failed to resume session {}: {}",
+                    self.channels[i].id(),
+                    next_session,
+                    e
+                );
+                for channel in &mut self.channels[..i] {
+                    if let Err(e) = channel.pause().await {
+                        tracing::warn!("Failed to re-pause channel {}: {}", channel.id(), e);
+                    }
+                }
+                return Err(e);
+            }
+            if let Some(times) = self.channel_times.get_mut(i) {
+                times.1 = None;
+            }
+            let start_ms = self.process_time_ms();
+            if let Some(anchors) = self.channel_anchors.get_mut(i) {
+                anchors.push(ChannelAnchor {
+                    session: next_session,
+                    start_ms,
+                    stop_ms: None,
+                });
+            }
+            if let Some(record) = self.channel_health.get_mut(i) {
+                record.isolated = false;
+            }
+        }
+        
+        self.current_session = next_session;
+        let session = RecordingSession::new(next_session, self.process_time_ms());
+        self.sessions.push(session);
+        Ok(())
+    }
+    
+    /// Whether the recording can go on without channel `i`. Only the screen
+    /// (or the camera, for a camera-only recording) and audio are essential.
+    fn is_optional(&self, i: usize) -> bool {
+        let capture_screen = self.config.as_ref().is_none_or(|c| c.capture_screen);
+        match self.channels[i].channel_type() {
+            ChannelType::Display | ChannelType::SystemAudio | ChannelType::Microphone => false,
+            ChannelType::Webcam => capture_screen,
+            ChannelType::Input
+            | ChannelType::Annotation
+            | ChannelType::AppActivity
+            | ChannelType::Keyboard => true,
+        }
+    }
+    
+    /// Mark channel `i` failed and carry on if it's optional; otherwise hand
+    /// the error back
+    fn isolate_failure(&mut self, i: usize, error: RecordingError) -> RecordingResult<()> {
+        if !self.is_optional(i) {
+            return Err(error);
+        }
+        let Some(record) = self.channel_health.get_mut(i) else {
+            return Ok(());
+        };
+        // A channel left out earlier fails again when it's next stopped
+        if record.isolated {
+            tracing::debug!("Channel {} already failed: {}", self.channels[i].id(), error);
+            return Ok(());
+        }
+        record.isolated = true;
+        tracing::error!(
+            "Channel {} failed, continuing without it: {}",
+            self.channels[i].id(),
+            error
+        );
+        self.set_health(
+            i,
+            ChannelHealth::Failed {
+                reason: error.to_string(),
+            },
+        );
         Ok(())
     }
     
+    /// Whether the recording should stop on its own at `now`, because it
+    /// reached the config's maximum duration or stop time
+    pub fn auto_stop_reason(&self, now: DateTime<Utc>) -> Option<AutoStopReason> {
+        let config = self.config.as_ref()?;
+        if config.max_duration_ms.is_some_and(|max| self.duration_ms() >= max) {
+            Some(AutoStopReason::MaxDuration)
+        } else if config.stop_at.is_some_and(|at| now >= at) {
+            Some(AutoStopReason::Scheduled)
+        } else {
+            None
+        }
+    }
+    
+    /// Report a recording stopped by `auto_stop_reason` to subscribers
+    pub fn report_auto_stop(&self, reason: AutoStopReason, result: RecordingOutput) {
+        let _ = self.event_tx.send(RecordingEvent::AutoStopped {
+            reason,
+            result: Box::new(result),
+        });
+    }
+    
+    /// Poll every channel's health and report changes for the worse as
+    /// `ChannelDegraded`/`ChannelFailed` events
+    pub fn check_channel_health(&mut self) {
+        for i in 0..self.channels.len() {
+            if self.channel_health.get(i).is_some_and(|record| record.isolated) {
+                continue;
+            }
+            let health = self.channels[i].health();
+            self.set_health(i, health);
+        }
+    }
+    
+    fn set_health(&mut self, i: usize, health: ChannelHealth) {
+        let Some(record) = self.channel_health.get_mut(i) else {
+            return;
+        };
+        if record.current == health {
+            return;
+        }
+        let channel = self.channels[i].id().to_string();
+        let event = match &health {
+            ChannelHealth::Healthy => {
+                tracing::info!("Channel {} recovered", channel);
+                None
+            }
+            ChannelHealth::Degraded { reason } => {
+                tracing::warn!("Channel {} degraded: {}", channel, reason);
+                Some(RecordingEvent::ChannelDegraded {
+                    channel,
+                    reason: reason.clone(),
+                })
+            }
+            ChannelHealth::Failed { reason } => {
+                tracing::error!("Channel {} failed: {}", channel, reason);
+                Some(RecordingEvent::ChannelFailed {
+                    channel,
+                    reason: reason.clone(),
+                })
+            }
+        };
+        record.worst = std::mem::take(&mut record.worst).worst(health.clone());
+        record.current = health;
+        if let Some(event) = event {
+            let _ = self.event_tx.send(event);
+        }
+    }
+    
+    /// Report of every channel for the recording result
+    fn channel_statuses(&self) -> Vec<ChannelStatus> {
+        self.channels
+            .iter()
+            .enumerate()
+            .map(|(i, channel)| ChannelStatus {
+                id: channel.id().to_string(),
+                channel_type: channel.channel_type(),
+                health: self
+                    .channel_health
+                    .get(i)
+                    .map(|record| record.worst.clone())
+                    .unwrap_or_default(),
+                output_files: channel.output_files().len(),
+                settings: channel.settings(),
+            })
+            .collect()
+    }
+    
     /// Get recording duration in milliseconds
     pub fn duration_ms(&self) -> f64 {
         let completed: f64 = self.sessions.iter()
@@ -253,15 +769,819 @@ impl RecordingCoordinator {
         
         completed + current
     }
+
+    /// Video of the recording in progress, to preview it before stopping.
+    /// `window_ms` limits the preview to the last part of the current
+    /// session, e.g. 30 seconds for a "review last 30 seconds" panel.
+    pub fn live_preview(&self, window_ms: Option<f64>) -> RecordingResult<LivePreview> {
+        let state = self.state();
+        if state != RecordingState::Recording && state != RecordingState::Paused {
+            return Err(RecordingError::NotRecording);
+        }
+        let (Some(output_dir), Some(session)) = (self.output_dir.as_ref(), self.sessions.last())
+        else {
+            return Err(RecordingError::NotRecording);
+        };
+        let capture_screen = self.config.as_ref().is_none_or(|c| c.capture_screen);
+        let recording_dir = output_dir.join("recording");
+        let path = segment::session_video(&recording_dir, session.index, capture_screen)
+            .ok_or_else(|| {
+                RecordingError::CaptureError("No video has been written yet".to_string())
+            })?;
+        
+        let session_duration_ms = if state == RecordingState::Recording {
+            self.process_time_ms() - session.process_time_start_ms
+        } else {
+            session.duration_ms
+        };
+        Ok(LivePreview {
+            path: path.to_string_lossy().to_string(),
+            session_index: session.index,
+            session_duration_ms,
+            start_ms: segment::preview_start_ms(session_duration_ms, window_ms),
+            recording_duration_ms: self.duration_ms(),
+        })
+    }
+
+    /// Flag the current moment of the recording with a marker, saved to the
+    /// bundle's markers.json right away so it survives a crash. The time is
+    /// on the recording clock, so paused time isn't counted.
+    pub fn add_marker(&self, label: String) -> RecordingResult<Marker> {
+        let state = self.state();
+        if state != RecordingState::Recording && state != RecordingState::Paused {
+            return Err(RecordingError::NotRecording);
+        }
+        let Some(output_dir) = self.output_dir.as_ref() else {
+            return Err(RecordingError::NotRecording);
+        };
+
+        let marker = Marker {
+            id: uuid::Uuid::new_v4().to_string(),
+            time: self.duration_ms(),
+            label,
+            color: None,
+        };
+        let to_io = |e: BundleError| std::io::Error::other(e.to_string());
+        let mut markers = read_markers(output_dir).map_err(to_io)?;
+        markers.push(marker.clone());
+        write_markers(&markers, output_dir).map_err(to_io)?;
+
+        tracing::info!("Added marker '{}' at {:.0}ms", marker.label, marker.time);
+        let _ = self.event_tx.send(RecordingEvent::MarkerAdded(marker.clone()));
+        Ok(marker)
+    }
     
     /// Clear all channels
     pub fn clear_channels(&mut self) {
         self.channels.clear();
+        self.channel_times.clear();
+        self.channel_anchors.clear();
+        self.channel_health.clear();
+    }
+    
+    /// Write `manifest.json` and `sessions.json` into the recording directory
+    fn write_manifest(&self, total_duration_ms: f64) -> std::io::Result<()> {
+        let Some(output_dir) = self.output_dir.as_ref() else {
+            return Ok(());
+        };
+        let recording_dir = output_dir.join("recording");
+        
+        let channels = self
+            .channels
+            .iter()
+            .enumerate()
+            .map(|(i, channel)| {
+                let (started_at_ms, stopped_at_ms) =
+                    self.channel_times.get(i).copied().unwrap_or((None, None));
+                ChannelManifest {
+                    id: channel.id().to_string(),
+                    channel_type: channel.channel_type(),
+                    files: channel
+                        .output_files()
+                        .iter()
+                        .map(|f| RecordingManifest::file_entry(&recording_dir, f))
+                        .collect(),
+                    started_at_ms,
+                    stopped_at_ms,
+                    settings: channel.settings(),
+                    dropped_frames: channel.dropped_frames(),
+                    anchors: self.channel_anchors.get(i).cloned().unwrap_or_default(),
+                }
+            })
+            .collect();
+        
+        let manifest = RecordingManifest {
+            version: MANIFEST_VERSION,
+            created_at: chrono::Utc::now(),
+            total_duration_ms,
+            sessions: self.sessions.clone(),
+            channels,
+            microphone_offset_ms: self.config.as_ref().and_then(|c| c.microphone_offset_ms),
+            display_name: self.config.as_ref().and_then(|c| c.display_name.clone()),
+            frontmost_app: self.config.as_ref().and_then(|c| c.frontmost_app.clone()),
+        };
+        
+        let path = manifest.write(&recording_dir)?;
+        tracing::info!("Wrote recording manifest: {:?}", path);
+        SessionList::from_manifest(&manifest).write(&recording_dir)?;
+        Ok(())
     }
 }
 
+/// React to display configuration changes reported by display channels
+/// until the recording stops: a display that changed resolution starts a new
+/// session, so capture restarts at the new size, and an unplugged one pauses
+/// the recording so everything recorded so far is finalized.
+pub fn watch_display_changes(
+    coordinator: Arc<Mutex<RecordingCoordinator>>,
+    mut events: broadcast::Receiver<RecordingEvent>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            match event {
+                RecordingEvent::DisplayChanged {
+                    channel,
+                    display: display_id,
+                    width,
+                    height,
+                } => {
+                    let mut coordinator = coordinator.lock().await;
+                    if coordinator.state() != RecordingState::Recording {
+                        continue;
+                    }
+                    tracing::info!("{}: display {} is now {}x{}", channel, display_id, width, height);
+                    let boundary = SessionBoundary::DisplayChanged {
+                        display: display_id.clone(),
+                        width,
+                        height,
+                    };
+                    if let Err(e) = coordinator.split_session(boundary).await {
+                        let _ = coordinator.event_tx.send(RecordingEvent::Error(format!(
+                            "Display {} changed resolution and capture could not restart: {}",
+                            display_id, e
+                        )));
+                    }
+                }
+                RecordingEvent::DisplayDisconnected {
+                    channel,
+                    display: display_id,
+                } => {
+                    let mut coordinator = coordinator.lock().await;
+                    if coordinator.state() != RecordingState::Recording {
+                        continue;
+                    }
+                    tracing::warn!("{}: display {} disconnected, pausing", channel, display_id);
+                    if let Err(e) = coordinator.pause().await {
+                        tracing::error!("Failed to pause after display disconnect: {}", e);
+                    }
+                    let _ = coordinator.event_tx.send(RecordingEvent::Error(format!(
+                        "Display {} was disconnected. The recording was paused; stop it to keep what was recorded.",
+                        display_id
+                    )));
+                }
+                RecordingEvent::Stopped => break,
+                _ => {}
+            }
+        }
+    })
+}
+
+/// Current Unix time in milliseconds
+fn unix_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
 impl Default for RecordingCoordinator {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::manifest::FileKind;
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Clock that only moves when the test advances it
+    struct FakeClock {
+        origin: Instant,
+        elapsed: parking_lot::Mutex<Duration>,
+    }
+
+    impl FakeClock {
+        fn new() -> Arc<Self> {
+            Arc::new(Self {
+                origin: Instant::now(),
+                elapsed: parking_lot::Mutex::new(Duration::ZERO),
+            })
+        }
+
+        fn advance(&self, ms: u64) {
+            *self.elapsed.lock() += Duration::from_millis(ms);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.origin + *self.elapsed.lock()
+        }
+    }
+
+    /// Channel that writes one file per session containing how long it recorded
+    struct FakeAudioChannel {
+        clock: Arc<dyn Clock>,
+        output_dir: Option<PathBuf>,
+        session_index: usize,
+        started: Option<Instant>,
+        output_files: Vec<String>,
+    }
+
+    impl FakeAudioChannel {
+        fn new(clock: Arc<dyn Clock>) -> Self {
+            Self {
+                clock,
+                output_dir: None,
+                session_index: 0,
+                started: None,
+                output_files: Vec::new(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl RecordingChannel for FakeAudioChannel {
+        fn id(&self) -> &str {
+            "microphone"
+        }
+
+        fn channel_type(&self) -> ChannelType {
+            ChannelType::Microphone
+        }
+
+        async fn initialize(&mut self, output_dir: &std::path::Path, session_index: usize) -> RecordingResult<()> {
+            self.output_dir = Some(output_dir.to_path_buf());
+            self.session_index = session_index;
+            Ok(())
+        }
+
+        async fn start(&mut self) -> RecordingResult<()> {
+            if self.started.is_some() {
+                return Err(RecordingError::AlreadyRecording);
+            }
+            self.started = Some(self.clock.now());
+            Ok(())
+        }
+
+        // Like the real audio channels, stopping twice is an error
+        async fn stop(&mut self) -> RecordingResult<()> {
+            let started = self.started.take().ok_or(RecordingError::NotRecording)?;
+            let path = self
+                .output_dir
+                .as_ref()
+                .unwrap()
+                .join(FileKind::MicAudio.conventional_name(self.session_index));
+            if path.exists() {
+                return Err(RecordingError::CaptureError(format!("{:?} would be overwritten", path)));
+            }
+            let recorded = self.clock.now().duration_since(started);
+            std::fs::write(&path, recorded.as_millis().to_string())?;
+            self.output_files.push(path.to_string_lossy().to_string());
+            Ok(())
+        }
+
+        async fn pause(&mut self) -> RecordingResult<()> {
+            self.stop().await
+        }
+
+        async fn resume(&mut self, session_index: usize) -> RecordingResult<()> {
+            self.session_index = session_index;
+            self.start().await
+        }
+
+        fn is_recording(&self) -> bool {
+            self.started.is_some()
+        }
+
+        fn output_files(&self) -> Vec<String> {
+            self.output_files.clone()
+        }
+    }
+
+    /// Webcam whose health is set by the test and that can't resume
+    struct FlakyWebcamChannel {
+        recording: bool,
+        health: Arc<parking_lot::Mutex<ChannelHealth>>,
+    }
+
+    #[async_trait]
+    impl RecordingChannel for FlakyWebcamChannel {
+        fn id(&self) -> &str {
+            "webcam"
+        }
+
+        fn channel_type(&self) -> ChannelType {
+            ChannelType::Webcam
+        }
+
+        async fn initialize(&mut self, _output_dir: &std::path::Path, _session_index: usize) -> RecordingResult<()> {
+            Ok(())
+        }
+
+        async fn start(&mut self) -> RecordingResult<()> {
+            self.recording = true;
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> RecordingResult<()> {
+            if !self.recording {
+                return Err(RecordingError::NotRecording);
+            }
+            self.recording = false;
+            Ok(())
+        }
+
+        async fn pause(&mut self) -> RecordingResult<()> {
+            self.stop().await
+        }
+
+        async fn resume(&mut self, _session_index: usize) -> RecordingResult<()> {
+            Err(RecordingError::DeviceNotFound("Camera unplugged".to_string()))
+        }
+
+        fn is_recording(&self) -> bool {
+            self.recording
+        }
+
+        fn output_files(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn health(&self) -> ChannelHealth {
+            self.health.lock().clone()
+        }
+    }
+
+    /// Input tracking without Accessibility
+    struct DeniedInputChannel;
+
+    #[async_trait]
+    impl RecordingChannel for DeniedInputChannel {
+        fn id(&self) -> &str {
+            "input"
+        }
+
+        fn channel_type(&self) -> ChannelType {
+            ChannelType::Input
+        }
+
+        async fn initialize(&mut self, _output_dir: &std::path::Path, _session_index: usize) -> RecordingResult<()> {
+            Err(RecordingError::PermissionDenied("Accessibility".to_string()))
+        }
+
+        async fn start(&mut self) -> RecordingResult<()> {
+            panic!("started without being initialized");
+        }
+
+        async fn stop(&mut self) -> RecordingResult<()> {
+            Err(RecordingError::NotRecording)
+        }
+
+        async fn pause(&mut self) -> RecordingResult<()> {
+            Err(RecordingError::NotRecording)
+        }
+
+        async fn resume(&mut self, _session_index: usize) -> RecordingResult<()> {
+            Err(RecordingError::NotRecording)
+        }
+
+        fn is_recording(&self) -> bool {
+            false
+        }
+
+        fn output_files(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    /// System audio that fails to start or stop, as the test chooses
+    struct BrokenSystemAudioChannel {
+        fail_start: bool,
+        recording: bool,
+    }
+
+    #[async_trait]
+    impl RecordingChannel for BrokenSystemAudioChannel {
+        fn id(&self) -> &str {
+            "system_audio"
+        }
+
+        fn channel_type(&self) -> ChannelType {
+            ChannelType::SystemAudio
+        }
+
+        async fn initialize(&mut self, _output_dir: &std::path::Path, _session_index: usize) -> RecordingResult<()> {
+            Ok(())
+        }
+
+        async fn start(&mut self) -> RecordingResult<()> {
+            if self.fail_start {
+                return Err(RecordingError::CaptureError("Tap failed".to_string()));
+            }
+            self.recording = true;
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> RecordingResult<()> {
+            self.recording = false;
+            Err(RecordingError::EncodingError("Encoder crashed".to_string()))
+        }
+
+        async fn pause(&mut self) -> RecordingResult<()> {
+            self.stop().await
+        }
+
+        async fn resume(&mut self, _session_index: usize) -> RecordingResult<()> {
+            self.start().await
+        }
+
+        fn is_recording(&self) -> bool {
+            self.recording
+        }
+
+        fn output_files(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    fn test_config(output_dir: &std::path::Path) -> RecordingConfig {
+        serde_json::from_value(serde_json::json!({
+            "displayId": 1,
+            "captureSystemAudio": false,
+            "captureMicrophone": true,
+            "microphoneDeviceId": null,
+            "captureWebcam": false,
+            "webcamDeviceId": null,
+            "trackInput": false,
+            "outputDir": output_dir.to_string_lossy(),
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_optional_channel_failing_to_initialize_is_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+        coordinator.add_channel(Box::new(DeniedInputChannel));
+        let mut events = coordinator.subscribe();
+
+        coordinator.start(test_config(dir.path())).await.unwrap();
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            RecordingEvent::ChannelFailed { channel, .. } if channel == "input"
+        ));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let output = coordinator.stop().await.unwrap();
+        assert_eq!(output.output_files.len(), 1);
+        assert!(matches!(output.channels[1].health, ChannelHealth::Failed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_failed_start_stops_started_channels_and_unlocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+        coordinator.add_channel(Box::new(BrokenSystemAudioChannel {
+            fail_start: true,
+            recording: false,
+        }));
+
+        assert!(matches!(
+            coordinator.start(test_config(dir.path())).await,
+            Err(RecordingError::CaptureError(_))
+        ));
+        assert_eq!(coordinator.state(), RecordingState::Idle);
+        let recording_dir = dir.path().join("recording");
+        assert!(recording_dir.join(FileKind::MicAudio.conventional_name(0)).exists());
+        assert!(!recording_dir.join(crate::recorder::recovery::LOCK_FILE).exists());
+        assert!(coordinator.take_lock().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_stop_still_stops_every_channel() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.add_channel(Box::new(BrokenSystemAudioChannel {
+            fail_start: false,
+            recording: false,
+        }));
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+        let mut events = coordinator.subscribe();
+
+        coordinator.start(test_config(dir.path())).await.unwrap();
+        assert!(matches!(
+            coordinator.stop().await,
+            Err(RecordingError::EncodingError(_))
+        ));
+        assert_eq!(coordinator.state(), RecordingState::Idle);
+        let recording_dir = dir.path().join("recording");
+        assert!(recording_dir.join(FileKind::MicAudio.conventional_name(0)).exists());
+        // Still locked, so it can be recovered
+        assert!(recording_dir.join(crate::recorder::recovery::LOCK_FILE).exists());
+        let mut stopped = false;
+        while let Ok(event) = events.try_recv() {
+            stopped |= matches!(event, RecordingEvent::Stopped);
+        }
+        assert!(stopped);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_pause_resume_writes_every_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = FakeClock::new();
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.set_clock(clock.clone());
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(clock.clone())));
+
+        coordinator.start(test_config(dir.path())).await.unwrap();
+        for _ in 0..3 {
+            clock.advance(30);
+            coordinator.pause().await.unwrap();
+            // Resume immediately, as a double-tapped hotkey would
+            coordinator.resume().await.unwrap();
+        }
+        clock.advance(30);
+        coordinator.pause().await.unwrap();
+        clock.advance(500);
+
+        // Stopping while paused must not stop channels twice or count the pause
+        let output = coordinator.stop().await.unwrap();
+        assert_eq!(output.session_count, 4);
+        assert_eq!(output.output_files.len(), 4);
+        // Times only move with the fake clock, up to rounding
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+        assert!(close(output.total_duration_ms, 120.0));
+
+        let recording_dir = dir.path().join("recording");
+        let manifest = RecordingManifest::read(&recording_dir).unwrap();
+        for (index, session) in manifest.sessions.iter().enumerate() {
+            assert_eq!(session.index, index);
+            let file = manifest.find(FileKind::MicAudio, index).unwrap();
+            let recorded_ms: f64 = std::fs::read_to_string(recording_dir.join(&file.path))
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(recorded_ms, 30.0);
+            assert!(close(session.duration_ms, 30.0));
+            
+            // The channel's capture is anchored on the sessions' clock
+            let anchor = manifest.anchor(FileKind::MicAudio, index).unwrap();
+            assert!(close(anchor.start_ms, session.process_time_start_ms));
+            assert!(close(anchor.stop_ms.unwrap() - anchor.start_ms, 30.0));
+        }
+        
+        // Each resume's files are listed under its own session
+        let sessions = SessionList::read(&recording_dir).unwrap();
+        assert_eq!(sessions.sessions.len(), 4);
+        for (index, session) in sessions.sessions.iter().enumerate() {
+            let name = FileKind::MicAudio.conventional_name(index);
+            assert_eq!(session.file(FileKind::MicAudio), Some(name.as_str()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_display_change_starts_new_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let coordinator = Arc::new(Mutex::new(RecordingCoordinator::new()));
+        let events = {
+            let mut coordinator = coordinator.lock().await;
+            coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+            coordinator.start(test_config(dir.path())).await.unwrap();
+            coordinator.subscribe()
+        };
+        let watcher = watch_display_changes(coordinator.clone(), events);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let event_tx = coordinator.lock().await.event_tx.clone();
+        event_tx
+            .send(RecordingEvent::DisplayChanged {
+                channel: "display".to_string(),
+                display: "1".to_string(),
+                width: 1920,
+                height: 1080,
+            })
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let output = coordinator.lock().await.stop().await.unwrap();
+        watcher.await.unwrap();
+        assert_eq!(output.session_count, 2);
+
+        let manifest = RecordingManifest::read(&dir.path().join("recording")).unwrap();
+        assert_eq!(manifest.sessions[0].boundary, None);
+        assert_eq!(
+            manifest.sessions[1].boundary,
+            Some(SessionBoundary::DisplayChanged {
+                display: "1".to_string(),
+                width: 1920,
+                height: 1080,
+            })
+        );
+    }
+
+    /// Cue player that records which cues were played
+    #[derive(Default)]
+    struct RecordingCuePlayer {
+        played: parking_lot::Mutex<Vec<Cue>>,
+    }
+
+    impl CuePlayer for RecordingCuePlayer {
+        fn play(&self, cue: Cue, _config: &crate::recorder::cues::CueConfig) {
+            self.played.lock().push(cue);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_live_marker_uses_recording_clock() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+        assert!(coordinator.add_marker("Too early".to_string()).is_err());
+
+        coordinator.start(test_config(dir.path())).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        coordinator.pause().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        // Paused time doesn't move the marker
+        let mut events = coordinator.subscribe();
+        let marker = coordinator.add_marker("Demo".to_string()).unwrap();
+        assert!(marker.time >= 50.0 && marker.time < 200.0);
+        assert!(matches!(
+            events.try_recv(),
+            Ok(RecordingEvent::MarkerAdded(added)) if added.id == marker.id
+        ));
+        coordinator.stop().await.unwrap();
+
+        let saved = read_markers(dir.path()).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].label, "Demo");
+    }
+
+    #[tokio::test]
+    async fn test_cues_play_only_when_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let player = Arc::new(RecordingCuePlayer::default());
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.set_cue_player(player.clone());
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+
+        coordinator.start(test_config(dir.path())).await.unwrap();
+        coordinator.stop().await.unwrap();
+        assert!(player.played.lock().is_empty());
+
+        let mut config = test_config(dir.path());
+        config.cues.sounds = true;
+        coordinator.clear_channels();
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+        std::fs::remove_dir_all(dir.path().join("recording")).unwrap();
+
+        coordinator.start(config).await.unwrap();
+        coordinator.pause().await.unwrap();
+        coordinator.resume().await.unwrap();
+        coordinator.stop().await.unwrap();
+        assert_eq!(*player.played.lock(), vec![Cue::Start, Cue::Pause, Cue::Resume, Cue::Stop]);
+    }
+
+    #[tokio::test]
+    async fn test_camera_only_config_needs_channels() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(dir.path());
+        config.capture_screen = false;
+        config.capture_microphone = false;
+        assert!(config.validate().is_err());
+        // Audio-only
+        config.capture_microphone = true;
+        assert!(config.validate().is_ok());
+        config.capture_microphone = false;
+        config.capture_webcam = true;
+        assert!(config.validate().is_ok());
+
+        let mut coordinator = RecordingCoordinator::new();
+        assert!(matches!(
+            coordinator.start(config).await,
+            Err(RecordingError::ConfigurationError(_))
+        ));
+        assert_eq!(coordinator.state(), RecordingState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_failed_webcam_does_not_stop_recording() {
+        let dir = tempfile::tempdir().unwrap();
+        let health = Arc::new(parking_lot::Mutex::new(ChannelHealth::Healthy));
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+        coordinator.add_channel(Box::new(FlakyWebcamChannel {
+            recording: false,
+            health: health.clone(),
+        }));
+        let mut events = coordinator.subscribe();
+
+        coordinator.start(test_config(dir.path())).await.unwrap();
+        *health.lock() = ChannelHealth::Degraded {
+            reason: "No frames".to_string(),
+        };
+        coordinator.check_channel_health();
+        coordinator.check_channel_health();
+
+        // The webcam can't resume; the recording carries on without it
+        coordinator.pause().await.unwrap();
+        coordinator.resume().await.unwrap();
+        let output = coordinator.stop().await.unwrap();
+        assert_eq!(output.session_count, 2);
+        assert_eq!(output.channels[0].health, ChannelHealth::Healthy);
+        assert_eq!(output.channels[0].output_files, 2);
+        assert_eq!(
+            output.channels[1].health,
+            ChannelHealth::Failed {
+                reason: "Device not found: Camera unplugged".to_string()
+            }
+        );
+
+        let mut warnings = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event {
+                RecordingEvent::ChannelDegraded { channel, .. } => {
+                    warnings.push(format!("degraded {}", channel))
+                }
+                RecordingEvent::ChannelFailed { channel, .. } => {
+                    warnings.push(format!("failed {}", channel))
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(warnings, vec!["degraded webcam", "failed webcam"]);
+    }
+
+    #[tokio::test]
+    async fn test_auto_stop_at_max_duration_or_stop_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut coordinator = RecordingCoordinator::new();
+        coordinator.add_channel(Box::new(FakeAudioChannel::new(Arc::new(SystemClock))));
+        let now = Utc::now();
+        let mut config = test_config(dir.path());
+        config.max_duration_ms = Some(60_000.0);
+        config.stop_at = Some(now + chrono::Duration::hours(1));
+
+        coordinator.start(config).await.unwrap();
+        assert_eq!(coordinator.auto_stop_reason(now), None);
+        assert_eq!(
+            coordinator.auto_stop_reason(now + chrono::Duration::hours(2)),
+            Some(AutoStopReason::Scheduled)
+        );
+
+        coordinator.config.as_mut().unwrap().max_duration_ms = Some(1.0);
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        assert_eq!(coordinator.auto_stop_reason(now), Some(AutoStopReason::MaxDuration));
+
+        coordinator.stop().await.unwrap();
+        assert_eq!(coordinator.auto_stop_reason(now + chrono::Duration::hours(2)), None);
+    }
+
+    #[test]
+    fn test_events_serialize_for_the_frontend() {
+        let json = |event: RecordingEvent| serde_json::to_value(event).unwrap();
+        assert_eq!(json(RecordingEvent::Started), serde_json::json!({"type": "started"}));
+        assert_eq!(
+            json(RecordingEvent::Progress(1520.0)),
+            serde_json::json!({"type": "progress", "data": 1520.0})
+        );
+        assert_eq!(
+            json(RecordingEvent::DeviceLost {
+                channel: "microphone".to_string(),
+                device: "USB Mic".to_string(),
+            }),
+            serde_json::json!({
+                "type": "deviceLost",
+                "data": {"channel": "microphone", "device": "USB Mic"}
+            })
+        );
+        assert_eq!(
+            json(RecordingEvent::DeviceRecovered {
+                channel: "webcam".to_string(),
+                device: "FaceTime HD Camera".to_string(),
+                gap_ms: 2500.0,
+            }),
+            serde_json::json!({
+                "type": "deviceRecovered",
+                "data": {"channel": "webcam", "device": "FaceTime HD Camera", "gapMs": 2500.0}
+            })
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Recording feedback cues
+//!
+//! Short sounds (and trackpad haptics where available) played when a
+//! recording starts, stops, pauses or resumes. Cues are triggered by the
+//! coordinator rather than the UI, so hotkey-triggered recordings give the
+//! same feedback. They are played while no channel is capturing, so they
+//! can't end up in the recorded system audio.
+
+use serde::{Deserialize, Serialize};
+
+/// Recording transitions that have a cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    Start,
+    Stop,
+    Pause,
+    Resume,
+}
+
+/// Which cues are played
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CueConfig {
+    /// Play a sound on start/stop/pause/resume
+    #[serde(default)]
+    pub sounds: bool,
+    /// Trigger trackpad haptic feedback (macOS)
+    #[serde(default)]
+    pub haptics: bool,
+}
+
+impl CueConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.sounds || self.haptics
+    }
+}
+
+/// Plays cues. `play` blocks until the sound has finished so callers can
+/// start capturing right after it.
+pub trait CuePlayer: Send + Sync {
+    fn play(&self, cue: Cue, config: &CueConfig);
+}
+
+/// Peak amplitude of cue tones
+const CUE_AMPLITUDE: f32 = 0.25;
+
+/// Fade at each end of a tone, avoiding clicks
+const CUE_FADE_MS: u32 = 5;
+
+/// Notes of a cue as (frequency in Hz, duration in ms). Starting is a rising
+/// pair and stopping a falling one.
+pub fn cue_notes(cue: Cue) -> &'static [(f32, u32)] {
+    match cue {
+        Cue::Start => &[(660.0, 80), (880.0, 120)],
+        Cue::Stop => &[(880.0, 80), (660.0, 120)],
+        Cue::Pause => &[(660.0, 100)],
+        Cue::Resume => &[(880.0, 100)],
+    }
+}
+
+/// Render a cue as interleaved f32 samples
+pub fn synthesize(cue: Cue, sample_rate: u32, channels: u16) -> Vec<f32> {
+    let fade = (sample_rate * CUE_FADE_MS / 1000).max(1) as usize;
+    let mut samples = Vec::new();
+
+    for &(frequency, duration_ms) in cue_notes(cue) {
+        let frames = (sample_rate as u64 * duration_ms as u64 / 1000) as usize;
+        for i in 0..frames {
+            let envelope = (i.min(frames - 1 - i) as f32 / fade as f32).min(1.0);
+            let t = i as f32 / sample_rate as f32;
+            let value = (t * frequency * std::f32::consts::TAU).sin() * CUE_AMPLITUDE * envelope;
+            samples.extend(std::iter::repeat_n(value, channels.max(1) as usize));
+        }
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthesized_cue_fades_in_and_out() {
+        let samples = synthesize(Cue::Start, 48_000, 2);
+        // 200ms of stereo audio
+        assert_eq!(samples.len(), 48 * 200 * 2);
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[0], samples[1]);
+        assert!(samples.iter().all(|s| s.abs() <= CUE_AMPLITUDE));
+        assert!(samples.last().unwrap().abs() < 0.01);
+    }
+}
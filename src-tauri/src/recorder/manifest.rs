@@ -0,0 +1,700 @@
+//! Recording manifest
+//!
+//! `RecordingCoordinator::stop` writes a `manifest.json` next to the recorded
+//! files describing every channel, the files it produced, timing, capture
+//! settings and dropped frames. Loaders use it to find files instead of relying
+//! on the `recording-{session}-{suffix}` naming convention, which remains the
+//! fallback for bundles recorded before the manifest existed.
+
+use super::channel::ChannelType;
+use super::state::RecordingSession;
+use crate::capture::frame::{window_point, ContentRect, WindowResizeEvent};
+use crate::capture::input::types::{MouseClick, MouseMove};
+use crate::project::paths;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Manifest file name inside the bundle's `recording/` directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Current manifest format version
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Kind of file produced by a recording channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileKind {
+    ScreenVideo,
+    WebcamVideo,
+    MicAudio,
+    SystemAudio,
+    MouseMoves,
+    MouseClicks,
+    Cursors,
+    Annotations,
+    WindowResizes,
+    AppActivity,
+    Windows,
+    Keystrokes,
+}
+
+impl FileKind {
+    const ALL: [FileKind; 12] = [
+        FileKind::ScreenVideo,
+        FileKind::WebcamVideo,
+        FileKind::MicAudio,
+        FileKind::SystemAudio,
+        FileKind::MouseMoves,
+        FileKind::MouseClicks,
+        FileKind::Cursors,
+        FileKind::Annotations,
+        FileKind::WindowResizes,
+        FileKind::AppActivity,
+        FileKind::Windows,
+        FileKind::Keystrokes,
+    ];
+
+    /// File name suffix after `recording-{session}`
+    pub const fn suffix(&self) -> &'static str {
+        match self {
+            FileKind::ScreenVideo => ".mp4",
+            FileKind::WebcamVideo => "-webcam.mp4",
+            FileKind::MicAudio => "-mic.m4a",
+            FileKind::SystemAudio => "-system.m4a",
+            FileKind::MouseMoves => "-mouse-moves.json",
+            FileKind::MouseClicks => "-mouse-clicks.json",
+            FileKind::Cursors => "-cursors.json",
+            FileKind::Annotations => "-annotations.json",
+            FileKind::WindowResizes => "-window-resizes.json",
+            FileKind::AppActivity => "-app-activity.json",
+            FileKind::Windows => "-windows.json",
+            FileKind::Keystrokes => "-keystrokes.json",
+        }
+    }
+
+    /// Kind of channel that records this kind of file
+    pub fn channel_type(&self) -> ChannelType {
+        match self {
+            FileKind::ScreenVideo | FileKind::WindowResizes => ChannelType::Display,
+            FileKind::WebcamVideo => ChannelType::Webcam,
+            FileKind::MicAudio => ChannelType::Microphone,
+            FileKind::SystemAudio => ChannelType::SystemAudio,
+            FileKind::MouseMoves | FileKind::MouseClicks | FileKind::Cursors => ChannelType::Input,
+            FileKind::Annotations => ChannelType::Annotation,
+            FileKind::AppActivity | FileKind::Windows => ChannelType::AppActivity,
+            FileKind::Keystrokes => ChannelType::Keyboard,
+        }
+    }
+
+    /// File name for a session. Channels name their files with it, and
+    /// loaders fall back to it when there is no manifest.
+    pub fn conventional_name(&self, session: usize) -> String {
+        format!("recording-{}{}", session, self.suffix())
+    }
+
+    /// Classify a file name produced by a channel into (kind, session)
+    pub fn classify(file_name: &str) -> Option<(FileKind, usize)> {
+        let rest = file_name.strip_prefix("recording-")?;
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let session = rest[..digits].parse().ok()?;
+        let suffix = &rest[digits..];
+
+        FileKind::ALL
+            .into_iter()
+            .find(|kind| kind.suffix() == suffix)
+            .map(|kind| (kind, session))
+    }
+}
+
+/// Capture settings reported by a channel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+    /// Rate the input device was opened at, when it differs from
+    /// `sample_rate` (the audio was resampled to it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_sample_rate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate_kbps: Option<u32>,
+    /// Part of the display recorded, in display coordinates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<ContentRect>,
+    /// `region` on the captured frames, in captured pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crop: Option<ContentRect>,
+    /// Factor frames were scaled down by before encoding (the Performance
+    /// quality preset); width and height are the captured size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downscale: Option<u32>,
+    /// Frames per second actually captured, when capture couldn't keep up
+    /// with `fps` or `fps` followed the display's refresh rate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub achieved_fps: Option<f64>,
+    /// Whether the system cursor was recorded into the video's frames
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shows_cursor: bool,
+}
+
+/// A file written by a channel, relative to the manifest's directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFile {
+    pub path: String,
+    pub session: usize,
+    pub kind: Option<FileKind>,
+}
+
+/// Per-channel entry in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelManifest {
+    pub id: String,
+    pub channel_type: ChannelType,
+    pub files: Vec<ManifestFile>,
+    /// Unix time the channel first started recording
+    pub started_at_ms: Option<u64>,
+    /// Unix time the channel was finally stopped
+    pub stopped_at_ms: Option<u64>,
+    pub settings: ChannelSettings,
+    pub dropped_frames: Option<u64>,
+    /// When the channel captured in each session, for lining its media up
+    /// with the other channels' at export
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anchors: Vec<ChannelAnchor>,
+}
+
+/// When a channel captured during a session, on the recording's monotonic
+/// clock: milliseconds since the recording started, the clock session
+/// process times use. Every channel's anchors share it, unlike the clocks of
+/// the devices they capture from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelAnchor {
+    pub session: usize,
+    /// When the channel's capture was running
+    pub start_ms: f64,
+    /// When the channel was asked to stop capturing
+    pub stop_ms: Option<f64>,
+}
+
+/// Description of everything recorded into a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingManifest {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub total_duration_ms: f64,
+    pub sessions: Vec<RecordingSession>,
+    pub channels: Vec<ChannelManifest>,
+    /// Microphone offset new projects start with, from the display's A/V
+    /// calibration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub microphone_offset_ms: Option<f64>,
+    /// Recorded display and frontmost application, used to name the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontmost_app: Option<String>,
+}
+
+impl RecordingManifest {
+    /// Build a manifest file entry from an absolute output path
+    pub fn file_entry(recording_dir: &Path, output_file: &str) -> ManifestFile {
+        let path = Path::new(output_file);
+        let relative = paths::to_relative(recording_dir, path);
+        let classified = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(FileKind::classify);
+
+        ManifestFile {
+            path: relative,
+            session: classified.map(|(_, s)| s).unwrap_or(0),
+            kind: classified.map(|(k, _)| k),
+        }
+    }
+
+    /// Find the file of a given kind for a session
+    pub fn find(&self, kind: FileKind, session: usize) -> Option<&ManifestFile> {
+        self.channels
+            .iter()
+            .flat_map(|c| c.files.iter())
+            .find(|f| f.kind == Some(kind) && f.session == session)
+    }
+
+    /// Factor the screen video was scaled down by, which mouse positions
+    /// (recorded in captured pixels) need dividing by as well
+    pub fn display_downscale(&self) -> u32 {
+        self.channels
+            .iter()
+            .find(|c| c.channel_type == ChannelType::Display)
+            .and_then(|c| c.settings.downscale)
+            .unwrap_or(1)
+            .max(1)
+    }
+
+    /// Bring mouse positions, recorded in captured display pixels, onto the
+    /// screen video, which may show only a region of the display or a single
+    /// window (placed by its `window_resizes`), and may have been scaled
+    /// down. Every loader of mouse data goes through this, so the editor and
+    /// export agree on where the cursor is.
+    pub fn map_mouse_to_video(
+        &self,
+        window_resizes: &[WindowResizeEvent],
+        moves: &mut [MouseMove],
+        clicks: &mut [MouseClick],
+    ) {
+        let Some(settings) = self.display_settings() else {
+            return;
+        };
+        if !window_resizes.is_empty() {
+            for m in moves.iter_mut() {
+                (m.x, m.y) = window_point(window_resizes, m.process_time_ms, m.x, m.y);
+            }
+            for c in clicks.iter_mut() {
+                (c.x, c.y) = window_point(window_resizes, c.process_time_ms, c.x, c.y);
+            }
+        }
+        let (x0, y0) = settings.crop.map_or((0.0, 0.0), |c| (c.x as f64, c.y as f64));
+        let n = settings.downscale.unwrap_or(1).max(1) as f64;
+        if (x0, y0, n) == (0.0, 0.0, 1.0) {
+            return;
+        }
+        let map = |x: &mut f64, y: &mut f64| {
+            *x = (*x - x0) / n;
+            *y = (*y - y0) / n;
+        };
+        for m in moves.iter_mut() {
+            map(&mut m.x, &mut m.y);
+        }
+        for c in clicks.iter_mut() {
+            map(&mut c.x, &mut c.y);
+        }
+    }
+
+    fn display_settings(&self) -> Option<&ChannelSettings> {
+        self.channels
+            .iter()
+            .find(|c| c.channel_type == ChannelType::Display)
+            .map(|c| &c.settings)
+    }
+
+    /// Whether the screen video already shows the system cursor, so export
+    /// shouldn't draw another
+    pub fn display_shows_cursor(&self) -> bool {
+        self.channels
+            .iter()
+            .find(|c| c.channel_type == ChannelType::Display)
+            .is_some_and(|c| c.settings.shows_cursor)
+    }
+
+    /// Frame rate the screen video plays back at: the rate capture achieved,
+    /// never above the rate it was asked for
+    pub fn display_fps(&self) -> Option<f64> {
+        let settings = &self
+            .channels
+            .iter()
+            .find(|c| c.channel_type == ChannelType::Display)?
+            .settings;
+        let requested = settings.fps.map(f64::from);
+        match (settings.achieved_fps, requested) {
+            (Some(achieved), Some(requested)) => Some(achieved.round().clamp(1.0, requested)),
+            (Some(achieved), None) => Some(achieved.round().max(1.0)),
+            (None, requested) => requested,
+        }
+    }
+
+    /// Anchor of the channel that recorded the file of a given kind/session
+    pub fn anchor(&self, kind: FileKind, session: usize) -> Option<&ChannelAnchor> {
+        self.channels
+            .iter()
+            .find(|c| {
+                c.files
+                    .iter()
+                    .any(|f| f.kind == Some(kind) && f.session == session)
+            })?
+            .anchors
+            .iter()
+            .find(|a| a.session == session)
+    }
+
+    /// Point the file of a given kind/session at a new path, replacing any
+    /// existing entry. The file is attached to the first channel of
+    /// `channel_type`, or to a new channel if the bundle has none. The
+    /// session's anchor is dropped, since the new file wasn't captured
+    /// against it.
+    pub fn replace_file(&mut self, channel_type: ChannelType, file: ManifestFile) {
+        for channel in &mut self.channels {
+            channel
+                .files
+                .retain(|f| !(f.kind == file.kind && f.session == file.session));
+        }
+
+        match self.channels.iter_mut().find(|c| c.channel_type == channel_type) {
+            Some(channel) => {
+                channel.anchors.retain(|a| a.session != file.session);
+                channel.files.push(file);
+            }
+            None => self.channels.push(ChannelManifest {
+                id: format!("imported-{}", self.channels.len()),
+                channel_type,
+                files: vec![file],
+                started_at_ms: None,
+                stopped_at_ms: None,
+                settings: ChannelSettings::default(),
+                dropped_frames: None,
+                anchors: Vec::new(),
+            }),
+        }
+    }
+
+    /// Read the manifest from a recording directory, if present. File paths
+    /// come back with `/` separators, whichever OS wrote them.
+    pub fn read(recording_dir: &Path) -> Option<Self> {
+        let path = recording_dir.join(MANIFEST_FILE);
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Self>(&content) {
+            Ok(mut manifest) => {
+                manifest.normalize_paths();
+                Some(manifest)
+            }
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable manifest {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Write the manifest into a recording directory, with portable paths
+    pub fn write(&self, recording_dir: &Path) -> std::io::Result<PathBuf> {
+        let mut manifest = self.clone();
+        manifest.normalize_paths();
+        let path = recording_dir.join(MANIFEST_FILE);
+        let content = serde_json::to_string_pretty(&manifest).map_err(std::io::Error::other)?;
+        std::fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    fn normalize_paths(&mut self) {
+        for file in self.channels.iter_mut().flat_map(|c| c.files.iter_mut()) {
+            file.path = paths::normalize(&file.path);
+        }
+    }
+}
+
+/// Resolve a recorded file, preferring the manifest and falling back to the
+/// naming convention. Returns None if the file doesn't exist.
+pub fn resolve_file(
+    recording_dir: &Path,
+    manifest: Option<&RecordingManifest>,
+    kind: FileKind,
+    session: usize,
+) -> Option<PathBuf> {
+    match manifest.and_then(|m| m.find(kind, session)) {
+        Some(file) => paths::resolve(recording_dir, &file.path),
+        None => paths::resolve(recording_dir, &kind.conventional_name(session)),
+    }
+}
+
+/// Resolve the video a session is built around: the screen recording, or
+/// the webcam recording for camera-only bundles
+pub fn resolve_primary_video(
+    recording_dir: &Path,
+    manifest: Option<&RecordingManifest>,
+    session: usize,
+) -> Option<PathBuf> {
+    resolve_file(recording_dir, manifest, FileKind::ScreenVideo, session)
+        .or_else(|| resolve_file(recording_dir, manifest, FileKind::WebcamVideo, session))
+}
+
+/// Resolve the audio an audio-only session is built around: the microphone,
+/// or the system audio when no microphone was recorded
+pub fn resolve_primary_audio(
+    recording_dir: &Path,
+    manifest: Option<&RecordingManifest>,
+    session: usize,
+) -> Option<PathBuf> {
+    resolve_file(recording_dir, manifest, FileKind::MicAudio, session)
+        .or_else(|| resolve_file(recording_dir, manifest, FileKind::SystemAudio, session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_file_names() {
+        assert_eq!(
+            FileKind::classify("recording-0.mp4"),
+            Some((FileKind::ScreenVideo, 0))
+        );
+        assert_eq!(
+            FileKind::classify("recording-12-mouse-moves.json"),
+            Some((FileKind::MouseMoves, 12))
+        );
+        assert_eq!(
+            FileKind::classify("recording-1-system.m4a"),
+            Some((FileKind::SystemAudio, 1))
+        );
+        assert_eq!(FileKind::classify("manifest.json"), None);
+        assert_eq!(FileKind::classify("recording-x.mp4"), None);
+    }
+
+    #[test]
+    fn test_conventional_name_roundtrip() {
+        for kind in FileKind::ALL {
+            let name = kind.conventional_name(3);
+            assert_eq!(FileKind::classify(&name), Some((kind, 3)));
+        }
+    }
+
+    #[test]
+    fn test_resolve_prefers_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("custom-mic.m4a"), b"").unwrap();
+        std::fs::write(dir.path().join("recording-0.mp4"), b"").unwrap();
+
+        let manifest = RecordingManifest {
+            version: MANIFEST_VERSION,
+            created_at: Utc::now(),
+            total_duration_ms: 0.0,
+            sessions: vec![],
+            microphone_offset_ms: None,
+            display_name: None,
+            frontmost_app: None,
+            channels: vec![ChannelManifest {
+                id: "microphone".to_string(),
+                channel_type: ChannelType::Microphone,
+                files: vec![ManifestFile {
+                    path: "custom-mic.m4a".to_string(),
+                    session: 0,
+                    kind: Some(FileKind::MicAudio),
+                }],
+                started_at_ms: None,
+                stopped_at_ms: None,
+                settings: ChannelSettings::default(),
+                dropped_frames: None,
+                anchors: Vec::new(),
+            }],
+        };
+
+        let mic = resolve_file(dir.path(), Some(&manifest), FileKind::MicAudio, 0);
+        assert_eq!(mic, Some(dir.path().join("custom-mic.m4a")));
+
+        // Not listed in the manifest: falls back to the convention
+        let screen = resolve_file(dir.path(), Some(&manifest), FileKind::ScreenVideo, 0);
+        assert_eq!(screen, Some(dir.path().join("recording-0.mp4")));
+
+        assert_eq!(resolve_file(dir.path(), None, FileKind::WebcamVideo, 0), None);
+    }
+
+    #[test]
+    fn test_primary_video_falls_back_to_webcam() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("recording-0-webcam.mp4"), b"").unwrap();
+        assert_eq!(
+            resolve_primary_video(dir.path(), None, 0),
+            Some(dir.path().join("recording-0-webcam.mp4"))
+        );
+
+        std::fs::write(dir.path().join("recording-0.mp4"), b"").unwrap();
+        assert_eq!(
+            resolve_primary_video(dir.path(), None, 0),
+            Some(dir.path().join("recording-0.mp4"))
+        );
+    }
+
+    #[test]
+    fn test_primary_audio_falls_back_to_system_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(resolve_primary_audio(dir.path(), None, 0), None);
+
+        std::fs::write(dir.path().join("recording-0-system.m4a"), b"").unwrap();
+        assert_eq!(
+            resolve_primary_audio(dir.path(), None, 0),
+            Some(dir.path().join("recording-0-system.m4a"))
+        );
+
+        std::fs::write(dir.path().join("recording-0-mic.m4a"), b"").unwrap();
+        assert_eq!(
+            resolve_primary_audio(dir.path(), None, 0),
+            Some(dir.path().join("recording-0-mic.m4a"))
+        );
+    }
+
+    #[test]
+    fn test_replace_file_overrides_recorded_entry() {
+        let mut manifest = RecordingManifest {
+            version: MANIFEST_VERSION,
+            created_at: Utc::now(),
+            total_duration_ms: 0.0,
+            sessions: vec![],
+            channels: vec![],
+            microphone_offset_ms: None,
+            display_name: None,
+            frontmost_app: None,
+        };
+        let entry = |path: &str| ManifestFile {
+            path: path.to_string(),
+            session: 0,
+            kind: Some(FileKind::MicAudio),
+        };
+
+        manifest.replace_file(ChannelType::Microphone, entry("recording-0-mic.m4a"));
+        manifest.channels[0].anchors.push(ChannelAnchor {
+            session: 0,
+            start_ms: 120.0,
+            stop_ms: None,
+        });
+        manifest.replace_file(ChannelType::Microphone, entry("voiceover.wav"));
+
+        assert_eq!(manifest.channels.len(), 1);
+        assert_eq!(manifest.channels[0].files.len(), 1);
+        assert_eq!(
+            manifest.find(FileKind::MicAudio, 0).map(|f| f.path.as_str()),
+            Some("voiceover.wav")
+        );
+        // The imported file isn't lined up by the recorded one's start
+        assert!(manifest.anchor(FileKind::MicAudio, 0).is_none());
+    }
+
+    #[test]
+    fn test_manifest_file_entry_is_relative() {
+        let dir = Path::new("/tmp/bundle/recording");
+        let entry = RecordingManifest::file_entry(dir, "/tmp/bundle/recording/recording-2-webcam.mp4");
+        assert_eq!(entry.path, "recording-2-webcam.mp4");
+        assert_eq!(entry.session, 2);
+        assert_eq!(entry.kind, Some(FileKind::WebcamVideo));
+    }
+
+    #[test]
+    fn test_display_fps_uses_achieved_rate() {
+        let display = |fps: Option<u32>, achieved_fps: Option<f64>| RecordingManifest {
+            version: MANIFEST_VERSION,
+            created_at: Utc::now(),
+            total_duration_ms: 0.0,
+            sessions: vec![],
+            channels: vec![ChannelManifest {
+                id: "display".to_string(),
+                channel_type: ChannelType::Display,
+                files: vec![],
+                started_at_ms: None,
+                stopped_at_ms: None,
+                settings: ChannelSettings {
+                    fps,
+                    achieved_fps,
+                    ..Default::default()
+                },
+                dropped_frames: None,
+                anchors: Vec::new(),
+            }],
+            microphone_offset_ms: None,
+            display_name: None,
+            frontmost_app: None,
+        };
+
+        assert_eq!(display(Some(60), None).display_fps(), Some(60.0));
+        assert_eq!(display(Some(60), Some(47.6)).display_fps(), Some(48.0));
+        // Timing jitter can't push it over the requested rate
+        assert_eq!(display(Some(60), Some(60.4)).display_fps(), Some(60.0));
+        assert_eq!(display(None, None).display_fps(), None);
+    }
+
+    #[test]
+    fn test_shows_cursor_only_written_when_set() {
+        let hidden = serde_json::to_value(ChannelSettings::default()).unwrap();
+        assert!(hidden.get("showsCursor").is_none());
+
+        let shown = ChannelSettings {
+            shows_cursor: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&shown).unwrap();
+        assert_eq!(json["showsCursor"], true);
+        // Manifests from before the option read as cursor-less
+        let old: ChannelSettings = serde_json::from_str("{}").unwrap();
+        assert!(!old.shows_cursor);
+    }
+
+    fn display_manifest(settings: ChannelSettings) -> RecordingManifest {
+        RecordingManifest {
+            version: MANIFEST_VERSION,
+            created_at: Utc::now(),
+            total_duration_ms: 0.0,
+            sessions: vec![],
+            microphone_offset_ms: None,
+            display_name: None,
+            frontmost_app: None,
+            channels: vec![ChannelManifest {
+                id: "display-1".to_string(),
+                channel_type: ChannelType::Display,
+                files: vec![],
+                started_at_ms: None,
+                stopped_at_ms: None,
+                settings,
+                dropped_frames: None,
+                anchors: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_mouse_positions_follow_cropped_and_downscaled_video() {
+        let manifest = display_manifest(ChannelSettings {
+            downscale: Some(2),
+            ..Default::default()
+        });
+        let mut moves: Vec<MouseMove> = serde_json::from_value(serde_json::json!([{
+            "x": 800.0, "y": 600.0, "cursorId": "arrow", "activeModifiers": [],
+            "processTimeMs": 0.0, "unixTimeMs": 0,
+        }]))
+        .unwrap();
+        let mut clicks: Vec<MouseClick> = serde_json::from_value(serde_json::json!([{
+            "x": 100.0, "y": 50.0, "button": "left", "eventType": "down", "clickCount": 1,
+            "activeModifiers": [], "processTimeMs": 0.0, "unixTimeMs": 0,
+        }]))
+        .unwrap();
+
+        manifest.map_mouse_to_video(&[], &mut moves, &mut clicks);
+        assert_eq!((moves[0].x, moves[0].y), (400.0, 300.0));
+        assert_eq!((clicks[0].x, clicks[0].y), (50.0, 25.0));
+
+        // A region's origin is subtracted before scaling
+        let manifest = display_manifest(ChannelSettings {
+            crop: Some(ContentRect { x: 200, y: 100, width: 800, height: 600 }),
+            downscale: Some(2),
+            ..Default::default()
+        });
+        manifest.map_mouse_to_video(&[], &mut moves, &mut clicks);
+        assert_eq!((moves[0].x, moves[0].y), (100.0, 100.0));
+        assert_eq!((clicks[0].x, clicks[0].y), (-75.0, -37.5));
+
+        // A window's origin is subtracted before scaling
+        let manifest = display_manifest(ChannelSettings {
+            downscale: Some(2),
+            ..Default::default()
+        });
+        let resizes = [WindowResizeEvent {
+            process_time_ms: 0.0,
+            source_width: 800,
+            source_height: 600,
+            content: ContentRect { x: 0, y: 0, width: 800, height: 600 },
+            window_origin: Some((20.0, 40.0)),
+        }];
+        manifest.map_mouse_to_video(&resizes, &mut moves, &mut clicks);
+        assert_eq!((moves[0].x, moves[0].y), (40.0, 30.0));
+    }
+}
@@ -3,12 +3,26 @@
 //! This module implements the multi-channel recording architecture:
 //! - RecordingChannel trait for different capture sources
 //! - RecordingCoordinator to orchestrate multiple channels
+//! - ChannelRegistry of the channel factories available per platform
 //! - Segment writer for HLS/fMP4 output
+//! - Crash recovery of recordings interrupted mid-way
+//! - Session list for stitching paused and resumed recordings
+//! - Permission checks before recording starts
 
+pub mod calibration;
 pub mod channel;
 pub mod coordinator;
+pub mod cues;
+pub mod manifest;
+pub mod permissions;
+pub mod recovery;
+pub mod registry;
+pub mod segment;
+pub mod self_test;
+pub mod sessions;
 pub mod state;
 
 pub use channel::RecordingChannel;
 pub use coordinator::RecordingCoordinator;
+pub use registry::{ChannelContext, ChannelRegistry};
 pub use state::{RecordingState, RecordingSession};
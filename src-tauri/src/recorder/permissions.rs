@@ -0,0 +1,217 @@
+//! Permission checks before recording
+//!
+//! Capture sources need OS permissions on some platforms: the screen (and on
+//! macOS system audio, which comes through ScreenCaptureKit) needs screen
+//! recording access, the webcam and microphone need camera and microphone
+//! access, and input tracking needs Accessibility. A source without its
+//! permission fails when its channel starts or silently records nothing, so
+//! `start_recording` checks them all first. It refuses to start with a report
+//! of what's missing, or with `allow_degraded_start` records only the sources
+//! that are permitted. Input tracking is never worth refusing a recording
+//! over: without Accessibility it's left out and reported.
+//!
+//! Only permissions that were refused count as missing. Camera and
+//! microphone access the user hasn't decided on yet is asked for when the
+//! device opens.
+
+use super::channel::ChannelType;
+use super::state::RecordingConfig;
+use crate::capture::traits::PermissionStatus;
+use serde::{Deserialize, Serialize};
+
+/// OS permission a capture source needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Permission {
+    ScreenRecording,
+    Camera,
+    Microphone,
+    Accessibility,
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Permission::ScreenRecording => write!(f, "screen recording"),
+            Permission::Camera => write!(f, "camera"),
+            Permission::Microphone => write!(f, "microphone"),
+            Permission::Accessibility => write!(f, "accessibility"),
+        }
+    }
+}
+
+/// A permission the recording needs but was refused
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingPermission {
+    pub permission: Permission,
+    /// Sources that can't be recorded without it
+    pub sources: Vec<ChannelType>,
+}
+
+/// Permissions missing for a recording config
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionReport {
+    pub missing: Vec<MissingPermission>,
+    /// Whether anything could still be recorded without the missing
+    /// permissions, i.e. whether a degraded start is possible. A recording
+    /// with video is never degraded to audio only.
+    pub can_degrade: bool,
+}
+
+impl PermissionReport {
+    /// Check every permission `config` needs, using `status` to look each up
+    pub fn check(
+        config: &RecordingConfig,
+        status: impl Fn(Permission) -> PermissionStatus,
+    ) -> Self {
+        let missing: Vec<MissingPermission> = required(config)
+            .into_iter()
+            .filter(|(permission, _)| status(*permission) == PermissionStatus::Denied)
+            .map(|(permission, sources)| MissingPermission {
+                permission,
+                sources,
+            })
+            .collect();
+        let mut report = Self {
+            missing,
+            can_degrade: false,
+        };
+        if !report.is_complete() {
+            let mut degraded = config.clone();
+            report.degrade(&mut degraded);
+            let has_video = |c: &RecordingConfig| c.capture_screen || c.capture_webcam;
+            report.can_degrade =
+                degraded.validate().is_ok() && (has_video(&degraded) || !has_video(config));
+        }
+        report
+    }
+
+    /// Whether everything requested can be recorded
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Whether the recording can't start as asked without a degraded start,
+    /// i.e. a permission other than Accessibility is missing
+    pub fn is_blocking(&self) -> bool {
+        self.missing
+            .iter()
+            .any(|m| m.permission != Permission::Accessibility)
+    }
+
+    pub fn is_missing(&self, permission: Permission) -> bool {
+        self.missing.iter().any(|m| m.permission == permission)
+    }
+
+    /// Turn off the sources whose permission is missing
+    pub fn degrade(&self, config: &mut RecordingConfig) {
+        for source in self.missing.iter().flat_map(|m| &m.sources) {
+            match source {
+                ChannelType::Display => config.capture_screen = false,
+                ChannelType::SystemAudio => config.capture_system_audio = false,
+                ChannelType::Webcam => config.capture_webcam = false,
+                ChannelType::Microphone => config.capture_microphone = false,
+                ChannelType::Input => config.track_input = false,
+                ChannelType::Keyboard => config.track_keystrokes = false,
+                ChannelType::Annotation | ChannelType::AppActivity => {}
+            }
+        }
+    }
+
+    /// Summary for error messages, e.g. "camera (webcam), microphone
+    /// (microphone)"
+    pub fn describe(&self) -> String {
+        self.missing
+            .iter()
+            .map(|m| {
+                let sources: Vec<String> = m.sources.iter().map(|s| s.to_string()).collect();
+                format!("{} ({})", m.permission, sources.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Permissions `config` needs, with the sources that need each
+fn required(config: &RecordingConfig) -> Vec<(Permission, Vec<ChannelType>)> {
+    let mut screen = Vec::new();
+    if config.capture_screen {
+        screen.push(ChannelType::Display);
+    }
+    if config.capture_system_audio && cfg!(target_os = "macos") {
+        screen.push(ChannelType::SystemAudio);
+    }
+
+    let mut required = Vec::new();
+    if !screen.is_empty() {
+        required.push((Permission::ScreenRecording, screen));
+    }
+    if config.capture_webcam {
+        required.push((Permission::Camera, vec![ChannelType::Webcam]));
+    }
+    if config.capture_microphone {
+        required.push((Permission::Microphone, vec![ChannelType::Microphone]));
+    }
+    let mut accessibility = Vec::new();
+    if config.track_input {
+        accessibility.push(ChannelType::Input);
+    }
+    if config.track_keystrokes {
+        accessibility.push(ChannelType::Keyboard);
+    }
+    if !accessibility.is_empty() {
+        required.push((Permission::Accessibility, accessibility));
+    }
+    required
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degraded_start_drops_unpermitted_sources() {
+        let mut config: RecordingConfig = serde_json::from_value(serde_json::json!({
+            "displayId": 1,
+            "captureSystemAudio": false,
+            "captureMicrophone": true,
+            "microphoneDeviceId": null,
+            "captureWebcam": true,
+            "webcamDeviceId": null,
+            "trackInput": true,
+            "outputDir": "/tmp/recording",
+        }))
+        .unwrap();
+        let refused = |refused: &'static [Permission]| {
+            move |p: Permission| {
+                if refused.contains(&p) {
+                    PermissionStatus::Denied
+                } else {
+                    PermissionStatus::Undetermined
+                }
+            }
+        };
+
+        assert!(PermissionReport::check(&config, refused(&[])).is_complete());
+
+        let report = PermissionReport::check(
+            &config,
+            refused(&[Permission::Camera, Permission::Accessibility]),
+        );
+        assert_eq!(report.describe(), "camera (webcam), accessibility (input)");
+        assert!(report.can_degrade && report.is_blocking());
+        let report_without_input =
+            PermissionReport::check(&config, refused(&[Permission::Accessibility]));
+        assert!(!report_without_input.is_blocking());
+        report.degrade(&mut config);
+        assert!(config.capture_screen && config.capture_microphone);
+        assert!(!config.capture_webcam && !config.track_input);
+
+        // Without the screen or a camera there's nothing to record video from
+        let report = PermissionReport::check(&config, refused(&[Permission::ScreenRecording]));
+        assert!(report.is_missing(Permission::ScreenRecording));
+        assert!(!report.can_degrade);
+    }
+}
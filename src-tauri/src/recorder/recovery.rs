@@ -0,0 +1,526 @@
+//! Crash recovery
+//!
+//! While a recording is running, `recording/recording.lock` in its bundle is
+//! held with an exclusive file lock and describes the recording, and the
+//! bundle is listed in `in-progress.json` in the app data directory. A lock
+//! file that is still there but no longer locked belongs to a recording cut
+//! short by a crash. Recovering it finalizes the fragmented media written so
+//! far (see `segment`) and writes the manifest and session list
+//! `RecordingCoordinator::stop` would have written, rebuilt from the files on
+//! disk. A recording meant to be encrypted keeps its lock file until its
+//! media is encrypted, so one cut short before or during encryption is
+//! encrypted when it's recovered. Its media is also encrypted at launch
+//! (`seal`), leaving it to be offered for recovery as before.
+
+use super::channel::{ChannelHealth, ChannelStatus, ChannelType};
+use super::manifest::{
+    ChannelManifest, ChannelSettings, FileKind, RecordingManifest, MANIFEST_VERSION,
+};
+use super::segment;
+use super::sessions::SessionList;
+use super::state::{RecordingConfig, RecordingResult, RecordingSession};
+use crate::export::archive::probe_duration_ms;
+use crate::project::encryption::{
+    encrypt_media_in_place, encrypted_key_id, readable_path, BundleKey,
+};
+use crate::project::paths;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Lock file name inside the bundle's `recording/` directory
+pub const LOCK_FILE: &str = "recording.lock";
+
+/// Files tried, in order, for the length of a recovered session
+const SESSION_LENGTH_KINDS: [FileKind; 4] = [
+    FileKind::ScreenVideo,
+    FileKind::WebcamVideo,
+    FileKind::MicAudio,
+    FileKind::SystemAudio,
+];
+
+/// Contents of the lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockInfo {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub config: RecordingConfig,
+}
+
+/// Held for as long as a recording runs
+#[derive(Debug)]
+pub struct RecordingLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl RecordingLock {
+    /// Lock the recording in `recording_dir`
+    pub fn acquire(recording_dir: &Path, config: &RecordingConfig) -> io::Result<Self> {
+        let path = recording_dir.join(LOCK_FILE);
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.try_lock().map_err(|e| match e {
+            TryLockError::WouldBlock => io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "Another recording is writing to this directory",
+            ),
+            TryLockError::Error(e) => e,
+        })?;
+
+        let info = LockInfo {
+            pid: std::process::id(),
+            started_at: Utc::now(),
+            config: config.clone(),
+        };
+        file.write_all(&serde_json::to_vec_pretty(&info).map_err(io::Error::other)?)?;
+        file.sync_all()?;
+        Ok(Self { file, path })
+    }
+
+    /// Unlock and remove the lock file once the recording stopped cleanly
+    pub fn release(self) {
+        let Self { file, path } = self;
+        // Windows can't remove a file that is still open
+        drop(file);
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!("Failed to remove {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Lock file contents of an interrupted recording in `bundle`
+pub fn lock_info(bundle: &Path) -> Option<LockInfo> {
+    let data = std::fs::read(bundle.join("recording").join(LOCK_FILE)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// A recording that was interrupted and can be recovered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableRecording {
+    pub bundle_path: String,
+    /// None if the lock file was cut short too
+    pub started_at: Option<DateTime<Utc>>,
+    pub session_count: usize,
+    /// Recorded files, relative to the bundle's `recording/` directory
+    pub files: Vec<String>,
+    pub size_bytes: u64,
+}
+
+/// `in-progress.json` in the app data directory
+pub fn index_path() -> io::Result<PathBuf> {
+    let data_dir =
+        dirs::data_dir().ok_or_else(|| io::Error::other("Could not determine data directory"))?;
+    Ok(data_dir.join("Open ScreenStudio").join("in-progress.json"))
+}
+
+fn read_index(index: &Path) -> Vec<PathBuf> {
+    std::fs::read(index)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(index: &Path, bundles: &[PathBuf]) -> io::Result<()> {
+    if let Some(parent) = index.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(
+        index,
+        serde_json::to_vec_pretty(bundles).map_err(io::Error::other)?,
+    )
+}
+
+/// Remember a bundle being recorded, so it can be found after a crash
+pub fn register(index: &Path, bundle: &Path) -> io::Result<()> {
+    let mut bundles = read_index(index);
+    if !bundles.iter().any(|b| b == bundle) {
+        bundles.push(bundle.to_path_buf());
+    }
+    write_index(index, &bundles)
+}
+
+/// Interrupted recordings listed in `index`. Bundles that finished cleanly,
+/// were deleted or have nothing to recover are dropped from the index;
+/// bundles still being recorded are left in it but not returned.
+pub fn list_recoverable(index: &Path) -> io::Result<Vec<RecoverableRecording>> {
+    let bundles = read_index(index);
+    let mut kept = Vec::new();
+    let mut recoverable = Vec::new();
+    for bundle in bundles {
+        match inspect(&bundle) {
+            Ok(Some(recording)) => {
+                kept.push(bundle);
+                recoverable.push(recording);
+            }
+            Ok(None) => {}
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => kept.push(bundle),
+            Err(e) => tracing::warn!("Skipping interrupted recording {:?}: {}", bundle, e),
+        }
+    }
+    write_index(index, &kept)?;
+    Ok(recoverable)
+}
+
+/// Recorded files of `recording_dir` as (kind, session, file name)
+fn recorded_files(recording_dir: &Path) -> io::Result<Vec<(FileKind, usize, String)>> {
+    let mut files: Vec<_> = std::fs::read_dir(recording_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            FileKind::classify(&name).map(|(kind, session)| (kind, session, name))
+        })
+        .collect();
+    files.sort_by(|a, b| a.2.cmp(&b.2));
+    Ok(files)
+}
+
+/// Lock the lock file of a bundle that isn't being recorded and read it;
+/// the info is None if the crash cut it short. `Ok(None)` if there's no lock
+/// file; `WouldBlock` if the recording is still running.
+fn open_stale_lock(recording_dir: &Path) -> io::Result<Option<(File, Option<LockInfo>)>> {
+    let file = match File::open(recording_dir.join(LOCK_FILE)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "The recording is still in progress",
+            ))
+        }
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+    // Read through the locked handle; Windows locks keep others out
+    let info = serde_json::from_reader(io::BufReader::new(&file)).ok();
+    Ok(Some((file, info)))
+}
+
+fn inspect(bundle: &Path) -> io::Result<Option<RecoverableRecording>> {
+    let recording_dir = bundle.join("recording");
+    let Some((lock, info)) = open_stale_lock(&recording_dir)? else {
+        return Ok(None);
+    };
+    let files = recorded_files(&recording_dir)?;
+    drop(lock);
+    if files.is_empty() {
+        // Stopped before anything was written
+        std::fs::remove_file(recording_dir.join(LOCK_FILE))?;
+        return Ok(None);
+    }
+
+    let sessions: BTreeSet<usize> = files.iter().map(|(_, session, _)| *session).collect();
+    let size_bytes = files
+        .iter()
+        .filter_map(|(_, _, name)| std::fs::metadata(recording_dir.join(name)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    Ok(Some(RecoverableRecording {
+        bundle_path: bundle.to_string_lossy().to_string(),
+        started_at: info.map(|info| info.started_at),
+        session_count: sessions.len(),
+        files: files.into_iter().map(|(_, _, name)| name).collect(),
+        size_bytes,
+    }))
+}
+
+/// Whether any recorded file of `recording_dir` is encrypted
+fn has_encrypted_media(recording_dir: &Path) -> io::Result<bool> {
+    for (_, _, name) in recorded_files(recording_dir)? {
+        if encrypted_key_id(&recording_dir.join(name))
+            .map_err(io::Error::other)?
+            .is_some()
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Leave a recording that was stopped but never saved (the app quit while
+/// recording) to be offered for recovery like a crashed one
+pub fn mark_unsaved(recording_dir: &Path, config: &RecordingConfig) -> io::Result<()> {
+    let info = LockInfo {
+        pid: std::process::id(),
+        started_at: Utc::now(),
+        config: config.clone(),
+    };
+    std::fs::write(
+        recording_dir.join(LOCK_FILE),
+        serde_json::to_vec_pretty(&info).map_err(io::Error::other)?,
+    )
+}
+
+/// Finalize an interrupted recording so it opens like one that was stopped.
+/// Input tracks written only on stop (mouse, annotations, app activity) are
+/// lost; everything else keeps what was recorded up to the crash. A
+/// recording that was stopped but not saved keeps its manifest.
+pub fn recover(index: &Path, bundle: &Path) -> io::Result<RecordingResult> {
+    let recording_dir = bundle.join("recording");
+    let Some((lock, info)) = open_stale_lock(&recording_dir)? else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{:?} has no interrupted recording", bundle),
+        ));
+    };
+    // A lock file cut short can't tell, but media already encrypted can
+    let encrypt_media = match &info {
+        Some(info) => info.config.encrypt_media,
+        None => has_encrypted_media(&recording_dir)?,
+    };
+
+    let (manifest, stopped_cleanly) = match RecordingManifest::read(&recording_dir) {
+        Some(manifest) => (manifest, true),
+        None => {
+            let manifest = rebuild_manifest(&recording_dir, info)?;
+            manifest.write(&recording_dir)?;
+            SessionList::from_manifest(&manifest).write(&recording_dir)?;
+            (manifest, false)
+        }
+    };
+    // Still locked, so a crash here leaves it to be recovered again
+    if encrypt_media {
+        encrypt_media_in_place(&recording_dir, &bundle_key(&recording_dir)?)
+            .map_err(io::Error::other)?;
+    }
+
+    drop(lock);
+    std::fs::remove_file(recording_dir.join(LOCK_FILE))?;
+    let bundles: Vec<PathBuf> = read_index(index)
+        .into_iter()
+        .filter(|b| b != bundle)
+        .collect();
+    write_index(index, &bundles)?;
+    tracing::info!(
+        "Recovered interrupted recording {:?} ({:.0}ms)",
+        bundle,
+        manifest.total_duration_ms
+    );
+
+    let health = if stopped_cleanly {
+        ChannelHealth::Healthy
+    } else {
+        ChannelHealth::Degraded {
+            reason: "Recovered after the app quit unexpectedly".to_string(),
+        }
+    };
+    Ok(RecordingResult {
+        bundle_path: bundle.to_string_lossy().to_string(),
+        total_duration_ms: manifest.total_duration_ms,
+        session_count: manifest.sessions.len(),
+        output_files: manifest
+            .channels
+            .iter()
+            .flat_map(|channel| channel.files.iter())
+            .filter_map(|file| paths::resolve(recording_dir, &file.path))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        channels: manifest
+            .channels
+            .iter()
+            .map(|channel| ChannelStatus {
+                id: channel.id.clone(),
+                channel_type: channel.channel_type,
+                health: health.clone(),
+                output_files: channel.files.len(),
+                settings: channel.settings.clone(),
+            })
+            .collect(),
+    })
+}
+
+/// Encrypt the media of an interrupted recording that was meant to be
+/// encrypted, without recovering it: the lock file and the index entry stay,
+/// so it's still offered for recovery. Sessions are finalized first, as
+/// encrypted media can't be. Holds the lock meanwhile, so recovering it at
+/// the same time fails as still in progress. Returns the number of files
+/// encrypted.
+pub fn seal(bundle: &Path) -> io::Result<usize> {
+    let recording_dir = bundle.join("recording");
+    let Some((lock, info)) = open_stale_lock(&recording_dir)? else {
+        return Ok(0);
+    };
+    if !info.is_some_and(|info| info.config.encrypt_media) {
+        return Ok(0);
+    }
+
+    let sessions: BTreeSet<usize> = recorded_files(&recording_dir)?
+        .into_iter()
+        .map(|(_, session, _)| session)
+        .collect();
+    for session in sessions {
+        segment::finalize_session(&recording_dir, session, None);
+    }
+    let count = encrypt_media_in_place(&recording_dir, &bundle_key(&recording_dir)?)
+        .map_err(io::Error::other)?;
+    drop(lock);
+    Ok(count)
+}
+
+/// Key some of the recording's media is already encrypted with, so all of
+/// it shares one, or a new key
+fn bundle_key(recording_dir: &Path) -> io::Result<BundleKey> {
+    for (_, _, name) in recorded_files(recording_dir)? {
+        if let Some(key_id) =
+            encrypted_key_id(&recording_dir.join(name)).map_err(io::Error::other)?
+        {
+            return BundleKey::load(&key_id).map_err(io::Error::other);
+        }
+    }
+    BundleKey::create().map_err(io::Error::other)
+}
+
+/// Finalize the media of a crashed recording and describe it from the files
+/// on disk
+fn rebuild_manifest(recording_dir: &Path, info: Option<LockInfo>) -> io::Result<RecordingManifest> {
+    let files = recorded_files(recording_dir)?;
+    let sessions: BTreeSet<usize> = files.iter().map(|(_, session, _)| *session).collect();
+    for &session in &sessions {
+        segment::finalize_session(recording_dir, session, None);
+    }
+
+    let started_at_ms = info
+        .as_ref()
+        .map(|info| info.started_at.timestamp_millis() as u64)
+        .unwrap_or_default();
+    let mut elapsed_ms = 0.0;
+    let sessions: Vec<RecordingSession> = sessions
+        .into_iter()
+        .map(|index| {
+            let duration_ms = session_length_ms(recording_dir, index);
+            let session = RecordingSession {
+                index,
+                duration_ms,
+                process_time_start_ms: elapsed_ms,
+                process_time_end_ms: elapsed_ms + duration_ms,
+                unix_start_ms: started_at_ms + elapsed_ms as u64,
+                unix_end_ms: started_at_ms + (elapsed_ms + duration_ms) as u64,
+                boundary: None,
+            };
+            elapsed_ms += duration_ms;
+            session
+        })
+        .collect();
+
+    let mut channels: Vec<ChannelManifest> = Vec::new();
+    for (kind, _, name) in &files {
+        let channel_type = kind.channel_type();
+        let file = RecordingManifest::file_entry(recording_dir, name);
+        match channels.iter_mut().find(|c| c.channel_type == channel_type) {
+            Some(channel) => channel.files.push(file),
+            None => channels.push(ChannelManifest {
+                id: channel_type.to_string(),
+                channel_type,
+                files: vec![file],
+                started_at_ms: Some(started_at_ms),
+                stopped_at_ms: None,
+                settings: ChannelSettings::default(),
+                dropped_frames: None,
+                anchors: Vec::new(),
+            }),
+        }
+    }
+
+    let config = info.map(|info| info.config);
+    Ok(RecordingManifest {
+        version: MANIFEST_VERSION,
+        created_at: Utc::now(),
+        total_duration_ms: elapsed_ms,
+        sessions,
+        channels,
+        microphone_offset_ms: config.as_ref().and_then(|c| c.microphone_offset_ms),
+        display_name: config.as_ref().and_then(|c| c.display_name.clone()),
+        frontmost_app: config.as_ref().and_then(|c| c.frontmost_app.clone()),
+    })
+}
+
+/// Length of a session from its longest-lived media file, or 0 if none can
+/// be read. Media encrypted at launch (`seal`) is read decrypted.
+fn session_length_ms(recording_dir: &Path, session: usize) -> f64 {
+    SESSION_LENGTH_KINDS
+        .into_iter()
+        .filter_map(|kind| paths::resolve(recording_dir, &kind.conventional_name(session)))
+        .find_map(|path| probe_duration_ms(&readable_path(&path).ok()?).ok())
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(output_dir: &Path) -> RecordingConfig {
+        serde_json::from_value(serde_json::json!({
+            "displayId": 1,
+            "captureSystemAudio": false,
+            "captureMicrophone": true,
+            "microphoneDeviceId": null,
+            "captureWebcam": false,
+            "webcamDeviceId": null,
+            "trackInput": false,
+            "outputDir": output_dir.to_string_lossy(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_unlocked_recordings_are_recoverable() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join("in-progress.json");
+        let running = dir.path().join("running");
+        let crashed = dir.path().join("crashed");
+        let finished = dir.path().join("finished");
+        for bundle in [&running, &crashed, &finished] {
+            let recording_dir = bundle.join("recording");
+            std::fs::create_dir_all(&recording_dir).unwrap();
+            std::fs::write(recording_dir.join("recording-0-mouse-moves.json"), "[]").unwrap();
+            register(&index, bundle).unwrap();
+        }
+
+        let _running =
+            RecordingLock::acquire(&running.join("recording"), &test_config(&running)).unwrap();
+        // A crash leaves the lock file behind, unlocked
+        drop(RecordingLock::acquire(&crashed.join("recording"), &test_config(&crashed)).unwrap());
+        RecordingLock::acquire(&finished.join("recording"), &test_config(&finished))
+            .unwrap()
+            .release();
+
+        let recoverable = list_recoverable(&index).unwrap();
+        assert_eq!(recoverable.len(), 1);
+        assert_eq!(recoverable[0].bundle_path, crashed.to_string_lossy());
+        assert_eq!(recoverable[0].files, vec!["recording-0-mouse-moves.json"]);
+        assert!(recoverable[0].started_at.is_some());
+        assert_eq!(read_index(&index), vec![running.clone(), crashed.clone()]);
+
+        assert!(recover(&index, &running).is_err());
+        let result = recover(&index, &crashed).unwrap();
+        assert_eq!(result.session_count, 1);
+        assert_eq!(result.channels[0].channel_type, ChannelType::Input);
+        assert!(RecordingManifest::read(&crashed.join("recording")).is_some());
+        assert!(!crashed.join("recording").join(LOCK_FILE).exists());
+        assert_eq!(read_index(&index), vec![running]);
+    }
+
+    #[test]
+    fn test_encrypted_media_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("recording-0-mic.m4a");
+        std::fs::write(&plain, b"not really audio").unwrap();
+        std::fs::write(dir.path().join("recording-0-mouse-moves.json"), "[]").unwrap();
+        assert!(!has_encrypted_media(dir.path()).unwrap());
+
+        let key = BundleKey::from_bytes("test", &[7u8; 32]).unwrap();
+        encrypt_media_in_place(dir.path(), &key).unwrap();
+        assert!(has_encrypted_media(dir.path()).unwrap());
+    }
+}
@@ -0,0 +1,200 @@
+//! Recording channel registry
+//!
+//! Maps each `ChannelType` to a factory that builds the channel for a
+//! recording configuration, or declines when the configuration doesn't ask
+//! for it. Platform sources are registered once at startup (see
+//! `capture::channels`), new sources are added by registering another
+//! factory, and tests can swap any factory for a mock channel.
+
+use super::channel::{ChannelType, RecordingChannel};
+use super::state::RecordingConfig;
+use crate::capture::annotation::AnnotationSink;
+
+/// Everything a factory may need to build its channel
+pub struct ChannelContext<'a> {
+    pub config: &'a RecordingConfig,
+    /// Entry point for annotations drawn on screen; None when annotations
+    /// aren't collected (e.g. during a self-test)
+    pub annotations: Option<&'a AnnotationSink>,
+}
+
+/// Builds a channel for a recording, or None if it isn't needed
+pub type ChannelFactory =
+    Box<dyn Fn(&ChannelContext) -> Option<Box<dyn RecordingChannel>> + Send + Sync>;
+
+/// Factories for every channel type available on this platform
+#[derive(Default)]
+pub struct ChannelRegistry {
+    /// Factories in registration order, which is also the start order
+    factories: Vec<(ChannelType, ChannelFactory)>,
+}
+
+impl ChannelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the factory for a channel type, replacing any existing one
+    /// (which keeps its position)
+    pub fn register<F>(&mut self, channel_type: ChannelType, factory: F) -> &mut Self
+    where
+        F: Fn(&ChannelContext) -> Option<Box<dyn RecordingChannel>> + Send + Sync + 'static,
+    {
+        let factory: ChannelFactory = Box::new(factory);
+        match self.factories.iter_mut().find(|(t, _)| *t == channel_type) {
+            Some(entry) => entry.1 = factory,
+            None => self.factories.push((channel_type, factory)),
+        }
+        self
+    }
+
+    /// Remove the factory for a channel type
+    pub fn unregister(&mut self, channel_type: ChannelType) {
+        self.factories.retain(|(t, _)| *t != channel_type);
+    }
+
+    pub fn contains(&self, channel_type: ChannelType) -> bool {
+        self.factories.iter().any(|(t, _)| *t == channel_type)
+    }
+
+    /// Channel types that can be recorded, in start order
+    pub fn channel_types(&self) -> Vec<ChannelType> {
+        self.factories.iter().map(|(t, _)| *t).collect()
+    }
+
+    /// Build the channels a recording configuration asks for
+    pub fn build(&self, context: &ChannelContext) -> Vec<Box<dyn RecordingChannel>> {
+        self.factories
+            .iter()
+            .filter_map(|(channel_type, factory)| {
+                let channel = factory(context)?;
+                if channel.channel_type() != *channel_type {
+                    tracing::warn!(
+                        "Factory for {} built a {} channel",
+                        channel_type,
+                        channel.channel_type()
+                    );
+                }
+                Some(channel)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::channel::RecordingResult;
+    use async_trait::async_trait;
+    use std::path::Path;
+
+    struct StubChannel(ChannelType);
+
+    #[async_trait]
+    impl RecordingChannel for StubChannel {
+        fn id(&self) -> &str {
+            "stub"
+        }
+
+        fn channel_type(&self) -> ChannelType {
+            self.0
+        }
+
+        async fn initialize(
+            &mut self,
+            _output_dir: &Path,
+            _session_index: usize,
+        ) -> RecordingResult<()> {
+            Ok(())
+        }
+
+        async fn start(&mut self) -> RecordingResult<()> {
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> RecordingResult<()> {
+            Ok(())
+        }
+
+        async fn pause(&mut self) -> RecordingResult<()> {
+            Ok(())
+        }
+
+        async fn resume(&mut self, _session_index: usize) -> RecordingResult<()> {
+            Ok(())
+        }
+
+        fn is_recording(&self) -> bool {
+            false
+        }
+
+        fn output_files(&self) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    fn stub(
+        channel_type: ChannelType,
+    ) -> impl Fn(&ChannelContext) -> Option<Box<dyn RecordingChannel>> {
+        move |_| Some(Box::new(StubChannel(channel_type)))
+    }
+
+    #[test]
+    fn test_build_follows_config_and_registration_order() {
+        let config: RecordingConfig = serde_json::from_value(serde_json::json!({
+            "displayId": 1,
+            "captureSystemAudio": false,
+            "captureMicrophone": true,
+            "microphoneDeviceId": null,
+            "captureWebcam": false,
+            "webcamDeviceId": null,
+            "trackInput": false,
+            "outputDir": "/tmp",
+        }))
+        .unwrap();
+
+        let mut registry = ChannelRegistry::new();
+        registry
+            .register(ChannelType::Display, stub(ChannelType::Display))
+            .register(ChannelType::Microphone, |ctx| {
+                ctx.config.capture_microphone.then(|| {
+                    Box::new(StubChannel(ChannelType::Microphone)) as Box<dyn RecordingChannel>
+                })
+            })
+            .register(ChannelType::Webcam, |ctx| {
+                ctx.config.capture_webcam.then(|| {
+                    Box::new(StubChannel(ChannelType::Webcam)) as Box<dyn RecordingChannel>
+                })
+            });
+
+        let context = ChannelContext {
+            config: &config,
+            annotations: None,
+        };
+        let types: Vec<_> = registry
+            .build(&context)
+            .iter()
+            .map(|c| c.channel_type())
+            .collect();
+        assert_eq!(types, vec![ChannelType::Display, ChannelType::Microphone]);
+
+        // Replacing a factory keeps its place; removing one drops it
+        registry.register(ChannelType::Display, |_| None);
+        registry.unregister(ChannelType::Microphone);
+        registry.register(ChannelType::Input, stub(ChannelType::Input));
+        assert_eq!(
+            registry.channel_types(),
+            vec![
+                ChannelType::Display,
+                ChannelType::Webcam,
+                ChannelType::Input
+            ]
+        );
+        let types: Vec<_> = registry
+            .build(&context)
+            .iter()
+            .map(|c| c.channel_type())
+            .collect();
+        assert_eq!(types, vec![ChannelType::Input]);
+    }
+}
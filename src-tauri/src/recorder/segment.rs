@@ -0,0 +1,197 @@
+//! Fragmented MP4 segment writer
+//!
+//! Video and audio encoders write each session as fragmented MP4, starting a
+//! new fragment at every keyframe, so the file can be played while it is
+//! still being written (see `RecordingCoordinator::live_preview`) and what
+//! was written before a crash can be recovered (see `recovery`). When a
+//! session ends the file is remuxed, without re-encoding, into a regular MP4
+//! with the index at the front, which is what the editor and export expect.
+//! If that fails the fragmented file is kept; anything FFmpeg-based still
+//! reads it.
+//!
+//! Screen recordings skip unchanged frames, so a screen that stays still at
+//! the end of a session leaves the video short of it. The remux holds the
+//! last frame up to the session's end.
+
+use super::manifest::FileKind;
+use crate::export::verify::has_top_level_box;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// `-movflags` for a file that is playable while being written: an empty
+/// `moov` up front and a fragment per keyframe
+pub const LIVE_MOVFLAGS: &str = "+frag_keyframe+empty_moov+default_base_moof";
+
+/// FFmpeg output arguments for writing a live-playable MP4
+pub fn live_movflags_args() -> [&'static str; 2] {
+    ["-movflags", LIVE_MOVFLAGS]
+}
+
+/// FFmpeg output arguments for a live-playable audio-only file. Every audio
+/// packet is a keyframe, so fragments are cut by duration instead (1s).
+pub fn live_audio_movflags_args() -> [&'static str; 4] {
+    ["-movflags", "+empty_moov+default_base_moof", "-frag_duration", "1000000"]
+}
+
+/// Tracks written as fragmented MP4
+const LIVE_KINDS: [FileKind; 4] = [
+    FileKind::ScreenVideo,
+    FileKind::WebcamVideo,
+    FileKind::MicAudio,
+    FileKind::SystemAudio,
+];
+
+/// Whether an MP4 file was written in fragments
+pub fn is_fragmented(path: &Path) -> io::Result<bool> {
+    has_top_level_box(&mut BufReader::new(File::open(path)?), b"moof")
+}
+
+/// Finalize the media files of a session that just ended, holding the last
+/// screen frame up to `duration_ms` if the session's length is known.
+/// Failures are logged and leave the fragmented file in place.
+pub fn finalize_session(recording_dir: &Path, session: usize, duration_ms: Option<f64>) {
+    for kind in LIVE_KINDS {
+        let path = recording_dir.join(kind.conventional_name(session));
+        // Channels that render their file at the end (mock capture) or write
+        // another format already have a regular file
+        if !path.exists() || !is_fragmented(&path).unwrap_or(false) {
+            continue;
+        }
+        let end_ms = duration_ms.filter(|_| kind == FileKind::ScreenVideo);
+        match finalize_fragmented_mp4(&path, end_ms) {
+            Ok(()) => tracing::debug!("Finalized {:?}", path),
+            Err(e) => tracing::warn!("Keeping fragmented {:?}: {}", path, e),
+        }
+    }
+}
+
+/// Path of the file that shows session `session` of a recording, if it has
+/// been written yet
+pub fn session_video(
+    recording_dir: &Path,
+    session: usize,
+    capture_screen: bool,
+) -> Option<PathBuf> {
+    let kind = if capture_screen {
+        FileKind::ScreenVideo
+    } else {
+        FileKind::WebcamVideo
+    };
+    Some(recording_dir.join(kind.conventional_name(session))).filter(|path| path.exists())
+}
+
+/// Remux a finished fragmented MP4 in place into a regular, faststart MP4.
+/// A video ending before `end_ms` has its last frame shown until then.
+pub fn finalize_fragmented_mp4(path: &Path, end_ms: Option<f64>) -> io::Result<()> {
+    let temp = path.with_extension("finalizing.mp4");
+    let hold = end_ms.and_then(|end_ms| {
+        let (packets, duration_ms) = probe_video_end(path)?;
+        hold_last_frame_bsf(packets, end_ms - duration_ms)
+    });
+    let output = Command::new("ffmpeg")
+        .args(["-y", "-v", "error", "-i"])
+        .arg(path)
+        .args(["-map", "0", "-c", "copy"])
+        .args(hold.into_iter().flat_map(|bsf| ["-bsf:v".to_string(), bsf]))
+        .args(["-movflags", "+faststart"])
+        .arg(&temp)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp);
+        return Err(io::Error::other(format!(
+            "FFmpeg remux failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    std::fs::rename(&temp, path)
+}
+
+/// Packet count and duration of a file's video stream
+fn probe_video_end(path: &Path) -> Option<(u64, f64)> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0", "-count_packets"])
+        .args(["-show_entries", "stream=nb_read_packets:format=duration"])
+        .args(["-of", "csv=p=0"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(str::trim);
+    let packets = lines.next()?.parse().ok()?;
+    let duration_secs: f64 = lines.next()?.parse().ok()?;
+    Some((packets, duration_secs * 1000.0))
+}
+
+/// Bitstream filter lengthening the last of `packets` video packets by
+/// `extra_ms`, or None if the video doesn't fall short. The player shows
+/// the last frame until the track ends. `trunc(N/last)` is 1 only for the
+/// last packet; the expression avoids commas, which split filter lists.
+fn hold_last_frame_bsf(packets: u64, extra_ms: f64) -> Option<String> {
+    let last = packets.checked_sub(1).filter(|&last| last > 0)?;
+    (extra_ms >= 1.0).then(|| {
+        format!(
+            "setts=duration=DURATION+trunc(N/{})*{:.3}/TB",
+            last,
+            extra_ms / 1000.0
+        )
+    })
+}
+
+/// Video of the recording in progress, for previewing before it's stopped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LivePreview {
+    /// Video file of the current session (or the last one while paused)
+    pub path: String,
+    pub session_index: usize,
+    /// Length of that session so far; the file trails it by up to one
+    /// keyframe interval
+    pub session_duration_ms: f64,
+    /// Where in the file to start playing to show the requested window
+    pub start_ms: f64,
+    /// Length of the whole recording so far, across sessions
+    pub recording_duration_ms: f64,
+}
+
+/// Position in a session of `session_duration_ms` to start playing from to
+/// show its last `window_ms`, or all of it
+pub fn preview_start_ms(session_duration_ms: f64, window_ms: Option<f64>) -> f64 {
+    window_ms
+        .map(|window| (session_duration_ms - window).max(0.0))
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_window() {
+        assert_eq!(preview_start_ms(45_000.0, Some(30_000.0)), 15_000.0);
+        assert_eq!(preview_start_ms(10_000.0, Some(30_000.0)), 0.0);
+        assert_eq!(preview_start_ms(45_000.0, None), 0.0);
+        assert_eq!(
+            live_movflags_args(),
+            ["-movflags", "+frag_keyframe+empty_moov+default_base_moof"]
+        );
+    }
+
+    #[test]
+    fn test_last_frame_is_held_to_the_session_end() {
+        assert_eq!(
+            hold_last_frame_bsf(300, 750.0).as_deref(),
+            Some("setts=duration=DURATION+trunc(N/299)*0.750/TB")
+        );
+        // Nothing to hold, or no frame but the last to tell it apart from
+        assert_eq!(hold_last_frame_bsf(300, 0.4), None);
+        assert_eq!(hold_last_frame_bsf(300, -20.0), None);
+        assert_eq!(hold_last_frame_bsf(1, 750.0), None);
+        assert_eq!(hold_last_frame_bsf(0, 750.0), None);
+    }
+}
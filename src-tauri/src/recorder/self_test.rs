@@ -0,0 +1,228 @@
+//! Capture self-test
+//!
+//! Records a few seconds with the configured channels into a temporary bundle
+//! and checks that the output actually contains something: video frames that
+//! aren't black and audio that isn't silent. Lets users validate their setup
+//! before committing to a long recording.
+
+use super::channel::{ChannelType, RecordingChannel, RecordingError, RecordingResult};
+use super::coordinator::RecordingCoordinator;
+use super::manifest::{FileKind, RecordingManifest};
+use super::state::RecordingConfig;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long the self-test records
+pub const SELF_TEST_DURATION: Duration = Duration::from_secs(3);
+
+/// Mean luma below which a frame counts as black (0-255)
+const BLACK_MEAN_LUMA: f64 = 8.0;
+
+/// Peak amplitude below which audio counts as silent (0.0-1.0)
+const SILENCE_PEAK: f64 = 0.001;
+
+/// Result of checking one channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelCheck {
+    pub channel_id: String,
+    pub channel_type: ChannelType,
+    pub passed: bool,
+    pub message: String,
+}
+
+/// Per-channel pass/fail report of a self-test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub duration_ms: f64,
+    pub channels: Vec<ChannelCheck>,
+}
+
+/// Record a short test with the given channels and analyze the output.
+///
+/// Uses its own coordinator and a temporary directory that is removed
+/// afterwards, so it never touches a real recording.
+pub async fn run_self_test(
+    channels: Vec<Box<dyn RecordingChannel>>,
+    mut config: RecordingConfig,
+) -> RecordingResult<SelfTestReport> {
+    let temp_dir = tempfile::tempdir()?;
+    config.output_dir = temp_dir.path().to_string_lossy().to_string();
+
+    let mut coordinator = RecordingCoordinator::new();
+    for channel in channels {
+        coordinator.add_channel(channel);
+    }
+
+    tracing::info!("Running capture self-test in {:?}", temp_dir.path());
+
+    coordinator.start(config).await?;
+    tokio::time::sleep(SELF_TEST_DURATION).await;
+    let output = coordinator.stop().await?;
+
+    let recording_dir = temp_dir.path().join("recording");
+    let manifest = RecordingManifest::read(&recording_dir).ok_or_else(|| {
+        RecordingError::CaptureError("Self-test produced no recording manifest".to_string())
+    })?;
+
+    let mut checks = Vec::new();
+    for channel in &manifest.channels {
+        // Input, annotation, app activity and keyboard channels only write
+        // metadata
+        if matches!(
+            channel.channel_type,
+            ChannelType::Input
+                | ChannelType::Annotation
+                | ChannelType::AppActivity
+                | ChannelType::Keyboard
+        ) {
+            continue;
+        }
+
+        let file = channel.files.iter().find_map(|f| f.kind.map(|k| (k, &f.path)));
+        let check = match file {
+            Some((kind, path)) => check_file(kind, &recording_dir.join(path)).await,
+            None => Err("No output file was produced".to_string()),
+        };
+
+        checks.push(ChannelCheck {
+            channel_id: channel.id.clone(),
+            channel_type: channel.channel_type,
+            passed: check.is_ok(),
+            message: check.unwrap_or_else(|e| e),
+        });
+    }
+
+    let report = SelfTestReport {
+        passed: !checks.is_empty() && checks.iter().all(|c| c.passed),
+        duration_ms: output.total_duration_ms,
+        channels: checks,
+    };
+
+    tracing::info!(
+        "Capture self-test {}",
+        if report.passed { "passed" } else { "failed" }
+    );
+    Ok(report)
+}
+
+/// Analyze one output file, returning a human-readable verdict
+async fn check_file(kind: FileKind, path: &Path) -> Result<String, String> {
+    match kind {
+        FileKind::ScreenVideo | FileKind::WebcamVideo => {
+            let frames = decode_luma_frames(path).await?;
+            if frames.is_empty() {
+                return Err("No video frames were recorded".to_string());
+            }
+            if frames.iter().all(|f| is_black_frame(f)) {
+                return Err("All sampled frames are black".to_string());
+            }
+            Ok(format!("{} frames sampled, picture detected", frames.len()))
+        }
+        FileKind::MicAudio | FileKind::SystemAudio => {
+            let samples = decode_audio_samples(path).await?;
+            if samples.is_empty() {
+                return Err("No audio samples were recorded".to_string());
+            }
+            if is_silent(&samples) {
+                return Err("Audio is silent".to_string());
+            }
+            Ok(format!("{} samples recorded, signal detected", samples.len()))
+        }
+        _ => Ok("Recorded".to_string()),
+    }
+}
+
+/// Size frames are scaled to before analysis
+const ANALYSIS_WIDTH: usize = 64;
+const ANALYSIS_HEIGHT: usize = 36;
+
+/// Decode one grayscale thumbnail per second of video
+async fn decode_luma_frames(path: &Path) -> Result<Vec<Vec<u8>>, String> {
+    let output = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(path)
+        .args([
+            "-vf",
+            &format!("fps=1,scale={}:{},format=gray", ANALYSIS_WIDTH, ANALYSIS_HEIGHT),
+            "-f",
+            "rawvideo",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+
+    if !output.status.success() {
+        return Err("Recorded video could not be decoded".to_string());
+    }
+
+    Ok(output
+        .stdout
+        .chunks_exact(ANALYSIS_WIDTH * ANALYSIS_HEIGHT)
+        .map(|f| f.to_vec())
+        .collect())
+}
+
+/// Decode audio to mono 8kHz samples normalized to -1.0..1.0
+async fn decode_audio_samples(path: &Path) -> Result<Vec<f64>, String> {
+    let output = Command::new("ffmpeg")
+        .arg("-i")
+        .arg(path)
+        .args(["-ac", "1", "-ar", "8000", "-f", "s16le", "-acodec", "pcm_s16le", "-"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+
+    if !output.status.success() {
+        return Err("Recorded audio could not be decoded".to_string());
+    }
+
+    Ok(output
+        .stdout
+        .chunks_exact(2)
+        .map(|c| i16::from_le_bytes([c[0], c[1]]) as f64 / i16::MAX as f64)
+        .collect())
+}
+
+/// Check whether a grayscale frame is (nearly) black
+pub fn is_black_frame(luma: &[u8]) -> bool {
+    if luma.is_empty() {
+        return true;
+    }
+    let mean = luma.iter().map(|&v| v as f64).sum::<f64>() / luma.len() as f64;
+    mean < BLACK_MEAN_LUMA
+}
+
+/// Check whether normalized audio samples are (nearly) silent
+pub fn is_silent(samples: &[f64]) -> bool {
+    samples.iter().all(|s| s.abs() < SILENCE_PEAK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_black_frame_detection() {
+        assert!(is_black_frame(&[0; 64]));
+        assert!(is_black_frame(&[3; 64]));
+        assert!(!is_black_frame(&[120; 64]));
+        assert!(is_black_frame(&[]));
+    }
+
+    #[test]
+    fn test_silence_detection() {
+        assert!(is_silent(&[0.0, 0.0005, -0.0002]));
+        assert!(!is_silent(&[0.0, 0.2, -0.1]));
+    }
+}
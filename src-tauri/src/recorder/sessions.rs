@@ -0,0 +1,214 @@
+//! Session list of a recording
+//!
+//! Pausing ends a session and resuming starts the next one, and every channel
+//! writes a new set of files per session named `recording-{session}{suffix}`
+//! (see `FileKind`). `sessions.json`, written next to the manifest, lists the
+//! sessions in recording order with where each one starts on the stitched
+//! timeline and the files recorded in it, so the editor and export can join
+//! them without going through each channel's file list.
+
+use super::manifest::{FileKind, RecordingManifest};
+use super::state::SessionBoundary;
+use crate::project::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Session list file name inside the bundle's `recording/` directory
+pub const SESSIONS_FILE: &str = "sessions.json";
+
+/// Current session list format version
+pub const SESSIONS_VERSION: u32 = 1;
+
+/// One recorded session and its files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEntry {
+    pub index: usize,
+    /// Where the session starts on the stitched timeline, i.e. the combined
+    /// length of the sessions before it
+    pub timeline_start_ms: f64,
+    pub duration_ms: f64,
+    pub unix_start_ms: u64,
+    pub unix_end_ms: u64,
+    /// Why the session started, if not by the user resuming
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boundary: Option<SessionBoundary>,
+    /// Files recorded in the session, relative to the recording directory
+    pub files: BTreeMap<FileKind, String>,
+}
+
+impl SessionEntry {
+    pub fn file(&self, kind: FileKind) -> Option<&str> {
+        self.files.get(&kind).map(String::as_str)
+    }
+
+    pub fn timeline_end_ms(&self) -> f64 {
+        self.timeline_start_ms + self.duration_ms
+    }
+}
+
+/// Every session of a recording, in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionList {
+    pub version: u32,
+    pub total_duration_ms: f64,
+    pub sessions: Vec<SessionEntry>,
+}
+
+impl SessionList {
+    /// Build the session list from a recording manifest
+    pub fn from_manifest(manifest: &RecordingManifest) -> Self {
+        let mut timeline_ms = 0.0;
+        let sessions = manifest
+            .sessions
+            .iter()
+            .map(|session| {
+                let files = manifest
+                    .channels
+                    .iter()
+                    .flat_map(|c| &c.files)
+                    .filter(|f| f.session == session.index)
+                    .filter_map(|f| Some((f.kind?, f.path.clone())))
+                    .collect();
+                let entry = SessionEntry {
+                    index: session.index,
+                    timeline_start_ms: timeline_ms,
+                    duration_ms: session.duration_ms,
+                    unix_start_ms: session.unix_start_ms,
+                    unix_end_ms: session.unix_end_ms,
+                    boundary: session.boundary.clone(),
+                    files,
+                };
+                timeline_ms += session.duration_ms;
+                entry
+            })
+            .collect();
+
+        Self {
+            version: SESSIONS_VERSION,
+            total_duration_ms: timeline_ms,
+            sessions,
+        }
+    }
+
+    /// Sessions of a recording directory, from `sessions.json` or, for
+    /// bundles recorded before it existed, from the manifest
+    pub fn load(recording_dir: &Path) -> Option<Self> {
+        Self::read(recording_dir)
+            .or_else(|| RecordingManifest::read(recording_dir).map(|m| Self::from_manifest(&m)))
+    }
+
+    /// Read `sessions.json` from a recording directory, if present. File
+    /// paths come back with `/` separators, whichever OS wrote them.
+    pub fn read(recording_dir: &Path) -> Option<Self> {
+        let path = recording_dir.join(SESSIONS_FILE);
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Self>(&content) {
+            Ok(mut list) => {
+                for file in list.sessions.iter_mut().flat_map(|s| s.files.values_mut()) {
+                    *file = paths::normalize(file);
+                }
+                Some(list)
+            }
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable session list {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Write `sessions.json` into a recording directory
+    pub fn write(&self, recording_dir: &Path) -> std::io::Result<PathBuf> {
+        let path = recording_dir.join(SESSIONS_FILE);
+        let content = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&SessionEntry> {
+        self.sessions.iter().find(|s| s.index == index)
+    }
+
+    /// Session playing at a time on the stitched timeline, with the time
+    /// inside that session. Times past the end land at the end of the last
+    /// session.
+    pub fn locate(&self, timeline_ms: f64) -> Option<(&SessionEntry, f64)> {
+        let session = self
+            .sessions
+            .iter()
+            .find(|s| timeline_ms < s.timeline_end_ms())
+            .or_else(|| self.sessions.last())?;
+        let offset = (timeline_ms - session.timeline_start_ms).clamp(0.0, session.duration_ms);
+        Some((session, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::channel::ChannelType;
+    use crate::recorder::manifest::{ChannelManifest, ChannelSettings, MANIFEST_VERSION};
+    use crate::recorder::state::RecordingSession;
+    use chrono::Utc;
+
+    #[test]
+    fn test_sessions_are_stitched_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorded = |index: usize, duration_ms: f64| {
+            let mut session = RecordingSession::new(index, 0.0);
+            session.end(duration_ms);
+            session
+        };
+        let channel = |channel_type: ChannelType, kinds: &[FileKind]| ChannelManifest {
+            id: channel_type.to_string(),
+            channel_type,
+            files: (0..2)
+                .flat_map(|session| kinds.iter().map(move |kind| (session, kind)))
+                .map(|(session, kind)| {
+                    let name = dir.path().join(kind.conventional_name(session));
+                    RecordingManifest::file_entry(dir.path(), &name.to_string_lossy())
+                })
+                .collect(),
+            started_at_ms: None,
+            stopped_at_ms: None,
+            settings: ChannelSettings::default(),
+            dropped_frames: None,
+            anchors: Vec::new(),
+        };
+        let manifest = RecordingManifest {
+            version: MANIFEST_VERSION,
+            created_at: Utc::now(),
+            total_duration_ms: 5000.0,
+            sessions: vec![recorded(0, 3000.0), recorded(1, 2000.0)],
+            channels: vec![
+                channel(ChannelType::Display, &[FileKind::ScreenVideo]),
+                channel(ChannelType::Microphone, &[FileKind::MicAudio]),
+            ],
+            microphone_offset_ms: None,
+            display_name: None,
+            frontmost_app: None,
+        };
+
+        // Bundles without sessions.json fall back to the manifest
+        manifest.write(dir.path()).unwrap();
+        let from_manifest = SessionList::load(dir.path()).unwrap();
+        from_manifest.write(dir.path()).unwrap();
+        let sessions = SessionList::read(dir.path()).unwrap();
+        assert_eq!(sessions, from_manifest);
+
+        assert_eq!(sessions.total_duration_ms, 5000.0);
+        let second = sessions.get(1).unwrap();
+        assert_eq!(second.timeline_start_ms, 3000.0);
+        assert_eq!(second.file(FileKind::ScreenVideo), Some("recording-1.mp4"));
+        assert_eq!(second.file(FileKind::MicAudio), Some("recording-1-mic.m4a"));
+        assert_eq!(second.file(FileKind::WebcamVideo), None);
+
+        let (session, offset) = sessions.locate(3500.0).unwrap();
+        assert_eq!((session.index, offset), (1, 500.0));
+        let (session, offset) = sessions.locate(9000.0).unwrap();
+        assert_eq!((session.index, offset), (1, 2000.0));
+        assert_eq!(sessions.locate(0.0).unwrap().0.index, 0);
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! Defines the recording state machine and session tracking.
 
-use chrono::Utc;
+use super::channel::ChannelStatus;
+use super::cues::CueConfig;
+use crate::capture::frame::ContentRect;
+use crate::capture::traits::CameraFormat;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Current state of the recording system
@@ -48,6 +52,23 @@ pub struct RecordingSession {
     
     /// Unix timestamp when session ended
     pub unix_end_ms: u64,
+    
+    /// Why the session started, if not by the user resuming
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boundary: Option<SessionBoundary>,
+}
+
+/// Reason a session was started automatically
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "camelCase")]
+pub enum SessionBoundary {
+    /// The recorded display changed resolution; capture restarted at the
+    /// new size
+    DisplayChanged {
+        display: String,
+        width: u32,
+        height: u32,
+    },
 }
 
 impl RecordingSession {
@@ -61,6 +82,7 @@ impl RecordingSession {
             process_time_end_ms: process_time_ms,
             unix_start_ms: now.timestamp_millis() as u64,
             unix_end_ms: now.timestamp_millis() as u64,
+            boundary: None,
         }
     }
     
@@ -77,11 +99,24 @@ impl RecordingSession {
 #[serde(rename_all = "camelCase")]
 pub struct RecordingConfig {
     /// Display ID to capture
+    #[serde(default)]
     pub display_id: u32,
     
+    /// Whether to capture the screen. When false only the webcam and audio
+    /// are recorded (camera-only recording), or just the audio when the
+    /// webcam is off too (audio-only recording).
+    #[serde(default = "default_capture_screen")]
+    pub capture_screen: bool,
+    
     /// Whether to capture system audio
     pub capture_system_audio: bool,
     
+    /// Output device to capture system audio from, from
+    /// `get_system_audio_devices` (the default output when unset). Windows
+    /// only; other platforms record everything the system plays.
+    #[serde(default)]
+    pub system_audio_device_id: Option<String>,
+    
     /// Whether to capture microphone
     pub capture_microphone: bool,
     
@@ -94,11 +129,488 @@ pub struct RecordingConfig {
     /// Webcam device ID (if capturing)
     pub webcam_device_id: Option<String>,
     
+    /// Webcam format to capture in, from the camera's `supportedFormats`
+    /// (the camera's highest resolution when unset)
+    #[serde(default)]
+    pub webcam_format: Option<CameraFormat>,
+    
     /// Whether to track mouse/keyboard input
     pub track_input: bool,
     
+    /// Mask keystrokes typed into password fields and other secure inputs
+    #[serde(default = "default_mask_secure_input")]
+    pub mask_secure_input: bool,
+    
+    /// Record key presses into a keystrokes track
+    #[serde(default)]
+    pub track_keystrokes: bool,
+    
+    /// Which key presses the keystrokes track keeps
+    #[serde(default)]
+    pub keystroke_mode: KeystrokeMode,
+    
+    /// Sample the frontmost application into an app activity track
+    #[serde(default = "default_track_app_activity")]
+    pub track_app_activity: bool,
+    
+    /// Include window titles in the app activity track. Off by default since
+    /// titles can show document names, email subjects and the like.
+    #[serde(default)]
+    pub record_window_titles: bool,
+    
     /// Output directory for the recording
     pub output_dir: String,
+    
+    /// Capture the display under the cursor at start instead of `display_id`
+    #[serde(default)]
+    pub display_under_cursor: bool,
+    
+    /// Re-pick the display under the cursor each time recording resumes
+    #[serde(default)]
+    pub follow_cursor_on_resume: bool,
+    
+    /// Window to capture instead of the whole display (macOS only)
+    #[serde(default)]
+    pub window_id: Option<u32>,
+    
+    /// Rectangle of the display to record instead of all of it, in the
+    /// display coordinates reported by `get_displays`
+    #[serde(default)]
+    pub region: Option<ContentRect>,
+    
+    /// Frame rate, resolution and encoder quality preset
+    #[serde(default)]
+    pub quality: CaptureQuality,
+    
+    /// Highest frame rate the screen is captured at. The screen follows the
+    /// display's refresh rate up to this.
+    #[serde(default = "default_max_fps")]
+    pub max_fps: u32,
+    
+    /// Keyframe and B-frame settings for the screen and webcam encoders
+    #[serde(default)]
+    pub video_encoding: VideoEncodingConfig,
+    
+    /// Encoder settings for the system audio track
+    #[serde(default)]
+    pub system_audio_encoding: AudioEncodingConfig,
+    
+    /// Record only some applications' audio, or leave some out, instead of
+    /// everything the system plays (macOS and Windows)
+    #[serde(default)]
+    pub audio_app_filter: Option<AudioAppFilter>,
+    
+    /// Encoder settings for the microphone track
+    #[serde(default)]
+    pub microphone_encoding: AudioEncodingConfig,
+    
+    /// Draw the system cursor into the screen video. Off by default, since
+    /// export draws the cursor from input tracking (smoothed, and swappable
+    /// for a cursor pack) and a recorded one would show up twice.
+    #[serde(default)]
+    pub show_system_cursor: bool,
+    
+    /// Keep the app's own windows (toolbar, overlays) out of the recording
+    #[serde(default = "default_exclude_app_windows")]
+    pub exclude_app_windows: bool,
+    
+    /// Sounds and haptics played on start/stop/pause/resume
+    #[serde(default)]
+    pub cues: CueConfig,
+    
+    /// Encrypt the recorded video and audio once recording stops, with a
+    /// per-bundle key kept in the OS keychain
+    #[serde(default)]
+    pub encrypt_media: bool,
+    
+    /// Microphone offset for the project made from this recording; the
+    /// display's A/V calibration is used when unset
+    #[serde(default)]
+    pub microphone_offset_ms: Option<f64>,
+    
+    /// Name of the recorded display, for naming the project; filled in when
+    /// recording starts if unset
+    #[serde(default)]
+    pub display_name: Option<String>,
+    
+    /// Application of the frontmost window when recording started, for
+    /// naming the project; filled in when recording starts if unset
+    #[serde(default)]
+    pub frontmost_app: Option<String>,
+    
+    /// Stop automatically once this much has been recorded, in milliseconds
+    /// (paused time doesn't count)
+    #[serde(default)]
+    pub max_duration_ms: Option<f64>,
+    
+    /// Stop automatically at this time
+    #[serde(default)]
+    pub stop_at: Option<DateTime<Utc>>,
+    
+    /// Global shortcut that adds a marker while recording, as an accelerator
+    /// like "CommandOrControl+Shift+M"; None registers no shortcut
+    #[serde(default = "default_marker_shortcut")]
+    pub marker_shortcut: Option<String>,
+    
+    /// When some permissions are refused, record the sources that are
+    /// permitted instead of refusing to start
+    #[serde(default)]
+    pub allow_degraded_start: bool,
+}
+
+fn default_mask_secure_input() -> bool {
+    true
+}
+
+fn default_track_app_activity() -> bool {
+    true
+}
+
+fn default_capture_screen() -> bool {
+    true
+}
+
+fn default_exclude_app_windows() -> bool {
+    true
+}
+
+fn default_marker_shortcut() -> Option<String> {
+    Some("CommandOrControl+Shift+M".to_string())
+}
+
+fn default_max_fps() -> u32 {
+    crate::capture::frame_rate::DEFAULT_MAX_FPS
+}
+
+impl RecordingConfig {
+    /// Check settings that can't be enforced by deserialization alone
+    pub fn validate(&self) -> Result<(), String> {
+        let records_audio = self.capture_microphone || self.capture_system_audio;
+        if !self.capture_screen && !self.capture_webcam && !records_audio {
+            return Err("Nothing to record: turn on the screen, webcam or audio".to_string());
+        }
+        if let Some(region) = self.region {
+            if region.width < 2 || region.height < 2 {
+                return Err("Capture region is too small".to_string());
+            }
+            if self.window_id.is_some() {
+                return Err("A capture region can't be combined with window capture".to_string());
+            }
+            // The region is relative to one display
+            if self.display_under_cursor || self.follow_cursor_on_resume {
+                return Err("A capture region can't follow the cursor between displays".to_string());
+            }
+        }
+        if !(1..=240).contains(&self.max_fps) {
+            return Err(format!("Maximum frame rate must be between 1 and 240, got {}", self.max_fps));
+        }
+        if let Some(format) = &self.webcam_format {
+            if format.width == 0 || format.height == 0 || format.fps == 0 {
+                return Err("Webcam format needs a size and frame rate".to_string());
+            }
+        }
+        if self.max_duration_ms.is_some_and(|ms| ms <= 0.0) {
+            return Err("Maximum duration must be positive".to_string());
+        }
+        if let Some(filter) = &self.audio_app_filter {
+            if filter.mode == AudioAppFilterMode::Include && filter.app_ids.is_empty() {
+                return Err("Choose at least one app to record audio from".to_string());
+            }
+        }
+        self.video_encoding.validate()?;
+        self.system_audio_encoding
+            .validate()
+            .map_err(|e| format!("System audio: {}", e))?;
+        self.microphone_encoding
+            .validate()
+            .map_err(|e| format!("Microphone: {}", e))
+    }
+}
+
+/// Whether an audio app filter lists the apps to record or the ones to skip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioAppFilterMode {
+    Include,
+    Exclude,
+}
+
+/// Applications whose audio the system audio track records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioAppFilter {
+    pub mode: AudioAppFilterMode,
+    
+    /// App IDs as returned by `get_audio_capture_apps`
+    pub app_ids: Vec<String>,
+}
+
+impl AudioAppFilter {
+    /// Whether the filter applies to the app with this ID
+    pub fn matches(&self, app_id: &str) -> bool {
+        self.app_ids.iter().any(|id| id.eq_ignore_ascii_case(app_id))
+    }
+}
+
+/// Channel layout of an encoded audio track
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioChannelLayout {
+    Mono,
+    Stereo,
+}
+
+impl AudioChannelLayout {
+    pub fn channel_count(&self) -> u16 {
+        match self {
+            AudioChannelLayout::Mono => 1,
+            AudioChannelLayout::Stereo => 2,
+        }
+    }
+}
+
+/// Sample rates the AAC encoder accepts
+pub const SUPPORTED_AUDIO_SAMPLE_RATES: [u32; 8] = [8000, 16000, 22050, 24000, 32000, 44100, 48000, 96000];
+
+/// Audio encoder settings for one track
+///
+/// Unset fields fall back to the capture device's native format and the
+/// default bitrate, which matches the previous hardcoded behavior.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioEncodingConfig {
+    /// Sample rate to open the input device at, if it supports it; the
+    /// device's default rate otherwise. Only microphones use this.
+    #[serde(default)]
+    pub input_sample_rate: Option<u32>,
+    
+    /// Output sample rate in Hz (the input's rate when unset)
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+    
+    /// Output channel layout
+    #[serde(default)]
+    pub channel_layout: Option<AudioChannelLayout>,
+    
+    /// AAC bitrate in kbps
+    #[serde(default)]
+    pub bitrate_kbps: Option<u32>,
+}
+
+impl AudioEncodingConfig {
+    /// Bitrate used when none is configured
+    pub const DEFAULT_BITRATE_KBPS: u32 = 192;
+    
+    /// Effective bitrate in kbps
+    pub fn bitrate_kbps(&self) -> u32 {
+        self.bitrate_kbps.unwrap_or(Self::DEFAULT_BITRATE_KBPS)
+    }
+    
+    /// Check the settings are something the encoder can produce
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(rate) = self.input_sample_rate {
+            if !SUPPORTED_AUDIO_SAMPLE_RATES.contains(&rate) {
+                return Err(format!("Unsupported input sample rate: {}Hz", rate));
+            }
+        }
+        if let Some(rate) = self.sample_rate {
+            if !SUPPORTED_AUDIO_SAMPLE_RATES.contains(&rate) {
+                return Err(format!("Unsupported audio sample rate: {}Hz", rate));
+            }
+        }
+        if let Some(kbps) = self.bitrate_kbps {
+            if !(32..=512).contains(&kbps) {
+                return Err(format!("Audio bitrate must be between 32 and 512 kbps, got {}", kbps));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which key presses are recorded into the keystrokes track
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeystrokeMode {
+    /// Every key
+    All,
+    /// Modifier keys and keys pressed with Cmd/Ctrl, so typed text is never
+    /// recorded
+    #[default]
+    ShortcutsOnly,
+}
+
+/// Recording quality preset
+///
+/// Picks the screen capture frame rate, how far the screen is scaled down
+/// before encoding, the x264 CRF and the default keyframe interval. Balanced
+/// matches recordings made before presets existed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureQuality {
+    /// Half resolution and smaller files, for slower machines
+    Performance,
+    /// Full resolution
+    #[default]
+    Balanced,
+    /// Full resolution, close to lossless
+    MaxQuality,
+}
+
+impl CaptureQuality {
+    /// Screen capture frame rate when the display's refresh rate is unknown
+    /// (see `frame_rate::display_fps`)
+    pub fn fps(&self) -> u32 {
+        match self {
+            CaptureQuality::Performance | CaptureQuality::Balanced => 30,
+            CaptureQuality::MaxQuality => 60,
+        }
+    }
+    
+    /// Factor the screen's width and height are divided by before encoding
+    pub fn downscale(&self) -> u32 {
+        match self {
+            CaptureQuality::Performance => 2,
+            CaptureQuality::Balanced | CaptureQuality::MaxQuality => 1,
+        }
+    }
+    
+    /// x264 constant rate factor; lower is better quality and larger files
+    pub fn crf(&self) -> u32 {
+        match self {
+            CaptureQuality::Performance => 23,
+            CaptureQuality::Balanced => 18,
+            CaptureQuality::MaxQuality => 12,
+        }
+    }
+    
+    /// Seconds between keyframes when `VideoEncodingConfig` doesn't set one
+    pub fn keyframe_interval_secs(&self) -> f64 {
+        match self {
+            CaptureQuality::Performance => 4.0,
+            CaptureQuality::Balanced => 2.0,
+            CaptureQuality::MaxQuality => 1.0,
+        }
+    }
+    
+    /// FFmpeg filter scaling frames down, if the preset does; sizes are kept
+    /// even for yuv420p
+    fn scale_filter(&self) -> Option<String> {
+        let half = 2 * self.downscale();
+        (self.downscale() > 1)
+            .then(|| format!("scale=trunc(iw/{half})*2:trunc(ih/{half})*2"))
+    }
+}
+
+/// Video encoder settings shared by the screen and webcam tracks
+///
+/// Unset fields keep the quality preset's behavior: a keyframe at the
+/// preset's interval, placed early on scene cuts, and the encoder's default
+/// B-frames. Screen recordings are variable frame rate: a frame identical to
+/// the one before isn't encoded, and the frames that are keep their capture
+/// timestamps. A fixed keyframe interval counts frames, so it keeps every
+/// frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoEncodingConfig {
+    /// Seconds between keyframes. When set, keyframes land exactly on the
+    /// interval so segments line up for smart rendering.
+    #[serde(default)]
+    pub keyframe_interval_secs: Option<f64>,
+    
+    /// Maximum consecutive B-frames; 0 disables them
+    #[serde(default)]
+    pub b_frames: Option<u32>,
+    
+    /// Encode every screen frame, including unchanged ones
+    #[serde(default)]
+    pub constant_frame_rate: bool,
+}
+
+impl VideoEncodingConfig {
+    /// Maximum B-frames x264 accepts
+    pub const MAX_B_FRAMES: u32 = 16;
+    
+    /// GOP size in frames at `fps`
+    pub fn gop_size(&self, fps: u32, quality: CaptureQuality) -> u32 {
+        let secs = self
+            .keyframe_interval_secs
+            .unwrap_or_else(|| quality.keyframe_interval_secs());
+        ((fps as f64 * secs).round() as u32).max(1)
+    }
+    
+    /// FFmpeg output arguments for an x264 encode at `fps`: the preset's CRF,
+    /// GOP size and B-frames
+    pub fn ffmpeg_args(&self, fps: u32, quality: CaptureQuality) -> Vec<String> {
+        let gop = self.gop_size(fps, quality).to_string();
+        let mut args = vec!["-crf".to_string(), quality.crf().to_string()];
+        args.extend(["-g".to_string(), gop.clone()]);
+        if self.keyframe_interval_secs.is_some() {
+            // Fixed GOP: no shorter GOPs and no extra keyframes on scene cuts
+            args.extend(["-keyint_min".to_string(), gop]);
+            args.extend(["-sc_threshold", "0"].map(String::from));
+        }
+        if let Some(b_frames) = self.b_frames {
+            args.extend(["-bf".to_string(), b_frames.to_string()]);
+        }
+        args
+    }
+    
+    /// Whether unchanged screen frames are skipped
+    pub fn is_variable_frame_rate(&self) -> bool {
+        !self.constant_frame_rate && self.keyframe_interval_secs.is_none()
+    }
+    
+    /// FFmpeg filter and frame rate arguments for a screen encode at `fps`:
+    /// `leading` (e.g. a crop), the preset's scaling, then dropping frames
+    /// identical to the previous one. At least one frame a second is kept
+    /// so a static screen still refreshes; frames dropped at the very end
+    /// are made up for by `segment::finalize_session` holding the last one.
+    pub fn screen_filter_args(
+        &self,
+        fps: u32,
+        quality: CaptureQuality,
+        leading: Option<String>,
+    ) -> Vec<String> {
+        let variable = self.is_variable_frame_rate();
+        let decimate =
+            variable.then(|| format!("mpdecimate=hi=0:lo=0:frac=0:max={}", fps.max(1)));
+        let filters: Vec<String> = leading
+            .into_iter()
+            .chain(quality.scale_filter())
+            .chain(decimate)
+            .collect();
+        let mut args = Vec::new();
+        if !filters.is_empty() {
+            args.extend(["-vf".to_string(), filters.join(",")]);
+        }
+        if variable {
+            // Keep the timestamps of the frames that are left
+            args.extend(["-fps_mode", "vfr"].map(String::from));
+        }
+        args
+    }
+    
+    /// Check the settings are something the encoder can produce
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(secs) = self.keyframe_interval_secs {
+            if !(0.1..=60.0).contains(&secs) {
+                return Err(format!(
+                    "Keyframe interval must be between 0.1 and 60 seconds, got {}",
+                    secs
+                ));
+            }
+        }
+        if let Some(b_frames) = self.b_frames {
+            if b_frames > Self::MAX_B_FRAMES {
+                return Err(format!(
+                    "At most {} B-frames are supported, got {}",
+                    Self::MAX_B_FRAMES,
+                    b_frames
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Result of a completed recording
@@ -116,4 +628,9 @@ pub struct RecordingResult {
     
     /// List of output files created
     pub output_files: Vec<String>,
+    
+    /// How each channel fared, so a channel that failed partway through
+    /// isn't mistaken for one that recorded nothing
+    #[serde(default)]
+    pub channels: Vec<ChannelStatus>,
 }
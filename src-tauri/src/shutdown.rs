@@ -0,0 +1,118 @@
+//! Graceful shutdown
+//!
+//! Quitting while recording or exporting would kill FFmpeg mid-write and
+//! leave corrupt files. The first exit request (quit, or closing the last
+//! window) is held back while the recording is stopped, finalizing its files
+//! as a normal stop does, and any export is cancelled and left to clean up.
+//! The stopped recording is kept for recovery so it can still be saved on
+//! the next launch. If this takes longer than `FINALIZE_TIMEOUT` the user is
+//! asked whether to quit anyway and discard what isn't finalized.
+
+use crate::commands::export::ExportState;
+use crate::commands::recording::{self as recording_commands, RecorderState};
+use crate::recorder::{recovery, RecordingState};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, ExitRequestApi, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+/// How long to wait for finalization before offering to quit anyway
+const FINALIZE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Set once shutdown has started; later exit requests (including our own)
+/// go through
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Handle `RunEvent::ExitRequested`, holding the exit back while there is
+/// work to finalize
+pub fn on_exit_requested(app: &AppHandle, api: &ExitRequestApi) {
+    if SHUTTING_DOWN.load(Ordering::SeqCst) || !has_work_in_progress(app) {
+        return;
+    }
+    api.prevent_exit();
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    tracing::info!("Exit requested while busy, finalizing first");
+    tauri::async_runtime::spawn(shutdown(app.clone()));
+}
+
+fn has_work_in_progress(app: &AppHandle) -> bool {
+    let recording = match app.state::<RecorderState>().coordinator.try_lock() {
+        Ok(coordinator) => coordinator.state() != RecordingState::Idle,
+        // A command is using the recorder (e.g. starting or stopping)
+        Err(_) => true,
+    };
+    recording || app.state::<ExportState>().is_running()
+}
+
+async fn shutdown(app: AppHandle) {
+    let mut finalizing = tauri::async_runtime::spawn(finalize(app.clone()));
+    loop {
+        match tokio::time::timeout(FINALIZE_TIMEOUT, &mut finalizing).await {
+            Ok(Ok(())) => break,
+            Ok(Err(e)) => {
+                tracing::error!("Finalizing before exit failed: {}", e);
+                break;
+            }
+            Err(_) if confirm_quit_anyway(&app).await => {
+                tracing::warn!("Quitting before finalization completed");
+                break;
+            }
+            Err(_) => {}
+        }
+    }
+    app.exit(0);
+}
+
+/// Stop the recording and any export, waiting for both to finish writing
+async fn finalize(app: AppHandle) {
+    let export = app.state::<ExportState>();
+    if export.is_running() {
+        tracing::info!("Cancelling export before exit");
+        export.cancel();
+    }
+
+    let recorder = app.state::<RecorderState>();
+    let config = {
+        let coordinator = recorder.coordinator.lock().await;
+        (coordinator.state() != RecordingState::Idle)
+            .then(|| coordinator.config().cloned())
+            .flatten()
+    };
+    if let Some(config) = config {
+        tracing::info!("Stopping recording before exit");
+        match recording_commands::stop_recording(app.clone(), recorder).await {
+            Ok(result) => {
+                // Nobody got to save it; offer it on the next launch
+                let recording_dir = Path::new(&result.bundle_path).join("recording");
+                if let Err(e) = recovery::mark_unsaved(&recording_dir, &config) {
+                    tracing::warn!("Failed to keep recording for recovery: {}", e);
+                }
+            }
+            Err(e) => tracing::error!("Failed to stop recording before exit: {}", e),
+        }
+    }
+
+    while export.is_running() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Ask whether to quit without waiting any longer
+async fn confirm_quit_anyway(app: &AppHandle) -> bool {
+    let dialog = app
+        .dialog()
+        .message(
+            "Open ScreenStudio is still saving your recording or export. Quitting now may \
+             leave it incomplete.",
+        )
+        .title("Still finishing up")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Quit Anyway".to_string(),
+            "Keep Waiting".to_string(),
+        ));
+    tokio::task::spawn_blocking(move || dialog.blocking_show())
+        .await
+        .unwrap_or(false)
+}
@@ -1,8 +1,15 @@
 //! Error types and handling
 //!
-//! Common error types used across the application.
+//! Common error types used across the application. Commands return
+//! `AppResult`, and errors reach the frontend as an `ErrorResponse` with a
+//! stable `code` it can branch on.
 
-use serde::{Deserialize, Serialize};
+use crate::export::share::ShareError;
+use crate::export::ExportError;
+use crate::project::bundle::BundleError;
+use crate::project::encryption::EncryptionError;
+use crate::recorder::channel::RecordingError;
+use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
 
 /// Application-wide error type
@@ -10,36 +17,54 @@ use thiserror::Error;
 pub enum AppError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
-    
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
-    
+
     #[error("Project error: {0}")]
     Project(String),
-    
+
     #[error("Recording error: {0}")]
     Recording(String),
-    
+
     #[error("Export error: {0}")]
     Export(String),
-    
+
     #[error("Platform error: {0}")]
     Platform(String),
-    
+
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
-}
 
-/// Error response for frontend
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ErrorResponse {
-    pub code: String,
-    pub message: String,
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Device not found: {0}")]
+    DeviceNotFound(String),
+
+    #[error("{0}")]
+    FfmpegMissing(String),
+
+    #[error("FFmpeg error: {0}")]
+    Ffmpeg(String),
+
+    #[error("{0}")]
+    Busy(String),
+
+    #[error("Read-only: {0}")]
+    ReadOnly(String),
+
+    #[error("Cancelled")]
+    Cancelled,
 }
 
-impl From<AppError> for ErrorResponse {
-    fn from(error: AppError) -> Self {
-        let code = match &error {
+impl AppError {
+    /// Stable code the frontend can branch on
+    pub fn code(&self) -> &'static str {
+        match self {
             AppError::Io(_) => "IO_ERROR",
             AppError::Serialization(_) => "SERIALIZATION_ERROR",
             AppError::Project(_) => "PROJECT_ERROR",
@@ -47,14 +72,124 @@ impl From<AppError> for ErrorResponse {
             AppError::Export(_) => "EXPORT_ERROR",
             AppError::Platform(_) => "PLATFORM_ERROR",
             AppError::PermissionDenied(_) => "PERMISSION_DENIED",
-        };
-        
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::InvalidInput(_) => "INVALID_INPUT",
+            AppError::DeviceNotFound(_) => "DEVICE_NOT_FOUND",
+            AppError::FfmpegMissing(_) => "FFMPEG_MISSING",
+            AppError::Ffmpeg(_) => "FFMPEG_ERROR",
+            AppError::Busy(_) => "BUSY",
+            AppError::ReadOnly(_) => "READ_ONLY",
+            AppError::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+/// Error response for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub code: String,
+    pub message: String,
+}
+
+impl From<AppError> for ErrorResponse {
+    fn from(error: AppError) -> Self {
         ErrorResponse {
-            code: code.to_string(),
+            code: error.code().to_string(),
             message: error.to_string(),
         }
     }
 }
 
+/// Commands return `AppError`, which Tauri sends to the frontend serialized
+/// as an `ErrorResponse`
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ErrorResponse {
+            code: self.code().to_string(),
+            message: self.to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<RecordingError> for AppError {
+    fn from(error: RecordingError) -> Self {
+        match error {
+            RecordingError::PermissionDenied(msg) => AppError::PermissionDenied(msg),
+            RecordingError::DeviceNotFound(msg) => AppError::DeviceNotFound(msg),
+            RecordingError::FfmpegMissing(msg) => AppError::FfmpegMissing(msg),
+            RecordingError::AlreadyRecording => AppError::Busy(error.to_string()),
+            RecordingError::IoError(e) => AppError::Io(e),
+            RecordingError::PlatformError(msg) => AppError::Platform(msg),
+            other => AppError::Recording(other.to_string()),
+        }
+    }
+}
+
+impl From<ExportError> for AppError {
+    fn from(error: ExportError) -> Self {
+        match error {
+            ExportError::Io(e) => AppError::Io(e),
+            ExportError::FfmpegMissing => AppError::FfmpegMissing(error.to_string()),
+            ExportError::Ffmpeg(msg) => AppError::Ffmpeg(msg),
+            ExportError::BundleNotFound(msg) => AppError::NotFound(msg),
+            ExportError::InvalidConfig(msg) => AppError::InvalidInput(msg),
+            ExportError::Cancelled => AppError::Cancelled,
+            other => AppError::Export(other.to_string()),
+        }
+    }
+}
+
+impl From<BundleError> for AppError {
+    fn from(error: BundleError) -> Self {
+        match error {
+            BundleError::Io(e) => AppError::Io(e),
+            BundleError::Json(e) => AppError::Serialization(e),
+            BundleError::MissingFile(msg) => AppError::NotFound(msg),
+            BundleError::InvalidBundle(msg) => AppError::Project(msg),
+        }
+    }
+}
+
+impl From<ShareError> for AppError {
+    fn from(error: ShareError) -> Self {
+        match error {
+            ShareError::Io(e) => AppError::Io(e),
+            ShareError::Json(e) => AppError::Serialization(e),
+            ShareError::InvalidConfig(msg) => AppError::InvalidInput(msg),
+            ShareError::NotAnExport(_) => AppError::InvalidInput(error.to_string()),
+            ShareError::Keychain(msg) => AppError::Platform(msg),
+            other => AppError::Export(other.to_string()),
+        }
+    }
+}
+
+impl From<EncryptionError> for AppError {
+    fn from(error: EncryptionError) -> Self {
+        match error {
+            EncryptionError::Io(e) => AppError::Io(e),
+            EncryptionError::Keychain(msg) => AppError::Platform(msg),
+            other => AppError::Project(other.to_string()),
+        }
+    }
+}
+
 /// Result type alias using AppError
 pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_serialize_with_codes() {
+        let missing: AppError = ExportError::FfmpegMissing.into();
+        let json = serde_json::to_value(&missing).unwrap();
+        assert_eq!(json["code"], "FFMPEG_MISSING");
+
+        let denied: AppError = RecordingError::PermissionDenied("screen".to_string()).into();
+        let response = ErrorResponse::from(denied);
+        assert_eq!(response.code, "PERMISSION_DENIED");
+        assert_eq!(response.message, "Permission denied: screen");
+    }
+}
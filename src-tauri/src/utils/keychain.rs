@@ -0,0 +1,36 @@
+//! OS keychain access
+//!
+//! Secrets are stored in the system keychain (Keychain on macOS, Credential
+//! Manager on Windows, Secret Service on Linux) under the app's service name.
+//! Values are cached for the lifetime of the process so reading many files
+//! doesn't prompt or hit the keychain repeatedly.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Service name entries are stored under
+const SERVICE: &str = "Open ScreenStudio";
+
+static CACHE: LazyLock<Mutex<HashMap<String, Vec<u8>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Store a secret under `account`, replacing any existing one
+pub fn store_secret(account: &str, secret: &[u8]) -> Result<(), keyring::Error> {
+    keyring::Entry::new(SERVICE, account)?.set_password(&hex::encode(secret))?;
+    CACHE.lock().insert(account.to_string(), secret.to_vec());
+    Ok(())
+}
+
+/// Load the secret stored under `account`
+pub fn load_secret(account: &str) -> Result<Vec<u8>, keyring::Error> {
+    if let Some(secret) = CACHE.lock().get(account) {
+        return Ok(secret.clone());
+    }
+
+    let encoded = keyring::Entry::new(SERVICE, account)?.get_password()?;
+    let secret = hex::decode(encoded)
+        .map_err(|e| keyring::Error::BadEncoding(e.to_string().into_bytes()))?;
+    CACHE.lock().insert(account.to_string(), secret.clone());
+    Ok(secret)
+}
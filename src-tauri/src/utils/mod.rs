@@ -3,3 +3,5 @@
 //! Common utilities used across the application.
 
 pub mod error;
+pub mod keychain;
+pub mod process;
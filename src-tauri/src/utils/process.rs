@@ -0,0 +1,297 @@
+//! Child process supervision
+//!
+//! Capture and export spawn many FFmpeg processes. Every long-running child
+//! is registered here so that none outlive the app: children are killed
+//! when the app exits or panics on its main thread, and children dropped
+//! without being waited for are kept until they exit and are reaped, instead
+//! of lingering as zombies. The registry holds each child's `Child` handle,
+//! so a process is only ever killed while it's known to be ours: once it's
+//! been waited for, its pid may belong to something else.
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, ExitStatus, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often dropped children are checked for exit
+const REAP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often `TrackedChild::wait` checks whether the child has exited. The
+/// handle isn't held between checks, so the child can be killed meanwhile.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A registered child process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// What the process is for, e.g. "audio encoder"
+    pub label: String,
+    pub started_at: DateTime<Utc>,
+    /// True if its owner dropped it while it was still running
+    pub orphaned: bool,
+}
+
+/// Handle to a child, shared by its owner and the registry
+type SharedChild = Arc<Mutex<Child>>;
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    /// Children still held by their owner
+    live: HashMap<u64, (ProcessInfo, SharedChild)>,
+    /// Children dropped before they exited, waiting to be reaped
+    orphans: Vec<(ProcessInfo, SharedChild)>,
+}
+
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(|| Mutex::new(Registry::default()));
+
+/// The reaper thread and the flag that stops it
+static SUPERVISOR: Mutex<Option<(JoinHandle<()>, Arc<AtomicBool>)>> = Mutex::new(None);
+
+/// A child process registered with the supervisor.
+///
+/// Offers the parts of `Child` its owners use, with the pipes taken out so
+/// they can be used while the supervisor holds the process. When dropped,
+/// the process is unregistered once it has exited; a still-running process
+/// is handed to the supervisor instead of being leaked.
+pub struct TrackedChild {
+    id: u64,
+    pid: u32,
+    child: SharedChild,
+    pub stdin: Option<ChildStdin>,
+    pub stdout: Option<ChildStdout>,
+    pub stderr: Option<ChildStderr>,
+}
+
+/// Register a spawned child process
+pub fn track(mut child: Child, label: impl Into<String>) -> TrackedChild {
+    let pid = child.id();
+    let info = ProcessInfo {
+        pid,
+        label: label.into(),
+        started_at: Utc::now(),
+        orphaned: false,
+    };
+    let (stdin, stdout, stderr) = (child.stdin.take(), child.stdout.take(), child.stderr.take());
+    let child = Arc::new(Mutex::new(child));
+
+    let mut registry = REGISTRY.lock();
+    let id = registry.next_id;
+    registry.next_id += 1;
+    registry.live.insert(id, (info, child.clone()));
+
+    TrackedChild {
+        id,
+        pid,
+        child,
+        stdin,
+        stdout,
+        stderr,
+    }
+}
+
+impl TrackedChild {
+    /// OS process id
+    pub fn id(&self) -> u32 {
+        self.pid
+    }
+
+    /// Kill the process, if it's still running
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.lock().kill()
+    }
+
+    /// Exit status, if the process has exited
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.child.lock().try_wait()
+    }
+
+    /// Wait for the process to exit. Stdin is closed first, as `Child`
+    /// does, so a process reading it can finish.
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        drop(self.stdin.take());
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+            std::thread::sleep(WAIT_POLL_INTERVAL);
+        }
+    }
+
+    /// Wait for the process to exit and collect its output
+    pub fn wait_with_output(mut self) -> io::Result<Output> {
+        drop(self.stdin.take());
+        let read_all = |mut pipe: Box<dyn Read + Send>| {
+            let mut output = Vec::new();
+            pipe.read_to_end(&mut output).map(|_| output)
+        };
+        // Both pipes are read at once so neither fills up and stalls FFmpeg
+        let stderr = self
+            .stderr
+            .take()
+            .map(|pipe| std::thread::spawn(move || read_all(Box::new(pipe))));
+        let stdout = match self.stdout.take() {
+            Some(pipe) => read_all(Box::new(pipe))?,
+            None => Vec::new(),
+        };
+        let stderr = match stderr {
+            Some(reader) => reader
+                .join()
+                .map_err(|_| io::Error::other("stderr reader panicked"))??,
+            None => Vec::new(),
+        };
+        Ok(Output {
+            status: self.wait()?,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+impl Drop for TrackedChild {
+    fn drop(&mut self) {
+        let mut registry = REGISTRY.lock();
+        let Some((mut info, child)) = registry.live.remove(&self.id) else {
+            return;
+        };
+
+        let running = matches!(child.lock().try_wait(), Ok(None));
+        if running {
+            tracing::debug!("{} (pid {}) dropped while running", info.label, info.pid);
+            info.orphaned = true;
+            registry.orphans.push((info, child));
+        }
+    }
+}
+
+/// Wait on dropped children that have exited. Returns how many were reaped.
+pub fn reap_orphans() -> usize {
+    let mut registry = REGISTRY.lock();
+    let before = registry.orphans.len();
+    registry
+        .orphans
+        .retain(|(_, child)| matches!(child.lock().try_wait(), Ok(None)));
+    before - registry.orphans.len()
+}
+
+/// Registered processes that are still running (or not yet waited for)
+pub fn active_processes() -> Vec<ProcessInfo> {
+    reap_orphans();
+    let registry = REGISTRY.lock();
+    let mut processes: Vec<ProcessInfo> = registry
+        .live
+        .values()
+        .chain(&registry.orphans)
+        .map(|(info, _)| info.clone())
+        .collect();
+    processes.sort_by_key(|p| p.started_at);
+    processes
+}
+
+/// Kill every registered process
+pub fn kill_all() {
+    // Called from the panic hook, so don't block on a lock the panicking
+    // thread may hold
+    let Some(mut registry) = REGISTRY.try_lock_for(Duration::from_millis(500)) else {
+        tracing::warn!("Process registry busy, not killing child processes");
+        return;
+    };
+
+    let orphans = registry.orphans.drain(..).collect::<Vec<_>>();
+    for (info, child) in registry.live.values().chain(&orphans) {
+        let Some(mut child) = child.try_lock_for(Duration::from_millis(100)) else {
+            tracing::warn!("{} (pid {}) busy, not killing it", info.label, info.pid);
+            continue;
+        };
+        // Already exited (and now reaped): nothing of ours to kill
+        if !matches!(child.try_wait(), Ok(None)) {
+            continue;
+        }
+        tracing::info!("Killing {} (pid {})", info.label, info.pid);
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Reap dropped children in the background and kill all children if the
+/// app panics on its main thread. Panics elsewhere are caught by their
+/// thread or task and the app carries on, children included.
+pub fn install_supervisor() {
+    let stop = Arc::new(AtomicBool::new(false));
+    let reaper = std::thread::spawn({
+        let stop = stop.clone();
+        move || {
+            while !stop.load(Ordering::SeqCst) {
+                std::thread::park_timeout(REAP_INTERVAL);
+                let reaped = reap_orphans();
+                if reaped > 0 {
+                    tracing::debug!("Reaped {} child processes", reaped);
+                }
+            }
+        }
+    });
+    *SUPERVISOR.lock() = Some((reaper, stop));
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        if std::thread::current().name() == Some("main") {
+            kill_all();
+        }
+        default_hook(panic_info);
+    }));
+}
+
+/// Stop the reaper thread and kill whatever children are left, as the app
+/// exits
+pub fn shutdown() {
+    if let Some((reaper, stop)) = SUPERVISOR.lock().take() {
+        stop.store(true, Ordering::SeqCst);
+        reaper.thread().unpark();
+        let _ = reaper.join();
+    }
+    kill_all();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn sleeper(seconds: &str) -> Child {
+        Command::new("sleep").arg(seconds).spawn().unwrap()
+    }
+
+    #[test]
+    fn test_dropped_children_are_reaped() {
+        let waited = track(sleeper("0"), "test waited");
+        let pid = waited.id();
+        assert!(active_processes().iter().any(|p| p.pid == pid));
+        assert!(waited.wait_with_output().unwrap().status.success());
+        assert!(!active_processes().iter().any(|p| p.pid == pid));
+
+        // Dropped while running: kept as an orphan until it exits
+        let mut orphan = track(sleeper("0.2"), "test orphan");
+        let pid = orphan.id();
+        assert!(orphan.try_wait().unwrap().is_none());
+        drop(orphan);
+        assert!(active_processes().iter().any(|p| p.pid == pid && p.orphaned));
+
+        std::thread::sleep(Duration::from_millis(400));
+        assert!(!active_processes().iter().any(|p| p.pid == pid));
+
+        // Killed while its owner waits on it
+        let mut killed = track(sleeper("30"), "test killed");
+        let pid = killed.id();
+        killed.kill().unwrap();
+        assert!(!killed.wait().unwrap().success());
+        drop(killed);
+        assert!(!active_processes().iter().any(|p| p.pid == pid));
+    }
+}
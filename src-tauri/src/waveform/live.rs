@@ -0,0 +1,178 @@
+//! Waveforms computed during recording
+//!
+//! Audio channels fold their samples into peaks as they are captured and
+//! write them next to the M4A when recording stops. The editor can then show
+//! waveforms right away instead of decoding the audio with FFmpeg first.
+
+use super::extractor::WaveformData;
+use crate::export::verify::sha256_file;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Peaks per second written during recording (matches the editor's default)
+pub const LIVE_PEAKS_PER_SECOND: u32 = 50;
+
+/// Rolling peak computation over interleaved f32 samples
+pub struct LivePeaks {
+    sample_rate: u32,
+    channels: usize,
+    /// Sample frames folded into each peak
+    frames_per_peak: usize,
+    frames_in_peak: usize,
+    current_peak: f32,
+    total_frames: u64,
+    peaks: Vec<f32>,
+}
+
+impl LivePeaks {
+    pub fn new(sample_rate: u32, channels: u16, peaks_per_second: u32) -> Self {
+        Self {
+            sample_rate,
+            channels: channels.max(1) as usize,
+            frames_per_peak: (sample_rate / peaks_per_second.max(1)).max(1) as usize,
+            frames_in_peak: 0,
+            current_peak: 0.0,
+            total_frames: 0,
+            peaks: Vec::new(),
+        }
+    }
+
+    /// Fold interleaved samples into the peaks
+    pub fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            let amplitude = frame.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+            self.current_peak = self.current_peak.max(amplitude);
+            self.frames_in_peak += 1;
+            self.total_frames += 1;
+
+            if self.frames_in_peak == self.frames_per_peak {
+                self.peaks.push(self.current_peak.min(1.0));
+                self.current_peak = 0.0;
+                self.frames_in_peak = 0;
+            }
+        }
+    }
+
+    /// Most recent completed peak (0.0-1.0), for level meters
+    pub fn level(&self) -> f32 {
+        self.peaks.last().copied().unwrap_or(0.0)
+    }
+
+    /// Waveform of everything pushed so far, including a partial last peak
+    pub fn to_waveform(&self) -> WaveformData {
+        let mut peaks = self.peaks.clone();
+        if self.frames_in_peak > 0 {
+            peaks.push(self.current_peak.min(1.0));
+        }
+
+        WaveformData {
+            peaks,
+            duration_ms: self.total_frames * 1000 / self.sample_rate.max(1) as u64,
+            samples_per_second: self.sample_rate / self.frames_per_peak as u32,
+        }
+    }
+}
+
+/// Path of the waveform written alongside a recorded audio file
+pub fn waveform_path(audio_path: &Path) -> PathBuf {
+    let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy();
+    audio_path.with_file_name(format!("{}-waveform.json", stem))
+}
+
+/// Contents of a waveform file: the peaks and the audio they belong to
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordedWaveform {
+    /// SHA-256 of the audio file when the waveform was written
+    audio_sha256: String,
+    #[serde(flatten)]
+    waveform: WaveformData,
+}
+
+/// Write the waveform computed for `audio_path` during recording
+pub fn write_waveform(audio_path: &Path, waveform: &WaveformData) -> std::io::Result<()> {
+    let recorded = RecordedWaveform {
+        audio_sha256: sha256_file(audio_path)?,
+        waveform: waveform.clone(),
+    };
+    let json = serde_json::to_string(&recorded).map_err(std::io::Error::other)?;
+    std::fs::write(waveform_path(audio_path), json)
+}
+
+/// Read the waveform recorded for `audio_path` if it has the requested
+/// resolution and the audio is still the one it was computed from
+///
+/// The audio's content is compared rather than its modification time,
+/// which copies and syncs don't keep.
+pub fn read_waveform(audio_path: &Path, samples_per_second: u32) -> Option<WaveformData> {
+    let content = std::fs::read_to_string(waveform_path(audio_path)).ok()?;
+    let recorded: RecordedWaveform = serde_json::from_str(&content).ok()?;
+    if recorded.waveform.samples_per_second != samples_per_second {
+        return None;
+    }
+    // Audio replaced after recording (e.g. an imported voiceover)
+    (sha256_file(audio_path).ok()? == recorded.audio_sha256).then_some(recorded.waveform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_peaks_fold_channels_and_flush_partial_peak() {
+        // 100Hz stereo at 10 peaks/sec: 10 frames per peak
+        let mut live = LivePeaks::new(100, 2, 10);
+        let mut samples = vec![0.0f32; 25 * 2];
+        samples[3] = -0.8; // right channel, frame 1
+        samples[24] = 0.3; // left channel, frame 12
+        samples[45] = 1.5; // clipped, frame 22
+        live.push(&samples);
+
+        assert_eq!(live.level(), 0.3);
+        let waveform = live.to_waveform();
+        assert_eq!(waveform.peaks, vec![0.8, 0.3, 1.0]);
+        assert_eq!(waveform.duration_ms, 250);
+        assert_eq!(waveform.samples_per_second, 10);
+    }
+
+    #[test]
+    fn test_waveform_file_sits_next_to_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio = dir.path().join("recording-0-mic.m4a");
+        assert_eq!(waveform_path(&audio), dir.path().join("recording-0-mic-waveform.json"));
+
+        std::fs::write(&audio, b"aac").unwrap();
+        let waveform = WaveformData {
+            peaks: vec![0.5],
+            duration_ms: 20,
+            samples_per_second: LIVE_PEAKS_PER_SECOND,
+        };
+        write_waveform(&audio, &waveform).unwrap();
+
+        assert_eq!(read_waveform(&audio, LIVE_PEAKS_PER_SECOND).unwrap().peaks, vec![0.5]);
+        assert!(read_waveform(&audio, 100).is_none());
+    }
+
+    #[test]
+    fn test_waveform_follows_audio_content_not_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio = dir.path().join("recording-0-mic.m4a");
+        std::fs::write(&audio, b"aac").unwrap();
+        let waveform = WaveformData {
+            peaks: vec![0.5],
+            duration_ms: 20,
+            samples_per_second: LIVE_PEAKS_PER_SECOND,
+        };
+        write_waveform(&audio, &waveform).unwrap();
+
+        // Copied with a newer modification time: still the same audio
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        let file = std::fs::File::options().write(true).open(&audio).unwrap();
+        file.set_modified(later).unwrap();
+        assert!(read_waveform(&audio, LIVE_PEAKS_PER_SECOND).is_some());
+
+        // Replaced by other audio of the same size
+        std::fs::write(&audio, b"mp3").unwrap();
+        assert!(read_waveform(&audio, LIVE_PEAKS_PER_SECOND).is_none());
+    }
+}
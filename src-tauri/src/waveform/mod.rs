@@ -1,27 +1,86 @@
 //! Waveform extraction module
 //!
-//! Extracts audio waveform peaks from audio files for visualization.
+//! Extracts audio waveform peaks from audio files for visualization, and
+//! serves short decoded chunks for audible scrubbing. Waveforms computed
+//! while recording are used instead of decoding when available.
 
 mod extractor;
+pub mod live;
+mod scrub;
 
 pub use extractor::{extract_waveform, WaveformData};
+pub use scrub::{ScrubChunk, ScrubState, SCRUB_CHUNK_MS};
 
-use tauri::command;
+use crate::export::ExportError;
+use crate::utils::error::{AppError, AppResult};
+use tauri::{command, State};
+
+/// Map a decoding failure to an `AppError`, distinguishing a missing FFmpeg
+fn decode_error(error: Box<dyn std::error::Error + Send + Sync>) -> AppError {
+    match error.downcast::<std::io::Error>() {
+        Ok(e) if e.kind() == std::io::ErrorKind::NotFound => ExportError::FfmpegMissing.into(),
+        Ok(e) => AppError::Io(*e),
+        Err(e) => AppError::Ffmpeg(e.to_string()),
+    }
+}
 
 /// Tauri command to extract waveform data from an audio file
 #[command]
 pub async fn get_waveform(
     audio_path: String,
     samples_per_second: Option<u32>,
-) -> Result<WaveformData, String> {
+) -> AppResult<WaveformData> {
     let sps = samples_per_second.unwrap_or(50); // 50 peaks/sec for smooth visualization
     let path = std::path::Path::new(&audio_path);
 
     if !path.exists() {
-        return Err(format!("Audio file not found: {}", audio_path));
+        return Err(AppError::NotFound(format!("Audio file not found: {}", audio_path)));
+    }
+
+    if let Some(waveform) = live::read_waveform(path, sps) {
+        return Ok(waveform);
     }
 
     extract_waveform(path, sps)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(decode_error)
+}
+
+/// Tauri command to get the decoded scrub chunk containing a source position
+#[command]
+pub async fn get_scrub_chunk(
+    state: State<'_, ScrubState>,
+    audio_path: String,
+    position_ms: f64,
+) -> AppResult<ScrubChunk> {
+    let path = std::path::Path::new(&audio_path);
+
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("Audio file not found: {}", audio_path)));
+    }
+
+    scrub::get_chunk(&state, path, position_ms)
+        .await
+        .map_err(decode_error)
+}
+
+/// Tauri command to pre-decode scrub chunks for a source range
+///
+/// Returns the number of chunks added to the cache.
+#[command]
+pub async fn prefetch_scrub_chunks(
+    state: State<'_, ScrubState>,
+    audio_path: String,
+    start_ms: f64,
+    end_ms: f64,
+) -> AppResult<usize> {
+    let path = std::path::Path::new(&audio_path);
+
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("Audio file not found: {}", audio_path)));
+    }
+
+    scrub::prefetch_range(&state, path, start_ms, end_ms)
+        .await
+        .map_err(decode_error)
 }
@@ -0,0 +1,273 @@
+//! Audio scrub chunks
+//!
+//! Decodes tiny PCM chunks aligned to a fixed timeline grid so the editor can
+//! play audible feedback while scrubbing, without decoding the whole M4A in
+//! the webview. Decoded chunks are kept in a small LRU cache.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Length of one scrub chunk in milliseconds
+pub const SCRUB_CHUNK_MS: u64 = 100;
+
+/// Sample rate scrub chunks are decoded at (mono)
+pub const SCRUB_SAMPLE_RATE: u32 = 22050;
+
+/// Fade applied at both chunk edges to avoid clicks between chunks
+const EDGE_FADE_MS: u64 = 5;
+
+/// Maximum number of chunks kept in memory (~25s of audio)
+const CACHE_CAPACITY: usize = 256;
+
+/// A short decoded audio chunk, ready for a WebAudio `AudioBuffer`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrubChunk {
+    /// Chunk start on the source timeline
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    pub sample_rate: u32,
+    /// Mono samples in the -1.0..1.0 range
+    pub samples: Vec<f32>,
+}
+
+/// Index of the chunk containing a source position
+pub fn chunk_index(position_ms: f64) -> u64 {
+    (position_ms.max(0.0) as u64) / SCRUB_CHUNK_MS
+}
+
+/// LRU cache of decoded chunks keyed by (file, chunk index)
+#[derive(Default)]
+pub struct ScrubCache {
+    chunks: HashMap<(PathBuf, u64), ScrubChunk>,
+    order: VecDeque<(PathBuf, u64)>,
+}
+
+impl ScrubCache {
+    pub fn get(&mut self, path: &Path, index: u64) -> Option<ScrubChunk> {
+        let key = (path.to_path_buf(), index);
+        let chunk = self.chunks.get(&key)?.clone();
+        self.touch(key);
+        Some(chunk)
+    }
+
+    pub fn insert(&mut self, path: &Path, index: u64, chunk: ScrubChunk) {
+        let key = (path.to_path_buf(), index);
+        self.chunks.insert(key.clone(), chunk);
+        self.touch(key);
+
+        while self.order.len() > CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.chunks.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn contains(&self, path: &Path, index: u64) -> bool {
+        self.chunks.contains_key(&(path.to_path_buf(), index))
+    }
+
+    /// Drop all chunks decoded from a file (e.g. after the track was replaced)
+    pub fn invalidate(&mut self, path: &Path) {
+        self.chunks.retain(|(p, _), _| p != path);
+        self.order.retain(|(p, _)| p != path);
+    }
+
+    fn touch(&mut self, key: (PathBuf, u64)) {
+        self.order.retain(|k| k != &key);
+        self.order.push_back(key);
+    }
+}
+
+/// Tauri state holding the scrub chunk cache
+#[derive(Default)]
+pub struct ScrubState {
+    pub cache: Mutex<ScrubCache>,
+}
+
+/// Decode the chunk containing `position_ms`, using the cache when possible
+pub async fn get_chunk(
+    state: &ScrubState,
+    audio_path: &Path,
+    position_ms: f64,
+) -> Result<ScrubChunk, Box<dyn std::error::Error + Send + Sync>> {
+    let index = chunk_index(position_ms);
+    if let Some(chunk) = state.cache.lock().get(audio_path, index) {
+        return Ok(chunk);
+    }
+
+    let mut chunks = decode_chunks(audio_path, index, 1).await?;
+    let chunk = chunks.pop().ok_or("No audio decoded at this position")?;
+    state.cache.lock().insert(audio_path, index, chunk.clone());
+    Ok(chunk)
+}
+
+/// Pre-decode all chunks in a source range with a single FFmpeg run.
+///
+/// Returns the number of chunks newly added to the cache.
+pub async fn prefetch_range(
+    state: &ScrubState,
+    audio_path: &Path,
+    start_ms: f64,
+    end_ms: f64,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let first = chunk_index(start_ms);
+    let last = chunk_index(end_ms.max(start_ms));
+    let count = last - first + 1;
+
+    {
+        let cache = state.cache.lock();
+        if (first..=last).all(|i| cache.contains(audio_path, i)) {
+            return Ok(0);
+        }
+    }
+
+    let chunks = decode_chunks(audio_path, first, count).await?;
+    let added = chunks.len();
+    let mut cache = state.cache.lock();
+    for chunk in chunks {
+        let index = chunk.start_ms / SCRUB_CHUNK_MS;
+        cache.insert(audio_path, index, chunk);
+    }
+    Ok(added)
+}
+
+/// Decode `count` consecutive chunks starting at chunk `first`
+async fn decode_chunks(
+    audio_path: &Path,
+    first: u64,
+    count: u64,
+) -> Result<Vec<ScrubChunk>, Box<dyn std::error::Error + Send + Sync>> {
+    let start_secs = (first * SCRUB_CHUNK_MS) as f64 / 1000.0;
+    let duration_secs = (count * SCRUB_CHUNK_MS) as f64 / 1000.0;
+
+    let output = Command::new("ffmpeg")
+        .args(["-ss", &format!("{:.3}", start_secs), "-t", &format!("{:.3}", duration_secs), "-i"])
+        .arg(audio_path)
+        .args([
+            "-ac",
+            "1",
+            "-ar",
+            &SCRUB_SAMPLE_RATE.to_string(),
+            "-f",
+            "s16le",
+            "-acodec",
+            "pcm_s16le",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err("FFmpeg failed to decode scrub audio".into());
+    }
+
+    Ok(split_into_chunks(&output.stdout, first))
+}
+
+/// Split raw s16le mono PCM into grid-aligned chunks with faded edges
+fn split_into_chunks(raw_samples: &[u8], first: u64) -> Vec<ScrubChunk> {
+    let samples: Vec<f32> = raw_samples
+        .chunks_exact(2)
+        .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / i16::MAX as f32)
+        .collect();
+
+    let per_chunk = (SCRUB_SAMPLE_RATE as u64 * SCRUB_CHUNK_MS / 1000) as usize;
+
+    samples
+        .chunks(per_chunk)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut samples = chunk.to_vec();
+            apply_edge_fades(&mut samples);
+            ScrubChunk {
+                start_ms: (first + i as u64) * SCRUB_CHUNK_MS,
+                duration_ms: samples.len() as u64 * 1000 / SCRUB_SAMPLE_RATE as u64,
+                sample_rate: SCRUB_SAMPLE_RATE,
+                samples,
+            }
+        })
+        .collect()
+}
+
+/// Linear fade-in/out over the first and last few milliseconds
+fn apply_edge_fades(samples: &mut [f32]) {
+    let fade = ((SCRUB_SAMPLE_RATE as u64 * EDGE_FADE_MS / 1000) as usize).min(samples.len() / 2);
+    if fade == 0 {
+        return;
+    }
+
+    let len = samples.len();
+    for i in 0..fade {
+        let gain = i as f32 / fade as f32;
+        samples[i] *= gain;
+        samples[len - 1 - i] *= gain;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(start_ms: u64) -> ScrubChunk {
+        ScrubChunk {
+            start_ms,
+            duration_ms: SCRUB_CHUNK_MS,
+            sample_rate: SCRUB_SAMPLE_RATE,
+            samples: vec![],
+        }
+    }
+
+    #[test]
+    fn test_chunk_index_alignment() {
+        assert_eq!(chunk_index(0.0), 0);
+        assert_eq!(chunk_index(99.9), 0);
+        assert_eq!(chunk_index(100.0), 1);
+        assert_eq!(chunk_index(-50.0), 0);
+    }
+
+    #[test]
+    fn test_split_into_chunks_fades_edges() {
+        // 250ms of full-scale samples starting at chunk 3
+        let sample_count = SCRUB_SAMPLE_RATE as usize / 4;
+        let raw: Vec<u8> = std::iter::repeat_n(i16::MAX.to_le_bytes(), sample_count)
+            .flatten()
+            .collect();
+
+        let chunks = split_into_chunks(&raw, 3);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].start_ms, 300);
+        assert_eq!(chunks[2].start_ms, 500);
+        assert_eq!(chunks[0].duration_ms, SCRUB_CHUNK_MS);
+
+        let first = &chunks[0].samples;
+        assert_eq!(first[0], 0.0);
+        assert_eq!(first[first.len() / 2], 1.0);
+        assert!(first[first.len() - 1] < 0.01);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cache = ScrubCache::default();
+        let path = Path::new("/tmp/mic.m4a");
+
+        for i in 0..CACHE_CAPACITY as u64 {
+            cache.insert(path, i, chunk(i * SCRUB_CHUNK_MS));
+        }
+        // Touch chunk 0 so chunk 1 becomes the oldest
+        assert!(cache.get(path, 0).is_some());
+        cache.insert(path, 9999, chunk(0));
+
+        assert!(cache.contains(path, 0));
+        assert!(!cache.contains(path, 1));
+
+        cache.invalidate(path);
+        assert!(!cache.contains(path, 0));
+    }
+}
@@ -2,8 +2,9 @@ import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import RecordingToolbar from "./components/recording/RecordingToolbar";
 import EditorView from "./components/editor/EditorView";
+import CountdownOverlay from "./components/recording/CountdownOverlay";
 
-type WindowType = "toolbar" | "editor" | "unknown";
+type WindowType = "toolbar" | "editor" | "countdown" | "unknown";
 
 function App() {
   const [windowType, setWindowType] = useState<WindowType>("unknown");
@@ -22,6 +23,12 @@ function App() {
           return;
         }
 
+        if (windowParam === "countdown") {
+          setWindowType("countdown");
+          document.body.classList.add("countdown-window");
+          return;
+        }
+
         // Then check via Tauri command
         const label = await invoke<string>("get_window_label");
         if (label === "toolbar") {
@@ -56,6 +63,11 @@ function App() {
     return <RecordingToolbar />;
   }
 
+  // Render the countdown before recording starts
+  if (windowType === "countdown") {
+    return <CountdownOverlay />;
+  }
+
   // Render the editor view for editor window
   return <EditorView />;
 }
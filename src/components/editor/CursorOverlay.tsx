@@ -27,6 +27,11 @@ interface CursorOverlayProps {
   containerHeight: number;
   /** Show debug visualization (raw position dot) */
   showDebug?: boolean;
+  /**
+   * The system cursor is recorded into the video; drawing another would
+   * show two, so nothing is drawn (as in export)
+   */
+  videoShowsCursor?: boolean;
 }
 
 /**
@@ -64,6 +69,7 @@ export function CursorOverlay({
   containerWidth,
   containerHeight,
   showDebug = false,
+  videoShowsCursor = false,
 }: CursorOverlayProps) {
   // Convert cursor image paths to asset URLs
   const cursorImageUrls = useMemo(() => {
@@ -77,7 +83,7 @@ export function CursorOverlay({
     return urls;
   }, [cursors]);
 
-  if (!position) return null;
+  if (!position || videoShowsCursor) return null;
 
   const cursorInfo = cursors[position.cursorId];
   const cursorImageUrl = cursorImageUrls[position.cursorId];
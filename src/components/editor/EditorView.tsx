@@ -28,7 +28,6 @@ import {
 } from "../../processing/spring";
 import type {
   RecordingBundle,
-  MouseMoveEvent,
   MouseClickEvent,
   CursorInfo,
 } from "../../types/recording";
@@ -332,14 +331,22 @@ export default function EditorView() {
         ? videoRef.current.currentTime * 1000
         : 0;
 
-      // Find cursor position at current source time
-      const cursorAtTime = findCursorAtTime(
-        recordingBundle.mouseMoves,
-        videoTime,
+      // Slowed slices show each recorded sample for several frames, so
+      // interpolate between samples there like the export does
+      const slowed = slices.some(
+        (slice) =>
+          slice.timeScale < 1 &&
+          videoTime >= slice.sourceStartMs &&
+          videoTime < slice.sourceEndMs,
       );
 
+      // Find cursor position at current source time
+      const cursorAtTime = slowed
+        ? findCursorAtTimeInterpolated(recordingBundle.mouseMoves, videoTime)
+        : findCursorAtTime(recordingBundle.mouseMoves, videoTime);
+
       if (cursorAtTime) {
-        const rawMove: MouseMoveEvent = cursorAtTime;
+        const rawMove = cursorAtTime;
 
         let newPosition: SmoothedPosition;
         if (smootherRef.current && smoothingEnabled) {
@@ -348,7 +355,7 @@ export default function EditorView() {
               x: rawMove.x,
               y: rawMove.y,
               cursorId: rawMove.cursorId,
-              processTimeMs: rawMove.processTimeMs,
+              processTimeMs: videoTime,
             },
             deltaMs / 1000,
           );
@@ -383,7 +390,7 @@ export default function EditorView() {
         cancelAnimationFrame(animationRef.current);
       }
     };
-  }, [isPlaying, recordingBundle, smoothingEnabled]);
+  }, [isPlaying, recordingBundle, smoothingEnabled, slices]);
 
   // Calculate audio offset when audio metadata loads
   // If video is longer than audio, audio started late during recording
@@ -837,6 +844,7 @@ export default function EditorView() {
               videoHeight={videoHeight}
               containerWidth={previewSize.width}
               containerHeight={previewSize.height}
+              videoShowsCursor={recordingBundle.videoShowsCursor}
             />
           )}
         </div>
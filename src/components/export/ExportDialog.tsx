@@ -2,6 +2,7 @@ import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import type { UnlistenFn } from "@tauri-apps/api/event";
+import { open } from "@tauri-apps/plugin-dialog";
 import {
   Film,
   Image,
@@ -17,10 +18,16 @@ import {
 } from "lucide-react";
 import { useProjectStore } from "../../stores/projectStore";
 import type { Slice } from "../../types/project";
-import type { TrackEdits, ExportSegment } from "../../types/export";
+import type {
+  AudioCodec,
+  TrackEdits,
+  ExportSegment,
+  WatchFolderSettings,
+} from "../../types/export";
+import { errorMessage as formatError, type ErrorResponse } from "../../types/error";
 
 type ExportFormat = "mp4" | "gif" | "webm";
-type ExportQuality = "low" | "medium" | "high" | "lossless";
+type ExportQuality = "draft" | "low" | "medium" | "high" | "lossless";
 type ExportState = "idle" | "exporting" | "complete" | "error";
 
 interface ExportPreset {
@@ -33,6 +40,7 @@ interface ExportPreset {
   // "original" means use source, otherwise "WIDTHxHEIGHT" for specific size
   resolution?: string;
   fps?: number; // Optional - if not specified, uses source fps
+  maxSizeMb?: number; // GIF only - shrink the export until it fits
 }
 
 interface ExportProgress {
@@ -40,12 +48,17 @@ interface ExportProgress {
   stage: {
     type:
       | "preparing"
+      | "joiningSessions"
+      | "processingAudio"
       | "smoothingCursor"
       | "encoding"
       | "finalizing"
+      | "generatingPalette"
+      | "uploading"
       | "complete"
       | "error";
     message?: string;
+    attempt?: number;
   };
   currentFrame: number;
   totalFrames: number;
@@ -92,6 +105,7 @@ const presets: ExportPreset[] = [
     quality: "medium",
     resolution: "640x480",
     fps: 15,
+    maxSizeMb: 10, // GitHub's image upload limit
   },
 ];
 
@@ -111,12 +125,20 @@ function getStageLabel(stage: ExportProgress["stage"]): string {
   switch (stage.type) {
     case "preparing":
       return "Preparing...";
+    case "joiningSessions":
+      return "Joining recording sessions...";
+    case "processingAudio":
+      return "Processing audio...";
     case "smoothingCursor":
       return "Smoothing cursor...";
     case "encoding":
       return "Encoding video...";
     case "finalizing":
       return "Finalizing...";
+    case "generatingPalette":
+      return `Optimizing GIF palette (attempt ${stage.attempt ?? 1})...`;
+    case "uploading":
+      return "Uploading...";
     case "complete":
       return "Complete!";
     case "error":
@@ -149,7 +171,8 @@ export default function ExportDialog({
   projectName = "Untitled Recording",
   durationMs = 0,
 }: ExportDialogProps) {
-  const { project, projectPath, getScreenSlices } = useProjectStore();
+  const { project, projectPath, getScreenSlices, getCameraSlices } =
+    useProjectStore();
   const [selectedPreset, setSelectedPreset] = useState<string>("web-hd");
   const [exportState, setExportState] = useState<ExportState>("idle");
   const [exportProgress, setExportProgress] = useState(0);
@@ -162,7 +185,24 @@ export default function ExportDialog({
   const [customQuality, setCustomQuality] = useState<ExportQuality>("high");
   const [customResolution, setCustomResolution] = useState("1920x1080");
   const [customFps, setCustomFps] = useState(60);
+  // null keeps the format's default codec and the recording's sample rate
+  const [customAudioCodec, setCustomAudioCodec] = useState<AudioCodec | null>(
+    null,
+  );
+  const [customSampleRate, setCustomSampleRate] = useState<number | null>(
+    null,
+  );
   const [useCustom, setUseCustom] = useState(false);
+  const [exportWebcamTrack, setExportWebcamTrack] = useState(false);
+  const [preserveSurround, setPreserveSurround] = useState(false);
+  // Saved watch-folder settings (null until loaded)
+  const [watchFolder, setWatchFolder] = useState<WatchFolderSettings | null>(
+    null,
+  );
+  const [watchFolderError, setWatchFolderError] = useState("");
+  // Zoom is only rendered by the frame-by-frame export, not with edits
+  const hasZoom =
+    project?.scenes.some((scene) => scene.zoomRanges.length > 0) ?? false;
 
   // Refs for event listeners
   const unlistenProgressRef = useRef<UnlistenFn | null>(null);
@@ -175,9 +215,86 @@ export default function ExportDialog({
       setExportState("idle");
       setExportProgress(0);
       setErrorMessage("");
+      setWatchFolderError("");
+      invoke<WatchFolderSettings>("get_watch_folder_settings")
+        .then(setWatchFolder)
+        .catch((e) => console.error("Failed to load watch folder:", e));
     }
   }, [isOpen]);
 
+  // Format, size and encoding picked in the dialog
+  const selectedSettings = () => {
+    const preset = useCustom
+      ? null
+      : presets.find((p) => p.id === selectedPreset);
+    // Resolution is optional - undefined/null means use source resolution
+    const { width, height } = parseResolution(
+      useCustom ? customResolution : preset?.resolution,
+    );
+    return {
+      format: useCustom ? customFormat : preset?.format || "mp4",
+      quality: useCustom ? customQuality : preset?.quality || "high",
+      width,
+      height,
+      // FPS is optional - undefined means use source fps
+      fps: useCustom ? customFps : preset?.fps,
+      audioCodec: useCustom ? customAudioCodec : null,
+      audioSampleRate: useCustom ? customSampleRate : null,
+      maxSizeMb: preset?.maxSizeMb,
+      preserveSurround,
+    };
+  };
+
+  // Save the watch folder with the dialog's current settings as its preset;
+  // each bundle's own volumes, mutes and edits apply on top
+  const saveWatchFolder = async (enabled: boolean, folder: string | null) => {
+    const settings: WatchFolderSettings = {
+      enabled,
+      folder,
+      outputDir: watchFolder?.outputDir ?? null,
+      preset: {
+        ...selectedSettings(),
+        outputPath: "",
+        includeCursor: true,
+        includeWebcam: true,
+        includeMicAudio: true,
+        includeSystemAudio: true,
+        screenEdits: null,
+        cameraEdits: null,
+      },
+    };
+    try {
+      await invoke("set_watch_folder_settings", { settings });
+      setWatchFolder(settings);
+      setWatchFolderError("");
+    } catch (e) {
+      setWatchFolderError(formatError(e));
+    }
+  };
+
+  const chooseWatchFolder = async () => {
+    const folder = await open({
+      title: "Watch Folder",
+      directory: true,
+      multiple: false,
+    });
+    return folder && !Array.isArray(folder) ? folder : null;
+  };
+
+  const handleWatchFolderToggle = async (enabled: boolean) => {
+    let folder = watchFolder?.folder ?? null;
+    if (enabled && !folder) {
+      folder = await chooseWatchFolder();
+      if (!folder) return;
+    }
+    await saveWatchFolder(enabled, folder);
+  };
+
+  const handleChangeWatchFolder = async () => {
+    const folder = await chooseWatchFolder();
+    if (folder) await saveWatchFolder(true, folder);
+  };
+
   // Cleanup event listeners on unmount
   useEffect(() => {
     return () => {
@@ -206,17 +323,8 @@ export default function ExportDialog({
     setErrorMessage("");
 
     // Get current settings
-    const preset = useCustom
-      ? null
-      : presets.find((p) => p.id === selectedPreset);
-    const format = useCustom ? customFormat : preset?.format || "mp4";
-    const quality = useCustom ? customQuality : preset?.quality || "high";
-    // Resolution is optional - undefined/null means use source resolution
-    const resolution = useCustom ? customResolution : preset?.resolution;
-    // FPS is optional - undefined means use source fps
-    const fps = useCustom ? customFps : preset?.fps;
-
-    const { width, height } = parseResolution(resolution);
+    const settings = selectedSettings();
+    const { format } = settings;
 
     const exportOutputPath = `${projectDir}/export.${format}`;
     setOutputPath(exportOutputPath);
@@ -242,11 +350,11 @@ export default function ExportDialog({
         unlistenErrorRef.current?.();
       });
 
-      unlistenErrorRef.current = await listen<string>(
+      unlistenErrorRef.current = await listen<ErrorResponse>(
         "export-error",
         (event) => {
           setExportState("error");
-          setErrorMessage(event.payload);
+          setErrorMessage(event.payload.message);
           setExportStage("Error");
           // Cleanup listeners
           unlistenProgressRef.current?.();
@@ -255,30 +363,42 @@ export default function ExportDialog({
         },
       );
 
-      // Get screen slices and convert to edits
+      // Get screen slices and convert to edits (camera slices for
+      // camera-only projects, which have no screen track)
       const screenSlices = getScreenSlices();
-      const edits = slicesToTrackEdits(screenSlices);
+      const cameraSlices = getCameraSlices();
+      const edits = slicesToTrackEdits(
+        screenSlices.length > 0 ? screenSlices : cameraSlices,
+      );
 
       // Start export with edits (respects trim/cut/speed changes)
       await invoke("start_export_with_edits", {
         projectDir: projectDir,
         options: {
-          format,
-          quality,
-          width,
-          height,
-          fps,
+          ...settings,
           outputPath: exportOutputPath,
           includeCursor: true,
+          // Annotations are only drawn by the frame-by-frame export
+          includeAnnotations: false,
           includeWebcam: true,
           includeMicAudio: true,
           includeSystemAudio: true,
+          micOffsetMs: project?.config.audio.microphoneOffsetMs ?? 0,
+          cancelEcho: project?.config.audio.cancelEcho ?? false,
+          microphoneVolume: project?.config.audio.microphoneVolume,
+          systemVolume: project?.config.audio.systemVolume,
+          exportWebcamTrack,
+          // The standalone webcam file follows the camera track's own slices
+          cameraEdits:
+            exportWebcamTrack && cameraSlices.length > 0
+              ? slicesToTrackEdits(cameraSlices)
+              : null,
         },
         edits,
       });
     } catch (e) {
       setExportState("error");
-      setErrorMessage(e instanceof Error ? e.message : String(e));
+      setErrorMessage(formatError(e));
       // Cleanup listeners on error
       unlistenProgressRef.current?.();
       unlistenCompleteRef.current?.();
@@ -418,6 +538,82 @@ export default function ExportDialog({
                 </label>
               </div>
 
+              {/* Standalone webcam file */}
+              <div>
+                <label className="flex items-center gap-2 cursor-pointer">
+                  <input
+                    type="checkbox"
+                    checked={exportWebcamTrack}
+                    onChange={(e) => setExportWebcamTrack(e.target.checked)}
+                    className="rounded border-border bg-transparent"
+                  />
+                  <span className="text-sm text-white/80">
+                    Also export the webcam as a separate file
+                  </span>
+                </label>
+              </div>
+
+              {/* Surround system audio */}
+              <div>
+                <label className="flex items-center gap-2 cursor-pointer">
+                  <input
+                    type="checkbox"
+                    checked={preserveSurround}
+                    onChange={(e) => setPreserveSurround(e.target.checked)}
+                    className="rounded border-border bg-transparent"
+                  />
+                  <span className="text-sm text-white/80">
+                    Keep surround audio instead of mixing to stereo (MP4 only)
+                  </span>
+                </label>
+              </div>
+
+              {/* Watch folder */}
+              <div className="space-y-2">
+                <label className="flex items-center gap-2 cursor-pointer">
+                  <input
+                    type="checkbox"
+                    checked={watchFolder?.enabled ?? false}
+                    disabled={!watchFolder}
+                    onChange={(e) => handleWatchFolderToggle(e.target.checked)}
+                    className="rounded border-border bg-transparent"
+                  />
+                  <span className="text-sm text-white/80">
+                    Export recordings dropped into a watch folder
+                  </span>
+                </label>
+                {watchFolder?.enabled && (
+                  <div className="flex items-center gap-2 pl-6 text-xs text-white/60">
+                    <span
+                      className="truncate flex-1"
+                      title={watchFolder.folder ?? ""}
+                    >
+                      {watchFolder.folder}
+                    </span>
+                    <button
+                      type="button"
+                      onClick={handleChangeWatchFolder}
+                      className="shrink-0 hover:text-white transition-colors"
+                    >
+                      Change...
+                    </button>
+                    <button
+                      type="button"
+                      onClick={() => saveWatchFolder(true, watchFolder.folder)}
+                      className="shrink-0 hover:text-white transition-colors"
+                      title="Export dropped recordings with the settings above"
+                    >
+                      Use these settings
+                    </button>
+                  </div>
+                )}
+                {watchFolderError && (
+                  <p className="pl-6 text-xs text-destructive">
+                    {watchFolderError}
+                  </p>
+                )}
+              </div>
+
               {/* Custom Settings Form */}
               {useCustom && (
                 <div className="grid grid-cols-2 gap-3 p-4 bg-panel rounded-lg">
@@ -431,9 +627,13 @@ export default function ExportDialog({
                     <select
                       id="export-format"
                       value={customFormat}
-                      onChange={(e) =>
-                        setCustomFormat(e.target.value as ExportFormat)
-                      }
+                      onChange={(e) => {
+                        const next = e.target.value as ExportFormat;
+                        setCustomFormat(next);
+                        // Only MP4 takes every codec
+                        if (next !== "mp4") setCustomAudioCodec(null);
+                        if (next === "webm") setCustomSampleRate(null);
+                      }}
                       className="w-full bg-background border border-border rounded-md px-2 py-1.5 text-sm text-white"
                     >
                       <option value="mp4">MP4</option>
@@ -456,6 +656,7 @@ export default function ExportDialog({
                       }
                       className="w-full bg-background border border-border rounded-md px-2 py-1.5 text-sm text-white"
                     >
+                      <option value="draft">Draft (fast, half size)</option>
                       <option value="low">Low</option>
                       <option value="medium">Medium</option>
                       <option value="high">High</option>
@@ -500,6 +701,69 @@ export default function ExportDialog({
                       <option value="15">15</option>
                     </select>
                   </div>
+                  {customFormat !== "gif" && (
+                    <div>
+                      <label
+                        htmlFor="export-audio-codec"
+                        className="text-xs text-white/60 block mb-1"
+                      >
+                        Audio Codec
+                      </label>
+                      <select
+                        id="export-audio-codec"
+                        value={customAudioCodec ?? ""}
+                        onChange={(e) => {
+                          const next = (e.target.value ||
+                            null) as AudioCodec | null;
+                          setCustomAudioCodec(next);
+                          if (next === "opus") setCustomSampleRate(null);
+                        }}
+                        className="w-full bg-background border border-border rounded-md px-2 py-1.5 text-sm text-white"
+                      >
+                        <option value="">
+                          {customFormat === "webm" ? "Opus" : "AAC"} (default)
+                        </option>
+                        {customFormat === "mp4" && (
+                          <>
+                            <option value="opus">Opus</option>
+                            <option value="flac">FLAC (lossless)</option>
+                          </>
+                        )}
+                      </select>
+                    </div>
+                  )}
+                  {customFormat !== "gif" && (
+                    <div>
+                      <label
+                        htmlFor="export-sample-rate"
+                        className="text-xs text-white/60 block mb-1"
+                      >
+                        Sample Rate
+                      </label>
+                      <select
+                        id="export-sample-rate"
+                        value={customSampleRate ?? ""}
+                        onChange={(e) =>
+                          setCustomSampleRate(
+                            e.target.value ? Number(e.target.value) : null,
+                          )
+                        }
+                        className="w-full bg-background border border-border rounded-md px-2 py-1.5 text-sm text-white"
+                      >
+                        <option value="">As recorded</option>
+                        {/* Opus only encodes up to 48 kHz */}
+                        {customAudioCodec !== "opus" &&
+                          customFormat !== "webm" && (
+                            <option value="96000">96 kHz</option>
+                          )}
+                        <option value="48000">48 kHz</option>
+                        {customAudioCodec !== "opus" &&
+                          customFormat !== "webm" && (
+                            <option value="44100">44.1 kHz</option>
+                          )}
+                      </select>
+                    </div>
+                  )}
                 </div>
               )}
 
@@ -520,6 +784,11 @@ export default function ExportDialog({
                       : ""}
                 </span>
               </div>
+              {hasZoom && (
+                <p className="text-xs text-white/40">
+                  Zoom ranges aren't applied to exports with edits yet.
+                </p>
+              )}
             </>
           )}
 
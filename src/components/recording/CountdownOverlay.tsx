@@ -0,0 +1,40 @@
+import { useState, useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import type { CountdownTick } from "../../types/recording";
+
+// Seconds the countdown started at, passed in the window URL so the first
+// number shows before the first tick arrives
+function initialSeconds(): number | null {
+  const seconds = Number(
+    new URLSearchParams(window.location.search).get("seconds"),
+  );
+  return seconds > 0 ? seconds : null;
+}
+
+export default function CountdownOverlay() {
+  const [remaining, setRemaining] = useState<number | null>(initialSeconds);
+
+  useEffect(() => {
+    const unlisten = listen<CountdownTick>("recording-countdown", (event) => {
+      const { remaining, cancelled } = event.payload;
+      setRemaining(remaining > 0 && !cancelled ? remaining : null);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  if (remaining === null) {
+    return null;
+  }
+
+  return (
+    <div className="flex items-center justify-center w-full h-full">
+      <div className="flex items-center justify-center w-48 h-48 rounded-full bg-black/60 text-white">
+        <span key={remaining} className="text-8xl font-semibold tabular-nums">
+          {remaining}
+        </span>
+      </div>
+    </div>
+  );
+}
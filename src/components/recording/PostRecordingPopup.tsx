@@ -114,6 +114,13 @@ export default function PostRecordingPopup({
 
     parts.push(formatDuration(recordingResult.totalDurationMs));
 
+    const mic = recordingResult.channels.find(
+      (channel) => channel.channelType === "microphone",
+    )?.settings;
+    if (mic?.sampleRate) {
+      parts.push(`Mic ${mic.sampleRate / 1000}kHz`);
+    }
+
     return parts.join(" \u2022 ");
   };
 
@@ -1,5 +1,6 @@
 import { useState, useEffect, useRef } from "react";
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import {
   Monitor,
@@ -20,9 +21,21 @@ import {
   Play,
   GripVertical,
   Check,
+  MousePointer2,
+  Flag,
+  CheckCircle,
+  AlertCircle,
 } from "lucide-react";
 import PostRecordingPopup from "./PostRecordingPopup";
-import type { RecordingResult } from "../../types/recording";
+import type {
+  AudioEncodingConfig,
+  CountdownTick,
+  MicLevel,
+  RecordingEvent,
+  RecordingResult,
+  RecoverableRecording,
+} from "../../types/recording";
+import type { WatchFolderExport } from "../../types/export";
 import { useProjectStore } from "../../stores/projectStore";
 
 type RecordingState = "idle" | "recording" | "paused";
@@ -36,6 +49,8 @@ interface DisplayInfo {
   scaleFactor: number;
   isPrimary: boolean;
   refreshRate: number | null;
+  // Clockwise degrees from the native orientation (0, 90, 180 or 270)
+  rotation: number;
 }
 
 interface AudioDeviceInfo {
@@ -43,12 +58,45 @@ interface AudioDeviceInfo {
   name: string;
   isInput: boolean;
   isDefault: boolean;
+  sampleRates: number[];
+}
+
+const MIC_BITRATES_KBPS = [96, 128, 192, 256, 320];
+
+// Seconds counted down in the overlay before recording starts
+const COUNTDOWN_SECONDS = 3;
+
+interface CameraFormat {
+  width: number;
+  height: number;
+  fps: number;
+  pixelFormat: string | null;
 }
 
 interface CameraInfo {
   id: string;
   name: string;
   supportedResolutions: { width: number; height: number }[];
+  // Largest and fastest first
+  supportedFormats: CameraFormat[];
+}
+
+// One format per size and frame rate, in the camera's order
+function formatChoices(formats: CameraFormat[]): CameraFormat[] {
+  const seen = new Set<string>();
+  return formats.filter((format) => {
+    const key = `${format.width}x${format.height}@${format.fps}`;
+    if (seen.has(key)) return false;
+    seen.add(key);
+    return true;
+  });
+}
+
+// Width of the mic meter for a linear level, over a -60 to 0 dBFS range
+function meterPercent(level: number): number {
+  if (level <= 0) return 0;
+  const db = 20 * Math.log10(level);
+  return Math.min(100, Math.max(0, ((db + 60) / 60) * 100));
 }
 
 export default function RecordingToolbar() {
@@ -58,34 +106,58 @@ export default function RecordingToolbar() {
     null,
   );
   const [displays, setDisplays] = useState<DisplayInfo[]>([]);
+  // Record whichever display the cursor is on (re-picked on resume)
+  const [followCursor, setFollowCursor] = useState(false);
 
   // Audio/Video toggles
   const [cameraEnabled, setCameraEnabled] = useState(false);
   const [micEnabled, setMicEnabled] = useState(true);
   const [systemAudioEnabled, setSystemAudioEnabled] = useState(true);
   const [selectedMicId, setSelectedMicId] = useState<string | null>(null);
+  const [micEncoding, setMicEncoding] = useState<AudioEncodingConfig>({});
+  // Output devices system audio can be recorded from (Windows only)
+  const [outputDevices, setOutputDevices] = useState<AudioDeviceInfo[]>([]);
+  const [selectedOutputId, setSelectedOutputId] = useState<string | null>(
+    null,
+  );
   const [selectedCameraId, setSelectedCameraId] = useState<string | null>(null);
+  // Webcam format to record in (null = the camera's highest resolution)
+  const [selectedCameraFormat, setSelectedCameraFormat] =
+    useState<CameraFormat | null>(null);
   const [audioDevices, setAudioDevices] = useState<AudioDeviceInfo[]>([]);
   const [cameras, setCameras] = useState<CameraInfo[]>([]);
+  const [cameraPreviewUrl, setCameraPreviewUrl] = useState<string | null>(
+    null,
+  );
+  // Preview starts and stops run one after another, so a late stop can't
+  // close the preview that replaced it
+  const cameraPreviewQueue = useRef<Promise<unknown>>(Promise.resolve());
 
   // Dropdowns
   const [showCameraDropdown, setShowCameraDropdown] = useState(false);
   const [showMicDropdown, setShowMicDropdown] = useState(false);
+  const [showOutputDropdown, setShowOutputDropdown] = useState(false);
   const [showDisplayDropdown, setShowDisplayDropdown] = useState(false);
 
   // Recording state
   const [recordingState, setRecordingState] = useState<RecordingState>("idle");
   const [recordingTime, setRecordingTime] = useState(0);
   const [isLoading, setIsLoading] = useState(false);
+  // Seconds left before recording starts, while counting down
+  const [countdown, setCountdown] = useState<number | null>(null);
+  const [micLevel, setMicLevel] = useState<MicLevel | null>(null);
+  // Devices that disconnected while recording, by channel ID, until the
+  // channel reconnects or falls back to another device
+  const [lostDevices, setLostDevices] = useState<Record<string, string>>({});
+  // Last watch-folder export, shown until it's clicked
+  const [watchFolderExport, setWatchFolderExport] =
+    useState<WatchFolderExport | null>(null);
 
   // Post-recording popup state
   const [showPostRecording, setShowPostRecording] = useState(false);
   const [recordingResult, setRecordingResult] =
     useState<RecordingResult | null>(null);
 
-  const timerRef = useRef<number | null>(null);
-  const recordingStartTime = useRef<number>(0);
-
   // Load displays and audio devices
   useEffect(() => {
     const init = async () => {
@@ -115,6 +187,14 @@ export default function RecordingToolbar() {
         console.error("Failed to load audio devices:", err);
       }
 
+      try {
+        setOutputDevices(
+          await invoke<AudioDeviceInfo[]>("get_system_audio_devices"),
+        );
+      } catch (err) {
+        console.error("Failed to load output devices:", err);
+      }
+
       // Load cameras
       try {
         const cameraList = await invoke<CameraInfo[]>("get_cameras");
@@ -130,37 +210,170 @@ export default function RecordingToolbar() {
     init();
   }, []);
 
-  // Timer for recording duration
+  // Preview the camera while it's on, until the recording takes it over
   useEffect(() => {
-    if (recordingState === "recording") {
-      if (recordingStartTime.current === 0) {
-        recordingStartTime.current = Date.now();
-      }
-      timerRef.current = window.setInterval(() => {
-        setRecordingTime(Date.now() - recordingStartTime.current);
-      }, 100);
-    } else {
-      if (timerRef.current) {
-        clearInterval(timerRef.current);
-        timerRef.current = null;
+    if (!cameraEnabled || recordingState !== "idle") return;
+
+    let frameUrl: string | null = null;
+    const onFrame = new Channel<ArrayBuffer>();
+    onFrame.onmessage = (jpeg) => {
+      const url = URL.createObjectURL(
+        new Blob([jpeg], { type: "image/jpeg" }),
+      );
+      setCameraPreviewUrl(url);
+      if (frameUrl) URL.revokeObjectURL(frameUrl);
+      frameUrl = url;
+    };
+    cameraPreviewQueue.current = cameraPreviewQueue.current
+      .then(() =>
+        invoke("start_camera_preview", {
+          deviceId: selectedCameraId,
+          format: selectedCameraFormat,
+          onFrame,
+        }),
+      )
+      .catch((err) => console.error("Failed to start camera preview:", err));
+
+    return () => {
+      onFrame.onmessage = () => {};
+      cameraPreviewQueue.current = cameraPreviewQueue.current
+        .then(() => invoke("stop_camera_preview"))
+        .catch((err) => console.error("Failed to stop camera preview:", err));
+      if (frameUrl) URL.revokeObjectURL(frameUrl);
+      setCameraPreviewUrl(null);
+    };
+  }, [cameraEnabled, recordingState, selectedCameraId, selectedCameraFormat]);
+
+  // Offer to recover a recording the app quit in the middle of
+  useEffect(() => {
+    const checkRecoverable = async () => {
+      try {
+        const recordings = await invoke<RecoverableRecording[]>(
+          "list_recoverable_recordings",
+        );
+        const recording = recordings[0];
+        if (!recording) return;
+        const started = recording.startedAt
+          ? new Date(recording.startedAt).toLocaleString()
+          : "an earlier session";
+        const message = `Recover the recording interrupted from ${started}?`;
+        if (!window.confirm(message)) return;
+        const result = await invoke<RecordingResult>("recover_recording", {
+          bundlePath: recording.bundlePath,
+        });
+        setRecordingResult(result);
+        setShowPostRecording(true);
+      } catch (err) {
+        console.error("Failed to recover recording:", err);
       }
-      if (recordingState === "idle") {
-        recordingStartTime.current = 0;
+    };
+
+    checkRecoverable();
+  }, []);
+
+  // Follow the recorder: state changes made elsewhere (display unplugged,
+  // automation) and the duration, which only counts recorded time
+  useEffect(() => {
+    const unlisten = listen<RecordingEvent>("recording-event", (event) => {
+      const payload = event.payload;
+      switch (payload.type) {
+        case "started":
+        case "resumed":
+          setRecordingState("recording");
+          break;
+        case "paused":
+          setRecordingState("paused");
+          break;
+        case "stopped":
+          setRecordingState("idle");
+          setRecordingTime(0);
+          setMicLevel(null);
+          setLostDevices({});
+          break;
+        case "progress":
+          setRecordingTime(payload.data);
+          break;
+        case "error":
+          console.error("Recording error:", payload.data);
+          break;
+        case "deviceLost": {
+          const { channel, device } = payload.data;
+          console.warn(`Recording channel ${channel}: lost ${device}`);
+          setLostDevices((lost) => ({ ...lost, [channel]: device }));
+          break;
+        }
+        case "deviceRecovered":
+        case "deviceFallback": {
+          const { channel } = payload.data;
+          setLostDevices((lost) => {
+            const next = { ...lost };
+            delete next[channel];
+            return next;
+          });
+          break;
+        }
+        case "channelDegraded":
+        case "channelFailed":
+          console.warn(
+            `Recording channel ${payload.data.channel}: ${payload.data.reason}`,
+          );
+          break;
+        case "autoStopped":
+          // Stopped by the backend; show the result as a manual stop would
+          setRecordingResult(payload.data.result);
+          setShowPostRecording(true);
+          break;
       }
-    }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
+  // Countdown before recording starts, shown on the record button
+  useEffect(() => {
+    const unlisten = listen<CountdownTick>("recording-countdown", (event) => {
+      const { remaining, cancelled } = event.payload;
+      setCountdown(remaining > 0 && !cancelled ? remaining : null);
+    });
     return () => {
-      if (timerRef.current) {
-        clearInterval(timerRef.current);
-      }
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Live microphone level for the meter under the mic button
+  useEffect(() => {
+    const unlisten = listen<MicLevel>("mic-level", (event) => {
+      setMicLevel(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Exports the backend ran for bundles dropped into the watch folder
+  useEffect(() => {
+    const unlisten = listen<WatchFolderExport>(
+      "watch-folder-export",
+      (event) => {
+        const { bundlePath, error } = event.payload;
+        if (error) {
+          console.error(`Watch folder export of ${bundlePath}:`, error);
+        }
+        setWatchFolderExport(event.payload);
+      },
+    );
+    return () => {
+      unlisten.then((fn) => fn());
     };
-  }, [recordingState]);
+  }, []);
 
   // Close dropdowns when clicking outside
   useEffect(() => {
     const handleClickOutside = () => {
       setShowCameraDropdown(false);
       setShowMicDropdown(false);
+      setShowOutputDropdown(false);
       setShowDisplayDropdown(false);
     };
     document.addEventListener("click", handleClickOutside);
@@ -180,14 +393,20 @@ export default function RecordingToolbar() {
 
     try {
       const outputDir = `/tmp/open-screenstudio-${Date.now()}`;
-      await invoke("start_recording", {
+      await invoke("start_recording_with_countdown", {
+        seconds: COUNTDOWN_SECONDS,
         config: {
           displayId: selectedDisplayId,
+          displayUnderCursor: followCursor,
+          followCursorOnResume: followCursor,
           captureSystemAudio: systemAudioEnabled,
+          systemAudioDeviceId: systemAudioEnabled ? selectedOutputId : null,
           captureMicrophone: micEnabled,
           microphoneDeviceId: micEnabled ? selectedMicId : null,
+          microphoneEncoding: micEncoding,
           captureWebcam: cameraEnabled,
           webcamDeviceId: cameraEnabled ? selectedCameraId : null,
+          webcamFormat: cameraEnabled ? selectedCameraFormat : null,
           trackInput: true,
           outputDir,
         },
@@ -197,10 +416,19 @@ export default function RecordingToolbar() {
     } catch (err) {
       console.error("Failed to start recording:", err);
     } finally {
+      setCountdown(null);
       setIsLoading(false);
     }
   };
 
+  const handleCancelCountdown = async () => {
+    try {
+      await invoke("cancel_recording_countdown");
+    } catch (err) {
+      console.error("Failed to cancel countdown:", err);
+    }
+  };
+
   const handleStopRecording = async () => {
     setIsLoading(true);
     try {
@@ -225,6 +453,14 @@ export default function RecordingToolbar() {
     }
   };
 
+  const handleAddMarker = async () => {
+    try {
+      await invoke("add_recording_marker", { label: "Marker" });
+    } catch (err) {
+      console.error("Failed to add marker:", err);
+    }
+  };
+
   const handleResumeRecording = async () => {
     try {
       await invoke("resume_recording");
@@ -288,6 +524,23 @@ export default function RecordingToolbar() {
     setRecordingResult(null);
   };
 
+  const handleWatchFolderNotice = async () => {
+    const notice = watchFolderExport;
+    setWatchFolderExport(null);
+    if (!notice || notice.error) return;
+    try {
+      // Open the folder containing the export
+      const { outputPath } = notice;
+      const folderPath = outputPath.substring(
+        0,
+        Math.max(outputPath.lastIndexOf("/"), outputPath.lastIndexOf("\\")),
+      );
+      await invoke("plugin:shell|open", { path: folderPath });
+    } catch (err) {
+      console.error("Failed to open folder:", err);
+    }
+  };
+
   const sourceButtons: {
     type: SourceType;
     icon: typeof Monitor;
@@ -300,6 +553,19 @@ export default function RecordingToolbar() {
   ];
 
   const isRecording = recordingState !== "idle";
+  const cameraFormats = formatChoices(
+    cameras.find((camera) => camera.id === selectedCameraId)
+      ?.supportedFormats ?? [],
+  );
+  const selectedMic = audioDevices.find(
+    (device) => device.id === selectedMicId,
+  );
+  const micSilent = isRecording && micEnabled && micLevel?.silent === true;
+  const lostMic = lostDevices["microphone"];
+  const lostCamera = lostDevices["webcam"];
+  const watchFolderBundle = watchFolderExport?.bundlePath
+    .split(/[\\/]/)
+    .pop();
 
   // Show post-recording popup instead of toolbar
   if (showPostRecording && recordingResult) {
@@ -369,6 +635,21 @@ export default function RecordingToolbar() {
                 showDisplayDropdown &&
                 sourceType === "display" && (
                   <div className="dropdown">
+                    <button
+                      type="button"
+                      onClick={(e) => {
+                        e.stopPropagation();
+                        setFollowCursor(true);
+                        setShowDisplayDropdown(false);
+                      }}
+                      className={`dropdown-item ${followCursor ? "active" : ""}`}
+                    >
+                      <MousePointer2 className="w-4 h-4" />
+                      <span className="flex-1">Display under cursor</span>
+                      {followCursor && (
+                        <Check className="w-4 h-4 text-green-400 ml-2" />
+                      )}
+                    </button>
                     {displays.map((display) => (
                       <button
                         key={display.id}
@@ -376,16 +657,17 @@ export default function RecordingToolbar() {
                         onClick={(e) => {
                           e.stopPropagation();
                           setSelectedDisplayId(display.id);
+                          setFollowCursor(false);
                           setShowDisplayDropdown(false);
                         }}
-                        className={`dropdown-item ${selectedDisplayId === display.id ? "active" : ""}`}
+                        className={`dropdown-item ${!followCursor && selectedDisplayId === display.id ? "active" : ""}`}
                       >
                         <Monitor className="w-4 h-4" />
                         <span className="flex-1">{display.name}</span>
                         <span className="text-xs opacity-60">
                           {display.width}x{display.height}
                         </span>
-                        {selectedDisplayId === display.id && (
+                        {!followCursor && selectedDisplayId === display.id && (
                           <Check className="w-4 h-4 text-green-400 ml-2" />
                         )}
                       </button>
@@ -409,14 +691,22 @@ export default function RecordingToolbar() {
             }}
             disabled={isRecording}
             className={`toolbar-btn ${cameraEnabled ? "active" : ""}`}
-            title={cameraEnabled ? "Camera On" : "Camera Off"}
+            title={
+              lostCamera
+                ? `${lostCamera} disconnected - reconnecting`
+                : cameraEnabled
+                  ? "Camera On"
+                  : "Camera Off"
+            }
           >
             {cameraEnabled ? (
               <Camera className="w-4 h-4" />
             ) : (
               <CameraOff className="w-4 h-4" />
             )}
-            <span className="text-xs">Camera</span>
+            <span className={`text-xs ${lostCamera ? "text-yellow-400" : ""}`}>
+              Camera
+            </span>
             <button
               type="button"
               onClick={(e) => {
@@ -438,6 +728,9 @@ export default function RecordingToolbar() {
                     type="button"
                     onClick={(e) => {
                       e.stopPropagation();
+                      if (camera.id !== selectedCameraId) {
+                        setSelectedCameraFormat(null);
+                      }
                       setSelectedCameraId(camera.id);
                       setCameraEnabled(true);
                       setShowCameraDropdown(false);
@@ -458,6 +751,56 @@ export default function RecordingToolbar() {
                   </span>
                 </div>
               )}
+              {cameraFormats.length > 0 && (
+                <>
+                  <div className="dropdown-divider" />
+                  <button
+                    type="button"
+                    onClick={(e) => {
+                      e.stopPropagation();
+                      setSelectedCameraFormat(null);
+                      setShowCameraDropdown(false);
+                    }}
+                    className={`dropdown-item ${selectedCameraFormat === null ? "active" : ""}`}
+                  >
+                    <span className="truncate">Highest resolution</span>
+                    {selectedCameraFormat === null && (
+                      <Check className="w-4 h-4 text-green-400 ml-2" />
+                    )}
+                  </button>
+                  {cameraFormats.map((format) => {
+                    const selected =
+                      selectedCameraFormat?.width === format.width &&
+                      selectedCameraFormat?.height === format.height &&
+                      selectedCameraFormat?.fps === format.fps;
+                    return (
+                      <button
+                        key={`${format.width}x${format.height}@${format.fps}`}
+                        type="button"
+                        onClick={(e) => {
+                          e.stopPropagation();
+                          setSelectedCameraFormat(format);
+                          setShowCameraDropdown(false);
+                        }}
+                        className={`dropdown-item ${selected ? "active" : ""}`}
+                      >
+                        <span className="truncate">
+                          {format.width}x{format.height} @ {format.fps}fps
+                        </span>
+                        {selected && (
+                          <Check className="w-4 h-4 text-green-400 ml-2" />
+                        )}
+                      </button>
+                    );
+                  })}
+                </>
+              )}
+            </div>
+          )}
+
+          {cameraPreviewUrl && !showCameraDropdown && (
+            <div className="camera-preview">
+              <img src={cameraPreviewUrl} alt="Camera preview" />
             </div>
           )}
         </div>
@@ -471,15 +814,35 @@ export default function RecordingToolbar() {
               setMicEnabled(!micEnabled);
             }}
             disabled={isRecording}
-            className={`toolbar-btn ${micEnabled ? "active" : ""}`}
-            title={micEnabled ? "Mic On" : "Mic Off"}
+            className={`toolbar-btn relative ${micEnabled ? "active" : ""}`}
+            title={
+              lostMic
+                ? `${lostMic} disconnected - recording silence until it's back`
+                : micSilent
+                  ? "No sound from the microphone - check it isn't muted"
+                  : micEnabled
+                    ? "Mic On"
+                    : "Mic Off"
+            }
           >
             {micEnabled ? (
               <Mic className="w-4 h-4" />
             ) : (
               <MicOff className="w-4 h-4" />
             )}
-            <span className="text-xs">Mic</span>
+            <span
+              className={`text-xs ${micSilent || lostMic ? "text-yellow-400" : ""}`}
+            >
+              Mic
+            </span>
+            {isRecording && micEnabled && micLevel && (
+              <span className="absolute left-1 right-1 bottom-0.5 h-0.5 rounded bg-white/10">
+                <span
+                  className={`block h-full rounded ${micLevel.peak > 0.9 ? "bg-red-400" : "bg-green-400"}`}
+                  style={{ width: `${meterPercent(micLevel.rms)}%` }}
+                />
+              </span>
+            )}
             <button
               type="button"
               onClick={(e) => {
@@ -500,6 +863,13 @@ export default function RecordingToolbar() {
                     key={device.id}
                     type="button"
                     onClick={() => {
+                      if (device.id !== selectedMicId) {
+                        // The new mic may not support the chosen rate
+                        setMicEncoding({
+                          ...micEncoding,
+                          inputSampleRate: undefined,
+                        });
+                      }
                       setSelectedMicId(device.id);
                       setShowMicDropdown(false);
                     }}
@@ -517,28 +887,154 @@ export default function RecordingToolbar() {
                   <span className="text-xs opacity-60">No mics detected</span>
                 </div>
               )}
+              {audioDevices.length > 0 && (
+                <>
+                  <div className="dropdown-divider" />
+                  <label
+                    className="dropdown-item"
+                    onClick={(e) => e.stopPropagation()}
+                  >
+                    <span>Sample rate</span>
+                    <select
+                      value={micEncoding.inputSampleRate ?? ""}
+                      onChange={(e) =>
+                        setMicEncoding({
+                          ...micEncoding,
+                          inputSampleRate: e.target.value
+                            ? Number(e.target.value)
+                            : undefined,
+                        })
+                      }
+                      className="dropdown-select"
+                    >
+                      <option value="">Default</option>
+                      {(selectedMic?.sampleRates ?? []).map((rate) => (
+                        <option key={rate} value={rate}>
+                          {rate / 1000} kHz
+                        </option>
+                      ))}
+                    </select>
+                  </label>
+                  <label
+                    className="dropdown-item"
+                    onClick={(e) => e.stopPropagation()}
+                  >
+                    <span>Channels</span>
+                    <select
+                      value={micEncoding.channelLayout ?? ""}
+                      onChange={(e) => {
+                        const layout = e.target.value;
+                        setMicEncoding({
+                          ...micEncoding,
+                          channelLayout:
+                            layout === "mono" || layout === "stereo"
+                              ? layout
+                              : undefined,
+                        });
+                      }}
+                      className="dropdown-select"
+                    >
+                      <option value="">Default</option>
+                      <option value="mono">Mono</option>
+                      <option value="stereo">Stereo</option>
+                    </select>
+                  </label>
+                  <label
+                    className="dropdown-item"
+                    onClick={(e) => e.stopPropagation()}
+                  >
+                    <span>Bitrate</span>
+                    <select
+                      value={micEncoding.bitrateKbps ?? ""}
+                      onChange={(e) =>
+                        setMicEncoding({
+                          ...micEncoding,
+                          bitrateKbps: e.target.value
+                            ? Number(e.target.value)
+                            : undefined,
+                        })
+                      }
+                      className="dropdown-select"
+                    >
+                      <option value="">Default (192 kbps)</option>
+                      {MIC_BITRATES_KBPS.map((kbps) => (
+                        <option key={kbps} value={kbps}>
+                          {kbps} kbps
+                        </option>
+                      ))}
+                    </select>
+                  </label>
+                </>
+              )}
             </div>
           )}
         </div>
 
         {/* System Audio Toggle */}
-        <button
-          type="button"
-          onClick={(e) => {
-            e.stopPropagation();
-            setSystemAudioEnabled(!systemAudioEnabled);
-          }}
-          disabled={isRecording}
-          className={`toolbar-btn ${systemAudioEnabled ? "active" : ""}`}
-          title={systemAudioEnabled ? "System Audio On" : "System Audio Off"}
-        >
-          {systemAudioEnabled ? (
-            <Volume2 className="w-4 h-4" />
-          ) : (
-            <VolumeX className="w-4 h-4" />
+        <div className="relative">
+          <button
+            type="button"
+            onClick={(e) => {
+              e.stopPropagation();
+              setSystemAudioEnabled(!systemAudioEnabled);
+            }}
+            disabled={isRecording}
+            className={`toolbar-btn ${systemAudioEnabled ? "active" : ""}`}
+            title={systemAudioEnabled ? "System Audio On" : "System Audio Off"}
+          >
+            {systemAudioEnabled ? (
+              <Volume2 className="w-4 h-4" />
+            ) : (
+              <VolumeX className="w-4 h-4" />
+            )}
+            <span className="text-xs">Audio</span>
+            {outputDevices.length > 0 && (
+              <button
+                type="button"
+                onClick={(e) => {
+                  e.stopPropagation();
+                  setShowOutputDropdown(!showOutputDropdown);
+                }}
+                className="dropdown-trigger"
+              >
+                <ChevronDown className="w-3 h-3" />
+              </button>
+            )}
+          </button>
+
+          {showOutputDropdown && (
+            <div className="dropdown">
+              <button
+                type="button"
+                onClick={() => {
+                  setSelectedOutputId(null);
+                  setShowOutputDropdown(false);
+                }}
+                className={`dropdown-item ${selectedOutputId === null ? "active" : ""}`}
+              >
+                <Volume2 className="w-4 h-4" />
+                <span className="truncate">Default output</span>
+              </button>
+              {outputDevices.map((device) => (
+                <button
+                  key={device.id}
+                  type="button"
+                  onClick={() => {
+                    setSelectedOutputId(device.id);
+                    setShowOutputDropdown(false);
+                  }}
+                  className={`dropdown-item ${selectedOutputId === device.id ? "active" : ""}`}
+                >
+                  <Volume2 className="w-4 h-4" />
+                  <span className="truncate">{device.name}</span>
+                  {device.isDefault && (
+                    <span className="text-xs opacity-60">(Default)</span>
+                  )}
+                </button>
+              ))}
+            </div>
           )}
-          <span className="text-xs">Audio</span>
-        </button>
+        </div>
 
         {/* Divider */}
         <div className="toolbar-divider" />
@@ -549,15 +1045,21 @@ export default function RecordingToolbar() {
             type="button"
             onClick={(e) => {
               e.stopPropagation();
-              handleStartRecording();
+              if (countdown !== null) {
+                handleCancelCountdown();
+              } else {
+                handleStartRecording();
+              }
             }}
-            disabled={isLoading || selectedDisplayId === null}
+            disabled={
+              (isLoading && countdown === null) || selectedDisplayId === null
+            }
             className="record-btn"
-            title="Start Recording"
+            title={countdown !== null ? "Cancel Countdown" : "Start Recording"}
           >
             <Circle className="w-4 h-4 fill-current" />
             <span className="text-xs font-medium">
-              {isLoading ? "..." : "Record"}
+              {countdown !== null ? countdown : isLoading ? "..." : "Record"}
             </span>
           </button>
         ) : (
@@ -589,6 +1091,19 @@ export default function RecordingToolbar() {
               )}
             </button>
 
+            {/* Marker */}
+            <button
+              type="button"
+              onClick={(e) => {
+                e.stopPropagation();
+                handleAddMarker();
+              }}
+              className="toolbar-btn-sm"
+              title="Add Marker (Ctrl/Cmd+Shift+M)"
+            >
+              <Flag className="w-4 h-4" />
+            </button>
+
             {/* Stop */}
             <button
               type="button"
@@ -607,6 +1122,29 @@ export default function RecordingToolbar() {
 
         {/* Right side controls */}
         <div className="flex items-center gap-1 ml-auto">
+          {/* Watch folder export finished */}
+          {watchFolderExport && (
+            <button
+              type="button"
+              onClick={(e) => {
+                e.stopPropagation();
+                handleWatchFolderNotice();
+              }}
+              className="toolbar-btn-icon"
+              title={
+                watchFolderExport.error
+                  ? `Couldn't export ${watchFolderBundle} from the watch folder: ${watchFolderExport.error.message}`
+                  : `Exported ${watchFolderBundle} from the watch folder - click to show`
+              }
+            >
+              {watchFolderExport.error ? (
+                <AlertCircle className="w-4 h-4 text-yellow-400" />
+              ) : (
+                <CheckCircle className="w-4 h-4 text-success" />
+              )}
+            </button>
+          )}
+
           {/* Settings */}
           <button
             type="button"
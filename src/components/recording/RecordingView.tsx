@@ -15,9 +15,13 @@ import {
   Settings,
   AlertCircle,
 } from "lucide-react";
+import { errorCode, errorMessage } from "../../types/error";
 
 type RecordingState = "idle" | "recording" | "paused";
 
+// How often the display thumbnails are refreshed while idle
+const THUMBNAIL_REFRESH_MS = 5000;
+
 interface DisplayInfo {
   id: number;
   name: string;
@@ -26,6 +30,8 @@ interface DisplayInfo {
   scaleFactor: number;
   isPrimary: boolean;
   refreshRate: number | null;
+  // Clockwise degrees from the native orientation (0, 90, 180 or 270)
+  rotation: number;
 }
 
 interface AudioDeviceInfo {
@@ -50,6 +56,9 @@ export default function RecordingView() {
   const [hasPermission, setHasPermission] = useState<boolean | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState(false);
+  const [thumbnailUrls, setThumbnailUrls] = useState<Record<number, string>>(
+    {},
+  );
 
   const timerRef = useRef<number | null>(null);
   const recordingStartTime = useRef<number>(0);
@@ -99,6 +108,46 @@ export default function RecordingView() {
     init();
   }, []);
 
+  // Thumbnails of every display in the picker, refreshed while idle
+  useEffect(() => {
+    if (displays.length === 0 || recordingState !== "idle") return;
+
+    let cancelled = false;
+    const refresh = async () => {
+      for (const display of displays) {
+        try {
+          const png = await invoke<ArrayBuffer>("get_display_thumbnail", {
+            displayId: display.id,
+          });
+          if (cancelled) return;
+          const url = URL.createObjectURL(
+            new Blob([png], { type: "image/png" }),
+          );
+          setThumbnailUrls((previous) => {
+            if (previous[display.id]) URL.revokeObjectURL(previous[display.id]);
+            return { ...previous, [display.id]: url };
+          });
+        } catch (err) {
+          console.error(
+            `Failed to capture thumbnail of display ${display.id}:`,
+            err,
+          );
+        }
+      }
+    };
+
+    refresh();
+    const interval = window.setInterval(refresh, THUMBNAIL_REFRESH_MS);
+    return () => {
+      cancelled = true;
+      window.clearInterval(interval);
+      setThumbnailUrls((previous) => {
+        Object.values(previous).forEach((url) => URL.revokeObjectURL(url));
+        return {};
+      });
+    };
+  }, [displays, recordingState]);
+
   // Timer for recording duration
   useEffect(() => {
     if (recordingState === "recording") {
@@ -173,10 +222,9 @@ export default function RecordingView() {
       setRecordingTime(0);
     } catch (err) {
       console.error("Failed to start recording:", err);
-      setError(String(err));
+      setError(errorMessage(err));
 
-      // Check if permission error
-      if (String(err).includes("permission")) {
+      if (errorCode(err) === "PERMISSION_DENIED") {
         setHasPermission(false);
       }
     } finally {
@@ -194,7 +242,7 @@ export default function RecordingView() {
       setRecordingTime(0);
     } catch (err) {
       console.error("Failed to stop recording:", err);
-      setError(String(err));
+      setError(errorMessage(err));
     } finally {
       setIsLoading(false);
     }
@@ -206,7 +254,7 @@ export default function RecordingView() {
       setRecordingState("paused");
     } catch (err) {
       console.error("Failed to pause recording:", err);
-      setError(String(err));
+      setError(errorMessage(err));
     }
   };
 
@@ -216,11 +264,13 @@ export default function RecordingView() {
       setRecordingState("recording");
     } catch (err) {
       console.error("Failed to resume recording:", err);
-      setError(String(err));
+      setError(errorMessage(err));
     }
   };
 
   const selectedDisplay = displays.find((d) => d.id === selectedDisplayId);
+  const thumbnailUrl =
+    selectedDisplayId !== null ? thumbnailUrls[selectedDisplayId] : undefined;
 
   return (
     <div className="h-full flex flex-col">
@@ -257,9 +307,15 @@ export default function RecordingView() {
       )}
 
       {/* Preview Area */}
-      <div className="flex-1 flex items-center justify-center bg-muted/30 p-8">
+      <div className="flex-1 flex flex-col items-center justify-center gap-4 bg-muted/30 p-8">
         <div className="w-full max-w-4xl aspect-video bg-black/50 rounded-lg border border-border flex items-center justify-center relative overflow-hidden">
-          {selectedDisplay ? (
+          {selectedDisplay && thumbnailUrl ? (
+            <img
+              src={thumbnailUrl}
+              alt={`Preview of ${selectedDisplay.name}`}
+              className="w-full h-full object-contain"
+            />
+          ) : selectedDisplay ? (
             <div className="text-muted-foreground text-sm text-center">
               <p>Preview of {selectedDisplay.name}</p>
               <p className="text-xs mt-1">
@@ -267,7 +323,6 @@ export default function RecordingView() {
                 {selectedDisplay.refreshRate &&
                   ` @ ${selectedDisplay.refreshRate}Hz`}
               </p>
-              <p className="text-xs mt-2">(Live preview will be implemented)</p>
             </div>
           ) : (
             <div className="text-center">
@@ -299,6 +354,36 @@ export default function RecordingView() {
             </div>
           )}
         </div>
+
+        {/* Display picker thumbnails */}
+        {displays.length > 1 && (
+          <div className="flex gap-3 overflow-x-auto max-w-4xl w-full">
+            {displays.map((display) => (
+              <button
+                key={display.id}
+                type="button"
+                onClick={() => setSelectedDisplayId(display.id)}
+                disabled={recordingState !== "idle"}
+                title={`${display.name} (${display.width}x${display.height})`}
+                className={`w-32 shrink-0 aspect-video bg-black/50 rounded-md border overflow-hidden flex items-center justify-center transition-colors disabled:opacity-50 ${
+                  display.id === selectedDisplayId
+                    ? "border-primary ring-2 ring-ring"
+                    : "border-border hover:border-muted-foreground"
+                }`}
+              >
+                {thumbnailUrls[display.id] ? (
+                  <img
+                    src={thumbnailUrls[display.id]}
+                    alt={`Preview of ${display.name}`}
+                    className="w-full h-full object-contain"
+                  />
+                ) : (
+                  <Monitor className="w-5 h-5 text-muted-foreground" />
+                )}
+              </button>
+            ))}
+          </div>
+        )}
       </div>
 
       {/* Controls */}
@@ -10,8 +10,10 @@ import type {
   Slice,
   Layout,
   Scene,
+  TrackMutes,
 } from "../types/project";
 import { generateSliceId, createDefaultSlice } from "../utils/sliceUtils";
+import { errorMessage } from "../types/error";
 
 // Auto-save debounce timeout
 let autoSaveTimeout: ReturnType<typeof setTimeout> | null = null;
@@ -42,6 +44,7 @@ const defaultConfig: ProjectConfig = {
     smoothing: {
       enabled: true,
       spring: { stiffness: 470, damping: 70, mass: 3 },
+      preset: "smooth",
     },
     hideAfterMs: null,
   },
@@ -59,6 +62,7 @@ const defaultConfig: ProjectConfig = {
   },
   recordingRange: [0, 0],
   outputAspectRatio: { x: 16, y: 9 },
+  zoom: { zoom: 2, type: "follow-cursor", snapToEdges: 0, instant: false },
 };
 
 function generateId(): string {
@@ -79,6 +83,9 @@ interface ProjectState {
   // Project path (where it's saved)
   projectPath: string | null;
 
+  // Opened with openProjectReadOnly: edits aren't saved
+  readOnly: boolean;
+
   // Active scene index
   activeSceneIndex: number;
 
@@ -88,9 +95,13 @@ interface ProjectState {
 
   // Basic project actions
   createProject: () => void;
-  createProjectFromRecording: (recordingBundlePath: string) => Promise<void>;
+  createProjectFromRecording: (
+    recordingBundlePath: string,
+    templateId?: string,
+  ) => Promise<void>;
   openProject: () => Promise<void>;
   openProjectFromPath: (path: string) => Promise<void>;
+  openProjectReadOnly: (path: string) => Promise<void>;
   closeProject: () => void;
   setProject: (project: Project) => void;
   updateConfig: (config: Partial<ProjectConfig>) => void;
@@ -141,6 +152,9 @@ interface ProjectState {
     splitTimeMs: number,
   ) => void;
 
+  // Track mute/solo
+  setTrackMutes: (sceneIndex: number, mutes: Partial<TrackMutes>) => void;
+
   // Helpers
   getActiveScene: () => Scene | null;
   getScreenSlices: () => Slice[];
@@ -160,10 +174,11 @@ function triggerAutoSave(get: () => ProjectState) {
 
   // Schedule a new auto-save
   autoSaveTimeout = setTimeout(async () => {
-    const { project, projectPath } = get();
+    const { project, projectPath, readOnly } = get();
 
-    // Only auto-save if we have a project and a saved path
-    if (!project || !projectPath) {
+    // Only auto-save if we have a project and a saved path, and the
+    // backend would accept the write
+    if (!project || !projectPath || readOnly) {
       return;
     }
 
@@ -186,6 +201,7 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
   markers: [],
   recordingMetadata: null,
   projectPath: null,
+  readOnly: false,
   activeSceneIndex: 0,
   isLoading: false,
   error: null,
@@ -214,19 +230,23 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
       markers: [],
       recordingMetadata: null,
       projectPath: null,
+      readOnly: false,
       activeSceneIndex: 0,
       error: null,
     });
   },
 
   // Create a project from a recording bundle - auto-saves to default location
-  createProjectFromRecording: async (recordingBundlePath: string) => {
+  createProjectFromRecording: async (
+    recordingBundlePath: string,
+    templateId?: string,
+  ) => {
     set({ isLoading: true, error: null });
     try {
       // Rust command now returns [Project, savedPath] tuple
       const [project, savedPath] = await invoke<[Project, string]>(
         "create_project_from_recording",
-        { recordingBundlePath },
+        { recordingBundlePath, templateId: templateId ?? null },
       );
 
       const now = new Date().toISOString();
@@ -241,13 +261,14 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
         project,
         meta,
         projectPath: savedPath, // Already saved to default location
+        readOnly: false,
         activeSceneIndex: 0,
         isLoading: false,
       });
 
       console.log(`Project created and saved to: ${savedPath}`);
     } catch (e) {
-      set({ error: String(e), isLoading: false });
+      set({ error: errorMessage(e), isLoading: false });
       throw e;
     }
   },
@@ -267,7 +288,7 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
 
       await get().openProjectFromPath(selectedPath);
     } catch (e) {
-      set({ error: String(e) });
+      set({ error: errorMessage(e) });
       throw e;
     }
   },
@@ -290,11 +311,39 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
         project,
         meta,
         projectPath: path,
+        readOnly: false,
+        isLoading: false,
+        activeSceneIndex: 0,
+      });
+    } catch (e) {
+      set({ error: errorMessage(e), isLoading: false });
+      throw e;
+    }
+  },
+
+  // Open a project for review; the backend rejects writes to its bundle
+  openProjectReadOnly: async (path: string) => {
+    set({ isLoading: true, error: null });
+    try {
+      const project = await invoke<Project>("open_project_readonly", { path });
+
+      const meta: ProjectMeta = {
+        version: "0.1.0",
+        format: "osp-v1",
+        createdAt: project.createdAt,
+        updatedAt: project.createdAt,
+      };
+
+      set({
+        project,
+        meta,
+        projectPath: path,
+        readOnly: true,
         isLoading: false,
         activeSceneIndex: 0,
       });
     } catch (e) {
-      set({ error: String(e), isLoading: false });
+      set({ error: errorMessage(e), isLoading: false });
       throw e;
     }
   },
@@ -318,6 +367,7 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
       markers: [],
       recordingMetadata: null,
       projectPath: null,
+      readOnly: false,
       activeSceneIndex: 0,
       error: null,
     });
@@ -658,6 +708,30 @@ export const useProjectStore = create<ProjectState>((set, get) => ({
     triggerAutoSave(get);
   },
 
+  // Mute or solo tracks of a scene (honored at export)
+  setTrackMutes: (sceneIndex: number, mutes: Partial<TrackMutes>) => {
+    const { project } = get();
+    if (!project || sceneIndex < 0 || sceneIndex >= project.scenes.length)
+      return;
+
+    const newScenes = [...project.scenes];
+    newScenes[sceneIndex] = {
+      ...newScenes[sceneIndex],
+      muted: {
+        microphone: false,
+        systemAudio: false,
+        camera: false,
+        ...newScenes[sceneIndex].muted,
+        ...mutes,
+      },
+    };
+
+    set({
+      project: { ...project, scenes: newScenes },
+    });
+    triggerAutoSave(get);
+  },
+
   // Add a layout to a scene
   addLayout: (sceneIndex: number, layout: Layout) => {
     const { project } = get();
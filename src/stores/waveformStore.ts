@@ -1,6 +1,7 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import type { WaveformData } from "../types/waveform";
+import { errorMessage } from "../types/error";
 
 interface WaveformState {
   /** Cached waveform data by audio path */
@@ -51,7 +52,7 @@ export const useWaveformStore = create<WaveformState>((set, get) => ({
 
       return waveformData;
     } catch (error) {
-      const errorMsg = error instanceof Error ? error.message : String(error);
+      const errorMsg = errorMessage(error);
       console.error(`Failed to fetch waveform for ${audioPath}:`, errorMsg);
 
       set((state) => ({
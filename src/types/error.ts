@@ -0,0 +1,52 @@
+/**
+ * Error codes returned by backend commands (mirrors AppError::code)
+ */
+export type ErrorCode =
+  | "IO_ERROR"
+  | "SERIALIZATION_ERROR"
+  | "PROJECT_ERROR"
+  | "RECORDING_ERROR"
+  | "EXPORT_ERROR"
+  | "PLATFORM_ERROR"
+  | "PERMISSION_DENIED"
+  | "NOT_FOUND"
+  | "INVALID_INPUT"
+  | "DEVICE_NOT_FOUND"
+  | "FFMPEG_MISSING"
+  | "FFMPEG_ERROR"
+  | "BUSY"
+  | "READ_ONLY"
+  | "CANCELLED";
+
+/**
+ * Error a command rejects with (and the export-error event payload)
+ */
+export interface ErrorResponse {
+  code: ErrorCode;
+  message: string;
+}
+
+export function isErrorResponse(error: unknown): error is ErrorResponse {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    "code" in error &&
+    "message" in error
+  );
+}
+
+/**
+ * Error code of a rejected command, if the backend sent one
+ */
+export function errorCode(error: unknown): ErrorCode | undefined {
+  return isErrorResponse(error) ? error.code : undefined;
+}
+
+/**
+ * Human-readable message for any caught error
+ */
+export function errorMessage(error: unknown): string {
+  if (isErrorResponse(error)) return error.message;
+  if (error instanceof Error) return error.message;
+  return String(error);
+}
@@ -1,6 +1,8 @@
 // Export types for edit-aware video export
 // Matches the Rust types in src-tauri/src/export/types.rs
 
+import type { ErrorResponse } from "./error";
+
 /**
  * A single segment to include in export (represents trim/cut edits)
  */
@@ -20,3 +22,139 @@ export interface TrackEdits {
   /** Ordered list of segments to include */
   segments: ExportSegment[];
 }
+
+/**
+ * Audio codec of an export. MP4 takes any of them, WebM only Opus, and
+ * GIF has no audio. Omitted, the format's default is used (AAC, or Opus
+ * for WebM)
+ */
+export type AudioCodec = "aac" | "opus" | "flac";
+
+/**
+ * Encoding parameters of a GIF
+ */
+export interface GifParams {
+  fps: number;
+  width: number;
+  maxColors: number;
+}
+
+/**
+ * Emitted as "export-gif-fit" when a GIF export with maxSizeMb was fitted
+ */
+export interface GifFit {
+  params: GifParams;
+  sizeBytes: number;
+  budgetBytes: number;
+  /** False if the smallest parameters tried still exceed the budget */
+  fitsBudget: boolean;
+}
+
+/**
+ * Payload of "export-complete": checksum and checks of the exported file
+ * (null if it couldn't be read)
+ */
+export interface ExportVerification {
+  path: string;
+  /** Hex-encoded SHA-256 of the file */
+  sha256: string;
+  sizeBytes: number;
+  /** Duration reported by the container, null if it couldn't be read */
+  durationMs: number | null;
+  expectedDurationMs: number;
+  videoStreams: number;
+  audioStreams: number;
+  /** Whether the MP4 index was found (null for other formats) */
+  hasMoov: boolean | null;
+  /** Checks that failed; empty if the export verified */
+  problems: string[];
+  passed: boolean;
+}
+
+/**
+ * Fading trail drawn behind the cursor in exports (ExportOptions.cursorTrail)
+ */
+export interface CursorTrail {
+  /** How far back the trail reaches (milliseconds) */
+  lengthMs: number;
+  /** Opacity at the cursor (0-1), fading to 0 at the end of the trail */
+  opacity: number;
+  /** Trail width in pixels at the cursor */
+  width: number;
+  /** CSS hex color */
+  color: string;
+}
+
+/**
+ * Upload provider for shareable review links
+ * Matches ShareProvider in src-tauri/src/export/share.rs
+ */
+export type ShareProvider =
+  | {
+      type: "s3";
+      bucket: string;
+      region: string;
+      accessKeyId: string;
+      /**
+       * Write-only: stored in the OS keychain and never returned by
+       * get_share_settings. Leave empty to keep the stored key.
+       */
+      secretAccessKey?: string;
+      /** Custom endpoint for S3-compatible stores */
+      endpoint?: string | null;
+      /** Key prefix, e.g. "reviews/" */
+      prefix?: string | null;
+      /** Public base URL; when set, links don't expire */
+      publicBaseUrl?: string | null;
+    }
+  | {
+      type: "custom-put";
+      /** Upload URL; {filename}, {id} and {expires} are substituted */
+      uploadUrl: string;
+      headers?: Record<string, string>;
+      /** Link template, with the same placeholders as uploadUrl */
+      publicUrl: string;
+    };
+
+/**
+ * Persisted share settings
+ */
+export interface ShareSettings {
+  provider: ShareProvider | null;
+  /** Link expiry used when share_export isn't given one (seconds) */
+  defaultExpirySecs?: number | null;
+}
+
+/**
+ * Result of share_export
+ */
+export interface ShareLink {
+  url: string;
+  /** ISO timestamp the link stops working, null if it doesn't expire */
+  expiresAt: string | null;
+  copiedToClipboard: boolean;
+}
+
+/**
+ * Persisted watch-folder settings: bundles dropped into `folder` are
+ * exported with `preset` once they finish copying
+ */
+export interface WatchFolderSettings {
+  enabled: boolean;
+  folder: string | null;
+  /** Where exports are written; null writes them next to the bundles */
+  outputDir: string | null;
+  /** Options as passed to start_export; outputPath is ignored */
+  preset: Record<string, unknown> | null;
+}
+
+/**
+ * Emitted as "watch-folder-export" when a watch-folder export finishes
+ */
+export interface WatchFolderExport {
+  bundlePath: string;
+  outputPath: string;
+  /** Null if the export succeeded */
+  error: ErrorResponse | null;
+  verification: ExportVerification | null;
+}
@@ -57,15 +57,45 @@ export interface SpringConfig {
   mass: number;
 }
 
+export type SmoothingPreset = "subtle" | "smooth" | "cinematic";
+
+export interface SmoothingPresetInfo {
+  preset: SmoothingPreset;
+  spring: SpringConfig;
+}
+
 export interface CursorSmoothingConfig {
   enabled: boolean;
   spring: SpringConfig;
+  /** Preset the spring was last set from (null once tuned by hand) */
+  preset?: SmoothingPreset | null;
 }
 
 export interface CursorConfig {
   size: number;
   smoothing: CursorSmoothingConfig;
   hideAfterMs: number | null;
+  /** Installed cursor pack to draw cursors with at export */
+  pack?: string | null;
+}
+
+export type CursorKind =
+  | "arrow"
+  | "pointer"
+  | "text"
+  | "crosshair"
+  | "openHand"
+  | "closedHand"
+  | "notAllowed"
+  | "resizeLeftRight"
+  | "resizeUpDown";
+
+/** Installed cursor pack (from list_cursor_packs) */
+export interface CursorPackInfo {
+  id: string;
+  name: string;
+  author: string | null;
+  kinds: CursorKind[];
 }
 
 // =============================================================================
@@ -95,6 +125,10 @@ export interface AudioConfig {
   systemVolume: number;
   microphoneVolume: number;
   enhanceMicrophone: boolean;
+  /** Offset applied to the microphone track in ms (positive delays it) */
+  microphoneOffsetMs?: number;
+  /** Remove speaker echo from the microphone at export (system audio is the reference) */
+  cancelEcho?: boolean;
 }
 
 // =============================================================================
@@ -113,6 +147,14 @@ export interface AspectRatio {
   y: number;
 }
 
+// Settings used for newly created zoom ranges
+export interface ZoomDefaults {
+  zoom: number;
+  type: ZoomType;
+  snapToEdges: number;
+  instant: boolean;
+}
+
 export interface ProjectConfig {
   background: Background;
   padding: Padding;
@@ -122,6 +164,30 @@ export interface ProjectConfig {
   audio: AudioConfig;
   recordingRange: [number, number];
   outputAspectRatio: AspectRatio;
+  zoom?: ZoomDefaults;
+  colorLut?: ColorLut | null;
+}
+
+/** 3D LUT (.cube) stored in the bundle and applied at export (see attach_color_lut) */
+export interface ColorLut {
+  /** Path relative to the bundle, under assets/luts */
+  file: string;
+  applyToScreen: boolean;
+  applyToCamera: boolean;
+}
+
+// Named set of visual settings that can be applied to projects
+export interface ProjectTemplate {
+  id: string;
+  name: string;
+  createdAt: string;
+  background: Background;
+  padding: Padding;
+  shadow: ShadowConfig;
+  cursor: CursorConfig;
+  camera: CameraConfig;
+  zoom: ZoomDefaults;
+  outputAspectRatio: AspectRatio;
 }
 
 // =============================================================================
@@ -147,10 +213,20 @@ export interface ZoomRange {
   zoom: number;
   type: ZoomType;
   targetPoint?: { x: number; y: number };
+  // How readily the zoomed view snaps flush to the frame's edges, 0 (never) to 1
   snapToEdges: number;
   instant: boolean;
 }
 
+// Zoom transform at a point in time, as returned by render_zoom_path
+export interface ZoomKeyframe {
+  timeMs: number;
+  scale: number;
+  /** Normalized center of the visible area (0-1) */
+  centerX: number;
+  centerY: number;
+}
+
 export type LayoutType =
   | "screen-only"
   | "camera-only"
@@ -168,6 +244,17 @@ export interface Layout {
 
 export type SceneType = "recording" | "title" | "transition";
 
+export type AudioTrack = "microphone" | "system-audio";
+
+/** Non-destructive mute/solo state of a scene's tracks */
+export interface TrackMutes {
+  microphone: boolean;
+  systemAudio: boolean;
+  camera: boolean;
+  /** When set, only this audio track is heard, regardless of mute flags */
+  solo?: AudioTrack | null;
+}
+
 export interface Scene {
   id: string;
   name: string;
@@ -179,6 +266,7 @@ export interface Scene {
   cameraSlices: Slice[];
   zoomRanges: ZoomRange[];
   layouts: Layout[];
+  muted?: TrackMutes;
 }
 
 // =============================================================================
@@ -370,6 +458,87 @@ export interface Marker {
   color?: string;
 }
 
+/**
+ * Template new projects are named with (get_naming_settings /
+ * set_naming_settings). Tokens: {date}, {time}, {date:FORMAT},
+ * {time:FORMAT} (strftime), {display}, {app}
+ */
+export interface NamingSettings {
+  template: string;
+}
+
+/**
+ * Editor view state saved in the bundle's ui-state.json (get_ui_state /
+ * save_ui_state), separate from project.json
+ */
+export interface UiState {
+  playheadMs: number;
+  timelineZoom: number; // 1.0 = 100%
+  timelineScrollX: number; // px
+  selectedSliceId: string | null;
+  selectedLayoutId: string | null;
+  panels: PanelLayout;
+}
+
+export interface PanelLayout {
+  sizes: Record<string, number>; // px, keyed by panel id
+  collapsed: string[];
+}
+
+/**
+ * Tuning for suggest_chapters scene detection
+ */
+export interface SceneDetectionConfig {
+  /** Fraction of pixels (0-1) that must change for a transition */
+  threshold: number;
+  /** Minimum length of a chapter in milliseconds */
+  minChapterMs: number;
+  /** Frames sampled per second of video */
+  sampleFps: number;
+}
+
+// =============================================================================
+// External Media Import
+// =============================================================================
+
+export type ReplaceableTrack = "webcam" | "microphone";
+
+export interface MediaInfo {
+  durationMs: number;
+  hasVideo: boolean;
+  hasAudio: boolean;
+}
+
+/**
+ * Automatic alignment of imported audio against the scratch microphone
+ */
+export interface AudioSync {
+  /** Delay applied to the imported audio (positive delays it) */
+  offsetMs: number;
+  /** Correlation of the aligned takes (0-1); low values aren't applied */
+  confidence: number;
+}
+
+export interface ImportedMedia {
+  track: ReplaceableTrack;
+  path: string;
+  info: MediaInfo;
+  sync?: AudioSync | null;
+}
+
+// =============================================================================
+// Backup Snapshots
+// =============================================================================
+
+/**
+ * A project.json snapshot in the bundle's history/ folder, from
+ * list_project_snapshots; pass its timestamp to restore_snapshot
+ */
+export interface Snapshot {
+  timestamp: number; // Unix time in milliseconds
+  sizeBytes: number;
+}
+
 // =============================================================================
 // Project Bundle (Full structure)
 // =============================================================================
@@ -1,10 +1,52 @@
 // Recording-related TypeScript types
 
+import type { Marker } from "./project";
+
 export interface RecordingResult {
   bundlePath: string;
   totalDurationMs: number;
   sessionCount: number;
   outputFiles: string[];
+  channels: ChannelStatus[];
+}
+
+// Recording interrupted by a crash (list_recoverable_recordings); pass
+// bundlePath to recover_recording
+export interface RecoverableRecording {
+  bundlePath: string;
+  startedAt: string | null;
+  sessionCount: number;
+  files: string[];
+  sizeBytes: number;
+}
+
+// How well a channel captured (matches ChannelHealth in the backend)
+export type ChannelHealth =
+  | { state: "healthy" }
+  | { state: "degraded"; reason: string }
+  | { state: "failed"; reason: string };
+
+// Outcome of one channel in a RecordingResult; health is the worst seen
+export interface ChannelStatus {
+  id: string;
+  channelType: ChannelType;
+  health: ChannelHealth;
+  outputFiles: number;
+  // What the channel actually recorded with
+  settings: ChannelSettings;
+}
+
+// Capture settings a channel reports (fields depend on the channel type)
+export interface ChannelSettings {
+  device?: string;
+  width?: number;
+  height?: number;
+  fps?: number;
+  sampleRate?: number;
+  // Rate the input device ran at, when the audio was resampled
+  inputSampleRate?: number;
+  channels?: number;
+  bitrateKbps?: number;
 }
 
 export interface VideoMetadata {
@@ -23,6 +65,54 @@ export interface RecordingInfo {
   aspectRatio: number;
 }
 
+// Device access checked by check_media_permission / request_media_permission
+export type MediaPermission = "camera" | "microphone";
+
+// "undetermined" means the user hasn't been asked yet; "denied" can only be
+// changed in System Settings
+export type PermissionStatus = "granted" | "denied" | "undetermined";
+
+// OS permission a capture source needs
+export type Permission =
+  | "screenRecording"
+  | "camera"
+  | "microphone"
+  | "accessibility";
+
+export interface MissingPermission {
+  permission: Permission;
+  // Sources that can't be recorded without it
+  sources: ChannelType[];
+}
+
+// Returned by check_recording_permissions and start_recording (which drops
+// the sources listed in missing when RecordingConfig.allowDegradedStart is set)
+export interface PermissionReport {
+  missing: MissingPermission[];
+  canDegrade: boolean;
+}
+
+// Result of check_system_audio_available; reason explains why not (e.g. macOS
+// older than 13)
+export interface SystemAudioSupport {
+  available: boolean;
+  reason: string | null;
+}
+
+// App whose audio can be recorded on its own (from get_audio_capture_apps)
+export interface AudioCaptureApp {
+  // Bundle identifier on macOS, executable name on Windows
+  id: string;
+  name: string;
+  pid: number;
+}
+
+// RecordingConfig.audioAppFilter: apps to record system audio from, or to leave out
+export interface AudioAppFilter {
+  mode: "include" | "exclude";
+  appIds: string[];
+}
+
 // Mouse move event from recording
 export interface MouseMoveEvent {
   x: number;
@@ -45,6 +135,21 @@ export interface MouseClickEvent {
   unixTimeMs: number;
 }
 
+// Key press event from recording (masked keys were typed into a secure input)
+export interface KeyPress {
+  key: string;
+  eventType: "down" | "up";
+  activeModifiers: string[];
+  masked: boolean;
+  processTimeMs: number;
+  unixTimeMs: number;
+}
+
+// RecordingConfig.keystrokeMode (with trackKeystrokes): "shortcutsOnly" keeps
+// only modifier keys and keys pressed with Cmd/Ctrl, so typed text isn't
+// recorded
+export type KeystrokeMode = "all" | "shortcutsOnly";
+
 // Cursor image info from recording
 export interface CursorInfo {
   id: string;
@@ -55,6 +160,33 @@ export interface CursorInfo {
   height: number;
 }
 
+// Point in display pixel coordinates
+export interface AnnotationPoint {
+  x: number;
+  y: number;
+}
+
+// Annotation geometry drawn from the overlay
+export type AnnotationShape =
+  | { type: "pen"; points: AnnotationPoint[] }
+  | { type: "arrow"; from: AnnotationPoint; to: AnnotationPoint }
+  | { type: "highlight"; x: number; y: number; width: number; height: number };
+
+// Annotation sent to add_annotation
+export interface AnnotationInput {
+  shape: AnnotationShape;
+  color: string;
+  strokeWidth: number;
+  durationMs: number | null;
+}
+
+// Annotation recorded during a session
+export interface Annotation extends AnnotationInput {
+  id: string;
+  processTimeMs: number;
+  unixTimeMs: number;
+}
+
 // Complete recording bundle data
 export interface RecordingBundle {
   // Paths
@@ -68,7 +200,273 @@ export interface RecordingBundle {
   mouseMoves: MouseMoveEvent[];
   mouseClicks: MouseClickEvent[];
   cursors: Record<string, CursorInfo>;
+  annotations: Annotation[];
+  keystrokes: KeyPress[];
 
   // Metadata
   videoMetadata: VideoMetadata;
+  // The system cursor is part of the screen video, so none is drawn on top
+  videoShowsCursor: boolean;
+
+  // Every session of a paused and resumed recording (the paths and data
+  // above are session 0), from sessions.json
+  sessions: SessionList | null;
+}
+
+export type SessionBoundary = {
+  reason: "displayChanged";
+  display: string;
+  width: number;
+  height: number;
+};
+
+// Kinds of file a channel records per session
+export type RecordedFileKind =
+  | "screen-video"
+  | "webcam-video"
+  | "mic-audio"
+  | "system-audio"
+  | "mouse-moves"
+  | "mouse-clicks"
+  | "cursors"
+  | "annotations"
+  | "window-resizes"
+  | "app-activity"
+  | "windows"
+  | "keystrokes";
+
+export interface SessionEntry {
+  index: number;
+  // Where the session starts on the stitched timeline
+  timelineStartMs: number;
+  durationMs: number;
+  unixStartMs: number;
+  unixEndMs: number;
+  boundary?: SessionBoundary;
+  // File names relative to the recording directory
+  files: Partial<Record<RecordedFileKind, string>>;
+}
+
+export interface SessionList {
+  version: number;
+  totalDurationMs: number;
+  sessions: SessionEntry[];
+}
+
+// Result of capture_screenshot
+export interface ScreenshotResult {
+  path: string;
+  width: number;
+  height: number;
+  displayId: number;
+}
+
+// Span of time one application (and window, if titles are recorded) was
+// frontmost, from recording-{n}-app-activity.json. Times are session ms.
+export interface AppActivitySpan {
+  app: string;
+  windowTitle: string | null;
+  startMs: number;
+  endMs: number;
+  unixTimeMs: number;
+}
+
+// Span of time one window was frontmost without moving or resizing, from
+// recording-{n}-windows.json. Bounds are global screen coordinates (points
+// on macOS, pixels on Windows).
+export interface WindowSpan {
+  app: string;
+  windowTitle: string | null;
+  windowId: number | null;
+  bounds: { x: number; y: number; width: number; height: number } | null;
+  startMs: number;
+  endMs: number;
+  unixTimeMs: number;
+}
+
+// "recording-countdown" payload, sent every second while
+// start_recording_with_countdown counts down
+export interface CountdownTick {
+  // 0 as recording starts
+  remaining: number;
+  total: number;
+  // Cancelled with cancel_recording_countdown; recording won't start
+  cancelled: boolean;
+}
+
+// Result of get_recording_preview: the current session's video, playable
+// while recording. Seek to startMs to show only the requested last seconds.
+export interface LivePreview {
+  path: string;
+  sessionIndex: number;
+  sessionDurationMs: number;
+  startMs: number;
+  recordingDurationMs: number;
+}
+
+// Payload of the "recording-event" event; progress data is the recording
+// duration in ms
+export type RecordingEvent =
+  | { type: "started" | "stopped" | "paused" | "resumed" }
+  | { type: "error"; data: string }
+  | { type: "progress"; data: number }
+  | { type: "deviceLost"; data: { channel: string; device: string } }
+  | {
+      type: "deviceFallback";
+      data: { channel: string; from: string; to: string; gapMs: number };
+    }
+  | {
+      type: "deviceRecovered";
+      data: { channel: string; device: string; gapMs: number };
+    }
+  | {
+      type: "displayChanged";
+      data: { channel: string; display: string; width: number; height: number };
+    }
+  | { type: "displayDisconnected"; data: { channel: string; display: string } }
+  | { type: "channelDegraded"; data: { channel: string; reason: string } }
+  | { type: "channelFailed"; data: { channel: string; reason: string } }
+  | { type: "markerAdded"; data: Marker }
+  | {
+      type: "autoStopped";
+      data: { reason: AutoStopReason; result: RecordingResult };
+    };
+
+// Payload of the "mic-level" event, sent every 100ms while recording.
+// Levels are linear (0-1); silent is set once the mic has picked up nothing
+// for a few seconds.
+export interface MicLevel {
+  rms: number;
+  peak: number;
+  silent: boolean;
+}
+
+// Why a recording stopped on its own (RecordingConfig.maxDurationMs/stopAt)
+export type AutoStopReason = "maxDuration" | "scheduled";
+
+// Result of frame_screenshot
+export interface FramedScreenshot {
+  path: string;
+  width: number;
+  height: number;
+}
+
+// Recording channel kinds (matches ChannelType in the backend)
+export type ChannelType =
+  | "display"
+  | "system-audio"
+  | "microphone"
+  | "webcam"
+  | "input"
+  | "annotation"
+  | "app-activity"
+  | "keyboard";
+
+// Result of run_capture_self_test
+export interface ChannelCheck {
+  channelId: string;
+  channelType: ChannelType;
+  passed: boolean;
+  message: string;
+}
+
+export interface SelfTestReport {
+  passed: boolean;
+  durationMs: number;
+  channels: ChannelCheck[];
+}
+
+// Payload of the av-calibration-cue event: flash the screen and beep now
+export interface CalibrationCue {
+  index: number;
+  count: number;
+}
+
+// Result of run_av_calibration
+export interface CalibrationResult {
+  displayId: number;
+  offsetMs: number; // positive delays the microphone
+  cuesSent: number;
+  cuesMatched: number;
+  spreadMs: number;
+}
+
+// Saved calibration returned by get_av_calibration, keyed by display ID
+export interface CalibrationStore {
+  displays: Record<string, { offsetMs: number; cuesMatched: number; measuredAt: string }>;
+}
+
+// Capturable window returned by get_windows (pass its id as windowId)
+export interface WindowInfo {
+  id: number;
+  title: string;
+  appName: string;
+  bounds: { x: number; y: number; width: number; height: number };
+  isOnScreen: boolean;
+}
+
+// Part of a display to record, passed to start_region_recording (or as
+// region in the recording config), in the coordinates get_displays reports
+export interface CaptureRegion {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+// Encoder settings for an audio track (unset fields use the device's native format)
+export interface AudioEncodingConfig {
+  // Microphones only: rate to open the device at, if it supports it
+  inputSampleRate?: number;
+  sampleRate?: number;
+  channelLayout?: "mono" | "stereo";
+  bitrateKbps?: number;
+}
+
+// RecordingConfig.quality: "performance" records at half resolution with
+// smaller files at up to 30fps, "balanced" at full resolution, "maxQuality"
+// close to lossless. The screen is captured at the display's refresh rate,
+// up to RecordingConfig.maxFps (default 60); displays that don't report one
+// record at 30fps, or 60fps with "maxQuality".
+export type CaptureQuality = "performance" | "balanced" | "maxQuality";
+
+// Keyframe and B-frame settings for the screen and webcam encoders (unset
+// fields keep the quality preset's keyframe interval and the encoder's
+// default B-frames). Screen recordings skip unchanged frames unless
+// constantFrameRate or a keyframe interval is set.
+export interface VideoEncodingConfig {
+  keyframeIntervalSecs?: number;
+  bFrames?: number;
+  constantFrameRate?: boolean;
+}
+
+// Timing of one recorded track in a SyncReport
+export interface TrackTiming {
+  channelType: ChannelType;
+  path: string;
+  startedAtMs: number | null;
+  startOffsetMs: number | null; // how much later than the reference video it started
+  durationMs: number | null;
+  durationShortfallMs: number | null; // how much shorter than the reference video it is
+  hasAudio: boolean;
+  anchored: boolean; // export lines it up from where capture started it
+}
+
+// Cross-correlated offset between two audio tracks
+export interface TrackCorrelation {
+  reference: ChannelType;
+  track: ChannelType;
+  offsetMs: number; // positive delays `track`
+  confidence: number;
+  confident: boolean;
+}
+
+// Result of analyze_recording_sync
+export interface SyncReport {
+  sessionIndex: number;
+  reference: ChannelType | null;
+  tracks: TrackTiming[];
+  correlations: TrackCorrelation[];
+  suggestedMicrophoneOffsetMs: number | null;
+  warnings: string[];
 }
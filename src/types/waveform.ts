@@ -9,3 +9,15 @@ export interface WaveformData {
   /** Samples per second (peaks.length / duration in seconds) */
   samplesPerSecond: number;
 }
+
+/**
+ * Short decoded audio chunk for scrub playback, aligned to a fixed grid
+ */
+export interface ScrubChunk {
+  /** Chunk start on the source timeline */
+  startMs: number;
+  durationMs: number;
+  sampleRate: number;
+  /** Mono samples in the -1.0..1.0 range */
+  samples: number[];
+}
@@ -0,0 +1,69 @@
+/**
+ * A/V Calibration
+ *
+ * Runs run_av_calibration and answers each av-calibration-cue event by
+ * flashing the window white and playing a beep in the same frame, so the
+ * backend can measure how far apart they land in the screen and microphone
+ * recordings.
+ */
+
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import type { CalibrationCue, CalibrationResult } from "../types/recording";
+
+const FLASH_MS = 100;
+const BEEP_FREQUENCY = 1000;
+
+/**
+ * Calibrate a display. The window should be on that display while this runs.
+ */
+export async function runAvCalibration(
+  displayId: number,
+  microphoneDeviceId: string | null,
+): Promise<CalibrationResult> {
+  const audio = new AudioContext();
+  const flash = document.createElement("div");
+  flash.style.cssText =
+    "position:fixed;inset:0;z-index:2147483647;background:#fff;pointer-events:none;visibility:hidden";
+  document.body.appendChild(flash);
+
+  const unlisten = await listen<CalibrationCue>("av-calibration-cue", () => {
+    requestAnimationFrame(() => {
+      flash.style.visibility = "visible";
+      playBeep(audio);
+      setTimeout(() => {
+        flash.style.visibility = "hidden";
+      }, FLASH_MS);
+    });
+  });
+
+  try {
+    return await invoke<CalibrationResult>("run_av_calibration", {
+      config: {
+        displayId,
+        captureSystemAudio: false,
+        captureMicrophone: true,
+        microphoneDeviceId,
+        captureWebcam: false,
+        webcamDeviceId: null,
+        trackInput: false,
+        outputDir: "",
+      },
+    });
+  } finally {
+    unlisten();
+    flash.remove();
+    await audio.close();
+  }
+}
+
+function playBeep(audio: AudioContext) {
+  const oscillator = audio.createOscillator();
+  const gain = audio.createGain();
+  oscillator.frequency.value = BEEP_FREQUENCY;
+  gain.gain.value = 0.5;
+  oscillator.connect(gain).connect(audio.destination);
+  // Output latency is part of what's being measured, so start right away
+  oscillator.start();
+  oscillator.stop(audio.currentTime + FLASH_MS / 1000);
+}